@@ -1,50 +1,145 @@
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
-use crate::config::Config;
+use crate::config::{Config, DependencySpec};
+
+/// Plugin id of Kotlin Symbol Processing, needed by Room and `ksp` dependencies
+const KSP_PLUGIN: &str = "com.google.devtools.ksp";
+
+/// Plugin id of kotlin-parcelize, needed by @Parcelize classes
+const PARCELIZE_PLUGIN: &str = "org.jetbrains.kotlin.plugin.parcelize";
+
+/// Optional Android libraries the app needs, detected from the project sources
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScaffoldFeatures {
+    /// Preferences DataStore for @store(persist = ...) stores
+    pub datastore: bool,
+    /// Room (and the KSP plugin) for @entity data classes
+    pub room: bool,
+    /// Ktor for $fetch and API clients
+    pub ktor: bool,
+    /// Coil for AsyncImage and Image
+    pub coil: bool,
+    /// navigation-compose for routes, screens and $navigate
+    pub navigation: bool,
+    /// The kotlin-parcelize plugin for @Parcelize classes
+    pub parcelize: bool,
+}
 
 /// Generate complete Android project scaffold
-pub fn generate(config: &Config, output_dir: &Path) -> Result<()> {
-    generate_root_gradle(config, output_dir)?;
+pub fn generate(config: &Config, output_dir: &Path, features: &ScaffoldFeatures) -> Result<()> {
+    generate_root_gradle(config, output_dir, features)?;
     generate_settings_gradle(config, output_dir)?;
     generate_gradle_properties(output_dir)?;
-    generate_app_gradle(config, output_dir)?;
-    generate_manifest(config, output_dir)?;
+    generate_app_gradle(config, output_dir, features)?;
+    generate_manifest(config, output_dir, features)?;
     generate_proguard_rules(output_dir)?;
 
     Ok(())
 }
 
+/// Re-sync the Gradle scripts and manifest of an existing scaffold with whitehall.toml
+/// and the project features, leaving the other scaffold files alone
+pub fn sync(config: &Config, output_dir: &Path, features: &ScaffoldFeatures) -> Result<()> {
+    generate_root_gradle(config, output_dir, features)?;
+    generate_settings_gradle(config, output_dir)?;
+    generate_app_gradle(config, output_dir, features)?;
+    generate_manifest(config, output_dir, features)?;
+
+    Ok(())
+}
+
+/// Write a generated file only when its content changed, so an existing scaffold can be
+/// re-synced cheaply (and Gradle doesn't see modified build scripts on every build)
+fn write_if_changed(path: &Path, content: &str) -> Result<()> {
+    if fs::read_to_string(path).map(|existing| existing == content).unwrap_or(false) {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Plugins the app applies, with the versions declared in the root build file
+fn scaffold_plugins<'a>(config: &'a Config, features: &ScaffoldFeatures) -> Vec<(&'static str, &'a str)> {
+    let mut plugins = vec![
+        ("com.android.application", config.toolchain.agp.as_str()),
+        ("org.jetbrains.kotlin.android", config.toolchain.kotlin.as_str()),
+        ("org.jetbrains.kotlin.plugin.compose", config.toolchain.kotlin.as_str()),
+        ("org.jetbrains.kotlin.plugin.serialization", config.toolchain.kotlin.as_str()),
+    ];
+    if features.parcelize {
+        plugins.push((PARCELIZE_PLUGIN, config.toolchain.kotlin.as_str()));
+    }
+    plugins
+}
+
 /// Generate root build.gradle.kts
-fn generate_root_gradle(config: &Config, output_dir: &Path) -> Result<()> {
+///
+/// `[plugins]` versions override the scaffold's; other `[plugins]` entries with a
+/// version are declared here and applied in app/build.gradle.kts.
+fn generate_root_gradle(config: &Config, output_dir: &Path, features: &ScaffoldFeatures) -> Result<()> {
+    let scaffold = scaffold_plugins(config, features);
+    let mut plugins = String::new();
+    for &(id, version) in &scaffold {
+        let version = config.plugins.get(id).map(String::as_str).unwrap_or(version);
+        plugins.push_str(&format!("    id(\"{}\") version \"{}\" apply false\n", id, version));
+    }
+    for (id, version) in &config.plugins {
+        if !version.is_empty() && !scaffold.iter().any(|(scaffold_id, _)| scaffold_id == id) {
+            plugins.push_str(&format!("    id(\"{}\") version \"{}\" apply false\n", id, version));
+        }
+    }
+
     let content = format!(
         r#"// Top-level build file where you can add configuration options common to all sub-projects/modules.
 plugins {{
-    id("com.android.application") version "{}" apply false
-    id("org.jetbrains.kotlin.android") version "{}" apply false
-    id("org.jetbrains.kotlin.plugin.compose") version "{}" apply false
-    id("org.jetbrains.kotlin.plugin.serialization") version "{}" apply false
-}}
+{}}}
 "#,
-        config.toolchain.agp,
-        config.toolchain.kotlin,
-        config.toolchain.kotlin,
-        config.toolchain.kotlin
+        plugins
     );
-    fs::write(output_dir.join("build.gradle.kts"), content)?;
-    Ok(())
+    write_if_changed(&output_dir.join("build.gradle.kts"), &content)
+}
+
+/// KSP release matching a Kotlin version (KSP versions are `<kotlin>-<ksp>`)
+fn ksp_version(kotlin: &str) -> String {
+    let ksp = match kotlin {
+        "2.1.0" => "1.0.29",
+        "2.0.21" => "1.0.28",
+        "2.0.20" => "1.0.25",
+        "2.0.10" => "1.0.24",
+        "2.0.0" => "1.0.24",
+        "1.9.24" => "1.0.20",
+        "1.9.23" => "1.0.20",
+        "1.9.22" => "1.0.17",
+        // Unknown Kotlin release: assume the latest KSP line
+        _ => "1.0.29",
+    };
+    format!("{}-{}", kotlin, ksp)
 }
 
 /// Generate settings.gradle.kts
+///
+/// `[repositories]` are searched after the default ones, for plugins and dependencies.
 fn generate_settings_gradle(config: &Config, output_dir: &Path) -> Result<()> {
+    let repositories: String = config
+        .repositories
+        .iter()
+        .map(|(name, url)| format!("        maven {{ name = \"{}\"; url = uri(\"{}\") }}\n", name, url))
+        .collect();
+
     let content = format!(
         r#"pluginManagement {{
     repositories {{
         google()
         mavenCentral()
         gradlePluginPortal()
-    }}
+{}    }}
 }}
 
 dependencyResolutionManagement {{
@@ -52,17 +147,18 @@ dependencyResolutionManagement {{
     repositories {{
         google()
         mavenCentral()
-    }}
+{}    }}
 }}
 
 rootProject.name = "{}"
 include(":app")
 "#,
+        repositories,
+        repositories,
         config.project.name
     );
 
-    fs::write(output_dir.join("settings.gradle.kts"), content)?;
-    Ok(())
+    write_if_changed(&output_dir.join("settings.gradle.kts"), &content)
 }
 
 /// Generate gradle.properties
@@ -83,15 +179,236 @@ android.enableJetifier=false
     Ok(())
 }
 
+/// One entry of the `dependencies { }` block in app/build.gradle.kts
+#[derive(Debug, Clone, PartialEq)]
+struct GradleDependency {
+    configuration: String,
+    /// `group:artifact`
+    module: String,
+    /// None when a BOM manages the version
+    version: Option<String>,
+    platform: bool,
+}
+
+impl GradleDependency {
+    /// Parse `group:artifact[:version]`
+    fn new(configuration: &str, notation: &str) -> Self {
+        let (module, version) = match notation.rsplit_once(':') {
+            Some((module, version)) if module.contains(':') => (module, Some(version.to_string())),
+            _ => (notation, None),
+        };
+        Self {
+            configuration: configuration.to_string(),
+            module: module.to_string(),
+            version,
+            platform: false,
+        }
+    }
+
+    fn from_spec(module: &str, spec: &DependencySpec) -> Self {
+        Self {
+            configuration: spec.configuration().to_string(),
+            module: module.to_string(),
+            version: spec.version().map(str::to_string),
+            platform: spec.platform(),
+        }
+    }
+
+    /// Take the version of a `[dependencies]` entry for the same module, and its
+    /// configuration if it sets one explicitly
+    fn override_with(&mut self, spec: &DependencySpec) {
+        self.version = spec.version().map(str::to_string);
+        if let DependencySpec::Detailed(detailed) = spec {
+            if let Some(configuration) = &detailed.configuration {
+                self.configuration = configuration.clone();
+            }
+            self.platform |= detailed.platform;
+        }
+    }
+
+    fn render(&self) -> String {
+        let notation = match &self.version {
+            Some(version) => format!("{}:{}", self.module, version),
+            None => self.module.clone(),
+        };
+        if self.platform {
+            format!("{}(platform(\"{}\"))", self.configuration, notation)
+        } else {
+            format!("{}(\"{}\")", self.configuration, notation)
+        }
+    }
+}
+
+/// A commented group of dependencies, separated from the next by a blank line
+type DependencyGroup = (Option<&'static str>, Vec<GradleDependency>);
+
+/// Dependencies the scaffold emits for the given features
+fn scaffold_dependencies(features: &ScaffoldFeatures) -> Vec<DependencyGroup> {
+    let implementation = |notation| GradleDependency::new("implementation", notation);
+
+    let mut groups = vec![
+        (
+            None,
+            vec![
+                implementation("androidx.core:core-ktx:1.13.1"),
+                implementation("androidx.lifecycle:lifecycle-runtime-ktx:2.8.4"),
+                implementation("androidx.activity:activity-compose:1.9.1"),
+            ],
+        ),
+        (
+            None,
+            vec![
+                GradleDependency {
+                    platform: true,
+                    ..implementation("androidx.compose:compose-bom:2024.09.00")
+                },
+                implementation("androidx.compose.ui:ui"),
+                implementation("androidx.compose.ui:ui-graphics"),
+                implementation("androidx.compose.ui:ui-tooling-preview"),
+                implementation("androidx.compose.material3:material3"),
+            ],
+        ),
+    ];
+    if features.navigation {
+        groups.push((
+            Some("Navigation (2.8+ required for type-safe navigation APIs)"),
+            vec![implementation("androidx.navigation:navigation-compose:2.8.4")],
+        ));
+    }
+    if features.coil {
+        groups.push((
+            Some("Coil for AsyncImage"),
+            vec![implementation("io.coil-kt:coil-compose:2.6.0")],
+        ));
+    }
+    if features.ktor {
+        groups.push((
+            Some("Ktor for HTTP client (fetch API)"),
+            vec![
+                implementation("io.ktor:ktor-client-core:3.0.3"),
+                implementation("io.ktor:ktor-client-okhttp:3.0.3"),
+                implementation("io.ktor:ktor-client-content-negotiation:3.0.3"),
+                implementation("io.ktor:ktor-serialization-kotlinx-json:3.0.3"),
+            ],
+        ));
+    }
+    groups.push((
+        Some("Kotlinx Serialization"),
+        vec![implementation("org.jetbrains.kotlinx:kotlinx-serialization-json:1.7.3")],
+    ));
+    if features.room {
+        groups.push((
+            Some("Room for @entity classes"),
+            vec![
+                implementation("androidx.room:room-runtime:2.6.1"),
+                implementation("androidx.room:room-ktx:2.6.1"),
+                GradleDependency::new("ksp", "androidx.room:room-compiler:2.6.1"),
+            ],
+        ));
+    }
+    if features.datastore {
+        groups.push((
+            Some("DataStore for persisted stores"),
+            vec![implementation("androidx.datastore:datastore-preferences:1.1.1")],
+        ));
+    }
+    groups
+}
+
+/// Body of the `dependencies { }` block: the scaffold's dependencies, with
+/// `[dependencies]` entries for the same module taking precedence, then the rest
+/// of `[dependencies]`
+fn render_dependencies(config: &Config, features: &ScaffoldFeatures) -> String {
+    let mut declared: BTreeMap<&str, &DependencySpec> = config
+        .dependencies
+        .iter()
+        .map(|(module, spec)| (module.as_str(), spec))
+        .collect();
+
+    let mut groups = scaffold_dependencies(features);
+    for (_, dependencies) in &mut groups {
+        for dependency in dependencies {
+            if let Some(spec) = declared.remove(dependency.module.as_str()) {
+                dependency.override_with(spec);
+            }
+        }
+    }
+    if !declared.is_empty() {
+        let mut dependencies: Vec<GradleDependency> = declared
+            .into_iter()
+            .map(|(module, spec)| GradleDependency::from_spec(module, spec))
+            .collect();
+        // BOMs first, like the Compose BOM above
+        dependencies.sort_by_key(|dependency| !dependency.platform);
+        groups.push((Some("From whitehall.toml [dependencies]"), dependencies));
+    }
+
+    let mut out = String::new();
+    for (i, (comment, dependencies)) in groups.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if let Some(comment) = comment {
+            out.push_str(&format!("    // {}\n", comment));
+        }
+        for dependency in dependencies {
+            out.push_str(&format!("    {}\n", dependency.render()));
+        }
+    }
+    out
+}
+
+/// Plugins applied after the scaffold's: kotlin-parcelize for @Parcelize classes, KSP
+/// when Room or a `ksp` dependency needs it, then `[plugins]`
+fn render_extra_plugins(config: &Config, features: &ScaffoldFeatures) -> String {
+    let mut out = String::new();
+
+    if features.parcelize {
+        out.push_str("    // @Parcelize support for $saved state with custom types\n");
+        out.push_str(&format!("    id(\"{}\")\n", PARCELIZE_PLUGIN));
+    }
+
+    let needs_ksp = features.room
+        || config.dependencies.values().any(|spec| spec.configuration() == "ksp");
+    // A KSP version from [plugins] is declared in the root build file instead
+    if needs_ksp && !config.plugins.contains_key(KSP_PLUGIN) {
+        out.push_str(if features.room {
+            "    // Room annotation processing for @entity classes\n"
+        } else {
+            "    // Annotation processing for ksp dependencies\n"
+        });
+        out.push_str(&format!(
+            "    id(\"{}\") version \"{}\"\n",
+            KSP_PLUGIN,
+            ksp_version(&config.toolchain.kotlin)
+        ));
+    }
+
+    let scaffold = scaffold_plugins(config, features);
+    let declared: Vec<&String> = config
+        .plugins
+        .keys()
+        .filter(|id| !scaffold.iter().any(|(scaffold_id, _)| scaffold_id == id))
+        .collect();
+    if !declared.is_empty() {
+        out.push_str("    // From whitehall.toml [plugins]\n");
+        for id in declared {
+            out.push_str(&format!("    id(\"{}\")\n", id));
+        }
+    }
+    out
+}
+
 /// Generate app/build.gradle.kts
-fn generate_app_gradle(config: &Config, output_dir: &Path) -> Result<()> {
+/// Only writes when the content changed, so an existing scaffold can be re-synced cheaply
+pub fn generate_app_gradle(config: &Config, output_dir: &Path, features: &ScaffoldFeatures) -> Result<()> {
     let content = format!(
         r#"plugins {{
     id("com.android.application")
     id("org.jetbrains.kotlin.android")
     id("org.jetbrains.kotlin.plugin.compose")
     id("org.jetbrains.kotlin.plugin.serialization")
-}}
+{}}}
 
 android {{
     namespace = "{}"
@@ -156,43 +473,19 @@ composeCompiler {{
 }}
 
 dependencies {{
-    implementation("androidx.core:core-ktx:1.13.1")
-    implementation("androidx.lifecycle:lifecycle-runtime-ktx:2.8.4")
-    implementation("androidx.activity:activity-compose:1.9.1")
-
-    implementation(platform("androidx.compose:compose-bom:2024.09.00"))
-    implementation("androidx.compose.ui:ui")
-    implementation("androidx.compose.ui:ui-graphics")
-    implementation("androidx.compose.ui:ui-tooling-preview")
-    implementation("androidx.compose.material3:material3")
-
-    // Navigation (2.8+ required for type-safe navigation APIs)
-    implementation("androidx.navigation:navigation-compose:2.8.4")
-
-    // Coil for AsyncImage
-    implementation("io.coil-kt:coil-compose:2.6.0")
-
-    // Ktor for HTTP client (fetch API)
-    implementation("io.ktor:ktor-client-core:3.0.3")
-    implementation("io.ktor:ktor-client-okhttp:3.0.3")
-    implementation("io.ktor:ktor-client-content-negotiation:3.0.3")
-    implementation("io.ktor:ktor-serialization-kotlinx-json:3.0.3")
-
-    // Kotlinx Serialization
-    implementation("org.jetbrains.kotlinx:kotlinx-serialization-json:1.7.3")
-}}
+{}}}
 "#,
+        render_extra_plugins(config, features),
         config.android.package,
         config.android.target_sdk,
         config.android.package,
         config.android.min_sdk,
         config.android.target_sdk,
-        config.project.version
+        config.project.version,
+        render_dependencies(config, features)
     );
 
-    fs::create_dir_all(output_dir.join("app"))?;
-    fs::write(output_dir.join("app/build.gradle.kts"), content)?;
-    Ok(())
+    write_if_changed(&output_dir.join("app/build.gradle.kts"), &content)
 }
 
 /// Escape XML special characters
@@ -204,16 +497,45 @@ fn escape_xml(text: &str) -> String {
         .replace('\'', "&apos;")
 }
 
+/// Permissions the manifest requests: INTERNET for the network libraries the
+/// project uses, then `[android] permissions`
+fn manifest_permissions(config: &Config, features: &ScaffoldFeatures) -> Vec<String> {
+    let mut permissions = Vec::new();
+    if features.ktor || features.coil {
+        permissions.push("android.permission.INTERNET".to_string());
+    }
+    for permission in &config.android.permissions {
+        // Short names refer to the platform's permissions
+        let permission = if permission.contains('.') {
+            permission.clone()
+        } else {
+            format!("android.permission.{}", permission)
+        };
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+    permissions
+}
+
 /// Generate AndroidManifest.xml
-fn generate_manifest(config: &Config, output_dir: &Path) -> Result<()> {
+fn generate_manifest(config: &Config, output_dir: &Path, features: &ScaffoldFeatures) -> Result<()> {
     let escaped_name = escape_xml(&config.project.name);
+    let permissions: String = manifest_permissions(config, features)
+        .iter()
+        .map(|permission| format!("    <uses-permission android:name=\"{}\" />\n\n", escape_xml(permission)))
+        .collect();
+    // Loads persisted stores at process start (see build_pipeline's StoreInitializer.kt)
+    let providers = if features.datastore {
+        "        <provider\n            android:name=\".StoreInitializer\"\n            android:authorities=\"${applicationId}.store-initializer\"\n            android:exported=\"false\" />\n"
+    } else {
+        ""
+    };
     let content = format!(
         r#"<?xml version="1.0" encoding="utf-8"?>
 <manifest xmlns:android="http://schemas.android.com/apk/res/android">
 
-    <uses-permission android:name="android.permission.INTERNET" />
-
-    <application
+{}    <application
         android:allowBackup="true"
         android:label="{}"
         android:supportsRtl="true"
@@ -227,17 +549,16 @@ fn generate_manifest(config: &Config, output_dir: &Path) -> Result<()> {
                 <category android:name="android.intent.category.LAUNCHER" />
             </intent-filter>
         </activity>
-    </application>
+{}    </application>
 
 </manifest>
 "#,
-        escaped_name
+        permissions,
+        escaped_name,
+        providers
     );
 
-    let manifest_dir = output_dir.join("app/src/main");
-    fs::create_dir_all(&manifest_dir)?;
-    fs::write(manifest_dir.join("AndroidManifest.xml"), content)?;
-    Ok(())
+    write_if_changed(&output_dir.join("app/src/main/AndroidManifest.xml"), &content)
 }
 
 /// Generate proguard-rules.pro
@@ -274,7 +595,7 @@ fn generate_proguard_rules(output_dir: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{AndroidConfig, BuildConfig, Config, FfiConfig, ProjectConfig, ToolchainConfig};
+    use crate::config::{AndroidConfig, BuildConfig, Config, FetchConfig, FfiConfig, ProjectConfig, ToolchainConfig};
     use tempfile::TempDir;
 
     fn make_test_config() -> Config {
@@ -287,10 +608,15 @@ mod tests {
                 min_sdk: 24,
                 target_sdk: 34,
                 package: "com.example.testapp".to_string(),
+                permissions: Vec::new(),
             },
             build: BuildConfig::default(),
             toolchain: ToolchainConfig::default(),
             ffi: FfiConfig::default(),
+            fetch: FetchConfig::default(),
+            dependencies: Default::default(),
+            plugins: Default::default(),
+            repositories: Default::default(),
         }
     }
 
@@ -299,7 +625,7 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let config = make_test_config();
 
-        let result = generate(&config, temp.path());
+        let result = generate(&config, temp.path(), &ScaffoldFeatures::default());
         assert!(result.is_ok());
 
         // Verify key files exist
@@ -327,11 +653,74 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let config = make_test_config();
 
-        generate_manifest(&config, temp.path()).unwrap();
+        generate_manifest(&config, temp.path(), &ScaffoldFeatures::default()).unwrap();
 
         let content =
             fs::read_to_string(temp.path().join("app/src/main/AndroidManifest.xml")).unwrap();
         assert!(content.contains("TestApp"));
+        assert!(!content.contains("uses-permission"));
+    }
+
+    #[test]
+    fn test_manifest_store_initializer() {
+        let temp = TempDir::new().unwrap();
+        let config = make_test_config();
+        let manifest = temp.path().join("app/src/main/AndroidManifest.xml");
+
+        generate_manifest(&config, temp.path(), &ScaffoldFeatures::default()).unwrap();
+        assert!(!fs::read_to_string(&manifest).unwrap().contains("<provider"));
+
+        let features = ScaffoldFeatures { datastore: true, ..Default::default() };
+        generate_manifest(&config, temp.path(), &features).unwrap();
+        let content = fs::read_to_string(&manifest).unwrap();
+        assert!(content.contains(
+            "        </activity>\n        <provider\n            android:name=\".StoreInitializer\"\n\
+             \x20           android:authorities=\"${applicationId}.store-initializer\"\n\
+             \x20           android:exported=\"false\" />\n    </application>"
+        ));
+    }
+
+    #[test]
+    fn test_manifest_permissions() {
+        let temp = TempDir::new().unwrap();
+        let mut config = make_test_config();
+        config.android.permissions = vec!["CAMERA".to_string(), "android.permission.INTERNET".to_string()];
+        let features = ScaffoldFeatures { ktor: true, ..Default::default() };
+
+        generate_manifest(&config, temp.path(), &features).unwrap();
+
+        let content =
+            fs::read_to_string(temp.path().join("app/src/main/AndroidManifest.xml")).unwrap();
+        assert!(content.contains(
+            "    <uses-permission android:name=\"android.permission.INTERNET\" />\n\n\
+             \x20   <uses-permission android:name=\"android.permission.CAMERA\" />\n\n\
+             \x20   <application"
+        ));
+        assert_eq!(content.matches("INTERNET").count(), 1);
+    }
+
+    #[test]
+    fn test_app_gradle_omits_unused_libraries() {
+        let temp = TempDir::new().unwrap();
+        let config = make_test_config();
+
+        generate_app_gradle(&config, temp.path(), &ScaffoldFeatures::default()).unwrap();
+
+        let content = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        assert!(!content.contains("io.ktor"));
+        assert!(!content.contains("io.coil-kt"));
+        assert!(!content.contains("androidx.navigation"));
+        assert!(!content.contains(PARCELIZE_PLUGIN));
+        assert!(content.contains("androidx.compose.material3:material3"));
+        assert!(content.contains("kotlinx-serialization-json"));
+
+        let features = ScaffoldFeatures { ktor: true, coil: true, navigation: true, ..Default::default() };
+        generate_app_gradle(&config, temp.path(), &features).unwrap();
+
+        let content = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        assert!(content.contains("io.ktor:ktor-client-okhttp:3.0.3"));
+        assert!(content.contains("io.coil-kt:coil-compose:2.6.0"));
+        assert!(content.contains("androidx.navigation:navigation-compose:2.8.4"));
     }
 
     #[test]
@@ -339,11 +728,174 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let config = make_test_config();
 
-        generate_app_gradle(&config, temp.path()).unwrap();
+        generate_app_gradle(&config, temp.path(), &ScaffoldFeatures::default()).unwrap();
 
         let content = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
         assert!(content.contains("minSdk = 24"));
         assert!(content.contains("targetSdk = 34"));
         assert!(content.contains("com.example.testapp"));
+        assert!(!content.contains("datastore"));
+    }
+
+    #[test]
+    fn test_app_gradle_datastore_feature() {
+        let temp = TempDir::new().unwrap();
+        let config = make_test_config();
+        let features = ScaffoldFeatures { datastore: true, ..Default::default() };
+
+        generate_app_gradle(&config, temp.path(), &features).unwrap();
+
+        let content = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        assert!(content.contains("androidx.datastore:datastore-preferences"));
+        assert!(!content.contains("ksp("));
+    }
+
+    #[test]
+    fn test_app_gradle_room_feature() {
+        let temp = TempDir::new().unwrap();
+        let config = make_test_config();
+        let features = ScaffoldFeatures { room: true, ..Default::default() };
+
+        generate_app_gradle(&config, temp.path(), &features).unwrap();
+
+        let content = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        assert!(content.contains("    id(\"com.google.devtools.ksp\") version \"2.1.0-1.0.29\"\n}"));
+        assert!(content.contains("ksp(\"androidx.room:room-compiler:2.6.1\")"));
+        assert_eq!(ksp_version("2.1.0"), "2.1.0-1.0.29");
+    }
+
+    #[test]
+    fn test_app_gradle_libraries_are_in_compatibility_matrix() {
+        let temp = TempDir::new().unwrap();
+        let config = make_test_config();
+        let features = ScaffoldFeatures { datastore: true, room: true, ktor: true, coil: true, navigation: true, parcelize: true };
+
+        generate_app_gradle(&config, temp.path(), &features).unwrap();
+
+        // Every library version the validator checks is the one the scaffold emits
+        let content = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        for library in crate::toolchain::validator::SCAFFOLD_LIBRARIES {
+            assert!(content.contains(&format!("\"{}\"", library.coordinate)), "{}", library.coordinate);
+        }
+    }
+
+    #[test]
+    fn test_app_gradle_declared_dependencies() {
+        use crate::config::DetailedDependency;
+
+        let temp = TempDir::new().unwrap();
+        let mut config = make_test_config();
+        config.dependencies.insert(
+            "io.coil-kt:coil-compose".to_string(),
+            DependencySpec::Version("2.7.0".to_string()),
+        );
+        config.dependencies.insert(
+            "com.google.firebase:firebase-bom".to_string(),
+            DependencySpec::Detailed(DetailedDependency {
+                version: Some("33.5.1".to_string()),
+                platform: true,
+                ..Default::default()
+            }),
+        );
+        config.dependencies.insert(
+            "com.google.firebase:firebase-analytics".to_string(),
+            DependencySpec::Detailed(DetailedDependency::default()),
+        );
+        config.dependencies.insert(
+            "junit:junit".to_string(),
+            DependencySpec::Detailed(DetailedDependency {
+                version: Some("4.13.2".to_string()),
+                configuration: Some("testImplementation".to_string()),
+                platform: false,
+            }),
+        );
+
+        let features = ScaffoldFeatures { coil: true, ..Default::default() };
+
+        generate_app_gradle(&config, temp.path(), &features).unwrap();
+
+        let content = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        // Overrides the scaffold's version in place
+        assert!(content.contains("    // Coil for AsyncImage\n    implementation(\"io.coil-kt:coil-compose:2.7.0\")\n"));
+        assert!(!content.contains("coil-compose:2.6.0"));
+        assert!(content.contains(
+            "    // From whitehall.toml [dependencies]\n\
+             \x20   implementation(platform(\"com.google.firebase:firebase-bom:33.5.1\"))\n"
+        ));
+        assert!(content.contains("    implementation(\"com.google.firebase:firebase-analytics\")\n"));
+        assert!(content.contains("    testImplementation(\"junit:junit:4.13.2\")\n"));
+        assert!(!content.contains(KSP_PLUGIN));
+    }
+
+    #[test]
+    fn test_app_gradle_ksp_dependency_adds_plugin() {
+        use crate::config::DetailedDependency;
+
+        let temp = TempDir::new().unwrap();
+        let mut config = make_test_config();
+        config.dependencies.insert(
+            "com.google.dagger:hilt-compiler".to_string(),
+            DependencySpec::Detailed(DetailedDependency {
+                version: Some("2.52".to_string()),
+                configuration: Some("ksp".to_string()),
+                platform: false,
+            }),
+        );
+
+        generate_app_gradle(&config, temp.path(), &ScaffoldFeatures::default()).unwrap();
+
+        let content = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        assert!(content.contains("    id(\"com.google.devtools.ksp\") version \"2.1.0-1.0.29\"\n}"));
+        assert!(content.contains("    ksp(\"com.google.dagger:hilt-compiler:2.52\")\n"));
+    }
+
+    #[test]
+    fn test_parcelize_plugin_only_when_used() {
+        let temp = TempDir::new().unwrap();
+        let config = make_test_config();
+
+        generate(&config, temp.path(), &ScaffoldFeatures::default()).unwrap();
+        let root = fs::read_to_string(temp.path().join("build.gradle.kts")).unwrap();
+        let app = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        assert!(!root.contains(PARCELIZE_PLUGIN));
+        assert!(!app.contains(PARCELIZE_PLUGIN));
+
+        let features = ScaffoldFeatures { parcelize: true, ..Default::default() };
+        sync(&config, temp.path(), &features).unwrap();
+        let root = fs::read_to_string(temp.path().join("build.gradle.kts")).unwrap();
+        let app = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        assert!(root.contains("    id(\"org.jetbrains.kotlin.plugin.parcelize\") version \"2.1.0\" apply false\n"));
+        assert!(app.contains("    id(\"org.jetbrains.kotlin.plugin.parcelize\")\n"));
+    }
+
+    #[test]
+    fn test_declared_plugins_and_repositories() {
+        let temp = TempDir::new().unwrap();
+        let mut config = make_test_config();
+        config.plugins.insert("com.google.gms.google-services".to_string(), "4.4.2".to_string());
+        config.plugins.insert("org.jetbrains.kotlin.android".to_string(), "2.1.10".to_string());
+        config.plugins.insert(KSP_PLUGIN.to_string(), "2.1.0-1.0.28".to_string());
+        config.repositories.insert("jitpack".to_string(), "https://jitpack.io".to_string());
+        let features = ScaffoldFeatures { room: true, ..Default::default() };
+
+        generate(&config, temp.path(), &features).unwrap();
+
+        let root = fs::read_to_string(temp.path().join("build.gradle.kts")).unwrap();
+        assert!(root.contains("    id(\"org.jetbrains.kotlin.android\") version \"2.1.10\" apply false\n"));
+        assert!(root.contains("    id(\"com.google.gms.google-services\") version \"4.4.2\" apply false\n"));
+        assert!(root.contains("    id(\"com.google.devtools.ksp\") version \"2.1.0-1.0.28\" apply false\n"));
+
+        // Declared in the root build file, applied without a version in the app
+        let app = fs::read_to_string(temp.path().join("app/build.gradle.kts")).unwrap();
+        assert!(app.contains("    id(\"com.google.gms.google-services\")\n"));
+        assert!(app.contains("    id(\"com.google.devtools.ksp\")\n"));
+        assert!(!app.contains("1.0.29"));
+        assert_eq!(app.matches("id(\"org.jetbrains.kotlin.android\")").count(), 1);
+
+        let settings = fs::read_to_string(temp.path().join("settings.gradle.kts")).unwrap();
+        assert_eq!(
+            settings.matches("        maven { name = \"jitpack\"; url = uri(\"https://jitpack.io\") }\n").count(),
+            2
+        );
     }
 }
@@ -4,9 +4,12 @@ use std::path::{Path, PathBuf};
 
 use crate::android_scaffold;
 use crate::config::Config;
+use crate::fetch;
+use crate::openapi;
 use crate::project::{discover_files, FileType, WhitehallFile};
 use crate::routes;
 use crate::transpiler;
+use crate::ui_state;
 
 /// App-level configuration parsed from main.wh's <App> component
 #[derive(Debug, Clone, Default)]
@@ -83,24 +86,29 @@ pub fn execute_build(config: &Config, clean: bool) -> Result<BuildResult> {
     // 3. Build project-wide store registry for cross-file store detection
     let global_store_registry = build_store_registry(&files)?;
 
-    // 4. Generate Android scaffold (only if clean or missing)
-    let scaffold_exists = output_dir.join("app/build.gradle.kts").exists();
-    if clean || !scaffold_exists {
-        android_scaffold::generate(config, output_dir)
-            .context("Failed to generate Android project scaffold")?;
-    }
+    // 3.5. Collect @entity classes for the Room database
+    let (entities, mut errors) = collect_entities(&files)?;
 
     // 4.5. Build FFI components if enabled
     crate::ffi_build::build_ffi(config, Path::new("."))
         .context("Failed to build FFI components")?;
 
     // 5. Transpile each file
-    let mut errors = Vec::new();
     let mut success_count = 0;
 
+    // Optional libraries the generated code needs, aggregated across files
+    let mut usage = transpiler::FeatureUsage::default();
+
     for file in &files {
+        // Files with invalid entities were already reported
+        if errors.iter().any(|e| e.file == file.path) {
+            continue;
+        }
         match transpile_file(file, config, output_dir, &global_store_registry) {
-            Ok(_) => success_count += 1,
+            Ok(file_usage) => {
+                usage.merge(file_usage);
+                success_count += 1;
+            }
             Err(e) => errors.push(BuildError {
                 file: file.path.clone(),
                 message: e.to_string(),
@@ -108,14 +116,45 @@ pub fn execute_build(config: &Config, clean: bool) -> Result<BuildResult> {
         }
     }
 
+    // 5.5. Generate typed API clients from OpenAPI specs in src/api/
+    errors.extend(generate_api_clients(config, output_dir)?);
+
+    // 5.6. Generate runtime support (shared HTTP client, async UiState)
+    generate_runtime_files(config, output_dir, &files, &mut usage)?;
+
     // 6. Generate Routes.kt from route structure
     if errors.is_empty() {
         generate_routes_file(config, output_dir)?;
     }
 
+    // 6.5. Generate AppDatabase for @entity classes
+    if errors.is_empty() {
+        generate_database_files(config, output_dir, &entities)?;
+    }
+
     // 7. Generate MainActivity if all files transpiled successfully
     if errors.is_empty() {
-        generate_main_activity(config, output_dir, &files, &global_store_registry)?;
+        usage.merge(generate_main_activity(config, output_dir, &files, &global_store_registry)?);
+    }
+
+    // 7.5. Load persisted stores at process start, whichever component the app starts with
+    if errors.is_empty() {
+        generate_store_initializer(config, output_dir, &global_store_registry)?;
+    }
+
+    // 8. Generate Android scaffold (only if clean or missing)
+    // The Gradle scripts and manifest are re-synced otherwise, since their dependencies and
+    // permissions follow whitehall.toml and the framework APIs the project uses
+    usage.navigation |= !routes::discover_routes()?.is_empty();
+    let features = scaffold_features(&global_store_registry, &entities, &usage);
+    let scaffold_exists = output_dir.join("app/build.gradle.kts").exists();
+    if clean || !scaffold_exists {
+        android_scaffold::generate(config, output_dir, &features)
+            .context("Failed to generate Android project scaffold")?;
+    } else if errors.is_empty() {
+        // Skipped on errors: usage from files that failed to transpile is unknown
+        android_scaffold::sync(config, output_dir, &features)
+            .context("Failed to update Gradle build scripts")?;
     }
 
     Ok(BuildResult {
@@ -167,6 +206,7 @@ fn build_store_registry(files: &[WhitehallFile]) -> Result<transpiler::StoreRegi
                     has_inject,
                     package: file.package_path.clone(),
                     route_params: vec![],  // Only components have route params
+                    persist: class.persist.clone(),
                 };
                 registry.insert(class.name.clone(), store_info);
             }
@@ -188,6 +228,7 @@ fn build_store_registry(files: &[WhitehallFile]) -> Result<transpiler::StoreRegi
                         has_inject: false,
                         package: file.package_path.clone(),
                         route_params: vec![],  // Will be filled during semantic analysis
+                        persist: None,
                     };
                     registry.insert(component_name, store_info);
                 }
@@ -198,16 +239,275 @@ fn build_store_registry(files: &[WhitehallFile]) -> Result<transpiler::StoreRegi
     Ok(registry)
 }
 
+/// `@entity` classes of the project as (package, entity) pairs
+type Entities = Vec<(String, transpiler::Entity)>;
+
+/// Collect `@entity` data classes from all files, with build errors for files
+/// whose entities are invalid
+fn collect_entities(files: &[WhitehallFile]) -> Result<(Entities, Vec<BuildError>)> {
+    let mut entities = Vec::new();
+    let mut errors = Vec::new();
+
+    // main.wh becomes MainActivity, never a model file
+    for file in files.iter().filter(|f| f.file_type != FileType::Main) {
+        let source = fs::read_to_string(&file.path)
+            .context(format!("Failed to read {} for entities", file.path.display()))?;
+
+        match transpiler::parse_entities(&source) {
+            Ok(file_entities) => {
+                for entity in file_entities {
+                    entities.push((file.package_path.clone(), entity));
+                }
+            }
+            Err(e) => errors.push(BuildError {
+                file: file.path.clone(),
+                message: format!("Compilation error: {}", e),
+            }),
+        }
+    }
+
+    entities.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+    Ok((entities, errors))
+}
+
+/// Generate AppDatabase.kt and Migrations.kt in the `data` package
+///
+/// Without entities they are removed, since Room is dropped from the Gradle script.
+fn generate_database_files(config: &Config, output_dir: &Path, entities: &[(String, transpiler::Entity)]) -> Result<()> {
+    let package = format!("{}.data", config.android.package);
+    let data_dir = output_dir
+        .join("app/src/main/kotlin")
+        .join(package.replace('.', "/"));
+    if entities.is_empty() {
+        remove_stale_file(&data_dir.join("AppDatabase.kt"))?;
+        return remove_stale_file(&data_dir.join("Migrations.kt"));
+    }
+    fs::create_dir_all(&data_dir)
+        .context("Failed to create output directories")?;
+
+    fs::write(data_dir.join("AppDatabase.kt"), transpiler::generate_database(&package, entities))
+        .context("Failed to write AppDatabase.kt")?;
+    fs::write(data_dir.join("Migrations.kt"), transpiler::generate_migrations(&package))
+        .context("Failed to write Migrations.kt")?;
+
+    Ok(())
+}
+
+/// Generate a Ktor client (`<package>.api.<Name>Api`) for each OpenAPI spec in src/api/
+/// Invalid specs are returned as build errors rather than aborting the build
+///
+/// The generated files are recorded in `.whitehall/api-clients`, so clients whose spec
+/// was removed or renamed are deleted on the next build rather than left to fail to
+/// compile once Ktor is dropped from the Gradle script.
+fn generate_api_clients(config: &Config, output_dir: &Path) -> Result<Vec<BuildError>> {
+    let package = format!("{}.api", config.android.package);
+    let api_dir = output_dir
+        .join("app/src/main/kotlin")
+        .join(package.replace('.', "/"));
+    let record = output_dir.join(".whitehall").join("api-clients");
+
+    let mut errors = Vec::new();
+    let mut generated = Vec::new();
+    // All clients and models share the api package, so names must be unique across specs
+    let mut declared: Vec<(String, PathBuf)> = Vec::new();
+    for spec_path in openapi::discover_specs()? {
+        let spec = match openapi::load_spec(&spec_path) {
+            Ok(spec) => spec,
+            Err(e) => {
+                errors.push(BuildError { file: spec_path, message: format!("{:#}", e) });
+                continue;
+            }
+        };
+
+        let mut names = vec![spec.name.clone()];
+        names.extend(spec.models.iter().map(|m| m.name().to_string()));
+        let conflict = names.iter().find_map(|name| {
+            declared.iter().find(|(other, _)| other == name).map(|(_, path)| (name, path))
+        });
+        if let Some((name, other)) = conflict {
+            errors.push(BuildError {
+                file: spec_path.clone(),
+                message: format!("'{}' is also generated from {}", name, other.display()),
+            });
+            continue;
+        }
+        declared.extend(names.into_iter().map(|name| (name, spec_path.clone())));
+
+        fs::create_dir_all(&api_dir)
+            .context("Failed to create output directories")?;
+        let output_path = api_dir.join(format!("{}.kt", spec.name));
+        fs::write(&output_path, openapi::generate_client(&spec, &config.android.package))
+            .context(format!("Failed to write {}", output_path.display()))?;
+        generated.push(format!("{}.kt", spec.name));
+    }
+
+    if let Ok(previous) = fs::read_to_string(&record) {
+        for stale in previous.lines().filter(|name| !generated.iter().any(|g| g == name)) {
+            remove_stale_file(&api_dir.join(stale))?;
+        }
+    }
+    if generated.is_empty() {
+        remove_stale_file(&record)?;
+    } else {
+        fs::create_dir_all(record.parent().unwrap())
+            .context("Failed to create .whitehall directory")?;
+        fs::write(&record, generated.join("\n"))
+            .context(format!("Failed to write {}", record.display()))?;
+    }
+
+    Ok(errors)
+}
+
+/// Generate runtime support files in the root package for the framework APIs the project uses:
+/// Fetch.kt for $fetch and API clients, UiState.kt for $async
+///
+/// Fetch.kt is recorded in `usage`, since stores and API clients use it without the
+/// codegen seeing `$fetch`. Files for APIs no longer used are removed, as their
+/// libraries are dropped from the Gradle script.
+fn generate_runtime_files(
+    config: &Config,
+    output_dir: &Path,
+    files: &[WhitehallFile],
+    usage: &mut transpiler::FeatureUsage,
+) -> Result<()> {
+    let sources: Vec<String> = files.iter()
+        .filter_map(|f| fs::read_to_string(&f.path).ok())
+        .collect();
+    let uses = |api: &str| sources.iter().any(|source| source.contains(api));
+
+    let package = &config.android.package;
+    let package_dir = output_dir
+        .join("app/src/main/kotlin")
+        .join(package.replace('.', "/"));
+
+    let mut runtime_files = Vec::new();
+    if usage.fetch || uses("$fetch") || !openapi::discover_specs()?.is_empty() {
+        usage.fetch = true;
+        runtime_files.push(("Fetch.kt", fetch::generate_fetch_kt(&config.fetch, package)));
+    } else {
+        remove_stale_file(&package_dir.join("Fetch.kt"))?;
+    }
+    if uses("$async") {
+        runtime_files.push(("UiState.kt", ui_state::generate_ui_state_kt(package)));
+    } else {
+        remove_stale_file(&package_dir.join("UiState.kt"))?;
+    }
+
+    for (name, content) in runtime_files {
+        fs::create_dir_all(&package_dir)
+            .context("Failed to create output directories")?;
+        fs::write(package_dir.join(name), content)
+            .context(format!("Failed to write {}", name))?;
+    }
+
+    Ok(())
+}
+
+/// Remove a generated file left over from an earlier build, if any
+fn remove_stale_file(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path)
+            .context(format!("Failed to remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Detect optional Android libraries needed by the project
+fn scaffold_features(
+    global_store_registry: &transpiler::StoreRegistry,
+    entities: &[(String, transpiler::Entity)],
+    usage: &transpiler::FeatureUsage,
+) -> android_scaffold::ScaffoldFeatures {
+    android_scaffold::ScaffoldFeatures {
+        datastore: persisted_stores(global_store_registry).next().is_some(),
+        room: !entities.is_empty(),
+        ktor: usage.fetch,
+        coil: usage.images,
+        navigation: usage.navigation,
+        parcelize: usage.parcelize,
+    }
+}
+
+/// Stores declared with @store(persist = ...)
+fn persisted_stores(global_store_registry: &transpiler::StoreRegistry) -> impl Iterator<Item = &transpiler::StoreInfo> {
+    global_store_registry.iter().map(|(_, info)| info).filter(|info| info.persist.is_some())
+}
+
+/// Generate StoreInitializer.kt, a ContentProvider that loads persisted stores from
+/// DataStore when the app process starts (they need a Context, which singletons don't
+/// have). Providers are created before any activity, service, receiver or widget runs,
+/// so every entry point sees loaded stores. Removed when no store is persisted.
+fn generate_store_initializer(config: &Config, output_dir: &Path, global_store_registry: &transpiler::StoreRegistry) -> Result<()> {
+    let package = &config.android.package;
+    let output_path = output_dir
+        .join("app/src/main/kotlin")
+        .join(package.replace('.', "/"))
+        .join("StoreInitializer.kt");
+
+    let mut stores: Vec<_> = persisted_stores(global_store_registry).collect();
+    if stores.is_empty() {
+        return remove_stale_file(&output_path);
+    }
+    stores.sort_by(|a, b| a.class_name.cmp(&b.class_name));
+
+    fs::create_dir_all(output_path.parent().unwrap())
+        .context("Failed to create output directories")?;
+    fs::write(&output_path, store_initializer_kt(package, &stores))
+        .context("Failed to write StoreInitializer.kt")?;
+    Ok(())
+}
+
+/// StoreInitializer.kt for the given persisted stores, registered in the manifest
+fn store_initializer_kt(package: &str, stores: &[&transpiler::StoreInfo]) -> String {
+    let mut imports = vec![
+        "android.content.ContentProvider".to_string(),
+        "android.content.ContentValues".to_string(),
+        "android.database.Cursor".to_string(),
+        "android.net.Uri".to_string(),
+    ];
+    imports.extend(
+        stores.iter()
+            .filter(|store| store.package != package)
+            .map(|store| format!("{}.{}", store.package, store.class_name)),
+    );
+    imports.sort();
+
+    let imports: String = imports.iter().map(|import| format!("import {}\n", import)).collect();
+    let inits: String = stores.iter()
+        .map(|store| format!("        {}.init(context)\n", store.class_name))
+        .collect();
+
+    format!(
+        r#"package {package}
+
+{imports}
+/** Loads @store(persist = ...) stores when the app process starts, before any component runs */
+class StoreInitializer : ContentProvider() {{
+    override fun onCreate(): Boolean {{
+        val context = context ?: return false
+{inits}        return true
+    }}
+
+    override fun query(uri: Uri, projection: Array<out String>?, selection: String?, selectionArgs: Array<out String>?, sortOrder: String?): Cursor? = null
+    override fun getType(uri: Uri): String? = null
+    override fun insert(uri: Uri, values: ContentValues?): Uri? = null
+    override fun delete(uri: Uri, selection: String?, selectionArgs: Array<out String>?): Int = 0
+    override fun update(uri: Uri, values: ContentValues?, selection: String?, selectionArgs: Array<out String>?): Int = 0
+}}
+"#
+    )
+}
+
 /// Transpile a single .wh file to Kotlin
 fn transpile_file(
     file: &WhitehallFile,
     _config: &Config,
     output_dir: &Path,
     global_store_registry: &transpiler::StoreRegistry,
-) -> Result<()> {
+) -> Result<transpiler::FeatureUsage> {
     // Skip main.wh - it's handled separately in MainActivity generation
     if file.file_type == FileType::Main {
-        return Ok(());
+        return Ok(transpiler::FeatureUsage::default());
     }
 
     // Read source file
@@ -222,7 +522,7 @@ fn transpile_file(
     };
 
     // Transpile to Kotlin with global store registry
-    let result = transpiler::transpile_with_registry(
+    let (result, usage) = transpiler::transpile_with_usage(
         &source,
         &file.package_path,
         &file.component_name,
@@ -253,7 +553,7 @@ fn transpile_file(
         }
     }
 
-    Ok(())
+    Ok(usage)
 }
 
 /// Get the output path for a transpiled Kotlin file
@@ -282,14 +582,16 @@ fn get_kotlin_output_path_with_suffix(output_dir: &Path, file: &WhitehallFile, s
 }
 
 /// Generate MainActivity.kt
+/// Returns the optional libraries main.wh needs when it is transpiled as the App composable
 fn generate_main_activity(
     config: &Config,
     output_dir: &Path,
     files: &[WhitehallFile],
     global_store_registry: &transpiler::StoreRegistry,
-) -> Result<()> {
+) -> Result<transpiler::FeatureUsage> {
     // Discover routes to determine if we need NavHost setup
     let discovered_routes = routes::discover_routes()?;
+    let mut usage = transpiler::FeatureUsage::default();
 
     // Check if there's a main.wh file
     let main_file = files.iter().find(|f| f.file_type == FileType::Main);
@@ -304,8 +606,9 @@ fn generate_main_activity(
     } else if let Some(main_file) = main_file {
         // No routes - use transpiled main.wh content as the App composable
         let source = fs::read_to_string(&main_file.path)?;
-        let result = transpiler::transpile_with_registry(&source, &config.android.package, "App", None, Some(global_store_registry))
+        let (result, main_usage) = transpiler::transpile_with_usage(&source, &config.android.package, "App", None, Some(global_store_registry))
             .map_err(|e| anyhow::anyhow!(e))?;
+        usage = main_usage;
 
         // Handle Multiple results (e.g., when main.wh has inline vars → generates ViewModel)
         match &result {
@@ -412,7 +715,7 @@ class MainActivity : ComponentActivity() {{
     fs::create_dir_all(output_path.parent().unwrap())?;
     fs::write(output_path, activity_content)?;
 
-    Ok(())
+    Ok(usage)
 }
 
 /// Generate MainActivity with NavHost for routing
@@ -946,7 +1249,7 @@ mod tests {
 
     #[test]
     fn test_default_main_activity_generation() {
-        use crate::config::{AndroidConfig, BuildConfig, Config, FfiConfig, ProjectConfig, ToolchainConfig};
+        use crate::config::{AndroidConfig, BuildConfig, Config, FetchConfig, FfiConfig, ProjectConfig, ToolchainConfig};
 
         let config = Config {
             project: ProjectConfig {
@@ -957,10 +1260,15 @@ mod tests {
                 min_sdk: 24,
                 target_sdk: 34,
                 package: "com.example.test".to_string(),
+                permissions: Vec::new(),
             },
             build: BuildConfig::default(),
             toolchain: ToolchainConfig::default(),
             ffi: FfiConfig::default(),
+            fetch: FetchConfig::default(),
+            dependencies: Default::default(),
+            plugins: Default::default(),
+            repositories: Default::default(),
         };
 
         let content = generate_default_main_activity(&config);
@@ -969,4 +1277,125 @@ mod tests {
         assert!(content.contains("class MainActivity"));
         assert!(content.contains("Hello, Whitehall!"));
     }
+
+    #[test]
+    fn test_store_initializer() {
+        use crate::config::{AndroidConfig, BuildConfig, Config, FetchConfig, FfiConfig, ProjectConfig, ToolchainConfig};
+
+        let config = Config {
+            project: ProjectConfig {
+                name: "test".to_string(),
+                version: "0.1.0".to_string(),
+            },
+            android: AndroidConfig {
+                min_sdk: 24,
+                target_sdk: 34,
+                package: "com.example.test".to_string(),
+                permissions: Vec::new(),
+            },
+            build: BuildConfig::default(),
+            toolchain: ToolchainConfig::default(),
+            ffi: FfiConfig::default(),
+            fetch: FetchConfig::default(),
+            dependencies: Default::default(),
+            plugins: Default::default(),
+            repositories: Default::default(),
+        };
+
+        let mut registry = transpiler::StoreRegistry::new();
+        registry.insert("AppSettings".to_string(), transpiler::StoreInfo {
+            class_name: "AppSettings".to_string(),
+            source: transpiler::StoreSource::Singleton,
+            has_vars: true,
+            has_hilt: false,
+            has_inject: false,
+            package: "com.example.test.stores".to_string(),
+            route_params: vec![],
+            persist: Some("settings".to_string()),
+        });
+
+        assert!(scaffold_features(&registry, &[], &transpiler::FeatureUsage::default()).datastore);
+
+        // Stores load at process start, not in MainActivity, so every entry point sees them
+        assert!(!generate_default_main_activity(&config).contains(".init("));
+
+        let dir = tempfile::tempdir().unwrap();
+        let initializer = dir.path().join("app/src/main/kotlin/com/example/test/StoreInitializer.kt");
+        generate_store_initializer(&config, dir.path(), &registry).unwrap();
+        let content = fs::read_to_string(&initializer).unwrap();
+        assert!(content.contains("import com.example.test.stores.AppSettings\n"));
+        assert!(content.contains("class StoreInitializer : ContentProvider() {"));
+        assert!(content.contains("        val context = context ?: return false\n        AppSettings.init(context)\n        return true\n"));
+
+        // Removed once no store is persisted
+        generate_store_initializer(&config, dir.path(), &transpiler::StoreRegistry::new()).unwrap();
+        assert!(!initializer.exists());
+    }
+
+    #[test]
+    fn test_feature_usage_selects_libraries() {
+        let usage = |source: &str| {
+            transpiler::transpile_with_usage(source, "com.example.app.components", "Widget", None, None)
+                .unwrap()
+                .1
+        };
+
+        let plain = usage("<Text>Hello</Text>");
+        assert_eq!(plain, transpiler::FeatureUsage::default());
+
+        let image = usage("@prop val url: String\n\n<AsyncImage url={url} />");
+        assert!(image.images && !image.fetch && !image.navigation);
+
+        let fetch = usage("fun load(): String {\n  return $fetch(\"https://example.com\")\n}\n\n<Text>Hi</Text>");
+        assert!(fetch.fetch && !fetch.images);
+
+        let navigate = usage("fun go() {\n  $navigate($routes.login)\n}\n\n<Button text=\"Go\" onClick={go} />");
+        assert!(navigate.navigation && !navigate.fetch);
+
+        let saved = usage(
+            "$saved var filter: Filter? = null\n<Text>Filter</Text>\n\n@Parcelize\ndata class Filter(val tag: String) : Parcelable",
+        );
+        assert!(saved.parcelize && !plain.parcelize);
+
+        let mut project = transpiler::FeatureUsage::default();
+        project.merge(image);
+        project.merge(fetch);
+        let features = scaffold_features(&transpiler::StoreRegistry::new(), &[], &project);
+        assert!(features.coil && features.ktor && !features.navigation && !features.room && !features.parcelize);
+    }
+
+    #[test]
+    fn test_collect_entities_reports_invalid_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |name: &str, file_type: FileType, package: &str, source: &str| {
+            let path = dir.path().join(format!("{}.wh", name));
+            fs::write(&path, source).unwrap();
+            WhitehallFile {
+                path,
+                file_type,
+                component_name: name.to_string(),
+                package_path: package.to_string(),
+            }
+        };
+
+        let files = vec![
+            file("Note", FileType::Component, "com.example.app.models",
+                "@entity\ndata class Note(\n  @primary val id: Long = 0,\n  val title: String\n)\n"),
+            file("Home", FileType::Screen, "com.example.app.screens",
+                "var query = \"\"\n\n@entity\ndata class Draft(\n  @primary val id: Long = 0\n)\n\n<Text>{query}</Text>\n"),
+            file("Tag", FileType::Component, "com.example.app.models",
+                "@entity\ndata class Tag(val label: String)\n"),
+        ];
+
+        let (entities, errors) = collect_entities(&files).unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].0, "com.example.app.models");
+        assert_eq!(entities[0].1.name, "Note");
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].file, files[1].path);
+        assert!(errors[0].message.contains("line 4: @entity data classes must be declared"));
+        assert_eq!(errors[1].file, files[2].path);
+        assert!(errors[1].message.contains("primary key"));
+    }
 }
@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use toml_edit::{value, DocumentMut, InlineTable, Item, Table};
+
+use crate::config::{self, DependencySpec, DetailedDependency};
+
+/// Add a Gradle dependency to `[dependencies]` in whitehall.toml, or update it
+///
+/// `coordinate` is `group:artifact[:version]`; without a version the artifact is
+/// expected to get one from a BOM. The rest of the file keeps its formatting.
+pub fn execute(manifest_path: &str, coordinate: &str, configuration: &str, platform: bool) -> Result<()> {
+    let (module, spec) = parse_coordinate(coordinate, configuration, platform)?;
+    config::validate_dependency(&module, &spec)?;
+
+    // Surface a missing or invalid manifest the same way the other commands do
+    config::read_config(manifest_path)?;
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("failed to read `{}`", manifest_path))?;
+
+    let (updated, replaced) = add_dependency(&content, &module, &spec)
+        .with_context(|| format!("failed to update `{}`", manifest_path))?;
+    fs::write(manifest_path, updated)
+        .with_context(|| format!("failed to write `{}`", manifest_path))?;
+
+    let verb = if replaced { "Updated" } else { "Added" };
+    println!(
+        "{:>12} {} ({})",
+        verb.green().bold(),
+        coordinate,
+        if platform { format!("{}, platform", configuration) } else { configuration.to_string() }
+    );
+    Ok(())
+}
+
+/// Split `group:artifact[:version]` into the `[dependencies]` key and value
+fn parse_coordinate(coordinate: &str, configuration: &str, platform: bool) -> Result<(String, DependencySpec)> {
+    let (module, version) = match coordinate.split(':').collect::<Vec<_>>().as_slice() {
+        [group, artifact] => (format!("{}:{}", group, artifact), None),
+        [group, artifact, version] => (format!("{}:{}", group, artifact), Some(version.to_string())),
+        _ => anyhow::bail!(
+            "Invalid coordinate '{}'. Expected group:artifact[:version] (e.g., io.coil-kt:coil-svg:2.6.0)",
+            coordinate
+        ),
+    };
+
+    // Plain `"group:artifact" = "version"` unless something else needs saying
+    let spec = match version {
+        Some(version) if configuration == "implementation" && !platform => DependencySpec::Version(version),
+        version => DependencySpec::Detailed(DetailedDependency {
+            version,
+            configuration: (configuration != "implementation").then(|| configuration.to_string()),
+            platform,
+        }),
+    };
+    Ok((module, spec))
+}
+
+/// Insert or replace `module` in the `[dependencies]` table of `content`
+///
+/// Returns the new document and whether the dependency was already declared.
+fn add_dependency(content: &str, module: &str, spec: &DependencySpec) -> Result<(String, bool)> {
+    let mut doc: DocumentMut = content.parse()?;
+
+    let dependencies = doc.entry("dependencies").or_insert_with(|| {
+        let mut table = Table::new();
+        table.decor_mut().set_prefix("\n");
+        Item::Table(table)
+    });
+    let dependencies = dependencies
+        .as_table_like_mut()
+        .context("`dependencies` is not a table")?;
+
+    let replaced = dependencies.contains_key(module);
+    dependencies.insert(module, dependency_item(spec));
+    Ok((doc.to_string(), replaced))
+}
+
+fn dependency_item(spec: &DependencySpec) -> Item {
+    match spec {
+        DependencySpec::Version(version) => value(version.as_str()),
+        DependencySpec::Detailed(detailed) => {
+            let mut table = InlineTable::new();
+            if let Some(version) = &detailed.version {
+                table.insert("version", version.as_str().into());
+            }
+            if let Some(configuration) = &detailed.configuration {
+                table.insert("configuration", configuration.as_str().into());
+            }
+            if detailed.platform {
+                table.insert("platform", true.into());
+            }
+            table.fmt();
+            value(table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"[project]
+name = "app"
+version = "0.1.0"
+
+# Keep in sync with the Play Store listing
+[android]
+min_sdk = 24
+target_sdk = 34
+package = "com.example.app"
+"#;
+
+    fn add(content: &str, coordinate: &str, configuration: &str, platform: bool) -> (String, bool) {
+        let (module, spec) = parse_coordinate(coordinate, configuration, platform).unwrap();
+        add_dependency(content, &module, &spec).unwrap()
+    }
+
+    #[test]
+    fn test_add_creates_dependencies_table() {
+        let (content, replaced) = add(MANIFEST, "io.coil-kt:coil-svg:2.6.0", "implementation", false);
+        assert!(!replaced);
+        assert_eq!(
+            content,
+            format!("{}\n[dependencies]\n\"io.coil-kt:coil-svg\" = \"2.6.0\"\n", MANIFEST)
+        );
+    }
+
+    #[test]
+    fn test_add_detailed_and_replace() {
+        let (content, _) = add(MANIFEST, "com.google.firebase:firebase-bom:33.5.1", "implementation", true);
+        let (content, _) = add(&content, "junit:junit:4.13.1", "testImplementation", false);
+        let (content, replaced) = add(&content, "junit:junit:4.13.2", "testImplementation", false);
+        let (content, _) = add(&content, "com.google.firebase:firebase-analytics", "implementation", false);
+        assert!(replaced);
+
+        // Comments and existing tables are untouched
+        assert!(content.starts_with(MANIFEST));
+        assert!(content.contains("\"com.google.firebase:firebase-bom\" = { version = \"33.5.1\", platform = true }\n"));
+        assert!(content.contains("\"junit:junit\" = { version = \"4.13.2\", configuration = \"testImplementation\" }\n"));
+        assert!(content.contains("\"com.google.firebase:firebase-analytics\" = {}\n"));
+        assert!(!content.contains("4.13.1"));
+
+        // The result reads back as the same dependencies
+        let config: config::Config = toml::from_str(&content).unwrap();
+        assert_eq!(config.dependencies.len(), 3);
+        assert!(config.dependencies["com.google.firebase:firebase-bom"].platform());
+        assert_eq!(config.dependencies["junit:junit"].configuration(), "testImplementation");
+        assert_eq!(config.dependencies["com.google.firebase:firebase-analytics"].version(), None);
+    }
+
+    #[test]
+    fn test_parse_coordinate_invalid() {
+        assert!(parse_coordinate("coil-svg", "implementation", false).is_err());
+        assert!(parse_coordinate("a:b:c:d", "implementation", false).is_err());
+    }
+}
@@ -12,17 +12,17 @@ use crate::build_pipeline;
 use crate::config;
 use crate::keyboard::{self, KeyAction, RawModeGuard};
 use crate::single_file;
-use crate::commands::{detect_target, Target, build_with_gradle};
+use crate::commands::{detect_target, resolve_ffi_abi, Target, build_with_gradle};
 use crate::toolchain::Toolchain;
 
-pub fn execute(target: &str, watch: bool, release: bool) -> Result<()> {
+pub fn execute(target: &str, watch: bool, release: bool, abi: Option<&str>) -> Result<()> {
     // Detect if we're building a project or single file
     match detect_target(target) {
         Target::Project(manifest_path) => {
             if watch {
-                execute_project_watch(&manifest_path, release)
+                execute_project_watch(&manifest_path, release, abi)
             } else {
-                execute_project(&manifest_path, release)
+                execute_project(&manifest_path, release, abi)
             }
         }
         Target::SingleFile(file_path) => {
@@ -111,7 +111,7 @@ fn execute_single_file(file_path: &str, release: bool) -> Result<()> {
 }
 
 /// Build a project (existing behavior)
-fn execute_project(manifest_path: &str, release: bool) -> Result<()> {
+fn execute_project(manifest_path: &str, release: bool, abi: Option<&str>) -> Result<()> {
     let start = Instant::now();
 
     // 1. Determine project directory from manifest path
@@ -143,7 +143,8 @@ fn execute_project(manifest_path: &str, release: bool) -> Result<()> {
 
     // 2. Load configuration
     let manifest_file = manifest_path.file_name().unwrap().to_str().unwrap();
-    let config = config::load_config(manifest_file)?;
+    let mut config = config::load_config(manifest_file)?;
+    config.ffi.abi = resolve_ffi_abi(abi, None)?;
 
     // 3. Run build pipeline (with clean)
     let result = build_pipeline::execute_build(&config, true)?;
@@ -322,7 +323,7 @@ fn run_single_file_build_watch(file_path: &Path, original_dir: &Path) -> Result<
 
 /// Watch a project for changes and rebuild
 /// Note: Watch mode always uses debug builds for fast iteration
-fn execute_project_watch(manifest_path: &str, _release: bool) -> Result<()> {
+fn execute_project_watch(manifest_path: &str, _release: bool, abi: Option<&str>) -> Result<()> {
     // Determine project directory from manifest path
     let manifest_path = Path::new(manifest_path);
     let original_dir = env::current_dir()?;
@@ -352,7 +353,8 @@ fn execute_project_watch(manifest_path: &str, _release: bool) -> Result<()> {
 
     // Load configuration
     let manifest_file = manifest_path.file_name().unwrap().to_str().unwrap();
-    let config = config::load_config(manifest_file)?;
+    let mut config = config::load_config(manifest_file)?;
+    config.ffi.abi = resolve_ffi_abi(abi, None)?;
 
     // Initial build
     let start = Instant::now();
@@ -71,6 +71,22 @@ pub fn get_devices(toolchain: &Toolchain) -> Result<Vec<DeviceInfo>> {
     Ok(devices)
 }
 
+/// Primary ABI of a connected device, e.g. `arm64-v8a`
+pub fn get_device_abi(toolchain: &Toolchain, device_id: &str) -> Result<String> {
+    let output = toolchain
+        .adb_cmd()?
+        .args(["-s", device_id, "shell", "getprop", "ro.product.cpu.abi"])
+        .output()
+        .context("Failed to query device ABI")?;
+
+    let abi = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if abi.is_empty() {
+        anyhow::bail!("Could not determine the ABI of device {}", device_id);
+    }
+
+    Ok(abi)
+}
+
 /// Find device by partial ID match (matches short_id prefix, id prefix, or id substring)
 pub fn find_device<'a>(devices: &'a [DeviceInfo], query: &str) -> Result<&'a DeviceInfo> {
     let query_lower = query.to_lowercase();
@@ -1,186 +1,435 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
-use std::path::Path;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::android_scaffold::{self, ScaffoldFeatures};
+use crate::commands;
+use crate::config::{self, Config};
+use crate::toolchain::{self, ci, Toolchain};
+
+/// Files `android_scaffold::generate` writes into the output directory
+const SCAFFOLD_FILES: &[&str] = &[
+    "build.gradle.kts",
+    "settings.gradle.kts",
+    "gradle.properties",
+    "app/build.gradle.kts",
+    "app/proguard-rules.pro",
+    "app/src/main/AndroidManifest.xml",
+];
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    /// Worth knowing, never a problem (e.g. not in a project)
+    Info,
+    Warning,
+    Error,
+}
+
+/// Remedy for a failed check, in the order `--fix` applies them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FixAction {
+    InstallToolchain,
+    AcceptLicenses,
+    CreateAvd,
+    RegenerateScaffold,
+    FreeDiskSpace,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Fix {
+    pub action: FixAction,
+    /// Command that applies the fix by hand
+    pub command: String,
+    /// Whether `doctor --fix` applies it (only safe fixes are automatic)
+    pub automatic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    /// Stable identifier for dashboards, e.g. `java` or `sdk-licenses`
+    pub id: &'static str,
+    pub status: Status,
+    pub summary: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+}
 
-use crate::config;
-use crate::toolchain::Toolchain;
+impl Check {
+    fn new(id: &'static str, status: Status, summary: impl Into<String>) -> Self {
+        Self { id, status, summary: summary.into(), details: Vec::new(), fix: None }
+    }
+
+    fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.details.push(detail.into());
+        self
+    }
+
+    fn fix(mut self, action: FixAction, command: impl Into<String>, automatic: bool) -> Self {
+        self.fix = Some(Fix { action, command: command.into(), automatic });
+        self
+    }
+}
+
+/// Everything `whitehall doctor` found, as printed by `--json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub version: &'static str,
+    /// No warnings or errors
+    pub healthy: bool,
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    fn new(checks: Vec<Check>) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            healthy: checks.iter().all(|c| matches!(c.status, Status::Ok | Status::Info)),
+            checks,
+        }
+    }
+
+    /// Automatic fixes for failed checks, each action once
+    pub fn automatic_fixes(&self) -> Vec<FixAction> {
+        let mut actions: Vec<FixAction> = self
+            .checks
+            .iter()
+            .filter(|c| c.status != Status::Ok)
+            .filter_map(|c| c.fix.as_ref())
+            .filter(|fix| fix.automatic)
+            .map(|fix| fix.action)
+            .collect();
+        actions.sort();
+        actions.dedup();
+        actions
+    }
+}
 
 /// Check system health and toolchain status
-pub fn execute(manifest_path: &str) -> Result<()> {
-    println!("{}", "Whitehall System Health Check".cyan().bold());
-    println!("{}", "=".repeat(50));
-    println!();
+///
+/// `json` prints the report for dashboards; `fix` applies the automatic
+/// fixes first and reports the state afterwards. With both, fix progress goes
+/// to stderr so stdout is only the JSON report.
+pub fn execute(manifest_path: &str, json: bool, fix: bool) -> Result<()> {
+    ci::set_stdout_reserved(json);
+    let mut report = run_checks(manifest_path)?;
+
+    if fix {
+        let actions = report.automatic_fixes();
+        if !actions.is_empty() {
+            apply_fixes(manifest_path, &actions);
+            report = run_checks(manifest_path)?;
+        }
+    }
 
-    let mut all_ok = true;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report, Path::new(manifest_path).exists());
+    }
 
-    // 1. Check Whitehall installation
-    print_check("Whitehall installation");
-    let version = env!("CARGO_PKG_VERSION");
-    println!("  {} Version: {}", "✓".green(), version);
-    println!();
+    Ok(())
+}
 
-    // 2. Check if we're in a project (optional)
-    let in_project = Path::new(manifest_path).exists();
+/// Run every check without changing anything
+pub fn run_checks(manifest_path: &str) -> Result<Report> {
+    let mut checks = vec![Check::new(
+        "whitehall",
+        Status::Ok,
+        format!("Whitehall {}", env!("CARGO_PKG_VERSION")),
+    )];
+    let toolchain = Toolchain::new()?;
 
-    if in_project {
-        print_check("Project configuration");
+    if !Path::new(manifest_path).exists() {
+        checks.push(Check::new("project", Status::Info, "Not in a Whitehall project"));
+    } else {
         match config::load_config(manifest_path) {
             Ok(config) => {
-                println!("  {} Project: {} v{}", "✓".green(), config.project.name, config.project.version);
-                println!("  {} Package: {}", "✓".green(), config.android.package);
-                println!("  {} Min SDK: {} | Target SDK: {}",
-                    "✓".green(), config.android.min_sdk, config.android.target_sdk);
-                println!("  {} Toolchain:", "✓".green());
-                println!("    - Java: {}", config.toolchain.java);
-                println!("    - Gradle: {}", config.toolchain.gradle);
-                println!("    - AGP: {}", config.toolchain.agp);
-                println!("    - Kotlin: {}", config.toolchain.kotlin);
-                println!();
-
-                // 3. Check toolchain availability
-                print_check("Toolchain availability");
-                let toolchain = Toolchain::new()?;
-
-                // Check Java
-                match toolchain.ensure_java(&config.toolchain.java) {
-                    Ok(java_home) => {
-                        println!("  {} Java {} installed at {}",
-                            "✓".green(), config.toolchain.java, java_home.display());
-                    }
-                    Err(e) => {
-                        println!("  {} Java {} not available: {}",
-                            "✗".red(), config.toolchain.java, e);
-                        all_ok = false;
-                    }
-                }
-
-                // Check Gradle
-                match toolchain.ensure_gradle(&config.toolchain.gradle) {
-                    Ok(gradle_bin) => {
-                        println!("  {} Gradle {} installed at {}",
-                            "✓".green(), config.toolchain.gradle, gradle_bin.display());
-                    }
-                    Err(e) => {
-                        println!("  {} Gradle {} not available: {}",
-                            "✗".red(), config.toolchain.gradle, e);
-                        all_ok = false;
-                    }
-                }
-
-                // Check Android SDK
-                let sdk_root = match toolchain.ensure_android_sdk() {
-                    Ok(sdk_root) => {
-                        println!("  {} Android SDK installed at {}",
-                            "✓".green(), sdk_root.display());
-                        Some(sdk_root)
-                    }
-                    Err(e) => {
-                        println!("  {} Android SDK not available: {}",
-                            "✗".red(), e);
-                        all_ok = false;
-                        None
-                    }
-                };
-
-                // Check Android Emulator (only if SDK is available)
-                if let Some(ref sdk) = sdk_root {
-                    let emulator_path = sdk.join("emulator/emulator");
-                    if emulator_path.exists() {
-                        println!("  {} Android Emulator installed",
-                            "✓".green());
-                    } else {
-                        println!("  {} Android Emulator not installed",
-                            "✗".red());
-                        println!("    Run 'sdkmanager emulator' to install");
-                        all_ok = false;
-                    }
-
-                    // Check System Image for target SDK
-                    let target_sdk = config.android.target_sdk;
-                    match Toolchain::get_system_image_package(target_sdk) {
-                        Ok(package) => {
-                            // Parse package to get path: system-images;android-34;google_apis_playstore;x86_64
-                            let parts: Vec<&str> = package.split(';').collect();
-                            if parts.len() == 4 {
-                                let image_path = sdk
-                                    .join("system-images")
-                                    .join(parts[1])
-                                    .join(parts[2])
-                                    .join(parts[3]);
-
-                                if image_path.exists() && image_path.join("system.img").exists() {
-                                    println!("  {} System image for Android {} installed",
-                                        "✓".green(), target_sdk);
-                                } else {
-                                    println!("  {} System image for Android {} not installed",
-                                        "✗".red(), target_sdk);
-                                    println!("    Package: {}", package);
-                                    println!("    Run 'whitehall toolchain install' to install");
-                                    all_ok = false;
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            println!("  {} Could not determine system image: {}",
-                                "⚠".yellow(), e);
-                        }
-                    }
-                }
-                println!();
+                checks.push(
+                    Check::new(
+                        "project",
+                        Status::Ok,
+                        format!("Project: {} v{}", config.project.name, config.project.version),
+                    )
+                    .detail(format!("Package: {}", config.android.package))
+                    .detail(format!(
+                        "Min SDK: {} | Target SDK: {}",
+                        config.android.min_sdk, config.android.target_sdk
+                    ))
+                    .detail(format!(
+                        "Toolchain: Java {}, Gradle {}, AGP {}, Kotlin {}",
+                        config.toolchain.java,
+                        config.toolchain.gradle,
+                        config.toolchain.agp,
+                        config.toolchain.kotlin
+                    )),
+                );
+                check_toolchain(&toolchain, &config, &mut checks);
+                checks.push(check_scaffold(manifest_path, &config));
             }
             Err(e) => {
-                println!("  {} Failed to load project config: {}", "✗".red(), e);
-                all_ok = false;
-                println!();
+                checks.push(
+                    Check::new("project", Status::Error, "Failed to load project config").detail(e.to_string()),
+                );
             }
         }
+    }
+
+    checks.push(check_storage(&toolchain));
+    checks.push(check_disk_space(toolchain.root()));
+
+    Ok(Report::new(checks))
+}
+
+fn install_fix(check: Check) -> Check {
+    check.fix(FixAction::InstallToolchain, "whitehall toolchain install", true)
+}
+
+fn check_toolchain(toolchain: &Toolchain, config: &Config, checks: &mut Vec<Check>) {
+    // Java
+    let java = &config.toolchain.java;
+    let java_bin = if cfg!(target_os = "macos") { "Contents/Home/bin/java" } else { "bin/java" };
+    checks.push(match toolchain.installed(&format!("java/{}", java)) {
+        Some(home) if home.join(java_bin).exists() => {
+            Check::new("java", Status::Ok, format!("Java {} installed at {}", java, home.display()))
+        }
+        Some(home) => install_fix(Check::new("java", Status::Error, format!("Java {} installation corrupt", java)))
+            .detail(format!("Binary not found at {}", home.join(java_bin).display())),
+        None => install_fix(Check::new("java", Status::Error, format!("Java {} not installed", java))),
+    });
+
+    // Gradle
+    let gradle = &config.toolchain.gradle;
+    checks.push(match toolchain.installed(&format!("gradle/{}/bin/gradle", gradle)) {
+        Some(bin) => Check::new("gradle", Status::Ok, format!("Gradle {} installed at {}", gradle, bin.display())),
+        None => install_fix(Check::new("gradle", Status::Error, format!("Gradle {} not installed", gradle))),
+    });
+
+    // Android SDK; everything below needs it
+    let Some(sdk) = toolchain.android_sdk() else {
+        checks.push(install_fix(Check::new("android-sdk", Status::Error, "Android SDK not installed")));
+        return;
+    };
+    checks.push(Check::new(
+        "android-sdk",
+        Status::Ok,
+        format!("Android SDK installed at {}", sdk.display()),
+    ));
+
+    checks.push(if sdk.join("licenses/android-sdk-license").exists() {
+        Check::new("sdk-licenses", Status::Ok, "Android SDK licenses accepted")
     } else {
-        println!("{}", "  (Not in a Whitehall project)".dimmed());
-        println!();
+        let sdkmanager = sdk.join("cmdline-tools/latest/bin/sdkmanager");
+        Check::new("sdk-licenses", Status::Error, "Android SDK licenses not accepted")
+            .detail("Review them before accepting; in CI pass --accept-licenses")
+            .fix(FixAction::AcceptLicenses, format!("{} --licenses", sdkmanager.display()), ci::accept_licenses())
+    });
+
+    let has_emulator = sdk.join("emulator/emulator").exists();
+    checks.push(if has_emulator {
+        Check::new("emulator", Status::Ok, "Android Emulator installed")
+    } else {
+        install_fix(Check::new("emulator", Status::Warning, "Android Emulator not installed"))
+    });
+
+    // System image for the target SDK
+    let target_sdk = config.android.target_sdk;
+    match Toolchain::get_system_image_package(target_sdk) {
+        Ok(package) => {
+            // system-images;android-34;google_apis_playstore;x86_64
+            let image = toolchain.installed(&format!("android/{}", package.replace(';', "/")));
+            checks.push(if image.is_some_and(|path| path.join("system.img").exists()) {
+                Check::new("system-image", Status::Ok, format!("System image for Android {} installed", target_sdk))
+            } else {
+                install_fix(Check::new(
+                    "system-image",
+                    Status::Warning,
+                    format!("System image for Android {} not installed", target_sdk),
+                ))
+                .detail(format!("Package: {}", package))
+            });
+        }
+        Err(e) => {
+            checks.push(
+                Check::new("system-image", Status::Warning, "Could not determine system image").detail(e.to_string()),
+            );
+        }
     }
 
-    // 4. Check toolchain storage
-    print_check("Toolchain storage");
-    let toolchain = Toolchain::new()?;
+    // Emulators (AVDs) to run the app on
+    let avds = list_avds();
+    checks.push(if !avds.is_empty() {
+        Check::new("avd", Status::Ok, format!("{} emulator(s) configured", avds.len())).detail(avds.join(", "))
+    } else {
+        Check::new("avd", Status::Warning, "No emulators configured").fix(
+            FixAction::CreateAvd,
+            "whitehall emulator create",
+            has_emulator,
+        )
+    });
+
+    // Connected devices
+    checks.push(match commands::device::get_devices(toolchain) {
+        Ok(devices) if devices.is_empty() => Check::new("devices", Status::Info, "No devices connected"),
+        Ok(devices) => devices.iter().fold(
+            Check::new("devices", Status::Ok, format!("{} device(s) connected", devices.len())),
+            |check, device| check.detail(device.display_name()),
+        ),
+        Err(e) => Check::new("devices", Status::Warning, "Could not list devices").detail(e.to_string()),
+    });
+}
+
+/// AVD names from the emulator's AVD directory
+fn list_avds() -> Vec<String> {
+    let avd_home = std::env::var_os("ANDROID_AVD_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".android/avd")));
+    let Some(entries) = avd_home.and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+
+    let mut avds: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ini"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect();
+    avds.sort();
+    avds
+}
+
+/// Output directory of the project, next to its whitehall.toml
+fn output_dir(manifest_path: &str, config: &Config) -> PathBuf {
+    Path::new(manifest_path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(&config.build.output_dir)
+}
+
+fn check_scaffold(manifest_path: &str, config: &Config) -> Check {
+    let output_dir = output_dir(manifest_path, config);
+    let missing: Vec<&str> = SCAFFOLD_FILES
+        .iter()
+        .copied()
+        .filter(|file| !output_dir.join(file).exists())
+        .collect();
+
+    if missing.len() == SCAFFOLD_FILES.len() {
+        return Check::new("scaffold", Status::Info, "Android project not generated yet (run 'whitehall build')");
+    }
+    if missing.is_empty() {
+        return Check::new("scaffold", Status::Ok, format!("Android project in {}", output_dir.display()));
+    }
+
+    missing.iter().fold(
+        Check::new("scaffold", Status::Error, format!("Android project in {} is incomplete", output_dir.display()))
+            .fix(FixAction::RegenerateScaffold, "whitehall clean && whitehall build", true),
+        |check, file| check.detail(format!("Missing {}", file)),
+    )
+}
+
+fn check_storage(toolchain: &Toolchain) -> Check {
     let root = toolchain.root();
+    let versions = toolchain::usage::installed_versions(root);
+    let count = |prefix: &str| versions.iter().filter(|v| v.starts_with(prefix)).count();
+
+    let mut check = Check::new(
+        "toolchain-storage",
+        Status::Info,
+        format!("Toolchains in {} ({})", root.display(), human_size(toolchain::dir_size(root))),
+    )
+    .detail(format!("Java: {} version(s)", count("java/")))
+    .detail(format!("Gradle: {} version(s)", count("gradle/")))
+    .detail(format!("Android SDK: {}", if root.join("android").exists() { "Yes" } else { "No" }));
+
+    if let Some(system_root) = toolchain.system_root() {
+        check = check.detail(format!("Shared (read-only): {}", system_root.display()));
+    }
+    check
+}
 
-    if root.exists() {
-        let size = get_dir_size(root)?;
-        println!("  {} Location: {}", "✓".green(), root.display());
-        println!("  {} Size: {}", "✓".green(), human_size(size));
+fn check_disk_space(root: &Path) -> Check {
+    match get_available_space(root) {
+        Ok(available) if available < 1_000_000_000 => {
+            // Less than 1GB
+            Check::new("disk-space", Status::Warning, format!("Low disk space: {} available", human_size(available)))
+                .fix(FixAction::FreeDiskSpace, "whitehall toolchain gc", false)
+        }
+        Ok(available) => Check::new("disk-space", Status::Ok, format!("Available: {}", human_size(available))),
+        Err(_) => Check::new("disk-space", Status::Info, "Could not determine available space"),
+    }
+}
 
-        // List installed components
-        let java_count = count_versions(&root.join("java"))?;
-        let gradle_count = count_versions(&root.join("gradle"))?;
-        let has_sdk = root.join("android").exists();
+/// Apply fixes in order, carrying on past failures (the re-check reports them)
+fn apply_fixes(manifest_path: &str, actions: &[FixAction]) {
+    for &action in actions {
+        if let Err(e) = apply_fix(manifest_path, action) {
+            eprintln!("{} {:?}: {:#}", "Fix failed".red().bold(), action, e);
+        }
+    }
+}
 
-        println!("  {} Installed components:", "✓".green());
-        println!("    - Java: {} version(s)", java_count);
-        println!("    - Gradle: {} version(s)", gradle_count);
-        println!("    - Android SDK: {}", if has_sdk { "Yes" } else { "No" });
-    } else {
-        println!("  {} No toolchains installed yet", "ℹ".yellow());
-        println!("    Run 'whitehall toolchain install' in a project to download");
+fn apply_fix(manifest_path: &str, action: FixAction) -> Result<()> {
+    match action {
+        FixAction::InstallToolchain => {
+            // Install without `toolchain install`'s lockfile handling: doctor never
+            // creates whitehall.lock (an existing one is still checked, as for builds)
+            let config = config::load_config(manifest_path)?;
+            let toolchain = Toolchain::new()?;
+            toolchain.ensure_all_with_emulator(
+                &config.toolchain.java,
+                &config.toolchain.gradle,
+                config.android.target_sdk,
+            )?;
+            toolchain.ensure_emulator()?;
+        }
+        FixAction::AcceptLicenses => Toolchain::new()?.accept_licenses()?,
+        FixAction::CreateAvd => commands::emulator::execute_create(manifest_path, None)?,
+        FixAction::RegenerateScaffold => {
+            let config = config::load_config(manifest_path)?;
+            // The next build re-syncs app/build.gradle.kts with the features it detects
+            android_scaffold::generate(&config, &output_dir(manifest_path, &config), &ScaffoldFeatures::default())
+                .context("Failed to regenerate Android project scaffold")?;
+            ci::status(format!("  {} Regenerated Android project scaffold", "✓".green()));
+        }
+        FixAction::FreeDiskSpace => anyhow::bail!("Not applied automatically; run 'whitehall toolchain gc'"),
     }
-    println!();
+    Ok(())
+}
 
-    // 5. Check disk space
-    print_check("Disk space");
-    if let Ok(available) = get_available_space(root) {
-        println!("  {} Available: {}", "✓".green(), human_size(available));
+fn print_report(report: &Report, in_project: bool) {
+    println!("{}", "Whitehall System Health Check".cyan().bold());
+    println!("{}", "=".repeat(50));
+    println!();
 
-        if available < 1_000_000_000 {  // Less than 1GB
-            println!("  {} Warning: Low disk space", "⚠".yellow());
-            all_ok = false;
+    for check in &report.checks {
+        let symbol = match check.status {
+            Status::Ok => "✓".green(),
+            Status::Info => "ℹ".yellow(),
+            Status::Warning => "⚠".yellow(),
+            Status::Error => "✗".red(),
+        };
+        println!("  {} {}", symbol, check.summary);
+        for detail in &check.details {
+            println!("    - {}", detail);
+        }
+        if check.status != Status::Ok {
+            if let Some(fix) = &check.fix {
+                println!("    {} {}", "Fix:".cyan(), fix.command);
+            }
         }
-    } else {
-        println!("  {} Could not determine available space", "⚠".yellow());
     }
     println!();
 
     // Summary
     println!("{}", "=".repeat(50));
-    if all_ok {
+    if report.healthy {
         println!("{} All checks passed!", "✓".green().bold());
         if !in_project {
             println!();
@@ -191,45 +440,13 @@ pub fn execute(manifest_path: &str) -> Result<()> {
         }
     } else {
         println!("{} Some issues detected", "⚠".yellow().bold());
-        println!();
-        println!("To install missing toolchains:");
-        println!("  whitehall toolchain install");
-    }
-
-    Ok(())
-}
-
-fn print_check(name: &str) {
-    println!("{} {}...", "Checking".cyan().bold(), name);
-}
-
-fn get_dir_size(path: &Path) -> Result<u64> {
-    let mut size = 0;
-    if path.is_dir() {
-        for entry in std::fs::read_dir(path)? {
-            let entry = entry?;
-            let metadata = entry.metadata()?;
-            if metadata.is_dir() {
-                size += get_dir_size(&entry.path())?;
-            } else {
-                size += metadata.len();
-            }
+        let fixes = report.automatic_fixes().len();
+        if fixes > 0 {
+            println!();
+            println!("Apply {} automatic fix(es) with:", fixes);
+            println!("  whitehall doctor --fix");
         }
     }
-    Ok(size)
-}
-
-fn count_versions(dir: &Path) -> Result<usize> {
-    if !dir.exists() {
-        return Ok(0);
-    }
-
-    let count = std::fs::read_dir(dir)?
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().is_dir())
-        .count();
-
-    Ok(count)
 }
 
 fn get_available_space(path: &Path) -> Result<u64> {
@@ -268,3 +485,41 @@ fn human_size(bytes: u64) -> String {
 
     format!("{:.1} {}", size, UNITS[unit_index])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_fixes_and_json() {
+        let report = Report::new(vec![
+            Check::new("whitehall", Status::Ok, "Whitehall"),
+            install_fix(Check::new("java", Status::Error, "Java 21 not installed")),
+            install_fix(Check::new("gradle", Status::Error, "Gradle 8.4 not installed")),
+            Check::new("avd", Status::Warning, "No emulators configured").fix(
+                FixAction::CreateAvd,
+                "whitehall emulator create",
+                true,
+            ),
+            Check::new("disk-space", Status::Warning, "Low disk space").fix(
+                FixAction::FreeDiskSpace,
+                "whitehall toolchain gc",
+                false,
+            ),
+        ]);
+
+        assert!(!report.healthy);
+        // Deduplicated, ordered, manual fixes left out
+        assert_eq!(report.automatic_fixes(), vec![FixAction::InstallToolchain, FixAction::CreateAvd]);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][1]["id"], "java");
+        assert_eq!(json["checks"][1]["status"], "error");
+        assert_eq!(json["checks"][1]["fix"]["action"], "install-toolchain");
+        assert_eq!(json["checks"][1]["fix"]["command"], "whitehall toolchain install");
+        assert!(json["checks"][0].get("fix").is_none());
+
+        let healthy = Report::new(vec![Check::new("project", Status::Info, "Not in a Whitehall project")]);
+        assert!(healthy.healthy);
+    }
+}
@@ -3,7 +3,7 @@ use colored::Colorize;
 use std::collections::HashMap;
 
 use crate::config;
-use crate::toolchain::Toolchain;
+use crate::toolchain::{ci, Toolchain};
 
 /// AVD info with short ID and status
 struct AvdInfo {
@@ -281,9 +281,9 @@ pub fn execute_create(manifest_path: &str, name: Option<&str>) -> Result<()> {
     let target_sdk = config.android.target_sdk;
     let system_image = format!("system-images;android-{};google_apis_playstore;x86_64", target_sdk);
 
-    println!("{} emulator '{}'", "Creating".green().bold(), avd_name);
-    println!("  Target SDK: {}", target_sdk);
-    println!("  System image: {}", system_image);
+    ci::status(format!("{} emulator '{}'", "Creating".green().bold(), avd_name));
+    ci::status(format!("  Target SDK: {}", target_sdk));
+    ci::status(format!("  System image: {}", system_image));
 
     // Set JAVA_HOME for avdmanager
     let java_home = toolchain.ensure_java(&config.toolchain.java)?;
@@ -299,6 +299,7 @@ pub fn execute_create(manifest_path: &str, name: Option<&str>) -> Result<()> {
             "--device", "pixel_6",
             "--force",
         ])
+        .stdout(ci::child_stdout())
         .status()
         .context("Failed to create AVD")?;
 
@@ -309,11 +310,11 @@ pub fn execute_create(manifest_path: &str, name: Option<&str>) -> Result<()> {
     // Get the short ID for the new AVD
     let short_id = generate_short_id(avd_name);
 
-    println!();
-    println!("{} Created emulator '{}' ({})", "✓".green().bold(), avd_name, short_id.yellow());
-    println!();
-    println!("Start it with:");
-    println!("  whitehall emulator start {}", short_id);
+    ci::status("");
+    ci::status(format!("{} Created emulator '{}' ({})", "✓".green().bold(), avd_name, short_id.yellow()));
+    ci::status("");
+    ci::status("Start it with:");
+    ci::status(format!("  whitehall emulator start {}", short_id));
 
     Ok(())
 }
@@ -1,4 +1,5 @@
 pub mod init;
+pub mod add;
 pub mod build;
 pub mod watch;
 pub mod run;
@@ -10,6 +11,7 @@ pub mod device;
 pub mod doctor;
 pub mod clean;
 pub mod check;
+pub mod routes;
 
 use anyhow::{Context, Result};
 use indicatif::{ProgressBar, ProgressStyle};
@@ -61,6 +63,32 @@ pub fn detect_target(target: &str) -> Target {
     Target::Project(target.to_string())
 }
 
+/// Resolve `--abi` to the ABI FFI native libraries are built for
+///
+/// A bare `--abi` (parsed as "device") picks the ABI of the connected device,
+/// for dev builds that only need to run there.
+pub fn resolve_ffi_abi(abi: Option<&str>, device_query: Option<&str>) -> Result<Option<String>> {
+    let abi = match abi {
+        None => return Ok(None),
+        Some("device") => {
+            let toolchain = Toolchain::new()?;
+            let device = device::resolve_device(&toolchain, device_query)?;
+            device::get_device_abi(&toolchain, &device.id)?
+        }
+        Some(abi) => abi.to_string(),
+    };
+
+    if !crate::ffi_build::ANDROID_ABIS.iter().any(|(known, _)| *known == abi) {
+        anyhow::bail!(
+            "Unsupported ABI '{}'. Expected one of: {}",
+            abi,
+            crate::ffi_build::ANDROID_ABIS.iter().map(|(known, _)| *known).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    Ok(Some(abi))
+}
+
 /// Build APK with Gradle
 ///
 /// Deletes existing APK first to force gradle to re-package,
@@ -0,0 +1,233 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::env;
+use std::path::Path;
+
+use crate::commands::{detect_target, Target};
+use crate::config;
+use crate::routes::{self, IssueSeverity, Layout, Route, RouteIssue};
+
+/// JSON representation of the route tree (`whitehall routes --json`)
+#[derive(Serialize)]
+struct RoutesReport {
+    routes: Vec<RouteEntry>,
+    layouts: Vec<LayoutEntry>,
+    issues: Vec<IssueEntry>,
+}
+
+#[derive(Serialize)]
+struct RouteEntry {
+    path: String,
+    name: String,
+    screen: String,
+    params: Vec<ParamEntry>,
+    layouts: Vec<String>,
+    source: String,
+}
+
+#[derive(Serialize)]
+struct ParamEntry {
+    name: String,
+    #[serde(rename = "type")]
+    param_type: String,
+}
+
+#[derive(Serialize)]
+struct LayoutEntry {
+    name: String,
+    composable: String,
+    parent: Option<String>,
+    source: String,
+}
+
+#[derive(Serialize)]
+struct IssueEntry {
+    severity: &'static str,
+    kind: &'static str,
+    message: String,
+    source: String,
+}
+
+/// Print and validate the route tree of a project
+pub fn execute(target: &str, json: bool) -> Result<()> {
+    match detect_target(target) {
+        Target::Project(manifest_path) => execute_project(&manifest_path, json),
+        Target::SingleFile(_) => {
+            anyhow::bail!("Routes are only available in project mode (src/routes/)")
+        }
+    }
+}
+
+fn execute_project(manifest_path: &str, json: bool) -> Result<()> {
+    let manifest_path = Path::new(manifest_path);
+    let original_dir = env::current_dir()?;
+
+    let project_dir = if manifest_path == Path::new("whitehall.toml") {
+        original_dir.clone()
+    } else {
+        let dir = manifest_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .to_path_buf();
+
+        if dir.is_relative() {
+            original_dir.join(dir)
+        } else {
+            dir
+        }
+    };
+
+    // Change to project directory if needed
+    if project_dir != original_dir {
+        env::set_current_dir(&project_dir)?;
+    }
+
+    // Load configuration (validates we're in a project)
+    let manifest_file = manifest_path.file_name().unwrap().to_str().unwrap();
+    let discovered = config::load_config(manifest_file).and_then(|_| {
+        let layouts = routes::discover_layouts()?;
+        let routes = routes::discover_routes_with_layouts(&layouts)?;
+        Ok((routes, layouts))
+    });
+
+    // Restore original directory if we changed it
+    if project_dir != original_dir {
+        env::set_current_dir(&original_dir)?;
+    }
+
+    let (routes, layouts) = discovered?;
+    let issues = routes::validate_routes(&routes, &layouts);
+
+    if json {
+        let report = build_report(&routes, &layouts, &issues);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_tree(&routes, &layouts);
+        print_issues(&issues);
+    }
+
+    let error_count = issues
+        .iter()
+        .filter(|i| i.severity == IssueSeverity::Error)
+        .count();
+    if error_count > 0 {
+        anyhow::bail!("Route validation failed with {} error(s)", error_count);
+    }
+
+    Ok(())
+}
+
+fn print_tree(routes: &[Route], layouts: &[Layout]) {
+    if routes.is_empty() {
+        println!("{} No routes found in src/routes/", "info:".blue().bold());
+        return;
+    }
+
+    println!("{} ({})", "Routes".cyan().bold(), routes.len());
+    for route in routes {
+        println!();
+        println!("  {}", route.path.bold());
+        println!("    {:<8} {} {}",
+            "screen".dimmed(),
+            route.screen_name,
+            format!("({})", route.source_path.display()).dimmed()
+        );
+        if !route.params.is_empty() {
+            let params = route
+                .params
+                .iter()
+                .map(|p| format!("{}: {}", p.name, p.param_type))
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!("    {:<8} {}", "params".dimmed(), params);
+        }
+        let chain = if route.layouts.is_empty() {
+            "(none)".dimmed().to_string()
+        } else {
+            route.layouts.join(" > ")
+        };
+        println!("    {:<8} {}", "layouts".dimmed(), chain);
+    }
+
+    if !layouts.is_empty() {
+        println!();
+        println!("{} ({})", "Layouts".cyan().bold(), layouts.len());
+        for layout in layouts {
+            let parent = layout
+                .parent
+                .as_deref()
+                .map(|p| format!(" < {}Layout", p))
+                .unwrap_or_default();
+            println!("  {}{} {}",
+                layout.composable_name,
+                parent,
+                format!("({})", layout.source_path.display()).dimmed()
+            );
+        }
+    }
+}
+
+/// Issues go to stderr, so the tree on stdout stays clean when piped
+fn print_issues(issues: &[RouteIssue]) {
+    if issues.is_empty() {
+        return;
+    }
+
+    eprintln!();
+    for issue in issues {
+        let label = match issue.severity {
+            IssueSeverity::Error => "error:".red().bold(),
+            IssueSeverity::Warning => "warning:".yellow().bold(),
+        };
+        eprintln!("{} {} {}",
+            label,
+            issue.message,
+            format!("({})", issue.source_path.display()).dimmed()
+        );
+    }
+}
+
+fn build_report(routes: &[Route], layouts: &[Layout], issues: &[RouteIssue]) -> RoutesReport {
+    RoutesReport {
+        routes: routes
+            .iter()
+            .map(|r| RouteEntry {
+                path: r.path.clone(),
+                name: r.name.clone(),
+                screen: r.screen_name.clone(),
+                params: r
+                    .params
+                    .iter()
+                    .map(|p| ParamEntry {
+                        name: p.name.clone(),
+                        param_type: p.param_type.clone(),
+                    })
+                    .collect(),
+                layouts: r.layouts.clone(),
+                source: r.source_path.display().to_string(),
+            })
+            .collect(),
+        layouts: layouts
+            .iter()
+            .map(|l| LayoutEntry {
+                name: l.name.clone(),
+                composable: l.composable_name.clone(),
+                parent: l.parent.clone(),
+                source: l.source_path.display().to_string(),
+            })
+            .collect(),
+        issues: issues
+            .iter()
+            .map(|i| IssueEntry {
+                severity: match i.severity {
+                    IssueSeverity::Error => "error",
+                    IssueSeverity::Warning => "warning",
+                },
+                kind: i.kind,
+                message: i.message.clone(),
+                source: i.source_path.display().to_string(),
+            })
+            .collect(),
+    }
+}
@@ -17,11 +17,11 @@ use crate::build_pipeline;
 use crate::config;
 use crate::keyboard::{self, KeyAction, RawModeGuard};
 use crate::single_file;
-use crate::commands::{detect_target, Target, build_with_gradle};
+use crate::commands::{detect_target, resolve_ffi_abi, Target, build_with_gradle};
 use crate::commands::device;
 use crate::toolchain::Toolchain;
 
-pub fn execute(target: &str, device_query: Option<&str>, watch: bool) -> Result<()> {
+pub fn execute(target: &str, device_query: Option<&str>, watch: bool, abi: Option<&str>) -> Result<()> {
     // Smart argument detection:
     // If target doesn't exist as a file/dir, but whitehall.toml exists in current dir,
     // treat target as device_query instead
@@ -41,9 +41,9 @@ pub fn execute(target: &str, device_query: Option<&str>, watch: bool) -> Result<
     match detect_target(actual_target) {
         Target::Project(manifest_path) => {
             if watch {
-                execute_project_watch(&manifest_path, actual_device)
+                execute_project_watch(&manifest_path, actual_device, abi)
             } else {
-                execute_project(&manifest_path, actual_device)
+                execute_project(&manifest_path, actual_device, abi)
             }
         }
         Target::SingleFile(file_path) => {
@@ -126,7 +126,7 @@ fn execute_single_file(file_path: &str, device_query: Option<&str>) -> Result<()
 }
 
 /// Run a project (existing behavior)
-fn execute_project(manifest_path: &str, device_query: Option<&str>) -> Result<()> {
+fn execute_project(manifest_path: &str, device_query: Option<&str>, abi: Option<&str>) -> Result<()> {
     // 1. Determine project directory from manifest path (same as build command)
     let manifest_path = Path::new(manifest_path);
     let original_dir = env::current_dir()?;
@@ -153,7 +153,8 @@ fn execute_project(manifest_path: &str, device_query: Option<&str>) -> Result<()
 
     // 2. Load configuration
     let manifest_file = manifest_path.file_name().unwrap().to_str().unwrap();
-    let config = config::load_config(manifest_file)?;
+    let mut config = config::load_config(manifest_file)?;
+    config.ffi.abi = resolve_ffi_abi(abi, device_query)?;
 
     // 3. Build project
     let result = build_pipeline::execute_build(&config, false)?;
@@ -627,7 +628,7 @@ fn execute_single_file_watch(file_path: &str, device_query: Option<&str>) -> Res
 }
 
 /// Watch and run a project
-fn execute_project_watch(manifest_path: &str, device_query: Option<&str>) -> Result<()> {
+fn execute_project_watch(manifest_path: &str, device_query: Option<&str>, abi: Option<&str>) -> Result<()> {
     let manifest_path = Path::new(manifest_path);
     let original_dir = env::current_dir()?;
 
@@ -654,7 +655,8 @@ fn execute_project_watch(manifest_path: &str, device_query: Option<&str>) -> Res
 
     // Load configuration
     let manifest_file = manifest_path.file_name().unwrap().to_str().unwrap();
-    let config = config::load_config(manifest_file)?;
+    let mut config = config::load_config(manifest_file)?;
+    config.ffi.abi = resolve_ffi_abi(abi, device_query)?;
 
     // Initialize toolchain
     let toolchain = Toolchain::new()?;
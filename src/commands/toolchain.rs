@@ -1,9 +1,9 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::config;
-use crate::toolchain::Toolchain;
+use crate::toolchain::{self, Toolchain, Usage};
 
 /// Install toolchains required by the current project
 pub fn execute_install(manifest_path: &str) -> Result<()> {
@@ -13,6 +13,10 @@ pub fn execute_install(manifest_path: &str) -> Result<()> {
     // Initialize toolchain manager
     let toolchain = Toolchain::new()?;
 
+    // Verify downloads against whitehall.lock, creating it on first install
+    let lock_path = lock_path(manifest_path);
+    toolchain::set_lockfile(Some(lock_path.clone()));
+
     // Download all toolchains including emulator and system images
     toolchain.ensure_all_with_emulator(
         &config.toolchain.java,
@@ -20,55 +24,84 @@ pub fn execute_install(manifest_path: &str) -> Result<()> {
         config.android.target_sdk
     )?;
 
+    // Lock toolchains that were already installed
+    toolchain.lock_installed(&config.toolchain.java, &config.toolchain.gradle, config.android.target_sdk)?;
+
+    println!("     {} {}", "Locked".green().bold(), lock_path.display());
+
     Ok(())
 }
 
-/// List installed toolchains
+/// Reinstall the project's toolchains and rewrite whitehall.lock from scratch
+pub fn execute_update(manifest_path: &str) -> Result<()> {
+    let config = config::load_config(manifest_path)?;
+    let toolchain = Toolchain::new()?;
+
+    let lock_path = lock_path(manifest_path);
+    if lock_path.exists() {
+        std::fs::remove_file(&lock_path)
+            .with_context(|| format!("Failed to remove {}", lock_path.display()))?;
+    }
+
+    toolchain.refresh(&config.toolchain.java, &config.toolchain.gradle)?;
+
+    execute_install(manifest_path)
+}
+
+/// whitehall.lock next to the given whitehall.toml
+fn lock_path(manifest_path: &str) -> PathBuf {
+    Path::new(manifest_path).with_file_name(toolchain::LOCKFILE)
+}
+
+/// List installed toolchains with their size and last use
 pub fn execute_list() -> Result<()> {
     let toolchain = Toolchain::new()?;
     let root = toolchain.root();
+    let usage = Usage::load(root)?;
+    let versions = toolchain::usage::installed_versions(root);
 
     println!("{} toolchains", "Installed".green().bold());
-    println!("Location: {}\n", root.display());
-
-    // List Java versions
-    let java_dir = root.join("java");
-    if java_dir.exists() {
-        println!("{}:", "Java".cyan().bold());
-        for entry in std::fs::read_dir(&java_dir)? {
-            let entry = entry?;
-            if entry.path().is_dir() {
-                let version = entry.file_name().to_string_lossy().to_string();
-                let size = get_dir_size(&entry.path())?;
-                println!("  - {} ({})", version, human_size(size));
-            }
-        }
-    } else {
-        println!("{}: None installed", "Java".cyan().bold());
+    println!("Location: {}", root.display());
+    if let Some(system_root) = toolchain.system_root() {
+        let shared = toolchain::usage::installed_versions(system_root);
+        println!("Shared (read-only): {} ({} versions)", system_root.display(), shared.len());
     }
 
-    // List Gradle versions
-    let gradle_dir = root.join("gradle");
-    if gradle_dir.exists() {
-        println!("\n{}:", "Gradle".cyan().bold());
-        for entry in std::fs::read_dir(&gradle_dir)? {
-            let entry = entry?;
-            if entry.path().is_dir() {
-                let version = entry.file_name().to_string_lossy().to_string();
-                let size = get_dir_size(&entry.path())?;
-                println!("  - {} ({})", version, human_size(size));
-            }
+    for (name, prefix) in [
+        ("Java", "java/"),
+        ("Gradle", "gradle/"),
+        ("CMake", "cmake/"),
+        ("NDK", "android/ndk/"),
+        ("System images", "android/system-images/"),
+    ] {
+        let installed: Vec<&String> = versions.iter().filter(|v| v.starts_with(prefix)).collect();
+        if installed.is_empty() {
+            println!("\n{}: None installed", name.cyan().bold());
+            continue;
+        }
+
+        println!("\n{}:", name.cyan().bold());
+        for version in installed {
+            let size = toolchain::dir_size(&root.join(version));
+            let last_use = match usage.last_used(version) {
+                Some((time, project)) => format!("last used {} by {}", toolchain::usage::describe_age(time), project),
+                None => "no recorded use".to_string(),
+            };
+            println!(
+                "  - {} ({}, {})",
+                &version[prefix.len()..],
+                human_size(size),
+                last_use.dimmed()
+            );
         }
-    } else {
-        println!("\n{}: None installed", "Gradle".cyan().bold());
     }
 
     // Android SDK
     let android_dir = root.join("android");
     if android_dir.exists() {
-        let size = get_dir_size(&android_dir)?;
+        let size = toolchain::dir_size(&android_dir);
         println!("\n{}:", "Android SDK".cyan().bold());
-        println!("  - Installed ({})", human_size(size));
+        println!("  - Installed ({}, including NDKs and system images)", human_size(size));
     } else {
         println!("\n{}: Not installed", "Android SDK".cyan().bold());
     }
@@ -76,23 +109,97 @@ pub fn execute_list() -> Result<()> {
     Ok(())
 }
 
+/// Remove toolchain versions that no existing project uses
+pub fn execute_gc(older_than: Option<&str>, dry_run: bool) -> Result<()> {
+    let older_than = older_than.map(toolchain::usage::parse_age).transpose()?;
+    let toolchain = Toolchain::new()?;
+
+    let removed = toolchain.gc(older_than, dry_run)?;
+
+    if removed.is_empty() {
+        println!("No unused toolchains");
+        return Ok(());
+    }
+
+    let verb = if dry_run { "Would remove" } else { "Removing" };
+    for (version, size) in &removed {
+        println!("{:>12} {} ({})", verb.yellow().bold(), version, human_size(*size));
+    }
+
+    let total: u64 = removed.iter().map(|(_, size)| size).sum();
+    let summary = if dry_run { "Would free" } else { "Freed" };
+    println!("{:>12} {}", summary.green().bold(), human_size(total));
+
+    Ok(())
+}
+
+/// Propose a consistent set of toolchain versions and SDK levels for the project
+pub fn execute_suggest(manifest_path: &str) -> Result<()> {
+    // Read without validating: this is how invalid configurations get fixed
+    let current = if Path::new(manifest_path).exists() {
+        config::toolchain_versions(&config::read_config(manifest_path)?)
+    } else {
+        toolchain::validator::ToolchainConfig {
+            java: toolchain::DEFAULT_JAVA.to_string(),
+            gradle: toolchain::DEFAULT_GRADLE.to_string(),
+            agp: toolchain::DEFAULT_AGP.to_string(),
+            kotlin: toolchain::DEFAULT_KOTLIN.to_string(),
+            min_sdk: toolchain::DEFAULT_MIN_SDK,
+            target_sdk: toolchain::DEFAULT_TARGET_SDK,
+        }
+    };
+    let suggested = toolchain::validator::suggest(&current)?;
+
+    if suggested == current {
+        println!("{} Toolchain versions are compatible", "✓".green().bold());
+    } else {
+        println!("{} versions for {}:", "Suggested".green().bold(), manifest_path);
+    }
+
+    let print = |key: &str, old: String, new: String| {
+        if old == new {
+            println!("{} = {}", key, new);
+        } else {
+            println!("{} = {}  {}", key, new, format!("# was {}", old).dimmed());
+        }
+    };
+    let quoted = |version: &str| format!("\"{}\"", version);
+
+    println!();
+    println!("[android]");
+    print("min_sdk", current.min_sdk.to_string(), suggested.min_sdk.to_string());
+    print("target_sdk", current.target_sdk.to_string(), suggested.target_sdk.to_string());
+    println!();
+    println!("[toolchain]");
+    print("java", quoted(&current.java), quoted(&suggested.java));
+    print("gradle", quoted(&current.gradle), quoted(&suggested.gradle));
+    print("agp", quoted(&current.agp), quoted(&suggested.agp));
+    print("kotlin", quoted(&current.kotlin), quoted(&suggested.kotlin));
+
+    Ok(())
+}
+
 /// Clean (remove) all installed toolchains
 pub fn execute_clean() -> Result<()> {
     let toolchain = Toolchain::new()?;
     let root = toolchain.root();
 
     println!("Remove all toolchains at {}", root.display());
-    print!("Continue? [y/n]: ");
 
-    use std::io::{self, Write};
-    io::stdout().flush()?;
+    // Running `clean` in CI is the confirmation
+    if !toolchain::ci::non_interactive() {
+        print!("Continue? [y/n]: ");
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
+        use std::io::{self, Write};
+        io::stdout().flush()?;
 
-    let input = input.trim().to_lowercase();
-    if input != "y" && input != "yes" {
-        return Ok(());
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        let input = input.trim().to_lowercase();
+        if input != "y" && input != "yes" {
+            return Ok(());
+        }
     }
 
     if root.exists() {
@@ -102,21 +209,38 @@ pub fn execute_clean() -> Result<()> {
     Ok(())
 }
 
-/// Get directory size recursively
-fn get_dir_size(path: &std::path::Path) -> Result<u64> {
-    let mut size = 0;
-    if path.is_dir() {
-        for entry in std::fs::read_dir(path)? {
-            let entry = entry?;
-            let metadata = entry.metadata()?;
-            if metadata.is_dir() {
-                size += get_dir_size(&entry.path())?;
-            } else {
-                size += metadata.len();
-            }
-        }
-    }
-    Ok(size)
+/// Export installed toolchains into a tarball for offline machines
+pub fn execute_pack(output: &str) -> Result<()> {
+    let toolchain = Toolchain::new()?;
+    let output = PathBuf::from(output);
+
+    let packed = toolchain.pack(&output)?;
+
+    let size = std::fs::metadata(&output)?.len();
+    println!(
+        "   {} {} into {} ({})",
+        "Packed".green().bold(),
+        packed.join(", "),
+        output.display(),
+        human_size(size)
+    );
+
+    Ok(())
+}
+
+/// Restore toolchains from a tarball created by `toolchain pack`
+pub fn execute_unpack(archive: &str) -> Result<()> {
+    let toolchain = Toolchain::new()?;
+
+    toolchain.unpack(&PathBuf::from(archive))?;
+
+    println!(
+        "   {} toolchains into {}",
+        "Unpacked".green().bold(),
+        toolchain.root().display()
+    );
+
+    Ok(())
 }
 
 /// Execute a command with the project's toolchain environment (lazy-loading)
@@ -11,12 +11,12 @@ use crate::build_pipeline;
 use crate::config;
 use crate::keyboard::{self, KeyAction, RawModeGuard};
 use crate::single_file;
-use crate::commands::{detect_target, Target};
+use crate::commands::{detect_target, resolve_ffi_abi, Target};
 
-pub fn execute(target: &str) -> Result<()> {
+pub fn execute(target: &str, abi: Option<&str>) -> Result<()> {
     // Detect if we're watching a project or single file
     match detect_target(target) {
-        Target::Project(manifest_path) => execute_project(&manifest_path),
+        Target::Project(manifest_path) => execute_project(&manifest_path, abi),
         Target::SingleFile(file_path) => execute_single_file(&file_path),
     }
 }
@@ -116,7 +116,7 @@ fn run_single_file_build(file_path: &Path, original_dir: &Path) -> Result<()> {
 }
 
 /// Watch a project (existing behavior)
-fn execute_project(manifest_path: &str) -> Result<()> {
+fn execute_project(manifest_path: &str, abi: Option<&str>) -> Result<()> {
     // 1. Determine project directory from manifest path (same as build command)
     let manifest_path = Path::new(manifest_path);
     let original_dir = env::current_dir()?;
@@ -143,7 +143,8 @@ fn execute_project(manifest_path: &str) -> Result<()> {
 
     // 2. Load configuration
     let manifest_file = manifest_path.file_name().unwrap().to_str().unwrap();
-    let config = config::load_config(manifest_file)?;
+    let mut config = config::load_config(manifest_file)?;
+    config.ffi.abi = resolve_ffi_abi(abi, None)?;
 
     // 3. Initial build
     match run_build(&config) {
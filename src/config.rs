@@ -1,5 +1,6 @@
 use anyhow::Result;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 
 #[derive(Debug, Deserialize)]
@@ -12,6 +13,17 @@ pub struct Config {
     pub toolchain: ToolchainConfig,
     #[serde(default)]
     pub ffi: FfiConfig,
+    #[serde(default)]
+    pub fetch: FetchConfig,
+    /// Extra Gradle dependencies for app/build.gradle.kts, keyed by `group:artifact`
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, DependencySpec>,
+    /// Extra Gradle plugins, plugin id to version
+    #[serde(default)]
+    pub plugins: BTreeMap<String, String>,
+    /// Extra Maven repositories, name to URL
+    #[serde(default)]
+    pub repositories: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,6 +37,10 @@ pub struct AndroidConfig {
     pub min_sdk: u32,
     pub target_sdk: u32,
     pub package: String,
+    /// Extra `<uses-permission>` entries for AndroidManifest.xml, e.g. `CAMERA` or
+    /// `android.permission.INTERNET` for network access the build can't detect
+    #[serde(default)]
+    pub permissions: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -62,6 +78,14 @@ pub struct ToolchainConfig {
     pub agp: String,
     #[serde(default = "default_kotlin")]
     pub kotlin: String,
+    /// Download toolchains from this `file://` directory or HTTP server instead of
+    /// the internet. `WHITEHALL_MIRROR` overrides it.
+    #[serde(default)]
+    pub mirror: Option<String>,
+    /// Install toolchains here instead of ~/.whitehall/toolchains. Relative
+    /// paths are resolved against whitehall.toml. `WHITEHALL_HOME` overrides it.
+    #[serde(default)]
+    pub root: Option<String>,
 }
 
 impl Default for ToolchainConfig {
@@ -71,6 +95,8 @@ impl Default for ToolchainConfig {
             gradle: default_gradle(),
             agp: default_agp(),
             kotlin: default_kotlin(),
+            mirror: None,
+            root: None,
         }
     }
 }
@@ -91,6 +117,83 @@ fn default_kotlin() -> String {
     crate::toolchain::DEFAULT_KOTLIN.to_string()
 }
 
+/// Shared HTTP client used by `$fetch` and the generated API clients
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct FetchConfig {
+    /// Prefix for relative `$fetch` URLs
+    pub base_url: Option<String>,
+    /// Default request timeout in milliseconds
+    pub timeout: Option<u64>,
+    /// Headers sent with every request
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// A `[dependencies]` entry: a version string, or a table for other
+/// configurations and BOMs
+///
+/// ```toml
+/// [dependencies]
+/// "io.coil-kt:coil-svg" = "2.6.0"
+/// "com.google.firebase:firebase-bom" = { version = "33.5.1", platform = true }
+/// "com.google.firebase:firebase-analytics" = {}
+/// "junit:junit" = { version = "4.13.2", configuration = "testImplementation" }
+/// ```
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DependencySpec {
+    Version(String),
+    Detailed(DetailedDependency),
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DetailedDependency {
+    /// Omitted for artifacts whose version comes from a BOM
+    pub version: Option<String>,
+    /// Gradle configuration, `implementation` by default
+    pub configuration: Option<String>,
+    /// Import as a BOM with `platform(...)`
+    #[serde(default)]
+    pub platform: bool,
+}
+
+impl DependencySpec {
+    pub fn version(&self) -> Option<&str> {
+        match self {
+            DependencySpec::Version(version) => Some(version),
+            DependencySpec::Detailed(detailed) => detailed.version.as_deref(),
+        }
+    }
+
+    pub fn configuration(&self) -> &str {
+        match self {
+            DependencySpec::Detailed(DetailedDependency {
+                configuration: Some(configuration),
+                ..
+            }) => configuration,
+            _ => "implementation",
+        }
+    }
+
+    pub fn platform(&self) -> bool {
+        matches!(self, DependencySpec::Detailed(detailed) if detailed.platform)
+    }
+}
+
+/// Gradle configurations accepted in `[dependencies]`
+pub const DEPENDENCY_CONFIGURATIONS: &[&str] = &[
+    "implementation",
+    "api",
+    "compileOnly",
+    "runtimeOnly",
+    "ksp",
+    "testImplementation",
+    "androidTestImplementation",
+    "debugImplementation",
+    "releaseImplementation",
+];
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct FfiConfig {
     /// Enable or disable FFI. If None, auto-detect based on directory existence
@@ -101,7 +204,14 @@ pub struct FfiConfig {
     #[serde(default)]
     pub cpp: CppConfig,
     #[serde(default)]
+    pub c: CConfig,
+    #[serde(default)]
     pub rust: RustConfig,
+    #[serde(default)]
+    pub zig: ZigConfig,
+    /// Build native libraries for this ABI only. Set by `--abi`, not read from whitehall.toml
+    #[serde(skip)]
+    pub abi: Option<String>,
 }
 
 impl Default for FfiConfig {
@@ -110,7 +220,10 @@ impl Default for FfiConfig {
             enabled: None, // Auto-detect by default
             ffi_only: false,
             cpp: CppConfig::default(),
+            c: CConfig::default(),
             rust: RustConfig::default(),
+            zig: ZigConfig::default(),
+            abi: None,
         }
     }
 }
@@ -143,6 +256,34 @@ fn default_cpp_standard() -> String {
     "17".to_string()
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct CConfig {
+    #[serde(default = "default_c_standard")]
+    pub standard: String,
+    #[serde(default)]
+    pub flags: Vec<String>,
+    #[serde(default)]
+    pub libraries: Vec<String>,
+    /// Library name for the C FFI library. Defaults to project name if not specified.
+    #[serde(default)]
+    pub library_name: Option<String>,
+}
+
+impl Default for CConfig {
+    fn default() -> Self {
+        Self {
+            standard: default_c_standard(),
+            flags: Vec::new(),
+            libraries: Vec::new(),
+            library_name: None,
+        }
+    }
+}
+
+fn default_c_standard() -> String {
+    "11".to_string()
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct RustConfig {
     #[serde(default = "default_rust_profile")]
@@ -177,8 +318,65 @@ fn default_rust_targets() -> Vec<String> {
     ]
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct ZigConfig {
+    /// `zig build-lib -O` mode: Debug, ReleaseSafe, ReleaseFast or ReleaseSmall
+    #[serde(default = "default_zig_optimize")]
+    pub optimize: String,
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// Library name for the Zig FFI library. Defaults to `<project name>_zig` if not specified.
+    #[serde(default)]
+    pub library_name: Option<String>,
+}
+
+impl Default for ZigConfig {
+    fn default() -> Self {
+        Self {
+            optimize: default_zig_optimize(),
+            flags: Vec::new(),
+            library_name: None,
+        }
+    }
+}
+
+fn default_zig_optimize() -> String {
+    "ReleaseFast".to_string()
+}
+
 /// Load and parse whitehall.toml configuration file
 pub fn load_config(path: &str) -> Result<Config> {
+    let config = read_config(path)?;
+
+    // Validate Android package name
+    validate_package_name(&config.android.package)?;
+
+    // Validate toolchain compatibility
+    crate::toolchain::validate_compatibility(&toolchain_versions(&config))?;
+
+    // Validate user-declared Gradle dependencies, plugins and repositories
+    validate_gradle_extras(&config)?;
+
+    crate::toolchain::set_mirror(config.toolchain.mirror.as_deref());
+    crate::toolchain::set_root(config.toolchain.root.as_ref().map(|root| {
+        let manifest_dir = std::path::Path::new(path).parent().unwrap_or(std::path::Path::new(""));
+        manifest_dir.join(root)
+    }));
+
+    // Verify toolchain downloads against whitehall.lock when the project has one
+    let lock_path = std::path::Path::new(path).with_file_name(crate::toolchain::LOCKFILE);
+    crate::toolchain::set_lockfile(lock_path.exists().then_some(lock_path));
+
+    // Attribute toolchain use to this project for `toolchain list` / `toolchain gc`
+    crate::toolchain::usage::set_project(fs::canonicalize(path).ok());
+
+    Ok(config)
+}
+
+/// Parse whitehall.toml without validating it or registering its settings
+///
+/// For commands that help fix an invalid configuration, like `toolchain suggest`.
+pub fn read_config(path: &str) -> Result<Config> {
     let content = fs::read_to_string(path)
         .map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
@@ -191,18 +389,90 @@ pub fn load_config(path: &str) -> Result<Config> {
     let config: Config = toml::from_str(&content)
         .map_err(|e| anyhow::anyhow!("failed to parse `{}`: {}", path, e))?;
 
-    // Validate Android package name
-    validate_package_name(&config.android.package)?;
+    Ok(config)
+}
 
-    // Validate toolchain compatibility
-    let validator_config = crate::toolchain::validator::ToolchainConfig {
+/// The versions `toolchain::validator` checks
+pub fn toolchain_versions(config: &Config) -> crate::toolchain::validator::ToolchainConfig {
+    crate::toolchain::validator::ToolchainConfig {
         java: config.toolchain.java.clone(),
         gradle: config.toolchain.gradle.clone(),
         agp: config.toolchain.agp.clone(),
-    };
-    crate::toolchain::validate_compatibility(&validator_config)?;
+        kotlin: config.toolchain.kotlin.clone(),
+        min_sdk: config.android.min_sdk,
+        target_sdk: config.android.target_sdk,
+    }
+}
 
-    Ok(config)
+/// Check `[dependencies]`, `[plugins]` and `[repositories]` before they are
+/// written into the generated Gradle scripts
+fn validate_gradle_extras(config: &Config) -> Result<()> {
+    for (module, spec) in &config.dependencies {
+        validate_dependency(module, spec)?;
+    }
+    for (id, version) in &config.plugins {
+        validate_gradle_string("plugin id", id)?;
+        if id.is_empty() || id.contains(':') {
+            anyhow::bail!("Invalid plugin id '{}' in [plugins]", id);
+        }
+        validate_gradle_string("plugin version", version)?;
+    }
+    for (name, url) in &config.repositories {
+        validate_gradle_string("repository name", name)?;
+        validate_gradle_string("repository URL", url)?;
+        if !["https://", "http://", "file:"].iter().any(|scheme| url.starts_with(scheme)) {
+            anyhow::bail!(
+                "Invalid URL '{}' for repository '{}'. Expected an http(s):// or file: URL",
+                url,
+                name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Validate one `[dependencies]` entry
+pub fn validate_dependency(module: &str, spec: &DependencySpec) -> Result<()> {
+    validate_gradle_string("dependency", module)?;
+    let parts: Vec<&str> = module.split(':').collect();
+    if parts.len() != 2 || parts.iter().any(|part| part.is_empty()) {
+        anyhow::bail!(
+            "Invalid dependency '{}'. Keys in [dependencies] must be 'group:artifact' \
+             with the version as the value (e.g., \"io.coil-kt:coil-svg\" = \"2.6.0\")",
+            module
+        );
+    }
+
+    if let Some(version) = spec.version() {
+        validate_gradle_string("version", version)?;
+        if version.is_empty() {
+            anyhow::bail!("Empty version for dependency '{}'", module);
+        }
+    } else if spec.platform() {
+        anyhow::bail!("BOM '{}' needs a version", module);
+    }
+
+    let configuration = spec.configuration();
+    if !DEPENDENCY_CONFIGURATIONS.contains(&configuration) {
+        anyhow::bail!(
+            "Unknown configuration '{}' for dependency '{}'. Expected one of: {}",
+            configuration,
+            module,
+            DEPENDENCY_CONFIGURATIONS.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Values end up inside Kotlin string literals in the generated scripts
+fn validate_gradle_string(kind: &str, value: &str) -> Result<()> {
+    if let Some(ch) = value
+        .chars()
+        .find(|ch| matches!(ch, '"' | '\\' | '$') || ch.is_whitespace())
+    {
+        anyhow::bail!("Invalid {} '{}'. Contains invalid character '{}'", kind, value, ch);
+    }
+    Ok(())
 }
 
 /// Validate Android package name format
@@ -278,6 +548,88 @@ mod tests {
         assert!(validate_package_name("com..app").is_err());
     }
 
+    #[test]
+    fn test_parse_gradle_extras() {
+        let config: Config = toml::from_str(
+            r#"
+[project]
+name = "app"
+version = "0.1.0"
+
+[android]
+min_sdk = 24
+target_sdk = 34
+package = "com.example.app"
+
+[dependencies]
+"io.coil-kt:coil-svg" = "2.6.0"
+"com.google.firebase:firebase-bom" = { version = "33.5.1", platform = true }
+"com.google.firebase:firebase-analytics" = {}
+"junit:junit" = { version = "4.13.2", configuration = "testImplementation" }
+
+[plugins]
+"com.google.gms.google-services" = "4.4.2"
+
+[repositories]
+jitpack = "https://jitpack.io"
+"#,
+        )
+        .unwrap();
+
+        let svg = &config.dependencies["io.coil-kt:coil-svg"];
+        assert_eq!(svg.version(), Some("2.6.0"));
+        assert_eq!(svg.configuration(), "implementation");
+        assert!(!svg.platform());
+
+        let bom = &config.dependencies["com.google.firebase:firebase-bom"];
+        assert!(bom.platform());
+        assert_eq!(config.dependencies["com.google.firebase:firebase-analytics"].version(), None);
+        assert_eq!(config.dependencies["junit:junit"].configuration(), "testImplementation");
+        assert_eq!(config.plugins["com.google.gms.google-services"], "4.4.2");
+        assert_eq!(config.repositories["jitpack"], "https://jitpack.io");
+        assert!(validate_gradle_extras(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_dependency_invalid() {
+        let version = DependencySpec::Version("1.0".to_string());
+        assert!(validate_dependency("io.coil-kt:coil-svg", &version).is_ok());
+
+        // Version belongs in the value, not the key
+        assert!(validate_dependency("io.coil-kt:coil-svg:2.6.0", &version).is_err());
+        assert!(validate_dependency("coil-svg", &version).is_err());
+        assert!(validate_dependency("io.coil-kt:coil\")", &version).is_err());
+
+        let unknown = DependencySpec::Detailed(DetailedDependency {
+            version: Some("1.0".to_string()),
+            configuration: Some("kapt".to_string()),
+            platform: false,
+        });
+        assert!(validate_dependency("com.example:lib", &unknown).is_err());
+
+        let bom_without_version = DependencySpec::Detailed(DetailedDependency {
+            platform: true,
+            ..Default::default()
+        });
+        assert!(validate_dependency("com.example:bom", &bom_without_version).is_err());
+    }
+
+    #[test]
+    fn test_example_manifests_load() {
+        // Every example has to pass the compatibility matrix, or it fails before Gradle runs
+        let mut manifests = 0;
+        for entry in fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/examples")).unwrap() {
+            let path = entry.unwrap().path().join("whitehall.toml");
+            if path.exists() {
+                manifests += 1;
+                if let Err(e) = load_config(path.to_str().unwrap()) {
+                    panic!("{}: {:#}", path.display(), e);
+                }
+            }
+        }
+        assert!(manifests > 0);
+    }
+
     #[test]
     fn test_default_build_config() {
         let config = BuildConfig::default();
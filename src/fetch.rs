@@ -0,0 +1,187 @@
+//! Shared HTTP client runtime (Fetch.kt) behind `$fetch` and the generated API clients
+//!
+//! One client per app, configured from the `[fetch]` section of whitehall.toml:
+//!
+//! ```toml
+//! [fetch]
+//! base_url = "https://api.example.com/v1"
+//! timeout = 10000
+//!
+//! [fetch.headers]
+//! Accept-Language = "en"
+//! ```
+
+use crate::config::FetchConfig;
+
+/// Quote a value as a Kotlin string literal
+fn kotlin_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$");
+    format!("\"{}\"", escaped)
+}
+
+/// Generate Fetch.kt for the app's root package
+pub fn generate_fetch_kt(config: &FetchConfig, package: &str) -> String {
+    let base_url = kotlin_string(config.base_url.as_deref().unwrap_or(""));
+
+    let headers = if config.headers.is_empty() {
+        "mutableMapOf()".to_string()
+    } else {
+        let entries: Vec<String> = config
+            .headers
+            .iter()
+            .map(|(name, value)| format!("        {} to {},\n", kotlin_string(name), kotlin_string(value)))
+            .collect();
+        format!("mutableMapOf(\n{}    )", entries.concat())
+    };
+
+    let timeout = match config.timeout {
+        Some(millis) => format!(" {{\n            requestTimeoutMillis = {}\n        }}", millis),
+        None => String::new(),
+    };
+
+    format!(
+        r#"package {package}
+
+import io.ktor.client.HttpClient
+import io.ktor.client.call.body
+import io.ktor.client.engine.okhttp.OkHttp
+import io.ktor.client.plugins.HttpTimeout
+import io.ktor.client.plugins.contentnegotiation.ContentNegotiation
+import io.ktor.client.plugins.defaultRequest
+import io.ktor.client.plugins.expectSuccess
+import io.ktor.client.plugins.timeout
+import io.ktor.client.request.header
+import io.ktor.client.request.request
+import io.ktor.client.request.setBody
+import io.ktor.client.statement.HttpResponse
+import io.ktor.client.statement.bodyAsText
+import io.ktor.http.ContentType
+import io.ktor.http.HttpMethod
+import io.ktor.http.contentType
+import io.ktor.http.isSuccess
+import io.ktor.serialization.kotlinx.json.json
+import kotlin.coroutines.cancellation.CancellationException
+import kotlinx.serialization.json.Json
+
+/** Outcome of a `$fetch` call typed as `FetchResult<T>` */
+sealed interface FetchResult<out T> {{
+    data class Success<T>(val value: T, val status: Int) : FetchResult<T>
+    data class HttpError(val status: Int, val body: String) : FetchResult<Nothing>
+    data class NetworkError(val cause: Throwable) : FetchResult<Nothing>
+}}
+
+/** Thrown by `$fetch` for non-2xx responses */
+class FetchException(val status: Int, val body: String) : Exception("HTTP $status")
+
+/** Shared HTTP client for `$fetch` and the generated API clients */
+object Fetch {{
+    /** Prefix for relative URLs */
+    var baseUrl = {base_url}
+
+    /** Sent with every request unless the request sets the same header */
+    val headers: MutableMap<String, String> = {headers}
+
+    val client = HttpClient(OkHttp) {{
+        expectSuccess = true
+        install(ContentNegotiation) {{
+            json(Json {{ ignoreUnknownKeys = true }})
+        }}
+        install(HttpTimeout){timeout}
+        defaultRequest {{
+            Fetch.headers.forEach {{ (name, value) -> header(name, value) }}
+        }}
+    }}
+
+    suspend inline fun <reified T> request(
+        url: String,
+        method: String = "GET",
+        body: Any? = null,
+        headers: Map<String, String> = emptyMap(),
+        timeout: Long? = null,
+    ): T {{
+        val response = send(url, method, body, headers, timeout)
+        if (!response.status.isSuccess()) {{
+            throw FetchException(response.status.value, response.bodyAsText())
+        }}
+        return response.body()
+    }}
+
+    suspend inline fun <reified T> result(
+        url: String,
+        method: String = "GET",
+        body: Any? = null,
+        headers: Map<String, String> = emptyMap(),
+        timeout: Long? = null,
+    ): FetchResult<T> {{
+        val response = try {{
+            send(url, method, body, headers, timeout)
+        }} catch (e: CancellationException) {{
+            throw e
+        }} catch (e: Exception) {{
+            return FetchResult.NetworkError(e)
+        }}
+        if (!response.status.isSuccess()) {{
+            return FetchResult.HttpError(response.status.value, response.bodyAsText())
+        }}
+        return FetchResult.Success(response.body(), response.status.value)
+    }}
+
+    @PublishedApi
+    internal suspend fun send(
+        url: String,
+        method: String,
+        body: Any?,
+        extraHeaders: Map<String, String>,
+        timeoutMillis: Long?,
+    ): HttpResponse = client.request(resolve(url)) {{
+        this.method = HttpMethod.parse(method.uppercase())
+        expectSuccess = false
+        extraHeaders.forEach {{ (name, value) -> header(name, value) }}
+        if (timeoutMillis != null) {{
+            timeout {{ requestTimeoutMillis = timeoutMillis }}
+        }}
+        if (body != null) {{
+            contentType(ContentType.Application.Json)
+            setBody(body)
+        }}
+    }}
+
+    private fun resolve(url: String): String =
+        if (baseUrl.isEmpty() || url.contains("://")) url
+        else baseUrl.trimEnd('/') + "/" + url.trimStart('/')
+}}
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_fetch_kt_from_config() {
+        let mut config = FetchConfig {
+            base_url: Some("https://api.example.com/v1".to_string()),
+            timeout: Some(5000),
+            ..Default::default()
+        };
+        config.headers.insert("X-Api-Key".to_string(), "abc$1".to_string());
+
+        let output = generate_fetch_kt(&config, "com.example.app");
+        assert!(output.starts_with("package com.example.app\n"));
+        assert!(output.contains("var baseUrl = \"https://api.example.com/v1\""));
+        assert!(output.contains("        \"X-Api-Key\" to \"abc\\$1\",\n"));
+        assert!(output.contains("install(HttpTimeout) {\n            requestTimeoutMillis = 5000\n        }"));
+    }
+
+    #[test]
+    fn test_generate_fetch_kt_defaults() {
+        let output = generate_fetch_kt(&FetchConfig::default(), "com.example.app");
+        assert!(output.contains("var baseUrl = \"\"\n"));
+        assert!(output.contains("val headers: MutableMap<String, String> = mutableMapOf()\n"));
+        assert!(output.contains("        install(HttpTimeout)\n"));
+    }
+}
@@ -1,18 +1,56 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::config::Config;
-use crate::ffi_parser::cpp::discover_cpp_ffi;
-use crate::ffi_parser::rust::discover_rust_ffi;
+use crate::ffi_parser::c::{c_source_files, discover_c_ffi};
+use crate::ffi_parser::cpp::{check_cpp_ffi_types, discover_cpp_ffi, discover_cpp_ffi_types, CppFfiFunction, CppFfiType};
+use crate::ffi_parser::zig::{discover_zig_ffi, zig_source_files};
+use crate::ffi_parser::rust::{
+    check_rust_ffi_classes, check_rust_ffi_types, discover_rust_ffi, discover_rust_ffi_classes, discover_rust_ffi_types,
+    RustFfiClass, RustFfiFunction, RustFfiType,
+};
 use crate::ffi_codegen::{
-    generate_kotlin_object, generate_jni_bridge, generate_cmake,
-    generate_kotlin_object_rust, generate_rust_bridge,
+    generate_kotlin_object, generate_kotlin_types, generate_jni_bridge, generate_cmake,
+    generate_c_jni_bridge, generate_c_cmake, generate_c_header, generate_zig_cmake,
+    generate_kotlin_object_rust, generate_kotlin_types_rust, generate_kotlin_class_rust, generate_rust_bridge,
 };
 use crate::toolchain::Toolchain;
 
+/// A native language whose `@ffi` declarations in `src/ffi/<dir>/` are exposed to Kotlin
+///
+/// `build_ffi` runs the three steps in turn for every language whose directory
+/// exists. Supporting another language means implementing this trait and adding
+/// it to `ffi_languages()`.
+pub trait FfiLanguage {
+    /// Directory under `src/ffi/` holding this language's sources
+    fn dir(&self) -> &'static str;
+
+    /// Find the `@ffi` declarations. Returns false if there is nothing to expose
+    fn discover(&mut self, config: &Config, ffi_dir: &Path) -> Result<bool>;
+
+    /// Name of the native library (`lib<name>.so`), known after `discover`
+    fn library_name(&self) -> &str;
+
+    /// Write the Kotlin bindings and the JNI bridge under the build directory
+    fn generate_bridge(&mut self, config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()>;
+
+    /// Compile the native library into jniLibs
+    fn build(&self, config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()>;
+}
+
+/// Supported FFI languages, in build order
+fn ffi_languages() -> Vec<Box<dyn FfiLanguage>> {
+    vec![
+        Box::<CppFfi>::default(),
+        Box::<CFfi>::default(),
+        Box::<RustFfi>::default(),
+        Box::<ZigFfi>::default(),
+    ]
+}
+
 /// Build FFI components if enabled
 pub fn build_ffi(config: &Config, project_root: &Path) -> Result<()> {
     // Check if FFI is enabled
@@ -23,28 +61,52 @@ pub fn build_ffi(config: &Config, project_root: &Path) -> Result<()> {
     let ffi_dir = project_root.join("src/ffi");
     let build_dir = project_root.join(&config.build.output_dir);
 
-    // Build C++ FFI if present
-    if ffi_dir.join("cpp").exists() {
-        build_cpp_ffi(config, &ffi_dir, &build_dir)?;
+    let mut languages = Vec::new();
+    for mut language in ffi_languages() {
+        if !ffi_dir.join(language.dir()).exists() {
+            continue;
+        }
+        if language.discover(config, &ffi_dir)? {
+            languages.push(language);
+        }
     }
+    check_library_names(&languages)?;
 
-    // Build Rust FFI if present
-    if ffi_dir.join("rust").exists() {
-        build_rust_ffi(config, &ffi_dir, &build_dir)?;
+    for language in &mut languages {
+        language.generate_bridge(config, &ffi_dir, &build_dir)?;
+        language.build(config, &ffi_dir, &build_dir)?;
     }
 
     Ok(())
 }
 
+/// Each language builds into `cmake-build/<library_name>/` and installs `lib<library_name>.so`,
+/// so two languages sharing a name would overwrite each other's build and library
+fn check_library_names(languages: &[Box<dyn FfiLanguage>]) -> Result<()> {
+    for (i, language) in languages.iter().enumerate() {
+        if let Some(other) = languages[..i].iter().find(|l| l.library_name() == language.library_name()) {
+            anyhow::bail!(
+                "src/ffi/{} and src/ffi/{} both build lib{}.so. Set a distinct library_name in [ffi.{}] or [ffi.{}]",
+                other.dir(),
+                language.dir(),
+                language.library_name(),
+                other.dir(),
+                language.dir()
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Determine if FFI should be built
 fn should_build_ffi(config: &Config, project_root: &Path) -> bool {
     match config.ffi.enabled {
         Some(true) => true,
         Some(false) => false,
         None => {
-            // Auto-detect: check if src/ffi/cpp/ or src/ffi/rust/ exists
+            // Auto-detect: check if any src/ffi/<language>/ directory exists
             let ffi_dir = project_root.join("src/ffi");
-            ffi_dir.join("cpp").exists() || ffi_dir.join("rust").exists()
+            ffi_languages().iter().any(|language| ffi_dir.join(language.dir()).exists())
         }
     }
 }
@@ -118,6 +180,38 @@ fn get_cpp_library_name(config: &Config, ffi_dir: &Path) -> String {
     config.project.name.clone()
 }
 
+/// Get C library name with precedence:
+/// 1. whitehall.toml override
+/// 2. src/ffi/c/CMakeLists.txt project name
+/// 3. `<project name>_c` fallback (distinct from the C++ default)
+fn get_c_library_name(config: &Config, ffi_dir: &Path) -> String {
+    // 1. Check whitehall.toml override
+    if let Some(name) = &config.ffi.c.library_name {
+        return name.clone();
+    }
+
+    // 2. Check user-provided CMakeLists.txt
+    let user_cmake = ffi_dir.join("c/CMakeLists.txt");
+    if user_cmake.exists() {
+        if let Ok(name) = parse_cmake_project_name(&user_cmake) {
+            return name;
+        }
+    }
+
+    // 3. Fallback to project name, suffixed so it can't collide with the C++ library
+    format!("{}_c", config.project.name)
+}
+
+/// Get Zig library name with precedence:
+/// 1. whitehall.toml override
+/// 2. `<project name>_zig` fallback (distinct from the C and C++ defaults)
+fn get_zig_library_name(config: &Config) -> String {
+    match &config.ffi.zig.library_name {
+        Some(name) => name.clone(),
+        None => format!("{}_zig", config.project.name),
+    }
+}
+
 /// Get Rust library name with precedence:
 /// 1. whitehall.toml override
 /// 2. src/ffi/rust/Cargo.toml package name
@@ -140,152 +234,493 @@ fn get_rust_library_name(config: &Config, ffi_dir: &Path) -> String {
     config.project.name.clone()
 }
 
-/// Build C++ FFI components
-fn build_cpp_ffi(config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()> {
-    use colored::Colorize;
-
-    // 1. Discover FFI functions
-    eprintln!("{}", "Discovering FFI functions...".dimmed());
-    let functions = discover_cpp_ffi(ffi_dir)
-        .context("Failed to discover C++ FFI functions")?;
+/// C++ sources in `src/ffi/cpp/`, built with CMake
+#[derive(Default)]
+struct CppFfi {
+    functions: Vec<CppFfiFunction>,
+    types: Vec<CppFfiType>,
+    library_name: String,
+    /// CMakeLists.txt the library is built from, once the bridge is generated
+    cmake_file: PathBuf,
+    /// Sources and generated files the library is built from
+    inputs: Vec<PathBuf>,
+}
 
-    if functions.is_empty() {
-        eprintln!("{}", "No FFI functions found".yellow());
-        return Ok(());
+impl FfiLanguage for CppFfi {
+    fn dir(&self) -> &'static str {
+        "cpp"
     }
 
-    eprintln!("{} {} FFI function(s)", "Found".green(), functions.len());
-
-    // Get library name with precedence: whitehall.toml > CMakeLists.txt > project.name
-    let library_name = get_cpp_library_name(config, ffi_dir);
-    eprintln!("{} {}", "Library name:".dimmed(), library_name);
-
-    // Generate PascalCase object name for Kotlin
-    // Derive from the source file name if there's only one .cpp file
-    let object_name = if functions.len() > 0 {
-        // Get the source file name from the first function
-        // All functions in a single .cpp file will have the same source_file
-        let source_file = &functions[0].source_file;
-        let file_stem = source_file.file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or(&library_name);
-        to_pascal_case(file_stem)
-    } else {
-        to_pascal_case(&library_name)
-    };
+    fn discover(&mut self, config: &Config, ffi_dir: &Path) -> Result<bool> {
+        eprintln!("{}", "Discovering FFI functions...".dimmed());
+        self.functions = discover_cpp_ffi(ffi_dir)
+            .context("Failed to discover C++ FFI functions")?;
 
-    // 2. Generate Kotlin bindings in ffi.cpp subpackage (matches Rust FFI pattern)
-    let kotlin_dir = build_dir.join("generated/kotlin");
-    let package_path = config.android.package.replace('.', "/");
-    let kotlin_package_dir = kotlin_dir.join(&package_path).join("ffi/cpp");
+        if self.functions.is_empty() {
+            eprintln!("{}", "No FFI functions found".yellow());
+            return Ok(false);
+        }
 
-    fs::create_dir_all(&kotlin_package_dir)
-        .context("Failed to create Kotlin output directory")?;
+        eprintln!("{} {} FFI function(s)", "Found".green(), self.functions.len());
 
-    let kotlin_package = format!("{}.ffi.cpp", config.android.package);
-    let kotlin_code = generate_kotlin_object(
-        &functions,
-        &kotlin_package,
-        &library_name,
-        &object_name,
-    );
+        self.types = discover_cpp_ffi_types(ffi_dir)
+            .context("Failed to discover C++ FFI types")?;
+        check_cpp_ffi_types(&self.functions, &self.types)?;
 
-    let kotlin_file = kotlin_package_dir.join(format!("{}.kt", object_name));
-    fs::write(&kotlin_file, kotlin_code)
-        .context(format!("Failed to write Kotlin binding: {}", kotlin_file.display()))?;
+        // Get library name with precedence: whitehall.toml > CMakeLists.txt > project.name
+        self.library_name = get_cpp_library_name(config, ffi_dir);
+        eprintln!("{} {}", "Library name:".dimmed(), self.library_name);
 
-    // 3. Generate JNI bridge
-    let jni_dir = build_dir.join("generated/jni");
-    fs::create_dir_all(&jni_dir)
-        .context("Failed to create JNI output directory")?;
+        Ok(true)
+    }
 
-    let source_files: Vec<String> = functions
-        .iter()
-        .map(|f| {
-            // Get relative path from project root
-            let rel_path = f.source_file.strip_prefix(ffi_dir.parent().unwrap()).unwrap();
-            rel_path.to_string_lossy().to_string()
-        })
-        .collect::<std::collections::HashSet<_>>() // Remove duplicates
-        .into_iter()
-        .collect();
+    fn library_name(&self) -> &str {
+        &self.library_name
+    }
 
-    let jni_code = generate_jni_bridge(&functions, &kotlin_package, &source_files);
+    fn generate_bridge(&mut self, config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()> {
+        let functions = &self.functions;
+        let types = &self.types;
+        let library_name = &self.library_name;
+
+        // Generate PascalCase object name for Kotlin
+        // Derive from the source file name of the first function
+        let object_name = source_object_name(functions, library_name);
+
+        // Generate Kotlin bindings in ffi.cpp subpackage (matches Rust FFI pattern)
+        let kotlin_dir = build_dir.join("generated/kotlin");
+        let package_path = config.android.package.replace('.', "/");
+        let kotlin_package_dir = kotlin_dir.join(&package_path).join("ffi/cpp");
+
+        fs::create_dir_all(&kotlin_package_dir)
+            .context("Failed to create Kotlin output directory")?;
+
+        let kotlin_package = format!("{}.ffi.cpp", config.android.package);
+        let kotlin_code = generate_kotlin_object(
+            functions,
+            &kotlin_package,
+            library_name,
+            &object_name,
+        );
 
-    let jni_file = jni_dir.join(format!("{}_bridge.cpp", library_name));
-    fs::write(&jni_file, jni_code)
-        .context(format!("Failed to write JNI bridge: {}", jni_file.display()))?;
+        let kotlin_file = kotlin_package_dir.join(format!("{}.kt", object_name));
+        fs::write(&kotlin_file, kotlin_code)
+            .context(format!("Failed to write Kotlin binding: {}", kotlin_file.display()))?;
 
-    // 4. CMakeLists.txt: Use user-provided or auto-generate
-    let cmake_dir = build_dir.join("cmake");
-    fs::create_dir_all(&cmake_dir)
-        .context("Failed to create CMake output directory")?;
+        if !types.is_empty() {
+            let types_file = kotlin_package_dir.join(format!("{}Types.kt", object_name));
+            fs::write(&types_file, generate_kotlin_types(types, &kotlin_package))
+                .context(format!("Failed to write Kotlin FFI types: {}", types_file.display()))?;
+        }
 
-    let cmake_file = cmake_dir.join("CMakeLists.txt");
-    let user_cmake = ffi_dir.join("cpp/CMakeLists.txt");
+        // Generate JNI bridge
+        let jni_dir = build_dir.join("generated/jni");
+        fs::create_dir_all(&jni_dir)
+            .context("Failed to create JNI output directory")?;
 
-    if user_cmake.exists() {
-        // User provided their own CMakeLists.txt - copy it
-        fs::copy(&user_cmake, &cmake_file)
-            .context(format!("Failed to copy user CMakeLists.txt from {}", user_cmake.display()))?;
-    } else {
-        // Auto-generate CMakeLists.txt with absolute paths
-        let project_root_abs = ffi_dir.parent().unwrap().canonicalize()
-            .context("Failed to canonicalize project root")?;
-
-        // Convert relative source paths to absolute
-        let absolute_source_files: Vec<String> = source_files
+        let source_files: Vec<String> = functions
             .iter()
-            .map(|rel_path| {
-                project_root_abs.join(rel_path).display().to_string()
+            .map(|f| {
+                // Get relative path from project root
+                let rel_path = f.source_file.strip_prefix(ffi_dir.parent().unwrap()).unwrap();
+                rel_path.to_string_lossy().to_string()
             })
+            .collect::<std::collections::HashSet<_>>() // Remove duplicates
+            .into_iter()
             .collect();
 
-        let cmake_code = generate_cmake(
-            &library_name,
-            &absolute_source_files,
+        let jni_code = generate_jni_bridge(functions, types, &kotlin_package, &object_name);
+
+        let jni_file = jni_dir.join(format!("{}_bridge.cpp", library_name));
+        fs::write(&jni_file, jni_code)
+            .context(format!("Failed to write JNI bridge: {}", jni_file.display()))?;
+
+        // CMakeLists.txt: Use user-provided or auto-generate
+        let cmake_dir = build_dir.join("cmake");
+        fs::create_dir_all(&cmake_dir)
+            .context("Failed to create CMake output directory")?;
+
+        let cmake_file = cmake_dir.join("CMakeLists.txt");
+        let user_cmake = ffi_dir.join("cpp/CMakeLists.txt");
+
+        if user_cmake.exists() {
+            // User provided their own CMakeLists.txt - copy it
+            fs::copy(&user_cmake, &cmake_file)
+                .context(format!("Failed to copy user CMakeLists.txt from {}", user_cmake.display()))?;
+        } else {
+            // Auto-generate CMakeLists.txt with absolute paths
+            let project_root_abs = ffi_dir.parent().unwrap().canonicalize()
+                .context("Failed to canonicalize project root")?;
+
+            // Convert relative source paths to absolute
+            let absolute_source_files: Vec<String> = source_files
+                .iter()
+                .map(|rel_path| {
+                    project_root_abs.join(rel_path).display().to_string()
+                })
+                .collect();
+
+            let cmake_code = generate_cmake(
+                library_name,
+                &absolute_source_files,
+                &jni_file.canonicalize()
+                    .context("Failed to canonicalize JNI bridge path")?
+                    .display()
+                    .to_string(),
+                &config.ffi.cpp.standard,
+                &config.ffi.cpp.flags,
+                &config.ffi.cpp.libraries,
+            );
+
+            fs::write(&cmake_file, cmake_code)
+                .context(format!("Failed to write CMakeLists.txt: {}", cmake_file.display()))?;
+        }
+
+        self.inputs = vec![ffi_dir.join("cpp"), jni_file, cmake_file.clone()];
+        self.cmake_file = cmake_file;
+
+        Ok(())
+    }
+
+    fn build(&self, config: &Config, _ffi_dir: &Path, build_dir: &Path) -> Result<()> {
+        // Build native library using CMake + NDK
+        build_native_library(config, &self.library_name, &self.cmake_file, &self.inputs, build_dir)
+    }
+}
+
+/// C sources in `src/ffi/c/`, declared with `// @ffi` in their headers and built with CMake
+#[derive(Default)]
+struct CFfi {
+    functions: Vec<CppFfiFunction>,
+    library_name: String,
+    /// CMakeLists.txt the library is built from, once the bridge is generated
+    cmake_file: PathBuf,
+    /// Sources and generated files the library is built from
+    inputs: Vec<PathBuf>,
+}
+
+impl FfiLanguage for CFfi {
+    fn dir(&self) -> &'static str {
+        "c"
+    }
+
+    fn discover(&mut self, config: &Config, ffi_dir: &Path) -> Result<bool> {
+        eprintln!("{}", "Discovering C FFI functions...".dimmed());
+        self.functions = discover_c_ffi(ffi_dir)
+            .context("Failed to discover C FFI functions")?;
+
+        if self.functions.is_empty() {
+            eprintln!("{}", "No C FFI functions found".yellow());
+            return Ok(false);
+        }
+
+        eprintln!("{} {} C FFI function(s)", "Found".green(), self.functions.len());
+
+        // Get library name with precedence: whitehall.toml > CMakeLists.txt > <project.name>_c
+        self.library_name = get_c_library_name(config, ffi_dir);
+        eprintln!("{} {}", "Library name:".dimmed(), self.library_name);
+
+        Ok(true)
+    }
+
+    fn library_name(&self) -> &str {
+        &self.library_name
+    }
+
+    fn generate_bridge(&mut self, config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()> {
+        let library_name = &self.library_name;
+        let object_name = source_object_name(&self.functions, library_name);
+
+        // Generate Kotlin bindings in ffi.c subpackage
+        let package_path = config.android.package.replace('.', "/");
+        let kotlin_package_dir = build_dir.join("generated/kotlin").join(&package_path).join("ffi/c");
+        fs::create_dir_all(&kotlin_package_dir)
+            .context("Failed to create Kotlin output directory")?;
+
+        let kotlin_package = format!("{}.ffi.c", config.android.package);
+        let kotlin_file = kotlin_package_dir.join(format!("{}.kt", object_name));
+        fs::write(&kotlin_file, generate_kotlin_object(&self.functions, &kotlin_package, library_name, &object_name))
+            .context(format!("Failed to write Kotlin binding: {}", kotlin_file.display()))?;
+
+        // Generate C JNI bridge
+        let jni_dir = build_dir.join("generated/jni");
+        fs::create_dir_all(&jni_dir)
+            .context("Failed to create JNI output directory")?;
+
+        let jni_file = jni_dir.join(format!("{}_bridge.c", library_name));
+        fs::write(&jni_file, generate_c_jni_bridge(&self.functions, &kotlin_package, &object_name))
+            .context(format!("Failed to write JNI bridge: {}", jni_file.display()))?;
+
+        // CMakeLists.txt: Use user-provided or auto-generate
+        let cmake_dir = build_dir.join("cmake/c");
+        fs::create_dir_all(&cmake_dir)
+            .context("Failed to create CMake output directory")?;
+
+        let cmake_file = cmake_dir.join("CMakeLists.txt");
+        let user_cmake = ffi_dir.join("c/CMakeLists.txt");
+
+        if user_cmake.exists() {
+            fs::copy(&user_cmake, &cmake_file)
+                .context(format!("Failed to copy user CMakeLists.txt from {}", user_cmake.display()))?;
+        } else {
+            let c_dir = ffi_dir.join("c").canonicalize()
+                .context("Failed to canonicalize C source directory")?;
+            let source_files: Vec<String> = c_source_files(ffi_dir)?
+                .iter()
+                .map(|path| c_dir.join(path.file_name().unwrap()).display().to_string())
+                .collect();
+
+            let cmake_code = generate_c_cmake(
+                library_name,
+                &source_files,
+                &jni_file.canonicalize()
+                    .context("Failed to canonicalize JNI bridge path")?
+                    .display()
+                    .to_string(),
+                &c_dir.display().to_string(),
+                &config.ffi.c.standard,
+                &config.ffi.c.flags,
+                &config.ffi.c.libraries,
+            );
+
+            fs::write(&cmake_file, cmake_code)
+                .context(format!("Failed to write CMakeLists.txt: {}", cmake_file.display()))?;
+        }
+
+        self.inputs = vec![ffi_dir.join("c"), jni_file, cmake_file.clone()];
+        self.cmake_file = cmake_file;
+
+        Ok(())
+    }
+
+    fn build(&self, config: &Config, _ffi_dir: &Path, build_dir: &Path) -> Result<()> {
+        build_native_library(config, &self.library_name, &self.cmake_file, &self.inputs, build_dir)
+    }
+}
+
+/// Zig sources in `src/ffi/zig/`, compiled per ABI by `zig build-lib` into static
+/// libraries that a C JNI bridge links into `lib<name>.so`
+#[derive(Default)]
+struct ZigFfi {
+    functions: Vec<CppFfiFunction>,
+    library_name: String,
+    /// Sources declaring `@ffi` functions; the files they `@import` are compiled in
+    roots: Vec<PathBuf>,
+    /// CMakeLists.txt the library is built from, once the bridge is generated
+    cmake_file: PathBuf,
+    /// Sources and generated files the library is built from
+    inputs: Vec<PathBuf>,
+}
+
+impl FfiLanguage for ZigFfi {
+    fn dir(&self) -> &'static str {
+        "zig"
+    }
+
+    fn discover(&mut self, config: &Config, ffi_dir: &Path) -> Result<bool> {
+        eprintln!("{}", "Discovering Zig FFI functions...".dimmed());
+        self.functions = discover_zig_ffi(ffi_dir)
+            .context("Failed to discover Zig FFI functions")?;
+
+        if self.functions.is_empty() {
+            eprintln!("{}", "No Zig FFI functions found".yellow());
+            return Ok(false);
+        }
+
+        eprintln!("{} {} Zig FFI function(s)", "Found".green(), self.functions.len());
+
+        self.roots = zig_source_files(ffi_dir)?
+            .into_iter()
+            .filter(|path| self.functions.iter().any(|function| &function.source_file == path))
+            .collect();
+
+        self.library_name = get_zig_library_name(config);
+        eprintln!("{} {}", "Library name:".dimmed(), self.library_name);
+
+        Ok(true)
+    }
+
+    fn library_name(&self) -> &str {
+        &self.library_name
+    }
+
+    fn generate_bridge(&mut self, config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()> {
+        let library_name = &self.library_name;
+        let object_name = source_object_name(&self.functions, library_name);
+
+        // Generate Kotlin bindings in ffi.zig subpackage
+        let package_path = config.android.package.replace('.', "/");
+        let kotlin_package_dir = build_dir.join("generated/kotlin").join(&package_path).join("ffi/zig");
+        fs::create_dir_all(&kotlin_package_dir)
+            .context("Failed to create Kotlin output directory")?;
+
+        let kotlin_package = format!("{}.ffi.zig", config.android.package);
+        let kotlin_file = kotlin_package_dir.join(format!("{}.kt", object_name));
+        fs::write(&kotlin_file, generate_kotlin_object(&self.functions, &kotlin_package, library_name, &object_name))
+            .context(format!("Failed to write Kotlin binding: {}", kotlin_file.display()))?;
+
+        // One C header per Zig source, declaring its exports for the bridge
+        let header_dir = build_dir.join("generated/jni/zig");
+        fs::create_dir_all(&header_dir)
+            .context("Failed to create Zig header directory")?;
+
+        let mut declared = Vec::new();
+        for root in &self.roots {
+            let stem = root.file_stem().and_then(|s| s.to_str()).unwrap_or(library_name);
+            let header = header_dir.join(format!("{}.h", stem));
+            let functions: Vec<CppFfiFunction> = self
+                .functions
+                .iter()
+                .filter(|function| &function.source_file == root)
+                .map(|function| CppFfiFunction { source_file: header.clone(), ..function.clone() })
+                .collect();
+            fs::write(&header, generate_c_header(&functions))
+                .context(format!("Failed to write C header: {}", header.display()))?;
+            declared.extend(functions);
+        }
+
+        // Generate C JNI bridge
+        let jni_file = build_dir.join("generated/jni").join(format!("{}_bridge.c", library_name));
+        fs::write(&jni_file, generate_c_jni_bridge(&declared, &kotlin_package, &object_name))
+            .context(format!("Failed to write JNI bridge: {}", jni_file.display()))?;
+
+        let cmake_dir = build_dir.join("cmake/zig");
+        fs::create_dir_all(&cmake_dir)
+            .context("Failed to create CMake output directory")?;
+
+        let lib_dir = zig_lib_dir(build_dir, library_name);
+        fs::create_dir_all(&lib_dir)
+            .context("Failed to create Zig output directory")?;
+        let lib_dir = lib_dir.canonicalize()
+            .context("Failed to canonicalize Zig output directory")?;
+        let static_libraries: Vec<String> = self
+            .roots
+            .iter()
+            .map(|root| lib_dir.join("${ANDROID_ABI}").join(zig_archive_name(root)).display().to_string())
+            .collect();
+
+        let cmake_file = cmake_dir.join("CMakeLists.txt");
+        let cmake_code = generate_zig_cmake(
+            library_name,
             &jni_file.canonicalize()
                 .context("Failed to canonicalize JNI bridge path")?
                 .display()
                 .to_string(),
-            &config.ffi.cpp.standard,
-            &config.ffi.cpp.flags,
-            &config.ffi.cpp.libraries,
+            &header_dir.canonicalize()
+                .context("Failed to canonicalize Zig header directory")?
+                .display()
+                .to_string(),
+            &static_libraries,
         );
-
         fs::write(&cmake_file, cmake_code)
             .context(format!("Failed to write CMakeLists.txt: {}", cmake_file.display()))?;
+
+        // The archives are inputs too, so a new zig version or flag rebuilds the library
+        self.inputs = vec![ffi_dir.join("zig"), jni_file, header_dir, cmake_file.clone(), lib_dir];
+        self.cmake_file = cmake_file;
+
+        Ok(())
     }
 
-    // 5. Build native library using CMake + NDK
-    let project_root = ffi_dir.parent().unwrap();
-    build_native_library(
-        &library_name,
-        &cmake_file,
-        project_root,
-        build_dir,
-        config.android.min_sdk,
-    )?;
+    fn build(&self, config: &Config, _ffi_dir: &Path, build_dir: &Path) -> Result<()> {
+        let zig_available = Command::new("zig")
+            .arg("version")
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !zig_available {
+            anyhow::bail!("Zig FFI needs the `zig` compiler (0.14 or newer) on PATH. See https://ziglang.org/download/");
+        }
 
-    Ok(())
+        println!("{}", format!("Compiling Zig sources: {}", self.library_name).cyan());
+
+        let abis = selected_abis(config, ANDROID_ABIS.iter().map(|(abi, _)| *abi).collect())?;
+        for abi in &abis {
+            for root in &self.roots {
+                let output = zig_build_lib_command(config, root, abi, build_dir, &self.library_name)?
+                    .output()
+                    .context(format!("Failed to run zig build-lib for {}", abi))?;
+
+                if !output.status.success() {
+                    eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+                    anyhow::bail!("zig build-lib failed for {} ({})", root.display(), abi);
+                }
+            }
+        }
+
+        build_native_library(config, &self.library_name, &self.cmake_file, &self.inputs, build_dir)
+    }
+}
+
+/// Where the Zig static libraries of `library_name` are written, one directory per ABI
+fn zig_lib_dir(build_dir: &Path, library_name: &str) -> PathBuf {
+    build_dir.join("zig-build").join(library_name)
+}
+
+/// Static library `zig build-lib` writes for a root source (`math.zig` -> `libmath.a`)
+fn zig_archive_name(root: &Path) -> String {
+    format!("lib{}.a", root.file_stem().and_then(|s| s.to_str()).unwrap_or("zig"))
+}
+
+/// Zig target triple and CPU for an Android ABI
+fn zig_target(abi: &str) -> Result<(&'static str, &'static str)> {
+    match abi {
+        "arm64-v8a" => Ok(("aarch64-linux-android", "baseline")),
+        "armeabi-v7a" => Ok(("arm-linux-androideabi", "generic+v7a")),
+        "x86_64" => Ok(("x86_64-linux-android", "baseline")),
+        "x86" => Ok(("x86-linux-android", "baseline")),
+        _ => anyhow::bail!("No Zig target for ABI '{}'", abi),
+    }
+}
+
+/// `zig build-lib` compiling `root` into a position-independent static library for `abi`
+fn zig_build_lib_command(config: &Config, root: &Path, abi: &str, build_dir: &Path, library_name: &str) -> Result<Command> {
+    let (target, cpu) = zig_target(abi)?;
+    let out_dir = zig_lib_dir(build_dir, library_name).join(abi);
+    fs::create_dir_all(&out_dir)
+        .context(format!("Failed to create Zig output directory for {}", abi))?;
+
+    let mut cmd = Command::new("zig");
+    cmd.arg("build-lib")
+        .arg(root)
+        .arg("-target").arg(target)
+        .arg(format!("-mcpu={}", cpu))
+        .arg("-O").arg(&config.ffi.zig.optimize)
+        .arg("-fPIC")
+        // Keep zig's cache out of the project root
+        .arg("--cache-dir").arg(build_dir.join("zig-cache"))
+        .arg(format!("-femit-bin={}", out_dir.join(zig_archive_name(root)).display()))
+        .args(&config.ffi.zig.flags);
+    Ok(cmd)
+}
+
+/// Kotlin object name for C/C++ functions: the first function's source file name
+/// in PascalCase, or the library name
+fn source_object_name(functions: &[CppFfiFunction], library_name: &str) -> String {
+    let file_stem = functions
+        .first()
+        .and_then(|function| function.source_file.file_stem())
+        .and_then(|s| s.to_str())
+        .unwrap_or(library_name);
+    to_pascal_case(file_stem)
 }
 
 /// Build native library using CMake and Android NDK
 ///
-/// Compiles C++ code to .so files for all Android ABIs
+/// Compiles C++ code to .so files for all Android ABIs (or just `config.ffi.abi`),
+/// skipping ABIs whose inputs haven't changed since their last build
 ///
 /// # Arguments
+/// * `config` - Project configuration (`[ffi]` settings, min SDK)
 /// * `library_name` - Name of the native library (used for .so filename)
 /// * `cmake_file` - Path to CMakeLists.txt
-/// * `project_root` - Root directory of the project
+/// * `inputs` - Sources and generated files the library is built from
 /// * `build_dir` - Build output directory
-/// * `min_sdk` - Minimum Android SDK version
 fn build_native_library(
+    config: &Config,
     library_name: &str,
     cmake_file: &Path,
-    _project_root: &Path,
+    inputs: &[PathBuf],
     build_dir: &Path,
-    min_sdk: u32,
 ) -> Result<()> {
     println!("{}", format!("Building native library: {}", library_name).cyan());
 
@@ -296,26 +731,33 @@ fn build_native_library(
     // Ensure NDK is installed
     let ndk_path = toolchain.ensure_ndk()
         .context("Failed to ensure Android NDK")?;
+    let cmake_bin = toolchain.ensure_cmake()?;
 
-    // Android ABIs to build for (cover most devices)
-    let abis = vec![
-        "arm64-v8a",     // Modern 64-bit ARM (most common)
-        "armeabi-v7a",   // Legacy 32-bit ARM
-        "x86_64",        // 64-bit x86 (emulators)
-        "x86",           // 32-bit x86 (old emulators)
-    ];
+    let so_name = format!("lib{}.so", library_name);
+    let fingerprint = ffi_fingerprint(
+        inputs,
+        config,
+        &format!("{} {}", ndk_path.display(), cmake_bin.display()),
+    )?;
+
+    let abis = selected_abis(config, ANDROID_ABIS.iter().map(|(abi, _)| *abi).collect())?;
 
     // Build for each ABI
     for abi in &abis {
+        if is_up_to_date(build_dir, &so_name, abi, &fingerprint) {
+            println!("    {} {}", "✓".green(), format!("{} up to date", abi).dimmed());
+            continue;
+        }
+
         println!("  {} {}", "→".dimmed(), format!("Building for {}", abi).dimmed());
 
-        // Create build directory for this ABI
-        let abi_build_dir = build_dir.join("cmake-build").join(abi);
+        // Create build directory for this library and ABI
+        let abi_build_dir = build_dir.join("cmake-build").join(library_name).join(abi);
         fs::create_dir_all(&abi_build_dir)
             .context(format!("Failed to create build directory for {}", abi))?;
 
         // Run CMake configure
-        let mut cmake_cmd = toolchain.cmake_cmd(&ndk_path, abi, min_sdk)?;
+        let mut cmake_cmd = toolchain.cmake_cmd(&ndk_path, abi, config.android.min_sdk)?;
 
         // Set source directory (parent of CMakeLists.txt)
         let source_dir = cmake_file.parent().unwrap();
@@ -337,7 +779,7 @@ fn build_native_library(
         }
 
         // Run CMake build
-        let build_output = Command::new(toolchain.ensure_cmake()?)
+        let build_output = Command::new(&cmake_bin)
             .arg("--build")
             .arg(&abi_build_dir)
             .arg("--config").arg("Release")
@@ -353,7 +795,7 @@ fn build_native_library(
         }
 
         // Copy .so file to jniLibs
-        let so_file = abi_build_dir.join(format!("lib{}.so", library_name));
+        let so_file = abi_build_dir.join(&so_name);
         if !so_file.exists() {
             anyhow::bail!(
                 "Built library not found: {}. Expected at: {}",
@@ -362,79 +804,128 @@ fn build_native_library(
             );
         }
 
-        // Destination: build/app/src/main/jniLibs/{abi}/lib{name}.so
-        let jnilib_dir = build_dir.join("app/src/main/jniLibs").join(abi);
-        fs::create_dir_all(&jnilib_dir)
-            .context(format!("Failed to create jniLibs directory for {}", abi))?;
-
-        let dest_so = jnilib_dir.join(format!("lib{}.so", library_name));
-        fs::copy(&so_file, &dest_so)
-            .context(format!("Failed to copy .so file for {}", abi))?;
+        install_library(&so_file, build_dir, abi)?;
+        record_fingerprint(build_dir, &so_name, abi, &fingerprint)?;
 
         println!("    {} {}", "✓".green(), format!("{} built successfully", abi).dimmed());
     }
 
-    println!("{}", format!("Native library '{}' built successfully for {} ABIs", library_name, abis.len()).green());
+    println!("{}", format!("Native library '{}' ready for {} ABI(s)", library_name, abis.len()).green());
 
     Ok(())
 }
 
-/// Build Rust FFI components
-fn build_rust_ffi(config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()> {
-    // 1. Discover FFI functions
-    let functions = discover_rust_ffi(ffi_dir)
-        .context("Failed to discover Rust FFI functions")?;
+/// Rust crate in `src/ffi/rust/`, built with Cargo
+#[derive(Default)]
+struct RustFfi {
+    functions: Vec<RustFfiFunction>,
+    classes: Vec<RustFfiClass>,
+    types: Vec<RustFfiType>,
+    library_name: String,
+    /// Sources and generated files the library is built from
+    inputs: Vec<PathBuf>,
+}
 
-    if functions.is_empty() {
-        return Ok(());
+impl FfiLanguage for RustFfi {
+    fn dir(&self) -> &'static str {
+        "rust"
+    }
+
+    fn discover(&mut self, config: &Config, ffi_dir: &Path) -> Result<bool> {
+        // Discover FFI functions and #[ffi] impl blocks
+        self.functions = discover_rust_ffi(ffi_dir)
+            .context("Failed to discover Rust FFI functions")?;
+        self.classes = discover_rust_ffi_classes(ffi_dir)
+            .context("Failed to discover Rust FFI impl blocks")?;
+
+        if self.functions.is_empty() && self.classes.is_empty() {
+            return Ok(false);
+        }
+
+        self.types = discover_rust_ffi_types(ffi_dir)
+            .context("Failed to discover Rust FFI types")?;
+        check_rust_ffi_classes(&self.classes, &self.functions, &self.types)?;
+        check_rust_ffi_types(&self.functions, &self.types)?;
+
+        // Get library name with precedence: whitehall.toml > Cargo.toml > project.name
+        self.library_name = get_rust_library_name(config, ffi_dir);
+
+        Ok(true)
     }
 
-    // Get library name with precedence: whitehall.toml > Cargo.toml > project.name
-    let library_name = get_rust_library_name(config, ffi_dir);
+    fn library_name(&self) -> &str {
+        &self.library_name
+    }
 
-    // Generate PascalCase object name for Kotlin
-    let object_name = to_pascal_case(&library_name);
+    fn generate_bridge(&mut self, config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()> {
+        let (functions, classes, types) = (&self.functions, &self.classes, &self.types);
+        let library_name = self.library_name.clone();
 
-    // 2. Generate Kotlin bindings in ffi.rust subpackage
-    let kotlin_dir = build_dir.join("generated/kotlin");
-    let package_path = config.android.package.replace('.', "/");
-    let kotlin_package_dir = kotlin_dir.join(&package_path).join("ffi/rust");
+        // Generate PascalCase object name for Kotlin
+        let object_name = to_pascal_case(&library_name);
 
-    fs::create_dir_all(&kotlin_package_dir)
-        .context("Failed to create Kotlin output directory")?;
+        // 1. Generate Kotlin bindings in ffi.rust subpackage
+        let kotlin_dir = build_dir.join("generated/kotlin");
+        let package_path = config.android.package.replace('.', "/");
+        let kotlin_package_dir = kotlin_dir.join(&package_path).join("ffi/rust");
 
-    let kotlin_package = format!("{}.ffi.rust", config.android.package);
-    let kotlin_code = generate_kotlin_object_rust(
-        &functions,
-        &kotlin_package,
-        &library_name,
-        &object_name,
-    );
+        fs::create_dir_all(&kotlin_package_dir)
+            .context("Failed to create Kotlin output directory")?;
 
-    let kotlin_file = kotlin_package_dir.join(format!("{}.kt", object_name));
-    fs::write(&kotlin_file, kotlin_code)
-        .context(format!("Failed to write Kotlin binding: {}", kotlin_file.display()))?;
+        let kotlin_package = format!("{}.ffi.rust", config.android.package);
+        if !functions.is_empty() {
+            let kotlin_code = generate_kotlin_object_rust(
+                functions,
+                &kotlin_package,
+                &library_name,
+                &object_name,
+            );
 
-    // 3. Generate Rust JNI bridge to build/generated/jni/rust/
-    let jni_dir = build_dir.join("generated/jni/rust");
-    fs::create_dir_all(&jni_dir)
-        .context("Failed to create Rust JNI output directory")?;
+            let kotlin_file = kotlin_package_dir.join(format!("{}.kt", object_name));
+            fs::write(&kotlin_file, kotlin_code)
+                .context(format!("Failed to write Kotlin binding: {}", kotlin_file.display()))?;
+        }
 
-    let rust_bridge_code = generate_rust_bridge(&functions, &kotlin_package);
+        // One Kotlin class per #[ffi] impl, wrapping a native handle
+        for class in classes {
+            if !functions.is_empty() && class.name == object_name {
+                anyhow::bail!(
+                    "#[ffi] impl '{}' at {} has the same name as the generated '{}' object. Rename the struct or set [ffi.rust] library_name",
+                    class.name, class.source_file.display(), object_name
+                );
+            }
+            let class_file = kotlin_package_dir.join(format!("{}.kt", class.name));
+            let class_code = generate_kotlin_class_rust(class, &kotlin_package, &library_name, config.android.min_sdk);
+            fs::write(&class_file, class_code)
+                .context(format!("Failed to write Kotlin class: {}", class_file.display()))?;
+        }
 
-    // Write generated bridge to build/ directory (not src/)
-    let bridge_file = jni_dir.join("jni_bridge.rs");
-    fs::write(&bridge_file, rust_bridge_code)
-        .context(format!("Failed to write Rust JNI bridge: {}", bridge_file.display()))?;
+        if !types.is_empty() {
+            let types_file = kotlin_package_dir.join(format!("{}Types.kt", object_name));
+            fs::write(&types_file, generate_kotlin_types_rust(types, &kotlin_package))
+                .context(format!("Failed to write Kotlin FFI types: {}", types_file.display()))?;
+        }
+
+        // 2. Generate Rust JNI bridge to build/generated/jni/rust/
+        let jni_dir = build_dir.join("generated/jni/rust");
+        fs::create_dir_all(&jni_dir)
+            .context("Failed to create Rust JNI output directory")?;
+
+        let rust_bridge_code = generate_rust_bridge(functions, types, classes, &kotlin_package);
 
-    // 4. Generate local ffi_macro crate (instead of fragile path dependencies)
-    let rust_project_dir = ffi_dir.join("rust");
-    let ffi_macro_dir = build_dir.join("generated/ffi_macro");
-    fs::create_dir_all(&ffi_macro_dir.join("src"))
-        .context("Failed to create ffi_macro directory")?;
+        // Write generated bridge to build/ directory (not src/)
+        let bridge_file = jni_dir.join("jni_bridge.rs");
+        fs::write(&bridge_file, rust_bridge_code)
+            .context(format!("Failed to write Rust JNI bridge: {}", bridge_file.display()))?;
 
-    // Generate ffi_macro/Cargo.toml
-    let ffi_macro_cargo_toml = r#"[package]
+        // 3. Generate local ffi_macro crate (instead of fragile path dependencies)
+        let rust_project_dir = ffi_dir.join("rust");
+        let ffi_macro_dir = build_dir.join("generated/ffi_macro");
+        fs::create_dir_all(&ffi_macro_dir.join("src"))
+            .context("Failed to create ffi_macro directory")?;
+
+        // Generate ffi_macro/Cargo.toml
+        let ffi_macro_cargo_toml = r#"[package]
 name = "whitehall"
 version = "0.1.0"
 edition = "2021"
@@ -444,11 +935,11 @@ proc-macro = true
 
 [dependencies]
 "#;
-    fs::write(ffi_macro_dir.join("Cargo.toml"), ffi_macro_cargo_toml)
-        .context("Failed to write ffi_macro/Cargo.toml")?;
+        fs::write(ffi_macro_dir.join("Cargo.toml"), ffi_macro_cargo_toml)
+            .context("Failed to write ffi_macro/Cargo.toml")?;
 
-    // Generate ffi_macro/src/lib.rs
-    let ffi_macro_lib = r#"//! Whitehall FFI Macro (auto-generated)
+        // Generate ffi_macro/src/lib.rs
+        let ffi_macro_lib = r#"//! Whitehall FFI Macro (auto-generated)
 //!
 //! Provides the #[ffi] attribute for marking functions to be exposed via JNI.
 //! This is a marker attribute that is detected by the Whitehall build system
@@ -471,55 +962,61 @@ pub fn ffi(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // Pass through unchanged - this is just a marker for whitehall's parser
     item
 }
-"#;
-    fs::write(ffi_macro_dir.join("src/lib.rs"), ffi_macro_lib)
-        .context("Failed to write ffi_macro/src/lib.rs")?;
-
-    // 5. Update main Cargo.toml to use local ffi_macro from build directory
-    let cargo_toml_path = rust_project_dir.join("Cargo.toml");
-    if cargo_toml_path.exists() {
-        let cargo_content = fs::read_to_string(&cargo_toml_path)?;
-
-        // Compute relative path from src/ffi/rust to build/generated/ffi_macro
-        // From src/ffi/rust, go up to project root (../../../) then to build/generated/ffi_macro
-        let ffi_macro_rel_path = format!("../../../{}/generated/ffi_macro", &config.build.output_dir);
-
-        // Replace any existing whitehall or whitehall-ffi-macro dependency with local path
-        let whitehall_dep = format!(r#"whitehall = {{ path = "{}" }}"#, ffi_macro_rel_path);
-
-        let updated_cargo = if cargo_content.contains("whitehall-ffi-macro") || cargo_content.contains(r#"whitehall = "#) {
-            // Replace existing dependency line (handle both old and new names)
-            let re1 = regex::Regex::new(r#"whitehall-ffi-macro\s*=\s*\{[^}]+\}"#).unwrap();
-            let re2 = regex::Regex::new(r#"whitehall\s*=\s*\{[^}]+\}"#).unwrap();
 
-            let temp = re1.replace(&cargo_content, whitehall_dep.as_str()).to_string();
-            re2.replace(&temp, whitehall_dep.as_str()).to_string()
-        } else {
-            // Add dependency if missing
-            cargo_content.replace(
-                "[dependencies]",
-                &format!("[dependencies]\n{}", whitehall_dep)
-            )
-        };
-
-        fs::write(&cargo_toml_path, updated_cargo)
-            .context("Failed to update Cargo.toml with local ffi_macro dependency")?;
-    }
+/// Marker attribute for traits implemented in Kotlin and passed to native code
+#[proc_macro_attribute]
+pub fn ffi_callback(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+"#;
+        fs::write(ffi_macro_dir.join("src/lib.rs"), ffi_macro_lib)
+            .context("Failed to write ffi_macro/src/lib.rs")?;
+
+        // 4. Update main Cargo.toml to use local ffi_macro from build directory
+        let cargo_toml_path = rust_project_dir.join("Cargo.toml");
+        if cargo_toml_path.exists() {
+            let cargo_content = fs::read_to_string(&cargo_toml_path)?;
+
+            // Compute relative path from src/ffi/rust to build/generated/ffi_macro
+            // From src/ffi/rust, go up to project root (../../../) then to build/generated/ffi_macro
+            let ffi_macro_rel_path = format!("../../../{}/generated/ffi_macro", &config.build.output_dir);
+
+            // Replace any existing whitehall or whitehall-ffi-macro dependency with local path
+            let whitehall_dep = format!(r#"whitehall = {{ path = "{}" }}"#, ffi_macro_rel_path);
+
+            let updated_cargo = if cargo_content.contains("whitehall-ffi-macro") || cargo_content.contains(r#"whitehall = "#) {
+                // Replace existing dependency line (handle both old and new names)
+                let re1 = regex::Regex::new(r#"whitehall-ffi-macro\s*=\s*\{[^}]+\}"#).unwrap();
+                let re2 = regex::Regex::new(r#"whitehall\s*=\s*\{[^}]+\}"#).unwrap();
+
+                let temp = re1.replace(&cargo_content, whitehall_dep.as_str()).to_string();
+                re2.replace(&temp, whitehall_dep.as_str()).to_string()
+            } else {
+                // Add dependency if missing
+                cargo_content.replace(
+                    "[dependencies]",
+                    &format!("[dependencies]\n{}", whitehall_dep)
+                )
+            };
+
+            fs::write(&cargo_toml_path, updated_cargo)
+                .context("Failed to update Cargo.toml with local ffi_macro dependency")?;
+        }
 
-    // 6. Generate build.rs to copy bridge to OUT_DIR during Rust build
-    let build_rs_path = rust_project_dir.join("build.rs");
+        // 5. Generate build.rs to copy bridge to OUT_DIR during Rust build
+        let build_rs_path = rust_project_dir.join("build.rs");
 
-    // Get absolute path to generated bridge
-    // ffi_dir is src/ffi, so we need to go up twice to get project root
-    let project_root = ffi_dir.parent().unwrap().parent().unwrap();
-    let bridge_abs = project_root
-        .canonicalize()
-        .context("Failed to get absolute project root path")?
-        .join(&config.build.output_dir)
-        .join("generated/jni/rust/jni_bridge.rs");
+        // Get absolute path to generated bridge
+        // ffi_dir is src/ffi, so we need to go up twice to get project root
+        let project_root = ffi_dir.parent().unwrap().parent().unwrap();
+        let bridge_abs = project_root
+            .canonicalize()
+            .context("Failed to get absolute project root path")?
+            .join(&config.build.output_dir)
+            .join("generated/jni/rust/jni_bridge.rs");
 
-    let build_rs_content = format!(
-        r#"// Auto-generated build script for Whitehall FFI
+        let build_rs_content = format!(
+            r#"// Auto-generated build script for Whitehall FFI
 // This copies the generated JNI bridge from build/ to OUT_DIR
 
 use std::{{env, fs, path::Path}};
@@ -537,70 +1034,74 @@ fn main() {{
     println!("cargo:rerun-if-changed={{}}", bridge_src.display());
 }}
 "#,
-        bridge_abs.display()
-    );
+            bridge_abs.display()
+        );
 
-    fs::write(&build_rs_path, build_rs_content)
-        .context(format!("Failed to write build.rs: {}", build_rs_path.display()))?;
+        fs::write(&build_rs_path, build_rs_content)
+            .context(format!("Failed to write build.rs: {}", build_rs_path.display()))?;
 
-    // 7. Ensure lib.rs includes the generated JNI bridge code directly
-    let rust_src_dir = ffi_dir.join("rust/src");
-    if !rust_src_dir.exists() {
-        anyhow::bail!("Rust src directory not found: {}", rust_src_dir.display());
-    }
+        // 6. Ensure lib.rs includes the generated JNI bridge code directly
+        let rust_src_dir = ffi_dir.join("rust/src");
+        if !rust_src_dir.exists() {
+            anyhow::bail!("Rust src directory not found: {}", rust_src_dir.display());
+        }
 
-    let lib_rs = rust_src_dir.join("lib.rs");
-    if lib_rs.exists() {
-        let mut content = fs::read_to_string(&lib_rs)?;
-        let include_directive = r#"include!(concat!(env!("OUT_DIR"), "/jni_bridge.rs"));"#;
-        let mut modified = false;
+        let lib_rs = rust_src_dir.join("lib.rs");
+        if lib_rs.exists() {
+            let mut content = fs::read_to_string(&lib_rs)?;
+            let include_directive = r#"include!(concat!(env!("OUT_DIR"), "/jni_bridge.rs"));"#;
+            let mut modified = false;
 
-        // Remove old stub module declarations if they exist
-        if content.contains("mod jni_bridge;") {
-            content = content.replace("mod jni_bridge;", "");
-            modified = true;
-        }
+            // Remove old stub module declarations if they exist
+            if content.contains("mod jni_bridge;") {
+                content = content.replace("mod jni_bridge;", "");
+                modified = true;
+            }
 
-        // Remove old comments about the bridge module to keep it clean
-        content = content.replace("// JNI bridge module (generated by Whitehall FFI)\n", "");
+            // Remove old comments about the bridge module to keep it clean
+            content = content.replace("// JNI bridge module (generated by Whitehall FFI)\n", "");
 
-        // Add include directive if not present
-        if !content.contains(include_directive) {
-            content = format!("{}\n\n// JNI bridge code (generated by Whitehall FFI)\n{}\n", content.trim_end(), include_directive);
-            modified = true;
-        }
+            // Add include directive if not present
+            if !content.contains(include_directive) {
+                content = format!("{}\n\n// JNI bridge code (generated by Whitehall FFI)\n{}\n", content.trim_end(), include_directive);
+                modified = true;
+            }
 
-        // Write file if any modifications were made
-        if modified {
-            fs::write(&lib_rs, content)?;
+            // Write file if any modifications were made
+            if modified {
+                fs::write(&lib_rs, content)?;
+            }
         }
-    }
 
-    // 9. Build Rust library using cargo
-    build_rust_library(
-        &library_name,
-        &ffi_dir.join("rust"),
-        build_dir,
-        config.android.min_sdk,
-    )?;
+        self.inputs = vec![ffi_dir.join("rust"), bridge_file, ffi_macro_dir];
+        self.inputs.extend(rust_path_dependencies(&rust_project_dir)?);
 
-    Ok(())
+        Ok(())
+    }
+
+    fn build(&self, config: &Config, ffi_dir: &Path, build_dir: &Path) -> Result<()> {
+        // Build Rust library using cargo
+        build_rust_library(config, &self.library_name, &ffi_dir.join("rust"), &self.inputs, build_dir)
+    }
 }
 
 /// Build Rust library using cargo for Android targets
 ///
-/// Compiles Rust code to .so files for all Android ABIs
+/// Compiles Rust code to .so files for the `[ffi.rust] targets` (or just the
+/// target for `config.ffi.abi`), skipping targets whose inputs haven't changed
 ///
 /// # Arguments
+/// * `config` - Project configuration (`[ffi]` settings)
 /// * `library_name` - Name of the Rust library (from Cargo.toml)
 /// * `rust_project_dir` - Path to Rust project directory (contains Cargo.toml)
+/// * `inputs` - Sources and generated files the library is built from
 /// * `build_dir` - Build output directory
-/// * `min_sdk` - Minimum Android SDK version
 fn build_rust_library(
+    config: &Config,
     library_name: &str,
     rust_project_dir: &Path,
+    inputs: &[PathBuf],
     build_dir: &Path,
-    _min_sdk: u32,
 ) -> Result<()> {
     println!("{}", format!("Building Rust library: {}", library_name).cyan());
 
@@ -611,13 +1112,38 @@ fn build_rust_library(
     let ndk_path = toolchain.ensure_ndk()
         .context("Failed to ensure Android NDK")?;
 
-    // Rust target triples for Android
-    let targets = vec![
-        ("aarch64-linux-android", "arm64-v8a"),
-        ("armv7-linux-androideabi", "armeabi-v7a"),
-        ("x86_64-linux-android", "x86_64"),
-        ("i686-linux-android", "x86"),
-    ];
+    let so_name = format!("lib{}.so", library_name.replace('-', "_"));
+    let rustc_version = Command::new("rustc")
+        .arg("--version")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+    let toolchain_version = format!("{} {}", ndk_path.display(), rustc_version);
+    let fingerprint = ffi_fingerprint(inputs, config, &toolchain_version)?;
+
+    // ABIs of the [ffi.rust] targets
+    let mut abis = Vec::new();
+    for target in &config.ffi.rust.targets {
+        // Either the target triple or the ABI name
+        match ANDROID_ABIS.iter().find(|(abi, triple)| triple == target || abi == target) {
+            Some((abi, _)) => abis.push(*abi),
+            None => anyhow::bail!(
+                "Unsupported Rust target '{}' in [ffi.rust] targets. Expected one of: {}",
+                target,
+                ANDROID_ABIS.iter().map(|(_, triple)| *triple).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+    let abis = selected_abis(config, abis)?;
+    let targets: Vec<(&str, &str)> = ANDROID_ABIS
+        .iter()
+        .filter(|(abi, _)| abis.contains(abi) && !is_up_to_date(build_dir, &so_name, abi, &fingerprint))
+        .map(|(abi, target)| (*target, *abi))
+        .collect();
+
+    for abi in abis.iter().filter(|abi| !targets.iter().any(|(_, built)| built == *abi)) {
+        println!("    {} {}", "✓".green(), format!("{} up to date", abi).dimmed());
+    }
 
     // Add Android targets if not already installed
     for (target, _) in &targets {
@@ -697,7 +1223,6 @@ fn build_rust_library(
         }
 
         // Copy .so file to jniLibs
-        let so_name = format!("lib{}.so", library_name.replace('-', "_"));
         let so_file = rust_project_dir
             .join("target")
             .join(target)
@@ -712,27 +1237,174 @@ fn build_rust_library(
             );
         }
 
-        // Destination: build/app/src/main/jniLibs/{abi}/lib{name}.so
-        let jnilib_dir = build_dir.join("app/src/main/jniLibs").join(abi);
-        fs::create_dir_all(&jnilib_dir)
-            .context(format!("Failed to create jniLibs directory for {}", abi))?;
-
-        let dest_so = jnilib_dir.join(&so_name);
-        fs::copy(&so_file, &dest_so)
-            .context(format!("Failed to copy Rust .so file for {}", abi))?;
+        install_library(&so_file, build_dir, abi)?;
+        // Fingerprint again: cargo may have written or updated Cargo.lock
+        record_fingerprint(build_dir, &so_name, abi, &ffi_fingerprint(inputs, config, &toolchain_version)?)?;
 
         println!("    {} {}", "✓".green(), format!("{} built successfully", abi).dimmed());
     }
 
-    println!("{}", format!("Rust library '{}' built successfully for {} ABIs", library_name, targets.len()).green());
+    println!("{}", format!("Rust library '{}' ready for {} ABI(s)", library_name, abis.len()).green());
 
     Ok(())
 }
 
+/// Android ABIs native libraries are built for, with their Rust target triples
+pub const ANDROID_ABIS: [(&str, &str); 4] = [
+    ("arm64-v8a", "aarch64-linux-android"),      // Modern 64-bit ARM (most common)
+    ("armeabi-v7a", "armv7-linux-androideabi"),  // Legacy 32-bit ARM
+    ("x86_64", "x86_64-linux-android"),          // 64-bit x86 (emulators)
+    ("x86", "i686-linux-android"),               // 32-bit x86 (old emulators)
+];
+
+/// The ABIs to build: all of `abis`, or only `config.ffi.abi` when set by `--abi`
+fn selected_abis<'a>(config: &Config, abis: Vec<&'a str>) -> Result<Vec<&'a str>> {
+    let Some(only) = &config.ffi.abi else {
+        return Ok(abis);
+    };
+
+    let selected: Vec<&str> = abis.into_iter().filter(|abi| abi == only).collect();
+    if selected.is_empty() {
+        anyhow::bail!("ABI '{}' is not built by this project. Check [ffi.rust] targets", only);
+    }
+    Ok(selected)
+}
+
+/// Fingerprint of everything a native library is built from: its sources and
+/// generated bridge, the `[ffi]` config and the toolchain versions
+fn ffi_fingerprint(inputs: &[PathBuf], config: &Config, toolchain: &str) -> Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut files = Vec::new();
+    for input in inputs {
+        collect_input_files(input, &mut files)?;
+    }
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for file in &files {
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update(fs::read(file).context(format!("Failed to read {}", file.display()))?);
+    }
+    hasher.update(format!(
+        "{:?} {:?} {:?} {:?} {}",
+        config.ffi.cpp, config.ffi.c, config.ffi.rust, config.ffi.zig, config.android.min_sdk
+    ));
+    hasher.update(toolchain);
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Files under `path`, skipping hidden entries and Cargo's `target/` directory
+fn collect_input_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_file() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    if !path.is_dir() {
+        return Ok(());
+    }
+
+    let entries = fs::read_dir(path)
+        .context(format!("Failed to read directory: {}", path.display()))?;
+    for entry in entries {
+        let entry_path = entry?.path();
+        let name = entry_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with('.') || name == "target" {
+            continue;
+        }
+        collect_input_files(&entry_path, files)?;
+    }
+
+    Ok(())
+}
+
+/// Directories of the `path = "..."` dependencies of the crate in `crate_dir` that
+/// live outside it, following their own path dependencies
+fn rust_path_dependencies(crate_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut pending = vec![crate_dir.to_path_buf()];
+    let mut seen = vec![fs::canonicalize(crate_dir).unwrap_or_else(|_| crate_dir.to_path_buf())];
+    let mut dependencies = Vec::new();
+
+    while let Some(dir) = pending.pop() {
+        let manifest = dir.join("Cargo.toml");
+        let Ok(content) = fs::read_to_string(&manifest) else {
+            continue;
+        };
+        let cargo_toml: toml::Value = toml::from_str(&content)
+            .context(format!("Failed to parse {}", manifest.display()))?;
+
+        // [dependencies], [build-dependencies] and their [target.'cfg(..)'] variants
+        let mut tables = vec![&cargo_toml];
+        if let Some(targets) = cargo_toml.get("target").and_then(|t| t.as_table()) {
+            tables.extend(targets.values());
+        }
+        for table in tables {
+            for section in ["dependencies", "build-dependencies"] {
+                let Some(deps) = table.get(section).and_then(|d| d.as_table()) else {
+                    continue;
+                };
+                for path in deps.values().filter_map(|dep| dep.get("path")?.as_str()) {
+                    let Ok(canonical) = fs::canonicalize(dir.join(path)) else {
+                        continue;
+                    };
+                    // Inside an already fingerprinted directory (or seen before)
+                    if seen.iter().any(|seen| canonical.starts_with(seen)) {
+                        continue;
+                    }
+                    seen.push(canonical.clone());
+                    dependencies.push(canonical.clone());
+                    pending.push(canonical);
+                }
+            }
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// Where the fingerprint of the last build of `so_name` for `abi` is kept
+fn fingerprint_file(build_dir: &Path, so_name: &str, abi: &str) -> PathBuf {
+    build_dir.join(".whitehall").join(format!("ffi-{}-{}.hash", so_name.trim_end_matches(".so"), abi))
+}
+
+/// Whether `abi` was last built from the same inputs and its library is still in jniLibs
+fn is_up_to_date(build_dir: &Path, so_name: &str, abi: &str, fingerprint: &str) -> bool {
+    build_dir.join("app/src/main/jniLibs").join(abi).join(so_name).exists()
+        && fs::read_to_string(fingerprint_file(build_dir, so_name, abi))
+            .is_ok_and(|stored| stored.trim() == fingerprint)
+}
+
+fn record_fingerprint(build_dir: &Path, so_name: &str, abi: &str, fingerprint: &str) -> Result<()> {
+    let file = fingerprint_file(build_dir, so_name, abi);
+    fs::create_dir_all(file.parent().unwrap())
+        .context("Failed to create .whitehall directory")?;
+    fs::write(&file, fingerprint)
+        .context(format!("Failed to write FFI fingerprint: {}", file.display()))
+}
+
+/// Copy a built library to build/app/src/main/jniLibs/{abi}/
+///
+/// An identical existing copy is left alone, so Gradle doesn't repackage the APK.
+fn install_library(so_file: &Path, build_dir: &Path, abi: &str) -> Result<()> {
+    let jnilib_dir = build_dir.join("app/src/main/jniLibs").join(abi);
+    fs::create_dir_all(&jnilib_dir)
+        .context(format!("Failed to create jniLibs directory for {}", abi))?;
+
+    let dest_so = jnilib_dir.join(so_file.file_name().unwrap());
+    let built = fs::read(so_file).context(format!("Failed to read {}", so_file.display()))?;
+    if fs::read(&dest_so).is_ok_and(|existing| existing == built) {
+        return Ok(());
+    }
+
+    fs::write(&dest_so, built)
+        .context(format!("Failed to copy .so file for {}", abi))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{AndroidConfig, BuildConfig, FfiConfig, ProjectConfig, ToolchainConfig, CppConfig};
+    use crate::config::{AndroidConfig, BuildConfig, FetchConfig, FfiConfig, ProjectConfig, ToolchainConfig, CppConfig};
 
     #[test]
     fn test_to_pascal_case() {
@@ -754,6 +1426,7 @@ mod tests {
                 min_sdk: 24,
                 target_sdk: 34,
                 package: "com.example.test".to_string(),
+                permissions: Vec::new(),
             },
             build: BuildConfig {
                 output_dir: "build".to_string(),
@@ -764,6 +1437,10 @@ mod tests {
                 enabled: ffi_enabled,
                 ..Default::default()
             },
+            fetch: FetchConfig::default(),
+            dependencies: Default::default(),
+            plugins: Default::default(),
+            repositories: Default::default(),
         }
     }
 
@@ -805,4 +1482,216 @@ mod tests {
         fs::create_dir_all(temp_dir.path().join("src/ffi/rust")).unwrap();
         assert!(should_build_ffi(&config, temp_dir.path()));
     }
+
+    #[test]
+    fn test_should_build_ffi_auto_detect_c() {
+        let config = create_test_config(None);
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // With c directory, should build
+        fs::create_dir_all(temp_dir.path().join("src/ffi/c")).unwrap();
+        assert!(should_build_ffi(&config, temp_dir.path()));
+    }
+
+    #[test]
+    fn test_should_build_ffi_auto_detect_zig() {
+        let config = create_test_config(None);
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // With zig directory, should build
+        fs::create_dir_all(temp_dir.path().join("src/ffi/zig")).unwrap();
+        assert!(should_build_ffi(&config, temp_dir.path()));
+    }
+
+    #[test]
+    fn test_zig_build_lib_per_abi() {
+        let mut config = create_test_config(None);
+        config.ffi.zig.flags.push("-fstrip".to_string());
+        let temp_dir = tempfile::tempdir().unwrap();
+        let build_dir = temp_dir.path().join("build");
+
+        let cmd = zig_build_lib_command(&config, Path::new("src/ffi/zig/math.zig"), "armeabi-v7a", &build_dir, "test_zig").unwrap();
+        let args: Vec<String> = cmd.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        assert_eq!(cmd.get_program(), "zig");
+        assert_eq!(args[..2], ["build-lib", "src/ffi/zig/math.zig"]);
+        assert!(args.windows(2).any(|pair| pair == ["-target", "arm-linux-androideabi"]));
+        assert!(args.windows(2).any(|pair| pair == ["-O", "ReleaseFast"]));
+        assert!(args.contains(&"-fPIC".to_string()));
+        assert!(args.contains(&format!(
+            "-femit-bin={}",
+            build_dir.join("zig-build/test_zig/armeabi-v7a/libmath.a").display()
+        )));
+        assert_eq!(args.last().unwrap(), "-fstrip");
+
+        for (abi, _) in ANDROID_ABIS {
+            assert!(zig_target(abi).is_ok(), "{}", abi);
+        }
+        assert!(zig_target("mips").is_err());
+    }
+
+    #[test]
+    fn test_zig_generate_bridge() {
+        let config = create_test_config(None);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ffi_dir = temp_dir.path().join("src/ffi");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(ffi_dir.join("zig")).unwrap();
+        fs::write(
+            ffi_dir.join("zig/math.zig"),
+            "const util = @import(\"util.zig\");\n\n// @ffi\npub export fn add(a: i32, b: i32) i32 {\n    return util.sum(a, b);\n}\n",
+        )
+        .unwrap();
+        fs::write(ffi_dir.join("zig/util.zig"), "pub fn sum(a: i32, b: i32) i32 {\n    return a + b;\n}\n").unwrap();
+
+        let mut zig = ZigFfi::default();
+        assert!(zig.discover(&config, &ffi_dir).unwrap());
+        assert_eq!(zig.library_name(), "test_zig");
+        // Only sources declaring @ffi functions are compiled; util.zig is imported
+        assert_eq!(zig.roots, vec![ffi_dir.join("zig/math.zig")]);
+
+        zig.generate_bridge(&config, &ffi_dir, &build_dir).unwrap();
+
+        let header = fs::read_to_string(build_dir.join("generated/jni/zig/math.h")).unwrap();
+        assert!(header.contains("int32_t add(int32_t a, int32_t b);"), "{}", header);
+
+        let bridge = fs::read_to_string(build_dir.join("generated/jni/test_zig_bridge.c")).unwrap();
+        assert!(bridge.contains("#include \"math.h\""), "{}", bridge);
+        assert!(bridge.contains("Java_com_example_test_ffi_zig_Math_add"), "{}", bridge);
+
+        let kotlin = fs::read_to_string(build_dir.join("generated/kotlin/com/example/test/ffi/zig/Math.kt")).unwrap();
+        assert!(kotlin.contains("System.loadLibrary(\"test_zig\")"), "{}", kotlin);
+
+        let cmake = fs::read_to_string(build_dir.join("cmake/zig/CMakeLists.txt")).unwrap();
+        assert!(cmake.contains("zig-build/test_zig/${ANDROID_ABI}/libmath.a"), "{}", cmake);
+    }
+
+    #[test]
+    fn test_ffi_fingerprint_tracks_inputs() {
+        let mut config = create_test_config(None);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let rust_dir = temp_dir.path().join("rust");
+        fs::create_dir_all(rust_dir.join("src")).unwrap();
+        fs::create_dir_all(rust_dir.join("target/release")).unwrap();
+        fs::write(rust_dir.join("src/lib.rs"), "pub fn add() {}").unwrap();
+        let inputs = [rust_dir.clone()];
+
+        let fingerprint = ffi_fingerprint(&inputs, &config, "ndk-26").unwrap();
+        assert_eq!(fingerprint, ffi_fingerprint(&inputs, &config, "ndk-26").unwrap());
+
+        // Build outputs don't count
+        fs::write(rust_dir.join("target/release/libmath.so"), "elf").unwrap();
+        assert_eq!(fingerprint, ffi_fingerprint(&inputs, &config, "ndk-26").unwrap());
+
+        // Lockfile-only dependency bumps (`cargo update`) do
+        fs::write(rust_dir.join("Cargo.lock"), "# lock").unwrap();
+        let locked = ffi_fingerprint(&inputs, &config, "ndk-26").unwrap();
+        assert_ne!(fingerprint, locked);
+        fs::write(rust_dir.join("Cargo.lock"), "# updated lock").unwrap();
+        assert_ne!(locked, ffi_fingerprint(&inputs, &config, "ndk-26").unwrap());
+        fs::remove_file(rust_dir.join("Cargo.lock")).unwrap();
+
+        assert_ne!(fingerprint, ffi_fingerprint(&inputs, &config, "ndk-27").unwrap());
+
+        config.ffi.cpp.flags.push("-O3".to_string());
+        assert_ne!(fingerprint, ffi_fingerprint(&inputs, &config, "ndk-26").unwrap());
+
+        let config = create_test_config(None);
+        fs::write(rust_dir.join("src/lib.rs"), "pub fn add() -> i32 { 1 }").unwrap();
+        assert_ne!(fingerprint, ffi_fingerprint(&inputs, &config, "ndk-26").unwrap());
+    }
+
+    #[test]
+    fn test_rust_path_dependencies() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let rust_dir = temp_dir.path().join("src/ffi/rust");
+        let shared_dir = temp_dir.path().join("shared");
+        let util_dir = temp_dir.path().join("util");
+        for dir in [&rust_dir, &shared_dir, &util_dir] {
+            fs::create_dir_all(dir.join("src")).unwrap();
+        }
+        fs::create_dir_all(rust_dir.join("crates/inner")).unwrap();
+        fs::write(
+            rust_dir.join("Cargo.toml"),
+            "[package]\nname = \"math\"\n\n[dependencies]\njni = \"0.21\"\n\
+             shared = { path = \"../../../shared\" }\ninner = { path = \"crates/inner\" }\n\
+             missing = { path = \"../missing\" }\n",
+        ).unwrap();
+        // Followed through the path dependency, and not listed twice
+        fs::write(
+            shared_dir.join("Cargo.toml"),
+            "[package]\nname = \"shared\"\n\n[target.'cfg(unix)'.dependencies]\nutil = { path = \"../util\" }\n\n\
+             [build-dependencies]\nutil = { path = \"../util\" }\n",
+        ).unwrap();
+
+        let deps = rust_path_dependencies(&rust_dir).unwrap();
+        assert_eq!(deps, vec![fs::canonicalize(&shared_dir).unwrap(), fs::canonicalize(&util_dir).unwrap()]);
+
+        // A change in a path dependency changes the fingerprint
+        let config = create_test_config(None);
+        let mut inputs = vec![rust_dir.clone()];
+        inputs.extend(deps);
+        let fingerprint = ffi_fingerprint(&inputs, &config, "ndk-26").unwrap();
+        fs::write(util_dir.join("src/lib.rs"), "pub fn util() {}").unwrap();
+        assert_ne!(fingerprint, ffi_fingerprint(&inputs, &config, "ndk-26").unwrap());
+    }
+
+    #[test]
+    fn test_ffi_build_cache_per_abi() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let build_dir = temp_dir.path().join("build");
+        let so_file = temp_dir.path().join("libmath.so");
+        fs::write(&so_file, "elf").unwrap();
+
+        assert!(!is_up_to_date(&build_dir, "libmath.so", "x86_64", "abc"));
+
+        install_library(&so_file, &build_dir, "x86_64").unwrap();
+        record_fingerprint(&build_dir, "libmath.so", "x86_64", "abc").unwrap();
+        assert_eq!(fs::read_to_string(build_dir.join("app/src/main/jniLibs/x86_64/libmath.so")).unwrap(), "elf");
+        assert!(is_up_to_date(&build_dir, "libmath.so", "x86_64", "abc"));
+        assert!(!is_up_to_date(&build_dir, "libmath.so", "x86_64", "def"));
+        assert!(!is_up_to_date(&build_dir, "libmath.so", "arm64-v8a", "abc"));
+
+        // A removed library is rebuilt even if the inputs match
+        fs::remove_file(build_dir.join("app/src/main/jniLibs/x86_64/libmath.so")).unwrap();
+        assert!(!is_up_to_date(&build_dir, "libmath.so", "x86_64", "abc"));
+    }
+
+    #[test]
+    fn test_c_and_cpp_library_names_distinct() {
+        let mut config = create_test_config(None);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ffi_dir = temp_dir.path().join("src/ffi");
+        fs::create_dir_all(ffi_dir.join("cpp")).unwrap();
+        fs::create_dir_all(ffi_dir.join("c")).unwrap();
+
+        // The defaults don't collide
+        let cpp = CppFfi { library_name: get_cpp_library_name(&config, &ffi_dir), ..Default::default() };
+        let c = CFfi { library_name: get_c_library_name(&config, &ffi_dir), ..Default::default() };
+        assert_eq!(cpp.library_name, "test");
+        assert_eq!(c.library_name, "test_c");
+        let zig = ZigFfi { library_name: get_zig_library_name(&config), ..Default::default() };
+        assert_eq!(zig.library_name, "test_zig");
+        let languages: Vec<Box<dyn FfiLanguage>> = vec![Box::new(cpp), Box::new(c), Box::new(zig)];
+        assert!(check_library_names(&languages).is_ok());
+
+        // Explicitly giving both the same name is an error
+        config.ffi.c.library_name = Some("test".to_string());
+        let c = CFfi { library_name: get_c_library_name(&config, &ffi_dir), ..Default::default() };
+        let languages: Vec<Box<dyn FfiLanguage>> = vec![languages.into_iter().next().unwrap(), Box::new(c)];
+        let err = check_library_names(&languages).unwrap_err().to_string();
+        assert!(err.contains("src/ffi/cpp and src/ffi/c both build libtest.so"), "{}", err);
+    }
+
+    #[test]
+    fn test_selected_abis() {
+        let mut config = create_test_config(None);
+        let all = vec!["arm64-v8a", "x86_64"];
+        assert_eq!(selected_abis(&config, all.clone()).unwrap(), all);
+
+        config.ffi.abi = Some("x86_64".to_string());
+        assert_eq!(selected_abis(&config, all.clone()).unwrap(), vec!["x86_64"]);
+
+        config.ffi.abi = Some("x86".to_string());
+        assert!(selected_abis(&config, all).is_err());
+    }
 }
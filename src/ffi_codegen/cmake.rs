@@ -69,6 +69,106 @@ pub fn generate_cmake(
     output
 }
 
+/// Generate CMakeLists.txt for a C library with a C JNI bridge
+///
+/// `include_dir` holds the `// @ffi` headers the bridge includes.
+pub fn generate_c_cmake(
+    library_name: &str,
+    source_files: &[String],
+    bridge_file: &str,
+    include_dir: &str,
+    c_standard: &str,
+    additional_flags: &[String],
+    link_libraries: &[String],
+) -> String {
+    let mut output = String::new();
+
+    output.push_str("# Auto-generated by Whitehall FFI\n");
+    output.push_str("# DO NOT EDIT MANUALLY\n\n");
+
+    output.push_str("cmake_minimum_required(VERSION 3.22.1)\n\n");
+
+    // C only, so CMake doesn't look for a C++ compiler
+    output.push_str(&format!("project(\"{}\" C)\n\n", library_name));
+
+    output.push_str(&format!("add_library({} SHARED\n", library_name));
+    for source_file in source_files {
+        output.push_str(&format!("    {}\n", source_file));
+    }
+    output.push_str(&format!("    {}\n", bridge_file));
+    output.push_str(")\n\n");
+
+    output.push_str(&format!("target_include_directories({} PRIVATE {})\n\n", library_name, include_dir));
+
+    output.push_str(&format!("set(CMAKE_C_STANDARD {})\n", c_standard));
+    output.push_str("set(CMAKE_C_STANDARD_REQUIRED ON)\n\n");
+
+    if !additional_flags.is_empty() {
+        output.push_str(&format!("target_compile_options({} PRIVATE\n", library_name));
+        for flag in additional_flags {
+            output.push_str(&format!("    {}\n", flag));
+        }
+        output.push_str(")\n\n");
+    }
+
+    if !link_libraries.is_empty() {
+        output.push_str("# Link external libraries\n");
+        for lib in link_libraries {
+            output.push_str(&format!("find_library({}-lib {})\n", lib, lib));
+        }
+        output.push_str(&format!("target_link_libraries({}", library_name));
+        for lib in link_libraries {
+            output.push_str(&format!(" ${{{}-lib}}", lib));
+        }
+        output.push_str(")\n\n");
+    }
+
+    output.push_str("# Android log library\n");
+    output.push_str("find_library(log-lib log)\n");
+    output.push_str(&format!("target_link_libraries({} ${{log-lib}})\n", library_name));
+
+    output
+}
+
+/// Generate CMakeLists.txt linking Zig static libraries through a C JNI bridge
+///
+/// `static_libraries` may use `${ANDROID_ABI}` to pick the archive built for the
+/// ABI CMake is configuring; `include_dir` holds the generated C headers.
+pub fn generate_zig_cmake(
+    library_name: &str,
+    bridge_file: &str,
+    include_dir: &str,
+    static_libraries: &[String],
+) -> String {
+    let mut output = String::new();
+
+    output.push_str("# Auto-generated by Whitehall FFI\n");
+    output.push_str("# DO NOT EDIT MANUALLY\n\n");
+
+    output.push_str("cmake_minimum_required(VERSION 3.22.1)\n\n");
+
+    output.push_str(&format!("project(\"{}\" C)\n\n", library_name));
+
+    output.push_str(&format!("add_library({} SHARED\n", library_name));
+    output.push_str(&format!("    {}\n", bridge_file));
+    output.push_str(")\n\n");
+
+    output.push_str(&format!("target_include_directories({} PRIVATE {})\n\n", library_name, include_dir));
+
+    output.push_str("# Static libraries built by `zig build-lib`\n");
+    output.push_str(&format!("target_link_libraries({}\n", library_name));
+    for lib in static_libraries {
+        output.push_str(&format!("    {}\n", lib));
+    }
+    output.push_str(")\n\n");
+
+    output.push_str("# Android log library\n");
+    output.push_str("find_library(log-lib log)\n");
+    output.push_str(&format!("target_link_libraries({} ${{log-lib}})\n", library_name));
+
+    output
+}
+
 /// Generate default CMakeLists.txt with minimal configuration
 pub fn generate_default_cmake(library_name: &str, source_files: &[String], bridge_file: &str) -> String {
     generate_cmake(
@@ -106,6 +206,44 @@ mod tests {
         assert!(cmake.contains("Auto-generated by Whitehall FFI"));
     }
 
+    #[test]
+    fn test_generate_c_cmake() {
+        let cmake = generate_c_cmake(
+            "codec",
+            &["/app/src/ffi/c/codec.c".to_string()],
+            "/app/build/generated/jni/codec_bridge.c",
+            "/app/src/ffi/c",
+            "11",
+            &["-O2".to_string()],
+            &["z".to_string()],
+        );
+
+        assert!(cmake.contains("project(\"codec\" C)"));
+        assert!(cmake.contains("/app/src/ffi/c/codec.c"));
+        assert!(cmake.contains("/app/build/generated/jni/codec_bridge.c"));
+        assert!(cmake.contains("target_include_directories(codec PRIVATE /app/src/ffi/c)"));
+        assert!(cmake.contains("set(CMAKE_C_STANDARD 11)"));
+        assert!(!cmake.contains("CMAKE_CXX_STANDARD"));
+        assert!(cmake.contains("target_link_libraries(codec ${z-lib})"));
+        assert!(cmake.contains("target_link_libraries(codec ${log-lib})"));
+    }
+
+    #[test]
+    fn test_generate_zig_cmake() {
+        let cmake = generate_zig_cmake(
+            "app_zig",
+            "/app/build/generated/jni/app_zig_bridge.c",
+            "/app/build/generated/jni/zig",
+            &["/app/build/zig-build/app_zig/${ANDROID_ABI}/libmath.a".to_string()],
+        );
+
+        assert!(cmake.contains("project(\"app_zig\" C)"));
+        assert!(cmake.contains("add_library(app_zig SHARED\n    /app/build/generated/jni/app_zig_bridge.c\n)"));
+        assert!(cmake.contains("target_include_directories(app_zig PRIVATE /app/build/generated/jni/zig)"));
+        assert!(cmake.contains("/app/build/zig-build/app_zig/${ANDROID_ABI}/libmath.a"));
+        assert!(cmake.contains("target_link_libraries(app_zig ${log-lib})"));
+    }
+
     #[test]
     fn test_generate_cmake_with_flags() {
         let cmake = generate_cmake(
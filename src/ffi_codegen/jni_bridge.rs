@@ -1,10 +1,11 @@
-use crate::ffi_parser::cpp::{CppFfiFunction, CppType};
+use crate::ffi_parser::cpp::{CppCallback, CppFfiFunction, CppFfiType, CppFfiTypeKind, CppType};
 
 /// Generate JNI bridge code for all FFI functions
 pub fn generate_jni_bridge(
     functions: &[CppFfiFunction],
+    types: &[CppFfiType],
     package: &str,
-    _source_files: &[String],
+    object_name: &str,
 ) -> String {
     let mut output = String::new();
 
@@ -17,9 +18,28 @@ pub fn generate_jni_bridge(
     // Include JNI header and standard library headers
     output.push_str("#include <jni.h>\n");
     output.push_str("#include <string>\n");
+    output.push_str("#include <vector>\n");
+    let has_callbacks = functions
+        .iter()
+        .any(|f| f.params.iter().any(|(_, t)| matches!(t, CppType::Function(_))));
+    if has_callbacks {
+        output.push_str("#include <functional>\n");
+        output.push_str("#include <memory>\n");
+    }
     output.push_str("#include <exception>\n");
     output.push_str("#include <stdexcept>\n\n");
 
+    // FFI types and their conversions to/from the generated Kotlin classes
+    if !types.is_empty() {
+        output.push_str(&generate_type_definitions(types));
+        output.push_str(&generate_type_conversions(types, package));
+    }
+
+    // Thread attachment for std::function callbacks invoked from native threads
+    if has_callbacks {
+        output.push_str(CALLBACK_HELPERS);
+    }
+
     // Note: Source files are compiled together by CMake, no need to #include them
     // Generate forward declarations for user functions
     output.push_str("// Forward declarations of user functions\n");
@@ -30,55 +50,236 @@ pub fn generate_jni_bridge(
 
     // Generate JNI bridge functions
     for function in functions {
-        output.push_str(&generate_jni_function(function, package));
+        let jni_name = generate_jni_name(function, functions, package, object_name);
+        output.push_str(&generate_jni_function(function, &jni_name, package));
         output.push_str("\n");
     }
 
     output
 }
 
+/// Generate a C JNI bridge for functions declared in `// @ffi` C headers
+///
+/// The bridge includes the headers (CMake adds `src/ffi/c` to the include path)
+/// instead of forward-declaring the functions, and uses the C JNI interface
+/// (`(*env)->`). C functions have no exceptions to translate.
+pub fn generate_c_jni_bridge(functions: &[CppFfiFunction], package: &str, object_name: &str) -> String {
+    let mut output = String::new();
+
+    // Header comment
+    output.push_str("/*\n");
+    output.push_str(" * Auto-generated by Whitehall FFI\n");
+    output.push_str(" * DO NOT EDIT MANUALLY\n");
+    output.push_str(" */\n\n");
+
+    output.push_str("#include <jni.h>\n");
+    output.push_str("#include <stdbool.h>\n");
+    output.push_str("#include <stdint.h>\n");
+    output.push_str("#include <stddef.h>\n\n");
+
+    // User headers, in order of first appearance
+    let mut headers: Vec<String> = Vec::new();
+    for function in functions {
+        if let Some(header) = function.source_file.file_name().and_then(|name| name.to_str()) {
+            if !headers.iter().any(|h| h == header) {
+                headers.push(header.to_string());
+            }
+        }
+    }
+    for header in &headers {
+        output.push_str(&format!("#include \"{}\"\n", header));
+    }
+    output.push('\n');
+
+    for function in functions {
+        let jni_name = generate_jni_name(function, functions, package, object_name);
+        output.push_str(&generate_c_jni_function(function, &jni_name));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Generate a C JNI function wrapper
+fn generate_c_jni_function(function: &CppFfiFunction, jni_name: &str) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("JNIEXPORT {} JNICALL\n", function.return_type.to_jni_type()));
+    output.push_str(jni_name);
+    output.push_str("(\n");
+    output.push_str("    JNIEnv* env,\n");
+    output.push_str("    jobject thiz");
+    for (param_name, param_type) in &function.params {
+        output.push_str(&format!(",\n    {} {}", param_type.to_jni_type(), param_name));
+    }
+    output.push_str("\n) {\n");
+
+    // Borrow string parameters as modified UTF-8, released after the call
+    let strings: Vec<&str> = function
+        .params
+        .iter()
+        .filter(|(_, typ)| *typ == CppType::String)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let failure = match function.return_type {
+        CppType::Void => "return;",
+        CppType::String => "return NULL;",
+        _ => "return 0;",
+    };
+    for (i, name) in strings.iter().enumerate() {
+        output.push_str(&format!(
+            "    const char* c_{} = (*env)->GetStringUTFChars(env, {}, NULL);\n",
+            name, name
+        ));
+        output.push_str(&format!("    if (c_{} == NULL) {{\n", name));
+        for acquired in strings[..i].iter().rev() {
+            output.push_str(&format!(
+                "        (*env)->ReleaseStringUTFChars(env, {}, c_{});\n",
+                acquired, acquired
+            ));
+        }
+        output.push_str(&format!("        {}  // OutOfMemoryError\n", failure));
+        output.push_str("    }\n");
+    }
+
+    let args = function
+        .params
+        .iter()
+        .map(|(name, typ)| if *typ == CppType::String { format!("c_{}", name) } else { name.clone() })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let call = format!("{}({})", function.name, args);
+
+    match &function.return_type {
+        CppType::Void => output.push_str(&format!("    {};\n", call)),
+        CppType::String => output.push_str(&format!("    const char* result = {};\n", call)),
+        other => output.push_str(&format!("    {} result = ({}) {};\n", other.to_jni_type(), other.to_jni_type(), call)),
+    }
+
+    for name in strings.iter().rev() {
+        output.push_str(&format!("    (*env)->ReleaseStringUTFChars(env, {}, c_{});\n", name, name));
+    }
+
+    match &function.return_type {
+        CppType::Void => {}
+        CppType::String => {
+            // The string is copied; the C library keeps ownership of `result`
+            output.push_str("    if (result == NULL) {\n");
+            output.push_str("        jclass exClass = (*env)->FindClass(env, \"java/lang/NullPointerException\");\n");
+            output.push_str("        if (exClass != NULL) {\n");
+            output.push_str(&format!(
+                "            (*env)->ThrowNew(env, exClass, \"{} returned NULL\");\n",
+                function.name
+            ));
+            output.push_str("        }\n");
+            output.push_str("        return NULL;\n");
+            output.push_str("    }\n");
+            output.push_str("    return (*env)->NewStringUTF(env, result);\n");
+        }
+        _ => output.push_str("    return result;\n"),
+    }
+
+    output.push_str("}\n");
+
+    output
+}
+
+/// Generate a C header declaring `// @ffi` functions exported by another language (Zig)
+///
+/// The C JNI bridge includes it in place of a hand-written header.
+pub fn generate_c_header(functions: &[CppFfiFunction]) -> String {
+    let mut output = String::new();
+
+    output.push_str("/*\n");
+    output.push_str(" * Auto-generated by Whitehall FFI\n");
+    output.push_str(" * DO NOT EDIT MANUALLY\n");
+    output.push_str(" */\n\n");
+    output.push_str("#pragma once\n\n");
+    output.push_str("#include <stdbool.h>\n");
+    output.push_str("#include <stdint.h>\n\n");
+
+    for function in functions {
+        let params = if function.params.is_empty() {
+            "void".to_string()
+        } else {
+            function
+                .params
+                .iter()
+                .map(|(name, typ)| format!("{} {}", c_type_name(typ), name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        output.push_str(&format!("{} {}({});\n", c_type_name(&function.return_type), function.name, params));
+    }
+
+    output
+}
+
+/// C spelling of the types a C ABI function can take or return
+fn c_type_name(typ: &CppType) -> String {
+    match typ {
+        CppType::Int => "int32_t".to_string(),
+        CppType::Long => "int64_t".to_string(),
+        CppType::String => "const char*".to_string(),
+        other => other.to_cpp_type(),
+    }
+}
+
 /// Check if a type is an array type
 fn is_array_type(typ: &CppType) -> bool {
     matches!(typ, CppType::IntArray | CppType::LongArray | CppType::FloatArray | CppType::DoubleArray | CppType::BoolArray | CppType::StringArray)
 }
 
+/// Check if a type is an @ffi struct/enum or a vector of them
+fn is_custom_type(typ: &CppType) -> bool {
+    matches!(typ, CppType::Custom(_) | CppType::CustomList(_))
+}
+
+/// Check if a type is passed as a Java object (nullptr on error paths)
+fn is_object_type(typ: &CppType) -> bool {
+    *typ == CppType::String || is_array_type(typ) || is_custom_type(typ) || matches!(typ, CppType::Function(_))
+}
+
 /// Generate forward declaration for a user function
 fn generate_forward_declaration(function: &CppFfiFunction) -> String {
     let params = function
         .params
         .iter()
         .map(|(_, typ)| {
-            // For String and Array parameters, use const reference
-            if *typ == CppType::String || is_array_type(typ) {
+            // For String, Array and struct parameters, use const reference
+            if is_object_type(typ) {
                 format!("const {}&", typ.to_cpp_type())
             } else {
-                typ.to_cpp_type().to_string()
+                typ.to_cpp_type()
             }
         })
         .collect::<Vec<_>>()
         .join(", ");
 
-    format!(
-        "{} {}({});\n",
+    let declaration = format!(
+        "{} {}({});",
         function.return_type.to_cpp_type(),
         function.name,
         params
-    )
+    );
+
+    // Declare it in the same namespace as the user's definition
+    let opening: String = function.namespace.iter().map(|name| format!("namespace {} {{ ", name)).collect();
+    let closing = " }".repeat(function.namespace.len());
+    format!("{}{}{}\n", opening, declaration, closing)
 }
 
 /// Generate JNI function wrapper
-fn generate_jni_function(function: &CppFfiFunction, package: &str) -> String {
+fn generate_jni_function(function: &CppFfiFunction, jni_name: &str, package: &str) -> String {
     let mut output = String::new();
 
-    // Generate JNI function name
-    let jni_name = generate_jni_name(&function.name, package);
     let jni_return_type = function.return_type.to_jni_type();
 
     // Function signature
     output.push_str("extern \"C\" JNIEXPORT ");
     output.push_str(jni_return_type);
     output.push_str(" JNICALL\n");
-    output.push_str(&jni_name);
+    output.push_str(jni_name);
     output.push_str("(\n");
     output.push_str("    JNIEnv* env,\n");
     output.push_str("    jobject thiz");
@@ -99,20 +300,20 @@ fn generate_jni_function(function: &CppFfiFunction, package: &str) -> String {
     output.push_str("    try {\n");
 
     // Check if we have any string/array parameters or return types
-    let has_strings_or_arrays = function.params.iter().any(|(_, t)| *t == CppType::String || is_array_type(t))
-        || function.return_type == CppType::String || is_array_type(&function.return_type);
+    let has_strings_or_arrays = function.params.iter().any(|(_, t)| is_object_type(t))
+        || is_object_type(&function.return_type);
 
     if has_strings_or_arrays {
         // Phase 2/3: Handle string and array conversions
-        output.push_str(&generate_complex_conversions(function));
+        output.push_str(&generate_complex_conversions(function, package));
     } else {
         // Phase 1: Direct call for primitives
         output.push_str("        // Call user function\n");
 
         if function.return_type == CppType::Void {
-            output.push_str(&format!("        {}(", function.name));
+            output.push_str(&format!("        {}(", function.qualified_name()));
         } else {
-            output.push_str(&format!("        return {}(", function.name));
+            output.push_str(&format!("        return {}(", function.qualified_name()));
         }
 
         // Pass parameters
@@ -172,12 +373,14 @@ fn generate_default_return(return_type: &CppType) -> String {
         CppType::Bool => "        return false;\n".to_string(),
         CppType::String | CppType::IntArray | CppType::LongArray |
         CppType::FloatArray | CppType::DoubleArray | CppType::BoolArray |
-        CppType::StringArray => "        return nullptr;\n".to_string(),
+        CppType::StringArray | CppType::Custom(_) | CppType::CustomList(_) | CppType::Function(_) => {
+            "        return nullptr;\n".to_string()
+        }
     }
 }
 
 /// Generate complex type conversion code for JNI bridge (Phase 2/3: Strings and Arrays)
-fn generate_complex_conversions(function: &CppFfiFunction) -> String {
+fn generate_complex_conversions(function: &CppFfiFunction, package: &str) -> String {
     let mut output = String::new();
 
     // Convert string and array parameters
@@ -186,19 +389,31 @@ fn generate_complex_conversions(function: &CppFfiFunction) -> String {
             output.push_str(&generate_string_param_conversion(param_name, &function.return_type));
         } else if is_array_type(param_type) {
             output.push_str(&generate_array_param_conversion(param_name, param_type, &function.return_type));
+        } else if let CppType::Custom(name) = param_type {
+            output.push_str(&format!(
+                "        {} cpp_{} = {}(env, {});\n\n",
+                name, param_name, from_java_fn(name), param_name
+            ));
+        } else if let CppType::CustomList(name) = param_type {
+            output.push_str(&format!(
+                "        std::vector<{}> cpp_{} = whitehall_list_from_java(env, {}, {});\n\n",
+                name, param_name, param_name, from_java_fn(name)
+            ));
+        } else if let CppType::Function(callback) = param_type {
+            output.push_str(&generate_callback_param_conversion(param_name, param_type, callback, package));
         }
     }
 
     // Call the user function (Phase 5: indented for try-catch block)
     output.push_str("            // Call user function\n");
-    let needs_result = function.return_type == CppType::String || is_array_type(&function.return_type);
+    let needs_result = is_object_type(&function.return_type);
 
     if needs_result {
-        output.push_str(&format!("        auto result = {}(", function.name));
+        output.push_str(&format!("        auto result = {}(", function.qualified_name()));
     } else if function.return_type == CppType::Void {
-        output.push_str(&format!("        {}(", function.name));
+        output.push_str(&format!("        {}(", function.qualified_name()));
     } else {
-        output.push_str(&format!("        return {}(", function.name));
+        output.push_str(&format!("        return {}(", function.qualified_name()));
     }
 
     // Pass converted parameters
@@ -206,7 +421,7 @@ fn generate_complex_conversions(function: &CppFfiFunction) -> String {
         .params
         .iter()
         .map(|(name, typ)| {
-            if *typ == CppType::String || is_array_type(typ) {
+            if is_object_type(typ) {
                 format!("cpp_{}", name)
             } else {
                 name.clone()
@@ -226,11 +441,194 @@ fn generate_complex_conversions(function: &CppFfiFunction) -> String {
         output.push_str("        return j_result;\n");
     } else if is_array_type(&function.return_type) {
         output.push_str(&generate_array_return_conversion(&function.return_type));
+    } else if let CppType::Custom(name) = &function.return_type {
+        output.push_str(&format!("        return {}(env, result);\n", to_java_fn(name)));
+    } else if let CppType::CustomList(name) = &function.return_type {
+        output.push_str(&format!(
+            "        return whitehall_list_to_java(env, result, {});\n",
+            to_java_fn(name)
+        ));
     }
 
     output
 }
 
+/// Generate a std::function that calls the Kotlin `fun interface` behind a parameter
+///
+/// The global ref is shared by all copies of the std::function and released (on
+/// an attached thread) when the last copy is destroyed.
+fn generate_callback_param_conversion(
+    param_name: &str,
+    param_type: &CppType,
+    callback: &CppCallback,
+    package: &str,
+) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("        JavaVM* vm_{} = nullptr;\n", param_name));
+    output.push_str(&format!("        env->GetJavaVM(&vm_{});\n", param_name));
+    output.push_str(&format!(
+        "        std::shared_ptr<_jobject> ref_{}(env->NewGlobalRef({}), [vm_{}](jobject obj) {{\n",
+        param_name, param_name, param_name
+    ));
+    output.push_str(&format!("            WhitehallAttach attach(vm_{});\n", param_name));
+    output.push_str("            attach.env->DeleteGlobalRef(obj);\n");
+    output.push_str("        });\n");
+
+    let names = callback.param_names();
+    let args = names
+        .iter()
+        .zip(&callback.params)
+        .map(|(name, typ)| format!("{} {}", typ.to_cpp_type(), name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    output.push_str(&format!(
+        "        {} cpp_{} = [vm_{}, ref_{}]({}) {{\n",
+        param_type.to_cpp_type(), param_name, param_name, param_name, args
+    ));
+    output.push_str(&format!("            WhitehallAttach attach(vm_{});\n", param_name));
+    output.push_str("            JNIEnv* env = attach.env;\n");
+
+    // Convert arguments
+    let mut signature = String::from("(");
+    let mut call_args = Vec::new();
+    let mut locals = Vec::new();
+    for (name, typ) in names.iter().zip(&callback.params) {
+        signature.push_str(&jni_signature(typ, package));
+        match typ {
+            CppType::String => {
+                output.push_str(&format!("            jstring j_{} = env->NewStringUTF({}.c_str());\n", name, name));
+                locals.push(format!("j_{}", name));
+            }
+            CppType::Custom(type_name) => {
+                output.push_str(&format!("            jobject j_{} = {}(env, {});\n", name, to_java_fn(type_name), name));
+                locals.push(format!("j_{}", name));
+            }
+            CppType::CustomList(type_name) => {
+                output.push_str(&format!(
+                    "            jobject j_{} = whitehall_list_to_java(env, {}, {});\n",
+                    name, name, to_java_fn(type_name)
+                ));
+                locals.push(format!("j_{}", name));
+            }
+            _ => {}
+        }
+        call_args.push(match typ {
+            CppType::Bool => format!("{} ? JNI_TRUE : JNI_FALSE", name),
+            CppType::String | CppType::Custom(_) | CppType::CustomList(_) => format!("j_{}", name),
+            _ => format!("({}) {}", typ.to_jni_type(), name),
+        });
+    }
+    signature.push(')');
+    signature.push_str(&match callback.return_type {
+        CppType::Void => "V".to_string(),
+        ref other => jni_signature(other, package),
+    });
+
+    output.push_str(&format!("            jclass cls = env->GetObjectClass(ref_{}.get());\n", param_name));
+    output.push_str(&format!(
+        "            jmethodID method = env->GetMethodID(cls, \"invoke\", \"{}\");\n",
+        signature
+    ));
+    output.push_str("            env->DeleteLocalRef(cls);\n");
+
+    let mut call = format!("env->Call{}Method(ref_{}.get(), method", call_kind(&callback.return_type), param_name);
+    for arg in &call_args {
+        call.push_str(", ");
+        call.push_str(arg);
+    }
+    call.push(')');
+
+    match callback.return_type {
+        CppType::Void => output.push_str(&format!("            {};\n", call)),
+        CppType::String => output.push_str(&format!("            jstring result = (jstring) {};\n", call)),
+        ref other => output.push_str(&format!("            {} result = {};\n", other.to_jni_type(), call)),
+    }
+    for local in &locals {
+        output.push_str(&format!("            env->DeleteLocalRef({});\n", local));
+    }
+
+    // Kotlin exceptions can't unwind through native code: report them and fall back
+    match callback.return_type {
+        CppType::Void => output.push_str("            whitehall_clear_exception(env);\n"),
+        CppType::String => {
+            output.push_str("            if (whitehall_clear_exception(env) || result == nullptr) {\n");
+            output.push_str("                return std::string();\n");
+            output.push_str("            }\n");
+            output.push_str("            const char* c_result = env->GetStringUTFChars(result, nullptr);\n");
+            output.push_str("            std::string cpp_result(c_result);\n");
+            output.push_str("            env->ReleaseStringUTFChars(result, c_result);\n");
+            output.push_str("            env->DeleteLocalRef(result);\n");
+            output.push_str("            return cpp_result;\n");
+        }
+        CppType::Bool => {
+            output.push_str("            return !whitehall_clear_exception(env) && result == JNI_TRUE;\n");
+        }
+        ref other => {
+            output.push_str(&format!(
+                "            return whitehall_clear_exception(env) ? 0 : ({}) result;\n",
+                other.to_cpp_type()
+            ));
+        }
+    }
+    output.push_str("        };\n\n");
+
+    output
+}
+
+/// JNI `Call<Kind>Method` variant for a callback return type
+fn call_kind(return_type: &CppType) -> &'static str {
+    match return_type {
+        CppType::Void => "Void",
+        CppType::Int => "Int",
+        CppType::Long => "Long",
+        CppType::Float => "Float",
+        CppType::Double => "Double",
+        CppType::Bool => "Boolean",
+        _ => "Object",
+    }
+}
+
+const CALLBACK_HELPERS: &str = r#"// Attaches the current thread to the JVM for the lifetime of the object
+// (no-op on threads that are already attached, e.g. the JNI caller's)
+struct WhitehallAttach {
+    JavaVM* vm;
+    JNIEnv* env = nullptr;
+    bool attached = false;
+
+    explicit WhitehallAttach(JavaVM* vm) : vm(vm) {
+        if (vm->GetEnv(reinterpret_cast<void**>(&env), JNI_VERSION_1_6) == JNI_EDETACHED) {
+#ifdef __ANDROID__
+            vm->AttachCurrentThread(&env, nullptr);
+#else
+            vm->AttachCurrentThread(reinterpret_cast<void**>(&env), nullptr);
+#endif
+            attached = true;
+        }
+    }
+
+    ~WhitehallAttach() {
+        if (attached) {
+            vm->DetachCurrentThread();
+        }
+    }
+
+    WhitehallAttach(const WhitehallAttach&) = delete;
+    WhitehallAttach& operator=(const WhitehallAttach&) = delete;
+};
+
+// Reports and clears a pending Kotlin exception; returns true if there was one
+static bool whitehall_clear_exception(JNIEnv* env) {
+    if (!env->ExceptionCheck()) {
+        return false;
+    }
+    env->ExceptionDescribe();
+    env->ExceptionClear();
+    return true;
+}
+
+"#;
+
 /// Generate string parameter conversion
 fn generate_string_param_conversion(param_name: &str, return_type: &CppType) -> String {
     let mut output = String::new();
@@ -254,7 +652,7 @@ fn generate_string_param_conversion(param_name: &str, return_type: &CppType) ->
         output.push_str(";\n");
     } else if *return_type == CppType::String {
         output.push_str("env->NewStringUTF(\"\");\n");
-    } else if is_array_type(return_type) {
+    } else if is_object_type(return_type) {
         output.push_str("nullptr;\n");
     } else {
         output.push_str("0;  // OutOfMemoryError\n");
@@ -294,7 +692,7 @@ fn generate_array_param_conversion(param_name: &str, param_type: &CppType, retur
     output.push_str("            return ");
     if *return_type == CppType::Void {
         output.push_str(";\n");
-    } else if is_array_type(return_type) || *return_type == CppType::String {
+    } else if is_object_type(return_type) {
         output.push_str("nullptr;\n");
     } else {
         output.push_str("0;\n");
@@ -308,7 +706,7 @@ fn generate_array_param_conversion(param_name: &str, param_type: &CppType, retur
     output.push_str("            return ");
     if *return_type == CppType::Void {
         output.push_str(";\n");
-    } else if is_array_type(return_type) || *return_type == CppType::String {
+    } else if is_object_type(return_type) {
         output.push_str("nullptr;\n");
     } else {
         output.push_str("0;\n");
@@ -405,14 +803,366 @@ fn generate_string_array_return_conversion() -> String {
     output
 }
 
-/// Generate JNI function name from Kotlin package and function name
+/// Convert snake_case to camelCase (Kotlin field names)
+fn snake_to_camel(name: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize_next = false;
+
+    for ch in name.chars() {
+        if ch == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            result.push(ch.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            result.push(ch);
+        }
+    }
+
+    result
+}
+
+/// Name of the generated C++ -> Kotlin conversion for an @ffi type
+fn to_java_fn(type_name: &str) -> String {
+    format!("whitehall_{}_to_java", type_name)
+}
+
+/// Name of the generated Kotlin -> C++ conversion for an @ffi type
+fn from_java_fn(type_name: &str) -> String {
+    format!("whitehall_{}_from_java", type_name)
+}
+
+/// JVM class name of a generated Kotlin class, e.g. `com/example/ffi/cpp/Point`
+fn jvm_class(package: &str, class_name: &str) -> String {
+    format!("{}/{}", package.replace('.', "/"), class_name)
+}
+
+/// JNI type signature of a struct field or function argument
+fn jni_signature(typ: &CppType, package: &str) -> String {
+    match typ {
+        CppType::Int => "I".to_string(),
+        CppType::Long => "J".to_string(),
+        CppType::Float => "F".to_string(),
+        CppType::Double => "D".to_string(),
+        CppType::Bool => "Z".to_string(),
+        CppType::String => "Ljava/lang/String;".to_string(),
+        CppType::Custom(name) => format!("L{};", jvm_class(package, name)),
+        CppType::CustomList(_) => "Ljava/util/List;".to_string(),
+        CppType::IntArray => "[I".to_string(),
+        CppType::LongArray => "[J".to_string(),
+        CppType::FloatArray => "[F".to_string(),
+        CppType::DoubleArray => "[D".to_string(),
+        CppType::BoolArray => "[Z".to_string(),
+        CppType::StringArray => "[Ljava/lang/String;".to_string(),
+        CppType::Void | CppType::Function(_) => unreachable!("not a value type"),
+    }
+}
+
+/// Re-declare the @ffi structs and enum classes for this translation unit
+///
+/// The user's sources are compiled separately, so the bridge needs its own
+/// (identical) definitions to pass them by value.
+fn generate_type_definitions(types: &[CppFfiType]) -> String {
+    let mut output = String::new();
+
+    output.push_str("// FFI types (mirrors the @ffi declarations in the C++ sources)\n");
+    for ffi_type in types {
+        match &ffi_type.kind {
+            CppFfiTypeKind::Struct(fields) => {
+                output.push_str(&format!("struct {} {{\n", ffi_type.name));
+                for (name, typ) in fields {
+                    output.push_str(&format!("    {} {};\n", typ.to_cpp_type(), name));
+                }
+                output.push_str("};\n");
+            }
+            CppFfiTypeKind::Enum(enumerators) => {
+                output.push_str(&format!(
+                    "enum class {} {{ {} }};\n",
+                    ffi_type.name,
+                    enumerators.join(", ")
+                ));
+            }
+        }
+    }
+    output.push('\n');
+
+    output
+}
+
+/// Generate conversions between @ffi types and their Kotlin classes
+fn generate_type_conversions(types: &[CppFfiType], package: &str) -> String {
+    let mut output = String::new();
+
+    // Forward declarations so nested types can be converted in any order
+    output.push_str("// Conversions between FFI types and their Kotlin classes\n");
+    for ffi_type in types {
+        output.push_str(&format!(
+            "static jobject {}(JNIEnv* env, const {}& value);\n",
+            to_java_fn(&ffi_type.name), ffi_type.name
+        ));
+        output.push_str(&format!(
+            "static {} {}(JNIEnv* env, jobject obj);\n",
+            ffi_type.name, from_java_fn(&ffi_type.name)
+        ));
+    }
+    output.push('\n');
+
+    output.push_str(LIST_HELPERS);
+
+    for ffi_type in types {
+        match &ffi_type.kind {
+            CppFfiTypeKind::Struct(fields) => {
+                output.push_str(&generate_struct_to_java(&ffi_type.name, fields, package));
+                output.push_str(&generate_struct_from_java(&ffi_type.name, fields, package));
+            }
+            CppFfiTypeKind::Enum(enumerators) => {
+                output.push_str(&generate_enum_conversions(&ffi_type.name, enumerators, package));
+            }
+        }
+    }
+
+    output
+}
+
+/// std::vector <-> java.util.List helpers shared by all @ffi types
+const LIST_HELPERS: &str = r#"template <typename T>
+static jobject whitehall_list_to_java(JNIEnv* env, const std::vector<T>& items, jobject (*convert)(JNIEnv*, const T&)) {
+    jclass list_class = env->FindClass("java/util/ArrayList");
+    jmethodID init = env->GetMethodID(list_class, "<init>", "(I)V");
+    jmethodID add = env->GetMethodID(list_class, "add", "(Ljava/lang/Object;)Z");
+    jobject list = env->NewObject(list_class, init, (jint) items.size());
+    for (const T& item : items) {
+        jobject element = convert(env, item);
+        env->CallBooleanMethod(list, add, element);
+        env->DeleteLocalRef(element);
+    }
+    env->DeleteLocalRef(list_class);
+    return list;
+}
+
+template <typename T>
+static std::vector<T> whitehall_list_from_java(JNIEnv* env, jobject list, T (*convert)(JNIEnv*, jobject)) {
+    jclass list_class = env->FindClass("java/util/List");
+    jmethodID size = env->GetMethodID(list_class, "size", "()I");
+    jmethodID get = env->GetMethodID(list_class, "get", "(I)Ljava/lang/Object;");
+    jint len = env->CallIntMethod(list, size);
+    std::vector<T> items;
+    items.reserve(len);
+    for (jint i = 0; i < len; ++i) {
+        jobject element = env->CallObjectMethod(list, get, i);
+        items.push_back(convert(env, element));
+        env->DeleteLocalRef(element);
+    }
+    env->DeleteLocalRef(list_class);
+    return items;
+}
+
+"#;
+
+/// Generate the C++ -> Kotlin conversion for a struct (field-by-field constructor call)
+fn generate_struct_to_java(name: &str, fields: &[(String, CppType)], package: &str) -> String {
+    let mut output = String::new();
+    let mut signature = String::from("(");
+    let mut args = Vec::new();
+    let mut locals = Vec::new();
+
+    output.push_str(&format!("static jobject {}(JNIEnv* env, const {}& value) {{\n", to_java_fn(name), name));
+    output.push_str(&format!("    jclass cls = env->FindClass(\"{}\");\n", jvm_class(package, name)));
+
+    for (field, typ) in fields {
+        signature.push_str(&jni_signature(typ, package));
+        match typ {
+            CppType::String => {
+                output.push_str(&format!("    jstring f_{} = env->NewStringUTF(value.{}.c_str());\n", field, field));
+                locals.push(format!("f_{}", field));
+                args.push(format!("f_{}", field));
+            }
+            CppType::Custom(type_name) => {
+                output.push_str(&format!("    jobject f_{} = {}(env, value.{});\n", field, to_java_fn(type_name), field));
+                locals.push(format!("f_{}", field));
+                args.push(format!("f_{}", field));
+            }
+            CppType::CustomList(type_name) => {
+                output.push_str(&format!(
+                    "    jobject f_{} = whitehall_list_to_java(env, value.{}, {});\n",
+                    field, field, to_java_fn(type_name)
+                ));
+                locals.push(format!("f_{}", field));
+                args.push(format!("f_{}", field));
+            }
+            _ => args.push(format!("({}) value.{}", typ.to_jni_type(), field)),
+        }
+    }
+    signature.push_str(")V");
+
+    output.push_str(&format!("    jmethodID init = env->GetMethodID(cls, \"<init>\", \"{}\");\n", signature));
+    if args.is_empty() {
+        output.push_str("    jobject result = env->NewObject(cls, init);\n");
+    } else {
+        output.push_str(&format!("    jobject result = env->NewObject(cls, init, {});\n", args.join(", ")));
+    }
+    for local in &locals {
+        output.push_str(&format!("    env->DeleteLocalRef({});\n", local));
+    }
+    output.push_str("    env->DeleteLocalRef(cls);\n");
+    output.push_str("    return result;\n");
+    output.push_str("}\n\n");
+
+    output
+}
+
+/// Generate the Kotlin -> C++ conversion for a struct (field-by-field reads)
+fn generate_struct_from_java(name: &str, fields: &[(String, CppType)], package: &str) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("static {} {}(JNIEnv* env, jobject obj) {{\n", name, from_java_fn(name)));
+    output.push_str("    jclass cls = env->GetObjectClass(obj);\n");
+    output.push_str(&format!("    {} value;\n", name));
+
+    for (field, typ) in fields {
+        let field_id = format!(
+            "env->GetFieldID(cls, \"{}\", \"{}\")",
+            snake_to_camel(field),
+            jni_signature(typ, package)
+        );
+        match typ {
+            CppType::Int => output.push_str(&format!("    value.{} = env->GetIntField(obj, {});\n", field, field_id)),
+            CppType::Long => output.push_str(&format!("    value.{} = env->GetLongField(obj, {});\n", field, field_id)),
+            CppType::Float => output.push_str(&format!("    value.{} = env->GetFloatField(obj, {});\n", field, field_id)),
+            CppType::Double => output.push_str(&format!("    value.{} = env->GetDoubleField(obj, {});\n", field, field_id)),
+            CppType::Bool => output.push_str(&format!(
+                "    value.{} = env->GetBooleanField(obj, {}) == JNI_TRUE;\n",
+                field, field_id
+            )),
+            CppType::String => {
+                output.push_str("    {\n");
+                output.push_str(&format!("        jstring f = (jstring) env->GetObjectField(obj, {});\n", field_id));
+                output.push_str("        const char* c_str = env->GetStringUTFChars(f, nullptr);\n");
+                output.push_str(&format!("        value.{} = c_str;\n", field));
+                output.push_str("        env->ReleaseStringUTFChars(f, c_str);\n");
+                output.push_str("        env->DeleteLocalRef(f);\n");
+                output.push_str("    }\n");
+            }
+            CppType::Custom(type_name) => {
+                output.push_str("    {\n");
+                output.push_str(&format!("        jobject f = env->GetObjectField(obj, {});\n", field_id));
+                output.push_str(&format!("        value.{} = {}(env, f);\n", field, from_java_fn(type_name)));
+                output.push_str("        env->DeleteLocalRef(f);\n");
+                output.push_str("    }\n");
+            }
+            CppType::CustomList(type_name) => {
+                output.push_str("    {\n");
+                output.push_str(&format!("        jobject f = env->GetObjectField(obj, {});\n", field_id));
+                output.push_str(&format!(
+                    "        value.{} = whitehall_list_from_java(env, f, {});\n",
+                    field, from_java_fn(type_name)
+                ));
+                output.push_str("        env->DeleteLocalRef(f);\n");
+                output.push_str("    }\n");
+            }
+            _ => unreachable!("unsupported struct field type"),
+        }
+    }
+
+    output.push_str("    env->DeleteLocalRef(cls);\n");
+    output.push_str("    return value;\n");
+    output.push_str("}\n\n");
+
+    output
+}
+
+/// Generate both conversions for an enum class (Kotlin enum entries by name/ordinal)
+fn generate_enum_conversions(name: &str, enumerators: &[String], package: &str) -> String {
+    let mut output = String::new();
+    let class = jvm_class(package, name);
+
+    output.push_str(&format!("static jobject {}(JNIEnv* env, const {}& value) {{\n", to_java_fn(name), name));
+    output.push_str("    const char* name = nullptr;\n");
+    output.push_str("    switch (value) {\n");
+    for enumerator in enumerators {
+        output.push_str(&format!(
+            "        case {}::{}: name = \"{}\"; break;\n",
+            name, enumerator, enumerator
+        ));
+    }
+    output.push_str("    }\n");
+    output.push_str(&format!("    jclass cls = env->FindClass(\"{}\");\n", class));
+    output.push_str(&format!(
+        "    jobject result = env->GetStaticObjectField(cls, env->GetStaticFieldID(cls, name, \"L{};\"));\n",
+        class
+    ));
+    output.push_str("    env->DeleteLocalRef(cls);\n");
+    output.push_str("    return result;\n");
+    output.push_str("}\n\n");
+
+    output.push_str(&format!("static {} {}(JNIEnv* env, jobject obj) {{\n", name, from_java_fn(name)));
+    output.push_str("    jclass cls = env->GetObjectClass(obj);\n");
+    output.push_str("    jint ordinal = env->CallIntMethod(obj, env->GetMethodID(cls, \"ordinal\", \"()I\"));\n");
+    output.push_str("    env->DeleteLocalRef(cls);\n");
+    output.push_str(&format!("    return static_cast<{}>(ordinal);\n", name));
+    output.push_str("}\n\n");
+
+    output
+}
+
+/// Generate JNI function name from Kotlin package, object and function name
 /// Format: Java_com_example_ffi_ClassName_methodName
-fn generate_jni_name(function_name: &str, package: &str) -> String {
-    // Replace dots with underscores and add class name
-    let package_path = package.replace('.', "_");
-    // For now, we'll use "Math" as the default class name
-    // TODO: Make this configurable or derive from source file
-    format!("Java_{}_Math_{}", package_path, function_name)
+///
+/// Functions in a namespace are declared in a nested Kotlin object
+/// (`Math$Audio`). Overloaded functions get the long form with the argument
+/// signature appended (`Java_..._Math_area__DD`) so each has a unique symbol.
+fn generate_jni_name(
+    function: &CppFfiFunction,
+    functions: &[CppFfiFunction],
+    package: &str,
+    object_name: &str,
+) -> String {
+    let class = std::iter::once(object_name.to_string())
+        .chain(function.kotlin_scope())
+        .collect::<Vec<_>>()
+        .join("$");
+    let mut jni_name = format!(
+        "Java_{}_{}_{}",
+        jni_mangle(package),
+        jni_mangle(&class),
+        jni_mangle(&snake_to_camel(&function.name))
+    );
+
+    let overloaded = functions
+        .iter()
+        .filter(|f| f.namespace == function.namespace && f.name == function.name)
+        .count() > 1;
+    if overloaded {
+        let class_path = jvm_class(package, &class);
+        let arguments: String = function
+            .params
+            .iter()
+            .map(|(_, typ)| match typ {
+                CppType::Function(callback) => format!("L{}${};", class_path, callback.interface_name),
+                _ => jni_signature(typ, package),
+            })
+            .collect();
+        jni_name.push_str("__");
+        jni_name.push_str(&jni_mangle(&arguments));
+    }
+
+    jni_name
+}
+
+/// Escape a Java name or type signature for use in a JNI symbol
+fn jni_mangle(name: &str) -> String {
+    let mut mangled = String::new();
+    for ch in name.chars() {
+        match ch {
+            '.' | '/' => mangled.push('_'),
+            '_' => mangled.push_str("_1"),
+            ';' => mangled.push_str("_2"),
+            '[' => mangled.push_str("_3"),
+            ch if ch.is_ascii_alphanumeric() => mangled.push(ch),
+            ch => mangled.push_str(&format!("_0{:04x}", ch as u32)),
+        }
+    }
+    mangled
 }
 
 #[cfg(test)]
@@ -424,16 +1174,18 @@ mod tests {
     fn test_generate_simple_jni_function() {
         let func = CppFfiFunction {
             name: "add".to_string(),
+            namespace: Vec::new(),
             params: vec![
                 ("a".to_string(), CppType::Int),
                 ("b".to_string(), CppType::Int),
             ],
             return_type: CppType::Int,
+            docs: Default::default(),
             source_file: PathBuf::from("math.cpp"),
         };
 
         let functions = vec![func];
-        let bridge = generate_jni_bridge(&functions, "com.example.ffi", &["math.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.example.ffi", "Math");
 
         println!("{}", bridge);
 
@@ -445,17 +1197,56 @@ mod tests {
         assert!(bridge.contains("return add(a, b);"));
     }
 
+    #[test]
+    fn test_generate_c_jni_bridge() {
+        let functions = vec![
+            CppFfiFunction {
+                name: "add".to_string(),
+                namespace: Vec::new(),
+                params: vec![("a".to_string(), CppType::Int), ("b".to_string(), CppType::Int)],
+                return_type: CppType::Int,
+                docs: Default::default(),
+                source_file: PathBuf::from("src/ffi/c/math.h"),
+            },
+            CppFfiFunction {
+                name: "join_words".to_string(),
+                namespace: Vec::new(),
+                params: vec![("first".to_string(), CppType::String), ("second".to_string(), CppType::String)],
+                return_type: CppType::String,
+                docs: Default::default(),
+                source_file: PathBuf::from("src/ffi/c/text.h"),
+            },
+        ];
+
+        let bridge = generate_c_jni_bridge(&functions, "com.example.ffi.c", "Math");
+
+        assert!(bridge.contains("#include \"math.h\"\n#include \"text.h\""));
+        assert!(!bridge.contains("extern \"C\""));
+        assert!(!bridge.contains("try {"));
+        assert!(bridge.contains("JNIEXPORT jint JNICALL\nJava_com_example_ffi_c_Math_add("));
+        assert!(bridge.contains("jint result = (jint) add(a, b);"));
+        assert!(bridge.contains("Java_com_example_ffi_c_Math_joinWords("));
+        assert!(bridge.contains("const char* result = join_words(c_first, c_second);"));
+        // A failed second conversion releases the first
+        assert!(bridge.contains(
+            "    if (c_second == NULL) {\n        (*env)->ReleaseStringUTFChars(env, first, c_first);\n        return NULL;"
+        ));
+        assert!(bridge.contains("return (*env)->NewStringUTF(env, result);"));
+    }
+
     #[test]
     fn test_generate_no_params_function() {
         let func = CppFfiFunction {
             name: "getRandom".to_string(),
+            namespace: Vec::new(),
             params: vec![],
             return_type: CppType::Int,
+            docs: Default::default(),
             source_file: PathBuf::from("random.cpp"),
         };
 
         let functions = vec![func];
-        let bridge = generate_jni_bridge(&functions, "com.test", &["random.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.test", "Math");
 
         assert!(bridge.contains("int getRandom();"));
         assert!(bridge.contains("return getRandom();"));
@@ -465,13 +1256,15 @@ mod tests {
     fn test_generate_void_return() {
         let func = CppFfiFunction {
             name: "doSomething".to_string(),
+            namespace: Vec::new(),
             params: vec![("x".to_string(), CppType::Int)],
             return_type: CppType::Void,
+            docs: Default::default(),
             source_file: PathBuf::from("test.cpp"),
         };
 
         let functions = vec![func];
-        let bridge = generate_jni_bridge(&functions, "com.test", &["test.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.test", "Math");
 
         assert!(bridge.contains("void doSomething(int);"));
         assert!(bridge.contains("void JNICALL"));
@@ -484,25 +1277,29 @@ mod tests {
         let functions = vec![
             CppFfiFunction {
                 name: "add".to_string(),
+                namespace: Vec::new(),
                 params: vec![
                     ("a".to_string(), CppType::Int),
                     ("b".to_string(), CppType::Int),
                 ],
                 return_type: CppType::Int,
+                docs: Default::default(),
                 source_file: PathBuf::from("math.cpp"),
             },
             CppFfiFunction {
                 name: "multiply".to_string(),
+                namespace: Vec::new(),
                 params: vec![
                     ("x".to_string(), CppType::Double),
                     ("y".to_string(), CppType::Double),
                 ],
                 return_type: CppType::Double,
+                docs: Default::default(),
                 source_file: PathBuf::from("math.cpp"),
             },
         ];
 
-        let bridge = generate_jni_bridge(&functions, "com.example", &["math.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.example", "Math");
 
         assert!(bridge.contains("int add(int, int);"));
         assert!(bridge.contains("double multiply(double, double);"));
@@ -515,25 +1312,31 @@ mod tests {
         let functions = vec![
             CppFfiFunction {
                 name: "testBool".to_string(),
+                namespace: Vec::new(),
                 params: vec![("b".to_string(), CppType::Bool)],
                 return_type: CppType::Bool,
+                docs: Default::default(),
                 source_file: PathBuf::from("test.cpp"),
             },
             CppFfiFunction {
                 name: "testFloat".to_string(),
+                namespace: Vec::new(),
                 params: vec![("f".to_string(), CppType::Float)],
                 return_type: CppType::Float,
+                docs: Default::default(),
                 source_file: PathBuf::from("test.cpp"),
             },
             CppFfiFunction {
                 name: "testLong".to_string(),
+                namespace: Vec::new(),
                 params: vec![("l".to_string(), CppType::Long)],
                 return_type: CppType::Long,
+                docs: Default::default(),
                 source_file: PathBuf::from("test.cpp"),
             },
         ];
 
-        let bridge = generate_jni_bridge(&functions, "com.test", &["test.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.test", "Math");
 
         assert!(bridge.contains("bool testBool(bool);"));
         assert!(bridge.contains("float testFloat(float);"));
@@ -545,13 +1348,45 @@ mod tests {
 
     #[test]
     fn test_jni_name_generation() {
+        let function = |name: &str, namespace: &[&str], params: Vec<CppType>| CppFfiFunction {
+            name: name.to_string(),
+            namespace: namespace.iter().map(|n| n.to_string()).collect(),
+            params: params.into_iter().enumerate().map(|(i, t)| (format!("p{}", i), t)).collect(),
+            return_type: CppType::Double,
+            docs: Default::default(),
+            source_file: PathBuf::from("math.cpp"),
+        };
+
+        let add = function("add", &[], vec![CppType::Int]);
         assert_eq!(
-            generate_jni_name("add", "com.example.ffi"),
+            generate_jni_name(&add, &[add.clone()], "com.example.ffi", "Math"),
             "Java_com_example_ffi_Math_add"
         );
+
+        // Kotlin names are camelCase; '_' in the package is escaped
+        let snake = function("fast_inverse", &["audio_dsp"], vec![]);
+        assert_eq!(
+            generate_jni_name(&snake, &[snake.clone()], "org.my_app", "Math"),
+            "Java_org_my_1app_Math_00024AudioDsp_fastInverse"
+        );
+
+        // Overloads get the argument signature appended
+        let overloads = vec![
+            function("area", &["geo"], vec![CppType::Double]),
+            function("area", &["geo"], vec![CppType::Double, CppType::DoubleArray, CppType::String]),
+            function("area", &[], vec![]),
+        ];
+        assert_eq!(
+            generate_jni_name(&overloads[0], &overloads, "com.example", "Math"),
+            "Java_com_example_Math_00024Geo_area__D"
+        );
+        assert_eq!(
+            generate_jni_name(&overloads[1], &overloads, "com.example", "Math"),
+            "Java_com_example_Math_00024Geo_area__D_3DLjava_lang_String_2"
+        );
         assert_eq!(
-            generate_jni_name("multiply", "org.test"),
-            "Java_org_test_Math_multiply"
+            generate_jni_name(&overloads[2], &overloads, "com.example", "Math"),
+            "Java_com_example_Math_area"
         );
     }
 
@@ -559,12 +1394,14 @@ mod tests {
     fn test_auto_generated_comment() {
         let functions = vec![CppFfiFunction {
             name: "test".to_string(),
+            namespace: Vec::new(),
             params: vec![],
             return_type: CppType::Void,
+            docs: Default::default(),
             source_file: PathBuf::from("test.cpp"),
         }];
 
-        let bridge = generate_jni_bridge(&functions, "com.test", &["test.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.test", "Math");
 
         assert!(bridge.contains("Auto-generated by Whitehall FFI"));
         assert!(bridge.contains("DO NOT EDIT MANUALLY"));
@@ -574,15 +1411,17 @@ mod tests {
     fn test_string_parameter_conversion() {
         let func = CppFfiFunction {
             name: "greet".to_string(),
+            namespace: Vec::new(),
             params: vec![
                 ("name".to_string(), CppType::String),
             ],
             return_type: CppType::String,
+            docs: Default::default(),
             source_file: PathBuf::from("string.cpp"),
         };
 
         let functions = vec![func];
-        let bridge = generate_jni_bridge(&functions, "com.example", &["string.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.example", "Math");
 
         println!("{}", bridge);
 
@@ -610,16 +1449,18 @@ mod tests {
     fn test_mixed_string_and_primitives() {
         let func = CppFfiFunction {
             name: "formatNumber".to_string(),
+            namespace: Vec::new(),
             params: vec![
                 ("format".to_string(), CppType::String),
                 ("number".to_string(), CppType::Int),
             ],
             return_type: CppType::String,
+            docs: Default::default(),
             source_file: PathBuf::from("format.cpp"),
         };
 
         let functions = vec![func];
-        let bridge = generate_jni_bridge(&functions, "com.test", &["format.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.test", "Math");
 
         // Check forward declaration includes both types
         assert!(bridge.contains("std::string formatNumber(const std::string&, int);"));
@@ -637,13 +1478,15 @@ mod tests {
     fn test_string_return_only() {
         let func = CppFfiFunction {
             name: "getMessage".to_string(),
+            namespace: Vec::new(),
             params: vec![],
             return_type: CppType::String,
+            docs: Default::default(),
             source_file: PathBuf::from("msg.cpp"),
         };
 
         let functions = vec![func];
-        let bridge = generate_jni_bridge(&functions, "com.test", &["msg.cpp".to_string()]);
+        let bridge = generate_jni_bridge(&functions, &[], "com.test", "Math");
 
         assert!(bridge.contains("std::string getMessage();"));
         assert!(bridge.contains("jstring JNICALL"));
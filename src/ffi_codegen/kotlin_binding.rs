@@ -1,12 +1,14 @@
-use crate::ffi_parser::cpp::{CppFfiFunction, CppType};
-use crate::ffi_parser::rust::{RustFfiFunction, RustType};
+use crate::ffi_parser::cpp::{CppFfiFunction, CppFfiType, CppFfiTypeKind, CppType};
+use crate::ffi_parser::docs::{kotlin_string_literal, FfiDocs};
+use crate::ffi_parser::rust::{MethodReceiver, RustFfiClass, RustFfiFunction, RustFfiType, RustFfiTypeKind, RustType};
+use super::rust_bridge::{external_name, native_name};
 
 /// Generate a single Kotlin external function declaration
 pub fn generate_kotlin_binding(function: &CppFfiFunction) -> String {
     let params = function
         .params
         .iter()
-        .map(|(name, typ)| format!("{}: {}", name, typ.to_kotlin_type()))
+        .map(|(name, typ)| kotlin_param(name, typ.to_kotlin_type(), &function.docs))
         .collect::<Vec<_>>()
         .join(", ");
 
@@ -19,11 +21,43 @@ pub fn generate_kotlin_binding(function: &CppFfiFunction) -> String {
     let kotlin_name = snake_to_camel(&function.name);
 
     format!(
-        "    external fun {}({}){}\n",
-        kotlin_name, params, return_type
+        "{}    external fun {}({}){}\n",
+        generate_kdoc(&function.docs, "    "), kotlin_name, params, return_type
     )
 }
 
+/// KDoc and `@Deprecated` annotation for a generated declaration, from the native docs
+fn generate_kdoc(docs: &FfiDocs, indent: &str) -> String {
+    let mut output = String::new();
+
+    if !docs.lines.is_empty() {
+        output.push_str(&format!("{}/**\n", indent));
+        for line in &docs.lines {
+            // A literal "*/" would end the KDoc early
+            let line = line.replace("*/", "*&#47;");
+            if line.is_empty() {
+                output.push_str(&format!("{} *\n", indent));
+            } else {
+                output.push_str(&format!("{} * {}\n", indent, line));
+            }
+        }
+        output.push_str(&format!("{} */\n", indent));
+    }
+    if let Some(message) = &docs.deprecated {
+        output.push_str(&format!("{}@Deprecated({})\n", indent, kotlin_string_literal(message)));
+    }
+
+    output
+}
+
+/// Kotlin parameter declaration, with the default value declared natively, if any
+fn kotlin_param(name: &str, kotlin_type: String, docs: &FfiDocs) -> String {
+    match docs.default_for(name) {
+        Some(value) => format!("{}: {} = {}", name, kotlin_type, value),
+        None => format!("{}: {}", name, kotlin_type),
+    }
+}
+
 /// Generate a complete Kotlin object with all FFI functions
 pub fn generate_kotlin_object(
     functions: &[CppFfiFunction],
@@ -42,17 +76,63 @@ pub fn generate_kotlin_object(
     output.push_str(" * DO NOT EDIT MANUALLY\n");
     output.push_str(" */\n");
 
+    output.push_str(&generate_kotlin_scope(functions, &[], object_name, library_name));
+
+    output
+}
+
+/// Kotlin object for the functions in `scope`, with a nested object per sub-namespace
+fn generate_kotlin_scope(
+    functions: &[CppFfiFunction],
+    scope: &[String],
+    object_name: &str,
+    library_name: &str,
+) -> String {
+    let mut output = String::new();
+    let members: Vec<CppFfiFunction> = functions
+        .iter()
+        .filter(|function| function.kotlin_scope() == scope)
+        .cloned()
+        .collect();
+
     // Object declaration
     output.push_str(&format!("object {} {{\n", object_name));
 
+    // Kotlin side of std::function parameters
+    output.push_str(&generate_kotlin_closure_interfaces(&members));
+
     // External function declarations
-    for function in functions {
+    for function in &members {
         output.push_str(&generate_kotlin_binding(function));
     }
 
+    // Nested objects for namespaces, in order of first appearance
+    let mut nested: Vec<String> = Vec::new();
+    for function in functions {
+        let function_scope = function.kotlin_scope();
+        if function_scope.len() > scope.len() && function_scope.starts_with(scope) {
+            let name = &function_scope[scope.len()];
+            if !nested.contains(name) {
+                nested.push(name.clone());
+            }
+        }
+    }
+    for name in nested {
+        let mut nested_scope = scope.to_vec();
+        nested_scope.push(name.clone());
+        output.push('\n');
+        for line in generate_kotlin_scope(functions, &nested_scope, &name, library_name).lines() {
+            if line.is_empty() {
+                output.push('\n');
+            } else {
+                output.push_str(&format!("    {}\n", line));
+            }
+        }
+    }
+
     output.push('\n');
 
-    // init block to load library
+    // init block to load library; each nested object is its own class, initialized on first use
     output.push_str("    init {\n");
     output.push_str(&format!("        System.loadLibrary(\"{}\")\n", library_name));
     output.push_str("    }\n");
@@ -63,6 +143,75 @@ pub fn generate_kotlin_object(
     output
 }
 
+/// Generate Kotlin classes for @ffi structs (data classes) and enum classes
+pub fn generate_kotlin_types(types: &[CppFfiType], package: &str) -> String {
+    let mut output = String::new();
+
+    // Package declaration
+    output.push_str(&format!("package {}\n\n", package));
+
+    // Auto-generated comment
+    output.push_str("/**\n");
+    output.push_str(" * Auto-generated by Whitehall FFI\n");
+    output.push_str(" * DO NOT EDIT MANUALLY\n");
+    output.push_str(" */\n");
+
+    for ffi_type in types {
+        output.push('\n');
+        match &ffi_type.kind {
+            CppFfiTypeKind::Struct(fields) => {
+                output.push_str(&format!("data class {}(\n", ffi_type.name));
+                for (name, typ) in fields {
+                    output.push_str(&format!("    val {}: {},\n", snake_to_camel(name), typ.to_kotlin_type()));
+                }
+                output.push_str(")\n");
+            }
+            CppFfiTypeKind::Enum(enumerators) => {
+                output.push_str(&format!("enum class {} {{\n", ffi_type.name));
+                for enumerator in enumerators {
+                    output.push_str(&format!("    {},\n", enumerator));
+                }
+                output.push_str("}\n");
+            }
+        }
+    }
+
+    output
+}
+
+/// Generate the `fun interface` for each std::function parameter, nested in the object
+fn generate_kotlin_closure_interfaces(functions: &[CppFfiFunction]) -> String {
+    let mut output = String::new();
+    let mut declared = Vec::new();
+
+    for function in functions {
+        for (_, typ) in &function.params {
+            let CppType::Function(callback) = typ else { continue };
+            // Overloads may share a callback interface
+            if declared.contains(&callback.interface_name) {
+                continue;
+            }
+            declared.push(callback.interface_name.clone());
+            let params = callback
+                .param_names()
+                .iter()
+                .zip(&callback.params)
+                .map(|(name, typ)| format!("{}: {}", name, typ.to_kotlin_type()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let return_type = match callback.return_type {
+                CppType::Void => String::new(),
+                ref ret => format!(": {}", ret.to_kotlin_type()),
+            };
+            output.push_str(&format!("    fun interface {} {{\n", callback.interface_name));
+            output.push_str(&format!("        fun invoke({}){}\n", params, return_type));
+            output.push_str("    }\n\n");
+        }
+    }
+
+    output
+}
+
 /// Convert snake_case to camelCase
 fn snake_to_camel(name: &str) -> String {
     let mut result = String::new();
@@ -83,11 +232,17 @@ fn snake_to_camel(name: &str) -> String {
 }
 
 /// Generate a single Kotlin external function declaration from Rust function
+///
+/// `#[ffi(async)]` functions get a private external plus a `suspend fun` wrapper.
 pub fn generate_kotlin_binding_rust(function: &RustFfiFunction) -> String {
+    if function.is_async {
+        return generate_kotlin_suspend_binding_rust(function);
+    }
+
     let params = function
         .params
         .iter()
-        .map(|(name, typ)| format!("{}: {}", name, typ.to_kotlin_type()))
+        .map(|(name, typ)| kotlin_param(name, typ.to_kotlin_type(), &function.docs))
         .collect::<Vec<_>>()
         .join(", ");
 
@@ -100,11 +255,68 @@ pub fn generate_kotlin_binding_rust(function: &RustFfiFunction) -> String {
     let kotlin_name = snake_to_camel(&function.name);
 
     format!(
-        "    external fun {}({}){}\n",
-        kotlin_name, params, return_type
+        "{}    external fun {}({}){}\n",
+        generate_kdoc(&function.docs, "    "), kotlin_name, params, return_type
     )
 }
 
+/// Generate a `suspend fun` that runs the native call on `Dispatchers.IO`
+///
+/// The coroutine resumes as soon as it is cancelled; a `&AtomicBool` parameter on the
+/// Rust side receives a flag that is set at that point so the work can stop early.
+/// Exceptions thrown by the bridge (e.g. `Result` errors) are rethrown to the caller.
+fn generate_kotlin_suspend_binding_rust(function: &RustFfiFunction) -> String {
+    let mut output = String::new();
+
+    let native_name = external_name(function);
+    let kotlin_name = snake_to_camel(&function.name);
+    let return_type = match function.return_type.base_type {
+        RustType::Void => "Unit".to_string(),
+        _ => function.return_type.to_kotlin_type(),
+    };
+
+    let external_params = function
+        .params
+        .iter()
+        .map(|(name, typ)| format!("{}: {}", name, typ.to_kotlin_type()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let wrapper_params = function
+        .kotlin_params()
+        .map(|(name, typ)| kotlin_param(name, typ.to_kotlin_type(), &function.docs))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let args = function
+        .params
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    output.push_str(&format!(
+        "    private external fun {}({}): {}\n\n",
+        native_name, external_params, return_type
+    ));
+    output.push_str(&generate_kdoc(&function.docs, "    "));
+    output.push_str(&format!(
+        "    suspend fun {}({}): {} = suspendCancellableCoroutine {{ continuation ->\n",
+        kotlin_name, wrapper_params, return_type
+    ));
+    if let Some((flag, _)) = function.params.iter().find(|(_, typ)| *typ == RustType::CancelFlag) {
+        output.push_str(&format!("        val {} = java.nio.ByteBuffer.allocateDirect(1)\n", flag));
+        output.push_str(&format!("        continuation.invokeOnCancellation {{ {}.put(0, 1) }}\n", flag));
+    }
+    output.push_str("        Dispatchers.IO.asExecutor().execute {\n");
+    output.push_str(&format!(
+        "            continuation.resumeWith(runCatching {{ {}({}) }})\n",
+        native_name, args
+    ));
+    output.push_str("        }\n");
+    output.push_str("    }\n");
+
+    output
+}
+
 /// Generate a complete Kotlin object with all Rust FFI functions
 pub fn generate_kotlin_object_rust(
     functions: &[RustFfiFunction],
@@ -117,6 +329,12 @@ pub fn generate_kotlin_object_rust(
     // Package declaration
     output.push_str(&format!("package {}\n\n", package));
 
+    if functions.iter().any(|f| f.is_async) {
+        output.push_str("import kotlinx.coroutines.Dispatchers\n");
+        output.push_str("import kotlinx.coroutines.asExecutor\n");
+        output.push_str("import kotlinx.coroutines.suspendCancellableCoroutine\n\n");
+    }
+
     // Auto-generated comment
     output.push_str("/**\n");
     output.push_str(" * Auto-generated by Whitehall FFI (Rust)\n");
@@ -126,6 +344,9 @@ pub fn generate_kotlin_object_rust(
     // Object declaration
     output.push_str(&format!("object {} {{\n", object_name));
 
+    // Kotlin side of `impl Fn(..)` parameters
+    output.push_str(&generate_kotlin_closure_interfaces_rust(functions));
+
     // External function declarations
     for function in functions {
         output.push_str(&generate_kotlin_binding_rust(function));
@@ -144,6 +365,302 @@ pub fn generate_kotlin_object_rust(
     output
 }
 
+/// First Android API level that ships `java.lang.ref.Cleaner`
+const CLEANER_MIN_SDK: u32 = 33;
+
+/// Generate the Kotlin class for an #[ffi] impl block
+///
+/// The class owns a native handle: `close()` frees it deterministically, and it is also
+/// freed if the object is garbage collected without being closed. That uses a shared
+/// `Cleaner` when `min_sdk` has one (API 33+) and a `PhantomReference` queue drained by
+/// a daemon thread otherwise. A read-write lock keeps `close()` from freeing the handle
+/// while a method is using it; closing from inside a method throws instead of deadlocking.
+pub fn generate_kotlin_class_rust(class: &RustFfiClass, package: &str, library_name: &str, min_sdk: u32) -> String {
+    let mut output = String::new();
+    let name = &class.name;
+    let use_cleaner = min_sdk >= CLEANER_MIN_SDK;
+
+    // Package declaration
+    output.push_str(&format!("package {}\n\n", package));
+    if use_cleaner {
+        output.push_str("import java.lang.ref.Cleaner\n");
+    } else {
+        output.push_str("import java.lang.ref.PhantomReference\n");
+        output.push_str("import java.lang.ref.ReferenceQueue\n");
+        output.push_str("import java.util.concurrent.ConcurrentHashMap\n");
+    }
+    output.push_str("import java.util.concurrent.atomic.AtomicLong\n");
+    output.push_str("import java.util.concurrent.locks.ReentrantReadWriteLock\n");
+    if !use_cleaner {
+        output.push_str("import kotlin.concurrent.thread\n");
+    }
+    output.push_str("import kotlin.concurrent.read\n");
+    output.push_str("import kotlin.concurrent.write\n\n");
+
+    // Auto-generated comment
+    output.push_str("/**\n");
+    output.push_str(" * Auto-generated by Whitehall FFI (Rust)\n");
+    output.push_str(" * DO NOT EDIT MANUALLY\n");
+    output.push_str(" */\n");
+
+    output.push_str(&generate_kdoc(&class.docs, ""));
+    output.push_str(&format!("class {} private constructor(handle: Long) : AutoCloseable {{\n", name));
+    output.push_str("    private val handle = AtomicLong(handle)\n");
+    output.push_str("    private val lock = ReentrantReadWriteLock()\n");
+    if use_cleaner {
+        output.push_str("    private val cleanable = cleaner.register(this, Destroy(this.handle))\n\n");
+    } else {
+        output.push_str("    private val cleanable = Destroy(this, this.handle)\n\n");
+    }
+
+    // Kotlin side of `impl Fn(..)` parameters
+    let functions = class.methods.iter().map(|m| m.function.clone()).collect::<Vec<_>>();
+    output.push_str(&generate_kotlin_closure_interfaces_rust(&functions));
+
+    for method in class.methods.iter().filter(|m| m.receiver != MethodReceiver::Static) {
+        output.push_str(&generate_kotlin_method_rust(&method.function, true));
+    }
+
+    // A read lock can't be upgraded, so taking the write lock from inside a method
+    // (e.g. in a callback) would block forever
+    output.push_str("    override fun close() {\n");
+    output.push_str(&format!(
+        "        check(lock.readHoldCount == 0) {{ \"{}.close() called from inside one of its methods\" }}\n",
+        name
+    ));
+    output.push_str("        lock.write { cleanable.clean() }\n");
+    output.push_str("    }\n\n");
+
+    output.push_str("    private inline fun <T> withHandle(block: (Long) -> T): T = lock.read {\n");
+    output.push_str("        val current = handle.get()\n");
+    output.push_str(&format!("        check(current != 0L) {{ \"{} is closed\" }}\n", name));
+    output.push_str("        block(current)\n");
+    output.push_str("    }\n\n");
+
+    if use_cleaner {
+        // The cleaning action must not reference the instance, or it would never be collected
+        output.push_str("    private class Destroy(private val handle: AtomicLong) : Runnable {\n");
+        output.push_str("        override fun run() {\n");
+        output.push_str("            val current = handle.getAndSet(0)\n");
+        output.push_str("            if (current != 0L) destroyHandle(current)\n");
+        output.push_str("        }\n");
+        output.push_str("    }\n\n");
+    } else {
+        // The phantom reference must stay reachable until it is enqueued, so live ones are
+        // kept in `pending`; `clean()` runs at most once, whether from close() or the queue
+        output.push_str(&format!(
+            "    private class Destroy(referent: {}, private val handle: AtomicLong) : PhantomReference<{}>(referent, queue) {{\n",
+            name, name
+        ));
+        output.push_str("        init {\n");
+        output.push_str("            pending.add(this)\n");
+        output.push_str("        }\n\n");
+        output.push_str("        fun clean() {\n");
+        output.push_str("            if (!pending.remove(this)) return\n");
+        output.push_str("            clear()\n");
+        output.push_str("            val current = handle.getAndSet(0)\n");
+        output.push_str("            if (current != 0L) destroyHandle(current)\n");
+        output.push_str("        }\n");
+        output.push_str("    }\n\n");
+    }
+
+    output.push_str("    companion object {\n");
+    if use_cleaner {
+        output.push_str("        private val cleaner = Cleaner.create()\n\n");
+    } else {
+        output.push_str(&format!("        private val queue = ReferenceQueue<{}>()\n", name));
+        output.push_str("        private val pending: MutableSet<Destroy> = ConcurrentHashMap.newKeySet()\n\n");
+    }
+    output.push_str("        init {\n");
+    output.push_str(&format!("            System.loadLibrary(\"{}\")\n", library_name));
+    if !use_cleaner {
+        output.push_str(&format!("            thread(isDaemon = true, name = \"{}-cleaner\") {{\n", name));
+        output.push_str("                while (true) {\n");
+        output.push_str("                    (queue.remove() as Destroy).clean()\n");
+        output.push_str("                }\n");
+        output.push_str("            }\n");
+    }
+    output.push_str("        }\n\n");
+
+    for method in class.methods.iter().filter(|m| m.receiver == MethodReceiver::Static) {
+        let wrapper = generate_kotlin_method_rust(&method.function, false);
+        for line in wrapper.lines() {
+            if line.is_empty() {
+                output.push('\n');
+            } else {
+                output.push_str(&format!("    {}\n", line));
+            }
+        }
+    }
+
+    for method in &class.methods {
+        let function = &method.function;
+        let mut params = Vec::new();
+        if method.receiver != MethodReceiver::Static {
+            params.push("handle: Long".to_string());
+        }
+        params.extend(function.params.iter().map(|(name, typ)| format!("{}: {}", name, typ.to_kotlin_type())));
+        let return_type = match function.return_type.base_type {
+            RustType::Void => String::new(),
+            RustType::Handle(_) => ": Long".to_string(),
+            _ => format!(": {}", function.return_type.to_kotlin_type()),
+        };
+        output.push_str(&format!(
+            "        @JvmStatic private external fun {}({}){}\n",
+            native_name(&function.name),
+            params.join(", "),
+            return_type
+        ));
+    }
+    output.push_str("        @JvmStatic private external fun destroyHandle(handle: Long)\n");
+    output.push_str("    }\n");
+
+    // Close class
+    output.push_str("}\n");
+
+    output
+}
+
+/// Generate the public wrapper for a method of an #[ffi] impl block
+///
+/// Instance methods pass the handle through `withHandle`; returning `Self` wraps the
+/// new handle in the class.
+fn generate_kotlin_method_rust(function: &RustFfiFunction, has_receiver: bool) -> String {
+    let params = function
+        .params
+        .iter()
+        .map(|(name, typ)| kotlin_param(name, typ.to_kotlin_type(), &function.docs))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut args = function.params.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+    if has_receiver {
+        args.insert(0, "it");
+    }
+
+    let mut call = format!("{}({})", native_name(&function.name), args.join(", "));
+    if has_receiver {
+        call = format!("withHandle {{ {} }}", call);
+    }
+    let return_type = match function.return_type.base_type {
+        RustType::Void => String::new(),
+        RustType::Handle(ref name) => {
+            call = format!("{}({})", name, call);
+            format!(": {}", name)
+        }
+        _ => format!(": {}", function.return_type.to_kotlin_type()),
+    };
+
+    format!(
+        "{}    fun {}({}){} = {}\n\n",
+        generate_kdoc(&function.docs, "    "), snake_to_camel(&function.name), params, return_type, call
+    )
+}
+
+/// Generate Kotlin classes for #[ffi] structs and enums
+///
+/// Structs become data classes, enums with fields become sealed classes (one
+/// data class or data object per variant) and field-less enums become enum classes.
+pub fn generate_kotlin_types_rust(types: &[RustFfiType], package: &str) -> String {
+    let mut output = String::new();
+
+    // Package declaration
+    output.push_str(&format!("package {}\n\n", package));
+
+    // Auto-generated comment
+    output.push_str("/**\n");
+    output.push_str(" * Auto-generated by Whitehall FFI (Rust)\n");
+    output.push_str(" * DO NOT EDIT MANUALLY\n");
+    output.push_str(" */\n");
+
+    for ffi_type in types {
+        output.push('\n');
+        match &ffi_type.kind {
+            RustFfiTypeKind::Struct(fields) => {
+                output.push_str(&format!("data class {}(\n", ffi_type.name));
+                for (name, typ) in fields {
+                    output.push_str(&format!("    val {}: {},\n", snake_to_camel(name), typ.to_kotlin_type()));
+                }
+                output.push_str(")\n");
+            }
+            RustFfiTypeKind::Enum(variants) if ffi_type.is_unit_enum() => {
+                output.push_str(&format!("enum class {} {{\n", ffi_type.name));
+                for variant in variants {
+                    output.push_str(&format!("    {},\n", variant.name));
+                }
+                output.push_str("}\n");
+            }
+            RustFfiTypeKind::Enum(variants) => {
+                output.push_str(&format!("sealed class {} {{\n", ffi_type.name));
+                for variant in variants {
+                    if variant.fields.is_empty() {
+                        output.push_str(&format!("    data object {} : {}()\n", variant.name, ffi_type.name));
+                    } else {
+                        let fields = variant
+                            .fields
+                            .iter()
+                            .map(|(name, typ)| format!("val {}: {}", snake_to_camel(name), typ.to_kotlin_type()))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        output.push_str(&format!(
+                            "    data class {}({}) : {}()\n",
+                            variant.name, fields, ffi_type.name
+                        ));
+                    }
+                }
+                output.push_str("}\n");
+            }
+            RustFfiTypeKind::Callback(methods) => {
+                // Single-method traits can be implemented with a lambda
+                let keyword = if methods.len() == 1 { "fun interface" } else { "interface" };
+                output.push_str(&format!("{} {} {{\n", keyword, ffi_type.name));
+                for method in methods {
+                    let params = method
+                        .params
+                        .iter()
+                        .map(|(name, typ)| format!("{}: {}", snake_to_camel(name), typ.to_kotlin_type()))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let return_type = match method.return_type {
+                        RustType::Void => String::new(),
+                        ref ret => format!(": {}", ret.to_kotlin_type()),
+                    };
+                    output.push_str(&format!("    fun {}({}){}\n", snake_to_camel(&method.name), params, return_type));
+                }
+                output.push_str("}\n");
+            }
+        }
+    }
+
+    output
+}
+
+/// Generate the `fun interface` for each `impl Fn(..)` parameter, nested in the object
+fn generate_kotlin_closure_interfaces_rust(functions: &[RustFfiFunction]) -> String {
+    let mut output = String::new();
+
+    for function in functions {
+        for (_, typ) in &function.params {
+            let RustType::Closure(closure) = typ else { continue };
+            let params = closure
+                .param_names()
+                .iter()
+                .zip(&closure.params)
+                .map(|(name, typ)| format!("{}: {}", name, typ.to_kotlin_type()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let return_type = match closure.return_type {
+                RustType::Void => String::new(),
+                ref ret => format!(": {}", ret.to_kotlin_type()),
+            };
+            output.push_str(&format!("    fun interface {} {{\n", closure.interface_name));
+            output.push_str(&format!("        fun invoke({}){}\n", params, return_type));
+            output.push_str("    }\n\n");
+        }
+    }
+
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,11 +670,13 @@ mod tests {
     fn test_generate_simple_binding() {
         let func = CppFfiFunction {
             name: "add".to_string(),
+            namespace: Vec::new(),
             params: vec![
                 ("a".to_string(), CppType::Int),
                 ("b".to_string(), CppType::Int),
             ],
             return_type: CppType::Int,
+            docs: Default::default(),
             source_file: PathBuf::from("test.cpp"),
         };
 
@@ -169,8 +688,10 @@ mod tests {
     fn test_generate_no_params() {
         let func = CppFfiFunction {
             name: "getRandom".to_string(),
+            namespace: Vec::new(),
             params: vec![],
             return_type: CppType::Int,
+            docs: Default::default(),
             source_file: PathBuf::from("test.cpp"),
         };
 
@@ -182,8 +703,10 @@ mod tests {
     fn test_generate_void_return() {
         let func = CppFfiFunction {
             name: "doSomething".to_string(),
+            namespace: Vec::new(),
             params: vec![("x".to_string(), CppType::Int)],
             return_type: CppType::Void,
+            docs: Default::default(),
             source_file: PathBuf::from("test.cpp"),
         };
 
@@ -196,20 +719,24 @@ mod tests {
         let functions = vec![
             CppFfiFunction {
                 name: "add".to_string(),
+                namespace: Vec::new(),
                 params: vec![
                     ("a".to_string(), CppType::Int),
                     ("b".to_string(), CppType::Int),
                 ],
                 return_type: CppType::Int,
+                docs: Default::default(),
                 source_file: PathBuf::from("math.cpp"),
             },
             CppFfiFunction {
                 name: "multiply".to_string(),
+                namespace: Vec::new(),
                 params: vec![
                     ("x".to_string(), CppType::Double),
                     ("y".to_string(), CppType::Double),
                 ],
                 return_type: CppType::Double,
+                docs: Default::default(),
                 source_file: PathBuf::from("math.cpp"),
             },
         ];
@@ -229,32 +756,42 @@ mod tests {
         let functions = vec![
             CppFfiFunction {
                 name: "testInt".to_string(),
+                namespace: Vec::new(),
                 params: vec![("a".to_string(), CppType::Int)],
                 return_type: CppType::Int,
+                docs: Default::default(),
                 source_file: PathBuf::from("test.cpp"),
             },
             CppFfiFunction {
                 name: "testLong".to_string(),
+                namespace: Vec::new(),
                 params: vec![("a".to_string(), CppType::Long)],
                 return_type: CppType::Long,
+                docs: Default::default(),
                 source_file: PathBuf::from("test.cpp"),
             },
             CppFfiFunction {
                 name: "testFloat".to_string(),
+                namespace: Vec::new(),
                 params: vec![("a".to_string(), CppType::Float)],
                 return_type: CppType::Float,
+                docs: Default::default(),
                 source_file: PathBuf::from("test.cpp"),
             },
             CppFfiFunction {
                 name: "testDouble".to_string(),
+                namespace: Vec::new(),
                 params: vec![("a".to_string(), CppType::Double)],
                 return_type: CppType::Double,
+                docs: Default::default(),
                 source_file: PathBuf::from("test.cpp"),
             },
             CppFfiFunction {
                 name: "testBool".to_string(),
+                namespace: Vec::new(),
                 params: vec![("a".to_string(), CppType::Bool)],
                 return_type: CppType::Bool,
+                docs: Default::default(),
                 source_file: PathBuf::from("test.cpp"),
             },
         ];
@@ -272,4 +809,59 @@ mod tests {
         assert!(kotlin.contains(": Double"));
         assert!(kotlin.contains(": Boolean"));
     }
+
+    #[test]
+    fn test_generate_kotlin_types_rust() {
+        use crate::ffi_parser::rust::parse_rust_ffi_types_from_string;
+
+        let rust = r#"
+            #[ffi]
+            pub struct Point { x: f64, y: f64 }
+
+            #[ffi]
+            pub struct Polygon { display_name: String, points: Vec<Point> }
+
+            #[ffi]
+            pub enum Shape { Circle { center: Point, radius: f64 }, Tagged(String), Empty }
+
+            #[ffi]
+            pub enum Color { Red, Green }
+        "#;
+        let types = parse_rust_ffi_types_from_string(rust, &PathBuf::from("lib.rs")).unwrap();
+
+        let kotlin = generate_kotlin_types_rust(&types, "com.example.ffi.rust");
+
+        assert!(kotlin.starts_with("package com.example.ffi.rust\n"));
+        assert!(kotlin.contains("data class Point(\n    val x: Double,\n    val y: Double,\n)\n"));
+        assert!(kotlin.contains("    val displayName: String,\n    val points: List<Point>,\n"));
+        assert!(kotlin.contains("sealed class Shape {\n"));
+        assert!(kotlin.contains("    data class Circle(val center: Point, val radius: Double) : Shape()\n"));
+        assert!(kotlin.contains("    data class Tagged(val value: String) : Shape()\n"));
+        assert!(kotlin.contains("    data object Empty : Shape()\n"));
+        assert!(kotlin.contains("enum class Color {\n    Red,\n    Green,\n}\n"));
+    }
+
+    #[test]
+    fn test_generate_kotlin_types_cpp() {
+        let types = vec![
+            CppFfiType {
+                name: "Point".to_string(),
+                kind: CppFfiTypeKind::Struct(vec![
+                    ("x".to_string(), CppType::Double),
+                    ("y".to_string(), CppType::Double),
+                ]),
+                source_file: PathBuf::from("geo.cpp"),
+            },
+            CppFfiType {
+                name: "Color".to_string(),
+                kind: CppFfiTypeKind::Enum(vec!["Red".to_string(), "Green".to_string()]),
+                source_file: PathBuf::from("geo.cpp"),
+            },
+        ];
+
+        let kotlin = generate_kotlin_types(&types, "com.example.ffi.cpp");
+
+        assert!(kotlin.contains("data class Point(\n    val x: Double,\n    val y: Double,\n)\n"));
+        assert!(kotlin.contains("enum class Color {\n    Red,\n    Green,\n}\n"));
+    }
 }
@@ -4,9 +4,10 @@ pub mod rust_bridge;
 pub mod cmake;
 
 pub use kotlin_binding::{
-    generate_kotlin_binding, generate_kotlin_object,
-    generate_kotlin_binding_rust, generate_kotlin_object_rust
+    generate_kotlin_binding, generate_kotlin_object, generate_kotlin_types,
+    generate_kotlin_binding_rust, generate_kotlin_object_rust, generate_kotlin_types_rust,
+    generate_kotlin_class_rust,
 };
-pub use jni_bridge::generate_jni_bridge;
+pub use jni_bridge::{generate_c_header, generate_c_jni_bridge, generate_jni_bridge};
 pub use rust_bridge::generate_rust_bridge;
-pub use cmake::generate_cmake;
+pub use cmake::{generate_c_cmake, generate_cmake, generate_zig_cmake};
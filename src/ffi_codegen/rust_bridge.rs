@@ -1,4 +1,7 @@
-use crate::ffi_parser::rust::{RustFfiFunction, RustType};
+use crate::ffi_parser::rust::{
+    CallbackPassing, MethodReceiver, RustCallbackMethod, RustFfiClass, RustFfiFunction, RustFfiType, RustFfiTypeKind,
+    RustType,
+};
 
 /// Convert snake_case to camelCase
 fn snake_to_camel(name: &str) -> String {
@@ -19,36 +22,96 @@ fn snake_to_camel(name: &str) -> String {
     result
 }
 
+/// Convert PascalCase to snake_case (for generated helper names)
+fn pascal_to_snake(name: &str) -> String {
+    let mut result = String::new();
+
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_ascii_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.push(ch.to_ascii_lowercase());
+        } else {
+            result.push(ch);
+        }
+    }
+
+    result
+}
+
 /// Generate complete Rust JNI bridge for all functions
 pub fn generate_rust_bridge(
     functions: &[RustFfiFunction],
+    types: &[RustFfiType],
+    classes: &[RustFfiClass],
     package: &str,
 ) -> String {
     let mut output = String::new();
 
+    // Free functions and #[ffi] impl methods share the same conversions
+    let all_functions = functions
+        .iter()
+        .chain(classes.iter().flat_map(|c| c.methods.iter().map(|m| &m.function)))
+        .collect::<Vec<_>>();
+
     // Header with imports
     output.push_str("// Auto-generated by Whitehall FFI (Rust)\n");
     output.push_str("// DO NOT EDIT MANUALLY\n\n");
     output.push_str("use jni::JNIEnv;\n");
-    output.push_str("use jni::objects::{JClass, JString, JObject};\n");
+    let needs_objects = !types.is_empty()
+        || all_functions.iter().any(|f| {
+            f.params.iter().any(|(_, t)| is_object_type(t)) || is_object_type(&f.return_type.base_type)
+        });
+    if needs_objects {
+        output.push_str("use jni::objects::{JClass, JString, JObject, JValue};\n");
+    } else {
+        output.push_str("use jni::objects::{JClass, JString, JObject};\n");
+    }
     output.push_str("use jni::sys::*;\n\n");
 
+    // Conversions for lists/maps and between #[ffi] structs/enums and their Kotlin classes
+    if needs_objects {
+        output.push_str(&generate_list_helpers());
+        output.push_str(&generate_map_helpers());
+    }
+    let has_callbacks = types.iter().any(|t| t.is_callback())
+        || all_functions.iter().any(|f| f.params.iter().any(|(_, t)| matches!(t, RustType::Closure(_))));
+    if has_callbacks {
+        output.push_str(&generate_callback_helpers());
+    }
+    if !types.is_empty() {
+        for ffi_type in types {
+            if let RustFfiTypeKind::Callback(methods) = &ffi_type.kind {
+                output.push_str(&generate_callback_adapter(&ffi_type.name, methods, package));
+                output.push('\n');
+                continue;
+            }
+            output.push_str(&generate_to_java(ffi_type, package));
+            output.push('\n');
+            output.push_str(&generate_from_java(ffi_type, package));
+            output.push('\n');
+        }
+    }
+
     // Generate each function
     for function in functions {
         output.push_str(&generate_rust_bridge_function(function, package));
         output.push('\n');
     }
 
+    for class in classes {
+        output.push_str(&generate_rust_class_bridge(class, package));
+    }
+
     output
 }
 
 /// Generate a single JNI bridge function in Rust
 fn generate_rust_bridge_function(function: &RustFfiFunction, package: &str) -> String {
-    let mut output = String::new();
-
     // JNI function name: Java_com_example_package_ClassName_functionName
     // Convert to camelCase to match Kotlin convention
-    let kotlin_name = snake_to_camel(&function.name);
+    let kotlin_name = external_name(function);
     let jni_name = format!(
         "Java_{}_{}",
         package.replace('.', "_"),
@@ -62,8 +125,26 @@ fn generate_rust_bridge_function(function: &RustFfiFunction, package: &str) -> S
         jni_params.push(format!("{}: {}", param_name, param_type.to_jni_type()));
     }
 
-    // Return type
-    let return_type = function.return_type.to_jni_type();
+    // Function body with type conversions
+    let mut body = String::new();
+    generate_function_body(&mut body, function, &format!("crate::{}", function.name), package);
+
+    let export = generate_jni_export(&jni_name, &jni_params, function.return_type.to_jni_type(), &body);
+    allow_deprecated(function, export)
+}
+
+/// Silence the warning for calling a `#[deprecated]` function from its export
+fn allow_deprecated(function: &RustFfiFunction, export: String) -> String {
+    if function.docs.deprecated.is_some() {
+        format!("#[allow(deprecated)]\n{}", export)
+    } else {
+        export
+    }
+}
+
+/// Wrap a function body in a `#[no_mangle]` JNI export
+fn generate_jni_export(jni_name: &str, jni_params: &[String], return_type: &str, body: &str) -> String {
+    let mut output = String::new();
 
     // Function signature
     output.push_str("#[no_mangle]\n");
@@ -80,43 +161,123 @@ fn generate_rust_bridge_function(function: &RustFfiFunction, package: &str) -> S
         }
     }
 
-    output.push_str(&format!(") -> {} {{\n", return_type));
+    if return_type == "void" {
+        output.push_str(") {\n");
+    } else {
+        output.push_str(&format!(") -> {} {{\n", return_type));
+    }
+    output.push_str(body);
+    output.push_str("}\n");
 
-    // Function body with type conversions
-    generate_function_body(&mut output, function);
+    output
+}
 
-    output.push_str("}\n");
+/// Generate the exports behind an #[ffi] impl's Kotlin class
+///
+/// The value lives in a `Box<Mutex<T>>` whose pointer is the Kotlin `Long` handle, so
+/// methods called from several Kotlin threads are serialized. `T` must be `Send`, since
+/// the handle may be used and freed (by the `Cleaner`) from any thread.
+fn generate_rust_class_bridge(class: &RustFfiClass, package: &str) -> String {
+    let mut output = String::new();
+    let jni_prefix = format!("Java_{}_{}", package.replace('.', "_"), class.name);
+    let cell = format!("std::sync::Mutex<crate::{}>", class.name);
+
+    output.push_str(&format!("// Thread-safety check: {} handles cross threads\n", class.name));
+    output.push_str("const _: fn() = || {\n");
+    output.push_str("    fn assert_send<T: Send + 'static>() {}\n");
+    output.push_str(&format!("    assert_send::<crate::{}>();\n", class.name));
+    output.push_str("};\n\n");
+
+    for method in &class.methods {
+        let function = &method.function;
+
+        let mut jni_params = vec!["mut env: JNIEnv".to_string(), "_class: JClass".to_string()];
+        let mut body = String::new();
+        let callee = if method.receiver == MethodReceiver::Static {
+            format!("crate::{}::{}", class.name, function.name)
+        } else {
+            jni_params.push("handle: jlong".to_string());
+            let binding = if method.receiver == MethodReceiver::Mut { "let mut this" } else { "let this" };
+            body.push_str(&format!("    let this = unsafe {{ &*(handle as *const {}) }};\n", cell));
+            body.push_str(&format!("    {} = this.lock().unwrap_or_else(|e| e.into_inner());\n", binding));
+            format!("this.{}", function.name)
+        };
+        for (param_name, param_type) in &function.params {
+            jni_params.push(format!("{}: {}", param_name, param_type.to_jni_type()));
+        }
+        generate_function_body(&mut body, function, &callee, package);
+
+        let jni_name = format!("{}_{}", jni_prefix, native_name(&function.name));
+        let export = generate_jni_export(&jni_name, &jni_params, function.return_type.to_jni_type(), &body);
+        output.push_str(&allow_deprecated(function, export));
+        output.push('\n');
+    }
+
+    let body = format!("    drop(unsafe {{ Box::from_raw(handle as *mut {}) }});\n", cell);
+    output.push_str(&generate_jni_export(
+        &format!("{}_destroyHandle", jni_prefix),
+        &["_env: JNIEnv".to_string(), "_class: JClass".to_string(), "handle: jlong".to_string()],
+        "void",
+        &body,
+    ));
+    output.push('\n');
 
     output
 }
 
+/// Name of the Kotlin `external fun`; async functions hide it behind a suspend wrapper
+pub(crate) fn external_name(function: &RustFfiFunction) -> String {
+    if function.is_async {
+        native_name(&function.name)
+    } else {
+        snake_to_camel(&function.name)
+    }
+}
+
+/// Name of a private `external fun` behind a Kotlin wrapper (`query` -> `nativeQuery`)
+pub(crate) fn native_name(name: &str) -> String {
+    let kotlin_name = snake_to_camel(name);
+    let mut chars = kotlin_name.chars();
+    match chars.next() {
+        Some(first) => format!("native{}{}", first.to_ascii_uppercase(), chars.as_str()),
+        None => kotlin_name,
+    }
+}
+
 /// Generate the function body with proper type conversions
-fn generate_function_body(output: &mut String, function: &RustFfiFunction) {
+///
+/// `callee` is the path of the user's function, e.g. `crate::add` or `this.query`.
+fn generate_function_body(output: &mut String, function: &RustFfiFunction, callee: &str, package: &str) {
     let _has_complex_types = function.params.iter().any(|(_, typ)| is_complex_type(typ))
         || is_complex_type(&function.return_type.base_type);
 
     // Convert parameters
     for (param_name, param_type) in &function.params {
         if is_complex_type(param_type) {
-            generate_param_conversion(output, param_name, param_type);
+            generate_param_conversion(output, param_name, param_type, package);
         }
     }
 
     // Build function call
     let mut call_params = Vec::new();
     for (param_name, param_type) in &function.params {
-        if is_complex_type(param_type) {
+        let passing = match param_type {
+            RustType::Closure(closure) => Some(closure.passing),
+            RustType::Callback(_, passing) => Some(*passing),
+            _ => None,
+        };
+        if *param_type == RustType::ByteSlice || passing == Some(CallbackPassing::Ref) {
+            call_params.push(format!("&rust_{}", param_name));
+        } else if passing == Some(CallbackPassing::Boxed) {
+            call_params.push(format!("Box::new(rust_{})", param_name));
+        } else if is_complex_type(param_type) {
             call_params.push(format!("rust_{}", param_name));
         } else {
             call_params.push(param_name.clone());
         }
     }
 
-    let call_expr = if call_params.is_empty() {
-        format!("crate::{}()", function.name)
-    } else {
-        format!("crate::{}({})", function.name, call_params.join(", "))
-    };
+    let call_expr = format!("{}({})", callee, call_params.join(", "));
 
     // Phase 5: Handle Result<T, E> types
     if function.return_type.is_result {
@@ -145,9 +306,7 @@ fn generate_function_body(output: &mut String, function: &RustFfiFunction) {
         // Handle error case - throw Java exception
         output.push_str("        Err(e) => {\n");
         output.push_str("            let err_msg = format!(\"{}\", e);\n");
-        output.push_str("            let err_str = env.new_string(err_msg)\n");
-        output.push_str("                .expect(\"Couldn't create error string!\");\n");
-        output.push_str("            env.throw_new(\"java/lang/RuntimeException\", err_str.to_str().unwrap_or(\"\"))\n");
+        output.push_str("            env.throw_new(\"java/lang/RuntimeException\", &err_msg)\n");
         output.push_str("                .expect(\"Couldn't throw exception!\");\n");
         output.push_str(&generate_default_return_for_result(&function.return_type.base_type));
         output.push_str("        }\n");
@@ -179,31 +338,38 @@ fn generate_function_body(output: &mut String, function: &RustFfiFunction) {
 fn generate_default_return_for_result(base_type: &RustType) -> String {
     match base_type {
         RustType::Void => String::new(),
-        RustType::Int | RustType::Long => "            0\n".to_string(),
+        RustType::Int | RustType::Long | RustType::Handle(_) => "            0\n".to_string(),
         RustType::Float | RustType::Double => "            0.0\n".to_string(),
         RustType::Bool => "            false\n".to_string(),
         RustType::String | RustType::IntArray | RustType::LongArray |
         RustType::FloatArray | RustType::DoubleArray | RustType::BoolArray |
-        RustType::StringArray => "            JObject::null().as_raw()\n".to_string(),
+        RustType::StringArray | RustType::Custom(_) | RustType::CustomList(_)
+        | RustType::Option(_) | RustType::Map(_) | RustType::Bytes
+        | RustType::ByteSlice | RustType::ByteBuffer | RustType::CancelFlag
+        | RustType::Closure(_) | RustType::Callback(..) => {
+            "            JObject::null().as_raw()\n".to_string()
+        }
     }
 }
 
 /// Check if a type needs conversion (not a simple primitive)
 fn is_complex_type(typ: &RustType) -> bool {
+    !matches!(
+        typ,
+        RustType::Void | RustType::Int | RustType::Long | RustType::Float | RustType::Double | RustType::Bool
+    )
+}
+
+/// Check if a type is converted through the generic Java object helpers
+fn is_object_type(typ: &RustType) -> bool {
     matches!(
         typ,
-        RustType::String
-            | RustType::IntArray
-            | RustType::LongArray
-            | RustType::FloatArray
-            | RustType::DoubleArray
-            | RustType::BoolArray
-            | RustType::StringArray
+        RustType::Custom(_) | RustType::CustomList(_) | RustType::Option(_) | RustType::Map(_)
     )
 }
 
 /// Generate parameter conversion code
-fn generate_param_conversion(output: &mut String, param_name: &str, param_type: &RustType) {
+fn generate_param_conversion(output: &mut String, param_name: &str, param_type: &RustType, package: &str) {
     match param_type {
         RustType::String => {
             output.push_str(&format!(
@@ -224,6 +390,119 @@ fn generate_param_conversion(output: &mut String, param_name: &str, param_type:
         RustType::StringArray => {
             generate_string_array_conversion(output, param_name);
         }
+        RustType::Custom(name) => {
+            output.push_str(&format!(
+                "    let rust_{}: crate::{} = {}(&mut env, &unsafe {{ JObject::from_raw({}) }});\n",
+                param_name, name, from_java_fn(name), param_name
+            ));
+        }
+        RustType::CustomList(name) => {
+            output.push_str(&format!(
+                "    let rust_{}: Vec<crate::{}> = whitehall_list_from_java(&mut env, &unsafe {{ JObject::from_raw({}) }}, {});\n",
+                param_name, name, param_name, from_java_fn(name)
+            ));
+        }
+        RustType::Option(_) | RustType::Map(_) => {
+            output.push_str(&format!(
+                "    let obj_{} = unsafe {{ JObject::from_raw({}) }};\n",
+                param_name, param_name
+            ));
+            output.push_str(&format!(
+                "    let rust_{} = {};\n",
+                param_name,
+                from_java_object(param_type, &format!("&obj_{}", param_name), "&mut env")
+            ));
+        }
+        RustType::Bytes | RustType::ByteSlice => {
+            output.push_str(&format!(
+                "    let rust_{}: Vec<u8> = env.convert_byte_array(unsafe {{ jni::objects::JByteArray::from_raw({}) }})\n",
+                param_name, param_name
+            ));
+            output.push_str("        .expect(\"Couldn't get byte array!\");\n");
+        }
+        RustType::Closure(closure) => {
+            // The closure owns a global ref to the Kotlin fun interface and attaches
+            // whichever thread it is called from
+            output.push_str(&format!(
+                "    let vm_{} = env.get_java_vm().expect(\"Couldn't get JavaVM!\");\n",
+                param_name
+            ));
+            output.push_str(&format!(
+                "    let callback_{} = env.new_global_ref(unsafe {{ JObject::from_raw({}) }})\n",
+                param_name, param_name
+            ));
+            output.push_str("        .expect(\"Couldn't create global ref!\");\n");
+
+            let names = closure.param_names();
+            let args = names
+                .iter()
+                .zip(&closure.params)
+                .map(|(name, typ)| format!("{}: {}", name, qualified_rust_type(typ)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let return_type = match closure.return_type {
+                RustType::Void => String::new(),
+                ref ret => format!(" -> {}", qualified_rust_type(ret)),
+            };
+            output.push_str(&format!("    let rust_{} = move |{}|{} {{\n", param_name, args, return_type));
+            let params = names.into_iter().zip(closure.params.iter().cloned()).collect::<Vec<_>>();
+            generate_callback_call(
+                output,
+                &format!("vm_{}", param_name),
+                &format!("callback_{}.as_obj()", param_name),
+                "invoke",
+                &params,
+                &closure.return_type,
+                package,
+            );
+            output.push_str("    };\n");
+        }
+        RustType::Callback(name, _) => {
+            output.push_str(&format!("    let rust_{} = {} {{\n", param_name, adapter_name(name)));
+            output.push_str("        vm: env.get_java_vm().expect(\"Couldn't get JavaVM!\"),\n");
+            output.push_str(&format!(
+                "        callback: env.new_global_ref(unsafe {{ JObject::from_raw({}) }}).expect(\"Couldn't create global ref!\"),\n",
+                param_name
+            ));
+            output.push_str("    };\n");
+        }
+        RustType::CancelFlag => {
+            // One-byte direct buffer written by the Kotlin wrapper on cancellation
+            output.push_str(&format!(
+                "    let buffer_{} = unsafe {{ jni::objects::JByteBuffer::from_raw({}) }};\n",
+                param_name, param_name
+            ));
+            output.push_str(&format!(
+                "    let ptr_{} = env.get_direct_buffer_address(&buffer_{})\n",
+                param_name, param_name
+            ));
+            output.push_str("        .expect(\"Couldn't get cancellation flag!\");\n");
+            output.push_str(&format!(
+                "    let rust_{}: &std::sync::atomic::AtomicBool = unsafe {{ &*(ptr_{} as *const std::sync::atomic::AtomicBool) }};\n",
+                param_name, param_name
+            ));
+        }
+        RustType::ByteBuffer => {
+            // Zero-copy: borrow the direct buffer's memory for the duration of the call
+            output.push_str(&format!(
+                "    let buffer_{} = unsafe {{ jni::objects::JByteBuffer::from_raw({}) }};\n",
+                param_name, param_name
+            ));
+            output.push_str(&format!(
+                "    let ptr_{} = env.get_direct_buffer_address(&buffer_{})\n",
+                param_name, param_name
+            ));
+            output.push_str("        .expect(\"ByteBuffer must be direct (ByteBuffer.allocateDirect)!\");\n");
+            output.push_str(&format!(
+                "    let len_{} = env.get_direct_buffer_capacity(&buffer_{})\n",
+                param_name, param_name
+            ));
+            output.push_str("        .expect(\"Couldn't get ByteBuffer capacity!\");\n");
+            output.push_str(&format!(
+                "    let rust_{}: &mut [u8] = unsafe {{ std::slice::from_raw_parts_mut(ptr_{}, len_{}) }};\n",
+                param_name, param_name, param_name
+            ));
+        }
         _ => {}
     }
 }
@@ -234,33 +513,50 @@ fn generate_primitive_array_conversion(
     param_name: &str,
     param_type: &RustType,
 ) {
-    let (get_fn, rust_elem_type) = match param_type {
-        RustType::IntArray => ("get_int_array_elements", "i32"),
-        RustType::LongArray => ("get_long_array_elements", "i64"),
-        RustType::FloatArray => ("get_float_array_elements", "f32"),
-        RustType::DoubleArray => ("get_double_array_elements", "f64"),
-        RustType::BoolArray => ("get_boolean_array_elements", "bool"),
+    let (array_type, get_fn, jni_elem_type) = match param_type {
+        RustType::IntArray => ("JIntArray", "get_int_array_region", "jint"),
+        RustType::LongArray => ("JLongArray", "get_long_array_region", "jlong"),
+        RustType::FloatArray => ("JFloatArray", "get_float_array_region", "jfloat"),
+        RustType::DoubleArray => ("JDoubleArray", "get_double_array_region", "jdouble"),
+        RustType::BoolArray => ("JBooleanArray", "get_boolean_array_region", "jboolean"),
         _ => unreachable!(),
     };
 
     output.push_str(&format!(
-        "    let rust_{}: Vec<{}> = env.{}({})\n",
-        param_name, rust_elem_type, get_fn, param_name
+        "    let array_{} = unsafe {{ jni::objects::{}::from_raw({}) }};\n",
+        param_name, array_type, param_name
+    ));
+    output.push_str(&format!("    let len_{} = env.get_array_length(&array_{})\n", param_name, param_name));
+    output.push_str("        .expect(\"Couldn't get array length!\") as usize;\n");
+    output.push_str(&format!(
+        "    let mut rust_{}: Vec<{}> = vec![Default::default(); len_{}];\n",
+        param_name, jni_elem_type, param_name
     ));
-    output.push_str("        .expect(\"Couldn't get array elements!\")\n");
-    output.push_str("        .to_vec();\n");
+    output.push_str(&format!("    env.{}(&array_{}, 0, &mut rust_{})\n", get_fn, param_name, param_name));
+    output.push_str("        .expect(\"Couldn't get array elements!\");\n");
+    if *param_type == RustType::BoolArray {
+        // jboolean is a u8
+        output.push_str(&format!(
+            "    let rust_{}: Vec<bool> = rust_{}.into_iter().map(|b| b != 0).collect();\n",
+            param_name, param_name
+        ));
+    }
 }
 
 /// Generate string array parameter conversion
 fn generate_string_array_conversion(output: &mut String, param_name: &str) {
-    output.push_str(&format!("    let len = env.get_array_length({})\n", param_name));
+    output.push_str(&format!(
+        "    let array_{} = unsafe {{ jni::objects::JObjectArray::from_raw({}) }};\n",
+        param_name, param_name
+    ));
+    output.push_str(&format!("    let len = env.get_array_length(&array_{})\n", param_name));
     output.push_str("        .expect(\"Couldn't get array length!\") as usize;\n");
     output.push_str(&format!("    let mut rust_{}: Vec<String> = Vec::with_capacity(len);\n", param_name));
     output.push_str("    for i in 0..len {\n");
-    output.push_str(&format!("        let elem = env.get_object_array_element({}, i as i32)\n", param_name));
+    output.push_str(&format!("        let elem = env.get_object_array_element(&array_{}, i as i32)\n", param_name));
     output.push_str("            .expect(\"Couldn't get array element!\");\n");
     output.push_str("        let jstr = JString::from(elem);\n");
-    output.push_str("        let rust_str: String = env.get_string(jstr)\n");
+    output.push_str("        let rust_str: String = env.get_string(&jstr)\n");
     output.push_str("            .expect(\"Couldn't get string!\")\n");
     output.push_str("            .into();\n");
     output.push_str(&format!("        rust_{}.push(rust_str);\n", param_name));
@@ -282,6 +578,30 @@ fn generate_return_conversion(output: &mut String, return_type: &RustType) {
         RustType::StringArray => {
             generate_string_array_return(output);
         }
+        RustType::Custom(name) => {
+            output.push_str(&format!("    {}(&mut env, &result).into_raw()\n", to_java_fn(name)));
+        }
+        RustType::CustomList(name) => {
+            output.push_str(&format!(
+                "    whitehall_list_to_java(&mut env, &result, {}).into_raw()\n",
+                to_java_fn(name)
+            ));
+        }
+        RustType::Option(_) | RustType::Map(_) => {
+            output.push_str(&format!(
+                "    let java_result = {};\n",
+                to_java_object(return_type, "&result", "&mut env")
+            ));
+            output.push_str("    java_result.into_raw()\n");
+        }
+        RustType::Bytes => {
+            output.push_str("    env.byte_array_from_slice(&result)\n");
+            output.push_str("        .expect(\"Couldn't create byte array!\")\n");
+            output.push_str("        .into_raw()\n");
+        }
+        RustType::Handle(_) => {
+            output.push_str("    Box::into_raw(Box::new(std::sync::Mutex::new(result))) as jlong\n");
+        }
         _ => {}
     }
 }
@@ -311,12 +631,16 @@ fn generate_primitive_array_return(output: &mut String, return_type: &RustType)
         new_array_fn
     ));
     output.push_str("        .expect(\"Couldn't create array!\");\n");
+    if *return_type == RustType::BoolArray {
+        // jboolean is a u8
+        output.push_str("    let result: Vec<jboolean> = result.iter().map(|&b| b as jboolean).collect();\n");
+    }
     output.push_str(&format!(
-        "    env.{}(output, 0, &result)\n",
+        "    env.{}(&output, 0, &result)\n",
         set_region_fn
     ));
     output.push_str("        .expect(\"Couldn't set array region!\");\n");
-    output.push_str("    output\n");
+    output.push_str("    output.into_raw()\n");
 }
 
 /// Generate string array return conversion
@@ -331,10 +655,596 @@ fn generate_string_array_return(output: &mut String) {
     output.push_str("    for (i, s) in result.iter().enumerate() {\n");
     output.push_str("        let jstr = env.new_string(s)\n");
     output.push_str("            .expect(\"Couldn't create string!\");\n");
-    output.push_str("        env.set_object_array_element(output, i as i32, jstr)\n");
+    output.push_str("        env.set_object_array_element(&output, i as i32, jstr)\n");
     output.push_str("            .expect(\"Couldn't set array element!\");\n");
     output.push_str("    }\n");
-    output.push_str("    output\n");
+    output.push_str("    output.into_raw()\n");
+}
+
+/// Name of the generated Rust -> Kotlin conversion for an #[ffi] type
+fn to_java_fn(type_name: &str) -> String {
+    format!("whitehall_{}_to_java", pascal_to_snake(type_name))
+}
+
+/// Name of the generated Kotlin -> Rust conversion for an #[ffi] type
+fn from_java_fn(type_name: &str) -> String {
+    format!("whitehall_{}_from_java", pascal_to_snake(type_name))
+}
+
+/// JVM class name of a generated Kotlin class, e.g. `com/example/ffi/rust/Shape$Circle`
+fn jvm_class(package: &str, class_name: &str) -> String {
+    format!("{}/{}", package.replace('.', "/"), class_name)
+}
+
+/// JNI type signature of a struct field
+fn jni_signature(typ: &RustType, package: &str) -> String {
+    match typ {
+        RustType::Int => "I".to_string(),
+        RustType::Long => "J".to_string(),
+        RustType::Float => "F".to_string(),
+        RustType::Double => "D".to_string(),
+        RustType::Bool => "Z".to_string(),
+        RustType::String => "Ljava/lang/String;".to_string(),
+        RustType::Custom(name) => format!("L{};", jvm_class(package, name)),
+        RustType::CustomList(_) => "Ljava/util/List;".to_string(),
+        RustType::Option(inner) => match &**inner {
+            RustType::Int => "Ljava/lang/Integer;".to_string(),
+            RustType::Long => "Ljava/lang/Long;".to_string(),
+            RustType::Float => "Ljava/lang/Float;".to_string(),
+            RustType::Double => "Ljava/lang/Double;".to_string(),
+            RustType::Bool => "Ljava/lang/Boolean;".to_string(),
+            other => jni_signature(other, package),
+        },
+        RustType::Map(_) => "Ljava/util/Map;".to_string(),
+        RustType::Bytes => "[B".to_string(),
+        _ => unreachable!("unsupported struct field type"),
+    }
+}
+
+/// Expression converting `value` (a `&T`) into a Java object, boxing primitives
+///
+/// `env` is the expression passed to helper functions (`env` or `&mut env`).
+fn to_java_object(typ: &RustType, value: &str, env: &str) -> String {
+    let boxed = |class: &str, sig: &str, jvalue: &str| {
+        format!(
+            "env.new_object(\"java/lang/{}\", \"({})V\", &[JValue::{}(*{})]).expect(\"Couldn't box {}!\")",
+            class, sig, jvalue, value, class
+        )
+    };
+
+    match typ {
+        RustType::Int => boxed("Integer", "I", "Int"),
+        RustType::Long => boxed("Long", "J", "Long"),
+        RustType::Float => boxed("Float", "F", "Float"),
+        RustType::Double => boxed("Double", "D", "Double"),
+        RustType::Bool => format!(
+            "env.new_object(\"java/lang/Boolean\", \"(Z)V\", &[JValue::Bool(*{} as jboolean)]).expect(\"Couldn't box Boolean!\")",
+            value
+        ),
+        RustType::String => format!(
+            "JObject::from(env.new_string({}).expect(\"Couldn't create Java string!\"))",
+            value
+        ),
+        RustType::Custom(name) => format!("{}({}, {})", to_java_fn(name), env, value),
+        RustType::CustomList(name) => format!("whitehall_list_to_java({}, {}, {})", env, value, to_java_fn(name)),
+        RustType::Bytes => format!(
+            "JObject::from(env.byte_array_from_slice({}).expect(\"Couldn't create byte array!\"))",
+            value
+        ),
+        RustType::Option(inner) => format!(
+            "match {} {{ Some(v) => {}, None => JObject::null() }}",
+            value,
+            to_java_object(inner, "v", env)
+        ),
+        RustType::Map(inner) => format!(
+            "whitehall_map_to_java({}, {}, |env, v| {})",
+            env,
+            value,
+            to_java_object(inner, "v", "env")
+        ),
+        _ => unreachable!("type cannot be converted to a Java object"),
+    }
+}
+
+/// Expression converting `obj` (a `&JObject`) back into a Rust value, unboxing primitives
+fn from_java_object(typ: &RustType, obj: &str, env: &str) -> String {
+    let unboxed = |method: &str, sig: &str, getter: &str| {
+        format!(
+            "env.call_method({}, \"{}\", \"(){}\", &[]).and_then(|v| v.{}()).expect(\"Couldn't unbox value!\")",
+            obj, method, sig, getter
+        )
+    };
+
+    match typ {
+        RustType::Int => unboxed("intValue", "I", "i"),
+        RustType::Long => unboxed("longValue", "J", "j"),
+        RustType::Float => unboxed("floatValue", "F", "f"),
+        RustType::Double => unboxed("doubleValue", "D", "d"),
+        RustType::Bool => unboxed("booleanValue", "Z", "z"),
+        RustType::String => format!(
+            "String::from(env.get_string(<&JString>::from({})).expect(\"Couldn't get Java string!\"))",
+            obj
+        ),
+        RustType::Custom(name) => format!("{}({}, {})", from_java_fn(name), env, obj),
+        RustType::CustomList(name) => format!("whitehall_list_from_java({}, {}, {})", env, obj, from_java_fn(name)),
+        RustType::Bytes => format!(
+            "env.convert_byte_array(<&jni::objects::JByteArray>::from({})).expect(\"Couldn't get byte array!\")",
+            obj
+        ),
+        RustType::Option(inner) => format!(
+            "if {}.is_null() {{ None }} else {{ Some({}) }}",
+            obj.trim_start_matches('&'),
+            from_java_object(inner, obj, env)
+        ),
+        RustType::Map(inner) => format!(
+            "whitehall_map_from_java({}, {}, |env, obj| {})",
+            env,
+            obj,
+            from_java_object(inner, "obj", "env")
+        ),
+        _ => unreachable!("type cannot be converted from a Java object"),
+    }
+}
+
+/// Rust type of a callback argument as written in the bridge (user types via `crate::`)
+fn qualified_rust_type(typ: &RustType) -> String {
+    match typ {
+        RustType::Custom(name) => format!("crate::{}", name),
+        RustType::CustomList(name) => format!("Vec<crate::{}>", name),
+        RustType::Option(inner) => format!("Option<{}>", qualified_rust_type(inner)),
+        RustType::Map(inner) => format!("std::collections::HashMap<String, {}>", qualified_rust_type(inner)),
+        _ => typ.to_rust_type(),
+    }
+}
+
+/// Name of the bridge struct implementing an #[ffi_callback] trait
+fn adapter_name(trait_name: &str) -> String {
+    format!("Whitehall{}", trait_name)
+}
+
+/// Helper shared by all callbacks
+fn generate_callback_helpers() -> String {
+    let mut output = String::new();
+
+    // Kotlin exceptions can't unwind through native code: report them and fall back
+    output.push_str("#[allow(dead_code)]\n");
+    output.push_str("fn whitehall_clear_exception(env: &mut JNIEnv) {\n");
+    output.push_str("    let _ = env.exception_describe();\n");
+    output.push_str("    let _ = env.exception_clear();\n");
+    output.push_str("}\n\n");
+
+    output
+}
+
+/// Generate a struct implementing an #[ffi_callback] trait by calling the Kotlin object
+fn generate_callback_adapter(name: &str, methods: &[RustCallbackMethod], package: &str) -> String {
+    let mut output = String::new();
+    let adapter = adapter_name(name);
+
+    output.push_str(&format!("struct {} {{\n", adapter));
+    output.push_str("    vm: jni::JavaVM,\n");
+    output.push_str("    callback: jni::objects::GlobalRef,\n");
+    output.push_str("}\n\n");
+
+    output.push_str(&format!("impl crate::{} for {} {{\n", name, adapter));
+    for (i, method) in methods.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        let params = method
+            .params
+            .iter()
+            .map(|(param, typ)| format!(", {}: {}", param, qualified_rust_type(typ)))
+            .collect::<String>();
+        let return_type = match method.return_type {
+            RustType::Void => String::new(),
+            ref ret => format!(" -> {}", qualified_rust_type(ret)),
+        };
+        output.push_str(&format!("    fn {}(&self{}){} {{\n", method.name, params, return_type));
+        generate_callback_call(
+            &mut output,
+            "self.vm",
+            "self.callback.as_obj()",
+            &snake_to_camel(&method.name),
+            &method.params,
+            &method.return_type,
+            package,
+        );
+        output.push_str("    }\n");
+    }
+    output.push_str("}\n");
+
+    output
+}
+
+/// Generate the body of a callback: attach the current thread, convert the
+/// arguments and call `method` on the Kotlin object behind `target`
+fn generate_callback_call(
+    output: &mut String,
+    vm: &str,
+    target: &str,
+    method: &str,
+    params: &[(String, RustType)],
+    return_type: &RustType,
+    package: &str,
+) {
+    // Callbacks are closure bodies or trait methods, both nested one level deep
+    let indent = "        ";
+
+    output.push_str(&format!(
+        "{}let mut env = {}.attach_current_thread().expect(\"Couldn't attach thread!\");\n",
+        indent, vm
+    ));
+
+    let mut signature = String::from("(");
+    let mut args = Vec::new();
+    let mut locals = Vec::new();
+    for (name, typ) in params {
+        signature.push_str(&jni_signature(typ, package));
+        match typ {
+            RustType::Int => args.push(format!("JValue::Int({})", name)),
+            RustType::Long => args.push(format!("JValue::Long({})", name)),
+            RustType::Float => args.push(format!("JValue::Float({})", name)),
+            RustType::Double => args.push(format!("JValue::Double({})", name)),
+            RustType::Bool => args.push(format!("JValue::Bool({} as jboolean)", name)),
+            _ => {
+                output.push_str(&format!(
+                    "{}let j_{} = {};\n",
+                    indent, name, to_java_object(typ, &format!("&{}", name), "&mut env")
+                ));
+                args.push(format!("JValue::Object(&j_{})", name));
+                locals.push(format!("j_{}", name));
+            }
+        }
+    }
+    signature.push(')');
+    signature.push_str(&match return_type {
+        RustType::Void => "V".to_string(),
+        other => jni_signature(other, package),
+    });
+
+    output.push_str(&format!(
+        "{}let result = env.call_method({}, \"{}\", \"{}\", &[{}]);\n",
+        indent, target, method, signature, args.join(", ")
+    ));
+    // The thread may stay attached (e.g. the JNI caller's thread), so free arguments eagerly
+    for local in &locals {
+        output.push_str(&format!("{}let _ = env.delete_local_ref({});\n", indent, local));
+    }
+
+    let getter = match return_type {
+        RustType::Void => {
+            output.push_str(&format!("{}if result.is_err() {{\n", indent));
+            output.push_str(&format!("{}    whitehall_clear_exception(&mut env);\n", indent));
+            output.push_str(&format!("{}}}\n", indent));
+            return;
+        }
+        RustType::Int => "i",
+        RustType::Long => "j",
+        RustType::Float => "f",
+        RustType::Double => "d",
+        RustType::Bool => "z",
+        _ => "l",
+    };
+
+    output.push_str(&format!("{}match result.and_then(|v| v.{}()) {{\n", indent, getter));
+    if *return_type == RustType::String {
+        output.push_str(&format!(
+            "{}    Ok(obj) if !obj.is_null() => {},\n",
+            indent, from_java_object(return_type, "&obj", "&mut env")
+        ));
+    } else {
+        output.push_str(&format!("{}    Ok(value) => value,\n", indent));
+    }
+    output.push_str(&format!("{}    _ => {{\n", indent));
+    output.push_str(&format!("{}        whitehall_clear_exception(&mut env);\n", indent));
+    output.push_str(&format!("{}        Default::default()\n", indent));
+    output.push_str(&format!("{}    }}\n", indent));
+    output.push_str(&format!("{}}}\n", indent));
+}
+
+/// Helpers converting between HashMap<String, T> and java.util.Map
+fn generate_map_helpers() -> String {
+    let mut output = String::new();
+
+    output.push_str("#[allow(dead_code)]\n");
+    output.push_str("fn whitehall_map_to_java<'local, T>(\n");
+    output.push_str("    env: &mut JNIEnv<'local>,\n");
+    output.push_str("    map: &std::collections::HashMap<String, T>,\n");
+    output.push_str("    convert: fn(&mut JNIEnv<'local>, &T) -> JObject<'local>,\n");
+    output.push_str(") -> JObject<'local> {\n");
+    output.push_str("    let result = env.new_object(\"java/util/HashMap\", \"()V\", &[])\n");
+    output.push_str("        .expect(\"Couldn't create HashMap!\");\n");
+    output.push_str("    for (key, value) in map {\n");
+    output.push_str("        let key = JObject::from(env.new_string(key).expect(\"Couldn't create Java string!\"));\n");
+    output.push_str("        let value = convert(env, value);\n");
+    output.push_str("        env.call_method(\n");
+    output.push_str("            &result,\n");
+    output.push_str("            \"put\",\n");
+    output.push_str("            \"(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;\",\n");
+    output.push_str("            &[JValue::Object(&key), JValue::Object(&value)],\n");
+    output.push_str("        ).expect(\"Couldn't put map entry!\");\n");
+    output.push_str("        env.delete_local_ref(key)\n");
+    output.push_str("            .expect(\"Couldn't delete local ref!\");\n");
+    output.push_str("        env.delete_local_ref(value)\n");
+    output.push_str("            .expect(\"Couldn't delete local ref!\");\n");
+    output.push_str("    }\n");
+    output.push_str("    result\n");
+    output.push_str("}\n\n");
+
+    output.push_str("#[allow(dead_code)]\n");
+    output.push_str("fn whitehall_map_from_java<T>(\n");
+    output.push_str("    env: &mut JNIEnv,\n");
+    output.push_str("    map: &JObject,\n");
+    output.push_str("    convert: fn(&mut JNIEnv, &JObject) -> T,\n");
+    output.push_str(") -> std::collections::HashMap<String, T> {\n");
+    output.push_str("    let keys = env.call_method(map, \"keySet\", \"()Ljava/util/Set;\", &[])\n");
+    output.push_str("        .and_then(|v| v.l())\n");
+    output.push_str("        .expect(\"Couldn't get map keys!\");\n");
+    output.push_str("    let keys = env.call_method(&keys, \"toArray\", \"()[Ljava/lang/Object;\", &[])\n");
+    output.push_str("        .and_then(|v| v.l())\n");
+    output.push_str("        .expect(\"Couldn't get map keys!\");\n");
+    output.push_str("    let keys = jni::objects::JObjectArray::from(keys);\n");
+    output.push_str("    let len = env.get_array_length(&keys)\n");
+    output.push_str("        .expect(\"Couldn't get array length!\");\n");
+    output.push_str("    let mut result = std::collections::HashMap::with_capacity(len as usize);\n");
+    output.push_str("    for i in 0..len {\n");
+    output.push_str("        let key = env.get_object_array_element(&keys, i)\n");
+    output.push_str("            .expect(\"Couldn't get array element!\");\n");
+    output.push_str("        let value = env.call_method(map, \"get\", \"(Ljava/lang/Object;)Ljava/lang/Object;\", &[JValue::Object(&key)])\n");
+    output.push_str("            .and_then(|v| v.l())\n");
+    output.push_str("            .expect(\"Couldn't get map value!\");\n");
+    output.push_str("        let name: String = env.get_string(<&JString>::from(&key))\n");
+    output.push_str("            .expect(\"Couldn't get Java string!\")\n");
+    output.push_str("            .into();\n");
+    output.push_str("        result.insert(name, convert(env, &value));\n");
+    output.push_str("        env.delete_local_ref(key)\n");
+    output.push_str("            .expect(\"Couldn't delete local ref!\");\n");
+    output.push_str("        env.delete_local_ref(value)\n");
+    output.push_str("            .expect(\"Couldn't delete local ref!\");\n");
+    output.push_str("    }\n");
+    output.push_str("    result\n");
+    output.push_str("}\n\n");
+
+    output
+}
+
+/// Helpers converting between Vec<T> and java.util.List for #[ffi] types
+fn generate_list_helpers() -> String {
+    let mut output = String::new();
+
+    output.push_str("#[allow(dead_code)]\n");
+    output.push_str("fn whitehall_list_to_java<'local, T>(\n");
+    output.push_str("    env: &mut JNIEnv<'local>,\n");
+    output.push_str("    items: &[T],\n");
+    output.push_str("    convert: fn(&mut JNIEnv<'local>, &T) -> JObject<'local>,\n");
+    output.push_str(") -> JObject<'local> {\n");
+    output.push_str("    let list = env.new_object(\"java/util/ArrayList\", \"(I)V\", &[JValue::Int(items.len() as i32)])\n");
+    output.push_str("        .expect(\"Couldn't create ArrayList!\");\n");
+    output.push_str("    for item in items {\n");
+    output.push_str("        let element = convert(env, item);\n");
+    output.push_str("        env.call_method(&list, \"add\", \"(Ljava/lang/Object;)Z\", &[JValue::Object(&element)])\n");
+    output.push_str("            .expect(\"Couldn't add list element!\");\n");
+    output.push_str("        env.delete_local_ref(element)\n");
+    output.push_str("            .expect(\"Couldn't delete local ref!\");\n");
+    output.push_str("    }\n");
+    output.push_str("    list\n");
+    output.push_str("}\n\n");
+
+    output.push_str("#[allow(dead_code)]\n");
+    output.push_str("fn whitehall_list_from_java<T>(\n");
+    output.push_str("    env: &mut JNIEnv,\n");
+    output.push_str("    list: &JObject,\n");
+    output.push_str("    convert: fn(&mut JNIEnv, &JObject) -> T,\n");
+    output.push_str(") -> Vec<T> {\n");
+    output.push_str("    let size = env.call_method(list, \"size\", \"()I\", &[])\n");
+    output.push_str("        .and_then(|v| v.i())\n");
+    output.push_str("        .expect(\"Couldn't get list size!\");\n");
+    output.push_str("    let mut items = Vec::with_capacity(size as usize);\n");
+    output.push_str("    for i in 0..size {\n");
+    output.push_str("        let element = env.call_method(list, \"get\", \"(I)Ljava/lang/Object;\", &[JValue::Int(i)])\n");
+    output.push_str("            .and_then(|v| v.l())\n");
+    output.push_str("            .expect(\"Couldn't get list element!\");\n");
+    output.push_str("        items.push(convert(env, &element));\n");
+    output.push_str("        env.delete_local_ref(element)\n");
+    output.push_str("            .expect(\"Couldn't delete local ref!\");\n");
+    output.push_str("    }\n");
+    output.push_str("    items\n");
+    output.push_str("}\n\n");
+
+    output
+}
+
+/// Generate the constructor call for a Kotlin class from Rust fields
+///
+/// `fields` holds (Rust field name, type). Struct fields are read as `value.field`,
+/// enum variant fields through match bindings of the same name (`bound`). Object
+/// fields are converted into locals first, then passed as constructor arguments.
+fn generate_new_object(
+    output: &mut String,
+    fields: &[(String, RustType)],
+    bound: bool,
+    class: &str,
+    package: &str,
+    indent: &str,
+) {
+    let mut signature = String::from("(");
+    let mut args = Vec::new();
+
+    for (name, typ) in fields {
+        signature.push_str(&jni_signature(typ, package));
+        let (copy, value) = if bound {
+            (format!("*{}", name), name.clone())
+        } else {
+            (format!("value.{}", name), format!("&value.{}", name))
+        };
+        match typ {
+            RustType::Int => args.push(format!("JValue::Int({})", copy)),
+            RustType::Long => args.push(format!("JValue::Long({})", copy)),
+            RustType::Float => args.push(format!("JValue::Float({})", copy)),
+            RustType::Double => args.push(format!("JValue::Double({})", copy)),
+            RustType::Bool => args.push(format!("JValue::Bool({} as jboolean)", copy)),
+            _ => {
+                output.push_str(&format!("{}let f_{} = {};\n", indent, name, to_java_object(typ, &value, "env")));
+                args.push(format!("JValue::Object(&f_{})", name));
+            }
+        }
+    }
+    signature.push_str(")V");
+
+    output.push_str(&format!(
+        "{}env.new_object(\"{}\", \"{}\", &[{}])\n",
+        indent, jvm_class(package, class), signature, args.join(", ")
+    ));
+    output.push_str(&format!("{}    .expect(\"Couldn't create {}!\")\n", indent, class.replace('$', ".")));
+}
+
+/// Expression reading one field of a Kotlin object back into Rust
+fn field_from_java(field: &str, typ: &RustType, package: &str) -> String {
+    let kotlin_field = snake_to_camel(field);
+    let get = format!(
+        "env.get_field(obj, \"{}\", \"{}\")",
+        kotlin_field,
+        jni_signature(typ, package)
+    );
+
+    match typ {
+        RustType::Int => format!("{}.and_then(|v| v.i()).expect(\"Couldn't read {}!\")", get, kotlin_field),
+        RustType::Long => format!("{}.and_then(|v| v.j()).expect(\"Couldn't read {}!\")", get, kotlin_field),
+        RustType::Float => format!("{}.and_then(|v| v.f()).expect(\"Couldn't read {}!\")", get, kotlin_field),
+        RustType::Double => format!("{}.and_then(|v| v.d()).expect(\"Couldn't read {}!\")", get, kotlin_field),
+        RustType::Bool => format!("{}.and_then(|v| v.z()).expect(\"Couldn't read {}!\")", get, kotlin_field),
+        _ => format!(
+            "{{ let f = {}.and_then(|v| v.l()).expect(\"Couldn't read {}!\"); {} }}",
+            get, kotlin_field, from_java_object(typ, "&f", "env")
+        ),
+    }
+}
+
+/// Generate the Rust -> Kotlin conversion for an #[ffi] struct or enum
+fn generate_to_java(ffi_type: &RustFfiType, package: &str) -> String {
+    let mut output = String::new();
+    let name = &ffi_type.name;
+
+    output.push_str("#[allow(dead_code)]\n");
+    output.push_str(&format!(
+        "fn {}<'local>(env: &mut JNIEnv<'local>, value: &crate::{}) -> JObject<'local> {{\n",
+        to_java_fn(name), name
+    ));
+
+    match &ffi_type.kind {
+        RustFfiTypeKind::Struct(fields) => {
+            generate_new_object(&mut output, fields, false, name, package, "    ");
+        }
+        RustFfiTypeKind::Callback(_) => unreachable!("callback traits have no value conversions"),
+        RustFfiTypeKind::Enum(variants) if ffi_type.is_unit_enum() => {
+            output.push_str("    let name = match value {\n");
+            for variant in variants {
+                output.push_str(&format!("        crate::{}::{} => \"{}\",\n", name, variant.name, variant.name));
+            }
+            output.push_str("    };\n");
+            let class = jvm_class(package, name);
+            output.push_str(&format!("    env.get_static_field(\"{}\", name, \"L{};\")\n", class, class));
+            output.push_str("        .and_then(|v| v.l())\n");
+            output.push_str(&format!("        .expect(\"Couldn't get {} entry!\")\n", name));
+        }
+        RustFfiTypeKind::Enum(variants) => {
+            output.push_str("    match value {\n");
+            for variant in variants {
+                let class = format!("{}${}", name, variant.name);
+                let bindings: Vec<&str> = variant.fields.iter().map(|(f, _)| f.as_str()).collect();
+                let pattern = if variant.fields.is_empty() {
+                    String::new()
+                } else if variant.is_tuple {
+                    format!("({})", bindings.join(", "))
+                } else {
+                    format!(" {{ {} }}", bindings.join(", "))
+                };
+                output.push_str(&format!("        crate::{}::{}{} => {{\n", name, variant.name, pattern));
+
+                if variant.fields.is_empty() {
+                    let jvm = jvm_class(package, &class);
+                    output.push_str(&format!(
+                        "            env.get_static_field(\"{}\", \"INSTANCE\", \"L{};\")\n",
+                        jvm, jvm
+                    ));
+                    output.push_str("                .and_then(|v| v.l())\n");
+                    output.push_str(&format!("                .expect(\"Couldn't get {}.{}!\")\n", name, variant.name));
+                } else {
+                    generate_new_object(&mut output, &variant.fields, true, &class, package, "            ");
+                }
+                output.push_str("        }\n");
+            }
+            output.push_str("    }\n");
+        }
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// Generate the Kotlin -> Rust conversion for an #[ffi] struct or enum
+fn generate_from_java(ffi_type: &RustFfiType, package: &str) -> String {
+    let mut output = String::new();
+    let name = &ffi_type.name;
+
+    output.push_str("#[allow(dead_code)]\n");
+    output.push_str(&format!(
+        "fn {}(env: &mut JNIEnv, obj: &JObject) -> crate::{} {{\n",
+        from_java_fn(name), name
+    ));
+
+    match &ffi_type.kind {
+        RustFfiTypeKind::Callback(_) => unreachable!("callback traits have no value conversions"),
+        RustFfiTypeKind::Struct(fields) => {
+            output.push_str(&format!("    crate::{} {{\n", name));
+            for (field, typ) in fields {
+                output.push_str(&format!("        {}: {},\n", field, field_from_java(field, typ, package)));
+            }
+            output.push_str("    }\n");
+        }
+        RustFfiTypeKind::Enum(variants) if ffi_type.is_unit_enum() => {
+            output.push_str("    let ordinal = env.call_method(obj, \"ordinal\", \"()I\", &[])\n");
+            output.push_str("        .and_then(|v| v.i())\n");
+            output.push_str(&format!("        .expect(\"Couldn't read {} ordinal!\");\n", name));
+            output.push_str("    match ordinal {\n");
+            for (i, variant) in variants.iter().enumerate() {
+                output.push_str(&format!("        {} => crate::{}::{},\n", i, name, variant.name));
+            }
+            output.push_str(&format!("        _ => panic!(\"Unknown {} ordinal {{}}\", ordinal),\n", name));
+            output.push_str("    }\n");
+        }
+        RustFfiTypeKind::Enum(variants) => {
+            for variant in variants {
+                let class = jvm_class(package, &format!("{}${}", name, variant.name));
+                output.push_str(&format!(
+                    "    if env.is_instance_of(obj, \"{}\").expect(\"Couldn't check {} variant!\") {{\n",
+                    class, name
+                ));
+                let values: Vec<String> = variant
+                    .fields
+                    .iter()
+                    .map(|(field, typ)| {
+                        let value = field_from_java(field, typ, package);
+                        if variant.is_tuple { value } else { format!("{}: {}", field, value) }
+                    })
+                    .collect();
+                let construct = if variant.fields.is_empty() {
+                    String::new()
+                } else if variant.is_tuple {
+                    format!("(\n            {},\n        )", values.join(",\n            "))
+                } else {
+                    format!(" {{\n            {},\n        }}", values.join(",\n            "))
+                };
+                output.push_str(&format!("        return crate::{}::{}{};\n", name, variant.name, construct));
+                output.push_str("    }\n");
+            }
+            output.push_str(&format!("    panic!(\"Unknown {} variant\")\n", name));
+        }
+    }
+
+    output.push_str("}\n");
+    output
 }
 
 #[cfg(test)]
@@ -352,10 +1262,12 @@ mod tests {
                 ("b".to_string(), RustType::Int),
             ],
             return_type: RustFunctionReturn::plain(RustType::Int),
+            is_async: false,
+            docs: Default::default(),
             source_file: PathBuf::from("lib.rs"),
         };
 
-        let bridge = generate_rust_bridge(&vec![func], "com.example.ffi");
+        let bridge = generate_rust_bridge(&vec![func], &[], &[], "com.example.ffi");
 
         assert!(bridge.contains("use jni::JNIEnv"));
         assert!(bridge.contains("use jni::objects::{JClass"));
@@ -373,10 +1285,12 @@ mod tests {
             name: "greet".to_string(),
             params: vec![("name".to_string(), RustType::String)],
             return_type: RustFunctionReturn::plain(RustType::String),
+            is_async: false,
+            docs: Default::default(),
             source_file: PathBuf::from("lib.rs"),
         };
 
-        let bridge = generate_rust_bridge(&vec![func], "com.example.ffi");
+        let bridge = generate_rust_bridge(&vec![func], &[], &[], "com.example.ffi");
 
         assert!(bridge.contains("name: jstring"));
         assert!(bridge.contains("-> jstring"));
@@ -391,15 +1305,39 @@ mod tests {
             name: "double_values".to_string(),
             params: vec![("values".to_string(), RustType::IntArray)],
             return_type: RustFunctionReturn::plain(RustType::IntArray),
+            is_async: false,
+            docs: Default::default(),
             source_file: PathBuf::from("lib.rs"),
         };
 
-        let bridge = generate_rust_bridge(&vec![func], "com.example.ffi");
+        let bridge = generate_rust_bridge(&vec![func], &[], &[], "com.example.ffi");
 
         assert!(bridge.contains("values: jintArray"));
         assert!(bridge.contains("-> jintArray"));
-        assert!(bridge.contains("get_int_array_elements"));
+        assert!(bridge.contains("get_int_array_region"));
         assert!(bridge.contains("new_int_array"));
         assert!(bridge.contains("set_int_array_region"));
     }
+
+    #[test]
+    fn test_generate_async_function() {
+        let func = RustFfiFunction {
+            name: "hash_file".to_string(),
+            params: vec![
+                ("path".to_string(), RustType::String),
+                ("cancelled".to_string(), RustType::CancelFlag),
+            ],
+            return_type: RustFunctionReturn::result(RustType::Bytes),
+            is_async: true,
+            docs: Default::default(),
+            source_file: PathBuf::from("lib.rs"),
+        };
+
+        let bridge = generate_rust_bridge(&vec![func], &[], &[], "com.example.ffi");
+
+        assert!(bridge.contains("pub extern \"system\" fn Java_com_example_ffi_nativeHashFile"));
+        assert!(bridge.contains("cancelled: jobject"));
+        assert!(bridge.contains("unsafe { &*(ptr_cancelled as *const std::sync::atomic::AtomicBool) }"));
+        assert!(bridge.contains("crate::hash_file(rust_path, rust_cancelled)"));
+    }
 }
@@ -0,0 +1,217 @@
+//! `// @ffi` declarations in C headers
+//!
+//! C functions are described with the C++ FFI types, so the Kotlin generator is
+//! shared; only the JNI bridge differs (see `generate_c_jni_bridge`).
+
+use std::path::{Path, PathBuf};
+use std::fs;
+use anyhow::{Result, Context, bail};
+use super::cpp::{doxygen_comment, split_top_level, CppFfiFunction, CppType};
+use super::cpp_decl;
+use super::docs::FfiDocs;
+
+/// Map a C type to its FFI type
+///
+/// Strings are `const char*`: parameters are only valid during the call, and returned
+/// strings are copied into a Kotlin String but never freed.
+pub fn c_type_from_str(type_str: &str) -> Result<CppType> {
+    // `const char *`, `const char*` and `const  char  *` are the same type
+    let normalized = type_str
+        .replace('*', " * ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace(" *", "*");
+
+    match normalized.as_str() {
+        "void" => Ok(CppType::Void),
+        "int" | "int32_t" => Ok(CppType::Int),
+        "long" | "long long" | "int64_t" => Ok(CppType::Long),
+        "float" => Ok(CppType::Float),
+        "double" => Ok(CppType::Double),
+        "bool" | "_Bool" => Ok(CppType::Bool),
+        "const char*" | "char const*" => Ok(CppType::String),
+        "char*" => bail!("Unsupported C type: 'char*'. Use 'const char*' for strings"),
+        _ => bail!(
+            "Unsupported C type: '{}'. C FFI supports: int, int32_t, long, int64_t, float, double, bool and const char*",
+            type_str.trim()
+        ),
+    }
+}
+
+/// Discover all @ffi annotated declarations in C headers
+pub fn discover_c_ffi(ffi_dir: &Path) -> Result<Vec<CppFfiFunction>> {
+    let mut functions = Vec::new();
+
+    for path in c_files(ffi_dir, "h")? {
+        let content = fs::read_to_string(&path)
+            .context(format!("Failed to read file: {}", path.display()))?;
+        functions.extend(parse_c_ffi_from_string(&content, &path)?);
+    }
+
+    Ok(functions)
+}
+
+/// C sources compiled into the library: ffi/c/*.c
+pub fn c_source_files(ffi_dir: &Path) -> Result<Vec<PathBuf>> {
+    c_files(ffi_dir, "c")
+}
+
+/// Files in ffi/c/ with the given extension, sorted so builds are reproducible
+fn c_files(ffi_dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    let c_dir = ffi_dir.join("c");
+
+    if !c_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    let entries = fs::read_dir(&c_dir)
+        .context(format!("Failed to read directory: {}", c_dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some(extension) {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Parse a C header for @ffi declarations (testable)
+pub fn parse_c_ffi_from_string(content: &str, source_file: &Path) -> Result<Vec<CppFfiFunction>> {
+    let Some(tokens) = cpp_decl::tokenize(content) else {
+        bail!(
+            "Could not parse {}: unbalanced braces or an unterminated comment or literal",
+            source_file.display()
+        );
+    };
+
+    let mut functions = Vec::new();
+    for declaration in cpp_decl::scan_declarations(&tokens, source_file)? {
+        let location = format!("{}:{}", source_file.display(), declaration.line);
+        let function_name = declaration.name.as_str();
+
+        if !declaration.namespace.is_empty() {
+            bail!("@ffi function '{}' at {} is in a namespace. C headers can't declare namespaces", function_name, location);
+        }
+
+        let return_type = c_type_from_str(&declaration.return_type)
+            .context(format!("In function '{}' at {}", function_name, location))?;
+
+        let mut params = Vec::new();
+        for param in split_top_level(&declaration.params) {
+            // `int f(void)` takes no arguments
+            if param == "void" {
+                continue;
+            }
+
+            // The name is the last identifier; a `*` attached to it belongs to the type
+            let Some(split) = param.rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_')) else {
+                bail!(
+                    "Invalid parameter '{}' in function '{}' at {}. Expected format: 'type name'",
+                    param, function_name, location
+                );
+            };
+            let (param_type, param_name) = param.split_at(split + 1);
+            if param_name.is_empty() || param_type.trim().is_empty() {
+                bail!(
+                    "Invalid parameter '{}' in function '{}' at {}. C FFI parameters need a name",
+                    param, function_name, location
+                );
+            }
+
+            let param_type = c_type_from_str(param_type).context(format!(
+                "In parameter '{}' of function '{}' at {}",
+                param_name, function_name, location
+            ))?;
+            if param_type == CppType::Void {
+                bail!("Parameter '{}' of function '{}' at {} can't be void", param_name, function_name, location);
+            }
+            params.push((param_name.to_string(), param_type));
+        }
+
+        let (lines, doc_deprecated) = doxygen_comment(&content[..declaration.marker]);
+
+        functions.push(CppFfiFunction {
+            name: declaration.name,
+            namespace: Vec::new(),
+            params,
+            return_type,
+            docs: FfiDocs {
+                lines,
+                deprecated: declaration.deprecated.or(doc_deprecated),
+                defaults: Vec::new(),
+            },
+            source_file: source_file.to_path_buf(),
+        });
+    }
+
+    Ok(functions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_c_header() {
+        let header = r#"
+#ifndef MATH_H
+#define MATH_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/** Add two numbers */
+// @ffi
+int add(int a, int b);
+
+// @ffi
+const char *greet(const char *name);
+
+// @ffi
+double average(void);
+
+// @ffi
+bool is_even(int64_t n);
+
+int helper(int x);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
+"#;
+        let functions = parse_c_ffi_from_string(header, Path::new("math.h")).unwrap();
+        assert_eq!(functions.len(), 4);
+
+        assert_eq!(functions[0].name, "add");
+        assert_eq!(functions[0].params, vec![("a".to_string(), CppType::Int), ("b".to_string(), CppType::Int)]);
+        assert_eq!(functions[0].docs.lines, vec!["Add two numbers"]);
+
+        assert_eq!(functions[1].return_type, CppType::String);
+        assert_eq!(functions[1].params, vec![("name".to_string(), CppType::String)]);
+
+        assert!(functions[2].params.is_empty());
+        assert_eq!(functions[3].params, vec![("n".to_string(), CppType::Long)]);
+        assert_eq!(functions[3].return_type, CppType::Bool);
+    }
+
+    #[test]
+    fn test_c_unsupported_types() {
+        assert!(c_type_from_str("char *").is_err());
+        assert!(c_type_from_str("struct point").is_err());
+        assert!(c_type_from_str("unsigned int").is_err());
+        assert_eq!(c_type_from_str("char const *").unwrap(), CppType::String);
+
+        let err = parse_c_ffi_from_string("// @ffi\nvoid fill(int* out);\n", Path::new("buf.h")).unwrap_err();
+        assert!(format!("{:#}", err).contains("int*"));
+    }
+}
@@ -2,6 +2,8 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use regex::Regex;
 use anyhow::{Result, Context, bail};
+use super::cpp_decl::{self, RawDeclaration};
+use super::docs::{kotlin_default, trim_doc_lines, DefaultLiteral, FfiDocs};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CppType {
@@ -19,6 +21,34 @@ pub enum CppType {
     DoubleArray,
     BoolArray,
     StringArray,
+    /// An `@ffi` struct or enum class, by name
+    Custom(String),
+    /// `std::vector<T>` of an `@ffi` struct or enum class (Kotlin `List<T>`)
+    CustomList(String),
+    /// `std::function<R(A, ..)>` parameter (Kotlin `fun interface`)
+    Function(Box<CppCallback>),
+}
+
+/// Signature of a `std::function` parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct CppCallback {
+    /// Name of the generated Kotlin `fun interface`, e.g. `DownloadOnProgressCallback`
+    pub interface_name: String,
+    pub params: Vec<CppType>,
+    pub return_type: CppType,
+    /// `R(A, B)` as written, so the bridge's declaration matches the user's exactly
+    pub signature: String,
+}
+
+impl CppCallback {
+    /// Kotlin parameter names for the callback arguments: `value` (or `value0`, `value1`, ...)
+    pub fn param_names(&self) -> Vec<String> {
+        if self.params.len() == 1 {
+            vec!["value".to_string()]
+        } else {
+            (0..self.params.len()).map(|i| format!("value{}", i)).collect()
+        }
+    }
 }
 
 impl CppType {
@@ -43,6 +73,11 @@ impl CppType {
             }
         }
 
+        // Callbacks: std::function<R(A, B)>
+        if type_str.starts_with("std::function<") && type_str.ends_with('>') {
+            return Self::parse_function_type(&type_str["std::function<".len()..type_str.len() - 1]);
+        }
+
         // Check for array types (std::vector<T> or const std::vector<T>&)
         // Note: const and & have already been stripped above
         if type_str.starts_with("std::vector<") {
@@ -58,7 +93,8 @@ impl CppType {
                 "double" => Ok(CppType::DoubleArray),
                 "bool" => Ok(CppType::BoolArray),
                 "std::string" | "string" => Ok(CppType::StringArray),
-                _ => bail!("Unsupported array element type: '{}'. Phase 3 supports: int, long, float, double, bool, std::string", inner_type),
+                _ if is_custom_type_name(inner_type) => Ok(CppType::CustomList(inner_type.to_string())),
+                _ => bail!("Unsupported array element type: '{}'. Phase 3 supports: int, long, float, double, bool, std::string and @ffi structs/enums", inner_type),
             };
         }
 
@@ -71,10 +107,59 @@ impl CppType {
             "bool" => Ok(CppType::Bool),
             "std::string" => Ok(CppType::String),
             "string" => Ok(CppType::String),
-            _ => bail!("Unsupported C++ type: '{}'. Phase 3 supports: int, long, float, double, bool, std::string, std::vector<T>", type_str),
+            _ if is_custom_type_name(type_str) => Ok(CppType::Custom(type_str.to_string())),
+            _ => bail!("Unsupported C++ type: '{}'. Phase 3 supports: int, long, float, double, bool, std::string, std::vector<T> and @ffi structs/enums", type_str),
         }
     }
 
+    /// Parse the `R(A, B)` part of `std::function<R(A, B)>`
+    fn parse_function_type(signature: &str) -> Result<Self> {
+        let (Some(open), Some(close)) = (signature.find('('), signature.rfind(')')) else {
+            bail!("Invalid std::function signature: '{}'. Expected std::function<R(A, B)>", signature);
+        };
+
+        let return_type = CppType::from_str(&signature[..open])?;
+        let params = split_top_level(&signature[open + 1..close])
+            .into_iter()
+            .map(|param| {
+                // Argument names are optional: `void(int percent)`
+                CppType::from_str(param).or_else(|err| match param.rsplit_once(char::is_whitespace) {
+                    Some((typ, _)) => CppType::from_str(typ),
+                    None => Err(err),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for param in &params {
+            if !matches!(
+                param,
+                CppType::Int | CppType::Long | CppType::Float | CppType::Double | CppType::Bool
+                    | CppType::String | CppType::Custom(_) | CppType::CustomList(_)
+            ) {
+                bail!(
+                    "Unsupported callback argument type: {}. Callbacks support primitives, std::string and @ffi types",
+                    param.to_cpp_type()
+                );
+            }
+        }
+        if !matches!(
+            return_type,
+            CppType::Void | CppType::Int | CppType::Long | CppType::Float | CppType::Double | CppType::Bool | CppType::String
+        ) {
+            bail!(
+                "Unsupported callback return type: {}. Callbacks can return primitives, std::string or void",
+                return_type.to_cpp_type()
+            );
+        }
+
+        Ok(CppType::Function(Box::new(CppCallback {
+            interface_name: String::new(),
+            params,
+            return_type,
+            signature: signature.split_whitespace().collect::<Vec<_>>().join(" "),
+        })))
+    }
+
     /// Get the corresponding JNI type name
     pub fn to_jni_type(&self) -> &'static str {
         match self {
@@ -91,65 +176,191 @@ impl CppType {
             CppType::DoubleArray => "jdoubleArray",
             CppType::BoolArray => "jbooleanArray",
             CppType::StringArray => "jobjectArray",
+            CppType::Custom(_) | CppType::CustomList(_) | CppType::Function(_) => "jobject",
         }
     }
 
     /// Get the corresponding Kotlin type name
-    pub fn to_kotlin_type(&self) -> &'static str {
+    pub fn to_kotlin_type(&self) -> String {
         match self {
-            CppType::Void => "Unit",
-            CppType::Int => "Int",
-            CppType::Long => "Long",
-            CppType::Float => "Float",
-            CppType::Double => "Double",
-            CppType::Bool => "Boolean",
-            CppType::String => "String",
-            CppType::IntArray => "IntArray",
-            CppType::LongArray => "LongArray",
-            CppType::FloatArray => "FloatArray",
-            CppType::DoubleArray => "DoubleArray",
-            CppType::BoolArray => "BooleanArray",
-            CppType::StringArray => "Array<String>",
+            CppType::Void => "Unit".to_string(),
+            CppType::Int => "Int".to_string(),
+            CppType::Long => "Long".to_string(),
+            CppType::Float => "Float".to_string(),
+            CppType::Double => "Double".to_string(),
+            CppType::Bool => "Boolean".to_string(),
+            CppType::String => "String".to_string(),
+            CppType::IntArray => "IntArray".to_string(),
+            CppType::LongArray => "LongArray".to_string(),
+            CppType::FloatArray => "FloatArray".to_string(),
+            CppType::DoubleArray => "DoubleArray".to_string(),
+            CppType::BoolArray => "BooleanArray".to_string(),
+            CppType::StringArray => "Array<String>".to_string(),
+            CppType::Custom(name) => name.clone(),
+            CppType::CustomList(name) => format!("List<{}>", name),
+            CppType::Function(callback) => callback.interface_name.clone(),
         }
     }
 
     /// Get the C++ type string for forward declarations
-    pub fn to_cpp_type(&self) -> &'static str {
+    pub fn to_cpp_type(&self) -> String {
         match self {
-            CppType::Void => "void",
-            CppType::Int => "int",
-            CppType::Long => "long long",
-            CppType::Float => "float",
-            CppType::Double => "double",
-            CppType::Bool => "bool",
-            CppType::String => "std::string",
-            CppType::IntArray => "std::vector<int>",
-            CppType::LongArray => "std::vector<long long>",
-            CppType::FloatArray => "std::vector<float>",
-            CppType::DoubleArray => "std::vector<double>",
-            CppType::BoolArray => "std::vector<bool>",
-            CppType::StringArray => "std::vector<std::string>",
+            CppType::Void => "void".to_string(),
+            CppType::Int => "int".to_string(),
+            CppType::Long => "long long".to_string(),
+            CppType::Float => "float".to_string(),
+            CppType::Double => "double".to_string(),
+            CppType::Bool => "bool".to_string(),
+            CppType::String => "std::string".to_string(),
+            CppType::IntArray => "std::vector<int>".to_string(),
+            CppType::LongArray => "std::vector<long long>".to_string(),
+            CppType::FloatArray => "std::vector<float>".to_string(),
+            CppType::DoubleArray => "std::vector<double>".to_string(),
+            CppType::BoolArray => "std::vector<bool>".to_string(),
+            CppType::StringArray => "std::vector<std::string>".to_string(),
+            CppType::Custom(name) => name.clone(),
+            CppType::CustomList(name) => format!("std::vector<{}>", name),
+            CppType::Function(callback) => format!("std::function<{}>", callback.signature),
+        }
+    }
+
+    /// Name of the `@ffi` struct/enum this type refers to, if any
+    pub fn custom_name(&self) -> Option<&str> {
+        match self {
+            CppType::Custom(name) | CppType::CustomList(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Split on commas that are not nested inside `<>` or `()`
+pub(super) fn split_top_level(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, ch) in list.char_indices() {
+        match ch {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
         }
     }
+    parts.push(list[start..].trim());
+
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+/// Convert snake_case or camelCase to PascalCase (`on_progress` -> `OnProgress`)
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Type names starting with an uppercase letter may refer to `@ffi` structs/enums;
+/// check_cpp_ffi_types() verifies they actually exist once everything is discovered
+fn is_custom_type_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether a parameter name can be used as-is in C++ and Kotlin
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
 #[derive(Debug, Clone)]
 pub struct CppFfiFunction {
     pub name: String,
+    /// Enclosing C++ namespaces, outermost first (`audio::dsp` -> ["audio", "dsp"])
+    pub namespace: Vec<String>,
     pub params: Vec<(String, CppType)>,
     pub return_type: CppType,
+    /// Doxygen comment, `[[deprecated]]` and literal default arguments for the Kotlin API
+    pub docs: FfiDocs,
     pub source_file: PathBuf,
 }
 
+impl CppFfiFunction {
+    /// Name to call the function by from the bridge (`audio::dsp::gain`)
+    pub fn qualified_name(&self) -> String {
+        self.namespace
+            .iter()
+            .chain(std::iter::once(&self.name))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// Nested Kotlin objects the function is declared in (`audio::dsp` -> ["Audio", "Dsp"])
+    pub fn kotlin_scope(&self) -> Vec<String> {
+        self.namespace.iter().map(|name| to_pascal_case(name)).collect()
+    }
+}
+
+/// An `@ffi` struct or enum class, mirrored as a Kotlin data class or enum class
+#[derive(Debug, Clone)]
+pub struct CppFfiType {
+    pub name: String,
+    pub kind: CppFfiTypeKind,
+    pub source_file: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CppFfiTypeKind {
+    /// `struct Point { double x; double y; };`
+    Struct(Vec<(String, CppType)>),
+    /// `enum class Color { Red, Green };`
+    Enum(Vec<String>),
+}
+
 /// Discover all @ffi annotated functions in C++ files
 pub fn discover_cpp_ffi(ffi_dir: &Path) -> Result<Vec<CppFfiFunction>> {
+    let mut functions = Vec::new();
+
+    for path in cpp_source_files(ffi_dir)? {
+        let file_functions = parse_cpp_file(&path)?;
+        functions.extend(file_functions);
+    }
+
+    Ok(functions)
+}
+
+/// Discover all @ffi annotated structs and enum classes in C++ files
+pub fn discover_cpp_ffi_types(ffi_dir: &Path) -> Result<Vec<CppFfiType>> {
+    let mut types = Vec::new();
+
+    for path in cpp_source_files(ffi_dir)? {
+        let content = fs::read_to_string(&path)
+            .context(format!("Failed to read file: {}", path.display()))?;
+        types.extend(parse_cpp_ffi_types_from_string(&content, &path)?);
+    }
+
+    Ok(types)
+}
+
+/// C++ files scanned for @ffi declarations: ffi/cpp/*.cpp
+fn cpp_source_files(ffi_dir: &Path) -> Result<Vec<PathBuf>> {
     let cpp_dir = ffi_dir.join("cpp");
 
     if !cpp_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let mut functions = Vec::new();
+    let mut files = Vec::new();
 
     // Find all .cpp files
     let entries = fs::read_dir(&cpp_dir)
@@ -160,12 +371,11 @@ pub fn discover_cpp_ffi(ffi_dir: &Path) -> Result<Vec<CppFfiFunction>> {
         let path = entry.path();
 
         if path.extension().and_then(|s| s.to_str()) == Some("cpp") {
-            let file_functions = parse_cpp_file(&path)?;
-            functions.extend(file_functions);
+            files.push(path);
         }
     }
 
-    Ok(functions)
+    Ok(files)
 }
 
 /// Parse a single C++ file for @ffi annotations
@@ -177,9 +387,24 @@ fn parse_cpp_file(path: &Path) -> Result<Vec<CppFfiFunction>> {
 }
 
 /// Parse C++ source code for @ffi annotations (testable)
+///
+/// Declarations are found by a tokenizer that tracks namespaces and reads
+/// multi-line signatures. Sources it can't follow (e.g. braces hidden in macros)
+/// fall back to the line-based regex scan.
 pub fn parse_cpp_ffi_from_string(content: &str, source_file: &Path) -> Result<Vec<CppFfiFunction>> {
-    let mut functions = Vec::new();
+    let declarations = match cpp_decl::tokenize(content) {
+        Some(tokens) => cpp_decl::scan_declarations(&tokens, source_file)?,
+        None => scan_declarations_with_regex(content, source_file)?,
+    };
+
+    declarations
+        .into_iter()
+        .map(|declaration| function_from_declaration(declaration, content, source_file))
+        .collect()
+}
 
+/// Regex fallback for `parse_cpp_ffi_from_string`: top-level, single-line signatures only
+fn scan_declarations_with_regex(content: &str, source_file: &Path) -> Result<Vec<RawDeclaration>> {
     // Regex to match: // @ffi followed by a function signature
     // Pattern: return_type function_name(params) {
     // Note: Handles multi-word types, templates, and references
@@ -188,48 +413,109 @@ pub fn parse_cpp_ffi_from_string(content: &str, source_file: &Path) -> Result<Ve
     // - Type name with :: namespace
     // - Template parameters <...>
     // - Reference &
+    //
+    // The parameter list is read up to the matching ')' since std::function
+    // parameters contain parentheses of their own.
+    //
+    // An optional [[deprecated("..")]] attribute may precede the return type.
     let ffi_regex = Regex::new(
-        r"(?m)^\s*//\s*@ffi\s*\n\s*([a-zA-Z_][\w:<>, &]*)\s+(\w+)\s*\(([^)]*)\)"
+        r#"(?m)^\s*//\s*@ffi\s*\n\s*(\[\[deprecated(?:\s*\(\s*"((?:[^"\\]|\\.)*)"\s*\))?\]\]\s*)?([a-zA-Z_][\w:<>, &]*)\s+(\w+)\s*\("#
     ).unwrap();
 
-    for cap in ffi_regex.captures_iter(content) {
-        let return_type_str = cap.get(1).unwrap().as_str().trim();
-        let function_name = cap.get(2).unwrap().as_str().trim();
-        let params_str = cap.get(3).unwrap().as_str().trim();
-
-        // Parse return type
-        let return_type = CppType::from_str(return_type_str)
-            .context(format!("In function '{}' at {}", function_name, source_file.display()))?;
+    ffi_regex
+        .captures_iter(content)
+        .map(|cap| {
+            let name = cap.get(4).unwrap();
+            let line = content[..name.start()].matches('\n').count() + 1;
+            let params_start = cap.get(0).unwrap().end();
+            let params = match matching_paren(&content[params_start..]) {
+                Some(end) => &content[params_start..params_start + end],
+                None => bail!(
+                    "Unterminated parameter list in function '{}' at {}:{}",
+                    name.as_str(), source_file.display(), line
+                ),
+            };
 
-        // Parse parameters
-        let params = parse_parameters(params_str, function_name, source_file)?;
+            Ok(RawDeclaration {
+                name: name.as_str().to_string(),
+                namespace: Vec::new(),
+                return_type: cap.get(3).unwrap().as_str().trim().to_string(),
+                params: params.trim().to_string(),
+                deprecated: cap
+                    .get(1)
+                    .map(|_| cap.get(2).map_or("Deprecated".to_string(), |m| m.as_str().to_string())),
+                line,
+                marker: cap.get(0).unwrap().start() + cap.get(0).unwrap().as_str().find("//").unwrap(),
+            })
+        })
+        .collect()
+}
 
-        functions.push(CppFfiFunction {
-            name: function_name.to_string(),
-            params,
-            return_type,
-            source_file: source_file.to_path_buf(),
-        });
+/// Interpret the types of a scanned declaration
+fn function_from_declaration(
+    declaration: RawDeclaration,
+    content: &str,
+    source_file: &Path,
+) -> Result<CppFfiFunction> {
+    let function_name = declaration.name.as_str();
+    let location = format!("{}:{}", source_file.display(), declaration.line);
+
+    // Parse return type
+    let return_type = CppType::from_str(&declaration.return_type)
+        .context(format!("In function '{}' at {}", function_name, location))?;
+
+    if matches!(return_type, CppType::Function(_)) {
+        bail!("FFI functions cannot return std::function in function '{}' at {}",
+              function_name, location);
     }
 
-    Ok(functions)
+    // Parse parameters
+    let (params, defaults) = parse_parameters(&declaration.params, function_name, &location)?;
+
+    // Doxygen comment directly above the `// @ffi` line
+    let (lines, doc_deprecated) = doxygen_comment(&content[..declaration.marker]);
+    let deprecated = declaration.deprecated.or(doc_deprecated);
+
+    Ok(CppFfiFunction {
+        name: declaration.name,
+        namespace: declaration.namespace,
+        params,
+        return_type,
+        docs: FfiDocs { lines, deprecated, defaults },
+        source_file: source_file.to_path_buf(),
+    })
 }
 
+/// Kotlin default value expressions, by parameter name
+type KotlinDefaults = Vec<(String, String)>;
+
 /// Parse parameter list: "int a, float b" -> [("a", Int), ("b", Float)]
 /// Handles multi-word types like "long long"
-fn parse_parameters(params_str: &str, function_name: &str, source_file: &Path) -> Result<Vec<(String, CppType)>> {
+///
+/// Also returns the Kotlin defaults for literal default arguments (`int retries = 3`);
+/// other default expressions can't be evaluated in Kotlin and are dropped.
+fn parse_parameters(
+    params_str: &str,
+    function_name: &str,
+    location: &str,
+) -> Result<(Vec<(String, CppType)>, KotlinDefaults)> {
     if params_str.is_empty() {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), Vec::new()));
     }
 
     let mut params = Vec::new();
+    let mut defaults = Vec::new();
 
-    for param in params_str.split(',') {
-        let param = param.trim();
+    for param in split_top_level(params_str) {
         if param.is_empty() {
             continue;
         }
 
+        let (param, default_value) = match param.find('=') {
+            Some(eq) => (param[..eq].trim(), Some(param[eq + 1..].trim())),
+            None => (param, None),
+        };
+
         // Split into type and name
         // For "int a" or "long long a": last word is name, everything before is type
         let parts: Vec<&str> = param.split_whitespace().collect();
@@ -237,26 +523,349 @@ fn parse_parameters(params_str: &str, function_name: &str, source_file: &Path) -
         if parts.len() < 2 {
             bail!(
                 "Invalid parameter '{}' in function '{}' at {}. Expected format: 'type name'",
-                param, function_name, source_file.display()
+                param, function_name, location
             );
         }
 
-        // Last part is the parameter name
-        let param_name = parts[parts.len() - 1];
+        // Last part is the parameter name; `&` or `*` written against it ("int *p",
+        // "const std::vector<int> &xs") belongs to the type
+        let last = parts[parts.len() - 1];
+        let param_name = last.trim_start_matches(['&', '*']);
+        if !is_identifier(param_name) {
+            bail!(
+                "Invalid parameter name '{}' in function '{}' at {}",
+                param_name, function_name, location
+            );
+        }
 
         // Everything before is the type (handles "long long", "unsigned int", etc.)
-        let param_type_str = parts[..parts.len() - 1].join(" ");
+        let param_type_str = format!(
+            "{}{}",
+            parts[..parts.len() - 1].join(" "),
+            &last[..last.len() - param_name.len()]
+        );
 
         let param_type = CppType::from_str(&param_type_str)
             .context(format!(
                 "In parameter '{}' of function '{}' at {}",
-                param_name, function_name, source_file.display()
+                param_name, function_name, location
             ))?;
 
+        let param_type = match param_type {
+            CppType::Function(mut callback) => {
+                callback.interface_name = format!(
+                    "{}{}Callback",
+                    to_pascal_case(function_name),
+                    to_pascal_case(param_name)
+                );
+                CppType::Function(callback)
+            }
+            other => other,
+        };
+
+        let kotlin_value = default_value
+            .and_then(cpp_default_literal)
+            .and_then(|literal| kotlin_default(&literal, &param_type.to_kotlin_type()));
+        if let Some(value) = kotlin_value {
+            defaults.push((param_name.to_string(), value));
+        }
+
         params.push((param_name.to_string(), param_type));
     }
 
-    Ok(params)
+    Ok((params, defaults))
+}
+
+/// Literal of a C++ default argument (`3`, `-1L`, `0.5f`, `true`, `"text"`, `nullptr`)
+fn cpp_default_literal(value: &str) -> Option<DefaultLiteral> {
+    let number = value.trim_end_matches(['u', 'U', 'l', 'L', 'f', 'F']);
+    let digits = number.strip_prefix('-').unwrap_or(number);
+    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        return Some(DefaultLiteral::Int(number.to_string()));
+    }
+    if digits.parse::<f64>().is_ok() && digits.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+        return Some(DefaultLiteral::Float(number.to_string()));
+    }
+
+    match value {
+        "true" => Some(DefaultLiteral::Bool(true)),
+        "false" => Some(DefaultLiteral::Bool(false)),
+        "nullptr" | "std::nullopt" => Some(DefaultLiteral::Null),
+        _ if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') => {
+            let inner = &value[1..value.len() - 1];
+            if inner.contains('\\') {
+                return None;
+            }
+            Some(DefaultLiteral::Str(inner.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Doxygen comment (`///`, `//!` lines or a `/** */` block) ending right before `text`
+///
+/// Returns the lines converted to KDoc and the `@deprecated` message, if any.
+pub(super) fn doxygen_comment(text: &str) -> (Vec<String>, Option<String>) {
+    let mut raw = Vec::new();
+    let mut lines = text.lines().rev();
+
+    // `text` ends at the `// @ffi` marker, so its last line holds only indentation
+    if !text.ends_with('\n') {
+        lines.next();
+    }
+    let mut lines = lines.map(str::trim).peekable();
+
+    if lines.peek().is_some_and(|line| line.ends_with("*/")) {
+        for line in lines {
+            let is_start = line.starts_with("/**") || line.starts_with("/*!");
+            raw.push(line);
+            if is_start {
+                break;
+            }
+            if line.starts_with("/*") {
+                // A plain block comment, not documentation
+                return (Vec::new(), None);
+            }
+        }
+        if !raw.last().is_some_and(|line| line.starts_with("/**") || line.starts_with("/*!")) {
+            return (Vec::new(), None);
+        }
+    } else {
+        raw.extend(lines.take_while(|line| line.starts_with("///") || line.starts_with("//!")));
+    }
+    raw.reverse();
+
+    let mut result = Vec::new();
+    let mut deprecated = None;
+    for line in raw {
+        let line = line
+            .trim_start_matches("/**")
+            .trim_start_matches("/*!")
+            .trim_start_matches("///")
+            .trim_start_matches("//!")
+            .trim_end_matches("*/")
+            .trim();
+        let line = line.strip_prefix('*').map_or(line, str::trim_start);
+
+        // Doxygen commands may start with '\' or '@'; KDoc uses '@'
+        let (command, rest) = match line.strip_prefix('\\').or_else(|| line.strip_prefix('@')) {
+            Some(tagged) => tagged.split_once(char::is_whitespace).unwrap_or((tagged, "")),
+            None => ("", line),
+        };
+        let rest = rest.trim();
+        match command {
+            "" => result.push(line.to_string()),
+            "brief" | "short" => result.push(rest.to_string()),
+            "param" | "param[in]" | "param[out]" | "param[in,out]" => result.push(format!("@param {}", rest)),
+            "return" | "returns" | "result" => result.push(format!("@return {}", rest)),
+            "throw" | "throws" | "exception" => result.push(format!("@throws {}", rest)),
+            "deprecated" => deprecated = Some(if rest.is_empty() { "Deprecated".to_string() } else { rest.to_string() }),
+            other => result.push(format!("@{} {}", other, rest).trim_end().to_string()),
+        }
+    }
+
+    (trim_doc_lines(result), deprecated)
+}
+
+/// Byte offset of the ')' closing a parameter list whose '(' was just consumed
+fn matching_paren(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, ch) in text.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse C++ source code for @ffi structs and enum classes (testable)
+pub fn parse_cpp_ffi_types_from_string(content: &str, source_file: &Path) -> Result<Vec<CppFfiType>> {
+    let mut types = Vec::new();
+
+    // Pattern: // @ffi followed by `struct Name { ... };` or `enum class Name { ... };`
+    let type_regex = Regex::new(
+        r"(?m)^\s*//\s*@ffi\s*\n\s*(struct|enum\s+class|enum\s+struct)\s+(\w+)\s*\{([^}]*)\}"
+    ).unwrap();
+
+    for cap in type_regex.captures_iter(content) {
+        let keyword = cap.get(1).unwrap().as_str();
+        let name = cap.get(2).unwrap().as_str().to_string();
+        let body = cap.get(3).unwrap().as_str();
+
+        let kind = if keyword == "struct" {
+            CppFfiTypeKind::Struct(parse_struct_fields(body, &name, source_file)?)
+        } else {
+            CppFfiTypeKind::Enum(parse_enumerators(body, &name, source_file)?)
+        };
+
+        types.push(CppFfiType {
+            name,
+            kind,
+            source_file: source_file.to_path_buf(),
+        });
+    }
+
+    Ok(types)
+}
+
+/// Parse struct members: "double x; std::string name;" -> [("x", Double), ("name", String)]
+fn parse_struct_fields(body: &str, struct_name: &str, source_file: &Path) -> Result<Vec<(String, CppType)>> {
+    let mut fields = Vec::new();
+
+    for member in body.split(';') {
+        // Drop line comments and default member initializers
+        let member: String = member
+            .lines()
+            .map(|line| line.split("//").next().unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let member = member.split('=').next().unwrap_or("").trim();
+        if member.is_empty() {
+            continue;
+        }
+
+        if member.contains(',') && !member.contains('<') {
+            bail!(
+                "Member '{}' of struct '{}' at {}: declare one field per line",
+                member, struct_name, source_file.display()
+            );
+        }
+
+        let parts: Vec<&str> = member.split_whitespace().collect();
+        if parts.len() < 2 {
+            bail!(
+                "Invalid member '{}' in struct '{}' at {}. Expected format: 'type name;'",
+                member, struct_name, source_file.display()
+            );
+        }
+
+        let field_name = parts[parts.len() - 1];
+        let field_type = CppType::from_str(&parts[..parts.len() - 1].join(" "))
+            .context(format!(
+                "In field '{}' of struct '{}' at {}",
+                field_name, struct_name, source_file.display()
+            ))?;
+
+        let supported = matches!(
+            field_type,
+            CppType::Int | CppType::Long | CppType::Float | CppType::Double | CppType::Bool
+                | CppType::String | CppType::Custom(_) | CppType::CustomList(_)
+        );
+        if !supported {
+            bail!(
+                "Field '{}' of struct '{}' at {}: struct fields support primitives, std::string, @ffi types and std::vector of @ffi types",
+                field_name, struct_name, source_file.display()
+            );
+        }
+
+        fields.push((field_name.to_string(), field_type));
+    }
+
+    Ok(fields)
+}
+
+/// Parse enum class enumerators: "Red, Green" -> ["Red", "Green"]
+fn parse_enumerators(body: &str, enum_name: &str, source_file: &Path) -> Result<Vec<String>> {
+    let mut enumerators = Vec::new();
+
+    for entry in body.split(',') {
+        let entry: String = entry
+            .lines()
+            .map(|line| line.split("//").next().unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+
+        if entry.contains('=') {
+            bail!(
+                "Enum '{}' at {} cannot use explicit enumerator values ('{}')",
+                enum_name, source_file.display(), entry
+            );
+        }
+
+        enumerators.push(entry.to_string());
+    }
+
+    Ok(enumerators)
+}
+
+/// Check that every struct/enum name used by functions and fields is an @ffi type
+pub fn check_cpp_ffi_types(functions: &[CppFfiFunction], types: &[CppFfiType]) -> Result<()> {
+    let known = |name: &str| types.iter().any(|t| t.name == name);
+
+    for function in functions {
+        let mut used = vec![&function.return_type];
+        for (_, typ) in &function.params {
+            match typ {
+                CppType::Function(callback) => used.extend(callback.params.iter()),
+                _ => used.push(typ),
+            }
+        }
+        for typ in used {
+            if let Some(name) = typ.custom_name() {
+                if !known(name) {
+                    bail!(
+                        "Unknown type '{}' in function '{}' at {}. Mark the struct or enum class with // @ffi",
+                        name, function.name, function.source_file.display()
+                    );
+                }
+            }
+        }
+    }
+
+    // Overloads stay overloads in Kotlin, so they must differ in their Kotlin parameter types
+    let kotlin_params = |function: &CppFfiFunction| {
+        function.params.iter().map(|(_, typ)| typ.to_kotlin_type()).collect::<Vec<_>>()
+    };
+    let callbacks = |function: &CppFfiFunction| {
+        function.params.iter().filter_map(|(_, typ)| match typ {
+            CppType::Function(callback) => Some(callback.as_ref().clone()),
+            _ => None,
+        }).collect::<Vec<_>>()
+    };
+    for (i, function) in functions.iter().enumerate() {
+        for other in functions[..i].iter().filter(|f| f.namespace == function.namespace && f.name == function.name) {
+            if kotlin_params(other) == kotlin_params(function) {
+                bail!(
+                    "Overloads of '{}' in {} and {} have the same Kotlin parameter types ({})",
+                    function.qualified_name(), other.source_file.display(),
+                    function.source_file.display(), kotlin_params(function).join(", ")
+                );
+            }
+            for callback in callbacks(function) {
+                if callbacks(other).iter().any(|c| c.interface_name == callback.interface_name && c.signature != callback.signature) {
+                    bail!(
+                        "Overloads of '{}' in {} and {} take different callbacks named '{}'. Rename one of the parameters",
+                        function.qualified_name(), other.source_file.display(),
+                        function.source_file.display(), callback.interface_name
+                    );
+                }
+            }
+        }
+    }
+
+    for ffi_type in types {
+        if let CppFfiTypeKind::Struct(fields) = &ffi_type.kind {
+            for (_, typ) in fields {
+                if let Some(name) = typ.custom_name() {
+                    if !known(name) {
+                        bail!(
+                            "Unknown type '{}' in struct '{}' at {}. Mark the struct or enum class with // @ffi",
+                            name, ffi_type.name, ffi_type.source_file.display()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -522,4 +1131,248 @@ mod tests {
         assert_eq!(functions[0].params[0].1, CppType::IntArray);
         assert_eq!(functions[0].return_type, CppType::Int);
     }
+
+    #[test]
+    fn test_reference_and_pointer_next_to_name() {
+        let cpp = r#"
+            // @ffi
+            int squares(const std::vector<int> &xs) { return 0; }
+
+            // @ffi
+            int cubes(const std::vector<int>& xs) { return 0; }
+
+            // @ffi
+            int deref(int *p) { return *p; }
+        "#;
+
+        let path = Path::new("test.cpp");
+        let functions = parse_cpp_ffi_from_string(cpp, path).unwrap();
+
+        assert_eq!(functions.len(), 3);
+        assert_eq!(functions[0].params, vec![("xs".to_string(), CppType::IntArray)]);
+        assert_eq!(functions[1].params, vec![("xs".to_string(), CppType::IntArray)]);
+        assert_eq!(functions[2].params, vec![("p".to_string(), CppType::Int)]);
+    }
+
+    #[test]
+    fn test_parse_structs_and_enums() {
+        let cpp = r#"
+            // @ffi
+            struct Point {
+                double x;
+                double y;  // vertical
+            };
+
+            // @ffi
+            struct Polygon {
+                std::string name = "";
+                std::vector<Point> points;
+            };
+
+            // @ffi
+            enum class Color { Red, Green };
+
+            // @ffi
+            Polygon outline(const Polygon& polygon, Color color) {
+                return polygon;
+            }
+        "#;
+
+        let path = Path::new("shapes.cpp");
+        let types = parse_cpp_ffi_types_from_string(cpp, path).unwrap();
+        let functions = parse_cpp_ffi_from_string(cpp, path).unwrap();
+
+        assert_eq!(types.len(), 3);
+        assert_eq!(
+            types[0].kind,
+            CppFfiTypeKind::Struct(vec![
+                ("x".to_string(), CppType::Double),
+                ("y".to_string(), CppType::Double),
+            ])
+        );
+        assert_eq!(
+            types[1].kind,
+            CppFfiTypeKind::Struct(vec![
+                ("name".to_string(), CppType::String),
+                ("points".to_string(), CppType::CustomList("Point".to_string())),
+            ])
+        );
+        assert_eq!(types[2].kind, CppFfiTypeKind::Enum(vec!["Red".to_string(), "Green".to_string()]));
+
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].return_type, CppType::Custom("Polygon".to_string()));
+        assert_eq!(functions[0].params[1].1, CppType::Custom("Color".to_string()));
+        assert!(check_cpp_ffi_types(&functions, &types).is_ok());
+        assert!(check_cpp_ffi_types(&functions, &types[..1]).is_err());
+    }
+
+    #[test]
+    fn test_std_function_params() {
+        let cpp = r#"
+            // @ffi
+            void download(const std::string& url, const std::function<void(int, float)>& on_progress) {
+            }
+        "#;
+
+        let functions = parse_cpp_ffi_from_string(cpp, Path::new("test.cpp")).unwrap();
+
+        assert_eq!(functions[0].params.len(), 2);
+        match &functions[0].params[1].1 {
+            CppType::Function(callback) => {
+                assert_eq!(callback.interface_name, "DownloadOnProgressCallback");
+                assert_eq!(callback.params, vec![CppType::Int, CppType::Float]);
+                assert_eq!(callback.return_type, CppType::Void);
+            }
+            other => panic!("expected std::function, got {:?}", other),
+        }
+        assert_eq!(functions[0].params[1].1.to_cpp_type(), "std::function<void(int, float)>");
+
+        let unsupported = "// @ffi\nvoid f(const std::function<void(std::vector<int>)>& cb) {}";
+        assert!(parse_cpp_ffi_from_string(unsupported, Path::new("test.cpp")).is_err());
+    }
+
+    #[test]
+    fn test_doxygen_deprecated_and_defaults() {
+        let cpp = r#"
+/**
+ * @brief Blur an image.
+ *
+ * \param radius Blur radius in pixels
+ * @return The blurred pixels
+ */
+// @ffi
+std::vector<int> blur(const std::vector<int>& pixels, int radius = 3, double sigma = 1.5, bool fast = true, const std::string& mode = "box") {
+    return pixels;
+}
+
+/// Old API.
+/// @deprecated Use blur instead
+// @ffi
+int legacyBlur(int radius) { return 0; }
+
+/* Not documentation */
+// @ffi
+[[deprecated("use blur")]] int oldBlur(long long size = kDefault) { return 0; }
+"#;
+
+        let functions = parse_cpp_ffi_from_string(cpp, Path::new("test.cpp")).unwrap();
+        assert_eq!(functions.len(), 3);
+
+        let docs = &functions[0].docs;
+        assert_eq!(
+            docs.lines,
+            vec!["Blur an image.", "", "@param radius Blur radius in pixels", "@return The blurred pixels"]
+        );
+        assert_eq!(docs.deprecated, None);
+        assert_eq!(docs.default_for("radius"), Some("3"));
+        assert_eq!(docs.default_for("sigma"), Some("1.5"));
+        assert_eq!(docs.default_for("fast"), Some("true"));
+        assert_eq!(docs.default_for("mode"), Some("\"box\""));
+        assert_eq!(functions[0].params[1], ("radius".to_string(), CppType::Int));
+
+        assert_eq!(functions[1].docs.lines, vec!["Old API."]);
+        assert_eq!(functions[1].docs.deprecated.as_deref(), Some("Use blur instead"));
+
+        // Non-literal defaults are dropped; plain comments aren't docs
+        assert_eq!(functions[2].name, "oldBlur");
+        assert!(functions[2].docs.lines.is_empty());
+        assert_eq!(functions[2].docs.deprecated.as_deref(), Some("use blur"));
+        assert!(functions[2].docs.defaults.is_empty());
+    }
+
+    #[test]
+    fn test_namespaces_and_multiline_signatures() {
+        let cpp = r#"
+#include <string>
+#define BODY { return 0; }
+
+namespace audio {
+namespace dsp {
+    // @ffi
+    double gain(
+        double input,  // linear sample
+        /* decibels */ double db = 0.0
+    ) {
+        const char* brace = "}";
+        return input;
+    }
+}  // namespace dsp
+
+    // @ffi
+    int channels() { return 2; }
+}
+
+namespace geo::shapes {
+    // @ffi
+    double area(double radius) { return radius; }
+
+    // @ffi
+    double area(double width, double height) { return width * height; }
+}
+
+namespace fs = std::filesystem;
+
+// @ffi
+int sumValues(const std::vector<int>& values) { return 0; }
+"#;
+
+        let functions = parse_cpp_ffi_from_string(cpp, Path::new("test.cpp")).unwrap();
+        let names: Vec<_> = functions.iter().map(|f| f.qualified_name()).collect();
+        assert_eq!(
+            names,
+            vec!["audio::dsp::gain", "audio::channels", "geo::shapes::area", "geo::shapes::area", "sumValues"]
+        );
+        assert_eq!(functions[0].kotlin_scope(), vec!["Audio", "Dsp"]);
+        assert_eq!(functions[0].params.len(), 2);
+        assert_eq!(functions[0].docs.default_for("db"), Some("0.0"));
+        assert_eq!(functions[3].params.len(), 2);
+        assert_eq!(functions[4].params[0].1, CppType::IntArray);
+
+        // Overloads must map to distinct Kotlin signatures
+        assert!(check_cpp_ffi_types(&functions, &[]).is_ok());
+        let mut duplicate = functions[2].clone();
+        duplicate.params[0].0 = "r".to_string();
+        let err = check_cpp_ffi_types(&[functions[2].clone(), duplicate], &[]).unwrap_err();
+        assert!(err.to_string().contains("Overloads of 'geo::shapes::area'"));
+    }
+
+    #[test]
+    fn test_declaration_errors_name_file_and_line() {
+        let error = |cpp: &str| parse_cpp_ffi_from_string(cpp, Path::new("ffi/cpp/math.cpp")).unwrap_err().to_string();
+
+        let err = error("int ok();\n\n// @ffi\nunsigned short narrow(int a) { return 0; }\n");
+        assert!(err.contains("ffi/cpp/math.cpp:4"), "{}", err);
+
+        let err = error("// @ffi\nint add(\n    int a,\n    int\n) { return a; }\n");
+        assert!(err.contains("Invalid parameter 'int'") && err.contains("ffi/cpp/math.cpp:2"), "{}", err);
+
+        let err = error("int ok();\n// @ffi\nint first(int 1st) { return 0; }\n");
+        assert!(err.contains("Invalid parameter name '1st'") && err.contains("ffi/cpp/math.cpp:3"), "{}", err);
+
+        let err = error("namespace {\n// @ffi\nint hidden() { return 0; }\n}\n");
+        assert!(err.contains("anonymous namespace") && err.contains("math.cpp:3"), "{}", err);
+
+        let err = error("struct Counter {\n    // @ffi\n    int next() { return 0; }\n};\n");
+        assert!(err.contains("inside a class") && err.contains("math.cpp:3"), "{}", err);
+
+        let err = error("// @ffi\nstatic int helper() { return 0; }\n");
+        assert!(err.contains("external linkage"), "{}", err);
+    }
+
+    #[test]
+    fn test_regex_fallback_for_unbalanced_braces() {
+        // Braces hidden in macros can't be followed by the tokenizer
+        let cpp = r#"
+#define END_API }
+namespace api {
+// @ffi
+int add(int a, int b) { return a + b; }
+END_API
+"#;
+
+        let functions = parse_cpp_ffi_from_string(cpp, Path::new("test.cpp")).unwrap();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name, "add");
+        assert!(functions[0].namespace.is_empty());
+    }
 }
@@ -0,0 +1,384 @@
+//! Tokenizer-based scanner for `// @ffi` function declarations in C++ sources
+//!
+//! Unlike a line regex, the scanner understands comments, string literals,
+//! preprocessor lines and brace scopes, so it knows which namespace a declaration
+//! lives in and can read signatures spread over several lines.
+
+use std::path::Path;
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum TokenKind {
+    Ident,
+    Punct,
+    Literal,
+    Comment,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    /// Byte offset in the source
+    offset: usize,
+    /// 1-based line number
+    line: usize,
+    /// Preceded by whitespace or a comment
+    spaced: bool,
+}
+
+/// An `// @ffi` function declaration, before its types are interpreted
+#[derive(Debug, Clone)]
+pub(super) struct RawDeclaration {
+    pub name: String,
+    /// Enclosing namespaces, outermost first
+    pub namespace: Vec<String>,
+    pub return_type: String,
+    /// Parameter list without the parentheses, comments replaced by spaces
+    pub params: String,
+    /// Message of a `[[deprecated]]` attribute
+    pub deprecated: Option<String>,
+    /// Line of the function name
+    pub line: usize,
+    /// Byte offset of the `// @ffi` marker, where the doc comment ends
+    pub marker: usize,
+}
+
+/// Split C++ source into tokens, skipping whitespace and preprocessor lines
+///
+/// Returns None for source the scanner can't follow: unterminated comments or
+/// literals, or braces that don't balance (e.g. braces hidden in macros).
+pub(super) fn tokenize(content: &str) -> Option<Vec<Token<'_>>> {
+    let bytes = content.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut line = 1;
+    let mut spaced = true;
+    let mut line_start = true;
+    let mut depth = 0usize;
+
+    while i < bytes.len() {
+        let start = i;
+        let start_line = line;
+        let kind = match bytes[i] {
+            b'\n' => {
+                line += 1;
+                i += 1;
+                spaced = true;
+                line_start = true;
+                continue;
+            }
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                spaced = true;
+                continue;
+            }
+            b'#' if line_start => {
+                // Preprocessor directive, including `\` continuation lines
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'\n') {
+                        line += 1;
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                TokenKind::Comment
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = start + 2 + content[start + 2..].find("*/")? + 2;
+                TokenKind::Comment
+            }
+            b'"' | b'\'' => {
+                i = skip_quoted(bytes, i)?;
+                TokenKind::Literal
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                match (&content[start..i], bytes.get(i)) {
+                    ("R" | "u8R" | "uR" | "UR" | "LR", Some(b'"')) => {
+                        i = skip_raw_string(content, i)?;
+                        TokenKind::Literal
+                    }
+                    ("u8" | "u" | "U" | "L", Some(b'"' | b'\'')) => {
+                        i = skip_quoted(bytes, i)?;
+                        TokenKind::Literal
+                    }
+                    _ => TokenKind::Ident,
+                }
+            }
+            c if c.is_ascii_digit() => {
+                while i < bytes.len() {
+                    let c = bytes[i];
+                    let exponent_sign = (c == b'+' || c == b'-') && matches!(bytes[i - 1], b'e' | b'E' | b'p' | b'P');
+                    let separator = c == b'\'' && bytes.get(i + 1).is_some_and(u8::is_ascii_alphanumeric);
+                    if !(c.is_ascii_alphanumeric() || c == b'.' || c == b'_' || exponent_sign || separator) {
+                        break;
+                    }
+                    i += 1;
+                }
+                TokenKind::Literal
+            }
+            b':' if bytes.get(i + 1) == Some(&b':') => {
+                i += 2;
+                TokenKind::Punct
+            }
+            _ => {
+                match bytes[i] {
+                    b'{' => depth += 1,
+                    b'}' => depth = depth.checked_sub(1)?,
+                    _ => {}
+                }
+                i += content[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Punct
+            }
+        };
+
+        let text = &content[start..i];
+        line += text.matches('\n').count();
+        tokens.push(Token { kind, text, offset: start, line: start_line, spaced });
+        spaced = kind == TokenKind::Comment;
+        line_start = false;
+    }
+
+    (depth == 0).then_some(tokens)
+}
+
+/// End of a string or character literal starting at `start`
+fn skip_quoted(bytes: &[u8], start: usize) -> Option<usize> {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' => return None,
+            c if c == quote => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// End of a raw string literal `"delim( ... )delim"` starting at `start`
+fn skip_raw_string(content: &str, start: usize) -> Option<usize> {
+    let open = start + content[start..].find('(')?;
+    let terminator = format!("){}\"", &content[start + 1..open]);
+    Some(open + content[open..].find(&terminator)? + terminator.len())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Scope {
+    /// `namespace a::b {`, or an `extern "C" {` block with no name
+    Namespace(Vec<String>),
+    /// `namespace {`: internal linkage
+    Anonymous,
+    /// Class, function or initializer body
+    Block,
+}
+
+/// Find the `// @ffi` function declarations among `tokens`
+///
+/// Structs and enums marked `// @ffi` are skipped; they're read by the type parser.
+pub(super) fn scan_declarations(tokens: &[Token], source_file: &Path) -> Result<Vec<RawDeclaration>> {
+    let mut declarations = Vec::new();
+    let mut scopes = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+        match (token.kind, token.text) {
+            (TokenKind::Comment, text) if is_ffi_marker(text) => {
+                if let Some(declaration) = parse_declaration(tokens, i, &scopes, source_file)? {
+                    declarations.push(declaration);
+                }
+            }
+            (TokenKind::Ident, "namespace") => {
+                let mut names = Vec::new();
+                let mut j = next_code(tokens, i + 1);
+                while let Some(t) = tokens.get(j).filter(|t| t.kind == TokenKind::Ident || t.text == "::") {
+                    if t.kind == TokenKind::Ident && t.text != "inline" {
+                        names.push(t.text.to_string());
+                    }
+                    j = next_code(tokens, j + 1);
+                }
+                // Anything but `{` is a namespace alias (`namespace fs = std::filesystem;`)
+                if tokens.get(j).is_some_and(|t| t.text == "{") {
+                    scopes.push(if names.is_empty() { Scope::Anonymous } else { Scope::Namespace(names) });
+                    i = j + 1;
+                    continue;
+                }
+            }
+            (TokenKind::Ident, "extern") => {
+                // Linkage specification block: `extern "C" {`
+                let literal = next_code(tokens, i + 1);
+                let brace = next_code(tokens, literal + 1);
+                if tokens.get(literal).is_some_and(|t| t.kind == TokenKind::Literal)
+                    && tokens.get(brace).is_some_and(|t| t.text == "{")
+                {
+                    scopes.push(Scope::Namespace(Vec::new()));
+                    i = brace + 1;
+                    continue;
+                }
+            }
+            (TokenKind::Punct, "{") => scopes.push(Scope::Block),
+            (TokenKind::Punct, "}") => {
+                scopes.pop();
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    Ok(declarations)
+}
+
+/// `// @ffi` on a line of its own
+fn is_ffi_marker(comment: &str) -> bool {
+    comment.strip_prefix("//").is_some_and(|rest| rest.trim() == "@ffi")
+}
+
+/// Index of the first non-comment token at or after `from`
+fn next_code(tokens: &[Token], from: usize) -> usize {
+    (from..tokens.len())
+        .find(|&i| tokens[i].kind != TokenKind::Comment)
+        .unwrap_or(tokens.len())
+}
+
+/// Read the function declaration following the `// @ffi` marker at `marker`
+fn parse_declaration(
+    tokens: &[Token],
+    marker: usize,
+    scopes: &[Scope],
+    source_file: &Path,
+) -> Result<Option<RawDeclaration>> {
+    let marker_token = &tokens[marker];
+    let location = |line: usize| format!("{}:{}", source_file.display(), line);
+
+    // Code tokens up to the body or the terminating ';'
+    let mut decl = Vec::new();
+    let mut depth = 0;
+    for token in tokens[marker + 1..].iter().filter(|t| t.kind != TokenKind::Comment) {
+        match token.text {
+            "(" => depth += 1,
+            ")" => depth -= 1,
+            "{" | ";" if depth == 0 => break,
+            _ => {}
+        }
+        decl.push(token);
+    }
+
+    match decl.first().map(|t| t.text) {
+        None => bail!("Expected a function declaration after // @ffi at {}", location(marker_token.line)),
+        Some("struct" | "enum" | "class" | "union") => return Ok(None),
+        Some(_) => {}
+    }
+
+    // Leading attributes: [[nodiscard]], [[deprecated("message")]]
+    let mut deprecated = None;
+    let mut k = 0;
+    while decl.get(k).is_some_and(|t| t.text == "[") && decl.get(k + 1).is_some_and(|t| t.text == "[") {
+        let Some(end) = (k + 2..decl.len()).find(|&j| decl[j].text == "]") else {
+            bail!("Unterminated attribute at {}", location(decl[k].line));
+        };
+        if decl[k + 2..end].first().is_some_and(|t| t.text == "deprecated") {
+            let message = decl[k + 2..end]
+                .iter()
+                .find(|t| t.kind == TokenKind::Literal && t.text.starts_with('"'))
+                .map(|t| t.text[1..t.text.len() - 1].to_string());
+            deprecated = Some(message.unwrap_or_else(|| "Deprecated".to_string()));
+        }
+        k = end + 2;
+    }
+
+    let Some(open) = (k..decl.len()).find(|&j| decl[j].text == "(") else {
+        bail!("Expected a function declaration after // @ffi at {}", location(marker_token.line));
+    };
+    let name_token = match open.checked_sub(1).map(|j| decl[j]) {
+        Some(token) if token.kind == TokenKind::Ident && open > k => token,
+        _ => bail!("Expected a function name before '(' at {}", location(decl[open].line)),
+    };
+    let name = name_token.text.to_string();
+    let here = location(name_token.line);
+
+    if open >= 2 && decl[open - 2].text == "::" {
+        bail!(
+            "Qualified name in @ffi function '{}' at {}. Declare it inside its namespace instead",
+            name, here
+        );
+    }
+
+    let mut specifiers = k;
+    while specifiers < open - 1 {
+        match decl[specifiers].text {
+            "extern" => specifiers += 1,
+            keyword @ ("static" | "inline" | "constexpr" | "consteval") => bail!(
+                "@ffi function '{}' at {} is {}, but the JNI bridge needs a function with external linkage",
+                name, here, keyword
+            ),
+            _ => break,
+        }
+    }
+    if specifiers == open - 1 {
+        bail!("Missing return type in function '{}' at {}", name, here);
+    }
+
+    if scopes.contains(&Scope::Block) {
+        bail!(
+            "@ffi function '{}' at {} is inside a class or function body. Only free functions are supported",
+            name, here
+        );
+    }
+    if scopes.contains(&Scope::Anonymous) {
+        bail!(
+            "@ffi function '{}' at {} is in an anonymous namespace, so the JNI bridge can't call it",
+            name, here
+        );
+    }
+
+    let mut depth = 0;
+    let Some(close) = (open..decl.len()).find(|&j| {
+        match decl[j].text {
+            "(" => depth += 1,
+            ")" => depth -= 1,
+            _ => {}
+        }
+        depth == 0
+    }) else {
+        bail!("Unterminated parameter list in function '{}' at {}", name, here);
+    };
+
+    Ok(Some(RawDeclaration {
+        name,
+        namespace: scopes
+            .iter()
+            .flat_map(|scope| match scope {
+                Scope::Namespace(names) => names.clone(),
+                _ => Vec::new(),
+            })
+            .collect(),
+        return_type: join_tokens(&decl[specifiers..open - 1]),
+        params: join_tokens(&decl[open + 1..close]),
+        deprecated,
+        line: name_token.line,
+        marker: marker_token.offset,
+    }))
+}
+
+/// Source text for a run of tokens, with whitespace (and comments) collapsed to single spaces
+fn join_tokens(tokens: &[&Token]) -> String {
+    let mut text = String::new();
+    for token in tokens {
+        if token.spaced && !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(token.text);
+    }
+    text
+}
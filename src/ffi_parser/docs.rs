@@ -0,0 +1,79 @@
+/// Documentation carried from native declarations into the generated Kotlin API
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FfiDocs {
+    /// Doc comment lines, without comment markers (KDoc tags such as `@param` kept as-is)
+    pub lines: Vec<String>,
+    /// Message of a `#[deprecated]` / `[[deprecated]]` attribute
+    pub deprecated: Option<String>,
+    /// Kotlin default value expressions, by parameter name
+    pub defaults: Vec<(String, String)>,
+}
+
+impl FfiDocs {
+    /// Kotlin default value for a parameter, if one was declared
+    pub fn default_for(&self, param: &str) -> Option<&str> {
+        self.defaults
+            .iter()
+            .find(|(name, _)| name == param)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A literal default argument, before it is checked against the parameter type
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefaultLiteral {
+    /// Integer digits, with a leading '-' if negative
+    Int(String),
+    /// Decimal digits, with a leading '-' if negative
+    Float(String),
+    Bool(bool),
+    Str(String),
+    /// `None`, `nullptr` or `std::nullopt`
+    Null,
+}
+
+/// Kotlin expression for `literal` as a value of `kotlin_type`, or None if it doesn't fit
+pub fn kotlin_default(literal: &DefaultLiteral, kotlin_type: &str) -> Option<String> {
+    if let Some(inner) = kotlin_type.strip_suffix('?') {
+        return match literal {
+            DefaultLiteral::Null => Some("null".to_string()),
+            other => kotlin_default(other, inner),
+        };
+    }
+
+    match (literal, kotlin_type) {
+        (DefaultLiteral::Int(digits), "Int") => Some(digits.clone()),
+        (DefaultLiteral::Int(digits), "Long") => Some(format!("{}L", digits)),
+        (DefaultLiteral::Int(digits), "Float") => Some(format!("{}f", digits)),
+        (DefaultLiteral::Int(digits), "Double") => Some(format!("{}.0", digits)),
+        (DefaultLiteral::Float(digits), "Float") => Some(format!("{}f", digits)),
+        (DefaultLiteral::Float(digits), "Double") => Some(digits.clone()),
+        (DefaultLiteral::Bool(value), "Boolean") => Some(value.to_string()),
+        (DefaultLiteral::Str(value), "String") => Some(kotlin_string_literal(value)),
+        _ => None,
+    }
+}
+
+/// Quote a string for Kotlin source, escaping `$` so it isn't a template
+pub fn kotlin_string_literal(value: &str) -> String {
+    let mut literal = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '$' => literal.push_str("\\$"),
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            other => literal.push(other),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Drop leading/trailing blank lines from a doc comment
+pub fn trim_doc_lines(lines: Vec<String>) -> Vec<String> {
+    let start = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.trim().is_empty()).map_or(start, |i| i + 1);
+    lines[start..end].to_vec()
+}
@@ -1,5 +1,11 @@
+pub mod c;
 pub mod cpp;
+mod cpp_decl;
+pub mod docs;
 pub mod rust;
+pub mod zig;
 
-pub use cpp::{CppFfiFunction, CppType, discover_cpp_ffi};
-pub use rust::{RustFfiFunction, RustType, discover_rust_ffi};
+pub use c::{discover_c_ffi, c_source_files};
+pub use cpp::{CppFfiFunction, CppFfiType, CppType, discover_cpp_ffi, discover_cpp_ffi_types};
+pub use rust::{RustFfiClass, RustFfiFunction, RustFfiType, RustType, discover_rust_ffi, discover_rust_ffi_classes, discover_rust_ffi_types};
+pub use zig::{discover_zig_ffi, zig_source_files};
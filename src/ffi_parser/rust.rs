@@ -1,7 +1,8 @@
 use std::path::{Path, PathBuf};
 use std::fs;
 use anyhow::{Result, Context, bail};
-use syn::{self, Item, ItemFn, FnArg, Pat, Type, ReturnType, Attribute};
+use super::docs::{kotlin_default, trim_doc_lines, DefaultLiteral, FfiDocs};
+use syn::{self, Item, ItemFn, ItemImpl, ItemStruct, ItemEnum, ItemTrait, Fields, FnArg, Pat, Type, ReturnType, Attribute};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum RustType {
@@ -19,6 +20,65 @@ pub enum RustType {
     DoubleArray,
     BoolArray,
     StringArray,
+    /// An `#[ffi]` struct or enum, by name
+    Custom(String),
+    /// `Vec<T>` of an `#[ffi]` struct or enum (Kotlin `List<T>`)
+    CustomList(String),
+    /// `Option<T>` (Kotlin `T?`, primitives boxed)
+    Option(Box<RustType>),
+    /// `HashMap<String, T>` (Kotlin `Map<String, T>`)
+    Map(Box<RustType>),
+    /// `Vec<u8>` (Kotlin `ByteArray`, copied)
+    Bytes,
+    /// `&[u8]` parameter (Kotlin `ByteArray`, copied then borrowed)
+    ByteSlice,
+    /// `&mut [u8]` parameter (Kotlin direct `java.nio.ByteBuffer`, zero-copy)
+    ByteBuffer,
+    /// `&AtomicBool` parameter of an `#[ffi(async)]` function, set when the coroutine is cancelled
+    CancelFlag,
+    /// `impl Fn(A, ..) -> R` parameter (Kotlin `fun interface`)
+    Closure(Box<RustClosure>),
+    /// `impl Trait`, `&dyn Trait` or `Box<dyn Trait>` of an `#[ffi_callback]` trait
+    Callback(String, CallbackPassing),
+    /// `Self` returned from an `#[ffi] impl` method (Kotlin class wrapping a `Long` handle)
+    Handle(String),
+}
+
+/// How a callback parameter is handed to the user's function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CallbackPassing {
+    /// `impl Fn(..)` / `impl Trait`
+    Impl,
+    /// `&dyn Fn(..)` / `&dyn Trait`
+    Ref,
+    /// `Box<dyn Fn(..)>` / `Box<dyn Trait>`
+    Boxed,
+}
+
+/// Signature of an `impl Fn(..)` parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct RustClosure {
+    /// Name of the generated Kotlin `fun interface`, e.g. `DownloadOnProgressCallback`
+    pub interface_name: String,
+    pub params: Vec<RustType>,
+    pub return_type: RustType,
+    pub passing: CallbackPassing,
+}
+
+impl RustClosure {
+    /// Kotlin parameter names for the closure arguments: `value` (or `value0`, `value1`, ...)
+    pub fn param_names(&self) -> Vec<String> {
+        callback_param_names(self.params.len())
+    }
+}
+
+/// Positional names, matching the names given to tuple fields
+pub fn callback_param_names(count: usize) -> Vec<String> {
+    if count == 1 {
+        vec!["value".to_string()]
+    } else {
+        (0..count).map(|i| format!("value{}", i)).collect()
+    }
 }
 
 /// Wrapper that indicates whether a type is a Result<T, E>
@@ -44,7 +104,7 @@ impl RustFunctionReturn {
     }
 
     /// Get the Kotlin type (same whether Result or not)
-    pub fn to_kotlin_type(&self) -> &'static str {
+    pub fn to_kotlin_type(&self) -> String {
         self.base_type.to_kotlin_type()
     }
 
@@ -58,7 +118,7 @@ impl RustFunctionReturn {
         if self.is_result {
             format!("Result<{}, String>", self.base_type.to_rust_type())
         } else {
-            self.base_type.to_rust_type().to_string()
+            self.base_type.to_rust_type()
         }
     }
 }
@@ -87,6 +147,34 @@ impl RustType {
                     bail!("Vec must have a type parameter");
                 }
 
+                // Option<T> and HashMap<String, T>
+                if type_name == "Option" {
+                    let inner = Self::generic_args(last_segment, 1)?;
+                    let inner = Self::from_syn_type(inner[0])?;
+                    if !inner.is_boxable() {
+                        bail!("Unsupported Option type: Option<{}>", inner.to_rust_type());
+                    }
+                    return Ok(RustType::Option(Box::new(inner)));
+                }
+                if type_name == "Box" {
+                    let inner = Self::generic_args(last_segment, 1)?;
+                    if let Type::TraitObject(object) = inner[0] {
+                        return Self::from_callback_bounds(&object.bounds, CallbackPassing::Boxed);
+                    }
+                    bail!("Unsupported Box type. Only Box<dyn Fn(..)> and Box<dyn Trait> callbacks are supported");
+                }
+                if type_name == "HashMap" {
+                    let args = Self::generic_args(last_segment, 2)?;
+                    if Self::from_syn_type(args[0])? != RustType::String {
+                        bail!("HashMap keys must be String");
+                    }
+                    let value = Self::from_syn_type(args[1])?;
+                    if !value.is_boxable() && !matches!(value, RustType::Option(_)) {
+                        bail!("Unsupported HashMap value type: {}", value.to_rust_type());
+                    }
+                    return Ok(RustType::Map(Box::new(value)));
+                }
+
                 // Simple types
                 match type_name.as_str() {
                     "i32" => Ok(RustType::Int),
@@ -95,7 +183,8 @@ impl RustType {
                     "f64" => Ok(RustType::Double),
                     "bool" => Ok(RustType::Bool),
                     "String" => Ok(RustType::String),
-                    _ => bail!("Unsupported Rust type: '{}'. Phase 4 supports: i32, i64, f32, f64, bool, String, Vec<T>", type_name),
+                    _ if is_custom_type_name(&type_name) => Ok(RustType::Custom(type_name)),
+                    _ => bail!("Unsupported Rust type: '{}'. Phase 4 supports: i32, i64, f32, f64, bool, String, Vec<T> and #[ffi] structs/enums", type_name),
                 }
             }
             Type::Tuple(tuple) => {
@@ -105,10 +194,117 @@ impl RustType {
                     bail!("Tuple types not supported (except unit type ())")
                 }
             }
+            // Byte slices: &[u8] (copied) and &mut [u8] (direct ByteBuffer)
+            Type::ImplTrait(impl_trait) => {
+                Self::from_callback_bounds(&impl_trait.bounds, CallbackPassing::Impl)
+            }
+            Type::Reference(reference) if matches!(&*reference.elem, Type::TraitObject(_)) => {
+                if reference.mutability.is_some() {
+                    bail!("Callbacks must be taken as &dyn, not &mut dyn");
+                }
+                match &*reference.elem {
+                    Type::TraitObject(object) => Self::from_callback_bounds(&object.bounds, CallbackPassing::Ref),
+                    _ => unreachable!(),
+                }
+            }
+            Type::Reference(reference) => {
+                let is_u8_slice = match &*reference.elem {
+                    Type::Slice(slice) => matches!(&*slice.elem, Type::Path(p) if p.path.is_ident("u8")),
+                    _ => false,
+                };
+                let is_atomic_bool = match &*reference.elem {
+                    Type::Path(p) => p.path.segments.last().is_some_and(|s| s.ident == "AtomicBool"),
+                    _ => false,
+                };
+                match (is_u8_slice, is_atomic_bool, reference.mutability.is_some()) {
+                    (true, _, false) => Ok(RustType::ByteSlice),
+                    (true, _, true) => Ok(RustType::ByteBuffer),
+                    (false, true, false) => Ok(RustType::CancelFlag),
+                    _ => bail!("Unsupported reference type. Only &[u8], &mut [u8] and &AtomicBool are supported"),
+                }
+            }
             _ => bail!("Unsupported Rust type structure"),
         }
     }
 
+    /// Parse `Fn(A, ..) -> R` or an `#[ffi_callback]` trait from `impl`/`dyn` bounds
+    ///
+    /// Marker bounds such as `Send`, `Sync` and `'static` are ignored.
+    fn from_callback_bounds(
+        bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+        passing: CallbackPassing,
+    ) -> Result<Self> {
+        let traits = bounds.iter().filter_map(|bound| match bound {
+            syn::TypeParamBound::Trait(bound) => bound.path.segments.last(),
+            _ => None,
+        });
+
+        for segment in traits {
+            let name = segment.ident.to_string();
+            match name.as_str() {
+                "Send" | "Sync" | "Unpin" => continue,
+                "Fn" | "FnMut" => {
+                    let syn::PathArguments::Parenthesized(args) = &segment.arguments else {
+                        bail!("{} callbacks must have a signature, e.g. {}(i32)", name, name);
+                    };
+                    let params = args
+                        .inputs
+                        .iter()
+                        .map(Self::from_syn_type)
+                        .collect::<Result<Vec<_>>>()?;
+                    let return_type = match &args.output {
+                        ReturnType::Default => RustType::Void,
+                        ReturnType::Type(_, ty) => Self::from_syn_type(ty)?,
+                    };
+                    check_callback_signature(&params, &return_type)?;
+                    return Ok(RustType::Closure(Box::new(RustClosure {
+                        interface_name: String::new(),
+                        params,
+                        return_type,
+                        passing,
+                    })));
+                }
+                "FnOnce" => bail!("FnOnce callbacks are not supported, use Fn"),
+                _ => return Ok(RustType::Callback(name, passing)),
+            }
+        }
+
+        bail!("Unsupported trait bound. Callbacks must be Fn(..) or an #[ffi_callback] trait")
+    }
+
+    /// Get the type arguments of `Name<A, B, ...>`, requiring exactly `count`
+    fn generic_args(segment: &syn::PathSegment, count: usize) -> Result<Vec<&Type>> {
+        let mut types = Vec::new();
+        if let syn::PathArguments::AngleBracketed(ref args) = segment.arguments {
+            for arg in &args.args {
+                if let syn::GenericArgument::Type(ty) = arg {
+                    types.push(ty);
+                }
+            }
+        }
+        if types.len() != count {
+            bail!("{} must have {} type parameter(s)", segment.ident, count);
+        }
+        Ok(types)
+    }
+
+    /// Types that can cross the boundary as a single Java object (Option/Map contents)
+    pub fn is_boxable(&self) -> bool {
+        matches!(
+            self,
+            RustType::Int
+                | RustType::Long
+                | RustType::Float
+                | RustType::Double
+                | RustType::Bool
+                | RustType::String
+                | RustType::Custom(_)
+                | RustType::CustomList(_)
+                | RustType::Bytes
+                | RustType::Map(_)
+        )
+    }
+
     /// Get the RustType for a Vec element type
     fn vec_element_type(ty: &Type) -> Result<Self> {
         match ty {
@@ -128,7 +324,9 @@ impl RustType {
                     "f64" => Ok(RustType::DoubleArray),
                     "bool" => Ok(RustType::BoolArray),
                     "String" => Ok(RustType::StringArray),
-                    _ => bail!("Unsupported Vec element type: '{}'. Supported: i32, i64, f32, f64, bool, String", type_name),
+                    "u8" => Ok(RustType::Bytes),
+                    _ if is_custom_type_name(&type_name) => Ok(RustType::CustomList(type_name)),
+                    _ => bail!("Unsupported Vec element type: '{}'. Supported: i32, i64, f32, f64, bool, String and #[ffi] structs/enums", type_name),
                 }
             }
             _ => bail!("Unsupported Vec element type structure"),
@@ -151,46 +349,114 @@ impl RustType {
             RustType::DoubleArray => "jdoubleArray",
             RustType::BoolArray => "jbooleanArray",
             RustType::StringArray => "jobjectArray",
+            RustType::Custom(_) | RustType::CustomList(_) => "jobject",
+            RustType::Option(_) | RustType::Map(_) | RustType::ByteBuffer | RustType::CancelFlag
+            | RustType::Closure(_) | RustType::Callback(..) => "jobject",
+            RustType::Handle(_) => "jlong",
+            RustType::Bytes | RustType::ByteSlice => "jbyteArray",
         }
     }
 
     /// Get the corresponding Kotlin type name
-    pub fn to_kotlin_type(&self) -> &'static str {
+    pub fn to_kotlin_type(&self) -> String {
         match self {
-            RustType::Void => "Unit",
-            RustType::Int => "Int",
-            RustType::Long => "Long",
-            RustType::Float => "Float",
-            RustType::Double => "Double",
-            RustType::Bool => "Boolean",
-            RustType::String => "String",
-            RustType::IntArray => "IntArray",
-            RustType::LongArray => "LongArray",
-            RustType::FloatArray => "FloatArray",
-            RustType::DoubleArray => "DoubleArray",
-            RustType::BoolArray => "BooleanArray",
-            RustType::StringArray => "Array<String>",
+            RustType::Void => "Unit".to_string(),
+            RustType::Int => "Int".to_string(),
+            RustType::Long => "Long".to_string(),
+            RustType::Float => "Float".to_string(),
+            RustType::Double => "Double".to_string(),
+            RustType::Bool => "Boolean".to_string(),
+            RustType::String => "String".to_string(),
+            RustType::IntArray => "IntArray".to_string(),
+            RustType::LongArray => "LongArray".to_string(),
+            RustType::FloatArray => "FloatArray".to_string(),
+            RustType::DoubleArray => "DoubleArray".to_string(),
+            RustType::BoolArray => "BooleanArray".to_string(),
+            RustType::StringArray => "Array<String>".to_string(),
+            RustType::Custom(name) => name.clone(),
+            RustType::CustomList(name) => format!("List<{}>", name),
+            RustType::Option(inner) => format!("{}?", inner.to_kotlin_type()),
+            RustType::Map(value) => format!("Map<String, {}>", value.to_kotlin_type()),
+            RustType::Bytes | RustType::ByteSlice => "ByteArray".to_string(),
+            RustType::ByteBuffer | RustType::CancelFlag => "java.nio.ByteBuffer".to_string(),
+            RustType::Closure(closure) => closure.interface_name.clone(),
+            RustType::Callback(name, _) | RustType::Handle(name) => name.clone(),
         }
     }
 
     /// Get the Rust type string for forward declarations
-    pub fn to_rust_type(&self) -> &'static str {
+    pub fn to_rust_type(&self) -> String {
         match self {
-            RustType::Void => "()",
-            RustType::Int => "i32",
-            RustType::Long => "i64",
-            RustType::Float => "f32",
-            RustType::Double => "f64",
-            RustType::Bool => "bool",
-            RustType::String => "String",
-            RustType::IntArray => "Vec<i32>",
-            RustType::LongArray => "Vec<i64>",
-            RustType::FloatArray => "Vec<f32>",
-            RustType::DoubleArray => "Vec<f64>",
-            RustType::BoolArray => "Vec<bool>",
-            RustType::StringArray => "Vec<String>",
+            RustType::Void => "()".to_string(),
+            RustType::Int => "i32".to_string(),
+            RustType::Long => "i64".to_string(),
+            RustType::Float => "f32".to_string(),
+            RustType::Double => "f64".to_string(),
+            RustType::Bool => "bool".to_string(),
+            RustType::String => "String".to_string(),
+            RustType::IntArray => "Vec<i32>".to_string(),
+            RustType::LongArray => "Vec<i64>".to_string(),
+            RustType::FloatArray => "Vec<f32>".to_string(),
+            RustType::DoubleArray => "Vec<f64>".to_string(),
+            RustType::BoolArray => "Vec<bool>".to_string(),
+            RustType::StringArray => "Vec<String>".to_string(),
+            RustType::Custom(name) => name.clone(),
+            RustType::CustomList(name) => format!("Vec<{}>", name),
+            RustType::Option(inner) => format!("Option<{}>", inner.to_rust_type()),
+            RustType::Map(value) => format!("HashMap<String, {}>", value.to_rust_type()),
+            RustType::Bytes => "Vec<u8>".to_string(),
+            RustType::ByteSlice => "&[u8]".to_string(),
+            RustType::ByteBuffer => "&mut [u8]".to_string(),
+            RustType::CancelFlag => "&std::sync::atomic::AtomicBool".to_string(),
+            RustType::Closure(closure) => {
+                let params = closure.params.iter().map(|t| t.to_rust_type()).collect::<Vec<_>>().join(", ");
+                match closure.return_type {
+                    RustType::Void => format!("impl Fn({})", params),
+                    ref ret => format!("impl Fn({}) -> {}", params, ret.to_rust_type()),
+                }
+            }
+            RustType::Callback(name, _) => format!("impl {}", name),
+            RustType::Handle(name) => name.clone(),
         }
     }
+
+    /// Name of the `#[ffi]` struct/enum this type refers to, if any
+    pub fn custom_name(&self) -> Option<&str> {
+        match self {
+            RustType::Custom(name) | RustType::CustomList(name) => Some(name),
+            RustType::Option(inner) | RustType::Map(inner) => inner.custom_name(),
+            _ => None,
+        }
+    }
+}
+
+/// Callback arguments must be convertible to Java objects (or primitives); results are
+/// limited to primitives and String so a failed call can fall back to a default value
+fn check_callback_signature(params: &[RustType], return_type: &RustType) -> Result<()> {
+    for param in params {
+        if !param.is_boxable() && !matches!(param, RustType::Option(_)) {
+            bail!(
+                "Unsupported callback argument type: {}. Callbacks support primitives, String, Vec<u8>, Option, HashMap and #[ffi] types",
+                param.to_rust_type()
+            );
+        }
+    }
+    if !matches!(
+        return_type,
+        RustType::Void | RustType::Int | RustType::Long | RustType::Float | RustType::Double | RustType::Bool | RustType::String
+    ) {
+        bail!(
+            "Unsupported callback return type: {}. Callbacks can return primitives, String or nothing",
+            return_type.to_rust_type()
+        );
+    }
+    Ok(())
+}
+
+/// Type names starting with an uppercase letter may refer to `#[ffi]` structs/enums;
+/// check_rust_ffi_types() verifies they actually exist once everything is discovered
+fn is_custom_type_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_uppercase())
 }
 
 #[derive(Debug, Clone)]
@@ -198,18 +464,158 @@ pub struct RustFfiFunction {
     pub name: String,
     pub params: Vec<(String, RustType)>,
     pub return_type: RustFunctionReturn,  // Phase 5: Support Result<T, E>
+    /// `#[ffi(async)]`: exposed to Kotlin as a `suspend fun`
+    pub is_async: bool,
+    /// Doc comment, `#[deprecated]` and `#[ffi(default(..))]` values for the Kotlin API
+    pub docs: FfiDocs,
     pub source_file: PathBuf,
 }
 
+impl RustFfiFunction {
+    /// Parameters visible to Kotlin callers (the cancellation flag is supplied by the wrapper)
+    pub fn kotlin_params(&self) -> impl Iterator<Item = &(String, RustType)> {
+        self.params.iter().filter(|(_, typ)| *typ != RustType::CancelFlag)
+    }
+}
+
+/// An `#[ffi] impl` block, exposed to Kotlin as a class wrapping an opaque native handle
+#[derive(Debug, Clone)]
+pub struct RustFfiClass {
+    pub name: String,
+    pub methods: Vec<RustFfiMethod>,
+    /// Doc comment and `#[deprecated]` of the impl block (defaults are per method)
+    pub docs: FfiDocs,
+    pub source_file: PathBuf,
+}
+
+/// A method of an `#[ffi] impl` block
+#[derive(Debug, Clone)]
+pub struct RustFfiMethod {
+    pub receiver: MethodReceiver,
+    /// Signature without the receiver; returning `Self` is `RustType::Handle`
+    pub function: RustFfiFunction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MethodReceiver {
+    /// Associated function (`fn open(path: String) -> Self`), a companion function in Kotlin
+    Static,
+    /// `&self`
+    Ref,
+    /// `&mut self`
+    Mut,
+}
+
+/// An `#[ffi]` struct or enum, mirrored as a Kotlin data class, sealed class or enum class
+#[derive(Debug, Clone)]
+pub struct RustFfiType {
+    pub name: String,
+    pub kind: RustFfiTypeKind,
+    pub source_file: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RustFfiTypeKind {
+    /// `struct Point { x: f64, y: f64 }`
+    Struct(Vec<(String, RustType)>),
+    /// `enum Shape { Circle { radius: f64 }, Empty }`
+    Enum(Vec<RustFfiVariant>),
+    /// `#[ffi_callback] trait Listener { fn on_event(&self, ..); }`, implemented in Kotlin
+    Callback(Vec<RustCallbackMethod>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RustCallbackMethod {
+    pub name: String,
+    pub params: Vec<(String, RustType)>,
+    pub return_type: RustType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RustFfiVariant {
+    pub name: String,
+    /// Named fields; tuple fields are named `value` (or `value0`, `value1`, ...)
+    pub fields: Vec<(String, RustType)>,
+    pub is_tuple: bool,
+}
+
+impl RustFfiType {
+    /// True for enums without any fields, which map to a Kotlin `enum class`
+    pub fn is_unit_enum(&self) -> bool {
+        match &self.kind {
+            RustFfiTypeKind::Enum(variants) => variants.iter().all(|v| v.fields.is_empty()),
+            RustFfiTypeKind::Struct(_) | RustFfiTypeKind::Callback(_) => false,
+        }
+    }
+
+    /// True for `#[ffi_callback]` traits, which have no value conversions
+    pub fn is_callback(&self) -> bool {
+        matches!(self.kind, RustFfiTypeKind::Callback(_))
+    }
+
+    /// All field types, across variants for enums (method parameters for callbacks)
+    pub fn field_types(&self) -> Vec<&RustType> {
+        match &self.kind {
+            RustFfiTypeKind::Struct(fields) => fields.iter().map(|(_, t)| t).collect(),
+            RustFfiTypeKind::Enum(variants) => variants
+                .iter()
+                .flat_map(|v| v.fields.iter().map(|(_, t)| t))
+                .collect(),
+            RustFfiTypeKind::Callback(methods) => methods
+                .iter()
+                .flat_map(|m| m.params.iter().map(|(_, t)| t))
+                .collect(),
+        }
+    }
+}
+
 /// Discover all #[ffi] annotated functions in Rust files
 pub fn discover_rust_ffi(ffi_dir: &Path) -> Result<Vec<RustFfiFunction>> {
+    let mut functions = Vec::new();
+
+    for path in rust_source_files(ffi_dir)? {
+        let file_functions = parse_rust_file(&path)?;
+        functions.extend(file_functions);
+    }
+
+    Ok(functions)
+}
+
+/// Discover all #[ffi] annotated structs and enums in Rust files
+pub fn discover_rust_ffi_types(ffi_dir: &Path) -> Result<Vec<RustFfiType>> {
+    let mut types = Vec::new();
+
+    for path in rust_source_files(ffi_dir)? {
+        let content = fs::read_to_string(&path)
+            .context(format!("Failed to read file: {}", path.display()))?;
+        types.extend(parse_rust_ffi_types_from_string(&content, &path)?);
+    }
+
+    Ok(types)
+}
+
+/// Discover all #[ffi] impl blocks in Rust files
+pub fn discover_rust_ffi_classes(ffi_dir: &Path) -> Result<Vec<RustFfiClass>> {
+    let mut classes = Vec::new();
+
+    for path in rust_source_files(ffi_dir)? {
+        let content = fs::read_to_string(&path)
+            .context(format!("Failed to read file: {}", path.display()))?;
+        classes.extend(parse_rust_ffi_classes_from_string(&content, &path)?);
+    }
+
+    Ok(classes)
+}
+
+/// Rust files scanned for #[ffi] items: ffi/rust/*.rs and ffi/rust/src/lib.rs
+fn rust_source_files(ffi_dir: &Path) -> Result<Vec<PathBuf>> {
     let rust_dir = ffi_dir.join("rust");
 
     if !rust_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let mut functions = Vec::new();
+    let mut files = Vec::new();
 
     // Find all .rs files
     let entries = fs::read_dir(&rust_dir)
@@ -220,19 +626,17 @@ pub fn discover_rust_ffi(ffi_dir: &Path) -> Result<Vec<RustFfiFunction>> {
         let path = entry.path();
 
         if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("rs") {
-            let file_functions = parse_rust_file(&path)?;
-            functions.extend(file_functions);
+            files.push(path);
         }
     }
 
     // Also check for src/lib.rs inside rust directory
     let lib_path = rust_dir.join("src").join("lib.rs");
     if lib_path.exists() {
-        let file_functions = parse_rust_file(&lib_path)?;
-        functions.extend(file_functions);
+        files.push(lib_path);
     }
 
-    Ok(functions)
+    Ok(files)
 }
 
 /// Parse a single Rust file for #[ffi] annotations
@@ -243,6 +647,371 @@ fn parse_rust_file(path: &Path) -> Result<Vec<RustFfiFunction>> {
     parse_rust_ffi_from_string(&content, path)
 }
 
+/// Parse Rust source code for #[ffi] structs and enums (testable)
+pub fn parse_rust_ffi_types_from_string(content: &str, source_file: &Path) -> Result<Vec<RustFfiType>> {
+    let syntax = syn::parse_file(content)
+        .context(format!("Failed to parse Rust file: {}", source_file.display()))?;
+
+    let mut types = Vec::new();
+
+    for item in syntax.items {
+        match item {
+            Item::Struct(item) if has_ffi_attribute(&item.attrs) => {
+                types.push(parse_ffi_struct(item, source_file)?);
+            }
+            Item::Enum(item) if has_ffi_attribute(&item.attrs) => {
+                types.push(parse_ffi_enum(item, source_file)?);
+            }
+            Item::Trait(item) if item.attrs.iter().any(|attr| attr.path().is_ident("ffi_callback")) => {
+                types.push(parse_ffi_callback_trait(item, source_file)?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(types)
+}
+
+/// Parse Rust source code for #[ffi] impl blocks (testable)
+pub fn parse_rust_ffi_classes_from_string(content: &str, source_file: &Path) -> Result<Vec<RustFfiClass>> {
+    let syntax = syn::parse_file(content)
+        .context(format!("Failed to parse Rust file: {}", source_file.display()))?;
+
+    let mut classes = Vec::new();
+
+    for item in syntax.items {
+        if let Item::Impl(item) = item {
+            if has_ffi_attribute(&item.attrs) {
+                classes.push(parse_ffi_impl(item, source_file)?);
+            }
+        }
+    }
+
+    Ok(classes)
+}
+
+/// Parse an impl block with #[ffi] attribute
+///
+/// Every method in the block is exported; helpers belong in a separate impl block.
+fn parse_ffi_impl(item: ItemImpl, source_file: &Path) -> Result<RustFfiClass> {
+    let name = match &*item.self_ty {
+        Type::Path(type_path) if type_path.qself.is_none() && type_path.path.segments.len() == 1 => {
+            type_path.path.segments[0].ident.to_string()
+        }
+        _ => bail!("#[ffi] impl blocks at {} must be for a plain struct name", source_file.display()),
+    };
+
+    if item.trait_.is_some() {
+        bail!("#[ffi] cannot be used on trait impls ('{}' at {})", name, source_file.display());
+    }
+    if !item.generics.params.is_empty() {
+        bail!("#[ffi] impl '{}' at {} cannot be generic", name, source_file.display());
+    }
+
+    let mut methods = Vec::new();
+    for impl_item in &item.items {
+        let syn::ImplItem::Fn(method) = impl_item else {
+            bail!("#[ffi] impl '{}' at {} can only contain methods", name, source_file.display());
+        };
+        let method_name = method.sig.ident.to_string();
+        let owner = format!("{}::{}", name, method_name);
+
+        if method_name == "close" {
+            bail!(
+                "Method '{}' at {} is reserved for releasing the handle. Implement Drop for cleanup instead",
+                owner, source_file.display()
+            );
+        }
+
+        let receiver = match method.sig.receiver() {
+            None => MethodReceiver::Static,
+            Some(receiver) if receiver.reference.is_some() && receiver.mutability.is_some() => MethodReceiver::Mut,
+            Some(receiver) if receiver.reference.is_some() => MethodReceiver::Ref,
+            Some(_) => bail!(
+                "Method '{}' at {} cannot take self by value. Use &self or &mut self",
+                owner, source_file.display()
+            ),
+        };
+
+        let mut sig = method.sig.clone();
+        sig.inputs = sig.inputs.into_iter().filter(|arg| matches!(arg, FnArg::Typed(_))).collect();
+        let mut function = parse_ffi_signature(&sig, &method.attrs, source_file)
+            .context(format!("In #[ffi] impl '{}'", name))?;
+        if function.is_async {
+            bail!("#[ffi(async)] is not supported on methods ('{}' at {})", owner, source_file.display());
+        }
+
+        // `-> Self` and `-> Result<Self, E>` hand a new handle to Kotlin
+        let is_self = |typ: &RustType| typ.custom_name().is_some_and(|n| n == "Self" || n == name);
+        if let RustType::Custom(ref returned) = function.return_type.base_type {
+            if returned == "Self" || *returned == name {
+                function.return_type.base_type = RustType::Handle(name.clone());
+            }
+        }
+        if function.params.iter().any(|(_, typ)| is_self(typ)) || is_self(&function.return_type.base_type) {
+            bail!(
+                "'{}' at {} can only be returned directly (Self or Result<Self, E>)",
+                owner, source_file.display()
+            );
+        }
+
+        methods.push(RustFfiMethod { receiver, function });
+    }
+
+    Ok(RustFfiClass {
+        docs: FfiDocs {
+            lines: doc_lines(&item.attrs),
+            deprecated: deprecation(&item.attrs),
+            defaults: Vec::new(),
+        },
+        name,
+        methods,
+        source_file: source_file.to_path_buf(),
+    })
+}
+
+/// Parse a struct with #[ffi] attribute
+fn parse_ffi_struct(item: ItemStruct, source_file: &Path) -> Result<RustFfiType> {
+    let name = item.ident.to_string();
+
+    let fields = match &item.fields {
+        Fields::Named(_) => parse_fields(&item.fields, &name, source_file)?,
+        Fields::Unit => Vec::new(),
+        Fields::Unnamed(_) => bail!(
+            "FFI struct '{}' at {} must have named fields",
+            name, source_file.display()
+        ),
+    };
+
+    Ok(RustFfiType {
+        name,
+        kind: RustFfiTypeKind::Struct(fields),
+        source_file: source_file.to_path_buf(),
+    })
+}
+
+/// Parse an enum with #[ffi] attribute
+fn parse_ffi_enum(item: ItemEnum, source_file: &Path) -> Result<RustFfiType> {
+    let name = item.ident.to_string();
+
+    let mut variants = Vec::new();
+    for variant in &item.variants {
+        if variant.discriminant.is_some() {
+            bail!(
+                "FFI enum '{}' at {} cannot use explicit discriminants",
+                name, source_file.display()
+            );
+        }
+
+        let owner = format!("{}::{}", name, variant.ident);
+        variants.push(RustFfiVariant {
+            name: variant.ident.to_string(),
+            fields: parse_fields(&variant.fields, &owner, source_file)?,
+            is_tuple: matches!(variant.fields, Fields::Unnamed(_)),
+        });
+    }
+
+    Ok(RustFfiType {
+        name,
+        kind: RustFfiTypeKind::Enum(variants),
+        source_file: source_file.to_path_buf(),
+    })
+}
+
+/// Parse a trait with #[ffi_callback] attribute
+fn parse_ffi_callback_trait(item: ItemTrait, source_file: &Path) -> Result<RustFfiType> {
+    let name = item.ident.to_string();
+
+    if !item.generics.params.is_empty() {
+        bail!("Callback trait '{}' at {} cannot be generic", name, source_file.display());
+    }
+
+    let mut methods = Vec::new();
+    for trait_item in &item.items {
+        let syn::TraitItem::Fn(method) = trait_item else {
+            bail!(
+                "Callback trait '{}' at {} can only contain methods",
+                name, source_file.display()
+            );
+        };
+        let method_name = method.sig.ident.to_string();
+        let owner = format!("{}::{}", name, method_name);
+
+        let mut inputs = method.sig.inputs.iter();
+        match inputs.next() {
+            Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+            _ => bail!(
+                "Callback method '{}' at {} must take &self",
+                owner, source_file.display()
+            ),
+        }
+
+        let mut params = Vec::new();
+        for input in inputs {
+            let FnArg::Typed(pat_type) = input else { unreachable!() };
+            let param_name = match &*pat_type.pat {
+                Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+                _ => bail!("Unsupported parameter pattern in '{}' at {}", owner, source_file.display()),
+            };
+            let param_type = RustType::from_syn_type(&pat_type.ty)
+                .context(format!("In parameter '{}' of '{}' at {}", param_name, owner, source_file.display()))?;
+            params.push((param_name, param_type));
+        }
+
+        let return_type = match &method.sig.output {
+            ReturnType::Default => RustType::Void,
+            ReturnType::Type(_, ty) => RustType::from_syn_type(ty)
+                .context(format!("In return type of '{}' at {}", owner, source_file.display()))?,
+        };
+
+        let param_types = params.iter().map(|(_, t)| t.clone()).collect::<Vec<_>>();
+        check_callback_signature(&param_types, &return_type)
+            .context(format!("In '{}' at {}", owner, source_file.display()))?;
+
+        methods.push(RustCallbackMethod {
+            name: method_name,
+            params,
+            return_type,
+        });
+    }
+
+    Ok(RustFfiType {
+        name,
+        kind: RustFfiTypeKind::Callback(methods),
+        source_file: source_file.to_path_buf(),
+    })
+}
+
+/// Parse struct or variant fields; tuple fields get positional names
+fn parse_fields(fields: &Fields, owner: &str, source_file: &Path) -> Result<Vec<(String, RustType)>> {
+    let count = fields.len();
+    let mut result = Vec::new();
+
+    for (i, field) in fields.iter().enumerate() {
+        let field_name = match &field.ident {
+            Some(ident) => ident.to_string(),
+            None if count == 1 => "value".to_string(),
+            None => format!("value{}", i),
+        };
+
+        let field_type = RustType::from_syn_type(&field.ty)
+            .context(format!(
+                "In field '{}' of '{}' at {}",
+                field_name, owner, source_file.display()
+            ))?;
+
+        if !field_type.is_boxable() && !matches!(field_type, RustType::Option(_)) {
+            bail!(
+                "Field '{}' of '{}' at {}: struct fields support primitives, String, Vec<u8>, Option, HashMap, #[ffi] types and Vec of #[ffi] types",
+                field_name, owner, source_file.display()
+            );
+        }
+
+        result.push((field_name, field_type));
+    }
+
+    Ok(result)
+}
+
+/// Check that every struct/enum name used by functions and fields is an #[ffi] type
+/// and every callback trait is an #[ffi_callback] trait
+pub fn check_rust_ffi_types(functions: &[RustFfiFunction], types: &[RustFfiType]) -> Result<()> {
+    let known = |name: &str| types.iter().any(|t| t.name == name && !t.is_callback());
+
+    for function in functions {
+        check_function_types(function, types)?;
+    }
+
+    for ffi_type in types {
+        for typ in ffi_type.field_types() {
+            if let Some(name) = typ.custom_name() {
+                if !known(name) {
+                    bail!(
+                        "Unknown type '{}' in '{}' at {}. Mark the struct or enum with #[ffi]",
+                        name, ffi_type.name, ffi_type.source_file.display()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the #[ffi] types and callback traits used by one function's signature
+fn check_function_types(function: &RustFfiFunction, types: &[RustFfiType]) -> Result<()> {
+    let known = |name: &str| types.iter().any(|t| t.name == name && !t.is_callback());
+    let is_callback = |name: &str| types.iter().any(|t| t.name == name && t.is_callback());
+
+    let mut used = vec![&function.return_type.base_type];
+    for (_, typ) in &function.params {
+        match typ {
+            RustType::Closure(closure) => {
+                used.extend(closure.params.iter());
+                used.push(&closure.return_type);
+            }
+            RustType::Callback(name, _) if !is_callback(name) => bail!(
+                "Unknown callback trait '{}' in function '{}' at {}. Mark the trait with #[ffi_callback]",
+                name, function.name, function.source_file.display()
+            ),
+            _ => used.push(typ),
+        }
+    }
+    for typ in used {
+        if let Some(name) = typ.custom_name() {
+            if is_callback(name) {
+                bail!(
+                    "Callback trait '{}' in function '{}' at {} must be taken as impl {}, &dyn {} or Box<dyn {}>",
+                    name, function.name, function.source_file.display(), name, name, name
+                );
+            }
+            if !known(name) {
+                bail!(
+                    "Unknown type '{}' in function '{}' at {}. Mark the struct or enum with #[ffi]",
+                    name, function.name, function.source_file.display()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check #[ffi] impl methods, and that opaque types are only returned from their own methods
+///
+/// Run before check_rust_ffi_types() so using an opaque type gets a specific error.
+pub fn check_rust_ffi_classes(classes: &[RustFfiClass], functions: &[RustFfiFunction], types: &[RustFfiType]) -> Result<()> {
+    let is_class = |name: &str| classes.iter().any(|c| c.name == name);
+    let methods = classes.iter().flat_map(|c| c.methods.iter().map(|m| &m.function));
+
+    for function in functions.iter().chain(methods) {
+        let used = function.params.iter().map(|(_, t)| t).chain([&function.return_type.base_type]);
+        for typ in used {
+            if let Some(name) = typ.custom_name().filter(|name| is_class(name)) {
+                bail!(
+                    "Opaque type '{}' in function '{}' at {} can only be returned from its own #[ffi] impl methods",
+                    name, function.name, function.source_file.display()
+                );
+            }
+        }
+    }
+
+    for class in classes {
+        if types.iter().any(|t| t.name == class.name) {
+            bail!(
+                "'{}' at {} cannot be both an #[ffi] type and an #[ffi] impl. Remove #[ffi] from the struct to pass it as a handle",
+                class.name, class.source_file.display()
+            );
+        }
+        for method in &class.methods {
+            check_function_types(&method.function, types)
+                .context(format!("In #[ffi] impl '{}'", class.name))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Parse Rust source code for #[ffi] annotations (testable)
 pub fn parse_rust_ffi_from_string(content: &str, source_file: &Path) -> Result<Vec<RustFfiFunction>> {
     let syntax = syn::parse_file(content)
@@ -269,13 +1038,126 @@ fn has_ffi_attribute(attrs: &[Attribute]) -> bool {
     })
 }
 
+/// Parse `#[ffi(async, default(x = 0))]` arguments: whether the function is async, and
+/// the default values given to its parameters
+fn parse_ffi_arguments(
+    attrs: &[Attribute],
+    function_name: &str,
+    source_file: &Path,
+) -> Result<(bool, Vec<(String, DefaultLiteral)>)> {
+    let mut is_async = false;
+    let mut defaults = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("ffi")) {
+        if !matches!(attr.meta, syn::Meta::List(_)) {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("async") {
+                is_async = true;
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                meta.parse_nested_meta(|param| {
+                    let Some(name) = param.path.get_ident() else {
+                        return Err(param.error("expected a parameter name"));
+                    };
+                    let value: syn::Expr = param.value()?.parse()?;
+                    match default_literal(&value) {
+                        Some(literal) => {
+                            defaults.push((name.to_string(), literal));
+                            Ok(())
+                        }
+                        None => Err(param.error("default values must be literals or None")),
+                    }
+                })
+            } else {
+                Err(meta.error("unknown #[ffi] argument, expected `async` or `default(..)`"))
+            }
+        })
+        .context(format!("In #[ffi] attribute of function '{}' at {}", function_name, source_file.display()))?;
+    }
+    Ok((is_async, defaults))
+}
+
+/// Literal of a `default(x = ..)` argument: numbers (optionally negative), bools, strings or `None`
+fn default_literal(expr: &syn::Expr) -> Option<DefaultLiteral> {
+    match expr {
+        syn::Expr::Lit(lit) => match &lit.lit {
+            syn::Lit::Int(int) => Some(DefaultLiteral::Int(int.base10_digits().to_string())),
+            syn::Lit::Float(float) => Some(DefaultLiteral::Float(float.base10_digits().to_string())),
+            syn::Lit::Bool(value) => Some(DefaultLiteral::Bool(value.value)),
+            syn::Lit::Str(value) => Some(DefaultLiteral::Str(value.value())),
+            _ => None,
+        },
+        syn::Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Neg(_)) => match default_literal(&unary.expr)? {
+            DefaultLiteral::Int(digits) => Some(DefaultLiteral::Int(format!("-{}", digits))),
+            DefaultLiteral::Float(digits) => Some(DefaultLiteral::Float(format!("-{}", digits))),
+            _ => None,
+        },
+        syn::Expr::Path(path) if path.path.is_ident("None") => Some(DefaultLiteral::Null),
+        _ => None,
+    }
+}
+
+/// `///` doc comment lines of an item
+fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }) => Some(doc.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flat_map(|doc| doc.split('\n').map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end().to_string()).collect::<Vec<_>>())
+        .collect();
+    trim_doc_lines(lines)
+}
+
+/// Message of a `#[deprecated]`, `#[deprecated = ".."]` or `#[deprecated(note = "..")]` attribute
+fn deprecation(attrs: &[Attribute]) -> Option<String> {
+    let attr = attrs.iter().find(|attr| attr.path().is_ident("deprecated"))?;
+    let note = match &attr.meta {
+        syn::Meta::NameValue(nv) => match &nv.value {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(note), .. }) => Some(note.value()),
+            _ => None,
+        },
+        syn::Meta::List(_) => {
+            let mut note = None;
+            let _ = attr.parse_nested_meta(|meta| {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                if meta.path.is_ident("note") {
+                    note = Some(value.value());
+                }
+                Ok(())
+            });
+            note
+        }
+        syn::Meta::Path(_) => None,
+    };
+    Some(note.unwrap_or_else(|| "Deprecated".to_string()))
+}
+
 /// Parse a function with #[ffi] attribute
 fn parse_ffi_function(func: ItemFn, source_file: &Path) -> Result<RustFfiFunction> {
-    let function_name = func.sig.ident.to_string();
+    parse_ffi_signature(&func.sig, &func.attrs, source_file)
+}
+
+/// Parse the parameters and return type of an #[ffi] function or method, along with
+/// the docs, `#[deprecated]` and `#[ffi(..)]` arguments in its attributes
+fn parse_ffi_signature(sig: &syn::Signature, attrs: &[Attribute], source_file: &Path) -> Result<RustFfiFunction> {
+    let function_name = sig.ident.to_string();
+    let (is_async, default_literals) = parse_ffi_arguments(attrs, &function_name, source_file)?;
+
+    if sig.asyncness.is_some() {
+        bail!("FFI functions cannot be `async fn` in function '{}' at {}. Use a plain fn with #[ffi(async)] instead",
+              function_name, source_file.display());
+    }
 
     // Parse parameters
     let mut params = Vec::new();
-    for input in &func.sig.inputs {
+    for input in &sig.inputs {
         match input {
             FnArg::Typed(pat_type) => {
                 // Get parameter name
@@ -292,6 +1174,18 @@ fn parse_ffi_function(func: ItemFn, source_file: &Path) -> Result<RustFfiFunctio
                         param_name, function_name, source_file.display()
                     ))?;
 
+                let param_type = match param_type {
+                    RustType::Closure(mut closure) => {
+                        closure.interface_name = format!(
+                            "{}{}Callback",
+                            snake_to_pascal(&function_name),
+                            snake_to_pascal(&param_name)
+                        );
+                        RustType::Closure(closure)
+                    }
+                    other => other,
+                };
+
                 params.push((param_name, param_type));
             }
             FnArg::Receiver(_) => {
@@ -302,19 +1196,72 @@ fn parse_ffi_function(func: ItemFn, source_file: &Path) -> Result<RustFfiFunctio
     }
 
     // Parse return type (Phase 5: Support Result<T, E>)
-    let return_type = match &func.sig.output {
+    let return_type = match &sig.output {
         ReturnType::Default => RustFunctionReturn::plain(RustType::Void),
         ReturnType::Type(_, ty) => parse_return_type(ty, &function_name, source_file)?,
     };
 
+    if matches!(return_type.base_type, RustType::ByteSlice | RustType::ByteBuffer | RustType::CancelFlag) {
+        bail!("FFI functions cannot return borrowed references in function '{}' at {}. Return Vec<u8> instead",
+              function_name, source_file.display());
+    }
+
+    if matches!(return_type.base_type, RustType::Closure(_) | RustType::Callback(..)) {
+        bail!("FFI functions cannot return callbacks in function '{}' at {}",
+              function_name, source_file.display());
+    }
+
+    let cancel_flags = params.iter().filter(|(_, typ)| *typ == RustType::CancelFlag).count();
+    if cancel_flags > 0 && !is_async {
+        bail!("&AtomicBool cancellation flags are only supported in #[ffi(async)] functions in function '{}' at {}",
+              function_name, source_file.display());
+    }
+    if cancel_flags > 1 {
+        bail!("At most one &AtomicBool cancellation flag is allowed in function '{}' at {}",
+              function_name, source_file.display());
+    }
+
+    let mut defaults = Vec::new();
+    for (name, literal) in &default_literals {
+        let Some((_, typ)) = params.iter().find(|(param, _)| param == name) else {
+            bail!("Default value for unknown parameter '{}' in function '{}' at {}",
+                  name, function_name, source_file.display());
+        };
+        let Some(value) = kotlin_default(literal, &typ.to_kotlin_type()) else {
+            bail!("Default value for parameter '{}' in function '{}' at {} doesn't match its type {}",
+                  name, function_name, source_file.display(), typ.to_rust_type());
+        };
+        defaults.push((name.clone(), value));
+    }
+
     Ok(RustFfiFunction {
         name: function_name,
         params,
         return_type,
+        is_async,
+        docs: FfiDocs {
+            lines: doc_lines(attrs),
+            deprecated: deprecation(attrs),
+            defaults,
+        },
         source_file: source_file.to_path_buf(),
     })
 }
 
+/// Convert snake_case to PascalCase (`on_progress` -> `OnProgress`)
+fn snake_to_pascal(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
 /// Parse return type, detecting Result<T, E> wrappers
 fn parse_return_type(ty: &Type, function_name: &str, source_file: &Path) -> Result<RustFunctionReturn> {
     match ty {
@@ -588,4 +1535,275 @@ mod tests {
         assert_eq!(functions[4].return_type, RustFunctionReturn::plain(RustType::BoolArray));
         assert_eq!(functions[5].return_type, RustFunctionReturn::plain(RustType::StringArray));
     }
+
+    #[test]
+    fn test_parse_structs_and_enums() {
+        let rust = r#"
+            #[ffi]
+            pub struct Point { x: f64, y: f64 }
+
+            #[ffi]
+            pub struct Polygon { name: String, points: Vec<Point> }
+
+            #[ffi]
+            pub enum Shape {
+                Circle { center: Point, radius: f64 },
+                Tagged(String),
+                Empty,
+            }
+
+            #[ffi]
+            pub enum Color { Red, Green }
+
+            pub struct Internal { x: i32 }
+
+            #[ffi]
+            pub fn area(shape: Shape) -> f64 { 0.0 }
+
+            #[ffi]
+            pub fn outline(polygon: Polygon) -> Vec<Point> { polygon.points }
+        "#;
+
+        let path = Path::new("test.rs");
+        let types = parse_rust_ffi_types_from_string(rust, path).unwrap();
+        let functions = parse_rust_ffi_from_string(rust, path).unwrap();
+
+        assert_eq!(types.len(), 4);
+        assert_eq!(types[0].name, "Point");
+        assert_eq!(
+            types[1].kind,
+            RustFfiTypeKind::Struct(vec![
+                ("name".to_string(), RustType::String),
+                ("points".to_string(), RustType::CustomList("Point".to_string())),
+            ])
+        );
+        match &types[2].kind {
+            RustFfiTypeKind::Enum(variants) => {
+                assert_eq!(variants.len(), 3);
+                assert_eq!(variants[0].fields[0], ("center".to_string(), RustType::Custom("Point".to_string())));
+                assert!(variants[1].is_tuple);
+                assert_eq!(variants[1].fields[0].0, "value");
+                assert!(variants[2].fields.is_empty());
+            }
+            _ => panic!("Shape should be an enum"),
+        }
+        assert!(!types[2].is_unit_enum());
+        assert!(types[3].is_unit_enum());
+
+        assert_eq!(functions[0].params[0].1, RustType::Custom("Shape".to_string()));
+        assert_eq!(functions[1].return_type.to_kotlin_type(), "List<Point>");
+        assert!(check_rust_ffi_types(&functions, &types).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_custom_type() {
+        let rust = r#"
+            #[ffi]
+            pub fn origin() -> Point { Point { x: 0.0, y: 0.0 } }
+        "#;
+
+        let path = Path::new("test.rs");
+        let functions = parse_rust_ffi_from_string(rust, path).unwrap();
+        let err = check_rust_ffi_types(&functions, &[]).unwrap_err();
+        assert!(err.to_string().contains("Unknown type 'Point'"));
+    }
+
+    #[test]
+    fn test_struct_rejects_primitive_array_fields() {
+        let rust = r#"
+            #[ffi]
+            pub struct Samples { values: Vec<i32> }
+        "#;
+
+        let result = parse_rust_ffi_types_from_string(rust, Path::new("test.rs"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_option_map_and_bytes() {
+        let rust = r#"
+            #[ffi]
+            pub fn find(id: Option<i32>, tags: HashMap<String, Option<String>>) -> Option<String> { None }
+
+            #[ffi]
+            pub fn checksum(data: &[u8], buffer: &mut [u8]) -> Vec<u8> { data.to_vec() }
+        "#;
+
+        let functions = parse_rust_ffi_from_string(rust, Path::new("test.rs")).unwrap();
+
+        assert_eq!(functions[0].params[0].1.to_kotlin_type(), "Int?");
+        assert_eq!(functions[0].params[1].1.to_kotlin_type(), "Map<String, String?>");
+        assert_eq!(functions[0].return_type.to_kotlin_type(), "String?");
+        assert_eq!(functions[1].params[0].1, RustType::ByteSlice);
+        assert_eq!(functions[1].params[1].1.to_kotlin_type(), "java.nio.ByteBuffer");
+        assert_eq!(functions[1].return_type.to_kotlin_type(), "ByteArray");
+    }
+
+    #[test]
+    fn test_rejects_unsupported_map_and_slice_types() {
+        let bad_key = "#[ffi]\npub fn f(m: HashMap<i32, String>) {}";
+        assert!(parse_rust_ffi_from_string(bad_key, Path::new("test.rs")).is_err());
+
+        let slice_return = "#[ffi]\npub fn f() -> &'static [u8] { &[] }";
+        assert!(parse_rust_ffi_from_string(slice_return, Path::new("test.rs")).is_err());
+    }
+
+    #[test]
+    fn test_async_functions() {
+        let rust = r#"
+            #[ffi(async)]
+            pub fn hash_file(path: String, cancelled: &AtomicBool) -> Result<Vec<u8>, String> { Ok(vec![]) }
+
+            #[ffi]
+            pub fn add(a: i32, b: i32) -> i32 { a + b }
+        "#;
+
+        let functions = parse_rust_ffi_from_string(rust, Path::new("test.rs")).unwrap();
+
+        assert!(functions[0].is_async);
+        assert_eq!(functions[0].params[1].1, RustType::CancelFlag);
+        assert_eq!(functions[0].kotlin_params().count(), 1);
+        assert!(!functions[1].is_async);
+
+        let flag_without_async = "#[ffi]\npub fn f(cancelled: &AtomicBool) {}";
+        assert!(parse_rust_ffi_from_string(flag_without_async, Path::new("test.rs")).is_err());
+
+        let unknown_argument = "#[ffi(blocking)]\npub fn f() {}";
+        assert!(parse_rust_ffi_from_string(unknown_argument, Path::new("test.rs")).is_err());
+    }
+
+    #[test]
+    fn test_callback_params() {
+        let rust = r#"
+            #[ffi_callback]
+            pub trait Listener: Send + Sync {
+                fn on_event(&self, name: String, count: i32);
+                fn is_cancelled(&self) -> bool;
+            }
+
+            #[ffi]
+            pub fn run(
+                on_progress: impl Fn(i32) + Send + 'static,
+                format: &dyn Fn(f64) -> String,
+                listener: Box<dyn Listener>,
+            ) {}
+        "#;
+
+        let path = Path::new("test.rs");
+        let functions = parse_rust_ffi_from_string(rust, path).unwrap();
+        let types = parse_rust_ffi_types_from_string(rust, path).unwrap();
+
+        match &functions[0].params[0].1 {
+            RustType::Closure(closure) => {
+                assert_eq!(closure.interface_name, "RunOnProgressCallback");
+                assert_eq!(closure.params, vec![RustType::Int]);
+                assert_eq!(closure.passing, CallbackPassing::Impl);
+            }
+            other => panic!("expected closure, got {:?}", other),
+        }
+        match &functions[0].params[1].1 {
+            RustType::Closure(closure) => {
+                assert_eq!(closure.return_type, RustType::String);
+                assert_eq!(closure.passing, CallbackPassing::Ref);
+            }
+            other => panic!("expected closure, got {:?}", other),
+        }
+        assert_eq!(functions[0].params[2].1, RustType::Callback("Listener".to_string(), CallbackPassing::Boxed));
+
+        assert!(types[0].is_callback());
+        match &types[0].kind {
+            RustFfiTypeKind::Callback(methods) => {
+                assert_eq!(methods.len(), 2);
+                assert_eq!(methods[0].params[1], ("count".to_string(), RustType::Int));
+                assert_eq!(methods[1].return_type, RustType::Bool);
+            }
+            _ => panic!("Listener should be a callback trait"),
+        }
+        assert!(check_rust_ffi_types(&functions, &types).is_ok());
+
+        let once = "#[ffi]\npub fn f(cb: impl FnOnce(i32)) {}";
+        assert!(parse_rust_ffi_from_string(once, path).is_err());
+    }
+
+    #[test]
+    fn test_ffi_impl_methods() {
+        let rust = r#"
+            pub struct Counter { value: i64 }
+
+            impl Counter {
+                fn helper(&self) {}
+            }
+
+            #[ffi]
+            impl Counter {
+                pub fn new(start: i64) -> Self { Counter { value: start } }
+                pub fn open(path: String) -> Result<Counter, String> { todo!() }
+                pub fn get(&self) -> i64 { self.value }
+                pub fn add(&mut self, amount: i64) {}
+            }
+        "#;
+
+        let path = Path::new("test.rs");
+        let classes = parse_rust_ffi_classes_from_string(rust, path).unwrap();
+        assert_eq!(classes.len(), 1);
+        let class = &classes[0];
+        assert_eq!(class.name, "Counter");
+        assert_eq!(class.methods.len(), 4);
+
+        let receivers = class.methods.iter().map(|m| m.receiver).collect::<Vec<_>>();
+        assert_eq!(receivers, vec![MethodReceiver::Static, MethodReceiver::Static, MethodReceiver::Ref, MethodReceiver::Mut]);
+        assert_eq!(class.methods[0].function.return_type, RustFunctionReturn::plain(RustType::Handle("Counter".to_string())));
+        assert_eq!(class.methods[1].function.return_type, RustFunctionReturn::result(RustType::Handle("Counter".to_string())));
+        assert_eq!(class.methods[3].function.params, vec![("amount".to_string(), RustType::Long)]);
+
+        // Free functions are unaffected by impl blocks
+        assert!(parse_rust_ffi_from_string(rust, path).unwrap().is_empty());
+
+        let by_value = "#[ffi]\nimpl Counter { pub fn into_inner(self) -> i64 { 0 } }";
+        assert!(parse_rust_ffi_classes_from_string(by_value, path).is_err());
+        let self_param = "#[ffi]\nimpl Counter { pub fn merge(&mut self, other: Counter) {} }";
+        assert!(parse_rust_ffi_classes_from_string(self_param, path).is_err());
+        let close = "#[ffi]\nimpl Counter { pub fn close(&mut self) {} }";
+        assert!(parse_rust_ffi_classes_from_string(close, path).is_err());
+        let trait_impl = "#[ffi]\nimpl Default for Counter { fn default() -> Self { todo!() } }";
+        assert!(parse_rust_ffi_classes_from_string(trait_impl, path).is_err());
+    }
+
+    #[test]
+    fn test_docs_deprecated_and_defaults() {
+        let rust = r#"
+            /// Resize an image.
+            ///
+            /// Returns the encoded PNG.
+            #[ffi(default(width = 640, scale = 1.5, label = "thumb", crop = false, quality = None))]
+            #[deprecated(since = "2.0", note = "use resize_to instead")]
+            pub fn resize(width: i64, scale: f32, label: String, crop: bool, quality: Option<i32>) -> Vec<u8> {
+                Vec::new()
+            }
+        "#;
+
+        let functions = parse_rust_ffi_from_string(rust, Path::new("test.rs")).unwrap();
+        let docs = &functions[0].docs;
+        assert_eq!(docs.lines, vec!["Resize an image.", "", "Returns the encoded PNG."]);
+        assert_eq!(docs.deprecated.as_deref(), Some("use resize_to instead"));
+        assert_eq!(docs.default_for("width"), Some("640L"));
+        assert_eq!(docs.default_for("scale"), Some("1.5f"));
+        assert_eq!(docs.default_for("label"), Some("\"thumb\""));
+        assert_eq!(docs.default_for("crop"), Some("false"));
+        assert_eq!(docs.default_for("quality"), Some("null"));
+
+        let bare = "#[ffi]\n#[deprecated]\npub fn old() {}";
+        let functions = parse_rust_ffi_from_string(bare, Path::new("test.rs")).unwrap();
+        assert_eq!(functions[0].docs.deprecated.as_deref(), Some("Deprecated"));
+        assert!(functions[0].docs.lines.is_empty());
+
+        let unknown = "#[ffi(default(y = 1))]\npub fn f(x: i32) {}";
+        let err = parse_rust_ffi_from_string(unknown, Path::new("test.rs")).unwrap_err();
+        assert!(err.to_string().contains("unknown parameter 'y'"));
+        let mismatch = "#[ffi(default(x = \"a\"))]\npub fn f(x: i32) {}";
+        let err = parse_rust_ffi_from_string(mismatch, Path::new("test.rs")).unwrap_err();
+        assert!(err.to_string().contains("doesn't match its type i32"));
+        let not_literal = "#[ffi(default(x = 1 + 2))]\npub fn f(x: i32) {}";
+        assert!(parse_rust_ffi_from_string(not_literal, Path::new("test.rs")).is_err());
+    }
 }
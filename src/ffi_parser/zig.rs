@@ -0,0 +1,243 @@
+//! `// @ffi` functions in Zig sources
+//!
+//! Zig functions exported with the C ABI are described with the C++ FFI types and
+//! declared to the JNI bridge through a generated C header, so both the Kotlin
+//! generator and the C bridge are shared with the C FFI.
+
+use std::path::{Path, PathBuf};
+use std::fs;
+use anyhow::{Result, Context, bail};
+use super::cpp::{doxygen_comment, split_top_level, CppFfiFunction, CppType};
+use super::docs::{trim_doc_lines, FfiDocs};
+
+/// Map a Zig type to its FFI type
+///
+/// Strings are `[*:0]const u8`: parameters are only valid during the call, and returned
+/// strings are copied into a Kotlin String but never freed.
+pub fn zig_type_from_str(type_str: &str) -> Result<CppType> {
+    // `[*:0]const u8` and `[*:0] const u8` are the same type
+    let normalized: String = type_str.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    match normalized.replace("] ", "]").as_str() {
+        "void" => Ok(CppType::Void),
+        "i32" | "c_int" => Ok(CppType::Int),
+        "i64" | "c_longlong" => Ok(CppType::Long),
+        "f32" => Ok(CppType::Float),
+        "f64" => Ok(CppType::Double),
+        "bool" => Ok(CppType::Bool),
+        "[*:0]const u8" => Ok(CppType::String),
+        "[*:0]u8" | "[*]const u8" | "[]const u8" => bail!(
+            "Unsupported Zig type: '{}'. Use '[*:0]const u8' for strings",
+            type_str.trim()
+        ),
+        _ => bail!(
+            "Unsupported Zig type: '{}'. Zig FFI supports: i32, c_int, i64, c_longlong, f32, f64, bool and [*:0]const u8",
+            type_str.trim()
+        ),
+    }
+}
+
+/// Discover all @ffi annotated functions in Zig sources
+pub fn discover_zig_ffi(ffi_dir: &Path) -> Result<Vec<CppFfiFunction>> {
+    let mut functions = Vec::new();
+
+    for path in zig_source_files(ffi_dir)? {
+        let content = fs::read_to_string(&path)
+            .context(format!("Failed to read file: {}", path.display()))?;
+        functions.extend(parse_zig_ffi_from_string(&content, &path)?);
+    }
+
+    Ok(functions)
+}
+
+/// Zig sources in ffi/zig/, sorted so builds are reproducible
+pub fn zig_source_files(ffi_dir: &Path) -> Result<Vec<PathBuf>> {
+    let zig_dir = ffi_dir.join("zig");
+
+    if !zig_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    let entries = fs::read_dir(&zig_dir)
+        .context(format!("Failed to read directory: {}", zig_dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("zig") {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Parse a Zig source for @ffi functions (testable)
+pub fn parse_zig_ffi_from_string(content: &str, source_file: &Path) -> Result<Vec<CppFfiFunction>> {
+    let lines: Vec<(usize, &str)> = content
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .collect();
+
+    let mut functions = Vec::new();
+    for (index, &(marker, line)) in lines.iter().enumerate() {
+        if line.trim() != "// @ffi" {
+            continue;
+        }
+
+        // Doc comments may sit on either side of the marker
+        let mut doc_lines = Vec::new();
+        let mut decl = index + 1;
+        while let Some(doc) = lines.get(decl).and_then(|(_, line)| line.trim().strip_prefix("///")) {
+            doc_lines.push(doc.trim().to_string());
+            decl += 1;
+        }
+
+        let location = format!("{}:{}", source_file.display(), decl + 1);
+        let Some(&(start, _)) = lines.get(decl) else {
+            bail!("@ffi marker at {} is not followed by a function", location);
+        };
+        let rest = &content[start..];
+        let Some(body) = rest.find('{') else {
+            bail!("@ffi marker at {} is not followed by a function", location);
+        };
+        let signature = rest[..body].split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let Some(after_fn) = signature.strip_prefix("pub export fn ").or_else(|| signature.strip_prefix("export fn ")) else {
+            bail!(
+                "@ffi declaration '{}' at {} must be an 'export fn' so it has C linkage",
+                signature, location
+            );
+        };
+        let (Some(open), Some(close)) = (after_fn.find('('), after_fn.rfind(')')) else {
+            bail!("Could not parse @ffi function '{}' at {}", signature, location);
+        };
+        let function_name = after_fn[..open].trim();
+        if function_name.is_empty() || !function_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            bail!("Could not parse @ffi function '{}' at {}", signature, location);
+        }
+
+        let return_type = zig_type_from_str(&after_fn[close + 1..])
+            .context(format!("In function '{}' at {}", function_name, location))?;
+
+        let mut params = Vec::new();
+        for param in split_top_level(&after_fn[open + 1..close]) {
+            let param = param.trim();
+            // Zig allows a trailing comma after the last parameter
+            if param.is_empty() {
+                continue;
+            }
+
+            let Some((param_name, param_type)) = param.split_once(':') else {
+                bail!(
+                    "Invalid parameter '{}' in function '{}' at {}. Expected format: 'name: type'",
+                    param, function_name, location
+                );
+            };
+            let param_name = param_name.trim();
+            let param_type = zig_type_from_str(param_type).context(format!(
+                "In parameter '{}' of function '{}' at {}",
+                param_name, function_name, location
+            ))?;
+            if param_type == CppType::Void {
+                bail!("Parameter '{}' of function '{}' at {} can't be void", param_name, function_name, location);
+            }
+            params.push((param_name.to_string(), param_type));
+        }
+
+        let (lines, deprecated) = if doc_lines.is_empty() {
+            doxygen_comment(&content[..marker])
+        } else {
+            (trim_doc_lines(doc_lines), None)
+        };
+
+        functions.push(CppFfiFunction {
+            name: function_name.to_string(),
+            namespace: Vec::new(),
+            params,
+            return_type,
+            docs: FfiDocs {
+                lines,
+                deprecated,
+                defaults: Vec::new(),
+            },
+            source_file: source_file.to_path_buf(),
+        });
+    }
+
+    Ok(functions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zig_source() {
+        let source = r#"
+const std = @import("std");
+
+/// Add two numbers
+// @ffi
+pub export fn add(a: i32, b: i32) i32 {
+    return a + b;
+}
+
+// @ffi
+/// Greeting for the Kotlin side
+pub export fn greet(
+    name: [*:0]const u8,
+) [*:0]const u8 {
+    _ = name;
+    return "Hello";
+}
+
+// @ffi
+export fn average(values: i64, count: c_int) f64 {
+    return @as(f64, @floatFromInt(values)) / @as(f64, @floatFromInt(count));
+}
+
+fn helper(x: i32) i32 {
+    return x;
+}
+"#;
+        let functions = parse_zig_ffi_from_string(source, Path::new("math.zig")).unwrap();
+        assert_eq!(functions.len(), 3);
+
+        assert_eq!(functions[0].name, "add");
+        assert_eq!(functions[0].params, vec![("a".to_string(), CppType::Int), ("b".to_string(), CppType::Int)]);
+        assert_eq!(functions[0].return_type, CppType::Int);
+        assert_eq!(functions[0].docs.lines, vec!["Add two numbers"]);
+
+        assert_eq!(functions[1].params, vec![("name".to_string(), CppType::String)]);
+        assert_eq!(functions[1].return_type, CppType::String);
+        assert_eq!(functions[1].docs.lines, vec!["Greeting for the Kotlin side"]);
+
+        assert_eq!(functions[2].params, vec![("values".to_string(), CppType::Long), ("count".to_string(), CppType::Int)]);
+        assert_eq!(functions[2].return_type, CppType::Double);
+    }
+
+    #[test]
+    fn test_zig_requires_export() {
+        let err = parse_zig_ffi_from_string("// @ffi\npub fn add(a: i32) i32 {\n    return a;\n}\n", Path::new("math.zig"))
+            .unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("export fn"));
+        assert!(message.contains("math.zig:2"));
+    }
+
+    #[test]
+    fn test_zig_unsupported_types() {
+        assert!(zig_type_from_str("u32").is_err());
+        assert!(zig_type_from_str("[]const u8").is_err());
+        assert_eq!(zig_type_from_str("[*:0] const u8").unwrap(), CppType::String);
+
+        let err = parse_zig_ffi_from_string("// @ffi\nexport fn fill(out: *i32) void {}\n", Path::new("buf.zig")).unwrap_err();
+        assert!(format!("{:#}", err).contains("*i32"));
+    }
+}
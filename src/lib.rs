@@ -6,8 +6,11 @@ pub mod project;
 pub mod build_pipeline;
 pub mod android_scaffold;
 pub mod routes;
+pub mod fetch;
+pub mod openapi;
 pub mod single_file;
 pub mod toolchain;
+pub mod ui_state;
 pub mod ffi_parser;
 pub mod ffi_codegen;
 pub mod ffi_build;
@@ -1,12 +1,18 @@
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use whitehall::commands;
+use whitehall::{commands, toolchain};
 
 #[derive(Parser)]
 #[command(name = "whitehall")]
 #[command(about = "A unified Rust toolchain for Android app development", long_about = None)]
 #[command(version)]
 struct Cli {
+    /// Never prompt; retry failed downloads with backoff instead (implied by CI=true)
+    #[arg(long, global = true)]
+    non_interactive: bool,
+    /// Accept Android SDK licenses without prompting in non-interactive mode
+    #[arg(long, global = true)]
+    accept_licenses: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -18,6 +24,20 @@ enum Commands {
         /// Name of the project
         name: String,
     },
+    /// Add a Gradle dependency to whitehall.toml
+    Add {
+        /// Maven coordinate: group:artifact[:version] (omit the version for artifacts managed by a BOM)
+        coordinate: String,
+        /// Gradle configuration, e.g. ksp or testImplementation
+        #[arg(long, default_value = "implementation")]
+        configuration: String,
+        /// Import the artifact as a BOM with platform(...)
+        #[arg(long)]
+        platform: bool,
+        /// Path to whitehall.toml
+        #[arg(long, default_value = "whitehall.toml")]
+        manifest: String,
+    },
     /// Transpile to Kotlin (no APK build)
     /// Works with both project directories (whitehall.toml) and single .wh files
     Compile {
@@ -46,6 +66,9 @@ enum Commands {
         /// Build optimized release APK (default: debug)
         #[arg(long, short)]
         release: bool,
+        /// Build FFI native libraries for one ABI only (e.g. arm64-v8a); a bare --abi uses the connected device's ABI
+        #[arg(long, num_args = 0..=1, default_missing_value = "device", value_name = "ABI")]
+        abi: Option<String>,
     },
     /// Watch for changes and rebuild automatically
     /// Works with both project directories (whitehall.toml) and single .wh files
@@ -53,6 +76,9 @@ enum Commands {
         /// Path to project directory or .wh file (defaults to current directory)
         #[arg(default_value = ".")]
         target: String,
+        /// Build FFI native libraries for one ABI only (e.g. arm64-v8a); a bare --abi uses the connected device's ABI
+        #[arg(long, num_args = 0..=1, default_missing_value = "device", value_name = "ABI")]
+        abi: Option<String>,
     },
     /// Build and install the app on a connected device (without launching)
     /// Works with both project directories (whitehall.toml) and single .wh files
@@ -65,6 +91,9 @@ enum Commands {
         /// Watch for changes and rebuild/reinstall automatically
         #[arg(long, short)]
         watch: bool,
+        /// Build FFI native libraries for one ABI only (e.g. arm64-v8a); a bare --abi uses the connected device's ABI
+        #[arg(long, num_args = 0..=1, default_missing_value = "device", value_name = "ABI")]
+        abi: Option<String>,
     },
     /// Build, install, and run the app on a connected device
     /// Works with both project directories (whitehall.toml) and single .wh files
@@ -77,6 +106,9 @@ enum Commands {
         /// Watch for changes and rebuild/reinstall/relaunch automatically
         #[arg(long, short)]
         watch: bool,
+        /// Build FFI native libraries for one ABI only (e.g. arm64-v8a); a bare --abi uses the connected device's ABI
+        #[arg(long, num_args = 0..=1, default_missing_value = "device", value_name = "ABI")]
+        abi: Option<String>,
     },
     /// Manage toolchains (Java, Gradle, Android SDK)
     Toolchain {
@@ -122,6 +154,12 @@ enum Commands {
         /// Path to whitehall.toml (defaults to current directory, optional)
         #[arg(default_value = "whitehall.toml")]
         manifest: String,
+        /// Print the checks as JSON
+        #[arg(long, conflicts_with = "fix")]
+        json: bool,
+        /// Apply safe fixes: install missing toolchains, accept licenses (when allowed), create an emulator, regenerate the scaffold
+        #[arg(long)]
+        fix: bool,
     },
     /// Remove build artifacts
     Clean {
@@ -135,6 +173,15 @@ enum Commands {
         #[arg(default_value = ".")]
         target: String,
     },
+    /// Print the route tree and validate it
+    Routes {
+        /// Path to project directory (defaults to current directory)
+        #[arg(default_value = ".")]
+        target: String,
+        /// Output the route tree as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -145,10 +192,42 @@ enum ToolchainCommands {
         #[arg(default_value = "whitehall.toml")]
         manifest: String,
     },
+    /// Re-resolve the project's toolchains and rewrite whitehall.lock
+    Update {
+        /// Path to whitehall.toml (defaults to current directory)
+        #[arg(default_value = "whitehall.toml")]
+        manifest: String,
+    },
+    /// Propose compatible Java, Gradle, AGP, Kotlin and SDK versions
+    Suggest {
+        /// Path to whitehall.toml (defaults to current directory)
+        #[arg(default_value = "whitehall.toml")]
+        manifest: String,
+    },
     /// List installed toolchains
     List,
     /// Remove all installed toolchains
     Clean,
+    /// Remove toolchain versions that no existing project has used
+    Gc {
+        /// Also remove versions whose last use is older than this (e.g. 30d, 12h, 2w)
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Show what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export installed toolchains into a portable tarball
+    Pack {
+        /// Archive to write
+        #[arg(default_value = "whitehall-toolchains.tar.gz")]
+        output: String,
+    },
+    /// Restore toolchains from a tarball created by `toolchain pack`
+    Unpack {
+        /// Archive to restore
+        archive: String,
+    },
     /// Execute a command with the project's toolchain environment
     Exec {
         /// Path to whitehall.toml (defaults to current directory)
@@ -207,36 +286,57 @@ enum EmulatorCommands {
 fn main() {
     let cli = Cli::parse();
 
+    toolchain::ci::set_non_interactive(cli.non_interactive);
+    toolchain::ci::set_accept_licenses(cli.accept_licenses);
+
     let result = match cli.command {
         Commands::Init { name } => {
             commands::init::execute(&name)
         }
+        Commands::Add { coordinate, configuration, platform, manifest } => {
+            commands::add::execute(&manifest, &coordinate, &configuration, platform)
+        }
         Commands::Compile { target, package, no_package, watch } => {
             commands::compile::execute(&target, package.as_deref(), no_package, watch)
         }
-        Commands::Build { target, watch, release } => {
-            commands::build::execute(&target, watch, release)
+        Commands::Build { target, watch, release, abi } => {
+            commands::build::execute(&target, watch, release, abi.as_deref())
         }
-        Commands::Watch { target } => {
-            commands::watch::execute(&target)
+        Commands::Watch { target, abi } => {
+            commands::watch::execute(&target, abi.as_deref())
         }
-        Commands::Install { target, device, watch } => {
-            commands::install::execute(&target, device.as_deref(), watch)
+        Commands::Install { target, device, watch, abi } => {
+            commands::install::execute(&target, device.as_deref(), watch, abi.as_deref())
         }
-        Commands::Run { target, device, watch } => {
-            commands::run::execute(&target, device.as_deref(), watch)
+        Commands::Run { target, device, watch, abi } => {
+            commands::run::execute(&target, device.as_deref(), watch, abi.as_deref())
         }
         Commands::Toolchain { command } => {
             match command {
                 ToolchainCommands::Install { manifest } => {
                     commands::toolchain::execute_install(&manifest)
                 }
+                ToolchainCommands::Update { manifest } => {
+                    commands::toolchain::execute_update(&manifest)
+                }
+                ToolchainCommands::Suggest { manifest } => {
+                    commands::toolchain::execute_suggest(&manifest)
+                }
                 ToolchainCommands::List => {
                     commands::toolchain::execute_list()
                 }
                 ToolchainCommands::Clean => {
                     commands::toolchain::execute_clean()
                 }
+                ToolchainCommands::Gc { older_than, dry_run } => {
+                    commands::toolchain::execute_gc(older_than.as_deref(), dry_run)
+                }
+                ToolchainCommands::Pack { output } => {
+                    commands::toolchain::execute_pack(&output)
+                }
+                ToolchainCommands::Unpack { archive } => {
+                    commands::toolchain::execute_unpack(&archive)
+                }
                 ToolchainCommands::Exec { manifest, command, args } => {
                     commands::toolchain::execute_exec(&manifest, &command, &args)
                 }
@@ -281,8 +381,8 @@ fn main() {
         Commands::Shell { manifest } => {
             commands::toolchain::execute_shell(&manifest)
         }
-        Commands::Doctor { manifest } => {
-            commands::doctor::execute(&manifest)
+        Commands::Doctor { manifest, json, fix } => {
+            commands::doctor::execute(&manifest, json, fix)
         }
         Commands::Clean { target } => {
             commands::clean::execute(&target)
@@ -290,10 +390,13 @@ fn main() {
         Commands::Check { target } => {
             commands::check::execute(&target)
         }
+        Commands::Routes { target, json } => {
+            commands::routes::execute(&target, json)
+        }
     };
 
     if let Err(e) = result {
         eprintln!("{} {}", "error:".red().bold(), e);
-        std::process::exit(1);
+        std::process::exit(toolchain::ci::exit_code(&e));
     }
 }
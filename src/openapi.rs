@@ -0,0 +1,906 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory scanned for OpenAPI specs
+pub const API_DIR: &str = "src/api";
+
+/// An OpenAPI 3.x spec reduced to what the Kotlin client needs
+#[derive(Debug, Clone)]
+pub struct ApiSpec {
+    /// Client object name: "PetsApi" (from the file name, or info.title for openapi.yaml)
+    pub name: String,
+    /// First server URL (empty if the spec has none)
+    pub base_url: String,
+    pub models: Vec<Model>,
+    pub operations: Vec<Operation>,
+    pub source_path: PathBuf,
+}
+
+/// A generated Kotlin type from a schema
+#[derive(Debug, Clone, PartialEq)]
+pub enum Model {
+    /// `@Serializable data class`
+    Data { name: String, description: Option<String>, fields: Vec<Field> },
+    /// `@Serializable enum class` (string enums)
+    Enum { name: String, values: Vec<String> },
+    /// `typealias` for non-object component schemas (e.g., `Pets = List<Pet>`)
+    Alias { name: String, type_name: String },
+}
+
+impl Model {
+    pub fn name(&self) -> &str {
+        match self {
+            Model::Data { name, .. } | Model::Enum { name, .. } | Model::Alias { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// Kotlin property name (camelCase, escaped if a keyword)
+    pub name: String,
+    /// Name in the JSON payload
+    pub json_name: String,
+    pub type_name: String,
+    /// Not required or nullable → `T? = null`
+    pub optional: bool,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    /// Kotlin function name (from operationId, or method + path)
+    pub name: String,
+    /// Ktor request function: get, post, put, patch, delete
+    pub method: String,
+    /// Path template: "/pets/{petId}"
+    pub path: String,
+    pub summary: Option<String>,
+    pub params: Vec<Param>,
+    /// JSON request body type
+    pub body: Option<String>,
+    /// JSON response type (None → Unit)
+    pub response: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamLocation {
+    Path,
+    Query,
+    Header,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    /// Kotlin parameter name
+    pub name: String,
+    /// Name in the path template, query string or header
+    pub api_name: String,
+    pub location: ParamLocation,
+    pub type_name: String,
+    pub required: bool,
+}
+
+const HTTP_METHODS: &[&str] = &["get", "post", "put", "patch", "delete"];
+
+const KOTLIN_KEYWORDS: &[&str] = &[
+    "as", "break", "class", "continue", "do", "else", "false", "for", "fun", "if", "in",
+    "interface", "is", "null", "object", "package", "return", "super", "this", "throw",
+    "true", "try", "typealias", "typeof", "val", "var", "when", "while",
+];
+
+/// Find OpenAPI specs (`.yaml`, `.yml`, `.json`) directly under src/api/
+pub fn discover_specs() -> Result<Vec<PathBuf>> {
+    discover_specs_in(Path::new(API_DIR))
+}
+
+fn discover_specs_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut specs = Vec::new();
+    for entry in fs::read_dir(dir).context(format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if path.is_file() && matches!(ext, "yaml" | "yml" | "json") {
+            specs.push(path);
+        }
+    }
+    specs.sort();
+    Ok(specs)
+}
+
+/// A parsed spec document
+///
+/// Like `serde_json::Value`, but objects keep their source key order so models,
+/// fields and operations come out in the order the spec declares them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(IndexMap<String, Value>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?.get(key)
+    }
+
+    /// Look up a JSON pointer (`/components/schemas/Pet`)
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer.strip_prefix('/')?.split('/').try_fold(self, |value, token| {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match value {
+                Value::Object(map) => map.get(&token),
+                Value::Array(items) => items.get(token.parse::<usize>().ok()?),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&IndexMap<String, Value>> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, Value::Object(_))
+    }
+
+    /// Convert a YAML document, turning scalar keys like unquoted `200:` into strings
+    pub fn from_yaml(value: serde_norway::Value) -> Result<Value, String> {
+        use serde_norway::Value as Yaml;
+
+        Ok(match value {
+            Yaml::Null => Value::Null,
+            Yaml::Bool(b) => Value::Bool(b),
+            Yaml::Number(n) => Value::Number(n.as_f64().unwrap_or(f64::NAN)),
+            Yaml::String(s) => Value::String(s),
+            Yaml::Sequence(items) => Value::Array(
+                items.into_iter().map(Value::from_yaml).collect::<Result<_, _>>()?,
+            ),
+            Yaml::Mapping(map) => {
+                let mut object = IndexMap::new();
+                for (key, value) in map {
+                    let key = match key {
+                        Yaml::String(s) => s,
+                        Yaml::Number(n) => n.to_string(),
+                        Yaml::Bool(b) => b.to_string(),
+                        Yaml::Null => "null".to_string(),
+                        other => return Err(format!("unsupported mapping key {:?}", other)),
+                    };
+                    object.insert(key, Value::from_yaml(value)?);
+                }
+                Value::Object(object)
+            }
+            Yaml::Tagged(tagged) => Value::from_yaml(tagged.value)?,
+        })
+    }
+}
+
+/// Load and parse an OpenAPI spec file (errors don't repeat the path; callers report it)
+pub fn load_spec(path: &Path) -> Result<ApiSpec> {
+    let source = fs::read_to_string(path)
+        .context(format!("Failed to read {}", path.display()))?;
+
+    let root: Value = if path.extension().and_then(|e| e.to_str()) == Some("json") {
+        serde_json::from_str(&source).context("Invalid JSON")?
+    } else {
+        let yaml: serde_norway::Value = serde_norway::from_str(&source).context("Invalid YAML")?;
+        Value::from_yaml(yaml).map_err(|e| anyhow::anyhow!("Invalid YAML: {}", e))?
+    };
+
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("api");
+    let mut spec = parse_spec(&root, stem).map_err(|e| anyhow::anyhow!(e))?;
+    spec.source_path = path.to_path_buf();
+    Ok(spec)
+}
+
+/// Parse an OpenAPI document; `file_stem` names the client unless it's the generic "openapi"
+pub fn parse_spec(root: &Value, file_stem: &str) -> Result<ApiSpec, String> {
+    let version = root.get("openapi").and_then(|v| v.as_str()).unwrap_or("");
+    if !version.starts_with("3.") {
+        return Err("only OpenAPI 3.x specs are supported (missing or unsupported 'openapi' version)".to_string());
+    }
+
+    let base_name = if file_stem.eq_ignore_ascii_case("openapi") {
+        root.pointer("/info/title").and_then(|v| v.as_str()).unwrap_or("Api")
+    } else {
+        file_stem
+    };
+    // "Weather API" → "WeatherApi" (not "WeatherAPIApi")
+    let mut base_name = to_pascal_case(base_name);
+    if base_name.len() > 3 && base_name.to_ascii_lowercase().ends_with("api") {
+        base_name.truncate(base_name.len() - 3);
+    }
+    let name = format!("{}Api", base_name);
+
+    let base_url = root.pointer("/servers/0")
+        .map(server_url)
+        .unwrap_or_default();
+
+    let mut ctx = SchemaContext { root, models: Vec::new() };
+
+    // Component schemas first, so inline models come after the named ones
+    if let Some(schemas) = root.pointer("/components/schemas").and_then(|v| v.as_object()) {
+        for (schema_name, schema) in schemas {
+            ctx.component_model(&to_pascal_case(schema_name), schema)?;
+        }
+    }
+
+    let mut operations = Vec::new();
+    if let Some(paths) = root.get("paths").and_then(|v| v.as_object()) {
+        for (path, item) in paths {
+            for method in HTTP_METHODS {
+                if let Some(op) = item.get(method) {
+                    operations.push(ctx.operation(path, method, item, op)?);
+                }
+            }
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    for op in &operations {
+        if !seen.insert(op.name.clone()) {
+            return Err(format!("duplicate operation name '{}'; set a unique operationId", op.name));
+        }
+    }
+
+    Ok(ApiSpec { name, base_url, models: ctx.models, operations, source_path: PathBuf::new() })
+}
+
+/// Server URL with `{variables}` replaced by their defaults
+fn server_url(server: &Value) -> String {
+    let mut url = server.get("url").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    if let Some(vars) = server.get("variables").and_then(|v| v.as_object()) {
+        for (var, def) in vars {
+            let default = def.get("default").and_then(|v| v.as_str()).unwrap_or("");
+            url = url.replace(&format!("{{{}}}", var), default);
+        }
+    }
+    url.trim_end_matches('/').to_string()
+}
+
+struct SchemaContext<'a> {
+    root: &'a Value,
+    models: Vec<Model>,
+}
+
+impl<'a> SchemaContext<'a> {
+    /// Follow a local `$ref` (`#/components/...`)
+    fn resolve(&self, value: &'a Value) -> Result<&'a Value, String> {
+        match value.get("$ref").and_then(|r| r.as_str()) {
+            Some(reference) => {
+                let pointer = reference.strip_prefix('#')
+                    .ok_or_else(|| format!("external $ref '{}' is not supported", reference))?;
+                self.root.pointer(pointer)
+                    .ok_or_else(|| format!("unresolved $ref '{}'", reference))
+            }
+            None => Ok(value),
+        }
+    }
+
+    fn has_model(&self, name: &str) -> bool {
+        self.models.iter().any(|m| m.name() == name)
+    }
+
+    /// Register a named component schema
+    fn component_model(&mut self, name: &str, schema: &'a Value) -> Result<(), String> {
+        if is_object_schema(schema) || is_string_enum(schema) {
+            self.inline_model(name, schema)?;
+        } else {
+            let (type_name, nullable) = self.schema_type(schema, name)?;
+            let type_name = if nullable { format!("{}?", type_name) } else { type_name };
+            self.models.push(Model::Alias { name: name.to_string(), type_name });
+        }
+        Ok(())
+    }
+
+    /// Generate a data class or enum for an object/enum schema and return its name
+    fn inline_model(&mut self, hint: &str, schema: &'a Value) -> Result<String, String> {
+        let mut name = hint.to_string();
+        let mut n = 2;
+        while self.has_model(&name) {
+            name = format!("{}{}", hint, n);
+            n += 1;
+        }
+
+        if let Some(values) = schema.get("enum").and_then(|v| v.as_array()) {
+            // Unquoted YAML values (`- 1` under `type: string`) are still strings
+            let values = values.iter().filter_map(|v| match v {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) if n.fract() == 0.0 => Some((*n as i64).to_string()),
+                Value::Number(n) => Some(n.to_string()),
+                Value::Bool(b) => Some(b.to_string()),
+                _ => None,
+            }).collect();
+            self.models.push(Model::Enum { name: name.clone(), values });
+            return Ok(name);
+        }
+
+        // Reserve the name before recursing (self-referencing schemas)
+        let index = self.models.len();
+        self.models.push(Model::Data { name: name.clone(), description: None, fields: Vec::new() });
+
+        // allOf: merge the properties of every part
+        let mut parts = vec![schema];
+        if let Some(all_of) = schema.get("allOf").and_then(|v| v.as_array()) {
+            for part in all_of {
+                parts.push(self.resolve(part)?);
+            }
+        }
+
+        let mut fields: Vec<Field> = Vec::new();
+        for part in parts {
+            let required: Vec<&str> = part.get("required")
+                .and_then(|v| v.as_array())
+                .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+
+            if let Some(properties) = part.get("properties").and_then(|v| v.as_object()) {
+                for (json_name, prop) in properties {
+                    let field_name = to_camel_case(json_name);
+                    let hint = format!("{}{}", name, to_pascal_case(json_name));
+                    let (type_name, nullable) = self.schema_type(prop, &hint)?;
+                    let description = self.resolve(prop)?.get("description")
+                        .and_then(|v| v.as_str())
+                        .map(String::from);
+                    fields.retain(|f| f.json_name != *json_name);
+                    fields.push(Field {
+                        name: escape_keyword(&field_name),
+                        json_name: json_name.clone(),
+                        type_name,
+                        optional: nullable || !required.contains(&json_name.as_str()),
+                        description,
+                    });
+                }
+            }
+        }
+
+        let description = schema.get("description").and_then(|v| v.as_str()).map(String::from);
+        self.models[index] = Model::Data { name: name.clone(), description, fields };
+        Ok(name)
+    }
+
+    /// Kotlin type for a schema; inline objects/enums become models named after `hint`
+    /// Returns (type, nullable)
+    fn schema_type(&mut self, schema: &'a Value, hint: &str) -> Result<(String, bool), String> {
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            if let Some(component) = reference.strip_prefix("#/components/schemas/") {
+                return Ok((to_pascal_case(component), false));
+            }
+            let resolved = self.resolve(schema)?;
+            return self.schema_type(resolved, hint);
+        }
+
+        // OpenAPI 3.0 `nullable: true` or 3.1 `type: [T, "null"]`
+        let mut nullable = schema.get("nullable").and_then(|v| v.as_bool()).unwrap_or(false);
+        let type_str = match schema.get("type") {
+            Some(Value::String(t)) => Some(t.as_str()),
+            Some(Value::Array(types)) => {
+                nullable |= types.iter().any(|t| t.as_str() == Some("null"));
+                types.iter().filter_map(|t| t.as_str()).find(|t| *t != "null")
+            }
+            _ => None,
+        };
+        let format = schema.get("format").and_then(|v| v.as_str()).unwrap_or("");
+
+        // allOf with a single reference is just that type
+        if let Some(all_of) = schema.get("allOf").and_then(|v| v.as_array()) {
+            if all_of.len() == 1 && schema.get("properties").is_none() {
+                let (type_name, inner_nullable) = self.schema_type(&all_of[0], hint)?;
+                return Ok((type_name, nullable || inner_nullable));
+            }
+        }
+
+        // Integer and number enums keep their primitive type
+        let type_name = if is_string_enum(schema) || is_object_schema(schema) {
+            self.inline_model(hint, schema)?
+        } else {
+            match type_str {
+                Some("string") => "String".to_string(),
+                Some("integer") if format == "int64" => "Long".to_string(),
+                Some("integer") => "Int".to_string(),
+                Some("number") if format == "float" => "Float".to_string(),
+                Some("number") => "Double".to_string(),
+                Some("boolean") => "Boolean".to_string(),
+                Some("array") => {
+                    let items = schema.get("items").unwrap_or(&Value::Null);
+                    let (item_type, item_nullable) = self.schema_type(items, &format!("{}Item", hint))?;
+                    let item_type = if item_nullable { format!("{}?", item_type) } else { item_type };
+                    format!("List<{}>", item_type)
+                }
+                Some("object") => match schema.get("additionalProperties") {
+                    Some(additional) if additional.is_object() => {
+                        let (value_type, _) = self.schema_type(additional, &format!("{}Value", hint))?;
+                        format!("Map<String, {}>", value_type)
+                    }
+                    _ => "JsonObject".to_string(),
+                },
+                // oneOf/anyOf and untyped schemas stay as raw JSON
+                _ => "JsonElement".to_string(),
+            }
+        };
+
+        Ok((type_name, nullable))
+    }
+
+    fn operation(&mut self, path: &str, method: &str, item: &'a Value, op: &'a Value) -> Result<Operation, String> {
+        let name = match op.get("operationId").and_then(|v| v.as_str()) {
+            Some(id) => escape_keyword(&to_camel_case(id)),
+            None => default_operation_name(method, path),
+        };
+        let type_hint = to_pascal_case(&name);
+
+        let summary = op.get("summary").or_else(|| op.get("description"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string());
+
+        // Path-level parameters apply to every operation (operation-level ones override)
+        let mut raw_params: Vec<&Value> = Vec::new();
+        for source in [item.get("parameters"), op.get("parameters")] {
+            if let Some(list) = source.and_then(|v| v.as_array()) {
+                for param in list {
+                    let param = self.resolve(param)?;
+                    let key = (param.get("name"), param.get("in"));
+                    raw_params.retain(|p| (p.get("name"), p.get("in")) != key);
+                    raw_params.push(param);
+                }
+            }
+        }
+
+        let mut params = Vec::new();
+        for param in raw_params {
+            let api_name = param.get("name").and_then(|v| v.as_str())
+                .ok_or_else(|| format!("parameter without a name in {} {}", method.to_uppercase(), path))?;
+            let location = match param.get("in").and_then(|v| v.as_str()) {
+                Some("path") => ParamLocation::Path,
+                Some("query") => ParamLocation::Query,
+                Some("header") => ParamLocation::Header,
+                // Cookie parameters aren't supported by the generated client
+                _ => continue,
+            };
+            let schema = param.get("schema").unwrap_or(&Value::Null);
+            let hint = format!("{}{}", type_hint, to_pascal_case(api_name));
+            let (type_name, _) = self.schema_type(schema, &hint)?;
+            let required = location == ParamLocation::Path
+                || param.get("required").and_then(|v| v.as_bool()).unwrap_or(false);
+            params.push(Param {
+                name: escape_keyword(&to_camel_case(api_name)),
+                api_name: api_name.to_string(),
+                location,
+                type_name,
+                required,
+            });
+        }
+
+        let body = match op.get("requestBody") {
+            Some(request_body) => {
+                let request_body = self.resolve(request_body)?;
+                match json_schema(request_body) {
+                    Some(schema) => Some(self.schema_type(schema, &format!("{}Request", type_hint))?.0),
+                    None => None,
+                }
+            }
+            None => None,
+        };
+
+        let mut response = None;
+        if let Some(responses) = op.get("responses").and_then(|v| v.as_object()) {
+            let success = responses.iter()
+                .filter(|(code, _)| code.starts_with('2'))
+                .map(|(_, r)| r)
+                .next();
+            if let Some(success) = success {
+                let success = self.resolve(success)?;
+                if let Some(schema) = json_schema(success) {
+                    response = Some(self.schema_type(schema, &format!("{}Response", type_hint))?.0);
+                }
+            }
+        }
+
+        Ok(Operation { name, method: method.to_string(), path: path.to_string(), summary, params, body, response })
+    }
+}
+
+/// The JSON schema of a request body or response (`content: application/json: schema`)
+fn json_schema(value: &Value) -> Option<&Value> {
+    let content = value.get("content")?.as_object()?;
+    content.iter()
+        .find(|(media_type, _)| media_type.contains("json"))
+        .and_then(|(_, media)| media.get("schema"))
+}
+
+/// `enum` of strings: `type: string`, or untyped with only string values
+fn is_string_enum(schema: &Value) -> bool {
+    let Some(values) = schema.get("enum").and_then(|v| v.as_array()) else {
+        return false;
+    };
+    match schema.get("type") {
+        Some(Value::String(t)) => t == "string",
+        Some(Value::Array(types)) => types.iter().any(|t| t.as_str() == Some("string")),
+        _ => values.iter().all(|v| v.as_str().is_some() || *v == Value::Null),
+    }
+}
+
+fn is_object_schema(schema: &Value) -> bool {
+    schema.get("properties").is_some()
+        || schema.get("allOf").map(|a| a.as_array().map(|a| a.len() > 1).unwrap_or(false)).unwrap_or(false)
+}
+
+/// Fallback operation name: GET /pets/{petId} → getPetsByPetId
+fn default_operation_name(method: &str, path: &str) -> String {
+    let mut name = method.to_string();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        if let Some(param) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            name.push_str("By");
+            name.push_str(&to_pascal_case(param));
+        } else {
+            name.push_str(&to_pascal_case(segment));
+        }
+    }
+    name
+}
+
+/// "pet_store-api v2" → "PetStoreApiV2"
+fn to_pascal_case(s: &str) -> String {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// "pet_id" → "petId", "X-Request-ID" → "xRequestID"
+fn to_camel_case(s: &str) -> String {
+    let pascal = to_pascal_case(s);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => "value".to_string(),
+    }
+}
+
+fn escape_keyword(name: &str) -> String {
+    if KOTLIN_KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// "in-stock" → "InStock" (enum constants can't start with a digit)
+fn enum_constant(value: &str) -> String {
+    let constant = to_pascal_case(value);
+    if constant.is_empty() || constant.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("Value{}", constant)
+    } else {
+        constant
+    }
+}
+
+/// Single-line KDoc comment
+fn kdoc(text: &str, indent: &str) -> String {
+    let line = text.lines().next().unwrap_or("").replace("*/", "*&#47;");
+    format!("{}/** {} */\n", indent, line.trim())
+}
+
+/// Generate the Kotlin client file (`<root_package>.api.<Name>`) for a spec
+/// Requests go through the app's shared `Fetch.client`
+pub fn generate_client(spec: &ApiSpec, root_package: &str) -> String {
+    let mut body = String::new();
+    let mut uses_serial_name = false;
+    let mut request_fns: Vec<&str> = Vec::new();
+
+    // Models
+    for model in &spec.models {
+        match model {
+            Model::Data { name, description, fields } => {
+                if let Some(description) = description {
+                    body.push_str(&kdoc(description, ""));
+                }
+                body.push_str("@Serializable\n");
+                if fields.is_empty() {
+                    body.push_str(&format!("class {}\n\n", name));
+                    continue;
+                }
+                body.push_str(&format!("data class {}(\n", name));
+                for (i, field) in fields.iter().enumerate() {
+                    if let Some(description) = &field.description {
+                        body.push_str(&kdoc(description, "    "));
+                    }
+                    let serial_name = if field.name != field.json_name {
+                        uses_serial_name = true;
+                        format!("@SerialName(\"{}\") ", field.json_name)
+                    } else {
+                        String::new()
+                    };
+                    let type_decl = if field.optional {
+                        format!("{}? = null", field.type_name.trim_end_matches('?'))
+                    } else {
+                        field.type_name.clone()
+                    };
+                    let comma = if i < fields.len() - 1 { "," } else { "" };
+                    body.push_str(&format!("    {}val {}: {}{}\n", serial_name, field.name, type_decl, comma));
+                }
+                body.push_str(")\n\n");
+            }
+            Model::Enum { name, values } => {
+                uses_serial_name = true;
+                body.push_str("@Serializable\n");
+                body.push_str(&format!("enum class {}(val value: String) {{\n", name));
+                for (i, value) in values.iter().enumerate() {
+                    let separator = if i < values.len() - 1 { "," } else { ";" };
+                    body.push_str(&format!(
+                        "    @SerialName(\"{}\") {}(\"{}\"){}\n",
+                        value, enum_constant(value), value, separator
+                    ));
+                }
+                // Members after the entries need the `;` even with no entries
+                if values.is_empty() {
+                    body.push_str("    ;\n");
+                }
+                // Query and header parameters are sent with toString()
+                body.push_str("\n    override fun toString() = value\n");
+                body.push_str("}\n\n");
+            }
+            Model::Alias { name, type_name } => {
+                body.push_str(&format!("typealias {} = {}\n\n", name, type_name));
+            }
+        }
+    }
+
+    // Client object
+    body.push_str(&format!("object {} {{\n", spec.name));
+    body.push_str(&format!("    var baseUrl = \"{}\"\n\n", spec.base_url));
+    body.push_str("    private val client get() = Fetch.client\n");
+
+    for op in &spec.operations {
+        body.push('\n');
+        if let Some(summary) = &op.summary {
+            body.push_str(&kdoc(summary, "    "));
+        }
+        request_fns.push(&op.method);
+
+        // Path params, body, required params, then optional params (defaulting to null)
+        let mut signature = Vec::new();
+        for param in op.params.iter().filter(|p| p.location == ParamLocation::Path) {
+            signature.push(format!("{}: {}", param.name, param.type_name));
+        }
+        if let Some(body_type) = &op.body {
+            signature.push(format!("body: {}", body_type));
+        }
+        for param in op.params.iter().filter(|p| p.location != ParamLocation::Path && p.required) {
+            signature.push(format!("{}: {}", param.name, param.type_name));
+        }
+        for param in op.params.iter().filter(|p| p.location != ParamLocation::Path && !p.required) {
+            signature.push(format!("{}: {}? = null", param.name, param.type_name));
+        }
+
+        let mut url = format!("$baseUrl{}", op.path);
+        for param in op.params.iter().filter(|p| p.location == ParamLocation::Path) {
+            url = url.replace(&format!("{{{}}}", param.api_name), &format!("${{{}}}", param.name));
+        }
+
+        let mut config = Vec::new();
+        for param in &op.params {
+            match param.location {
+                ParamLocation::Query if param.type_name.starts_with("List<") => {
+                    let access = if param.required { "" } else { "?" };
+                    config.push(format!("{}{}.forEach {{ parameter(\"{}\", it) }}", param.name, access, param.api_name));
+                    request_fns.push("parameter");
+                }
+                ParamLocation::Query => {
+                    config.push(format!("parameter(\"{}\", {})", param.api_name, param.name));
+                    request_fns.push("parameter");
+                }
+                ParamLocation::Header => {
+                    config.push(format!("header(\"{}\", {})", param.api_name, param.name));
+                    request_fns.push("header");
+                }
+                ParamLocation::Path => {}
+            }
+        }
+        if op.body.is_some() {
+            config.push("contentType(ContentType.Application.Json)".to_string());
+            config.push("setBody(body)".to_string());
+            request_fns.push("setBody");
+        }
+
+        let call = if config.is_empty() {
+            format!("client.{}(\"{}\")", op.method, url)
+        } else {
+            let mut call = format!("client.{}(\"{}\") {{\n", op.method, url);
+            for line in &config {
+                call.push_str(&format!("            {}\n", line));
+            }
+            call.push_str("        }");
+            call
+        };
+
+        match &op.response {
+            Some(response) => {
+                body.push_str(&format!("    suspend fun {}({}): {} =\n", op.name, signature.join(", "), response));
+                body.push_str(&format!("        {}.body()\n", call));
+            }
+            None => {
+                body.push_str(&format!("    suspend fun {}({}) {{\n", op.name, signature.join(", ")));
+                body.push_str(&format!("        {}\n", call));
+                body.push_str("    }\n");
+            }
+        }
+    }
+    body.push_str("}\n");
+
+    // Imports
+    let mut imports = vec![format!("{}.Fetch", root_package)];
+    if spec.operations.iter().any(|op| op.response.is_some()) {
+        imports.push("io.ktor.client.call.body".to_string());
+    }
+    for request_fn in request_fns {
+        imports.push(format!("io.ktor.client.request.{}", request_fn));
+    }
+    if spec.operations.iter().any(|op| op.body.is_some()) {
+        imports.push("io.ktor.http.ContentType".to_string());
+        imports.push("io.ktor.http.contentType".to_string());
+    }
+    if spec.models.iter().any(|m| !matches!(m, Model::Alias { .. })) {
+        imports.push("kotlinx.serialization.Serializable".to_string());
+    }
+    if uses_serial_name {
+        imports.push("kotlinx.serialization.SerialName".to_string());
+    }
+    for json_type in ["JsonElement", "JsonObject"] {
+        if body.contains(json_type) {
+            imports.push(format!("kotlinx.serialization.json.{}", json_type));
+        }
+    }
+    imports.sort();
+    imports.dedup();
+
+    let mut output = format!("package {}.api\n\n", root_package);
+    for import in imports {
+        output.push_str(&format!("import {}\n", import));
+    }
+    output.push('\n');
+    output.push_str(&body);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec_types_and_names() {
+        let root: Value = serde_json::from_str(r##"{
+            "openapi": "3.0.3",
+            "info": { "title": "Pet Store", "version": "1" },
+            "servers": [{ "url": "https://{env}.example.com/v1/", "variables": { "env": { "default": "api" } } }],
+            "paths": {
+                "/pets/{pet_id}": {
+                    "get": {
+                        "parameters": [
+                            { "name": "pet_id", "in": "path", "required": true, "schema": { "type": "integer", "format": "int64" } },
+                            { "name": "fields", "in": "query", "schema": { "type": "array", "items": { "type": "string" } } }
+                        ],
+                        "responses": { "200": { "description": "ok", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } } } }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {
+                            "id": { "type": "integer", "format": "int64" },
+                            "created_at": { "type": "string", "nullable": true },
+                            "status": { "type": "string", "enum": ["available", "sold"] }
+                        }
+                    }
+                }
+            }
+        }"##).unwrap();
+
+        let spec = parse_spec(&root, "openapi").unwrap();
+        assert_eq!(spec.name, "PetStoreApi");
+        assert_eq!(spec.base_url, "https://api.example.com/v1");
+
+        let op = &spec.operations[0];
+        assert_eq!(op.name, "getPetsByPetId");
+        assert_eq!(op.params[0].name, "petId");
+        assert_eq!(op.params[0].type_name, "Long");
+        assert_eq!(op.params[1].type_name, "List<String>");
+        assert_eq!(op.response.as_deref(), Some("Pet"));
+
+        match &spec.models[0] {
+            Model::Data { name, fields, .. } => {
+                assert_eq!(name, "Pet");
+                assert!(!fields[0].optional);
+                assert_eq!(fields[1].name, "createdAt");
+                assert!(fields[1].optional);
+                assert_eq!(fields[2].type_name, "PetStatus");
+            }
+            other => panic!("expected data class, got {:?}", other),
+        }
+        assert!(spec.models.iter().any(|m| matches!(m, Model::Enum { name, .. } if name == "PetStatus")));
+
+        let client = generate_client(&spec, "com.example.app");
+        assert!(client.contains("suspend fun getPetsByPetId(petId: Long, fields: List<String>? = null): Pet ="));
+        assert!(client.contains("client.get(\"$baseUrl/pets/${petId}\") {"));
+        assert!(client.contains("fields?.forEach { parameter(\"fields\", it) }"));
+        assert!(client.contains("@SerialName(\"created_at\") val createdAt: String? = null"));
+    }
+
+    #[test]
+    fn test_enum_models() {
+        let root: Value = serde_json::from_str(r##"{
+            "openapi": "3.1.0",
+            "info": { "title": "Codes", "version": "1" },
+            "paths": {},
+            "components": {
+                "schemas": {
+                    "Code": { "type": "integer", "enum": [200, 404] },
+                    "Ratio": { "type": "number", "enum": [0.5, 1.5] },
+                    "Size": { "type": "string", "enum": [1, 2] },
+                    "Empty": { "type": "string", "enum": [] }
+                }
+            }
+        }"##).unwrap();
+
+        let spec = parse_spec(&root, "codes").unwrap();
+        assert_eq!(spec.models[0], Model::Alias { name: "Code".to_string(), type_name: "Int".to_string() });
+        assert_eq!(spec.models[1], Model::Alias { name: "Ratio".to_string(), type_name: "Double".to_string() });
+        assert_eq!(spec.models[2], Model::Enum { name: "Size".to_string(), values: vec!["1".to_string(), "2".to_string()] });
+
+        // The `;` ends the (empty) entry list before toString()
+        let client = generate_client(&spec, "com.example.app");
+        assert!(client.contains("enum class Empty(val value: String) {\n    ;\n\n    override fun toString() = value"), "{}", client);
+    }
+
+    #[test]
+    fn test_parse_spec_rejects_swagger_2() {
+        let root: Value = serde_json::from_str(r#"{ "swagger": "2.0", "paths": {} }"#).unwrap();
+        assert!(parse_spec(&root, "pets").unwrap_err().contains("OpenAPI 3.x"));
+    }
+}
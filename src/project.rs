@@ -72,6 +72,8 @@ fn classify_file(path: &Path, config: &Config) -> Result<WhitehallFile> {
         (FileType::Screen, Some("screens"), filename)
     } else if is_under_directory(path, "src/stores") {
         (FileType::Component, Some("stores"), filename)
+    } else if is_under_directory(path, "src/models") {
+        (FileType::Component, Some("models"), filename)
     } else if is_under_directory(path, "src/routes") {
         // Handle route files: src/routes/**/+screen.wh or +layout.wh
         if filename == "+layout" || filename.starts_with("+layout@") {
@@ -230,7 +232,7 @@ fn is_under_directory(path: &Path, dir: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{AndroidConfig, BuildConfig, FfiConfig, ProjectConfig, ToolchainConfig};
+    use crate::config::{AndroidConfig, BuildConfig, FetchConfig, FfiConfig, ProjectConfig, ToolchainConfig};
 
     fn make_test_config() -> Config {
         Config {
@@ -242,10 +244,15 @@ mod tests {
                 min_sdk: 24,
                 target_sdk: 34,
                 package: "com.example.testapp".to_string(),
+                permissions: Vec::new(),
             },
             build: BuildConfig::default(),
             toolchain: ToolchainConfig::default(),
             ffi: FfiConfig::default(),
+            fetch: FetchConfig::default(),
+            dependencies: Default::default(),
+            plugins: Default::default(),
+            repositories: Default::default(),
         }
     }
 
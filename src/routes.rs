@@ -1,4 +1,5 @@
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
@@ -412,6 +413,152 @@ fn collect_layout_chain_up(layout: &Layout, layouts: &[Layout], chain: &mut Vec<
     chain.push(layout.composable_name.clone());
 }
 
+/// Severity of a route tree validation issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem found while validating the route tree
+#[derive(Debug, Clone)]
+pub struct RouteIssue {
+    pub severity: IssueSeverity,
+    /// Stable identifier for the check: "duplicate-route-name", "param-conflict", ...
+    pub kind: &'static str,
+    pub message: String,
+    /// File the issue was found in
+    pub source_path: PathBuf,
+}
+
+/// Validate the discovered route tree
+///
+/// Checks for:
+/// - routes whose names collide after PascalCase conversion (e.g. /profile and /profile/[id])
+/// - different param names at the same position (e.g. users/[id] and users/[userId]/edit)
+/// - `+layout@name` / `+screen@name` overrides pointing at a layout that doesn't exist
+/// - layouts that no route ends up using (warning)
+pub fn validate_routes(routes: &[Route], layouts: &[Layout]) -> Vec<RouteIssue> {
+    let mut issues = Vec::new();
+
+    // Duplicate route names (they become duplicate sealed class members in Routes.kt)
+    let mut names: BTreeMap<String, &Route> = BTreeMap::new();
+    for route in routes {
+        let key = to_pascal_case(&route.name);
+        if let Some(existing) = names.get(&key) {
+            issues.push(RouteIssue {
+                severity: IssueSeverity::Error,
+                kind: "duplicate-route-name",
+                message: format!(
+                    "Route '{}' and route '{}' both resolve to the name '{}'",
+                    existing.path, route.path, key
+                ),
+                source_path: route.source_path.clone(),
+            });
+        } else {
+            names.insert(key, route);
+        }
+    }
+
+    // Conflicting param names at the same depth under the same parent path
+    let mut params_by_prefix: BTreeMap<String, (String, &Route)> = BTreeMap::new();
+    for route in routes {
+        let segments: Vec<&str> = route.path.split('/').filter(|s| !s.is_empty()).collect();
+        for (i, segment) in segments.iter().enumerate() {
+            let Some(param_name) = segment.strip_prefix(':') else {
+                continue;
+            };
+            // Params earlier in the path are normalized so /a/:x/b and /a/:y/b share a prefix
+            let prefix = segments[..i]
+                .iter()
+                .map(|s| if s.starts_with(':') { "*" } else { s })
+                .collect::<Vec<_>>()
+                .join("/");
+            match params_by_prefix.get(&prefix) {
+                Some((existing_name, existing_route)) if existing_name != param_name => {
+                    issues.push(RouteIssue {
+                        severity: IssueSeverity::Error,
+                        kind: "param-conflict",
+                        message: format!(
+                            "Param ':{}' in '{}' conflicts with ':{}' in '{}' at the same position",
+                            param_name, route.path, existing_name, existing_route.path
+                        ),
+                        source_path: route.source_path.clone(),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    params_by_prefix.insert(prefix, (param_name.to_string(), route));
+                }
+            }
+        }
+    }
+
+    // @ overrides must point at an existing layout
+    let layout_exists = |target: &str| {
+        layouts.iter().any(|l| l.name.to_lowercase() == target.to_lowercase())
+    };
+    for layout in layouts {
+        if let Some(target) = layout.layout_override.as_deref() {
+            if !target.is_empty() && !layout_exists(target) {
+                issues.push(RouteIssue {
+                    severity: IssueSeverity::Error,
+                    kind: "missing-layout",
+                    message: format!(
+                        "Layout '{}' inherits from '@{}' but no such layout exists",
+                        layout.name, target
+                    ),
+                    source_path: layout.source_path.clone(),
+                });
+            }
+        }
+    }
+    for route in routes {
+        if let Some(target) = route.layout_override.as_deref() {
+            if !target.is_empty() && !layout_exists(target) {
+                issues.push(RouteIssue {
+                    severity: IssueSeverity::Error,
+                    kind: "missing-layout",
+                    message: format!(
+                        "Route '{}' uses layout '@{}' but no such layout exists",
+                        route.path, target
+                    ),
+                    source_path: route.source_path.clone(),
+                });
+            }
+        }
+    }
+
+    // Orphan layouts: not part of any route's layout chain
+    for layout in layouts {
+        let used = routes.iter().any(|r| r.layouts.contains(&layout.composable_name));
+        if !used {
+            issues.push(RouteIssue {
+                severity: IssueSeverity::Warning,
+                kind: "orphan-layout",
+                message: format!("Layout '{}' is not used by any route", layout.composable_name),
+                source_path: layout.source_path.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Convert a route name to PascalCase: "User-settings" → "UserSettings"
+fn to_pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let mut chars = s.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
 /// Generate Routes.kt sealed interface
 pub fn generate_routes_kt(routes: &[Route], package: &str) -> String {
     let mut output = String::new();
@@ -625,4 +772,83 @@ mod tests {
         let chain = compute_layout_chain("admin", &layouts, &Some("root".to_string()));
         assert_eq!(chain, vec!["RootLayout"]);
     }
+
+    fn layout(name: &str, dir_path: &str, layout_override: Option<&str>) -> Layout {
+        Layout {
+            name: name.to_string(),
+            composable_name: format!("{}Layout", name),
+            dir_path: dir_path.to_string(),
+            source_path: PathBuf::from(format!("src/routes/{}/+layout.wh", dir_path)),
+            parent: None,
+            layout_override: layout_override.map(|s| s.to_string()),
+        }
+    }
+
+    fn issue_kinds(issues: &[RouteIssue]) -> Vec<&'static str> {
+        issues.iter().map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn test_validate_routes_clean_tree() {
+        let layouts = vec![layout("Root", "", None)];
+        let routes = vec![
+            parse_route_from_path_with_layouts(Path::new("src/routes/+screen.wh"), &layouts).unwrap(),
+            parse_route_from_path_with_layouts(Path::new("src/routes/users/[id]/+screen.wh"), &layouts).unwrap(),
+            parse_route_from_path_with_layouts(Path::new("src/routes/users/[id]/edit/+screen.wh"), &layouts).unwrap(),
+        ];
+
+        let issues = validate_routes(&routes, &layouts);
+        assert!(issues.is_empty(), "unexpected issues: {:?}", issues);
+    }
+
+    #[test]
+    fn test_validate_duplicate_route_names() {
+        let routes = vec![
+            parse_route_from_path(Path::new("src/routes/profile/+screen.wh")).unwrap(),
+            parse_route_from_path(Path::new("src/routes/profile/[id]/+screen.wh")).unwrap(),
+            parse_route_from_path(Path::new("src/routes/user-settings/+screen.wh")).unwrap(),
+            parse_route_from_path(Path::new("src/routes/userSettings/+screen.wh")).unwrap(),
+        ];
+
+        let issues = validate_routes(&routes, &[]);
+        assert_eq!(issue_kinds(&issues), vec!["duplicate-route-name", "duplicate-route-name"]);
+        assert!(issues[0].message.contains("'Profile'"));
+        assert!(issues[1].message.contains("'UserSettings'"));
+    }
+
+    #[test]
+    fn test_validate_param_conflicts() {
+        let routes = vec![
+            parse_route_from_path(Path::new("src/routes/users/[id]/+screen.wh")).unwrap(),
+            parse_route_from_path(Path::new("src/routes/users/[userId]/posts/+screen.wh")).unwrap(),
+        ];
+
+        let issues = validate_routes(&routes, &[]);
+        assert_eq!(issue_kinds(&issues), vec!["param-conflict"]);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+    }
+
+    #[test]
+    fn test_validate_missing_layout_override_and_orphans() {
+        let layouts = vec![
+            layout("Root", "", None),
+            layout("Settings", "settings", Some("main")),
+            layout("Admin", "admin", None),
+        ];
+        let routes = vec![
+            parse_route_from_path_with_layouts(Path::new("src/routes/+screen.wh"), &layouts).unwrap(),
+            parse_route_from_path_with_layouts(Path::new("src/routes/settings/+screen.wh"), &layouts).unwrap(),
+            parse_route_from_path_with_layouts(Path::new("src/routes/login/+screen@auth.wh"), &layouts).unwrap(),
+        ];
+
+        let issues = validate_routes(&routes, &layouts);
+        assert_eq!(
+            issue_kinds(&issues),
+            vec!["missing-layout", "missing-layout", "orphan-layout"]
+        );
+        assert!(issues[0].message.contains("'@main'"));
+        assert!(issues[1].message.contains("'@auth'"));
+        assert!(issues[2].message.contains("AdminLayout"));
+        assert_eq!(issues[2].severity, IssueSeverity::Warning);
+    }
 }
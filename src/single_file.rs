@@ -4,7 +4,7 @@ use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::config::{AndroidConfig, BuildConfig, Config, FfiConfig, ProjectConfig, ToolchainConfig};
+use crate::config::{AndroidConfig, BuildConfig, Config, FetchConfig, FfiConfig, ProjectConfig, ToolchainConfig};
 
 /// Configuration extracted from single-file frontmatter
 #[derive(Debug, Deserialize)]
@@ -165,6 +165,7 @@ pub fn to_config(single_file_config: &SingleFileConfig, output_dir: &str) -> Con
             min_sdk: single_file_config.app.min_sdk,
             target_sdk: single_file_config.app.target_sdk,
             package: single_file_config.app.package.clone(),
+            permissions: Vec::new(),
         },
         build: BuildConfig {
             output_dir: output_dir.to_string(),
@@ -172,6 +173,10 @@ pub fn to_config(single_file_config: &SingleFileConfig, output_dir: &str) -> Con
         },
         toolchain: ToolchainConfig::default(),
         ffi: FfiConfig::default(),
+        fetch: FetchConfig::default(),
+        dependencies: Default::default(),
+        plugins: Default::default(),
+        repositories: Default::default(),
     }
 }
 
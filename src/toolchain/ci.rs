@@ -0,0 +1,187 @@
+//! Non-interactive mode for CI
+//!
+//! `--non-interactive` (or `CI=true`) replaces prompts with fixed retry
+//! policies, only accepts SDK licenses with `--accept-licenses`, and
+//! toolchain failures map to distinct exit codes (see `exit_code`).
+
+use indicatif::ProgressDrawTarget;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+static ACCEPT_LICENSES: AtomicBool = AtomicBool::new(false);
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_non_interactive(enabled: bool) {
+    NON_INTERACTIVE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn set_accept_licenses(accept: bool) {
+    ACCEPT_LICENSES.store(accept, Ordering::Relaxed);
+}
+
+/// Never prompt: `--non-interactive` or `CI=true`
+pub fn non_interactive() -> bool {
+    NON_INTERACTIVE.load(Ordering::Relaxed)
+        || std::env::var("CI").is_ok_and(|ci| ci.eq_ignore_ascii_case("true") || ci == "1")
+}
+
+/// Whether SDK licenses may be accepted on the user's behalf
+///
+/// Interactive runs accept them as before; CI has to opt in with `--accept-licenses`.
+pub fn accept_licenses() -> bool {
+    ACCEPT_LICENSES.load(Ordering::Relaxed) || !non_interactive()
+}
+
+/// Delay before retry `attempt` (1-based) in non-interactive mode: 2s, 4s, 8s, ...
+pub fn backoff(attempt: usize) -> Duration {
+    Duration::from_secs(2u64.saturating_pow(attempt.min(6) as u32))
+}
+
+/// Print progress as plain lines instead of bars (stdout is not a TTY)
+pub fn plain_progress() -> bool {
+    !io::stdout().is_terminal()
+}
+
+/// Where progress bars draw: hidden when progress is printed as plain lines
+pub fn progress_target() -> ProgressDrawTarget {
+    if plain_progress() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    }
+}
+
+/// A progress line for logs; bars already show this on a terminal
+pub fn progress_line(message: impl fmt::Display) {
+    if plain_progress() {
+        status(message);
+    }
+}
+
+/// Keep stdout for machine-readable output (`doctor --json`); status goes to stderr
+pub fn set_stdout_reserved(reserved: bool) {
+    STDOUT_RESERVED.store(reserved, Ordering::Relaxed);
+}
+
+pub fn stdout_reserved() -> bool {
+    STDOUT_RESERVED.load(Ordering::Relaxed)
+}
+
+/// Print a status line to stdout, or to stderr while stdout is reserved
+pub fn status(message: impl fmt::Display) {
+    if stdout_reserved() {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// Stdout for child processes whose output is status, not results
+pub fn child_stdout() -> Stdio {
+    if stdout_reserved() {
+        io::stderr().into()
+    } else {
+        Stdio::inherit()
+    }
+}
+
+/// Toolchain failures CI can tell apart by exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    Network,
+    Checksum,
+    DiskSpace,
+}
+
+impl FailureKind {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            FailureKind::Network => 3,
+            FailureKind::Checksum => 4,
+            FailureKind::DiskSpace => 5,
+        }
+    }
+}
+
+/// An error tagged with its `FailureKind`; displays as the wrapped error
+#[derive(Debug)]
+pub struct Failure {
+    pub kind: FailureKind,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for Failure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Tag `error` with a failure kind
+pub fn failure(kind: FailureKind, error: impl Into<anyhow::Error>) -> anyhow::Error {
+    anyhow::Error::new(Failure { kind, error: error.into() })
+}
+
+/// Classify an error by its chain: tagged failures, full disks and HTTP errors
+pub fn failure_kind(error: &anyhow::Error) -> Option<FailureKind> {
+    error.chain().find_map(|cause| {
+        if let Some(failure) = cause.downcast_ref::<Failure>() {
+            Some(failure.kind)
+        } else if let Some(io_error) = cause.downcast_ref::<io::Error>() {
+            // ENOSPC / EDQUOT
+            let full = io_error.kind() == io::ErrorKind::StorageFull
+                || matches!(io_error.raw_os_error(), Some(28) | Some(122));
+            full.then_some(FailureKind::DiskSpace)
+        } else if cause.is::<reqwest::Error>() {
+            Some(FailureKind::Network)
+        } else {
+            None
+        }
+    })
+}
+
+/// Process exit code for an error: 3 network, 4 checksum, 5 disk space, 1 otherwise
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    failure_kind(error).map_or(1, FailureKind::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_codes() {
+        let checksum = failure(FailureKind::Checksum, anyhow::anyhow!("Checksum mismatch"));
+        assert_eq!(checksum.to_string(), "Checksum mismatch");
+        assert_eq!(exit_code(&checksum), 4);
+
+        // Context added by callers doesn't hide the kind
+        let wrapped = Err::<(), _>(failure(FailureKind::Network, anyhow::anyhow!("timed out")))
+            .context("Failed to install Java 21")
+            .unwrap_err();
+        assert_eq!(exit_code(&wrapped), 3);
+
+        let disk = anyhow::Error::new(io::Error::from_raw_os_error(28)).context("Failed to extract");
+        assert_eq!(exit_code(&disk), 5);
+
+        assert_eq!(exit_code(&anyhow::anyhow!("Invalid package name")), 1);
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        assert_eq!(backoff(1), Duration::from_secs(2));
+        assert_eq!(backoff(2), Duration::from_secs(4));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+        assert_eq!(backoff(100), Duration::from_secs(64));
+    }
+}
@@ -5,24 +5,91 @@ use reqwest::blocking::Client;
 use std::fs::{self, File};
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
+use super::ci::{self, failure, FailureKind};
 use super::Platform;
 
 const MAX_RETRIES: usize = 3;
 
+/// Android command-line tools build (update as needed)
+pub const ANDROID_CMDLINE_TOOLS_VERSION: &str = "9477386";
+
+/// Mirror from `[toolchain] mirror`, registered when whitehall.toml is loaded
+static CONFIG_MIRROR: RwLock<Option<String>> = RwLock::new(None);
+
+/// Use `mirror` (a `file://` URL, local path or HTTP URL) for toolchain downloads
+pub fn set_mirror(mirror: Option<&str>) {
+    *CONFIG_MIRROR.write().unwrap() = mirror.map(str::to_string);
+}
+
+/// Toolchain mirror in effect: `WHITEHALL_MIRROR`, then `[toolchain] mirror`
+pub fn mirror() -> Option<String> {
+    std::env::var("WHITEHALL_MIRROR")
+        .ok()
+        .filter(|mirror| !mirror.is_empty())
+        .or_else(|| CONFIG_MIRROR.read().unwrap().clone())
+}
+
+/// `path` inside `mirror` if one is set, `upstream` otherwise
+///
+/// Mirrors don't copy upstream URL structure; each archive has a fixed path
+/// (`gradle/gradle-8.4-bin.zip`), so a directory of files is a valid mirror.
+fn mirrored(mirror: Option<&str>, path: &str, upstream: String) -> String {
+    match mirror {
+        Some(base) => format!("{}/{}", base.trim_end_matches('/'), path),
+        None => upstream,
+    }
+}
+
+/// File behind a `file://` URL or absolute path, as used by local mirrors
+fn local_path(url: &str) -> Option<PathBuf> {
+    match url.strip_prefix("file://") {
+        Some(path) => Some(PathBuf::from(path)),
+        None if url.starts_with('/') => Some(PathBuf::from(url)),
+        None => None,
+    }
+}
+
+/// Copy an archive from a local mirror
+fn copy_from_mirror(source: &Path, dest_path: &Path, pb: Option<&ProgressBar>) -> Result<()> {
+    if !source.is_file() {
+        anyhow::bail!("Not found in mirror: {}", source.display());
+    }
+
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let size = fs::copy(source, dest_path)
+        .with_context(|| format!("Failed to copy {} from mirror", source.display()))?;
+
+    if let Some(pb) = pb {
+        pb.set_length(size);
+        pb.set_position(size);
+    }
+
+    Ok(())
+}
+
 /// Download a file from URL with progress bar
 ///
-/// Returns the path to the downloaded file
+/// Returns the URL the file was served from, after redirects
 #[allow(dead_code)]
-pub fn download_with_progress(url: &str, dest_path: &Path) -> Result<PathBuf> {
+pub fn download_with_progress(url: &str, dest_path: &Path) -> Result<String> {
     download_with_progress_multi(url, dest_path, None)
 }
 
 /// Download a file from URL with progress bar, optionally attached to MultiProgress
 ///
-/// Returns the path to the downloaded file
-pub fn download_with_progress_multi(url: &str, dest_path: &Path, multi: Option<Arc<MultiProgress>>) -> Result<PathBuf> {
+/// Returns the URL the file was served from, after redirects; a floating URL
+/// like Adoptium's `latest` resolves to the exact artifact.
+pub fn download_with_progress_multi(url: &str, dest_path: &Path, multi: Option<Arc<MultiProgress>>) -> Result<String> {
+    if let Some(source) = local_path(url) {
+        copy_from_mirror(&source, dest_path, None)?;
+        return Ok(url.to_string());
+    }
+
     let client = Client::builder()
         .timeout(std::time::Duration::from_secs(600)) // 10 minute timeout
         .build()
@@ -34,18 +101,23 @@ pub fn download_with_progress_multi(url: &str, dest_path: &Path, multi: Option<A
         .with_context(|| format!("Failed to download from {}", url))?;
 
     if !response.status().is_success() {
-        anyhow::bail!("Download failed with status: {}", response.status());
+        return Err(failure(
+            FailureKind::Network,
+            anyhow::anyhow!("Download failed with status: {}", response.status()),
+        ));
     }
+    let final_url = response.url().to_string();
 
     // Get content length for progress bar
     let total_size = response.content_length().unwrap_or(0);
+    ci::progress_line(format!("Downloading {} ({})", file_name(dest_path), url));
 
     // Create progress bar with dim style matching other components
     let pb = if total_size > 0 {
         let pb = if let Some(ref m) = multi {
             m.add(ProgressBar::new(total_size))
         } else {
-            ProgressBar::new(total_size)
+            ProgressBar::with_draw_target(Some(total_size), ci::progress_target())
         };
         pb.set_style(
             ProgressStyle::default_bar()
@@ -60,7 +132,7 @@ pub fn download_with_progress_multi(url: &str, dest_path: &Path, multi: Option<A
         let pb = if let Some(ref m) = multi {
             m.add(ProgressBar::new_spinner())
         } else {
-            ProgressBar::new_spinner()
+            ProgressBar::with_draw_target(None, ci::progress_target())
         };
         let filename = dest_path.file_name().unwrap().to_str().unwrap();
         pb.set_message(format!("{} (unknown size)", filename.dimmed()));
@@ -98,27 +170,61 @@ pub fn download_with_progress_multi(url: &str, dest_path: &Path, multi: Option<A
     // Move temp file to final location
     fs::rename(&temp_path, dest_path)
         .with_context(|| format!("Failed to rename {} to {}", temp_path.display(), dest_path.display()))?;
+    ci::progress_line(format!("Downloaded {} ({} bytes)", file_name(dest_path), downloaded));
 
-    Ok(dest_path.to_path_buf())
+    Ok(final_url)
 }
 
 /// Download with automatic retry on failure
 ///
 /// Prompts user to retry if download fails
 /// Optionally verifies checksum if provided
-pub fn download_with_retry(url: &str, dest_path: &Path) -> Result<PathBuf> {
+pub fn download_with_retry(url: &str, dest_path: &Path) -> Result<String> {
     download_with_retry_and_checksum(url, dest_path, None, None)
 }
 
 /// Download with automatic retry on failure, with MultiProgress support
 #[allow(dead_code)]
-pub fn download_with_retry_multi(url: &str, dest_path: &Path, multi: Option<Arc<MultiProgress>>) -> Result<PathBuf> {
+pub fn download_with_retry_multi(url: &str, dest_path: &Path, multi: Option<Arc<MultiProgress>>) -> Result<String> {
     download_with_retry_and_checksum(url, dest_path, None, multi)
 }
 
 /// Download with retry and progress bar handle (caller manages the bar)
 /// Progress bar is updated from 0 to 100 during download, showing bytes downloaded
-pub fn download_with_retry_and_bar(url: &str, dest_path: &Path, pb: Option<&indicatif::ProgressBar>) -> Result<PathBuf> {
+///
+/// Parallel downloads can't prompt, so they only retry (with backoff) in non-interactive mode.
+pub fn download_with_retry_and_bar(url: &str, dest_path: &Path, pb: Option<&indicatif::ProgressBar>) -> Result<String> {
+    let mut attempt = 1;
+
+    loop {
+        match download_with_bar(url, dest_path, pb) {
+            Ok(final_url) => return Ok(final_url),
+            Err(e) if attempt < MAX_RETRIES && ci::non_interactive() => {
+                let delay = ci::backoff(attempt);
+                let message = format!(
+                    "Download of {} failed ({}), retrying in {}s",
+                    file_name(dest_path),
+                    e,
+                    delay.as_secs()
+                );
+                match pb {
+                    Some(pb) if !ci::plain_progress() => pb.println(message),
+                    _ => ci::status(message),
+                }
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn download_with_bar(url: &str, dest_path: &Path, pb: Option<&indicatif::ProgressBar>) -> Result<String> {
+    if let Some(source) = local_path(url) {
+        copy_from_mirror(&source, dest_path, pb)?;
+        return Ok(url.to_string());
+    }
+
     let client = Client::builder()
         .timeout(std::time::Duration::from_secs(600))
         .build()
@@ -130,10 +236,15 @@ pub fn download_with_retry_and_bar(url: &str, dest_path: &Path, pb: Option<&indi
         .with_context(|| format!("Failed to download from {}", url))?;
 
     if !response.status().is_success() {
-        anyhow::bail!("Download failed with status: {}", response.status());
+        return Err(failure(
+            FailureKind::Network,
+            anyhow::anyhow!("Download failed with status: {}", response.status()),
+        ));
     }
+    let final_url = response.url().to_string();
 
     let total_size = response.content_length().unwrap_or(0);
+    ci::progress_line(format!("Downloading {} ({})", file_name(dest_path), url));
 
     // Create parent directory
     if let Some(parent) = dest_path.parent() {
@@ -172,8 +283,9 @@ pub fn download_with_retry_and_bar(url: &str, dest_path: &Path, pb: Option<&indi
     // Move temp file to final location
     fs::rename(&temp_path, dest_path)
         .with_context(|| format!("Failed to rename {} to {}", temp_path.display(), dest_path.display()))?;
+    ci::progress_line(format!("Downloaded {} ({} bytes)", file_name(dest_path), downloaded));
 
-    Ok(dest_path.to_path_buf())
+    Ok(final_url)
 }
 
 /// Download with retry and optional checksum verification
@@ -188,53 +300,46 @@ pub fn download_with_retry_and_checksum(
     dest_path: &Path,
     expected_checksum: Option<&str>,
     multi: Option<Arc<MultiProgress>>
-) -> Result<PathBuf> {
+) -> Result<String> {
     let mut attempt = 1;
 
     loop {
         match download_with_progress_multi(url, dest_path, multi.clone()) {
-            Ok(path) => {
+            Ok(final_url) => {
                 // Verify checksum if provided
                 if let Some(expected) = expected_checksum {
-                    if let Err(e) = verify_checksum(&path, expected) {
+                    if let Err(e) = verify_checksum(dest_path, expected) {
                         eprintln!("\n{} {}", "error:".red().bold(), e);
 
                         // Clean up invalid file
-                        let _ = fs::remove_file(&path);
+                        let _ = fs::remove_file(dest_path);
 
                         if attempt >= MAX_RETRIES {
                             eprintln!("\n{} Maximum retry attempts reached.", "error:".red().bold());
-                            return Err(e);
+                            return Err(failure(FailureKind::Checksum, e));
                         }
 
-                        print!("\n{} Retry download? [Y/n]: ", "?".yellow().bold());
-                        io::stdout().flush()?;
-
-                        let mut input = String::new();
-                        io::stdin().read_line(&mut input)?;
-                        let input = input.trim().to_lowercase();
-
-                        if input == "n" || input == "no" {
-                            eprintln!("{} Download cancelled by user.", "info:".cyan());
-                            return Err(e);
+                        if !confirm_retry(attempt)? {
+                            return Err(failure(FailureKind::Checksum, e));
                         }
 
-                        println!("\n{} Retrying download...", "info:".cyan().bold());
                         attempt += 1;
                         continue;
                     }
                 }
 
-                return Ok(path);
+                return Ok(final_url);
             },
             Err(e) => {
                 let error_str = e.to_string().to_lowercase();
+                let mut kind = ci::failure_kind(&e);
 
                 // Provide specific error messages for common issues
                 eprintln!("\n{} Download failed (attempt {}/{})", "error:".red().bold(), attempt, MAX_RETRIES);
                 eprintln!();
 
                 if error_str.contains("no space left") || error_str.contains("disk quota") {
+                    kind = Some(FailureKind::DiskSpace);
                     eprintln!("Cause: Insufficient disk space");
                     eprintln!();
                     eprintln!("Suggestions:");
@@ -242,6 +347,7 @@ pub fn download_with_retry_and_checksum(
                     eprintln!("  - Clean package caches: yarn cache clean, pnpm store prune, uv cache clean");
                     eprintln!("  - Remove unused Docker images: docker system prune");
                 } else if error_str.contains("dns") || error_str.contains("resolve") || error_str.contains("name resolution") {
+                    kind = Some(FailureKind::Network);
                     eprintln!("Cause: DNS resolution failed - cannot resolve hostname");
                     eprintln!();
                     eprintln!("Suggestions:");
@@ -249,6 +355,7 @@ pub fn download_with_retry_and_checksum(
                     eprintln!("  - Verify DNS settings: cat /etc/resolv.conf");
                     eprintln!("  - Try alternate DNS: sudo systemctl restart systemd-resolved");
                 } else if error_str.contains("connection refused") || error_str.contains("connection reset") {
+                    kind = Some(FailureKind::Network);
                     eprintln!("Cause: Connection refused by server");
                     eprintln!();
                     eprintln!("Suggestions:");
@@ -256,12 +363,14 @@ pub fn download_with_retry_and_checksum(
                     eprintln!("  - Verify firewall settings");
                     eprintln!("  - Server may be temporarily unavailable");
                 } else if error_str.contains("timeout") || error_str.contains("timed out") {
+                    kind = Some(FailureKind::Network);
                     eprintln!("Cause: Connection timeout - server took too long to respond");
                     eprintln!();
                     eprintln!("Suggestions:");
                     eprintln!("  - Check internet connection speed");
                     eprintln!("  - Try again later (server may be under load)");
                 } else if error_str.contains("ssl") || error_str.contains("tls") || error_str.contains("certificate") {
+                    kind = Some(FailureKind::Network);
                     eprintln!("Cause: SSL/TLS certificate validation failed");
                     eprintln!();
                     eprintln!("Suggestions:");
@@ -277,27 +386,21 @@ pub fn download_with_retry_and_checksum(
                     eprintln!("Error: {}", e);
                 }
 
+                let e = match kind {
+                    Some(kind) => failure(kind, e),
+                    None => e,
+                };
+
                 if attempt >= MAX_RETRIES {
                     eprintln!();
                     eprintln!("{} Maximum retry attempts reached.", "error:".red().bold());
                     return Err(e);
                 }
 
-                // Ask user if they want to retry
-                eprintln!();
-                print!("{} Retry download? [Y/n]: ", "?".yellow().bold());
-                io::stdout().flush()?;
-
-                let mut input = String::new();
-                io::stdin().read_line(&mut input)?;
-                let input = input.trim().to_lowercase();
-
-                if input == "n" || input == "no" {
-                    eprintln!("{} Download cancelled by user.", "info:".cyan());
+                if !confirm_retry(attempt)? {
                     return Err(e);
                 }
 
-                println!("\n{} Retrying download...", "info:".cyan().bold());
                 attempt += 1;
 
                 // Clean up partial download if it exists
@@ -313,6 +416,48 @@ pub fn download_with_retry_and_checksum(
     }
 }
 
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string())
+}
+
+/// Decide whether to retry a failed download
+///
+/// Asks the user, or in non-interactive mode waits out an exponential backoff.
+fn confirm_retry(attempt: usize) -> Result<bool> {
+    if ci::non_interactive() {
+        let delay = ci::backoff(attempt);
+        ci::status(format!(
+            "{} Retrying download in {}s (attempt {}/{})...",
+            "info:".cyan().bold(),
+            delay.as_secs(),
+            attempt + 1,
+            MAX_RETRIES
+        ));
+        std::thread::sleep(delay);
+        return Ok(true);
+    }
+
+    let prompt = format!("\n{} Retry download? [Y/n]: ", "?".yellow().bold());
+    if ci::stdout_reserved() {
+        eprint!("{}", prompt);
+    } else {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+    }
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim().to_lowercase();
+
+    if input == "n" || input == "no" {
+        eprintln!("{} Download cancelled by user.", "info:".cyan());
+        return Ok(false);
+    }
+
+    ci::status(format!("\n{} Retrying download...", "info:".cyan().bold()));
+    Ok(true)
+}
+
 /// Extract a .tar.gz archive
 ///
 /// Used for Java and Gradle downloads
@@ -323,7 +468,7 @@ pub fn extract_tar_gz(archive_path: &Path, dest_dir: &Path) -> Result<()> {
 /// Extract a .tar.gz archive with optional progress display
 pub fn extract_tar_gz_with_progress(archive_path: &Path, dest_dir: &Path, show_progress: bool) -> Result<()> {
     if show_progress {
-        println!("Extracting {}...", archive_path.file_name().unwrap().to_str().unwrap());
+        ci::status(format!("Extracting {}...", archive_path.file_name().unwrap().to_str().unwrap()));
     }
 
     let tar_gz = File::open(archive_path)
@@ -375,7 +520,7 @@ pub fn extract_zip(archive_path: &Path, dest_dir: &Path) -> Result<()> {
 /// Extract a .zip archive with optional progress display
 pub fn extract_zip_with_progress(archive_path: &Path, dest_dir: &Path, show_progress: bool) -> Result<()> {
     if show_progress {
-        println!("Extracting {}...", archive_path.file_name().unwrap().to_str().unwrap());
+        ci::status(format!("Extracting {}...", archive_path.file_name().unwrap().to_str().unwrap()));
     }
 
     let file = File::open(archive_path)
@@ -458,12 +603,16 @@ pub fn verify_checksum(file_path: &Path, expected: &str) -> Result<()> {
         );
     }
 
-    println!("✓ Checksum verified");
+    ci::status("✓ Checksum verified");
     Ok(())
 }
 
 /// Get Java download URL for a specific version and platform
 pub fn get_java_download_url(version: &str, platform: Platform) -> Result<String> {
+    java_url(mirror().as_deref(), version, platform)
+}
+
+fn java_url(mirror: Option<&str>, version: &str, platform: Platform) -> Result<String> {
     let (os, arch) = platform.as_download_strings();
 
     // Adoptium/Temurin API
@@ -472,19 +621,29 @@ pub fn get_java_download_url(version: &str, platform: Platform) -> Result<String
         version, os, arch
     );
 
-    Ok(url)
+    Ok(mirrored(mirror, &format!("java/jdk-{}-{}-{}.tar.gz", version, os, arch), url))
 }
 
 /// Get Gradle download URL for a specific version
 pub fn get_gradle_download_url(version: &str) -> String {
-    format!(
-        "https://services.gradle.org/distributions/gradle-{}-bin.zip",
-        version
+    gradle_url(mirror().as_deref(), version)
+}
+
+fn gradle_url(mirror: Option<&str>, version: &str) -> String {
+    let file = format!("gradle-{}-bin.zip", version);
+    mirrored(
+        mirror,
+        &format!("gradle/{}", file),
+        format!("https://services.gradle.org/distributions/{}", file),
     )
 }
 
 /// Get Android cmdline-tools download URL for platform
 pub fn get_android_cmdline_tools_url(platform: Platform) -> Result<String> {
+    cmdline_tools_url(mirror().as_deref(), platform)
+}
+
+fn cmdline_tools_url(mirror: Option<&str>, platform: Platform) -> Result<String> {
     let os_name = if platform.is_linux() {
         "linux"
     } else if platform.is_macos() {
@@ -493,12 +652,13 @@ pub fn get_android_cmdline_tools_url(platform: Platform) -> Result<String> {
         anyhow::bail!("Unsupported platform for Android SDK");
     };
 
-    // Latest version number (update as needed)
-    let version = "9477386";
+    let version = ANDROID_CMDLINE_TOOLS_VERSION;
 
-    Ok(format!(
-        "https://dl.google.com/android/repository/commandlinetools-{}-{}_latest.zip",
-        os_name, version
+    let file = format!("commandlinetools-{}-{}_latest.zip", os_name, version);
+    Ok(mirrored(
+        mirror,
+        &format!("android/{}", file),
+        format!("https://dl.google.com/android/repository/{}", file),
     ))
 }
 
@@ -506,6 +666,10 @@ pub fn get_android_cmdline_tools_url(platform: Platform) -> Result<String> {
 ///
 /// CMake is required for building native C++ code with the Android NDK
 pub fn get_cmake_download_url(version: &str, platform: Platform) -> Result<String> {
+    cmake_url(mirror().as_deref(), version, platform)
+}
+
+fn cmake_url(mirror: Option<&str>, version: &str, platform: Platform) -> Result<String> {
     // Map platform to CMake platform strings
     let (os_name, arch_name) = match platform {
         Platform::LinuxX64 => ("linux", "x86_64"),
@@ -516,12 +680,43 @@ pub fn get_cmake_download_url(version: &str, platform: Platform) -> Result<Strin
 
     // CMake download URLs from cmake.org
     // Format: https://github.com/Kitware/CMake/releases/download/v3.28.1/cmake-3.28.1-linux-x86_64.tar.gz
-    Ok(format!(
-        "https://github.com/Kitware/CMake/releases/download/v{}/cmake-{}-{}-{}.tar.gz",
-        version, version, os_name, arch_name
+    let file = format!("cmake-{}-{}-{}.tar.gz", version, os_name, arch_name);
+    Ok(mirrored(
+        mirror,
+        &format!("cmake/{}", file),
+        format!("https://github.com/Kitware/CMake/releases/download/v{}/{}", version, file),
     ))
 }
 
+/// Fail before sdkmanager tries to download `packages` while a mirror is set
+///
+/// The mirror only covers Java, Gradle, cmdline-tools and CMake. Platforms,
+/// build-tools, the NDK and system images come from sdkmanager, which always
+/// talks to dl.google.com, so on a mirrored (usually offline) machine they
+/// have to be installed already.
+pub fn check_sdk_download(packages: &[&str]) -> Result<()> {
+    sdk_download_allowed(mirror().as_deref(), packages)
+}
+
+fn sdk_download_allowed(mirror: Option<&str>, packages: &[&str]) -> Result<()> {
+    let Some(mirror) = mirror else {
+        return Ok(());
+    };
+    if packages.is_empty() {
+        return Ok(());
+    }
+
+    anyhow::bail!(
+        "Android SDK package(s) not installed: {}\n\
+         The toolchain mirror ({}) only provides Java, Gradle, cmdline-tools and CMake; \
+         SDK packages are downloaded by sdkmanager from dl.google.com.\n\
+         Install them on a connected machine and bring them over with `whitehall toolchain pack`, \
+         or unset the mirror.",
+        packages.join(", "),
+        mirror
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -560,4 +755,78 @@ mod tests {
         assert!(url_mac.contains("macos"));
         assert!(url_mac.contains("universal"));
     }
+
+    #[test]
+    fn test_mirrored_urls() {
+        let upstream = "https://services.gradle.org/distributions/gradle-8.4-bin.zip".to_string();
+        assert_eq!(mirrored(None, "gradle/gradle-8.4-bin.zip", upstream.clone()), upstream);
+        assert_eq!(
+            mirrored(Some("file:///srv/mirror/"), "gradle/gradle-8.4-bin.zip", upstream.clone()),
+            "file:///srv/mirror/gradle/gradle-8.4-bin.zip"
+        );
+        assert_eq!(
+            mirrored(Some("https://mirror.internal/whitehall"), "gradle/gradle-8.4-bin.zip", upstream),
+            "https://mirror.internal/whitehall/gradle/gradle-8.4-bin.zip"
+        );
+
+        assert_eq!(local_path("file:///srv/mirror/a.zip"), Some(PathBuf::from("/srv/mirror/a.zip")));
+        assert_eq!(local_path("/srv/mirror/a.zip"), Some(PathBuf::from("/srv/mirror/a.zip")));
+        assert_eq!(local_path("https://mirror.internal/a.zip"), None);
+    }
+
+    #[test]
+    fn test_mirror_rewrites_toolchain_urls_only() {
+        let base = Some("https://mirror.internal/whitehall");
+        let platform = Platform::LinuxX64;
+
+        assert_eq!(
+            java_url(base, "21", platform).unwrap(),
+            "https://mirror.internal/whitehall/java/jdk-21-linux-x64.tar.gz"
+        );
+        assert_eq!(gradle_url(base, "8.4"), "https://mirror.internal/whitehall/gradle/gradle-8.4-bin.zip");
+        assert_eq!(
+            cmdline_tools_url(base, platform).unwrap(),
+            format!(
+                "https://mirror.internal/whitehall/android/commandlinetools-linux-{}_latest.zip",
+                ANDROID_CMDLINE_TOOLS_VERSION
+            )
+        );
+        assert_eq!(
+            cmake_url(base, "3.28.1", platform).unwrap(),
+            "https://mirror.internal/whitehall/cmake/cmake-3.28.1-linux-x86_64.tar.gz"
+        );
+
+        // Without a mirror every URL is upstream
+        assert!(java_url(None, "21", platform).unwrap().starts_with("https://api.adoptium.net/"));
+        assert!(gradle_url(None, "8.4").starts_with("https://services.gradle.org/"));
+        assert!(cmdline_tools_url(None, platform).unwrap().starts_with("https://dl.google.com/"));
+        assert!(cmake_url(None, "3.28.1", platform).unwrap().starts_with("https://github.com/Kitware/"));
+
+        // sdkmanager packages can't come from the mirror
+        let packages = ["platforms;android-34", "ndk;26.1.10909125"];
+        assert!(sdk_download_allowed(None, &packages).is_ok());
+        assert!(sdk_download_allowed(base, &[]).is_ok());
+        let err = sdk_download_allowed(base, &packages).unwrap_err().to_string();
+        assert!(err.contains("platforms;android-34, ndk;26.1.10909125"));
+        assert!(err.contains("dl.google.com"));
+    }
+
+    #[test]
+    fn test_download_from_local_mirror() {
+        let mirror = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        fs::create_dir_all(mirror.path().join("gradle")).unwrap();
+        fs::write(mirror.path().join("gradle/gradle-8.4-bin.zip"), "zip").unwrap();
+
+        let base = format!("file://{}", mirror.path().display());
+        let url = mirrored(Some(&base), "gradle/gradle-8.4-bin.zip", String::new());
+        let archive = dest.path().join("gradle/gradle-8.4.zip");
+
+        assert_eq!(download_with_progress_multi(&url, &archive, None).unwrap(), url);
+        assert_eq!(fs::read_to_string(&archive).unwrap(), "zip");
+
+        let missing = mirrored(Some(&base), "gradle/gradle-9.0-bin.zip", String::new());
+        let err = download_with_progress_multi(&missing, &archive, None).unwrap_err();
+        assert!(err.to_string().contains("Not found in mirror"));
+    }
 }
@@ -0,0 +1,73 @@
+//! Cross-process locks around toolchain installs
+//!
+//! Parallel CI jobs sharing a toolchain root would otherwise download and
+//! extract the same version on top of each other. Locks live in
+//! `<root>/.locks/<name>.lock` and are released when the guard is dropped.
+
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::Path;
+
+/// Held while installing; dropping it releases the lock
+pub struct InstallLock {
+    _file: File,
+}
+
+/// Lock `name` (e.g. `java-21`, `android`) in `root`, waiting for other processes
+pub fn acquire(root: &Path, name: &str) -> Result<InstallLock> {
+    let dir = root.join(".locks");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let path = dir.join(format!("{}.lock", name));
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            eprintln!("Waiting for another whitehall process to finish installing {}...", name);
+            file.lock().with_context(|| format!("Failed to lock {}", path.display()))?;
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+        }
+    }
+
+    Ok(InstallLock { _file: file })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_install_lock_excludes_other_holders() {
+        let root = tempfile::tempdir().unwrap();
+        let guard = acquire(root.path(), "java-21").unwrap();
+
+        let released = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let root = root.path().to_path_buf();
+            let released = Arc::clone(&released);
+            std::thread::spawn(move || {
+                let _guard = acquire(&root, "java-21").unwrap();
+                assert!(released.load(Ordering::SeqCst), "lock acquired while still held");
+            })
+        };
+
+        // Other names aren't blocked
+        drop(acquire(root.path(), "gradle-8.4").unwrap());
+
+        std::thread::sleep(Duration::from_millis(100));
+        released.store(true, Ordering::SeqCst);
+        drop(guard);
+        waiter.join().unwrap();
+    }
+}
@@ -0,0 +1,429 @@
+//! whitehall.lock: checksums of every toolchain archive and SDK package
+//!
+//! The lock is written next to whitehall.toml by `whitehall toolchain install`.
+//! While a lock is active, every archive download and every sdkmanager install
+//! is checked against it, and anything not yet locked is added.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::ci::{failure, FailureKind};
+use super::Platform;
+
+pub const LOCKFILE: &str = "whitehall.lock";
+
+const HEADER: &str = "# This file is generated by `whitehall toolchain install`. Do not edit it by hand.\n\
+                      # Run `whitehall toolchain update` to refresh it.\n\n";
+
+/// Lockfile checked by downloads in this process, registered by `set_lockfile`
+///
+/// The mutex is held across read-modify-write so parallel downloads don't race.
+static ACTIVE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "archive")]
+    pub archives: Vec<LockedArchive>,
+    #[serde(default, rename = "sdk-package")]
+    pub sdk_packages: Vec<LockedPackage>,
+}
+
+/// A downloaded archive (Java, Gradle, cmdline-tools, CMake)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedArchive {
+    pub name: String,
+    pub version: String,
+    pub platform: String,
+    pub url: String,
+    pub sha256: String,
+}
+
+/// An sdkmanager package, hashed after installation (see `hash_tree`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub revision: String,
+    pub platform: String,
+    pub sha256: String,
+}
+
+impl Lockfile {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string(self).context("Failed to serialize lockfile")?;
+        fs::write(path, format!("{}{}", HEADER, content))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Check an archive against the lock, adding it if it isn't locked yet
+    pub fn check_archive(&mut self, archive: LockedArchive) -> Result<()> {
+        let locked = self.archives.iter().find(|a| {
+            a.name == archive.name && a.version == archive.version && a.platform == archive.platform
+        });
+
+        match locked {
+            Some(locked) if locked.sha256 != archive.sha256 => Err(failure(
+                FailureKind::Checksum,
+                anyhow::anyhow!(
+                    "{} {} does not match {}\n  Locked:     {} ({})\n  Downloaded: {} ({})\n\
+                     Run `whitehall toolchain update` if this change is expected",
+                    archive.name, archive.version, LOCKFILE,
+                    locked.sha256, locked.url,
+                    archive.sha256, archive.url
+                ),
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.archives.push(archive);
+                self.archives.sort_by(|a, b| {
+                    (&a.name, &a.version, &a.platform).cmp(&(&b.name, &b.version, &b.platform))
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Check an installed SDK package against the lock, adding it if it isn't locked yet
+    pub fn check_package(&mut self, package: LockedPackage) -> Result<()> {
+        let locked = self
+            .sdk_packages
+            .iter()
+            .find(|p| p.name == package.name && p.platform == package.platform);
+
+        match locked {
+            Some(locked) if locked.sha256 != package.sha256 || locked.revision != package.revision => Err(failure(
+                FailureKind::Checksum,
+                anyhow::anyhow!(
+                    "SDK package '{}' does not match {}\n  Locked:    revision {} ({})\n  Installed: revision {} ({})\n\
+                     Run `whitehall toolchain update` if this change is expected",
+                    package.name, LOCKFILE,
+                    locked.revision, locked.sha256,
+                    package.revision, package.sha256
+                ),
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.sdk_packages.push(package);
+                self.sdk_packages.sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Check toolchain installs against `path` (or stop checking with `None`)
+pub fn set_lockfile(path: Option<PathBuf>) {
+    *ACTIVE.lock().unwrap() = path;
+}
+
+/// Load, update and save the active lockfile; does nothing without one
+fn with_active(update: impl FnOnce(&mut Lockfile) -> Result<()>) -> Result<()> {
+    let active = ACTIVE.lock().unwrap();
+    let Some(path) = active.as_ref() else {
+        return Ok(());
+    };
+
+    let mut lock = Lockfile::load(path)?;
+    update(&mut lock)?;
+    lock.save(path)
+}
+
+/// Whether the active lockfile has an entry for this archive on this platform
+pub fn is_archive_locked(name: &str, version: &str) -> Result<bool> {
+    let active = ACTIVE.lock().unwrap();
+    let Some(path) = active.as_ref() else {
+        return Ok(false);
+    };
+
+    let platform = Platform::detect()?.to_string();
+    Ok(Lockfile::load(path)?
+        .archives
+        .iter()
+        .any(|a| a.name == name && a.version == version && a.platform == platform))
+}
+
+/// URL to download an archive from
+///
+/// A locked archive is fetched from its recorded URL, so a floating upstream URL
+/// (Adoptium's `latest`) can't silently move to a newer build. A configured
+/// mirror still takes precedence; the checksum is verified either way.
+pub fn archive_url(name: &str, version: &str, url: String) -> Result<String> {
+    if super::downloader::mirror().is_some() {
+        return Ok(url);
+    }
+
+    let active = ACTIVE.lock().unwrap();
+    let Some(path) = active.as_ref() else {
+        return Ok(url);
+    };
+
+    let platform = Platform::detect()?.to_string();
+    Ok(Lockfile::load(path)?
+        .archives
+        .into_iter()
+        .find(|a| a.name == name && a.version == version && a.platform == platform)
+        .map_or(url, |a| a.url))
+}
+
+/// Verify a downloaded archive before it is extracted
+///
+/// `url` is where the archive was actually served from (after redirects); it is
+/// recorded for archives not yet locked.
+///
+/// A mismatch deletes the archive and fails; the lock is never silently rewritten.
+pub fn check_archive(name: &str, version: &str, url: &str, archive_path: &Path) -> Result<()> {
+    let archive = LockedArchive {
+        name: name.to_string(),
+        version: version.to_string(),
+        platform: Platform::detect()?.to_string(),
+        url: url.to_string(),
+        sha256: sha256_file(archive_path)?,
+    };
+
+    with_active(|lock| lock.check_archive(archive)).inspect_err(|_| {
+        let _ = fs::remove_file(archive_path);
+    })
+}
+
+/// Verify an SDK package just installed (or already installed) by sdkmanager
+///
+/// A mismatch removes the package so it can't be used by a build.
+pub fn check_sdk_package(sdk_root: &Path, package: &str) -> Result<()> {
+    let package_dir = sdk_package_dir(sdk_root, package);
+
+    with_active(|lock| {
+        let installed = LockedPackage {
+            name: package.to_string(),
+            revision: package_revision(&package_dir)?,
+            platform: Platform::detect()?.to_string(),
+            sha256: hash_tree(&package_dir)?,
+        };
+        lock.check_package(installed).inspect_err(|_| {
+            let _ = fs::remove_dir_all(&package_dir);
+        })
+    })
+}
+
+/// Refuse to install a locked SDK package sdkmanager would install at another revision
+///
+/// sdkmanager always installs the newest revision of a package, so it can't be
+/// pinned to the locked one. `list` runs `sdkmanager --list`, only for locked packages.
+pub fn check_sdk_available(package: &str, list: impl FnOnce() -> Result<String>) -> Result<()> {
+    let locked = {
+        let active = ACTIVE.lock().unwrap();
+        let Some(path) = active.as_ref() else {
+            return Ok(());
+        };
+        let platform = Platform::detect()?.to_string();
+        Lockfile::load(path)?
+            .sdk_packages
+            .into_iter()
+            .find(|p| p.name == package && p.platform == platform)
+    };
+    let Some(locked) = locked else {
+        return Ok(());
+    };
+
+    match available_revision(&list()?, package) {
+        Some(revision) if revision == locked.revision => Ok(()),
+        revision => Err(failure(
+            FailureKind::Checksum,
+            anyhow::anyhow!(
+                "SDK package '{}' is locked at revision {} in {}, but sdkmanager would install {}\n\
+                 Run `whitehall toolchain update` to lock the available revision",
+                package, locked.revision, LOCKFILE,
+                revision.map_or("nothing (not available)".to_string(), |r| format!("revision {}", r))
+            ),
+        )),
+    }
+}
+
+/// Revision of `package` under "Available Packages" in `sdkmanager --list` output
+fn available_revision(list: &str, package: &str) -> Option<String> {
+    list.lines()
+        .skip_while(|line| !line.trim_start().starts_with("Available Packages"))
+        .find_map(|line| {
+            let mut columns = line.split('|').map(str::trim);
+            (columns.next() == Some(package)).then(|| columns.next().map(String::from)).flatten()
+        })
+}
+
+/// Directory of an sdkmanager package: `platforms;android-34` -> `platforms/android-34`
+pub fn sdk_package_dir(sdk_root: &Path, package: &str) -> PathBuf {
+    package.split(';').fold(sdk_root.to_path_buf(), |dir, part| dir.join(part))
+}
+
+/// `Pkg.Revision` from the package's source.properties
+fn package_revision(package_dir: &Path) -> Result<String> {
+    let properties = package_dir.join("source.properties");
+    let content = fs::read_to_string(&properties)
+        .with_context(|| format!("Failed to read {}", properties.display()))?;
+
+    Ok(content
+        .lines()
+        .find_map(|line| line.strip_prefix("Pkg.Revision="))
+        .unwrap_or("unknown")
+        .trim()
+        .to_string())
+}
+
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// SHA-256 over an installed directory: relative paths, contents, symlink
+/// targets and executable bits, in sorted order so every machine agrees
+pub fn hash_tree(dir: &Path) -> Result<String> {
+    fn walk(root: &Path, dir: &Path, hasher: &mut Sha256) -> Result<()> {
+        let mut entries: Vec<_> = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read {}", dir.display()))?
+            .collect::<io::Result<_>>()?;
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/");
+            let file_type = entry.file_type()?;
+
+            if file_type.is_symlink() {
+                hasher.update(format!("link {} {}\n", relative, fs::read_link(&path)?.display()));
+            } else if file_type.is_dir() {
+                hasher.update(format!("dir {}\n", relative));
+                walk(root, &path, hasher)?;
+            } else {
+                hasher.update(format!("file {} {}\n", relative, is_executable(&entry.metadata()?)));
+                io::copy(&mut File::open(&path)?, hasher)?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut hasher = Sha256::new();
+    walk(dir, dir, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive(sha256: &str) -> LockedArchive {
+        LockedArchive {
+            name: "java".to_string(),
+            version: "21".to_string(),
+            platform: "linux-x64".to_string(),
+            url: "https://api.adoptium.net/v3/binary/latest/21/ga/linux/x64/jdk/hotspot/normal/eclipse".to_string(),
+            sha256: sha256.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_lockfile_records_then_verifies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCKFILE);
+
+        let mut lock = Lockfile::load(&path).unwrap();
+        lock.check_archive(archive("aaa")).unwrap();
+        lock.save(&path).unwrap();
+
+        let mut lock = Lockfile::load(&path).unwrap();
+        assert_eq!(lock.archives, vec![archive("aaa")]);
+        lock.check_archive(archive("aaa")).unwrap();
+
+        let err = lock.check_archive(archive("bbb")).unwrap_err();
+        assert!(err.to_string().contains("java 21 does not match whitehall.lock"));
+        assert_eq!(crate::toolchain::ci::exit_code(&err), 4);
+
+        // Other platforms are locked independently
+        let mut mac = archive("ccc");
+        mac.platform = "mac-aarch64".to_string();
+        lock.check_archive(mac).unwrap();
+        assert_eq!(lock.archives.len(), 2);
+    }
+
+    #[test]
+    fn test_sdk_package_revision_change_fails() {
+        let package = |revision: &str| LockedPackage {
+            name: "platforms;android-34".to_string(),
+            revision: revision.to_string(),
+            platform: "linux-x64".to_string(),
+            sha256: "aaa".to_string(),
+        };
+
+        let mut lock = Lockfile::default();
+        lock.check_package(package("3")).unwrap();
+        lock.check_package(package("3")).unwrap();
+        assert!(lock.check_package(package("4")).is_err());
+    }
+
+    #[test]
+    fn test_hash_tree_is_stable() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        for dir in [a.path(), b.path()] {
+            fs::create_dir_all(dir.join("bin")).unwrap();
+            fs::write(dir.join("bin/adb"), "adb").unwrap();
+            fs::write(dir.join("source.properties"), "Pkg.Revision=34.0.5\n").unwrap();
+        }
+        assert_eq!(hash_tree(a.path()).unwrap(), hash_tree(b.path()).unwrap());
+        assert_eq!(package_revision(a.path()).unwrap(), "34.0.5");
+
+        fs::write(b.path().join("bin/adb"), "patched").unwrap();
+        assert_ne!(hash_tree(a.path()).unwrap(), hash_tree(b.path()).unwrap());
+    }
+
+    #[test]
+    fn test_available_revision() {
+        let list = "\
+Installed Packages:
+  Path                 | Version | Description                | Location
+  -------              | ------- | -------                    | -------
+  platform-tools       | 34.0.5  | Android SDK Platform-Tools | platform-tools
+
+Available Packages:
+  Path                 | Version | Description
+  -------              | ------- | -------
+  platform-tools       | 35.0.2  | Android SDK Platform-Tools
+  platforms;android-34 | 3       | Android SDK Platform 34
+";
+        assert_eq!(available_revision(list, "platform-tools").as_deref(), Some("35.0.2"));
+        assert_eq!(available_revision(list, "platforms;android-34").as_deref(), Some("3"));
+        assert_eq!(available_revision(list, "emulator"), None);
+    }
+
+    #[test]
+    fn test_sdk_package_dir() {
+        assert_eq!(
+            sdk_package_dir(Path::new("/sdk"), "system-images;android-34;google_apis;x86_64"),
+            Path::new("/sdk/system-images/android-34/google_apis/x86_64")
+        );
+    }
+}
@@ -1,42 +1,140 @@
+pub mod ci;
 mod defaults;
 mod downloader;
+mod install_lock;
+mod lockfile;
+mod pack;
 mod platform;
+pub mod usage;
 pub mod validator;
 
 pub use defaults::*;
+pub use downloader::{mirror, set_mirror};
+pub use lockfile::{set_lockfile, LOCKFILE};
 pub use platform::Platform;
+pub use usage::{dir_size, Usage};
 pub use validator::validate_compatibility;
 
 use anyhow::{Context, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+
+/// Shared, read-only toolchains checked before the user root
+pub const SYSTEM_ROOT: &str = "/opt/whitehall/toolchains";
+
+/// Toolchain root from `[toolchain] root` in whitehall.toml, registered by `load_config`
+static CONFIG_ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Use `root` instead of ~/.whitehall/toolchains (`WHITEHALL_HOME` still wins)
+pub fn set_root(root: Option<PathBuf>) {
+    *CONFIG_ROOT.write().unwrap() = root;
+}
 
 /// Core toolchain manager for Whitehall
 ///
 /// Manages Java, Gradle, and Android SDK installations in ~/.whitehall/toolchains/
 /// Each project specifies required versions in [toolchain] section of whitehall.toml
+///
+/// Lookups are layered: a version present in the system root (`SYSTEM_ROOT`
+/// or `WHITEHALL_SYSTEM_ROOT`) is used from there, anything missing is
+/// installed into the user root. The system root is never written to.
 #[derive(Clone)]
 pub struct Toolchain {
     root: PathBuf,
+    system_root: Option<PathBuf>,
 }
 
 impl Toolchain {
     /// Create new toolchain manager
     ///
-    /// Uses ~/.whitehall/toolchains/ as the root directory for all toolchains
+    /// Installs into `$WHITEHALL_HOME/toolchains`, the `[toolchain] root` from
+    /// whitehall.toml, or ~/.whitehall/toolchains/, in that order
     pub fn new() -> Result<Self> {
+        let root = Self::user_root()?;
+
+        // Ensure root directory exists
+        std::fs::create_dir_all(&root)
+            .with_context(|| format!("Failed to create toolchain directory {}", root.display()))?;
+
+        let system_root = std::env::var_os("WHITEHALL_SYSTEM_ROOT")
+            .filter(|dir| !dir.is_empty())
+            .map_or_else(|| PathBuf::from(SYSTEM_ROOT), PathBuf::from);
+        let system_root = (system_root.is_dir() && system_root != root).then_some(system_root);
+
+        Ok(Self { root, system_root })
+    }
+
+    fn user_root() -> Result<PathBuf> {
+        if let Some(home) = std::env::var_os("WHITEHALL_HOME").filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(home).join("toolchains"));
+        }
+        if let Some(root) = CONFIG_ROOT.read().unwrap().clone() {
+            return Ok(root);
+        }
+
         let home = dirs::home_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+        Ok(home.join(".whitehall").join("toolchains"))
+    }
 
-        let root = home.join(".whitehall").join("toolchains");
+    /// `relative` in the system root, if it is installed there
+    fn system_path(&self, relative: &str) -> Option<PathBuf> {
+        let path = self.system_root.as_ref()?.join(relative);
+        path.exists().then_some(path)
+    }
 
-        // Ensure root directory exists
-        std::fs::create_dir_all(&root)
-            .context("Failed to create toolchain directory")?;
+    /// `relative` (e.g. `java/21`) if it is installed in either root, without installing it
+    pub fn installed(&self, relative: &str) -> Option<PathBuf> {
+        let path = self.resolve(relative);
+        path.exists().then_some(path)
+    }
+
+    /// `relative` from the system root if present, else from the user root
+    fn resolve(&self, relative: &str) -> PathBuf {
+        self.system_path(relative).unwrap_or_else(|| self.root.join(relative))
+    }
+
+    /// Resolve `relative`, running `install` under an install lock if neither root has it
+    fn ensure_installed(&self, relative: &str, install: impl FnOnce() -> Result<()>) -> Result<PathBuf> {
+        let path = self.resolve(relative);
+        if !path.exists() {
+            let _lock = install_lock::acquire(&self.root, &relative.replace('/', "-"))?;
+            // Another process may have finished the install while we waited
+            if !path.exists() {
+                install()?;
+            }
+        }
+        Ok(path)
+    }
+
+    /// Empty directory to download and extract `relative` (e.g. `java/21`) into
+    ///
+    /// Installs are assembled here and renamed into place by `finish_install`, so
+    /// `<root>/<relative>` only exists once it is complete. The caller holds the
+    /// install lock, so anything already here is left over from an interrupted install.
+    fn staging_dir(&self, relative: &str) -> Result<PathBuf> {
+        let staging = self.root.join(".staging").join(relative.replace('/', "-"));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)
+                .with_context(|| format!("Failed to remove {}", staging.display()))?;
+        }
+        fs::create_dir_all(&staging)
+            .with_context(|| format!("Failed to create {}", staging.display()))?;
+        Ok(staging)
+    }
 
-        Ok(Self { root })
+    /// Rename `staged` (inside `staging`) to `<root>/<relative>`, then remove `staging`
+    fn finish_install(&self, staging: &Path, staged: &Path, relative: &str) -> Result<()> {
+        let target = self.root.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(staged, &target)
+            .with_context(|| format!("Failed to move {} to {}", staged.display(), target.display()))?;
+        fs::remove_dir_all(staging)
+            .with_context(|| format!("Failed to remove {}", staging.display()))
     }
 
     /// Ensure Java is installed for the given version
@@ -46,12 +144,7 @@ impl Toolchain {
     /// # Arguments
     /// * `version` - Java version (e.g., "11", "17", "21")
     pub fn ensure_java(&self, version: &str) -> Result<PathBuf> {
-        let java_home = self.root.join(format!("java/{}", version));
-
-        if !java_home.exists() {
-            // Download Java
-            self.download_java(version)?;
-        }
+        let java_home = self.ensure_installed(&format!("java/{}", version), || self.download_java(version))?;
 
         // Verify java binary exists
         let java_bin = if cfg!(target_os = "macos") {
@@ -68,6 +161,8 @@ impl Toolchain {
             );
         }
 
+        self.record_use(&format!("java/{}", version));
+
         // Return JAVA_HOME path (not bin/java)
         if cfg!(target_os = "macos") {
             Ok(java_home.join("Contents/Home"))
@@ -79,35 +174,22 @@ impl Toolchain {
     /// Download and install Java
     fn download_java(&self, version: &str) -> Result<()> {
         let platform = Platform::detect()?;
-        let url = downloader::get_java_download_url(version, platform)?;
+        let url = lockfile::archive_url("java", version, downloader::get_java_download_url(version, platform)?)?;
 
-        let java_dir = self.root.join("java");
-        std::fs::create_dir_all(&java_dir)?;
+        let relative = format!("java/{}", version);
+        let staging = self.staging_dir(&relative)?;
 
         // Download to temporary file
-        let archive_path = java_dir.join(format!("java-{}.tar.gz", version));
-        downloader::download_with_retry(&url, &archive_path)?;
+        let archive_path = staging.join(format!("java-{}.tar.gz", version));
+        let url = downloader::download_with_retry(&url, &archive_path)?;
+        lockfile::check_archive("java", version, &url, &archive_path)?;
 
         // Extract
-        downloader::extract_tar_gz(&archive_path, &java_dir)?;
+        downloader::extract_tar_gz(&archive_path, &staging)?;
 
-        // The extracted directory structure varies, need to rename properly
         // Adoptium extracts to jdk-VERSION/ directory
-        let extracted_dirs: Vec<_> = std::fs::read_dir(&java_dir)?
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_dir())
-            .filter(|e| e.file_name().to_str().unwrap().starts_with("jdk-"))
-            .collect();
-
-        if let Some(extracted_dir) = extracted_dirs.first() {
-            let target = java_dir.join(version);
-            std::fs::rename(extracted_dir.path(), &target)?;
-        }
-
-        // Clean up archive
-        std::fs::remove_file(&archive_path)?;
-
-        Ok(())
+        let extracted = extracted_dir(&staging, "jdk-")?;
+        self.finish_install(&staging, &extracted, &relative)
     }
 
     /// Ensure Gradle is installed for the given version
@@ -117,11 +199,7 @@ impl Toolchain {
     /// # Arguments
     /// * `version` - Gradle version (e.g., "7.6", "8.0", "8.4")
     pub fn ensure_gradle(&self, version: &str) -> Result<PathBuf> {
-        let gradle_home = self.root.join(format!("gradle/{}", version));
-
-        if !gradle_home.exists() {
-            self.download_gradle(version)?;
-        }
+        let gradle_home = self.ensure_installed(&format!("gradle/{}", version), || self.download_gradle(version))?;
 
         let gradle_bin = gradle_home.join("bin/gradle");
 
@@ -133,34 +211,29 @@ impl Toolchain {
             );
         }
 
+        self.record_use(&format!("gradle/{}", version));
+
         Ok(gradle_bin)
     }
 
     /// Download and install Gradle
     fn download_gradle(&self, version: &str) -> Result<()> {
-        let url = downloader::get_gradle_download_url(version);
+        let url = lockfile::archive_url("gradle", version, downloader::get_gradle_download_url(version))?;
 
-        let gradle_dir = self.root.join("gradle");
-        std::fs::create_dir_all(&gradle_dir)?;
+        let relative = format!("gradle/{}", version);
+        let staging = self.staging_dir(&relative)?;
 
         // Download to temporary file
-        let archive_path = gradle_dir.join(format!("gradle-{}.zip", version));
-        downloader::download_with_retry(&url, &archive_path)?;
+        let archive_path = staging.join(format!("gradle-{}.zip", version));
+        let url = downloader::download_with_retry(&url, &archive_path)?;
+        lockfile::check_archive("gradle", version, &url, &archive_path)?;
 
         // Extract
-        downloader::extract_zip(&archive_path, &gradle_dir)?;
+        downloader::extract_zip(&archive_path, &staging)?;
 
         // Gradle extracts to gradle-VERSION/ directory, rename to VERSION/
-        let extracted = gradle_dir.join(format!("gradle-{}", version));
-        let target = gradle_dir.join(version);
-        if extracted.exists() {
-            std::fs::rename(&extracted, &target)?;
-        }
-
-        // Clean up archive
-        std::fs::remove_file(&archive_path)?;
-
-        Ok(())
+        let extracted = extracted_dir(&staging, &format!("gradle-{}", version))?;
+        self.finish_install(&staging, &extracted, &relative)
     }
 
     /// Ensure Android SDK is installed
@@ -171,10 +244,24 @@ impl Toolchain {
     /// # Arguments
     /// * `include_emulator` - Whether to include emulator components
     fn ensure_android_sdk_internal(&self, include_emulator: bool) -> Result<PathBuf> {
+        if let Some(adb) = self.system_path("android/platform-tools/adb") {
+            return Ok(adb.parent().and_then(Path::parent).unwrap().to_path_buf());
+        }
+        self.ensure_user_android_sdk(include_emulator)
+    }
+
+    /// Ensure the Android SDK in the user root, which NDKs and system images
+    /// missing from the system root are installed into
+    fn ensure_user_android_sdk(&self, include_emulator: bool) -> Result<PathBuf> {
         let sdk_root = self.root.join("android");
 
         if !sdk_root.exists() {
-            self.download_android_sdk(include_emulator)?;
+            // sdkmanager can't use the mirror, so don't fetch cmdline-tools for nothing
+            downloader::check_sdk_download(&Self::get_sdk_components(include_emulator))?;
+            let _lock = install_lock::acquire(&self.root, "android")?;
+            if !sdk_root.exists() {
+                self.download_android_sdk(include_emulator)?;
+            }
         }
 
         // Verify critical components exist
@@ -204,20 +291,59 @@ impl Toolchain {
         self.ensure_android_sdk_internal(true)
     }
 
+    /// Ensure the emulator is installed, adding it to an SDK installed without it
+    pub fn ensure_emulator(&self) -> Result<PathBuf> {
+        let sdk_root = self.ensure_android_sdk_with_emulator()?;
+        let emulator = sdk_root.join("emulator/emulator");
+
+        if !emulator.exists() {
+            if sdk_root != self.root.join("android") {
+                anyhow::bail!("Emulator not installed in the shared Android SDK at {}", sdk_root.display());
+            }
+            let _lock = install_lock::acquire(&self.root, "android")?;
+            Self::install_sdk_components(&sdk_root, true)?;
+        }
+
+        Ok(emulator)
+    }
+
+    /// Installed Android SDK, without installing it
+    pub fn android_sdk(&self) -> Option<PathBuf> {
+        let adb = self.installed("android/platform-tools/adb")?;
+        adb.parent().and_then(Path::parent).map(Path::to_path_buf)
+    }
+
+    /// Accept the SDK licenses for the installed Android SDK
+    ///
+    /// Follows the same rules as installs: CI needs `--accept-licenses`.
+    pub fn accept_licenses(&self) -> Result<()> {
+        let sdk_root = self
+            .android_sdk()
+            .ok_or_else(|| anyhow::anyhow!("Android SDK not installed. Run 'whitehall toolchain install' first."))?;
+        let sdkmanager = sdk_root.join("cmdline-tools/latest/bin/sdkmanager");
+        Self::accept_sdk_licenses(&sdk_root, &sdkmanager)
+    }
+
     /// Download and install Android SDK
     ///
     /// # Arguments
     /// * `include_emulator` - Whether to include emulator components
     fn download_android_sdk(&self, include_emulator: bool) -> Result<()> {
         let platform = Platform::detect()?;
-        let url = downloader::get_android_cmdline_tools_url(platform)?;
+        let url = lockfile::archive_url(
+            "android-cmdline-tools",
+            downloader::ANDROID_CMDLINE_TOOLS_VERSION,
+            downloader::get_android_cmdline_tools_url(platform)?,
+        )?;
 
-        let sdk_root = self.root.join("android");
-        std::fs::create_dir_all(&sdk_root)?;
+        // The SDK is assembled in staging and only moved to <root>/android once complete
+        let staging = self.staging_dir("android")?;
+        let sdk_root = staging.join("android");
 
         // Download cmdline-tools
-        let archive_path = sdk_root.join("cmdline-tools.zip");
-        downloader::download_with_retry(&url, &archive_path)?;
+        let archive_path = staging.join("cmdline-tools.zip");
+        let url = downloader::download_with_retry(&url, &archive_path)?;
+        lockfile::check_archive("android-cmdline-tools", downloader::ANDROID_CMDLINE_TOOLS_VERSION, &url, &archive_path)?;
 
         // Extract to cmdline-tools/latest/
         // IMPORTANT: Must be in "latest" subdirectory for sdkmanager to work
@@ -233,13 +359,10 @@ impl Toolchain {
             std::fs::rename(&extracted, &latest)?;
         }
 
-        // Clean up archive
-        std::fs::remove_file(&archive_path)?;
-
         // Now use sdkmanager to install components
-        self.install_sdk_components(include_emulator)?;
+        Self::install_sdk_components(&sdk_root, include_emulator)?;
 
-        Ok(())
+        self.finish_install(&staging, &sdk_root, "android")
     }
 
     /// Get the list of SDK components to install
@@ -260,19 +383,21 @@ impl Toolchain {
         components
     }
 
-    /// Use sdkmanager to install SDK components
+    /// Accept Android SDK licenses with sdkmanager
     ///
-    /// # Arguments
-    /// * `include_emulator` - Whether to include emulator components
-    fn install_sdk_components(&self, include_emulator: bool) -> Result<()> {
-        let sdk_root = self.root.join("android");
-        let sdkmanager = sdk_root.join("cmdline-tools/latest/bin/sdkmanager");
-
-        if !sdkmanager.exists() {
-            anyhow::bail!("sdkmanager not found at {}", sdkmanager.display());
+    /// In non-interactive mode this needs `--accept-licenses`, unless the licenses
+    /// were already accepted on this machine.
+    fn accept_sdk_licenses(sdk_root: &Path, sdkmanager: &Path) -> Result<()> {
+        if !ci::accept_licenses() {
+            if sdk_root.join("licenses/android-sdk-license").exists() {
+                return Ok(());
+            }
+            anyhow::bail!(
+                "Android SDK licenses have not been accepted on this machine.\n\
+                 Review them with `sdkmanager --licenses`, or pass --accept-licenses to accept them"
+            );
         }
 
-        // Accept licenses first using yes command to auto-accept
         let status = Command::new("sh")
             .arg("-c")
             .arg(format!(
@@ -280,7 +405,8 @@ impl Toolchain {
                 sdkmanager.display(),
                 sdk_root.display()
             ))
-            .env("ANDROID_HOME", &sdk_root)
+            .env("ANDROID_HOME", sdk_root)
+            .stdout(ci::child_stdout())
             .status()
             .context("Failed to accept SDK licenses")?;
 
@@ -288,14 +414,59 @@ impl Toolchain {
             anyhow::bail!("Failed to accept SDK licenses");
         }
 
+        Ok(())
+    }
+
+    /// Shell prefix answering sdkmanager's license prompts, when licenses may be accepted
+    fn license_answers() -> &'static str {
+        if ci::accept_licenses() {
+            "yes 2>/dev/null | "
+        } else {
+            ""
+        }
+    }
+
+    /// Fail before installing a locked package sdkmanager can't install at the locked revision
+    fn check_sdk_available(sdk_root: &Path, sdkmanager: &Path, package: &str) -> Result<()> {
+        downloader::check_sdk_download(&[package])?;
+        lockfile::check_sdk_available(package, || {
+            let output = Command::new(sdkmanager)
+                .arg(format!("--sdk_root={}", sdk_root.display()))
+                .arg("--list")
+                .env("ANDROID_HOME", sdk_root)
+                .stdin(std::process::Stdio::null())
+                .output()
+                .context("Failed to run sdkmanager --list")?;
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        })
+    }
+
+    /// Use sdkmanager to install SDK components
+    ///
+    /// # Arguments
+    /// * `sdk_root` - SDK to install into
+    /// * `include_emulator` - Whether to include emulator components
+    fn install_sdk_components(sdk_root: &Path, include_emulator: bool) -> Result<()> {
+        let sdkmanager = sdk_root.join("cmdline-tools/latest/bin/sdkmanager");
+
+        if !sdkmanager.exists() {
+            anyhow::bail!("sdkmanager not found at {}", sdkmanager.display());
+        }
+
+        // Accept licenses first
+        Self::accept_sdk_licenses(sdk_root, &sdkmanager)?;
+
         // Install components
         let components = Self::get_sdk_components(include_emulator);
 
         for component in components {
+            Self::check_sdk_available(sdk_root, &sdkmanager, component)?;
+            ci::progress_line(format!("Installing {}", component));
             let output = Command::new(&sdkmanager)
                 .arg(format!("--sdk_root={}", sdk_root.display()))
                 .arg(component)
-                .env("ANDROID_HOME", &sdk_root)
+                .env("ANDROID_HOME", sdk_root)
+                .stdin(std::process::Stdio::null())
                 .stdout(std::process::Stdio::null())
                 .stderr(std::process::Stdio::null())
                 .output()
@@ -304,6 +475,7 @@ impl Toolchain {
             if !output.status.success() {
                 anyhow::bail!("Failed to install {}", component);
             }
+            lockfile::check_sdk_package(sdk_root, component)?;
         }
 
         Ok(())
@@ -358,6 +530,157 @@ impl Toolchain {
         &self.root
     }
 
+    /// Get the shared read-only toolchain directory, if one exists
+    pub fn system_root(&self) -> Option<&Path> {
+        self.system_root.as_deref()
+    }
+
+    /// Record that the current project used a toolchain version (see `usage`)
+    fn record_use(&self, version: &str) {
+        usage::record(&self.root, version);
+    }
+
+    /// Remove toolchain versions no project still naming them has used (since `older_than`)
+    ///
+    /// Returns the removed versions with their sizes; `dry_run` only reports them.
+    pub fn gc(&self, older_than: Option<std::time::Duration>, dry_run: bool) -> Result<Vec<(String, u64)>> {
+        let usage = Usage::load(&self.root)?;
+        let mut removed = Vec::new();
+
+        for version in usage::unused_versions(&self.root, &usage, older_than) {
+            let path = self.root.join(&version);
+            let mut size = dir_size(&path);
+
+            if !dry_run {
+                // Wait out installs of the same version, and keep it if a
+                // project used it meanwhile
+                let lock_name = if version.starts_with("android/") {
+                    "android".to_string()
+                } else {
+                    version.replace('/', "-")
+                };
+                let _lock = install_lock::acquire(&self.root, &lock_name)?;
+                let mut current = Usage::load(&self.root)?;
+                if !path.exists() || current.last_used(&version) != usage.last_used(&version) {
+                    continue;
+                }
+
+                fs::remove_dir_all(&path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+
+                // Gradle's per-version daemon and caches go with it
+                if let Some(gradle_version) = version.strip_prefix("gradle/") {
+                    let gradle_home = self.root.join("gradle-home").join(gradle_version);
+                    size += dir_size(&gradle_home);
+                    let _ = fs::remove_dir_all(&gradle_home);
+                }
+
+                current.forget(&version);
+                current.save(&self.root)?;
+            }
+
+            removed.push((version, size));
+        }
+
+        Ok(removed)
+    }
+
+    /// Export installed toolchains into a portable .tar.gz
+    ///
+    /// Returns the packed toolchain directories
+    pub fn pack(&self, output: &Path) -> Result<Vec<String>> {
+        pack::pack(&self.root, output, Platform::detect()?)
+    }
+
+    /// Restore toolchains from an archive created by `pack`
+    pub fn unpack(&self, archive: &Path) -> Result<()> {
+        pack::unpack(archive, &self.root, Platform::detect()?)
+    }
+
+    /// Record installed toolchains in the active lockfile, verifying those already locked
+    ///
+    /// Archives installed before the lockfile existed are downloaded again to hash them.
+    pub fn lock_installed(&self, java_version: &str, gradle_version: &str, target_sdk: u32) -> Result<()> {
+        let platform = Platform::detect()?;
+        let lock_dir = self.root.join(".lock");
+
+        let mut archives = vec![
+            ("java", java_version.to_string(), downloader::get_java_download_url(java_version, platform)?),
+            ("gradle", gradle_version.to_string(), downloader::get_gradle_download_url(gradle_version)),
+            (
+                "android-cmdline-tools",
+                downloader::ANDROID_CMDLINE_TOOLS_VERSION.to_string(),
+                downloader::get_android_cmdline_tools_url(platform)?,
+            ),
+        ];
+        // CMake is only installed by projects with native code
+        if self.root.join(format!("cmake/{}", DEFAULT_CMAKE)).exists() {
+            archives.push(("cmake", DEFAULT_CMAKE.to_string(), downloader::get_cmake_download_url(DEFAULT_CMAKE, platform)?));
+        }
+
+        for (name, version, url) in archives {
+            if lockfile::is_archive_locked(name, &version)? {
+                continue;
+            }
+            let archive_path = lock_dir.join(format!("{}-{}", name, version));
+            let url = downloader::download_with_retry(&url, &archive_path)?;
+            lockfile::check_archive(name, &version, &url, &archive_path)?;
+            fs::remove_file(&archive_path)?;
+        }
+        let _ = fs::remove_dir_all(&lock_dir);
+
+        let sdk_root = self.root.join("android");
+        let mut packages: Vec<String> = Self::get_sdk_components(true).into_iter().map(String::from).collect();
+        packages.push(format!("ndk;{}", DEFAULT_NDK));
+        packages.push(Self::get_system_image_package(target_sdk)?);
+
+        for package in packages {
+            if lockfile::sdk_package_dir(&sdk_root, &package).exists() {
+                lockfile::check_sdk_package(&sdk_root, &package)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the project's Java, Gradle and CMake installs and update SDK packages in place
+    ///
+    /// Used by `whitehall toolchain update` before reinstalling against a fresh lockfile.
+    /// Takes the same install locks as `ensure_installed` and `gc`, so it never removes
+    /// a version another process is installing.
+    pub fn refresh(&self, java_version: &str, gradle_version: &str) -> Result<()> {
+        for dir in [
+            format!("java/{}", java_version),
+            format!("gradle/{}", gradle_version),
+            format!("cmake/{}", DEFAULT_CMAKE),
+        ] {
+            let _lock = install_lock::acquire(&self.root, &dir.replace('/', "-"))?;
+            let path = self.root.join(dir);
+            if path.exists() {
+                fs::remove_dir_all(&path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+        }
+
+        let sdk_root = self.root.join("android");
+        let sdkmanager = sdk_root.join("cmdline-tools/latest/bin/sdkmanager");
+        if sdkmanager.exists() {
+            let _lock = install_lock::acquire(&self.root, "android")?;
+            let output = Command::new(&sdkmanager)
+                .arg(format!("--sdk_root={}", sdk_root.display()))
+                .arg("--update")
+                .env("ANDROID_HOME", &sdk_root)
+                .output()
+                .context("Failed to run sdkmanager --update")?;
+
+            if !output.status.success() {
+                anyhow::bail!("Failed to update SDK packages:\n{}", String::from_utf8_lossy(&output.stderr).trim());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get system image package name based on target SDK
     ///
     /// # Arguments
@@ -389,20 +712,34 @@ impl Toolchain {
     /// # Returns
     /// Path to NDK installation directory
     pub fn ensure_ndk(&self) -> Result<PathBuf> {
-        let sdk_root = self.ensure_android_sdk()?;
         let ndk_version = DEFAULT_NDK;
+        let version = format!("android/ndk/{}", ndk_version);
+        let toolchain_file = "build/cmake/android.toolchain.cmake";
+
+        if let Some(ndk_path) = self.system_path(&version) {
+            if ndk_path.join(toolchain_file).exists() {
+                self.record_use(&version);
+                return Ok(ndk_path);
+            }
+        }
+
+        let sdk_root = self.ensure_user_android_sdk(true)?;
         let ndk_path = sdk_root.join("ndk").join(ndk_version);
+        let _lock = install_lock::acquire(&self.root, "android")?;
 
         // Check if already installed
-        if ndk_path.exists() && ndk_path.join("build/cmake/android.toolchain.cmake").exists() {
+        if ndk_path.exists() && ndk_path.join(toolchain_file).exists() {
+            self.record_use(&version);
             return Ok(ndk_path);
         }
+        let package = format!("ndk;{}", ndk_version);
+        Self::check_sdk_available(&sdk_root, &sdk_root.join("cmdline-tools/latest/bin/sdkmanager"), &package)?;
 
         // Create styled progress bar matching other components (40 chars wide like java/gradle/emulator)
         use colored::Colorize;
         use indicatif::{ProgressBar, ProgressStyle};
 
-        let pb = ProgressBar::new(100);
+        let pb = ProgressBar::with_draw_target(Some(100), ci::progress_target());
         pb.set_style(
             ProgressStyle::default_bar()
                 .template(&format!("{{msg:20}} {}{{bar:40.dim}}{} \x1b[2mdownloading (~1GB)\x1b[0m", "[".dimmed(), "]".dimmed()))
@@ -410,6 +747,7 @@ impl Toolchain {
                 .progress_chars("=> "),
         );
         pb.set_message(format!("{}", "android-ndk".dimmed()));
+        ci::progress_line("Installing android-ndk (~1GB)");
 
         // Start a thread to slowly increment progress (fake progress since sdkmanager doesn't provide it)
         use std::sync::atomic::{AtomicBool, Ordering};
@@ -429,17 +767,18 @@ impl Toolchain {
 
         // Install using sdkmanager
         let sdkmanager = sdk_root.join("cmdline-tools/latest/bin/sdkmanager");
-        let package = format!("ndk;{}", ndk_version);
 
         let output = Command::new("sh")
             .arg("-c")
             .arg(format!(
-                "yes 2>/dev/null | {} --sdk_root={} '{}'",
+                "{}{} --sdk_root={} '{}'",
+                Self::license_answers(),
                 sdkmanager.display(),
                 sdk_root.display(),
                 package
             ))
             .env("ANDROID_HOME", &sdk_root)
+            .stdin(std::process::Stdio::null())
             .output()
             .with_context(|| format!("Failed to install NDK: {}", package))?;
 
@@ -473,7 +812,10 @@ impl Toolchain {
                 eprintln!("  - Check disk usage: df -h");
                 eprintln!("  - Clean package caches: yarn cache clean, pnpm store prune, uv cache clean");
                 eprintln!("  - Remove unused Docker images: docker system prune");
-                anyhow::bail!("NDK installation failed due to insufficient disk space");
+                return Err(ci::failure(
+                    ci::FailureKind::DiskSpace,
+                    anyhow::anyhow!("NDK installation failed due to insufficient disk space"),
+                ));
             } else {
                 eprintln!("{} Failed to install NDK: {}", "error:".red().bold(), package);
                 eprintln!();
@@ -486,6 +828,9 @@ impl Toolchain {
         pb.set_position(100);
         pb.finish_and_clear();
 
+        lockfile::check_sdk_package(&sdk_root, &package)?;
+        self.record_use(&format!("android/ndk/{}", ndk_version));
+
         Ok(ndk_path)
     }
 
@@ -497,11 +842,7 @@ impl Toolchain {
     /// Path to CMake bin directory
     pub fn ensure_cmake(&self) -> Result<PathBuf> {
         let cmake_version = DEFAULT_CMAKE;
-        let cmake_home = self.root.join(format!("cmake/{}", cmake_version));
-
-        if !cmake_home.exists() {
-            self.download_cmake(cmake_version)?;
-        }
+        let cmake_home = self.ensure_installed(&format!("cmake/{}", cmake_version), || self.download_cmake(cmake_version))?;
 
         let cmake_bin = cmake_home.join("bin/cmake");
 
@@ -513,45 +854,30 @@ impl Toolchain {
             );
         }
 
+        self.record_use(&format!("cmake/{}", cmake_version));
+
         Ok(cmake_bin)
     }
 
     /// Download and install CMake
     fn download_cmake(&self, version: &str) -> Result<()> {
         let platform = Platform::detect()?;
-        let url = downloader::get_cmake_download_url(version, platform)?;
+        let url = lockfile::archive_url("cmake", version, downloader::get_cmake_download_url(version, platform)?)?;
 
-        let cmake_dir = self.root.join("cmake");
-        fs::create_dir_all(&cmake_dir)?;
+        let relative = format!("cmake/{}", version);
+        let staging = self.staging_dir(&relative)?;
 
         // Download to temporary file
-        let archive_path = cmake_dir.join(format!("cmake-{}.tar.gz", version));
-        downloader::download_with_retry(&url, &archive_path)?;
+        let archive_path = staging.join(format!("cmake-{}.tar.gz", version));
+        let url = downloader::download_with_retry(&url, &archive_path)?;
+        lockfile::check_archive("cmake", version, &url, &archive_path)?;
 
         // Extract
-        downloader::extract_tar_gz(&archive_path, &cmake_dir)?;
+        downloader::extract_tar_gz(&archive_path, &staging)?;
 
         // CMake extracts to cmake-VERSION-OS-ARCH/ directory
-        // Find the extracted directory
-        let extracted_dirs: Vec<_> = fs::read_dir(&cmake_dir)?
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_dir())
-            .filter(|e| {
-                let name = e.file_name();
-                let name_str = name.to_str().unwrap();
-                name_str.starts_with(&format!("cmake-{}", version))
-            })
-            .collect();
-
-        if let Some(extracted_dir) = extracted_dirs.first() {
-            let target = cmake_dir.join(version);
-            fs::rename(extracted_dir.path(), &target)?;
-        }
-
-        // Clean up archive
-        fs::remove_file(&archive_path)?;
-
-        Ok(())
+        let extracted = extracted_dir(&staging, &format!("cmake-{}", version))?;
+        self.finish_install(&staging, &extracted, &relative)
     }
 
     /// Get configured cmake Command
@@ -591,7 +917,6 @@ impl Toolchain {
     /// * `target_sdk` - Target SDK version (e.g., 34, 35)
     pub fn ensure_system_image(&self, target_sdk: u32) -> Result<()> {
         let package = Self::get_system_image_package(target_sdk)?;
-        let sdk_root = self.ensure_android_sdk()?;
 
         // Parse the package name to get the directory path
         // system-images;android-35;google_apis_playstore;x86_64
@@ -601,22 +926,35 @@ impl Toolchain {
             anyhow::bail!("Invalid system image package format: {}", package);
         }
 
+        let version = format!("android/system-images/{}/{}/{}", parts[1], parts[2], parts[3]);
+
+        if let Some(system_image_path) = self.system_path(&version) {
+            if system_image_path.join("system.img").exists() {
+                self.record_use(&version);
+                return Ok(());
+            }
+        }
+
+        let sdk_root = self.ensure_user_android_sdk(true)?;
         let system_image_path = sdk_root
             .join("system-images")
             .join(parts[1])
             .join(parts[2])
             .join(parts[3]);
+        let _lock = install_lock::acquire(&self.root, "android")?;
 
         // Check if already installed
         if system_image_path.exists() && system_image_path.join("system.img").exists() {
+            self.record_use(&version);
             return Ok(()); // Already installed
         }
+        Self::check_sdk_available(&sdk_root, &sdk_root.join("cmdline-tools/latest/bin/sdkmanager"), &package)?;
 
         // Create styled progress bar matching other components (40 chars wide like java/gradle/emulator)
         use colored::Colorize;
         use indicatif::{ProgressBar, ProgressStyle};
 
-        let pb = ProgressBar::new(100);
+        let pb = ProgressBar::with_draw_target(Some(100), ci::progress_target());
         pb.set_style(
             ProgressStyle::default_bar()
                 .template(&format!("{{msg:20}} {}{{bar:40.dim}}{} \x1b[2mdownloading (~1GB)\x1b[0m", "[".dimmed(), "]".dimmed()))
@@ -624,6 +962,7 @@ impl Toolchain {
                 .progress_chars("=> "),
         );
         pb.set_message(format!("{}", format!("system-image-{}", target_sdk).dimmed()));
+        ci::progress_line(format!("Installing {} (~1GB)", package));
 
         // Start a thread to slowly increment progress (fake progress since sdkmanager doesn't provide it)
         use std::sync::atomic::{AtomicBool, Ordering};
@@ -647,12 +986,14 @@ impl Toolchain {
         let output = Command::new("sh")
             .arg("-c")
             .arg(format!(
-                "yes 2>/dev/null | {} --sdk_root={} '{}'",
+                "{}{} --sdk_root={} '{}'",
+                Self::license_answers(),
                 sdkmanager.display(),
                 sdk_root.display(),
                 package
             ))
             .env("ANDROID_HOME", &sdk_root)
+            .stdin(std::process::Stdio::null())
             .output()
             .with_context(|| format!("Failed to install system image: {}", package))?;
 
@@ -686,7 +1027,10 @@ impl Toolchain {
                 eprintln!("  - Check disk usage: df -h");
                 eprintln!("  - Clean package caches: yarn cache clean, pnpm store prune, uv cache clean");
                 eprintln!("  - Remove unused Docker images: docker system prune");
-                anyhow::bail!("System image installation failed due to insufficient disk space");
+                return Err(ci::failure(
+                    ci::FailureKind::DiskSpace,
+                    anyhow::anyhow!("System image installation failed due to insufficient disk space"),
+                ));
             } else {
                 eprintln!("{} Failed to install system image: {}", "error:".red().bold(), package);
                 eprintln!();
@@ -699,6 +1043,9 @@ impl Toolchain {
         pb.set_position(100);
         pb.finish_and_clear();
 
+        lockfile::check_sdk_package(&sdk_root, &package)?;
+        self.record_use(&version);
+
         Ok(())
     }
 
@@ -714,7 +1061,6 @@ impl Toolchain {
         use std::sync::{mpsc, Arc, Mutex};
         use std::thread;
 
-        let root = self.root.clone();
         let java_ver = java_version.to_string();
         let gradle_ver = gradle_version.to_string();
 
@@ -722,7 +1068,7 @@ impl Toolchain {
         let results: Arc<Mutex<Vec<Result<String>>>> = Arc::new(Mutex::new(Vec::new()));
 
         // Create MultiProgress for coordinated display
-        let multi = Arc::new(MultiProgress::new());
+        let multi = Arc::new(MultiProgress::with_draw_target(ci::progress_target()));
 
         // Create all 3 progress bars upfront so they all show immediately
         use colored::Colorize;
@@ -775,7 +1121,7 @@ impl Toolchain {
         // Thread 1: Java download
         {
             let java_ver = java_ver.clone();
-            let root = root.clone();
+            let toolchain = self.clone();
             let results = Arc::clone(&results);
             let pb = Arc::clone(&pb_java);
             let permit_rx = Arc::clone(&permit_rx);
@@ -783,7 +1129,7 @@ impl Toolchain {
 
             let handle = thread::spawn(move || {
                 let _permit = permit_rx.lock().unwrap().recv().unwrap(); // Acquire permit
-                let result = Self::download_java_with_pb(&root, &java_ver, pb);
+                let result = Self::download_java_with_pb(&toolchain, &java_ver, pb);
                 let mut res_lock = results.lock().unwrap();
                 res_lock.push(result.map(|_| format!("java-{}", java_ver)));
                 drop(res_lock);
@@ -795,7 +1141,7 @@ impl Toolchain {
         // Thread 2: Gradle download
         {
             let gradle_ver = gradle_ver.clone();
-            let root = root.clone();
+            let toolchain = self.clone();
             let results = Arc::clone(&results);
             let pb = Arc::clone(&pb_gradle);
             let permit_rx = Arc::clone(&permit_rx);
@@ -803,7 +1149,7 @@ impl Toolchain {
 
             let handle = thread::spawn(move || {
                 let _permit = permit_rx.lock().unwrap().recv().unwrap(); // Acquire permit
-                let result = Self::download_gradle_with_pb(&root, &gradle_ver, pb);
+                let result = Self::download_gradle_with_pb(&toolchain, &gradle_ver, pb);
                 let mut res_lock = results.lock().unwrap();
                 res_lock.push(result.map(|_| format!("gradle-{}", gradle_ver)));
                 drop(res_lock);
@@ -814,7 +1160,7 @@ impl Toolchain {
 
         // Thread 3: Android SDK download
         {
-            let root = root.clone();
+            let toolchain = self.clone();
             let results = Arc::clone(&results);
             let pb = Arc::clone(&pb_android);
             let permit_rx = Arc::clone(&permit_rx);
@@ -822,7 +1168,7 @@ impl Toolchain {
 
             let handle = thread::spawn(move || {
                 let _permit = permit_rx.lock().unwrap().recv().unwrap(); // Acquire permit (will wait for slot)
-                let result = Self::download_android_sdk_with_pb(&root, true, pb); // include_emulator=true for backward compat
+                let result = Self::download_android_sdk_with_pb(&toolchain, true, pb); // include_emulator=true for backward compat
                 let mut res_lock = results.lock().unwrap();
                 res_lock.push(result.map(|_| "android-sdk".to_string()));
                 drop(res_lock);
@@ -842,15 +1188,21 @@ impl Toolchain {
         // Check results
         let results_vec = results.lock().unwrap();
         let mut errors = Vec::new();
+        let mut kind = None;
 
         for result in results_vec.iter() {
             if let Err(e) = result {
                 errors.push(format!("{}", e));
+                kind = kind.or(ci::failure_kind(e));
             }
         }
 
         if !errors.is_empty() {
-            anyhow::bail!("Some downloads failed:\n  {}", errors.join("\n  "));
+            let error = anyhow::anyhow!("Some downloads failed:\n  {}", errors.join("\n  "));
+            return Err(match kind {
+                Some(kind) => ci::failure(kind, error),
+                None => error,
+            });
         }
 
         // Return paths to installed components
@@ -874,12 +1226,11 @@ impl Toolchain {
         use std::sync::{mpsc, Arc, Mutex};
         use std::thread;
 
-        let root = self.root.clone();
         let java_ver = java_version.to_string();
         let gradle_ver = gradle_version.to_string();
 
         let results: Arc<Mutex<Vec<Result<String>>>> = Arc::new(Mutex::new(Vec::new()));
-        let multi = Arc::new(MultiProgress::new());
+        let multi = Arc::new(MultiProgress::with_draw_target(ci::progress_target()));
 
         use colored::Colorize;
         use indicatif::{ProgressBar, ProgressStyle};
@@ -925,7 +1276,7 @@ impl Toolchain {
         // Thread 1: Java
         {
             let java_ver = java_ver.clone();
-            let root = root.clone();
+            let toolchain = self.clone();
             let results = Arc::clone(&results);
             let pb = Arc::clone(&pb_java);
             let permit_rx = Arc::clone(&permit_rx);
@@ -933,7 +1284,7 @@ impl Toolchain {
 
             let handle = thread::spawn(move || {
                 let _permit = permit_rx.lock().unwrap().recv().unwrap();
-                let result = Self::download_java_with_pb(&root, &java_ver, pb);
+                let result = Self::download_java_with_pb(&toolchain, &java_ver, pb);
                 let mut res_lock = results.lock().unwrap();
                 res_lock.push(result.map(|_| format!("java-{}", java_ver)));
                 drop(res_lock);
@@ -945,7 +1296,7 @@ impl Toolchain {
         // Thread 2: Gradle
         {
             let gradle_ver = gradle_ver.clone();
-            let root = root.clone();
+            let toolchain = self.clone();
             let results = Arc::clone(&results);
             let pb = Arc::clone(&pb_gradle);
             let permit_rx = Arc::clone(&permit_rx);
@@ -953,7 +1304,7 @@ impl Toolchain {
 
             let handle = thread::spawn(move || {
                 let _permit = permit_rx.lock().unwrap().recv().unwrap();
-                let result = Self::download_gradle_with_pb(&root, &gradle_ver, pb);
+                let result = Self::download_gradle_with_pb(&toolchain, &gradle_ver, pb);
                 let mut res_lock = results.lock().unwrap();
                 res_lock.push(result.map(|_| format!("gradle-{}", gradle_ver)));
                 drop(res_lock);
@@ -964,7 +1315,7 @@ impl Toolchain {
 
         // Thread 3: Android SDK (without emulator)
         {
-            let root = root.clone();
+            let toolchain = self.clone();
             let results = Arc::clone(&results);
             let pb = Arc::clone(&pb_android);
             let permit_rx = Arc::clone(&permit_rx);
@@ -972,7 +1323,7 @@ impl Toolchain {
 
             let handle = thread::spawn(move || {
                 let _permit = permit_rx.lock().unwrap().recv().unwrap();
-                let result = Self::download_android_sdk_with_pb(&root, false, pb); // exclude emulator
+                let result = Self::download_android_sdk_with_pb(&toolchain, false, pb); // exclude emulator
                 let mut res_lock = results.lock().unwrap();
                 res_lock.push(result.map(|_| "android-sdk".to_string()));
                 drop(res_lock);
@@ -989,15 +1340,21 @@ impl Toolchain {
 
         let results_vec = results.lock().unwrap();
         let mut errors = Vec::new();
+        let mut kind = None;
 
         for result in results_vec.iter() {
             if let Err(e) = result {
                 errors.push(format!("{}", e));
+                kind = kind.or(ci::failure_kind(e));
             }
         }
 
         if !errors.is_empty() {
-            anyhow::bail!("Some downloads failed:\n  {}", errors.join("\n  "));
+            let error = anyhow::anyhow!("Some downloads failed:\n  {}", errors.join("\n  "));
+            return Err(match kind {
+                Some(kind) => ci::failure(kind, error),
+                None => error,
+            });
         }
 
         Ok((
@@ -1028,27 +1385,33 @@ impl Toolchain {
 
     // Static helper functions for parallel downloads with pre-created progress bars
 
-    fn download_java_with_pb(root: &Path, version: &str, pb: Arc<indicatif::ProgressBar>) -> Result<()> {
+    fn download_java_with_pb(toolchain: &Toolchain, version: &str, pb: Arc<indicatif::ProgressBar>) -> Result<()> {
         use colored::Colorize;
         use indicatif::ProgressStyle;
 
-        let java_home = root.join(format!("java/{}", version));
+        let root = &toolchain.root;
+        let relative = format!("java/{}", version);
 
-        if java_home.exists() {
+        if toolchain.resolve(&relative).exists() {
             pb.finish_and_clear();
             return Ok(()); // Already installed
         }
 
-        let platform = Platform::detect()?;
-        let url = downloader::get_java_download_url(version, platform)?;
+        let _lock = install_lock::acquire(root, &relative.replace('/', "-"))?;
+        if root.join(&relative).exists() {
+            pb.finish_and_clear();
+            return Ok(()); // Installed by another process while we waited
+        }
 
-        let java_dir = root.join("java");
-        std::fs::create_dir_all(&java_dir)?;
+        let platform = Platform::detect()?;
+        let url = lockfile::archive_url("java", version, downloader::get_java_download_url(version, platform)?)?;
 
-        let archive_path = java_dir.join(format!("java-{}.tar.gz", version));
+        let staging = toolchain.staging_dir(&relative)?;
+        let archive_path = staging.join(format!("java-{}.tar.gz", version));
 
         // Phase 1: Download (0-100% of file size)
-        downloader::download_with_retry_and_bar(&url, &archive_path, Some(&pb))?;
+        let url = downloader::download_with_retry_and_bar(&url, &archive_path, Some(&pb))?;
+        lockfile::check_archive("java", version, &url, &archive_path)?;
 
         // Phase 2: Extract (reset bar, show "extracting")
         pb.set_style(
@@ -1060,48 +1423,42 @@ impl Toolchain {
         pb.set_length(100);
         pb.set_position(0);
 
-        downloader::extract_tar_gz(&archive_path, &java_dir)?;
+        downloader::extract_tar_gz(&archive_path, &staging)?;
         pb.set_position(100);
 
-        // Rename extracted directory
-        let extracted_dirs: Vec<_> = std::fs::read_dir(&java_dir)?
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_dir())
-            .filter(|e| e.file_name().to_str().unwrap().starts_with("jdk-"))
-            .collect();
-
-        if let Some(extracted_dir) = extracted_dirs.first() {
-            let target = java_dir.join(version);
-            std::fs::rename(extracted_dir.path(), &target)?;
-        }
-
-        // Clean up archive
-        std::fs::remove_file(&archive_path)?;
+        let extracted = extracted_dir(&staging, "jdk-")?;
+        toolchain.finish_install(&staging, &extracted, &relative)?;
 
         pb.finish_and_clear();
         Ok(())
     }
 
-    fn download_gradle_with_pb(root: &Path, version: &str, pb: Arc<indicatif::ProgressBar>) -> Result<()> {
+    fn download_gradle_with_pb(toolchain: &Toolchain, version: &str, pb: Arc<indicatif::ProgressBar>) -> Result<()> {
         use colored::Colorize;
         use indicatif::ProgressStyle;
 
-        let gradle_home = root.join(format!("gradle/{}", version));
+        let root = &toolchain.root;
+        let relative = format!("gradle/{}", version);
 
-        if gradle_home.exists() {
+        if toolchain.resolve(&relative).exists() {
             pb.finish_and_clear();
             return Ok(()); // Already installed
         }
 
-        let url = downloader::get_gradle_download_url(version);
+        let _lock = install_lock::acquire(root, &relative.replace('/', "-"))?;
+        if root.join(&relative).exists() {
+            pb.finish_and_clear();
+            return Ok(()); // Installed by another process while we waited
+        }
 
-        let gradle_dir = root.join("gradle");
-        std::fs::create_dir_all(&gradle_dir)?;
+        let url = lockfile::archive_url("gradle", version, downloader::get_gradle_download_url(version))?;
 
-        let archive_path = gradle_dir.join(format!("gradle-{}.zip", version));
+        let staging = toolchain.staging_dir(&relative)?;
+        let archive_path = staging.join(format!("gradle-{}.zip", version));
 
         // Phase 1: Download (0-100% of file size)
-        downloader::download_with_retry_and_bar(&url, &archive_path, Some(&pb))?;
+        let url = downloader::download_with_retry_and_bar(&url, &archive_path, Some(&pb))?;
+        lockfile::check_archive("gradle", version, &url, &archive_path)?;
 
         // Phase 2: Extract (reset bar, show "extracting")
         pb.set_style(
@@ -1113,43 +1470,48 @@ impl Toolchain {
         pb.set_length(100);
         pb.set_position(0);
 
-        downloader::extract_zip(&archive_path, &gradle_dir)?;
+        downloader::extract_zip(&archive_path, &staging)?;
         pb.set_position(100);
 
-        // Rename extracted directory
-        let extracted = gradle_dir.join(format!("gradle-{}", version));
-        let target = gradle_dir.join(version);
-        if extracted.exists() {
-            std::fs::rename(&extracted, &target)?;
-        }
-
-        // Clean up archive
-        std::fs::remove_file(&archive_path)?;
+        let extracted = extracted_dir(&staging, &format!("gradle-{}", version))?;
+        toolchain.finish_install(&staging, &extracted, &relative)?;
 
         pb.finish_and_clear();
         Ok(())
     }
 
-    fn download_android_sdk_with_pb(root: &Path, include_emulator: bool, pb: Arc<indicatif::ProgressBar>) -> Result<()> {
+    fn download_android_sdk_with_pb(toolchain: &Toolchain, include_emulator: bool, pb: Arc<indicatif::ProgressBar>) -> Result<()> {
         use colored::Colorize;
         use indicatif::ProgressStyle;
 
-        let sdk_root = root.join("android");
+        let root = &toolchain.root;
 
-        if sdk_root.join("platform-tools/adb").exists() {
+        if toolchain.system_path("android/platform-tools/adb").is_some() || root.join("android").exists() {
             pb.finish_and_clear();
             return Ok(()); // Already installed
         }
 
-        let platform = Platform::detect()?;
-        let url = downloader::get_android_cmdline_tools_url(platform)?;
+        let _lock = install_lock::acquire(root, "android")?;
+        if root.join("android").exists() {
+            pb.finish_and_clear();
+            return Ok(()); // Installed by another process while we waited
+        }
 
-        std::fs::create_dir_all(&sdk_root)?;
+        let platform = Platform::detect()?;
+        let url = lockfile::archive_url(
+            "android-cmdline-tools",
+            downloader::ANDROID_CMDLINE_TOOLS_VERSION,
+            downloader::get_android_cmdline_tools_url(platform)?,
+        )?;
 
-        let archive_path = sdk_root.join("cmdline-tools.zip");
+        // The SDK is assembled in staging and only moved to <root>/android once complete
+        let staging = toolchain.staging_dir("android")?;
+        let sdk_root = staging.join("android");
+        let archive_path = staging.join("cmdline-tools.zip");
 
         // Phase 1: Download cmdline-tools (0-100% of file size)
-        downloader::download_with_retry_and_bar(&url, &archive_path, Some(&pb))?;
+        let url = downloader::download_with_retry_and_bar(&url, &archive_path, Some(&pb))?;
+        lockfile::check_archive("android-cmdline-tools", downloader::ANDROID_CMDLINE_TOOLS_VERSION, &url, &archive_path)?;
 
         // Phase 2: Extract (reset bar to 0-100%)
         pb.set_style(
@@ -1173,7 +1535,6 @@ impl Toolchain {
             std::fs::rename(&extracted, &latest)?;
         }
 
-        std::fs::remove_file(&archive_path)?;
         pb.set_position(100);
 
         // Phase 3: Install SDK components (reset bar to 0-100%)
@@ -1189,20 +1550,7 @@ impl Toolchain {
         // Accept licenses
         use std::process::Command;
         let sdkmanager = sdk_root.join("cmdline-tools/latest/bin/sdkmanager");
-        let status = Command::new("sh")
-            .arg("-c")
-            .arg(format!(
-                "yes | {} --sdk_root={} --licenses 2>&1 > /dev/null",
-                sdkmanager.display(),
-                sdk_root.display()
-            ))
-            .env("ANDROID_HOME", &sdk_root)
-            .status()
-            .context("Failed to accept SDK licenses")?;
-
-        if !status.success() {
-            anyhow::bail!("Failed to accept SDK licenses");
-        }
+        Self::accept_sdk_licenses(&sdk_root, &sdkmanager)?;
 
         pb.set_position(10); // License acceptance done
 
@@ -1212,10 +1560,13 @@ impl Toolchain {
         let progress_per_component = 90 / components.len() as u64; // 90% total for all components
 
         for (i, component) in components.iter().enumerate() {
+            Self::check_sdk_available(&sdk_root, &sdkmanager, component)?;
+            ci::progress_line(format!("Installing {}", component));
             let output = Command::new(&sdkmanager)
                 .arg(format!("--sdk_root={}", sdk_root.display()))
                 .arg(component)
                 .env("ANDROID_HOME", &sdk_root)
+                .stdin(std::process::Stdio::null())
                 .stdout(std::process::Stdio::null())
                 .stderr(std::process::Stdio::null())
                 .output()
@@ -1224,11 +1575,13 @@ impl Toolchain {
             if !output.status.success() {
                 anyhow::bail!("Failed to install {}", component);
             }
+            lockfile::check_sdk_package(&sdk_root, component)?;
 
             // Update progress: 10% for licenses + proportional progress for each component
             pb.set_position(10 + ((i + 1) as u64 * progress_per_component));
         }
 
+        toolchain.finish_install(&staging, &sdk_root, "android")?;
         pb.finish_and_clear();
         Ok(())
     }
@@ -1554,6 +1907,18 @@ impl Default for Toolchain {
     }
 }
 
+/// The directory an archive extracted into `dir`, named `<prefix>...`
+fn extracted_dir(dir: &Path, prefix: &str) -> Result<PathBuf> {
+    fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|path| {
+            path.is_dir() && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(prefix))
+        })
+        .ok_or_else(|| anyhow::anyhow!("Archive did not contain a {}* directory", prefix))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1570,6 +1935,89 @@ mod tests {
         assert!(root.exists());
     }
 
+    #[test]
+    fn test_layered_lookup() {
+        let user = tempfile::tempdir().unwrap();
+        let system = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(system.path().join("java/21")).unwrap();
+        let toolchain = Toolchain {
+            root: user.path().to_path_buf(),
+            system_root: Some(system.path().to_path_buf()),
+        };
+
+        // Present in the system root: used from there, nothing installed
+        let java = toolchain.ensure_installed("java/21", || panic!("should not install")).unwrap();
+        assert_eq!(java, system.path().join("java/21"));
+
+        // Missing: installed into the user root
+        let gradle = toolchain
+            .ensure_installed("gradle/8.4", || {
+                std::fs::create_dir_all(user.path().join("gradle/8.4"))?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(gradle, user.path().join("gradle/8.4"));
+        assert!(!system.path().join("gradle").exists());
+    }
+
+    #[test]
+    fn test_staged_install() {
+        let root = tempfile::tempdir().unwrap();
+        let toolchain = Toolchain { root: root.path().to_path_buf(), system_root: None };
+
+        // Two JDKs staged side by side don't see each other's extracted directories
+        let staging_21 = toolchain.staging_dir("java/21").unwrap();
+        let staging_17 = toolchain.staging_dir("java/17").unwrap();
+        std::fs::create_dir_all(staging_21.join("jdk-21.0.5+11/bin")).unwrap();
+        std::fs::create_dir_all(staging_17.join("jdk-17.0.13+11/bin")).unwrap();
+        std::fs::write(staging_21.join("java-21.tar.gz"), "archive").unwrap();
+
+        // Nothing is installed until the rename
+        assert!(!root.path().join("java/21").exists());
+
+        let extracted = extracted_dir(&staging_21, "jdk-").unwrap();
+        toolchain.finish_install(&staging_21, &extracted, "java/21").unwrap();
+        assert!(root.path().join("java/21/bin").is_dir());
+        assert!(!staging_21.exists());
+        assert!(!root.path().join("java/17").exists());
+        assert!(staging_17.join("jdk-17.0.13+11").is_dir());
+
+        // A leftover staging directory from an interrupted install is cleared
+        let staging_17 = toolchain.staging_dir("java/17").unwrap();
+        assert!(extracted_dir(&staging_17, "jdk-").is_err());
+    }
+
+    #[test]
+    fn test_refresh_waits_for_installs() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let root = tempfile::tempdir().unwrap();
+        let toolchain = Toolchain { root: root.path().to_path_buf(), system_root: None };
+        std::fs::create_dir_all(root.path().join("java/21/bin")).unwrap();
+        std::fs::create_dir_all(root.path().join("gradle/8.4")).unwrap();
+
+        // An install of Java 21 is in progress elsewhere
+        let install = install_lock::acquire(root.path(), "java-21").unwrap();
+        let installed = Arc::new(AtomicBool::new(false));
+        let refresh = {
+            let installed = Arc::clone(&installed);
+            std::thread::spawn(move || {
+                toolchain.refresh("21", "8.4").unwrap();
+                assert!(installed.load(Ordering::SeqCst), "removed java/21 during its install");
+            })
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(root.path().join("java/21/bin").is_dir());
+        installed.store(true, Ordering::SeqCst);
+        drop(install);
+        refresh.join().unwrap();
+
+        assert!(!root.path().join("java/21").exists());
+        assert!(!root.path().join("gradle/8.4").exists());
+    }
+
     // Phase 1 tests removed - Phase 2 now downloads automatically
     // To test downloads, run: cargo run --example test-toolchain
 }
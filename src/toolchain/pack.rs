@@ -0,0 +1,171 @@
+//! Portable toolchain archives for offline machines
+//!
+//! `whitehall toolchain pack` writes the toolchain root into a .tar.gz with a
+//! small manifest as its first entry; `unpack` checks the manifest before
+//! extracting anything, so a macOS archive isn't restored on Linux.
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use super::Platform;
+
+/// First entry of every pack, recording the platform it was made on
+const PACK_MANIFEST: &str = "whitehall-pack.txt";
+
+fn platform_id(platform: Platform) -> String {
+    let (os, arch) = platform.as_download_strings();
+    format!("{}-{}", os, arch)
+}
+
+/// Archive every toolchain under `root` into `output`
+///
+/// Returns the names of the packed top-level directories (java, gradle, ...)
+pub fn pack(root: &Path, output: &Path, platform: Platform) -> Result<Vec<String>> {
+    let mut toolchains: Vec<String> = fs::read_dir(root)
+        .with_context(|| format!("Failed to read toolchain directory: {}", root.display()))?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        // Install locks and scratch space aren't toolchains
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    toolchains.sort();
+
+    if toolchains.is_empty() {
+        anyhow::bail!("No toolchains installed in {}", root.display());
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::create(output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    // SDKs and JDKs link inside themselves; keep the links rather than duplicating files
+    builder.follow_symlinks(false);
+
+    let manifest = format!("platform = \"{}\"\n", platform_id(platform));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, PACK_MANIFEST, manifest.as_bytes())?;
+
+    for name in &toolchains {
+        builder
+            .append_dir_all(name, root.join(name))
+            .with_context(|| format!("Failed to pack {}", root.join(name).display()))?;
+    }
+
+    builder.into_inner()?.finish()?;
+
+    Ok(toolchains)
+}
+
+/// Restore a pack created by `pack` into `root`
+pub fn unpack(archive: &Path, root: &Path, platform: Platform) -> Result<()> {
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    tar.set_preserve_permissions(true);
+
+    let mut entries = tar.entries()?;
+
+    // Refuse before extracting anything if the pack is for another platform
+    let mut manifest = String::new();
+    match entries.next() {
+        Some(entry) => {
+            let mut entry = entry?;
+            if entry.path()?.as_ref() != Path::new(PACK_MANIFEST) {
+                anyhow::bail!("{} is not a whitehall toolchain pack", archive.display());
+            }
+            entry.read_to_string(&mut manifest)?;
+        }
+        None => anyhow::bail!("{} is empty", archive.display()),
+    }
+
+    let packed_platform = manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("platform = "))
+        .map(|value| value.trim().trim_matches('"'))
+        .unwrap_or("unknown");
+    let expected = platform_id(platform);
+    if packed_platform != expected {
+        anyhow::bail!(
+            "{} was packed on {}, but this machine is {}",
+            archive.display(),
+            packed_platform,
+            expected
+        );
+    }
+
+    fs::create_dir_all(root)?;
+    for entry in entries {
+        entry?
+            .unpack_in(root)
+            .with_context(|| format!("Failed to extract {}", archive.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        let source = tempfile::tempdir().unwrap();
+        let gradle_bin = source.path().join("gradle/8.4/bin");
+        fs::create_dir_all(&gradle_bin).unwrap();
+        fs::write(gradle_bin.join("gradle"), "#!/bin/sh\n").unwrap();
+        fs::create_dir_all(source.path().join("java/21/bin")).unwrap();
+        fs::write(source.path().join("java/21/bin/java"), "java").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{symlink, PermissionsExt};
+            fs::set_permissions(gradle_bin.join("gradle"), fs::Permissions::from_mode(0o755)).unwrap();
+            symlink("21", source.path().join("java/current")).unwrap();
+        }
+
+        let out = tempfile::tempdir().unwrap();
+        let archive = out.path().join("toolchains.tar.gz");
+        let packed = pack(source.path(), &archive, Platform::LinuxX64).unwrap();
+        assert_eq!(packed, vec!["gradle", "java"]);
+
+        let dest = tempfile::tempdir().unwrap();
+        unpack(&archive, dest.path(), Platform::LinuxX64).unwrap();
+        assert_eq!(fs::read_to_string(dest.path().join("java/21/bin/java")).unwrap(), "java");
+        assert!(!dest.path().join(PACK_MANIFEST).exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dest.path().join("gradle/8.4/bin/gradle")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+            assert_eq!(fs::read_link(dest.path().join("java/current")).unwrap(), Path::new("21"));
+        }
+    }
+
+    #[test]
+    fn test_unpack_rejects_other_platform() {
+        let source = tempfile::tempdir().unwrap();
+        fs::create_dir_all(source.path().join("java/21")).unwrap();
+
+        let out = tempfile::tempdir().unwrap();
+        let archive = out.path().join("toolchains.tar.gz");
+        pack(source.path(), &archive, Platform::MacAarch64).unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        let err = unpack(&archive, dest.path(), Platform::LinuxX64).unwrap_err();
+        assert!(err.to_string().contains("was packed on mac-aarch64"));
+        assert!(!dest.path().join("java").exists());
+    }
+}
@@ -0,0 +1,333 @@
+//! Which projects use which toolchain versions, for `toolchain list` and `toolchain gc`
+//!
+//! Every `ensure_*` call records the current project's manifest path and a
+//! timestamp against the version it used, in `<root>/usage.json`:
+//!
+//! ```json
+//! { "java/21": { "/home/me/app/whitehall.toml": 1760000000 } }
+//! ```
+//!
+//! A record only keeps a version alive while the manifest still exists and
+//! still names that version; switching a project to Java 21 frees Java 17.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const USAGE_FILE: &str = "usage.json";
+
+/// Manifest of the project being built, registered when whitehall.toml is loaded
+///
+/// Also serializes read-modify-write of usage.json between threads.
+static PROJECT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Toolchain version (`java/21`) -> manifest path -> last use (seconds since the epoch)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Usage {
+    versions: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl Usage {
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(USAGE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let versions = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Self { versions })
+    }
+
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let path = root.join(USAGE_FILE);
+        fs::write(&path, serde_json::to_string_pretty(&self.versions)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn record(&mut self, version: &str, project: &str, now: u64) {
+        self.versions
+            .entry(version.to_string())
+            .or_default()
+            .insert(project.to_string(), now);
+    }
+
+    /// Most recent use of a version: (timestamp, manifest path)
+    pub fn last_used(&self, version: &str) -> Option<(u64, &str)> {
+        self.versions
+            .get(version)?
+            .iter()
+            .map(|(project, &time)| (time, project.as_str()))
+            .max()
+    }
+
+    /// Whether a project that still names `version` used it at or after `since`
+    pub fn is_live(&self, version: &str, since: Option<u64>) -> bool {
+        self.versions.get(version).is_some_and(|projects| {
+            projects.iter().any(|(project, &time)| {
+                since.is_none_or(|since| time >= since) && names_version(Path::new(project), version)
+            })
+        })
+    }
+
+    pub fn forget(&mut self, version: &str) {
+        self.versions.remove(version);
+    }
+}
+
+/// Attribute toolchain use in this process to `manifest`
+pub fn set_project(manifest: Option<PathBuf>) {
+    *PROJECT.lock().unwrap() = manifest;
+}
+
+/// Record that the current project used `version` (e.g. `gradle/8.4`)
+///
+/// Best effort: usage tracking never fails a build.
+pub fn record(root: &Path, version: &str) {
+    let project = PROJECT.lock().unwrap();
+    let Some(project) = project.as_ref() else {
+        return;
+    };
+
+    let mut usage = Usage::load(root).unwrap_or_default();
+    usage.record(version, &project.to_string_lossy(), now());
+    let _ = usage.save(root);
+}
+
+/// Whether the manifest's current configuration still uses `version`
+///
+/// Java and Gradle come from `[toolchain]`, system images from `target_sdk`;
+/// NDK and CMake versions are whitehall's defaults. A manifest that exists but
+/// doesn't parse (e.g. mid-edit) keeps everything it recorded.
+fn names_version(manifest: &Path, version: &str) -> bool {
+    if !manifest.exists() {
+        return false;
+    }
+    let Ok(config) = crate::config::read_config(&manifest.to_string_lossy()) else {
+        return true;
+    };
+
+    let parts: Vec<&str> = version.split('/').collect();
+    match parts.as_slice() {
+        ["java", java] => *java == config.toolchain.java,
+        ["gradle", gradle] => *gradle == config.toolchain.gradle,
+        ["cmake", cmake] => *cmake == super::DEFAULT_CMAKE,
+        ["android", "ndk", ndk] => *ndk == super::DEFAULT_NDK,
+        ["android", "system-images", level, ..] => *level == format!("android-{}", config.android.target_sdk),
+        _ => true,
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Installed versions that `gc` manages, relative to the toolchain root
+///
+/// Java, Gradle and CMake versions, NDKs and system images. The Android SDK
+/// itself is shared by every project and never collected.
+pub fn installed_versions(root: &Path) -> Vec<String> {
+    let mut versions = Vec::new();
+    for (dir, depth) in [
+        ("java", 1),
+        ("gradle", 1),
+        ("cmake", 1),
+        ("android/ndk", 1),
+        ("android/system-images", 3),
+    ] {
+        collect_dirs(root, &root.join(dir), depth, &mut versions);
+    }
+    versions.sort();
+    versions
+}
+
+fn collect_dirs(root: &Path, dir: &Path, depth: usize, versions: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if depth > 1 {
+            collect_dirs(root, &path, depth - 1, versions);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            versions.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+}
+
+/// Versions `gc` would remove: not used by any project that still names them (since `older_than`)
+///
+/// Versions installed before usage tracking have no record; they are only
+/// collected with `older_than`, judged by the install directory's age.
+pub fn unused_versions(root: &Path, usage: &Usage, older_than: Option<Duration>) -> Vec<String> {
+    let since = older_than.map(|age| now().saturating_sub(age.as_secs()));
+
+    installed_versions(root)
+        .into_iter()
+        .filter(|version| match usage.last_used(version) {
+            Some(_) => !usage.is_live(version, since),
+            None => since.is_some_and(|since| modified(&root.join(version)) < since),
+        })
+        .collect()
+}
+
+/// Total size of the files under `path`, not following symlinks
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((e.path(), e.file_type().ok()?, e.metadata().ok()?)))
+        .map(|(path, file_type, metadata)| {
+            if file_type.is_dir() {
+                dir_size(&path)
+            } else {
+                metadata.len()
+            }
+        })
+        .sum()
+}
+
+fn modified(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Parse `--older-than`: `30d`, `12h`, `2w` (a bare number is days)
+pub fn parse_age(age: &str) -> Result<Duration> {
+    let age = age.trim();
+    let (number, unit) = match age.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => age.split_at(split),
+        None => (age, "d"),
+    };
+
+    let number: u64 = number
+        .parse()
+        .with_context(|| format!("Invalid age '{}'. Expected e.g. 30d, 12h or 2w", age))?;
+    let seconds = match unit {
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => anyhow::bail!("Invalid age '{}'. Use h (hours), d (days) or w (weeks)", age),
+    };
+
+    Ok(Duration::from_secs(number * seconds))
+}
+
+/// "3 days ago" style description of a timestamp
+pub fn describe_age(time: u64) -> String {
+    let elapsed = now().saturating_sub(time);
+    match elapsed {
+        0..=59 => "just now".to_string(),
+        60..=3599 => plural(elapsed / 60, "minute"),
+        3600..=86399 => plural(elapsed / 3600, "hour"),
+        _ => plural(elapsed / 86400, "day"),
+    }
+}
+
+fn plural(count: u64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_versions() {
+        let root = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let manifest = project.path().join("whitehall.toml");
+        fs::write(
+            &manifest,
+            "[project]\nname = \"app\"\nversion = \"1.0.0\"\n\n\
+             [android]\nmin_sdk = 24\ntarget_sdk = 34\npackage = \"com.example.app\"\n\n\
+             [toolchain]\njava = \"21\"\ngradle = \"8.4\"\n",
+        )
+        .unwrap();
+        let manifest = manifest.to_string_lossy().to_string();
+
+        for dir in [
+            "java/17",
+            "java/21",
+            "gradle/8.4",
+            "android/ndk/26.1.10909125",
+            "android/system-images/android-33/google_apis/x86_64",
+            "android/system-images/android-34/google_apis/x86_64",
+        ] {
+            fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        assert_eq!(
+            installed_versions(root.path()),
+            vec![
+                "android/ndk/26.1.10909125",
+                "android/system-images/android-33/google_apis/x86_64",
+                "android/system-images/android-34/google_apis/x86_64",
+                "gradle/8.4",
+                "java/17",
+                "java/21",
+            ]
+        );
+
+        let mut usage = Usage::default();
+        let now = now();
+        usage.record("java/21", &manifest, now);
+        usage.record("gradle/8.4", &manifest, now);
+        usage.record("android/ndk/26.1.10909125", "/deleted/project/whitehall.toml", now);
+        usage.record("android/system-images/android-34/google_apis/x86_64", &manifest, now);
+        usage.record("java/17", &manifest, now - 60 * 86400);
+        usage.record("android/system-images/android-33/google_apis/x86_64", &manifest, now);
+        usage.save(root.path()).unwrap();
+        let usage = Usage::load(root.path()).unwrap();
+
+        // Deleted projects and versions the manifest no longer names don't keep
+        // versions alive; untracked ones are kept
+        assert_eq!(
+            unused_versions(root.path(), &usage, None),
+            vec![
+                "android/ndk/26.1.10909125",
+                "android/system-images/android-33/google_apis/x86_64",
+                "java/17",
+            ]
+        );
+
+        // With an age limit, stale uses and old untracked installs go too
+        fs::create_dir_all(root.path().join("gradle/7.6")).unwrap();
+        let month = Some(Duration::from_secs(30 * 86400));
+        let unused = unused_versions(root.path(), &usage, month);
+        assert!(!unused.contains(&"java/21".to_string()));
+        assert!(!unused.contains(&"gradle/7.6".to_string()));
+
+        assert_eq!(usage.last_used("java/21"), Some((now, manifest.as_str())));
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d").unwrap(), Duration::from_secs(30 * 86400));
+        assert_eq!(parse_age("12h").unwrap(), Duration::from_secs(12 * 3600));
+        assert_eq!(parse_age("2w").unwrap(), Duration::from_secs(14 * 86400));
+        assert_eq!(parse_age("7").unwrap(), Duration::from_secs(7 * 86400));
+        assert!(parse_age("30m").is_err());
+        assert!(parse_age("d").is_err());
+    }
+}
@@ -1,18 +1,73 @@
 use anyhow::{Context, Result};
 
 /// Toolchain configuration from whitehall.toml
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ToolchainConfig {
     pub java: String,
     pub gradle: String,
     pub agp: String,
+    pub kotlin: String,
+    pub min_sdk: u32,
+    /// Also the generated app's compileSdk
+    pub target_sdk: u32,
 }
 
+/// A library the generated app depends on, and what it needs from the project
+#[derive(Debug, Clone, Copy)]
+pub struct Library {
+    /// Maven coordinate (`group:artifact:version`)
+    pub coordinate: &'static str,
+    /// Lowest compileSdk the library builds against (0 for plain JVM libraries)
+    pub min_compile_sdk: u32,
+    /// The library's minSdk (0 for plain JVM libraries)
+    pub min_sdk: u32,
+    /// Kotlin the library was compiled with; older compilers can't read its metadata
+    pub min_kotlin: &'static str,
+}
+
+const fn library(coordinate: &'static str, min_compile_sdk: u32, min_sdk: u32, min_kotlin: &'static str) -> Library {
+    Library { coordinate, min_compile_sdk, min_sdk, min_kotlin }
+}
+
+/// Libraries `android_scaffold::generate_app_gradle` adds, including the optional Room and DataStore
+pub const SCAFFOLD_LIBRARIES: &[Library] = &[
+    library("androidx.core:core-ktx:1.13.1", 34, 19, "1.8.0"),
+    library("androidx.lifecycle:lifecycle-runtime-ktx:2.8.4", 34, 19, "1.8.0"),
+    library("androidx.activity:activity-compose:1.9.1", 34, 21, "1.8.0"),
+    library("androidx.compose:compose-bom:2024.09.00", 34, 21, "1.8.0"),
+    library("androidx.navigation:navigation-compose:2.8.4", 34, 21, "1.9.0"),
+    library("io.coil-kt:coil-compose:2.6.0", 34, 21, "1.9.0"),
+    library("io.ktor:ktor-client-core:3.0.3", 0, 0, "2.0.0"),
+    library("io.ktor:ktor-client-okhttp:3.0.3", 0, 21, "2.0.0"),
+    library("io.ktor:ktor-client-content-negotiation:3.0.3", 0, 0, "2.0.0"),
+    library("io.ktor:ktor-serialization-kotlinx-json:3.0.3", 0, 0, "2.0.0"),
+    library("org.jetbrains.kotlinx:kotlinx-serialization-json:1.7.3", 0, 0, "2.0.0"),
+    library("androidx.room:room-runtime:2.6.1", 34, 21, "1.9.0"),
+    library("androidx.room:room-ktx:2.6.1", 34, 21, "1.9.0"),
+    library("androidx.room:room-compiler:2.6.1", 0, 0, "1.9.0"),
+    library("androidx.datastore:datastore-preferences:1.1.1", 34, 19, "1.9.0"),
+];
+
+/// The `org.jetbrains.kotlin.plugin.compose` compiler plugin ships with Kotlin 2.0+
+const COMPOSE_PLUGIN_MIN_KOTLIN: (u32, u32, u32) = (2, 0, 0);
+
+/// Latest patch of each AGP minor release, used to name the first compatible version
+const AGP_RELEASES: &[&str] = &[
+    "7.4.2", "8.0.2", "8.1.4", "8.2.2", "8.3.2", "8.4.2", "8.5.2", "8.6.1", "8.7.0", "9.0.0",
+];
+
+/// Kotlin releases, used to name the first compatible version
+const KOTLIN_RELEASES: &[&str] = &["1.8.0", "1.9.0", "1.9.20", "2.0.0", "2.0.20", "2.1.0"];
+
 /// Validate that toolchain versions are compatible with each other
 ///
 /// Checks:
 /// - AGP version requires compatible Java version
 /// - AGP version requires compatible Gradle version
+/// - AGP version requires compatible Kotlin version
+/// - Kotlin is new enough for the Compose compiler plugin and the scaffold's libraries
+/// - AGP version supports the compileSdk (target_sdk)
+/// - target_sdk and min_sdk satisfy the scaffold's libraries, and min_sdk <= target_sdk
 ///
 /// # Version Compatibility Matrix
 ///
@@ -26,11 +81,22 @@ pub struct ToolchainConfig {
 /// | 8.4.x       | 8.6+            | 17+           |
 /// | 9.0.x       | 8.6+            | 21+           |
 ///
+/// | AGP Version | Required Kotlin | Max compileSdk |
+/// |-------------|-----------------|----------------|
+/// | 7.4.x       | 1.8.0+          | 33             |
+/// | 8.0.x       | 1.9.0+          | 33             |
+/// | 8.1-8.3     | 1.9.0+          | 34             |
+/// | 8.4-8.5     | 2.0.0+          | 34             |
+/// | 8.6-8.8     | 2.0.0+          | 35             |
+/// | 8.9+, 9.x   | 2.1.0+          | 36             |
+///
+/// Library requirements are listed in `SCAFFOLD_LIBRARIES`.
 pub fn validate_compatibility(config: &ToolchainConfig) -> Result<()> {
     // Parse versions
     let java_version = parse_java_version(&config.java)?;
     let gradle_version = parse_gradle_version(&config.gradle)?;
     let agp_version = parse_agp_version(&config.agp)?;
+    let kotlin_version = parse_kotlin_version(&config.kotlin)?;
 
     // Validate AGP <-> Java compatibility
     validate_agp_java(agp_version, java_version, &config.java, &config.agp)?;
@@ -38,6 +104,14 @@ pub fn validate_compatibility(config: &ToolchainConfig) -> Result<()> {
     // Validate AGP <-> Gradle compatibility
     validate_agp_gradle(agp_version, gradle_version, &config.gradle, &config.agp)?;
 
+    // Validate AGP <-> Kotlin and Kotlin <-> libraries
+    validate_agp_kotlin(agp_version, kotlin_version, &config.kotlin, &config.agp)?;
+    validate_kotlin_libraries(kotlin_version, &config.kotlin)?;
+
+    // Validate compileSdk <-> AGP and SDK levels <-> libraries
+    validate_compile_sdk(agp_version, config.target_sdk, &config.agp)?;
+    validate_sdk_levels(config.min_sdk, config.target_sdk)?;
+
     Ok(())
 }
 
@@ -94,16 +168,32 @@ fn parse_agp_version(version: &str) -> Result<(u32, u32, u32)> {
     Ok((major, minor, patch))
 }
 
-/// Validate AGP and Java version compatibility
-fn validate_agp_java(
-    agp: (u32, u32, u32),
-    java: u32,
-    java_str: &str,
-    agp_str: &str,
-) -> Result<()> {
+/// Parse Kotlin version (e.g., "2.1.0" -> (2, 1, 0), "2.1.0-RC" -> (2, 1, 0))
+fn parse_kotlin_version(version: &str) -> Result<(u32, u32, u32)> {
+    let release = version.split('-').next().unwrap_or(version);
+    let parts: Vec<&str> = release.split('.').collect();
+    if parts.len() < 2 || parts.len() > 3 {
+        anyhow::bail!(
+            "Invalid Kotlin version: '{}'. Expected format: 'X.Y.Z' (e.g., '2.1.0')",
+            version
+        );
+    }
+
+    let mut numbers = [0; 3];
+    for (number, part) in numbers.iter_mut().zip(&parts) {
+        *number = part
+            .parse::<u32>()
+            .with_context(|| format!("Invalid Kotlin version: '{}'", version))?;
+    }
+
+    Ok((numbers[0], numbers[1], numbers[2]))
+}
+
+/// Java version AGP requires
+fn required_java(agp: (u32, u32, u32)) -> u32 {
     let (agp_major, agp_minor, _) = agp;
 
-    let required_java = match (agp_major, agp_minor) {
+    match (agp_major, agp_minor) {
         // AGP 7.4.x requires Java 11+
         (7, 4) => 11,
         // AGP 8.0-8.x requires Java 17+
@@ -112,7 +202,17 @@ fn validate_agp_java(
         (9, _) => 21,
         // Unknown AGP version - require Java 17 as safe default
         _ => 17,
-    };
+    }
+}
+
+/// Validate AGP and Java version compatibility
+fn validate_agp_java(
+    agp: (u32, u32, u32),
+    java: u32,
+    java_str: &str,
+    agp_str: &str,
+) -> Result<()> {
+    let required_java = required_java(agp);
 
     if java < required_java {
         anyhow::bail!(
@@ -129,17 +229,11 @@ fn validate_agp_java(
     Ok(())
 }
 
-/// Validate AGP and Gradle version compatibility
-fn validate_agp_gradle(
-    agp: (u32, u32, u32),
-    gradle: (u32, u32),
-    gradle_str: &str,
-    agp_str: &str,
-) -> Result<()> {
+/// Gradle version AGP requires
+fn required_gradle(agp: (u32, u32, u32)) -> (u32, u32) {
     let (agp_major, agp_minor, _) = agp;
-    let (gradle_major, gradle_minor) = gradle;
 
-    let (required_major, required_minor) = match (agp_major, agp_minor) {
+    match (agp_major, agp_minor) {
         // AGP 7.4.x requires Gradle 7.5+
         (7, 4) => (7, 5),
         // AGP 8.0-8.1 requires Gradle 8.0+
@@ -154,7 +248,18 @@ fn validate_agp_gradle(
         (9, _) => (8, 6),
         // Unknown AGP version - require Gradle 8.0 as safe default
         _ => (8, 0),
-    };
+    }
+}
+
+/// Validate AGP and Gradle version compatibility
+fn validate_agp_gradle(
+    agp: (u32, u32, u32),
+    gradle: (u32, u32),
+    gradle_str: &str,
+    agp_str: &str,
+) -> Result<()> {
+    let (gradle_major, gradle_minor) = gradle;
+    let (required_major, required_minor) = required_gradle(agp);
 
     // Check if Gradle version meets minimum requirement
     let gradle_sufficient = gradle_major > required_major
@@ -177,6 +282,219 @@ fn validate_agp_gradle(
     Ok(())
 }
 
+/// Kotlin version AGP requires (older Kotlin Gradle plugins fail against it)
+fn required_kotlin(agp: (u32, u32, u32)) -> (u32, u32, u32) {
+    let (agp_major, agp_minor, _) = agp;
+
+    match (agp_major, agp_minor) {
+        // AGP 7.x works with Kotlin 1.8.0+
+        (0..=7, _) => (1, 8, 0),
+        // AGP 8.0-8.3 requires Kotlin 1.9.0+
+        (8, 0..=3) => (1, 9, 0),
+        // AGP 8.4-8.8 requires Kotlin 2.0.0+
+        (8, 4..=8) => (2, 0, 0),
+        // AGP 8.9+ and 9.x require Kotlin 2.1.0+
+        _ => (2, 1, 0),
+    }
+}
+
+/// Highest compileSdk AGP supports
+fn max_compile_sdk(agp: (u32, u32, u32)) -> u32 {
+    let (agp_major, agp_minor, _) = agp;
+
+    match (agp_major, agp_minor) {
+        (0..=7, _) | (8, 0) => 33,
+        (8, 1..=5) => 34,
+        (8, 6..=8) => 35,
+        // AGP 8.9+ and 9.x support API 36
+        _ => 36,
+    }
+}
+
+fn format_version((major, minor, patch): (u32, u32, u32)) -> String {
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+/// First known release at or above `required`, or `required` itself
+fn first_release(releases: &[&str], required: (u32, u32, u32)) -> String {
+    releases
+        .iter()
+        .find(|release| parse_kotlin_version(release).is_ok_and(|version| version >= required))
+        .map_or_else(|| format_version(required), |release| release.to_string())
+}
+
+/// First known AGP release that supports compileSdk `sdk`
+fn first_agp_for_compile_sdk(sdk: u32) -> Option<&'static str> {
+    AGP_RELEASES
+        .iter()
+        .copied()
+        .find(|release| parse_agp_version(release).is_ok_and(|agp| max_compile_sdk(agp) >= sdk))
+}
+
+/// Validate AGP and Kotlin version compatibility
+fn validate_agp_kotlin(
+    agp: (u32, u32, u32),
+    kotlin: (u32, u32, u32),
+    kotlin_str: &str,
+    agp_str: &str,
+) -> Result<()> {
+    let required = required_kotlin(agp);
+
+    if kotlin < required {
+        anyhow::bail!(
+            "Incompatible toolchain configuration:\n\
+            AGP {} requires Kotlin {} or higher, but kotlin = \"{}\" specified.\n\
+            Suggestion: Update to kotlin = \"{}\"",
+            agp_str,
+            format_version(required),
+            kotlin_str,
+            first_release(KOTLIN_RELEASES, required)
+        );
+    }
+
+    Ok(())
+}
+
+/// Validate Kotlin against the Compose compiler plugin and the scaffold's libraries
+fn validate_kotlin_libraries(kotlin: (u32, u32, u32), kotlin_str: &str) -> Result<()> {
+    if kotlin < COMPOSE_PLUGIN_MIN_KOTLIN {
+        anyhow::bail!(
+            "Incompatible toolchain configuration:\n\
+            The Compose compiler plugin requires Kotlin {} or higher, but kotlin = \"{}\" specified.\n\
+            Suggestion: Update to kotlin = \"{}\"",
+            format_version(COMPOSE_PLUGIN_MIN_KOTLIN),
+            kotlin_str,
+            first_release(KOTLIN_RELEASES, COMPOSE_PLUGIN_MIN_KOTLIN)
+        );
+    }
+
+    for library in SCAFFOLD_LIBRARIES {
+        let required = parse_kotlin_version(library.min_kotlin)?;
+        if kotlin < required {
+            anyhow::bail!(
+                "Incompatible toolchain configuration:\n\
+                {} requires Kotlin {} or higher, but kotlin = \"{}\" specified.\n\
+                Suggestion: Update to kotlin = \"{}\"",
+                library.coordinate,
+                library.min_kotlin,
+                kotlin_str,
+                first_release(KOTLIN_RELEASES, required)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate that AGP supports the compileSdk (the generated app compiles against target_sdk)
+fn validate_compile_sdk(agp: (u32, u32, u32), target_sdk: u32, agp_str: &str) -> Result<()> {
+    if target_sdk <= max_compile_sdk(agp) {
+        return Ok(());
+    }
+
+    let suggestion = match first_agp_for_compile_sdk(target_sdk) {
+        Some(release) => format!("Update to agp = \"{}\"", release),
+        None => format!("Lower target_sdk to {}", max_compile_sdk(agp)),
+    };
+    anyhow::bail!(
+        "Incompatible toolchain configuration:\n\
+        AGP {} supports compileSdk up to {}, but target_sdk = {} specified.\n\
+        Suggestion: {}",
+        agp_str,
+        max_compile_sdk(agp),
+        target_sdk,
+        suggestion
+    );
+}
+
+/// Validate min_sdk and target_sdk against each other and the scaffold's libraries
+fn validate_sdk_levels(min_sdk: u32, target_sdk: u32) -> Result<()> {
+    if min_sdk > target_sdk {
+        anyhow::bail!(
+            "Invalid SDK levels: min_sdk = {} is higher than target_sdk = {}.\n\
+            Suggestion: Set min_sdk to {} or lower",
+            min_sdk,
+            target_sdk,
+            target_sdk
+        );
+    }
+
+    for library in SCAFFOLD_LIBRARIES {
+        if target_sdk < library.min_compile_sdk {
+            anyhow::bail!(
+                "Incompatible SDK configuration:\n\
+                {} requires compileSdk {} or higher, but target_sdk = {} specified.\n\
+                Suggestion: Update to target_sdk = {}",
+                library.coordinate,
+                library.min_compile_sdk,
+                target_sdk,
+                library.min_compile_sdk
+            );
+        }
+        if min_sdk < library.min_sdk {
+            anyhow::bail!(
+                "Incompatible SDK configuration:\n\
+                {} requires minSdk {} or higher, but min_sdk = {} specified.\n\
+                Suggestion: Update to min_sdk = {}",
+                library.coordinate,
+                library.min_sdk,
+                min_sdk,
+                library.min_sdk
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Propose a consistent set of versions, starting from `config`
+///
+/// Versions are only raised, each to the first release that satisfies every
+/// requirement; unparseable versions are replaced by the defaults.
+pub fn suggest(config: &ToolchainConfig) -> Result<ToolchainConfig> {
+    let mut suggested = config.clone();
+
+    // SDK levels the libraries need
+    let library_min_sdk = SCAFFOLD_LIBRARIES.iter().map(|l| l.min_sdk).max().unwrap_or(0);
+    let library_compile_sdk = SCAFFOLD_LIBRARIES.iter().map(|l| l.min_compile_sdk).max().unwrap_or(0);
+    suggested.min_sdk = suggested.min_sdk.max(library_min_sdk);
+    suggested.target_sdk = suggested.target_sdk.max(suggested.min_sdk).max(library_compile_sdk);
+
+    // AGP that supports the compileSdk
+    let mut agp = parse_agp_version(&suggested.agp).or_else(|_| {
+        suggested.agp = super::DEFAULT_AGP.to_string();
+        parse_agp_version(&suggested.agp)
+    })?;
+    if max_compile_sdk(agp) < suggested.target_sdk {
+        let release = first_agp_for_compile_sdk(suggested.target_sdk).ok_or_else(|| {
+            anyhow::anyhow!("No known AGP release supports compileSdk {}", suggested.target_sdk)
+        })?;
+        suggested.agp = release.to_string();
+        agp = parse_agp_version(release)?;
+    }
+
+    // Kotlin for AGP, the Compose plugin and the libraries
+    let mut required = required_kotlin(agp).max(COMPOSE_PLUGIN_MIN_KOTLIN);
+    for library in SCAFFOLD_LIBRARIES {
+        required = required.max(parse_kotlin_version(library.min_kotlin)?);
+    }
+    if parse_kotlin_version(&suggested.kotlin).map_or(true, |kotlin| kotlin < required) {
+        suggested.kotlin = first_release(KOTLIN_RELEASES, required);
+    }
+
+    // Gradle and Java for AGP
+    let required = required_gradle(agp);
+    if parse_gradle_version(&suggested.gradle).map_or(true, |gradle| gradle < required) {
+        suggested.gradle = format!("{}.{}", required.0, required.1);
+    }
+    let required = required_java(agp);
+    if parse_java_version(&suggested.java).map_or(true, |java| java < required) {
+        suggested.java = required.to_string();
+    }
+
+    Ok(suggested)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,6 +506,9 @@ mod tests {
             java: "21".to_string(),
             gradle: "8.4".to_string(),
             agp: "8.2.0".to_string(),
+            kotlin: "2.1.0".to_string(),
+            min_sdk: 24,
+            target_sdk: 34,
         };
         assert!(validate_compatibility(&config).is_ok());
     }
@@ -199,6 +520,9 @@ mod tests {
             java: "11".to_string(),
             gradle: "8.4".to_string(),
             agp: "8.2.0".to_string(),
+            kotlin: "2.1.0".to_string(),
+            min_sdk: 24,
+            target_sdk: 34,
         };
         let result = validate_compatibility(&config);
         assert!(result.is_err());
@@ -213,6 +537,9 @@ mod tests {
             java: "17".to_string(),
             gradle: "8.0".to_string(),
             agp: "8.2.0".to_string(),
+            kotlin: "2.1.0".to_string(),
+            min_sdk: 24,
+            target_sdk: 34,
         };
         let result = validate_compatibility(&config);
         assert!(result.is_err());
@@ -227,8 +554,18 @@ mod tests {
             java: "11".to_string(),
             gradle: "7.6".to_string(),
             agp: "7.4.2".to_string(),
+            kotlin: "2.1.0".to_string(),
+            min_sdk: 24,
+            target_sdk: 34,
         };
-        assert!(validate_compatibility(&config).is_ok());
+        let agp = parse_agp_version(&config.agp).unwrap();
+        assert!(validate_agp_java(agp, 11, &config.java, &config.agp).is_ok());
+        assert!(validate_agp_gradle(agp, (7, 6), &config.gradle, &config.agp).is_ok());
+
+        // ...but it can't compile against API 34, which the scaffold's libraries need
+        let err = validate_compatibility(&config).unwrap_err().to_string();
+        assert!(err.contains("AGP 7.4.2 supports compileSdk up to 33"));
+        assert!(err.contains("agp = \"8.1.4\""));
     }
 
     #[test]
@@ -238,16 +575,106 @@ mod tests {
             java: "21".to_string(),
             gradle: "8.6".to_string(),
             agp: "9.0.0".to_string(),
+            kotlin: "2.1.0".to_string(),
+            min_sdk: 24,
+            target_sdk: 34,
         };
         assert!(validate_compatibility(&config).is_ok());
     }
 
+    fn config(agp: &str, kotlin: &str, min_sdk: u32, target_sdk: u32) -> ToolchainConfig {
+        ToolchainConfig {
+            java: "21".to_string(),
+            gradle: "8.10".to_string(),
+            agp: agp.to_string(),
+            kotlin: kotlin.to_string(),
+            min_sdk,
+            target_sdk,
+        }
+    }
+
+    #[test]
+    fn test_kotlin_compatibility() {
+        // AGP 9 needs Kotlin 2.1.0
+        let err = validate_compatibility(&config("9.0.0", "2.0.20", 24, 35)).unwrap_err().to_string();
+        assert!(err.contains("AGP 9.0.0 requires Kotlin 2.1.0 or higher"));
+        assert!(err.contains("kotlin = \"2.1.0\""));
+
+        // The Compose compiler plugin needs Kotlin 2.0
+        let err = validate_compatibility(&config("7.4.2", "1.9.20", 24, 33)).unwrap_err().to_string();
+        assert!(err.contains("Compose compiler plugin requires Kotlin 2.0.0"));
+        assert!(err.contains("kotlin = \"2.0.0\""));
+
+        assert!(validate_compatibility(&config("8.2.0", "2.0.0", 24, 34)).is_ok());
+        assert!(validate_compatibility(&config("8.2.0", "2.1.0-RC2", 24, 34)).is_ok());
+    }
+
+    #[test]
+    fn test_sdk_compatibility() {
+        // AGP 8.2 can't compile against API 35
+        let err = validate_compatibility(&config("8.2.0", "2.1.0", 24, 35)).unwrap_err().to_string();
+        assert!(err.contains("AGP 8.2.0 supports compileSdk up to 34"));
+        assert!(err.contains("agp = \"8.6.1\""));
+
+        // Libraries need compileSdk 34 and minSdk 21
+        let err = validate_compatibility(&config("8.1.0", "2.1.0", 24, 33)).unwrap_err().to_string();
+        assert!(err.contains("requires compileSdk 34 or higher, but target_sdk = 33"));
+        let err = validate_compatibility(&config("8.2.0", "2.1.0", 19, 34)).unwrap_err().to_string();
+        assert!(err.contains("requires minSdk 21 or higher"));
+
+        let err = validate_compatibility(&config("8.2.0", "2.1.0", 35, 34)).unwrap_err().to_string();
+        assert!(err.contains("min_sdk = 35 is higher than target_sdk = 34"));
+    }
+
+    #[test]
+    fn test_suggest() {
+        let legacy = ToolchainConfig {
+            java: "11".to_string(),
+            gradle: "7.6".to_string(),
+            agp: "7.4.0".to_string(),
+            kotlin: "1.8.0".to_string(),
+            min_sdk: 21,
+            target_sdk: 35,
+        };
+        let suggested = suggest(&legacy).unwrap();
+        assert_eq!(
+            suggested,
+            ToolchainConfig {
+                java: "17".to_string(),
+                gradle: "8.6".to_string(),
+                agp: "8.6.1".to_string(),
+                kotlin: "2.0.0".to_string(),
+                min_sdk: 21,
+                target_sdk: 35,
+            }
+        );
+        assert!(validate_compatibility(&suggested).is_ok());
+
+        // A consistent configuration is left alone
+        let current = config("8.2.0", "2.0.0", 24, 34);
+        assert_eq!(suggest(&current).unwrap(), current);
+
+        // The defaults are consistent
+        let defaults = ToolchainConfig {
+            java: crate::toolchain::DEFAULT_JAVA.to_string(),
+            gradle: crate::toolchain::DEFAULT_GRADLE.to_string(),
+            agp: crate::toolchain::DEFAULT_AGP.to_string(),
+            kotlin: crate::toolchain::DEFAULT_KOTLIN.to_string(),
+            min_sdk: crate::toolchain::DEFAULT_MIN_SDK,
+            target_sdk: crate::toolchain::DEFAULT_TARGET_SDK,
+        };
+        assert!(validate_compatibility(&defaults).is_ok());
+    }
+
     #[test]
     fn test_parse_version_errors() {
         let config = ToolchainConfig {
             java: "invalid".to_string(),
             gradle: "8.4".to_string(),
             agp: "8.2.0".to_string(),
+            kotlin: "2.1.0".to_string(),
+            min_sdk: 24,
+            target_sdk: 34,
         };
         assert!(validate_compatibility(&config).is_err());
 
@@ -255,6 +682,9 @@ mod tests {
             java: "21".to_string(),
             gradle: "invalid".to_string(),
             agp: "8.2.0".to_string(),
+            kotlin: "2.1.0".to_string(),
+            min_sdk: 24,
+            target_sdk: 34,
         };
         assert!(validate_compatibility(&config).is_err());
 
@@ -262,6 +692,9 @@ mod tests {
             java: "21".to_string(),
             gradle: "8.4".to_string(),
             agp: "invalid".to_string(),
+            kotlin: "2.1.0".to_string(),
+            min_sdk: 24,
+            target_sdk: 34,
         };
         assert!(validate_compatibility(&config).is_err());
     }
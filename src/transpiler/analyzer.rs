@@ -80,6 +80,7 @@ pub struct StoreInfo {
     pub has_inject: bool,       // Has @Inject constructor?
     pub package: String,        // Package name (will be filled during codegen)
     pub route_params: Vec<String>, // Route parameters used in lifecycle hooks (e.g., ["id", "postId"])
+    pub persist: Option<String>, // DataStore name for @store(persist = "...") singletons
 }
 
 /// Symbol table: tracks all declarations
@@ -260,6 +261,10 @@ impl Analyzer {
         // Pass 0: Collect @store classes (Phase 0)
         analyzer.collect_stores(ast);
 
+        // Validate $saved / @saved state can be written to a Bundle
+        analyzer.check_saved_state(ast)?;
+        analyzer.check_persisted_stores(ast)?;
+
         // Pass 1: Collect declarations and enrich AST (Phase 0 + Semantic Enrichment)
         analyzer.collect_declarations(ast);
 
@@ -290,6 +295,10 @@ impl Analyzer {
         // Pass 0: Collect @store classes (Phase 0)
         analyzer.collect_stores(ast);
 
+        // Validate $saved / @saved state can be written to a Bundle
+        analyzer.check_saved_state(ast)?;
+        analyzer.check_persisted_stores(ast)?;
+
         // Phase 1.1: Detect component inline vars (for single-file transpilation)
         analyzer.collect_component_inline_vars(ast, component_name, package);
 
@@ -310,6 +319,63 @@ impl Analyzer {
         })
     }
 
+    /// Validate `$saved var` state and `@saved` store properties
+    ///
+    /// Saved values end up in a Bundle (rememberSaveable / SavedStateHandle), so their type
+    /// must be a primitive, String, an enum, or a @Parcelize class. Types declared in this
+    /// file are checked; types from other files are trusted.
+    fn check_saved_state(&self, ast: &WhitehallFile) -> Result<(), String> {
+        let declared = declared_kotlin_types(ast);
+
+        for state in ast.state.iter().filter(|s| s.is_saved) {
+            let type_str = literal_type(&state.name, state.type_annotation.as_deref(), &state.initial_value)?;
+            check_saveable_type(&state.name, &type_str, &declared)?;
+        }
+
+        for class in &ast.classes {
+            for prop in class.properties.iter().filter(|p| p.is_saved) {
+                if class.is_object {
+                    return Err(format!(
+                        "@saved property '{}' in object '{}': only ViewModel stores (class) support @saved",
+                        prop.name, class.name
+                    ));
+                }
+                let initial_value = prop.initial_value.as_deref().unwrap_or("");
+                let type_str = literal_type(&prop.name, prop.type_annotation.as_deref(), initial_value)?;
+                check_saveable_type(&prop.name, &type_str, &declared)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate `@store(persist = "...")` stores
+    ///
+    /// Only singletons can be persisted (DataStore is app-wide). Every `var` property is
+    /// written to Preferences; complex types are stored as JSON and must be @Serializable.
+    fn check_persisted_stores(&self, ast: &WhitehallFile) -> Result<(), String> {
+        let declared = declared_kotlin_types(ast);
+
+        for class in ast.classes.iter().filter(|c| c.persist.is_some()) {
+            if !class.is_object {
+                return Err(format!(
+                    "@store(persist = ...) on class '{}': only singleton stores (`@store object`) can be persisted",
+                    class.name
+                ));
+            }
+
+            let persisted = class.properties.iter()
+                .filter(|p| p.mutable);
+            for prop in persisted {
+                let initial_value = prop.initial_value.as_deref().unwrap_or("");
+                let type_str = literal_type(&prop.name, prop.type_annotation.as_deref(), initial_value)?;
+                check_persistable_type(&prop.name, &type_str, &declared)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Phase 1.1: Detect component inline vars (for single-file transpilation)
     /// This is called when transpiling a single component file with inline mutable state
     /// Only registers as ComponentInline for complex components that benefit from ViewModel pattern
@@ -350,6 +416,7 @@ impl Analyzer {
                     has_inject: false,
                     package: package.to_string(),
                     route_params,
+                    persist: None,
                 },
             );
         }
@@ -446,6 +513,7 @@ impl Analyzer {
                     has_inject,
                     package: String::new(),  // Will be filled during codegen
                     route_params: vec![],  // Only component inline vars have route params
+                    persist: class.persist.clone(),
                 },
             );
         }
@@ -551,6 +619,17 @@ impl Analyzer {
             Markup::ForLoop(for_loop) => self.walk_for_loop(for_loop),
             Markup::IfElse(if_else) => self.walk_if_else(if_else),
             Markup::When(when) => self.walk_when(when),
+            Markup::Await(block) => {
+                self.record_variable_access(
+                    &block.value,
+                    UsageContext::InCondition {
+                        condition_type: "await".to_string(),
+                    },
+                );
+                for child in block.children() {
+                    self.walk_markup(child);
+                }
+            }
             Markup::Sequence(items) => {
                 for item in items {
                     self.walk_markup(item);
@@ -830,6 +909,11 @@ impl Analyzer {
                     self.collect_for_loop_hints(&branch.body, hints);
                 }
             }
+            Markup::Await(block) => {
+                for child in block.children() {
+                    self.collect_for_loop_hints(child, hints);
+                }
+            }
             Markup::Sequence(items) => {
                 // Check all items in sequence
                 for item in items {
@@ -938,6 +1022,11 @@ impl Analyzer {
                         }
                     }
                 }
+                Markup::Await(block)
+                    if self.has_event_handlers(&block.children().cloned().collect::<Vec<_>>()) =>
+                {
+                    return true;
+                }
                 Markup::Sequence(items) => {
                     if self.has_event_handlers(items) {
                         return true;
@@ -950,6 +1039,143 @@ impl Analyzer {
     }
 }
 
+/// Resolve the type of a saved/persisted variable from its annotation or literal initial value
+fn literal_type(name: &str, type_annotation: Option<&str>, initial_value: &str) -> Result<String, String> {
+    if let Some(type_ann) = type_annotation {
+        return Ok(type_ann.trim().to_string());
+    }
+
+    let value = initial_value.trim();
+    if value.parse::<i32>().is_ok() {
+        Ok("Int".to_string())
+    } else if value.parse::<f64>().is_ok() {
+        Ok("Double".to_string())
+    } else if value == "true" || value == "false" {
+        Ok("Boolean".to_string())
+    } else if value.starts_with('"') && value.ends_with('"') {
+        Ok("String".to_string())
+    } else {
+        Err(format!(
+            "Variable '{}' needs an explicit type annotation to be saved (e.g. `var {}: MyType = ...`)",
+            name, name
+        ))
+    }
+}
+
+/// A class declared in one of this file's pass-through Kotlin blocks
+struct DeclaredType<'a> {
+    is_enum: bool,
+    /// Names of the annotations on this declaration (`@kotlinx.parcelize.Parcelize` → "Parcelize")
+    annotations: Vec<&'a str>,
+}
+
+/// Classes and enum classes declared in this file's pass-through Kotlin blocks, by name
+fn declared_kotlin_types(ast: &WhitehallFile) -> HashMap<&str, DeclaredType<'_>> {
+    let class_pattern = regex::Regex::new(r"\b(enum\s+)?class\s+([A-Za-z_][A-Za-z0-9_]*)\b").unwrap();
+
+    let mut declared = HashMap::new();
+    for block in &ast.kotlin_blocks {
+        for cap in class_pattern.captures_iter(&block.content) {
+            let name = cap.get(2).unwrap().as_str();
+            let annotations = class_annotations(&block.content[..cap.get(0).unwrap().start()]);
+            declared.entry(name).or_insert(DeclaredType { is_enum: cap.get(1).is_some(), annotations });
+        }
+    }
+    declared
+}
+
+/// Annotations written directly before a `class` keyword: on the same line and on the
+/// annotation-only lines above it, so those of neighbouring declarations and comments
+/// aren't picked up
+fn class_annotations(before: &str) -> Vec<&str> {
+    let mut lines = before.lines().rev();
+    let mut header = vec![lines.next().unwrap_or("")];
+    header.extend(lines.take_while(|line| line.trim_start().starts_with('@')));
+
+    header.iter()
+        .flat_map(|line| line.split('@').skip(1))
+        .filter_map(|annotation| {
+            let end = annotation
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(annotation.len());
+            annotation[..end].rsplit('.').next().filter(|name| !name.is_empty())
+        })
+        .collect()
+}
+
+/// Check that a type can be stored in a Bundle
+fn check_saveable_type(name: &str, type_str: &str, declared: &HashMap<&str, DeclaredType>) -> Result<(), String> {
+    const BUNDLE_PRIMITIVES: &[&str] = &[
+        "String", "Int", "Long", "Float", "Double", "Boolean", "Char", "Short", "Byte",
+    ];
+
+    let base_type = type_str.trim_end_matches('?').trim();
+    if BUNDLE_PRIMITIVES.contains(&base_type) {
+        return Ok(());
+    }
+
+    if base_type.contains('<') {
+        return Err(format!(
+            "Saved variable '{}' has type '{}' which can't be saved across process death; \
+             wrap it in a @Parcelize data class",
+            name, type_str
+        ));
+    }
+
+    // Look for the declaration in this file's pass-through Kotlin blocks
+    if let Some(declaration) = declared.get(base_type) {
+        if declaration.is_enum || declaration.annotations.contains(&"Parcelize") {
+            return Ok(());
+        }
+        return Err(format!(
+            "Saved variable '{}' has type '{}' which can't be saved across process death; \
+             annotate '{}' with @Parcelize and implement Parcelable",
+            name, type_str, base_type
+        ));
+    }
+
+    // Declared in another file - can't be verified here
+    Ok(())
+}
+
+/// Check that a type can be persisted in Preferences DataStore
+/// Primitives map to typed keys; anything else is stored as JSON via kotlinx.serialization
+fn check_persistable_type(name: &str, type_str: &str, declared: &HashMap<&str, DeclaredType>) -> Result<(), String> {
+    if preferences_key_type(type_str).is_some() {
+        return Ok(());
+    }
+
+    // Every type declared in this file that appears in the type must be serializable
+    let type_names = type_str.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'));
+    for type_name in type_names {
+        if let Some(declaration) = declared.get(type_name) {
+            if !declaration.is_enum && !declaration.annotations.contains(&"Serializable") {
+                return Err(format!(
+                    "Persisted property '{}' has type '{}' which can't be stored; annotate '{}' with @Serializable",
+                    name, type_str, type_name
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Preferences DataStore key type for a Kotlin type, if it has a native key
+/// e.g. "Boolean" → "boolean", "Set<String>" → "stringSet"
+pub(crate) fn preferences_key_type(type_str: &str) -> Option<&'static str> {
+    match type_str.trim().trim_end_matches('?') {
+        "Boolean" => Some("boolean"),
+        "Int" => Some("int"),
+        "Long" => Some("long"),
+        "Float" => Some("float"),
+        "Double" => Some("double"),
+        "String" => Some("string"),
+        "Set<String>" => Some("stringSet"),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -969,6 +1195,7 @@ mod tests {
                 type_annotation: Some("Int".to_string()),
                 initial_value: "0".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             ..Default::default()
         };
@@ -1009,6 +1236,7 @@ mod tests {
                 type_annotation: Some("Int".to_string()),
                 initial_value: "0".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::Interpolation("count".to_string()),
             ..Default::default()
@@ -1033,6 +1261,7 @@ mod tests {
                 type_annotation: Some("String".to_string()),
                 initial_value: "\"Hello\"".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::Component(Component {
                 name: "Text".to_string(),
@@ -1067,6 +1296,7 @@ mod tests {
                 type_annotation: Some("List<String>".to_string()),
                 initial_value: "listOf()".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::ForLoop(ForLoopBlock {
                 index: None,
@@ -1100,6 +1330,7 @@ mod tests {
                     type_annotation: Some("List<Post>".to_string()),
                     initial_value: "listOf()".to_string(),
                     is_derived_state: false,
+                    is_saved: false,
                 },
                 StateDeclaration {
                     name: "highlight".to_string(),
@@ -1107,6 +1338,7 @@ mod tests {
                     type_annotation: Some("String".to_string()),
                     initial_value: "\"red\"".to_string(),
                     is_derived_state: false,
+                    is_saved: false,
                 },
             ],
             markup: Markup::ForLoop(ForLoopBlock {
@@ -1157,6 +1389,7 @@ mod tests {
                 type_annotation: Some("List<Contact>".to_string()),
                 initial_value: "listOf()".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::ForLoop(ForLoopBlock {
                 index: None,
@@ -1190,6 +1423,7 @@ mod tests {
                 type_annotation: Some("Boolean".to_string()),
                 initial_value: "true".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::IfElse(IfElseBlock {
                 condition: "isVisible".to_string(),
@@ -1222,6 +1456,7 @@ mod tests {
                     type_annotation: Some("Int".to_string()),
                     initial_value: "0".to_string(),
                     is_derived_state: false,
+                    is_saved: false,
                 },
                 StateDeclaration {
                     name: "max".to_string(),
@@ -1229,6 +1464,7 @@ mod tests {
                     type_annotation: Some("Int".to_string()),
                     initial_value: "100".to_string(),
                     is_derived_state: false,
+                    is_saved: false,
                 },
             ],
             markup: Markup::Component(Component {
@@ -1264,6 +1500,7 @@ mod tests {
                 type_annotation: Some("String".to_string()),
                 initial_value: "\"Hello\"".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::Sequence(vec![
                 Markup::Component(Component {
@@ -1307,6 +1544,7 @@ mod tests {
                 type_annotation: Some("String".to_string()),
                 initial_value: "\"active\"".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::When(WhenBlock {
                 branches: vec![
@@ -1352,6 +1590,7 @@ mod tests {
                 type_annotation: Some("List<String>".to_string()),
                 initial_value: "listOf(\"a\", \"b\", \"c\")".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::ForLoop(ForLoopBlock {
                 index: None,
@@ -1397,6 +1636,7 @@ mod tests {
                 type_annotation: Some("List<String>".to_string()),
                 initial_value: "listOf(\"a\", \"b\", \"c\")".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::ForLoop(ForLoopBlock {
                 index: None,
@@ -1481,6 +1721,7 @@ mod tests {
                 type_annotation: Some("List<String>".to_string()),
                 initial_value: "listOf(\"a\", \"b\")".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::ForLoop(ForLoopBlock {
                 index: None,
@@ -1534,6 +1775,7 @@ mod tests {
                 type_annotation: Some("List<String>".to_string()),
                 initial_value: "mutableListOf()".to_string(),
                 is_derived_state: false,
+                is_saved: false,
             }],
             markup: Markup::ForLoop(ForLoopBlock {
                 index: None,
@@ -1580,6 +1822,7 @@ mod tests {
                     type_annotation: Some("List<String>".to_string()),
                     initial_value: "listOf()".to_string(),
                     is_derived_state: false,
+                    is_saved: false,
                 },
                 StateDeclaration {
                     name: "items2".to_string(),
@@ -1587,6 +1830,7 @@ mod tests {
                     type_annotation: Some("List<String>".to_string()),
                     initial_value: "listOf()".to_string(),
                     is_derived_state: false,
+                    is_saved: false,
                 },
             ],
             markup: Markup::Sequence(vec![
@@ -1650,6 +1894,7 @@ mod tests {
                     type_annotation: Some("List<Group>".to_string()),
                     initial_value: "listOf()".to_string(),
                     is_derived_state: false,
+                    is_saved: false,
                 },
                 StateDeclaration {
                     name: "inner".to_string(),
@@ -1657,6 +1902,7 @@ mod tests {
                     type_annotation: Some("List<Item>".to_string()),
                     initial_value: "listOf()".to_string(),
                     is_derived_state: false,
+                    is_saved: false,
                 },
             ],
             markup: Markup::ForLoop(ForLoopBlock {
@@ -1687,4 +1933,87 @@ mod tests {
         // Should detect both outer and inner loops
         assert_eq!(semantic_info.optimization_hints.len(), 2);
     }
+
+    #[test]
+    fn test_saved_state_accepts_primitives_and_parcelable() {
+        let mut parser = crate::transpiler::parser::Parser::new(
+            "$saved var query = \"\"\n$saved var page = 1\n$saved var filter: Filter? = null\n<Text>{query}</Text>\n\n@Parcelize\ndata class Filter(val tag: String) : Parcelable",
+        );
+        let mut ast = parser.parse().unwrap();
+        assert!(Analyzer::analyze(&mut ast).is_ok());
+    }
+
+    #[test]
+    fn test_saved_state_rejects_unsaveable_types() {
+        // Local data class without @Parcelize
+        let mut parser = crate::transpiler::parser::Parser::new(
+            "$saved var filter: Filter = Filter(\"\")\n<Text>{filter.tag}</Text>\n\ndata class Filter(val tag: String)",
+        );
+        let mut ast = parser.parse().unwrap();
+        let err = Analyzer::analyze(&mut ast).unwrap_err();
+        assert!(err.contains("@Parcelize"), "{}", err);
+
+        // @Parcelize on another class in the same block, or in a comment, doesn't count
+        for kotlin in [
+            "@Parcelize\ndata class Search(val query: String) : Parcelable {\n    data class Filter(val tag: String)\n}",
+            "data class Filter(val tag: String) {\n    // Can't be @Parcelize while tag is mutable\n}",
+        ] {
+            let mut parser = crate::transpiler::parser::Parser::new(&format!(
+                "$saved var filter: Filter? = null\n<Text>{{filter?.tag}}</Text>\n\n{}",
+                kotlin
+            ));
+            let mut ast = parser.parse().unwrap();
+            let err = Analyzer::analyze(&mut ast).unwrap_err();
+            assert!(err.contains("annotate 'Filter' with @Parcelize"), "{}", err);
+        }
+
+        // Collections
+        let mut parser = crate::transpiler::parser::Parser::new(
+            "$saved var tags: List<String> = []\n<Text>{tags.size}</Text>",
+        );
+        let mut ast = parser.parse().unwrap();
+        assert!(Analyzer::analyze(&mut ast).is_err());
+
+        // No type annotation on a non-literal
+        let mut parser = crate::transpiler::parser::Parser::new(
+            "$saved var now = System.currentTimeMillis()\n<Text>{now}</Text>",
+        );
+        let mut ast = parser.parse().unwrap();
+        let err = Analyzer::analyze(&mut ast).unwrap_err();
+        assert!(err.contains("explicit type annotation"), "{}", err);
+    }
+
+    #[test]
+    fn test_saved_property_rejected_on_singleton() {
+        let mut parser = crate::transpiler::parser::Parser::new(
+            "@store\nobject Settings {\n  @saved var darkMode = false\n}",
+        );
+        let mut ast = parser.parse().unwrap();
+        assert!(Analyzer::analyze(&mut ast).is_err());
+    }
+
+    #[test]
+    fn test_persisted_store_validation() {
+        let mut parser = crate::transpiler::parser::Parser::new(
+            "@store(persist = \"settings\")\nobject AppSettings {\n  var darkMode = false\n  var tags: Set<String> = setOf()\n  var profile: Profile? = null\n}\n\n@Serializable\ndata class Profile(val name: String)",
+        );
+        let mut ast = parser.parse().unwrap();
+        assert_eq!(ast.classes[0].persist.as_deref(), Some("settings"));
+        let info = Analyzer::analyze(&mut ast).unwrap();
+        assert_eq!(info.store_registry.get("AppSettings").unwrap().persist.as_deref(), Some("settings"));
+
+        // Local type without @Serializable
+        let mut parser = crate::transpiler::parser::Parser::new(
+            "@store(persist = \"settings\")\nobject AppSettings {\n  var profile: Profile? = null\n}\n\ndata class Profile(val name: String)",
+        );
+        let mut ast = parser.parse().unwrap();
+        assert!(Analyzer::analyze(&mut ast).unwrap_err().contains("@Serializable"));
+
+        // Only singletons can be persisted
+        let mut parser = crate::transpiler::parser::Parser::new(
+            "@store(persist = \"settings\")\nclass Settings {\n  var darkMode = false\n}",
+        );
+        let mut ast = parser.parse().unwrap();
+        assert!(Analyzer::analyze(&mut ast).is_err());
+    }
 }
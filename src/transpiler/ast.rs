@@ -31,6 +31,7 @@ pub struct StateDeclaration {
     pub type_annotation: Option<String>, // e.g., "List<Post>"
     pub initial_value: String,
     pub is_derived_state: bool,          // true if initial_value uses derivedStateOf
+    pub is_saved: bool,                  // true for `$saved var` (survives process death)
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -57,6 +58,7 @@ pub struct ClassDeclaration {
     pub constructor: Option<ConstructorDeclaration>,  // Constructor with @Inject
     pub properties: Vec<PropertyDeclaration>,  // var/val properties
     pub functions: Vec<FunctionDeclaration>,   // Methods
+    pub persist: Option<String>,               // DataStore name from @store(persist = "settings")
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -73,6 +75,7 @@ pub struct PropertyDeclaration {
     pub initial_value: Option<String>,   // e.g., "\"\"" or "false"
     pub getter: Option<String>,          // Custom getter for derived properties
     pub visibility: Option<String>,      // "private", "protected", "public", or None (default)
+    pub is_saved: bool,                  // true for `@saved var` (backed by SavedStateHandle)
 }
 
 /// Represents a block of Kotlin code that passes through unchanged.
@@ -117,6 +120,7 @@ pub enum Markup {
     IfElse(IfElseBlock),   // @if/@else control flow
     ForLoop(ForLoopBlock), // @for loop with key and empty block
     When(WhenBlock),       // @when expression
+    Await(AwaitBlock),     // @await block over an $async value
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -154,6 +158,23 @@ pub struct WhenBranch {
     pub body: Markup,              // Single markup item per branch
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct AwaitBlock {
+    pub value: String,               // The $async value, e.g., "photos"
+    pub loading: Vec<Markup>,        // loading => ...
+    pub ok_binding: Option<String>,  // "list" in ok(list) => ...
+    pub ok: Vec<Markup>,
+    pub err_binding: Option<String>, // "e" in err(e) => ...
+    pub err: Vec<Markup>,
+}
+
+impl AwaitBlock {
+    /// Markup of all branches (loading, ok, err)
+    pub fn children(&self) -> impl Iterator<Item = &Markup> {
+        self.loading.iter().chain(&self.ok).chain(&self.err)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Component {
     pub name: String,
@@ -189,6 +210,23 @@ impl WhitehallFile {
             kotlin_blocks: Vec::new(),
         }
     }
+
+    /// Whether the file only holds pass-through Kotlin (e.g. src/models/Note.wh),
+    /// with no markup, props, state, functions or stores
+    pub fn is_data_only(&self) -> bool {
+        let has_markup = match &self.markup {
+            Markup::Sequence(items) => !items.is_empty(),
+            Markup::Text(s) => !s.is_empty(),
+            _ => true,
+        };
+        !has_markup
+            && !self.kotlin_blocks.is_empty()
+            && self.classes.is_empty()
+            && self.props.is_empty()
+            && self.state.is_empty()
+            && self.functions.is_empty()
+            && self.lifecycle_hooks.is_empty()
+    }
 }
 
 impl Default for WhitehallFile {
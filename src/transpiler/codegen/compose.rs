@@ -4,6 +4,7 @@ use colored::Colorize;
 use crate::transpiler::analyzer::StoreRegistry;
 use crate::transpiler::ast::{ClassDeclaration, Component, ForLoopBlock, Markup, PropValue, WhitehallFile};
 use crate::transpiler::optimizer::Optimization;
+use crate::transpiler::util::{find_matching_paren, split_top_level};
 
 pub struct ComposeBackend {
     package: String,
@@ -27,6 +28,8 @@ pub struct ComposeBackend {
     uses_navigate: bool, // Track if $navigate() is used (for LocalNavController import)
     uses_route_path: bool, // Track if $route.path is used (for currentBackStackEntry observation)
     uses_material_icons: bool, // Track if Icon with name prop is used (for Icons import)
+    uses_coil: bool, // Track if AsyncImage/Image is used (for the Coil dependency)
+    async_values: std::collections::HashSet<String>, // vals declared with $async (for @await and refresh)
     // Phase 1.1: ViewModel wrapper context
     in_viewmodel_wrapper: bool, // Are we generating markup inside a ViewModel wrapper?
     mutable_vars: std::collections::HashSet<String>, // Mutable vars (need uiState prefix)
@@ -129,6 +132,8 @@ impl ComposeBackend {
             uses_navigate: false, // Track $navigate() usage for LocalNavController import
             uses_route_path: false, // Track $route.path usage for route observation
             uses_material_icons: false, // Track Icon with name prop for Icons import
+            uses_coil: false, // Track AsyncImage/Image usage for the Coil dependency
+            async_values: std::collections::HashSet::new(), // Track $async vals
             in_viewmodel_wrapper: false, // Phase 1.1: Not in ViewModel wrapper by default
             mutable_vars: std::collections::HashSet::new(), // Phase 1.1: Track mutable vars
             derived_props: std::collections::HashSet::new(), // Phase 1.1: Track derived properties
@@ -136,6 +141,21 @@ impl ComposeBackend {
         }
     }
 
+    /// Optional libraries the generated code needs, for project-wide dependency selection
+    pub fn feature_usage(&self) -> crate::transpiler::FeatureUsage {
+        crate::transpiler::FeatureUsage {
+            fetch: self.uses_fetch,
+            // Screens take a NavController parameter
+            navigation: self.uses_routes
+                || self.uses_navigate
+                || self.uses_route_path
+                || self.component_type.as_deref() == Some("screen"),
+            images: self.uses_coil,
+            // Set by `transpile_with_usage` from the parsed file
+            parcelize: false,
+        }
+    }
+
     /// Phase 6: Generate with optimization support
     ///
     /// This method receives optimization plans and routes for loops accordingly:
@@ -205,6 +225,8 @@ impl ComposeBackend {
     }
 
     pub fn generate(&mut self, file: &WhitehallFile) -> Result<crate::transpiler::TranspileResult, String> {
+        self.check_fetch_calls(file)?;
+
         // Check if this file contains a reactive class (in store registry)
         // This includes: classes with var properties OR @store object singletons
         let store_class = file.classes.iter().find(|c| {
@@ -232,6 +254,11 @@ impl ComposeBackend {
             // If has markup, continue below to generate screen + inline store
         }
 
+        // Data-only file (e.g., src/models/Note.wh): only pass-through Kotlin, no component
+        if file.is_data_only() {
+            return self.generate_model_file(file);
+        }
+
         // Check if this component has inline vars (ComponentInline in registry)
         let is_component_viewmodel = if let Some(registry) = &self.store_registry {
             registry.get(&self.component_name)
@@ -241,6 +268,9 @@ impl ComposeBackend {
             false
         };
 
+        // Pre-pass: Detect $async values (for @await and pull-to-refresh)
+        self.detect_async_usage(file);
+
         if is_component_viewmodel {
             // Pre-pass: Detect $routes/$navigate usage (for Routes import in wrapper)
             self.detect_routes_usage(file);
@@ -263,6 +293,11 @@ impl ComposeBackend {
         // Pre-pass: Detect $routes/$navigate usage (for Routes import)
         self.detect_routes_usage(file);
 
+        // Pre-pass: Detect onRefresh usage (PullToRefreshBox import and @OptIn)
+        if self.scan_for_on_refresh(&file.markup) {
+            self.uses_pull_to_refresh = true;
+        }
+
         // Otherwise, generate standard Composable component
         let mut output = String::new();
 
@@ -286,6 +321,11 @@ impl ComposeBackend {
             self.uses_experimental_material3 = true;
         }
 
+        // Check if Coil is used (AsyncImage, and Image which maps to it)
+        if component_imports.iter().chain(&prop_imports).any(|imp| imp.starts_with("coil.")) {
+            self.uses_coil = true;
+        }
+
         // Import ordering:
         // If there's exactly one component import, no props, no state, no user imports: component first
         // Otherwise: Composable, prop imports, user imports, component imports
@@ -303,6 +343,10 @@ impl ComposeBackend {
         // Add Composable or runtime imports
         if !file.state.is_empty() {
             imports.push("androidx.compose.runtime.*".to_string());
+            // $saved var → rememberSaveable (not covered by the runtime wildcard)
+            if file.state.iter().any(|s| s.is_saved) {
+                imports.push("androidx.compose.runtime.saveable.rememberSaveable".to_string());
+            }
             // Remove specific runtime imports since we're using wildcard
             prop_imports.retain(|imp| !imp.starts_with("androidx.compose.runtime."));
         } else {
@@ -358,15 +402,14 @@ impl ComposeBackend {
             imports.push("androidx.compose.material3.pulltorefresh.PullToRefreshBox".to_string());
         }
 
-        // Add Ktor imports for $fetch() API usage
+        // Add the shared Fetch client for $fetch() API usage
         if self.uses_fetch {
-            imports.push("io.ktor.client.HttpClient".to_string());
-            imports.push("io.ktor.client.call.body".to_string());
-            imports.push("io.ktor.client.engine.okhttp.OkHttp".to_string());
-            imports.push("io.ktor.client.plugins.contentnegotiation.ContentNegotiation".to_string());
-            imports.push("io.ktor.client.request.get".to_string());
-            imports.push("io.ktor.serialization.kotlinx.json.json".to_string());
-            imports.push("kotlinx.serialization.json.Json".to_string());
+            imports.extend(self.fetch_imports(file));
+        }
+
+        // $async values are remembered in the component
+        if !self.async_values.is_empty() {
+            imports.push(self.resolve_import("$rememberAsync"));
         }
 
         // Add StateFlow imports for inline @store objects
@@ -385,6 +428,15 @@ impl ComposeBackend {
             imports.push("kotlinx.coroutines.flow.asStateFlow".to_string());
             imports.push("kotlinx.coroutines.flow.update".to_string());
         }
+        for class in &file.classes {
+            let is_singleton = self.store_registry.as_ref()
+                .and_then(|registry| registry.get(&class.name))
+                .map(|info| info.source == crate::transpiler::analyzer::StoreSource::Singleton)
+                .unwrap_or(false);
+            if is_singleton {
+                imports.extend(self.persisted_store_imports(class));
+            }
+        }
 
         // Add Routes import if $routes or $navigate is used
         // For screens: uses_routes is set by route aliases ($routes.xxx)
@@ -445,11 +497,6 @@ impl ComposeBackend {
 
         output.push('\n');
 
-        // Generate HttpClient singleton if $fetch() is used
-        if self.uses_fetch {
-            output.push_str(&self.generate_http_client());
-        }
-
         // Generate inline @store object if file has both store and markup
         for class in &file.classes {
             if let Some(registry) = &self.store_registry {
@@ -583,6 +630,9 @@ impl ComposeBackend {
             let mut transformed_value = self.transform_array_literal(&state.initial_value, true);
             transformed_value = self.transform_range_literal(&transformed_value);
 
+            // $saved var survives configuration changes and process death
+            let remember_fn = if state.is_saved { "rememberSaveable" } else { "remember" };
+
             if let Some(ref type_ann) = state.type_annotation {
                 // Store type and default value for bind:value transformations
                 self.var_types.insert(
@@ -592,8 +642,8 @@ impl ComposeBackend {
 
                 // With type annotation: var name by remember { mutableStateOf<Type>(value) }
                 output.push_str(&format!(
-                    "var {} by remember {{ mutableStateOf<{}>({}) }}\n",
-                    state.name, type_ann, transformed_value
+                    "var {} by {} {{ mutableStateOf<{}>({}) }}\n",
+                    state.name, remember_fn, type_ann, transformed_value
                 ));
             } else {
                 // Try to infer type from initial value for bind:value support
@@ -605,8 +655,8 @@ impl ComposeBackend {
 
                 // Without type annotation: var name by remember { mutableStateOf(value) }
                 output.push_str(&format!(
-                    "var {} by remember {{ mutableStateOf({}) }}\n",
-                    state.name, transformed_value
+                    "var {} by {} {{ mutableStateOf({}) }}\n",
+                    state.name, remember_fn, transformed_value
                 ));
             }
         }
@@ -624,6 +674,15 @@ impl ComposeBackend {
             let mut transformed_value = self.transform_array_literal(&state.initial_value, false);
             transformed_value = self.transform_range_literal(&transformed_value);
 
+            // $async { ... } → rememberAsync { ... } (loads on first composition, restarts when keys change)
+            if let Some((keys, body)) = Self::parse_async_value(&state.initial_value) {
+                let keys = if keys.is_empty() { String::new() } else { format!("({})", keys) };
+                output.push_str(&format!("val {} = rememberAsync{} {{\n", state.name, keys));
+                output.push_str(&self.async_body(&body, &format!("{}    ", self.indent())));
+                output.push_str(&format!("{}}}\n", self.indent()));
+                continue;
+            }
+
             // Check if this is a custom scope: $scope() → rememberCoroutineScope()
             if transformed_value.trim() == "$scope()" {
                 output.push_str(&format!("val {} = rememberCoroutineScope()\n", state.name));
@@ -725,7 +784,7 @@ impl ComposeBackend {
                     let suspend_keyword = if func.is_suspend { "suspend " } else { "" };
                     output.push_str(&format!("{}fun {}({}){} {{\n", suspend_keyword, func.name, func.params, return_type_str));
                     // Output function body with proper indentation and transformations
-                    for line in func.body.lines() {
+                    for line in Self::join_fetch_calls(&func.body).lines() {
                         output.push_str(&self.indent());
                         output.push_str("    ");
 
@@ -735,8 +794,8 @@ impl ComposeBackend {
                         // Transform $screen.params.{name} → {name}
                         transformed_line = transformed_line.replace("$screen.params.", "");
 
-                        // Transform $fetch() calls to Ktor HttpClient calls
-                        if transformed_line.contains("$fetch(") {
+                        // Transform $fetch() calls to the shared Fetch client
+                        if transformed_line.contains("$fetch") {
                             transformed_line = self.transform_fetch_call(&transformed_line);
                         }
 
@@ -798,7 +857,7 @@ impl ComposeBackend {
 
             // Generate onMount body
             if let Some(mount_hook) = file.lifecycle_hooks.iter().find(|h| h.hook_type == "onMount") {
-                for line in mount_hook.body.lines() {
+                for line in Self::join_fetch_calls(&mount_hook.body).lines() {
                     if line.trim().is_empty() {
                         continue;
                     }
@@ -812,8 +871,8 @@ impl ComposeBackend {
                     }
 
                     let mut transformed_line = line.trim_start().replace("$screen.params.", "");
-                    // Transform $fetch() calls to Ktor HttpClient calls
-                    if transformed_line.contains("$fetch(") {
+                    // Transform $fetch() calls to the shared Fetch client
+                    if transformed_line.contains("$fetch") {
                         transformed_line = self.transform_fetch_call(&transformed_line);
                     }
                     // Transform $log() calls to Android Log calls
@@ -860,7 +919,7 @@ impl ComposeBackend {
                     output.push_str(&self.indent());
                     output.push_str("LaunchedEffect(Unit) {\n");
 
-                    for line in hook.body.lines() {
+                    for line in Self::join_fetch_calls(&hook.body).lines() {
                         if line.trim().is_empty() {
                             continue;
                         }
@@ -874,8 +933,8 @@ impl ComposeBackend {
                         }
 
                         let mut transformed_line = line.trim_start().replace("$screen.params.", "");
-                        // Transform $fetch() calls to Ktor HttpClient calls
-                        if transformed_line.contains("$fetch(") {
+                        // Transform $fetch() calls to the shared Fetch client
+                        if transformed_line.contains("$fetch") {
                             transformed_line = self.transform_fetch_call(&transformed_line);
                         }
                         // Transform $log() calls to Android Log calls
@@ -934,7 +993,7 @@ impl ComposeBackend {
                     let suspend_keyword = if func.is_suspend { "suspend " } else { "" };
                     output.push_str(&format!("{}fun {}({}){} {{\n", suspend_keyword, func.name, func.params, return_type_str));
                     // Output function body with proper indentation and transformations
-                    for line in func.body.lines() {
+                    for line in Self::join_fetch_calls(&func.body).lines() {
                         output.push_str(&self.indent());
                         output.push_str("    ");
 
@@ -944,8 +1003,8 @@ impl ComposeBackend {
                         // Transform $screen.params.{name} → {name}
                         transformed_line = transformed_line.replace("$screen.params.", "");
 
-                        // Transform $fetch() calls to Ktor HttpClient calls
-                        if transformed_line.contains("$fetch(") {
+                        // Transform $fetch() calls to the shared Fetch client
+                        if transformed_line.contains("$fetch") {
                             transformed_line = self.transform_fetch_call(&transformed_line);
                         }
 
@@ -1247,6 +1306,36 @@ impl ComposeBackend {
                 output.push_str(&format!("{}}}\n", indent_str));
                 Ok(output)
             }
+            Markup::Await(block) => {
+                // when over the $async value's UiState; every branch is emitted so the when is exhaustive
+                let mut output = String::new();
+                let indent_str = "    ".repeat(indent);
+                let value = self.transform_viewmodel_expression(&block.value);
+                let state_name = format!("{}State", block.value.rsplit('.').next().unwrap_or(&block.value));
+
+                output.push_str(&format!("{}when (val {} = {}.state) {{\n", indent_str, state_name, value));
+                let branches = [
+                    ("Loading", None, &block.loading),
+                    ("Ok", block.ok_binding.as_ref().map(|b| (b, "value")), &block.ok),
+                    ("Err", block.err_binding.as_ref().map(|b| (b, "error")), &block.err),
+                ];
+                for (variant, binding, body) in branches {
+                    if body.is_empty() && binding.is_none() {
+                        output.push_str(&format!("{}    is UiState.{} -> {{}}\n", indent_str, variant));
+                        continue;
+                    }
+                    output.push_str(&format!("{}    is UiState.{} -> {{\n", indent_str, variant));
+                    if let Some((name, field)) = binding {
+                        output.push_str(&format!("{}        val {} = {}.{}\n", indent_str, name, state_name, field));
+                    }
+                    for child in body {
+                        output.push_str(&self.generate_markup_with_indent(child, indent + 2)?);
+                    }
+                    output.push_str(&format!("{}    }}\n", indent_str));
+                }
+                output.push_str(&format!("{}}}\n", indent_str));
+                Ok(output)
+            }
             Markup::Component(comp) => {
                 let mut output = String::new();
                 let base_indent_str = "    ".repeat(indent);
@@ -1282,13 +1371,21 @@ impl ComposeBackend {
                     let on_refresh_expr = on_refresh_prop
                         .map(|p| self.get_prop_expr(&p.value))
                         .unwrap_or_default();
-                    let is_refreshing_expr = is_refreshing_prop
-                        .map(|p| self.get_prop_expr(&p.value))
-                        .unwrap_or("false");
+                    let mut is_refreshing_expr = is_refreshing_prop
+                        .map(|p| self.get_prop_expr(&p.value).to_string())
+                        .unwrap_or_else(|| "false".to_string());
 
                     // Transform onRefresh - if it's a function reference, make it a call
                     let mut on_refresh_call = on_refresh_expr.to_string();
-                    if self.in_viewmodel_wrapper {
+                    // onRefresh={photos.refresh} on an $async value also drives the indicator
+                    let async_refresh = on_refresh_expr.trim().strip_suffix(".refresh")
+                        .filter(|name| self.async_values.contains(*name));
+                    if let Some(name) = async_refresh {
+                        on_refresh_call = format!("{}.refresh()", name);
+                        if is_refreshing_prop.is_none() {
+                            is_refreshing_expr = format!("{}.isRefreshing", name);
+                        }
+                    } else if self.in_viewmodel_wrapper {
                         let expr_trimmed = on_refresh_expr.trim();
                         // If it's a known function name without (), add () to call it
                         if self.function_names.contains(&expr_trimmed.to_string()) && !expr_trimmed.contains('(') {
@@ -2352,9 +2449,9 @@ impl ComposeBackend {
                         }
                     }
 
-                    // Process other props
+                    // Process other props (skipping those handled by the PullToRefreshBox wrapper)
                     for prop in &comp.props {
-                        if handled.contains(&prop.name) {
+                        if handled.contains(&prop.name) || prop.name == "onRefresh" || prop.name == "isRefreshing" {
                             continue;
                         }
                         let prop_expr = self.get_prop_expr(&prop.value);
@@ -2886,6 +2983,12 @@ impl ComposeBackend {
                 }
                 false
             }
+            Markup::When(when_block) => {
+                when_block.branches.iter().any(|branch| self.scan_for_on_refresh(&branch.body))
+            }
+            Markup::Await(block) => {
+                block.children().any(|item| self.scan_for_on_refresh(item))
+            }
             _ => false,
         }
     }
@@ -3597,6 +3700,12 @@ impl ComposeBackend {
                     self.collect_imports_recursive(&branch.body, prop_imports, component_imports);
                 }
             }
+            Markup::Await(block) => {
+                self.add_import_if_missing(prop_imports, &self.resolve_import("$UiState"));
+                for item in block.children() {
+                    self.collect_imports_with_parent(item, prop_imports, component_imports, parent_component);
+                }
+            }
             Markup::Interpolation(expr) => {
                 // Check for R.string references
                 if expr.contains("R.string.") {
@@ -4798,6 +4907,11 @@ impl ComposeBackend {
                     self.extract_params_from_single_markup(&branch.body, params);
                 }
             }
+            Markup::Await(block) => {
+                for item in block.children() {
+                    self.extract_params_from_single_markup(item, params);
+                }
+            }
         }
     }
 
@@ -5259,27 +5373,70 @@ impl ComposeBackend {
     fn detect_fetch_usage(&mut self, file: &WhitehallFile) {
         // Check state initial values
         for state in &file.state {
-            if state.initial_value.contains("$fetch(") {
+            if state.initial_value.contains("$fetch") {
                 self.uses_fetch = true;
                 return;
             }
         }
         // Check lifecycle hooks
         for hook in &file.lifecycle_hooks {
-            if hook.body.contains("$fetch(") {
+            if hook.body.contains("$fetch") {
                 self.uses_fetch = true;
                 return;
             }
         }
         // Check functions
         for func in &file.functions {
-            if func.body.contains("$fetch(") {
+            if func.body.contains("$fetch") {
                 self.uses_fetch = true;
                 return;
             }
         }
     }
 
+    /// Detect vals declared with $async { ... }
+    fn detect_async_usage(&mut self, file: &WhitehallFile) {
+        for state in &file.state {
+            if !state.mutable && Self::parse_async_value(&state.initial_value).is_some() {
+                self.async_values.insert(state.name.clone());
+            }
+        }
+    }
+
+    /// Split `$async { body }` or `$async(key1, key2) { body }` into (keys, body)
+    fn parse_async_value(value: &str) -> Option<(String, String)> {
+        let rest = value.trim().strip_prefix("$async")?.trim_start();
+        let (keys, rest) = if rest.starts_with('(') {
+            let close = find_matching_paren(rest, 0)?;
+            (rest[1..close].trim().to_string(), rest[close + 1..].trim_start())
+        } else {
+            (String::new(), rest)
+        };
+        let body = rest.strip_prefix('{')?.strip_suffix('}')?;
+        Some((keys, body.to_string()))
+    }
+
+    /// Lines of an $async block, re-indented under `indent`, with $fetch calls transformed
+    /// The last line is the block's value, so it never defaults to a Unit response
+    fn async_body(&self, body: &str, indent: &str) -> String {
+        let body = Self::join_fetch_calls(body);
+        let lines: Vec<&str> = body.lines().filter(|line| !line.trim().is_empty()).collect();
+        let min_indent = lines.iter()
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        let mut output = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            // Source uses 2 spaces per level
+            let relative_levels = (line.len() - line.trim_start().len() - min_indent) / 2;
+            let transformed = self.transform_fetch(line.trim(), i < lines.len() - 1)
+                .unwrap_or_else(|_| line.trim().to_string());
+            output.push_str(&format!("{}{}{}\n", indent, "    ".repeat(relative_levels), transformed));
+        }
+        output
+    }
+
     /// Detect if a file uses the $log() API by scanning functions and lifecycle hooks
     fn detect_log_usage(&mut self, file: &WhitehallFile) {
         // Check lifecycle hooks
@@ -5343,77 +5500,222 @@ impl ComposeBackend {
         }
     }
 
-    /// Transform $fetch() calls to Ktor HttpClient calls
-    /// Input: photos = $fetch("https://api.example.com/data")
-    /// Output: photos = httpClient.get("https://api.example.com/data").body()
+    /// Transform $fetch calls to the shared `Fetch` client generated in the base package
+    /// Input:  val user: User = $fetch.post("/users", draft, { headers: { "X-Trace": id } })
+    /// Output: val user: User = Fetch.request<User>("/users", method = "POST", body = draft, headers = mapOf("X-Trace" to id))
+    /// A `FetchResult<T>` annotation (or `$fetch.result(...)`) returns errors as values instead of throwing
     fn transform_fetch_call(&self, line: &str) -> String {
-        // Simple regex-like replacement for $fetch("url") -> httpClient.get("url").body<Type>()
-        // Handle: val x: Type = $fetch("url")
-        let mut result = line.to_string();
+        self.transform_fetch(line, true).unwrap_or_else(|_| line.to_string())
+    }
 
-        // Try to extract type from assignment: val x: Type = $fetch(...)
-        let type_param = if let Some(colon_pos) = line.find(':') {
-            if let Some(eq_pos) = line.find('=') {
-                if colon_pos < eq_pos {
-                    // Extract type between : and =
-                    let type_str = line[colon_pos + 1..eq_pos].trim();
-                    Some(type_str.to_string())
-                } else {
-                    None
-                }
-            } else {
-                None
+    /// Options `Fetch.request` takes, besides the URL
+    const FETCH_OPTIONS: &'static [&'static str] = &["method", "headers", "body", "timeout"];
+
+    /// Report invalid `$fetch` calls before generating, since the transforms run line by
+    /// line where errors can't be returned
+    fn check_fetch_calls(&self, file: &WhitehallFile) -> Result<(), String> {
+        let sources = file.state.iter().map(|s| s.initial_value.as_str())
+            .chain(file.lifecycle_hooks.iter().map(|h| h.body.as_str()))
+            .chain(file.functions.iter().map(|f| f.body.as_str()))
+            .chain(file.classes.iter().flat_map(|c| c.functions.iter().map(|f| f.body.as_str())))
+            .chain(file.classes.iter().flat_map(|c| c.properties.iter().filter_map(|p| p.initial_value.as_deref())))
+            .filter(|source| source.contains("$fetch"));
+        for source in sources {
+            for line in Self::join_fetch_calls(source).lines() {
+                self.transform_fetch(line, false)?;
             }
-        } else {
-            None
-        };
+        }
+        Ok(())
+    }
 
-        // Find $fetch( and replace with httpClient.get(
-        if let Some(start) = result.find("$fetch(") {
-            // Find the matching closing paren
-            let after_fetch = &result[start + 7..]; // after "$fetch("
-            let mut depth = 1;
-            let mut end_pos = 0;
+    /// Put each `$fetch(...)` call on one line, so the line-by-line transforms also
+    /// see arguments and options objects written across several lines
+    fn join_fetch_calls(body: &str) -> String {
+        let mut result = body.to_string();
+        let mut search_from = 0;
 
-            for (i, ch) in after_fetch.char_indices() {
-                match ch {
-                    '(' => depth += 1,
-                    ')' => {
-                        depth -= 1;
-                        if depth == 0 {
-                            end_pos = i;
-                            break;
+        while let Some(found) = result[search_from..].find("$fetch") {
+            let start = search_from + found;
+            search_from = start + 6;
+
+            // The argument list after `$fetch`, `$fetch.post` or `$fetch<T>`, on the same line
+            let Some(open) = result[start..].find(['(', '\n']).map(|i| start + i) else {
+                break;
+            };
+            if !result[open..].starts_with('(') {
+                continue;
+            }
+            let Some(close) = find_matching_paren(&result, open) else {
+                break;
+            };
+            if result[open..close].contains('\n') {
+                let joined = result[open..=close]
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                result.replace_range(open..=close, &joined);
+            }
+        }
+
+        result
+    }
+
+    /// `statement_default`: calls used as statements decode the response as Unit
+    fn transform_fetch(&self, line: &str, statement_default: bool) -> Result<String, String> {
+        let mut result = line.to_string();
+        let mut search_from = 0;
+
+        while let Some(found) = result[search_from..].find("$fetch") {
+            let start = search_from + found;
+            let after = &result[start + 6..];
+
+            // $fetch(...) or $fetch.<verb>(...), optionally with an explicit response type: $fetch<T>(...)
+            let verb_len = after.strip_prefix('.')
+                .map(|rest| 1 + rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len()))
+                .unwrap_or(0);
+            let verb = if verb_len > 0 { &after[1..verb_len] } else { "" };
+            let mut open = start + 6 + verb_len;
+            let mut explicit_type = None;
+            if result[open..].starts_with('<') {
+                let mut depth = 0;
+                let close = result[open..].char_indices().find_map(|(i, ch)| {
+                    match ch {
+                        '<' => depth += 1,
+                        '>' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return Some(open + i);
+                            }
                         }
+                        _ => {}
                     }
-                    _ => {}
+                    None
+                });
+                if let Some(close) = close {
+                    explicit_type = Some(result[open + 1..close].trim().to_string());
+                    open = close + 1;
                 }
             }
-
-            if depth == 0 {
-                // Extract the URL argument
-                let url_arg = &after_fetch[..end_pos];
-                // Replace fetch(...) with httpClient.get(...).body<Type>() if type is known
-                let replacement = if let Some(ref t) = type_param {
-                    format!("httpClient.get({}).body<{}>()", url_arg, t)
-                } else {
-                    format!("httpClient.get({}).body()", url_arg)
-                };
-                result = format!("{}{}{}", &result[..start], replacement, &after_fetch[end_pos + 1..]);
+            if !result[open..].starts_with('(') {
+                search_from = start + 6;
+                continue;
             }
+            let Some(close) = find_matching_paren(&result, open) else {
+                break;
+            };
+
+            let mut args = split_top_level(&result[open + 1..close]).into_iter();
+            let url = args.next().unwrap_or_default();
+            let mut named = Vec::new();
+            let mut options = None;
+            match verb {
+                "" | "get" | "result" => options = args.next(),
+                "post" | "put" | "patch" | "delete" => {
+                    named.push(format!("method = \"{}\"", verb.to_uppercase()));
+                    match args.next() {
+                        Some(arg) if arg.starts_with('{') => options = Some(arg),
+                        Some(body) => {
+                            named.push(format!("body = {}", body));
+                            options = args.next();
+                        }
+                        None => {}
+                    }
+                }
+                _ => {
+                    search_from = start + 6;
+                    continue;
+                }
+            }
+            if let Some(options) = options {
+                named.extend(Self::fetch_options(&options, !verb.is_empty() && verb != "get" && verb != "result")?);
+            }
+
+            // Response type from the declaration before the call: val x: Type = $fetch(...)
+            let declared_type = result[..start].rfind('=').and_then(|eq| {
+                let before = &result[..eq];
+                let colon = before.rfind(':')?;
+                let type_str = before[colon + 1..].trim();
+                (!type_str.is_empty() && !type_str.contains('"')).then(|| type_str.to_string())
+            });
+            let wrapped = declared_type.as_deref()
+                .and_then(|t| t.strip_prefix("FetchResult<"))
+                .and_then(|t| t.strip_suffix('>'));
+            // A call used as a statement has no expected type to infer the response from
+            let statement = statement_default && result[..start].trim().is_empty();
+            let (function, type_arg) = match (verb, wrapped) {
+                ("result", _) if explicit_type.is_some() => ("result", explicit_type),
+                (_, Some(inner)) => ("result", Some(inner.to_string())),
+                ("result", None) => ("result", statement.then(|| "Unit".to_string())),
+                _ if explicit_type.is_some() => ("request", explicit_type),
+                _ if statement => ("request", Some("Unit".to_string())),
+                _ => ("request", declared_type),
+            };
+
+            let mut call_args = vec![url];
+            call_args.extend(named);
+            let type_args = type_arg.map(|t| format!("<{}>", t)).unwrap_or_default();
+            let replacement = format!("Fetch.{}{}({})", function, type_args, call_args.join(", "));
+            search_from = start + replacement.len();
+            result = format!("{}{}{}", &result[..start], replacement, &result[close + 1..]);
         }
 
-        result
+        Ok(result)
     }
 
-    /// Generate HttpClient singleton for $fetch() API
-    fn generate_http_client(&self) -> String {
-        r#"private val httpClient = HttpClient(OkHttp) {
-    install(ContentNegotiation) {
-        json(Json { ignoreUnknownKeys = true })
+    /// Convert a JS-style options object (`{ method: "POST", headers: { "A": b }, timeout: 5000 }`)
+    /// into named arguments for `Fetch.request`; keys it doesn't take are an error
+    fn fetch_options(options: &str, has_method: bool) -> Result<Vec<String>, String> {
+        let inner = options.trim().trim_start_matches('{').trim_end_matches('}');
+        let mut named = Vec::new();
+        for entry in split_top_level(inner) {
+            let Some((key, value)) = entry.split_once(':') else {
+                return Err(format!("Invalid $fetch option '{}'. Expected 'name: value'", entry));
+            };
+            let key = key.trim().trim_matches('"');
+            let value = value.trim();
+            if !Self::FETCH_OPTIONS.contains(&key) {
+                return Err(format!(
+                    "Unknown $fetch option '{}'. Supported options: {}",
+                    key,
+                    Self::FETCH_OPTIONS.join(", ")
+                ));
+            }
+            let argument = match key {
+                "method" if has_method => None,
+                "headers" if value.starts_with('{') => {
+                    let inner = value.trim_start_matches('{').trim_end_matches('}');
+                    let pairs: Vec<String> = split_top_level(inner).iter()
+                        .filter_map(|header| {
+                            let (name, value) = header.split_once(':')?;
+                            Some(format!("\"{}\" to {}", name.trim().trim_matches('"'), value.trim()))
+                        })
+                        .collect();
+                    Some(format!("headers = mapOf({})", pairs.join(", ")))
+                }
+                _ => Some(format!("{} = {}", key, value)),
+            };
+            named.extend(argument);
+        }
+        Ok(named)
     }
-}
 
-"#.to_string()
+    /// Imports for the shared `Fetch` client and its result/error types
+    fn fetch_imports(&self, file: &WhitehallFile) -> Vec<String> {
+        let mut imports = vec![self.resolve_import("$Fetch")];
+        let sources = file.state.iter().map(|s| s.initial_value.as_str())
+            .chain(file.state.iter().filter_map(|s| s.type_annotation.as_deref()))
+            .chain(file.lifecycle_hooks.iter().map(|h| h.body.as_str()))
+            .chain(file.functions.iter().map(|f| f.body.as_str()))
+            .chain(file.functions.iter().filter_map(|f| f.return_type.as_deref()))
+            .collect::<Vec<_>>();
+        for name in ["FetchResult", "FetchException"] {
+            if sources.iter().any(|s| s.contains(name)) {
+                imports.push(self.resolve_import(&format!("${}", name)));
+            }
+        }
+        imports
     }
 
     /// Transform $log() calls to Android Log calls
@@ -5851,7 +6153,8 @@ impl ComposeBackend {
                 }
 
                 let word: String = chars[start..i].iter().collect();
-                if word == identifier {
+                // Member access (x.name) isn't a reference to the variable, and keeps re-runs idempotent
+                if word == identifier && !result.ends_with('.') {
                     result.push_str(replacement);
                 } else {
                     result.push_str(&word);
@@ -5894,6 +6197,43 @@ impl ComposeBackend {
         "Unknown".to_string()
     }
 
+    /// Generate the `_uiState` MutableStateFlow declaration for a ViewModel
+    /// Saved properties (name, type, default) are restored from SavedStateHandle when present
+    fn generate_ui_state_flow(&self, saved: &[(String, String, String)]) -> String {
+        if saved.is_empty() {
+            return "    private val _uiState = MutableStateFlow(UiState())\n".to_string();
+        }
+
+        let mut output = String::from("    private val _uiState = MutableStateFlow(\n        UiState(\n");
+        for (i, (name, type_str, default_value)) in saved.iter().enumerate() {
+            // Nullable types can't use ?: (a saved null must stay null)
+            let restored = if let Some(base_type) = type_str.strip_suffix('?') {
+                format!(
+                    "if (\"{}\" in savedStateHandle) savedStateHandle.get<{}>(\"{}\") else {}",
+                    name, base_type, name, default_value
+                )
+            } else {
+                format!("savedStateHandle.get<{}>(\"{}\") ?: {}", type_str, name, default_value)
+            };
+            let comma = if i < saved.len() - 1 { "," } else { "" };
+            output.push_str(&format!("            {} = {}{}\n", name, restored, comma));
+        }
+        output.push_str("        )\n    )\n");
+        output
+    }
+
+    /// Generate the setter for a UiState-backed property (also writes @saved values to SavedStateHandle)
+    fn generate_state_setter(&self, name: &str, is_saved: bool) -> String {
+        if is_saved {
+            format!(
+                "        set(value) {{\n            _uiState.update {{ it.copy({} = value) }}\n            savedStateHandle[\"{}\"] = value\n        }}\n\n",
+                name, name
+            )
+        } else {
+            format!("        set(value) {{ _uiState.update {{ it.copy({} = value) }} }}\n\n", name)
+        }
+    }
+
     /// Check if a type is numeric (Int, Double, Float, Long)
     fn is_numeric_type(&self, type_str: &str) -> bool {
         matches!(
@@ -6104,6 +6444,7 @@ impl ComposeBackend {
         imports.push("kotlinx.coroutines.flow.StateFlow".to_string());
         imports.push("kotlinx.coroutines.flow.asStateFlow".to_string());
         imports.push("kotlinx.coroutines.flow.update".to_string());
+        imports.extend(self.persisted_store_imports(class));
 
         // Add user imports from file
         for import in &file.imports {
@@ -6121,16 +6462,61 @@ impl ComposeBackend {
         }
         output.push('\n');
 
-        // Object declaration (not class!)
-        output.push_str(&format!("object {} {{\n", class.name));
+        output.push_str(&self.generate_singleton_store_object(class));
+
+        // Append pass-through Kotlin blocks
+        if !file.kotlin_blocks.is_empty() {
+            output.push('\n');
+            for block in &file.kotlin_blocks {
+                output.push_str(&block.content);
+                output.push_str("\n\n");
+            }
+        }
+
+        Ok(output)
+    }
 
-        // Generate UiState data class (same as ViewModel)
+    /// Generate just the singleton store object code (no package/imports)
+    /// Also used when embedding store in a screen file that also has markup
+    fn generate_singleton_store_object(&self, class: &ClassDeclaration) -> String {
+        let mut output = String::new();
+
+        // Generate State data class
         let var_properties: Vec<_> = class.properties.iter().filter(|p| p.mutable).collect();
 
+        // (name, type) of each persisted property; empty unless @store(persist = ...)
+        let persisted: Vec<(String, String)> = if class.persist.is_some() {
+            var_properties.iter()
+                .map(|p| {
+                    let type_str = p.type_annotation.clone().unwrap_or_else(|| {
+                        self.infer_type_from_value(p.initial_value.as_deref().unwrap_or(""))
+                    });
+                    (p.name.clone(), type_str)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // DataStore delegate must be a top-level extension property on Context
+        if let Some(persist_name) = &class.persist {
+            output.push_str(&format!(
+                "private val Context.{}DataStore: DataStore<Preferences> by preferencesDataStore(name = \"{}\")\n\n",
+                self.snake_to_camel(&class.name), persist_name
+            ));
+        }
+
+        // Object declaration (not class!)
+        output.push_str(&format!("object {} {{\n", class.name));
+
         if !var_properties.is_empty() {
             output.push_str("    data class State(\n");
             for (i, prop) in var_properties.iter().enumerate() {
-                let type_annotation = prop.type_annotation.as_ref()
+                // Persisted properties always have a resolved type (validated by the analyzer)
+                let type_annotation = persisted.iter()
+                    .find(|(name, _)| name == &prop.name)
+                    .map(|(_, t)| t)
+                    .or(prop.type_annotation.as_ref())
                     .map(|t| format!(": {}", t))
                     .unwrap_or_else(|| String::from(": Any"));
                 let initial_value = prop.initial_value.as_ref()
@@ -6147,14 +6533,30 @@ impl ComposeBackend {
             output.push_str("    private val _state = MutableStateFlow(State())\n");
             output.push_str("    val state: StateFlow<State> = _state.asStateFlow()\n\n");
 
+            if !persisted.is_empty() {
+                output.push_str(&self.generate_persisted_store_init(class, &persisted));
+            }
+
             // Property accessors
             for prop in &var_properties {
-                output.push_str(&format!("    var {}: {}\n",
-                    prop.name,
-                    prop.type_annotation.as_ref().unwrap_or(&"Any".to_string())));
+                let persisted_type = persisted.iter()
+                    .find(|(name, _)| name == &prop.name)
+                    .map(|(_, t)| t.as_str());
+                let type_str = persisted_type
+                    .map(|t| t.to_string())
+                    .or_else(|| prop.type_annotation.clone())
+                    .unwrap_or_else(|| "Any".to_string());
+                output.push_str(&format!("    var {}: {}\n", prop.name, type_str));
                 output.push_str(&format!("        get() = _state.value.{}\n", prop.name));
-                output.push_str(&format!("        set(value) {{ _state.update {{ it.copy({} = value) }} }}\n\n",
-                    prop.name));
+                if let Some(type_str) = persisted_type {
+                    output.push_str("        set(value) {\n");
+                    output.push_str(&format!("            _state.update {{ it.copy({} = value) }}\n", prop.name));
+                    output.push_str(&format!("            persist {{ {} }}\n", self.preferences_write(&prop.name, type_str)));
+                    output.push_str("        }\n\n");
+                } else {
+                    output.push_str(&format!("        set(value) {{ _state.update {{ it.copy({} = value) }} }}\n\n",
+                        prop.name));
+                }
             }
         }
 
@@ -6184,92 +6586,223 @@ impl ComposeBackend {
             output.push_str("    }\n\n");
         }
 
-        output.push_str("}\n");
-
-        // Append pass-through Kotlin blocks
-        if !file.kotlin_blocks.is_empty() {
-            output.push('\n');
-            for block in &file.kotlin_blocks {
-                output.push_str(&block.content);
-                output.push_str("\n\n");
-            }
+        if !persisted.is_empty() {
+            output.push_str("    private fun persist(block: (MutablePreferences) -> Unit) {\n");
+            output.push_str(&format!(
+                "        val store = checkNotNull(dataStore) {{ \"{}.init(context) must run before its values are set. Is StoreInitializer missing from the manifest?\" }}\n",
+                class.name
+            ));
+            output.push_str("        scope.launch { store.edit { block(it) } }\n");
+            output.push_str("    }\n");
         }
 
-        Ok(output)
+        output.push_str("}\n");
+
+        output
     }
 
-    /// Generate just the singleton store object code (no package/imports)
-    /// Used when embedding store in a screen file that also has markup
-    fn generate_singleton_store_object(&self, class: &ClassDeclaration) -> String {
-        let mut output = String::new();
+    /// Generate a data-only file: pass-through Kotlin, with `@entity` classes turned into
+    /// Room entities and their DAOs written to a second `<Name>Dao.kt` file
+    fn generate_model_file(&self, file: &WhitehallFile) -> Result<crate::transpiler::TranspileResult, String> {
+        use crate::transpiler::room;
 
-        // Object declaration (not class!)
-        output.push_str(&format!("object {} {{\n", class.name));
+        let entities = room::parse_entities(&file.kotlin_blocks)?;
+        let user_imports: Vec<String> = file.imports.iter()
+            .map(|import| self.resolve_import(&import.path))
+            .collect();
 
-        // Generate State data class
-        let var_properties: Vec<_> = class.properties.iter().filter(|p| p.mutable).collect();
+        let mut imports = user_imports.clone();
+        if !entities.is_empty() {
+            imports.push("androidx.room.Entity".to_string());
+            imports.push("androidx.room.PrimaryKey".to_string());
+            imports.extend(room::converter_imports(&entities));
+        }
+        if file.kotlin_blocks.iter().any(|b| b.content.contains("@Serializable")) {
+            imports.push("kotlinx.serialization.Serializable".to_string());
+        }
+        imports.sort();
+        imports.dedup();
 
-        if !var_properties.is_empty() {
-            output.push_str("    data class State(\n");
-            for (i, prop) in var_properties.iter().enumerate() {
-                let type_annotation = prop.type_annotation.as_ref()
-                    .map(|t| format!(": {}", t))
-                    .unwrap_or_else(|| String::from(": Any"));
-                let initial_value = prop.initial_value.as_ref()
-                    .map(|v| format!(" = {}", v))
-                    .unwrap_or_default();
+        let mut output = format!("package {}\n\n", self.package);
+        for import in &imports {
+            output.push_str(&format!("import {}\n", import));
+        }
+        if !imports.is_empty() {
+            output.push('\n');
+        }
 
-                let comma = if i < var_properties.len() - 1 { "," } else { "" };
-                output.push_str(&format!("        val {}{}{}{}\n",
-                    prop.name, type_annotation, initial_value, comma));
+        let mut entity_iter = entities.iter();
+        for block in &file.kotlin_blocks {
+            if room::is_entity_block(block) {
+                if let Some(entity) = entity_iter.next() {
+                    output.push_str(&room::generate_entity(entity));
+                }
+            } else {
+                output.push_str(block.content.trim_end());
+                output.push('\n');
             }
-            output.push_str("    )\n\n");
+            output.push('\n');
+        }
+        let output = format!("{}\n", output.trim_end());
 
-            // StateFlow setup (no viewModelScope - it's a singleton)
-            output.push_str("    private val _state = MutableStateFlow(State())\n");
-            output.push_str("    val state: StateFlow<State> = _state.asStateFlow()\n\n");
+        if entities.is_empty() {
+            return Ok(crate::transpiler::TranspileResult::Single(output));
+        }
 
-            // Property accessors
-            for prop in &var_properties {
-                output.push_str(&format!("    var {}: {}\n",
-                    prop.name,
-                    prop.type_annotation.as_ref().unwrap_or(&"Any".to_string())));
-                output.push_str(&format!("        get() = _state.value.{}\n", prop.name));
-                output.push_str(&format!("        set(value) {{ _state.update {{ it.copy({} = value) }} }}\n\n",
-                    prop.name));
-            }
+        let mut dao_imports = user_imports;
+        for import in ["androidx.room.Dao", "androidx.room.Delete", "androidx.room.Insert",
+            "androidx.room.OnConflictStrategy", "androidx.room.Query", "androidx.room.Update",
+            "kotlinx.coroutines.flow.Flow"] {
+            dao_imports.push(import.to_string());
         }
+        dao_imports.sort();
+        dao_imports.dedup();
 
-        // Derived properties (val with getter)
-        for prop in &class.properties {
-            if !prop.mutable {
-                if let Some(getter) = &prop.getter {
-                    let type_annotation = prop.type_annotation.as_ref()
-                        .map(|t| format!(": {}", t))
-                        .unwrap_or_default();
-                    output.push_str(&format!("    val {}{}\n", prop.name, type_annotation));
-                    output.push_str(&format!("        get() = {}\n\n", getter));
+        let mut dao_output = format!("package {}\n\n", self.package);
+        for import in &dao_imports {
+            dao_output.push_str(&format!("import {}\n", import));
+        }
+        for entity in &entities {
+            dao_output.push('\n');
+            dao_output.push_str(&room::generate_dao(entity));
+        }
+
+        Ok(crate::transpiler::TranspileResult::Multiple(vec![
+            (String::new(), output),
+            ("Dao".to_string(), dao_output),
+        ]))
+    }
+
+    /// Imports needed by a DataStore-backed store (empty for non-persisted stores)
+    fn persisted_store_imports(&self, class: &ClassDeclaration) -> Vec<String> {
+        if class.persist.is_none() {
+            return Vec::new();
+        }
+
+        let mut imports = vec![
+            "android.content.Context".to_string(),
+            "androidx.datastore.core.DataStore".to_string(),
+            "androidx.datastore.preferences.core.MutablePreferences".to_string(),
+            "androidx.datastore.preferences.core.Preferences".to_string(),
+            "androidx.datastore.preferences.core.edit".to_string(),
+            "androidx.datastore.preferences.preferencesDataStore".to_string(),
+            "kotlinx.coroutines.CoroutineScope".to_string(),
+            "kotlinx.coroutines.Dispatchers".to_string(),
+            "kotlinx.coroutines.SupervisorJob".to_string(),
+            "kotlinx.coroutines.launch".to_string(),
+        ];
+
+        for prop in class.properties.iter().filter(|p| p.mutable) {
+            let type_str = prop.type_annotation.clone().unwrap_or_else(|| {
+                self.infer_type_from_value(prop.initial_value.as_deref().unwrap_or(""))
+            });
+            match crate::transpiler::analyzer::preferences_key_type(&type_str) {
+                Some(key_type) => {
+                    imports.push(format!("androidx.datastore.preferences.core.{}PreferencesKey", key_type));
+                    if type_str.trim().ends_with('?') {
+                        // Null marker, see generate_persisted_store_init
+                        imports.push("androidx.datastore.preferences.core.booleanPreferencesKey".to_string());
+                    }
+                }
+                None => {
+                    // Complex values are stored as JSON strings
+                    imports.push("androidx.datastore.preferences.core.stringPreferencesKey".to_string());
+                    imports.push("kotlinx.serialization.decodeFromString".to_string());
+                    imports.push("kotlinx.serialization.encodeToString".to_string());
+                    imports.push("kotlinx.serialization.json.Json".to_string());
                 }
             }
         }
 
-        // Functions (no viewModelScope for singletons)
-        for func in &class.functions {
-            let suspend_keyword = if func.is_suspend { "suspend " } else { "" };
-            output.push_str(&format!("    {}fun {}({})", suspend_keyword, func.name, func.params));
-            if let Some(return_type) = &func.return_type {
-                output.push_str(&format!(": {}", return_type));
+        imports
+    }
+
+    /// Generate the Preferences keys and `init(context)` loader for a persisted store
+    ///
+    /// A nullable primitive set to null gets a `NullKeys` marker, so the stored null
+    /// isn't read back as a missing key and replaced by the default.
+    fn generate_persisted_store_init(&self, class: &ClassDeclaration, persisted: &[(String, String)]) -> String {
+        let mut output = String::new();
+
+        output.push_str("    private object Keys {\n");
+        for (name, type_str) in persisted {
+            let key_type = crate::transpiler::analyzer::preferences_key_type(type_str).unwrap_or("string");
+            output.push_str(&format!("        val {} = {}PreferencesKey(\"{}\")\n", name, key_type, name));
+        }
+        output.push_str("    }\n\n");
+
+        let nullable: Vec<&String> = persisted.iter()
+            .filter(|(_, type_str)| Self::has_null_marker(type_str))
+            .map(|(name, _)| name)
+            .collect();
+        if !nullable.is_empty() {
+            output.push_str("    private object NullKeys {\n");
+            for name in nullable {
+                output.push_str(&format!("        val {} = booleanPreferencesKey(\"{}.isNull\")\n", name, name));
             }
-            output.push_str(" {\n");
-            output.push_str(&format!("        {}\n", func.body.trim()));
             output.push_str("    }\n\n");
         }
 
-        output.push_str("}\n");
+        output.push_str("    private val scope = CoroutineScope(SupervisorJob() + Dispatchers.IO)\n");
+        output.push_str("    private var dataStore: DataStore<Preferences>? = null\n\n");
+
+        output.push_str("    fun init(context: Context) {\n");
+        output.push_str("        if (dataStore != null) return\n");
+        output.push_str(&format!(
+            "        val store = context.applicationContext.{}DataStore\n",
+            self.snake_to_camel(&class.name)
+        ));
+        output.push_str("        dataStore = store\n");
+        output.push_str("        scope.launch {\n");
+        output.push_str("            store.data.collect { prefs ->\n");
+        output.push_str("                val defaults = State()\n");
+        output.push_str("                _state.update {\n");
+        output.push_str("                    it.copy(\n");
+        for (i, (name, type_str)) in persisted.iter().enumerate() {
+            let read = if Self::has_null_marker(type_str) {
+                format!("if (prefs[NullKeys.{}] == true) null else prefs[Keys.{}] ?: defaults.{}", name, name, name)
+            } else if crate::transpiler::analyzer::preferences_key_type(type_str).is_some() {
+                format!("prefs[Keys.{}] ?: defaults.{}", name, name)
+            } else {
+                format!(
+                    // A value that no longer decodes (e.g. after the type changed) falls back to the
+                    // default, but a stored JSON null stays null
+                    "prefs[Keys.{}].let {{ json -> if (json == null) defaults.{} else runCatching {{ Json.decodeFromString<{}>(json) }}.getOrElse {{ defaults.{} }} }}",
+                    name, name, type_str, name
+                )
+            };
+            let comma = if i < persisted.len() - 1 { "," } else { "" };
+            output.push_str(&format!("                        {} = {}{}\n", name, read, comma));
+        }
+        output.push_str("                    )\n");
+        output.push_str("                }\n");
+        output.push_str("            }\n");
+        output.push_str("        }\n");
+        output.push_str("    }\n\n");
 
         output
     }
 
+    /// Preferences write for a persisted property setter (inside `persist { ... }`)
+    fn preferences_write(&self, name: &str, type_str: &str) -> String {
+        if crate::transpiler::analyzer::preferences_key_type(type_str).is_none() {
+            format!("it[Keys.{}] = Json.encodeToString<{}>(value)", name, type_str)
+        } else if Self::has_null_marker(type_str) {
+            // Preferences can't hold null, so the key is removed and the marker set
+            format!(
+                "if (value != null) {{ it[Keys.{}] = value; it.remove(NullKeys.{}) }} else {{ it.remove(Keys.{}); it[NullKeys.{}] = true }}",
+                name, name, name, name
+            )
+        } else {
+            format!("it[Keys.{}] = value", name)
+        }
+    }
+
+    /// Whether a persisted property is a nullable primitive, whose null needs a marker key
+    fn has_null_marker(type_str: &str) -> bool {
+        type_str.trim().ends_with('?') && crate::transpiler::analyzer::preferences_key_type(type_str).is_some()
+    }
+
     /// Generate ViewModel code for reactive class
     fn generate_view_model_store(&self, file: &WhitehallFile, class: &ClassDeclaration, source: crate::transpiler::analyzer::StoreSource) -> Result<String, String> {
         let mut output = String::new();
@@ -6304,6 +6837,12 @@ impl ComposeBackend {
             imports.push("javax.inject.Inject".to_string());
         }
 
+        // @saved properties are backed by SavedStateHandle
+        let has_saved = class.properties.iter().any(|p| p.is_saved);
+        if has_saved {
+            imports.push("androidx.lifecycle.SavedStateHandle".to_string());
+        }
+
         // Add user imports from file
         for import in &file.imports {
             let resolved = self.resolve_import(&import.path);
@@ -6328,13 +6867,22 @@ impl ComposeBackend {
         // Class declaration
         output.push_str(&format!("class {}", class.name));
 
-        // Constructor
+        // Constructor (SavedStateHandle goes first so @Inject and viewModel() both provide it)
         if let Some(constructor) = &class.constructor {
             output.push(' ');
             if !constructor.annotations.is_empty() {
                 output.push_str("@Inject ");
             }
-            output.push_str(&format!("constructor(\n    {}\n)", constructor.parameters));
+            if has_saved {
+                output.push_str(&format!(
+                    "constructor(\n    private val savedStateHandle: SavedStateHandle,\n    {}\n)",
+                    constructor.parameters
+                ));
+            } else {
+                output.push_str(&format!("constructor(\n    {}\n)", constructor.parameters));
+            }
+        } else if has_saved {
+            output.push_str("(\n    private val savedStateHandle: SavedStateHandle\n)");
         }
 
         output.push_str(" : ViewModel() {\n");
@@ -6370,8 +6918,20 @@ impl ComposeBackend {
             }
             output.push_str("    )\n\n");
 
-            // Generate StateFlow
-            output.push_str("    private val _uiState = MutableStateFlow(UiState())\n");
+            // Generate StateFlow (restoring @saved properties from SavedStateHandle)
+            let saved: Vec<(String, String, String)> = ui_state_properties.iter()
+                .filter(|p| p.is_saved)
+                .map(|p| {
+                    let type_str = match (&p.type_annotation, &p.initial_value) {
+                        (Some(type_ann), _) => type_ann.clone(),
+                        (None, Some(init_val)) => self.infer_type_from_value(init_val),
+                        (None, None) => "String".to_string(),
+                    };
+                    let default_val = p.initial_value.clone().unwrap_or_else(|| "\"\"".to_string());
+                    (p.name.clone(), type_str, default_val)
+                })
+                .collect();
+            output.push_str(&self.generate_ui_state_flow(&saved));
             output.push_str("    val uiState: StateFlow<UiState> = _uiState.asStateFlow()\n\n");
         }
 
@@ -6420,7 +6980,7 @@ impl ComposeBackend {
                 };
                 output.push_str(&format!("    var {}: {}\n", prop.name, type_str));
                 output.push_str(&format!("        get() = _uiState.value.{}\n", prop.name));
-                output.push_str(&format!("        set(value) {{ _uiState.update {{ it.copy({} = value) }} }}\n\n", prop.name));
+                output.push_str(&self.generate_state_setter(&prop.name, prop.is_saved));
             }
         }
 
@@ -6452,6 +7012,9 @@ impl ComposeBackend {
                     if !user_defined {
                         output.push_str(&format!("    fun {}(value: {}) {{\n", method_name, type_str));
                         output.push_str(&format!("        _uiState.update {{ it.copy({} = value) }}\n", prop.name));
+                        if prop.is_saved {
+                            output.push_str(&format!("        savedStateHandle[\"{}\"] = value\n", prop.name));
+                        }
                         output.push_str("    }\n\n");
                     }
                 }
@@ -6538,14 +7101,18 @@ impl ComposeBackend {
         };
         let has_route_params = !route_params.is_empty();
 
+        // $saved vars are persisted through SavedStateHandle as well
+        let has_saved = file.state.iter().any(|s| s.mutable && s.is_saved);
+        let needs_saved_state_handle = has_route_params || has_saved;
+
         // Package declaration
         output.push_str(&format!("package {}\n\n", self.package));
 
         // Imports
         output.push_str("import androidx.lifecycle.ViewModel\n");
 
-        // Add SavedStateHandle import if route params are used
-        if has_route_params {
+        // Add SavedStateHandle import if route params or $saved vars are used
+        if needs_saved_state_handle {
             output.push_str("import androidx.lifecycle.SavedStateHandle\n");
         }
 
@@ -6576,17 +7143,18 @@ impl ComposeBackend {
             vm_imports.push("kotlinx.serialization.Serializable".to_string());
         }
 
-        // Check if $fetch() is used in lifecycle hooks or functions
-        let uses_fetch_in_vm = file.lifecycle_hooks.iter().any(|h| h.body.contains("$fetch("))
-            || file.functions.iter().any(|f| f.body.contains("$fetch("));
+        // Check if $fetch() is used in lifecycle hooks, functions or $async blocks
+        let uses_fetch_in_vm = file.lifecycle_hooks.iter().any(|h| h.body.contains("$fetch"))
+            || file.functions.iter().any(|f| f.body.contains("$fetch"))
+            || file.state.iter().any(|s| self.async_values.contains(&s.name) && s.initial_value.contains("$fetch"));
         if uses_fetch_in_vm {
-            vm_imports.push("io.ktor.client.HttpClient".to_string());
-            vm_imports.push("io.ktor.client.call.body".to_string());
-            vm_imports.push("io.ktor.client.engine.okhttp.OkHttp".to_string());
-            vm_imports.push("io.ktor.client.plugins.contentnegotiation.ContentNegotiation".to_string());
-            vm_imports.push("io.ktor.client.request.get".to_string());
-            vm_imports.push("io.ktor.serialization.kotlinx.json.json".to_string());
-            vm_imports.push("kotlinx.serialization.json.Json".to_string());
+            vm_imports.extend(self.fetch_imports(file));
+        }
+
+        // $async values load in viewModelScope
+        if !self.async_values.is_empty() {
+            vm_imports.push(self.resolve_import("$AsyncValue"));
+            vm_imports.push("androidx.lifecycle.viewModelScope".to_string());
         }
 
         // Check if $log() is used in lifecycle hooks or functions
@@ -6625,13 +7193,8 @@ impl ComposeBackend {
             output.push('\n');
         }
 
-        // Generate HttpClient singleton if $fetch() is used
-        if uses_fetch_in_vm {
-            output.push_str(&self.generate_http_client());
-        }
-
         // Class declaration with optional SavedStateHandle constructor
-        if has_route_params {
+        if needs_saved_state_handle {
             output.push_str(&format!("class {}(\n", viewmodel_name));
             output.push_str("    private val savedStateHandle: SavedStateHandle\n");
             output.push_str(") : ViewModel() {\n");
@@ -6658,8 +7221,16 @@ impl ComposeBackend {
             }
             output.push_str("    )\n\n");
 
-            // StateFlow setup
-            output.push_str("    private val _uiState = MutableStateFlow(UiState())\n");
+            // StateFlow setup (restoring $saved vars from SavedStateHandle)
+            let saved: Vec<(String, String, String)> = mutable_state.iter()
+                .filter(|s| s.is_saved)
+                .map(|s| {
+                    let type_str = s.type_annotation.clone()
+                        .unwrap_or_else(|| self.infer_type_from_value(&s.initial_value));
+                    (s.name.clone(), type_str, self.transform_array_literal(&s.initial_value, false))
+                })
+                .collect();
+            output.push_str(&self.generate_ui_state_flow(&saved));
             output.push_str("    val uiState: StateFlow<UiState> = _uiState.asStateFlow()\n\n");
 
             // Property accessors for mutable vars
@@ -6671,7 +7242,7 @@ impl ComposeBackend {
                 };
                 output.push_str(&format!("    var {}: {}\n", state.name, type_str));
                 output.push_str(&format!("        get() = _uiState.value.{}\n", state.name));
-                output.push_str(&format!("        set(value) {{ _uiState.update {{ it.copy({} = value) }} }}\n\n", state.name));
+                output.push_str(&self.generate_state_setter(&state.name, state.is_saved));
             }
         }
 
@@ -6681,6 +7252,13 @@ impl ComposeBackend {
             .filter(|s| !s.mutable && !s.is_derived_state)
             .collect();
         for state in &simple_val_state {
+            // $async values are owned by the ViewModel so they survive configuration changes
+            if let Some((_, body)) = Self::parse_async_value(&state.initial_value) {
+                output.push_str(&format!("    val {} = AsyncValue(viewModelScope) {{\n", state.name));
+                output.push_str(&self.async_body(&body, "        "));
+                output.push_str("    }.apply { start() }\n\n");
+                continue;
+            }
             let type_str = if let Some(type_ann) = &state.type_annotation {
                 format!(": {}", type_ann)
             } else {
@@ -6714,6 +7292,9 @@ impl ComposeBackend {
             if !user_defined {
                 output.push_str(&format!("    fun {}(value: {}) {{\n", method_name, type_str));
                 output.push_str(&format!("        _uiState.update {{ it.copy({} = value) }}\n", state.name));
+                if state.is_saved {
+                    output.push_str(&format!("        savedStateHandle[\"{}\"] = value\n", state.name));
+                }
                 output.push_str("    }\n\n");
             }
         }
@@ -6726,7 +7307,7 @@ impl ComposeBackend {
             }
 
             // Check if function uses $fetch - if so, wrap in viewModelScope.launch
-            let uses_fetch = func.body.contains("$fetch(");
+            let uses_fetch = func.body.contains("$fetch");
 
             output.push_str(&format!("    fun {}({})", func.name, func.params));
             if let Some(return_type) = &func.return_type {
@@ -6738,7 +7319,7 @@ impl ComposeBackend {
             if func.is_suspend || uses_fetch {
                 output.push_str("        viewModelScope.launch {\n");
                 // Indent each line of the function body properly and transform $fetch
-                for line in func.body.lines() {
+                for line in Self::join_fetch_calls(&func.body).lines() {
                     let trimmed = line.trim();
                     if !trimmed.is_empty() {
                         let mut transformed_line = trimmed.to_string();
@@ -6751,8 +7332,8 @@ impl ComposeBackend {
                                 transformed_line = format!("return@launch{}", &transformed_line[6..]);
                             }
                         }
-                        // Transform $fetch() calls to Ktor HttpClient calls
-                        if transformed_line.contains("$fetch(") {
+                        // Transform $fetch() calls to the shared Fetch client
+                        if transformed_line.contains("$fetch") {
                             transformed_line = self.transform_fetch_call(&transformed_line);
                         }
                         // Transform $log() calls to Android Log calls
@@ -6800,13 +7381,13 @@ impl ComposeBackend {
 
                         // Lifecycle hooks in ViewModel use viewModelScope.launch
                         output.push_str("        viewModelScope.launch {\n");
-                        for line in transformed_body.lines() {
+                        for line in Self::join_fetch_calls(&transformed_body).lines() {
                             if line.trim().is_empty() {
                                 continue;
                             }
-                            // Transform $fetch() calls to Ktor HttpClient calls
+                            // Transform $fetch() calls to the shared Fetch client
                             let mut transformed_line = line.trim().to_string();
-                            if transformed_line.contains("$fetch(") {
+                            if transformed_line.contains("$fetch") {
                                 transformed_line = self.transform_fetch_call(&transformed_line);
                             }
                             // Transform $log() calls to Android Log calls
@@ -6884,6 +7465,11 @@ impl ComposeBackend {
             self.uses_experimental_material3 = true;
         }
 
+        // Check if Coil is used (AsyncImage, and Image which maps to it)
+        if component_imports.iter().chain(&prop_imports).any(|imp| imp.starts_with("coil.")) {
+            self.uses_coil = true;
+        }
+
         let mut imports = Vec::new();
 
         // Add prop imports first (layout, styling, etc.)
@@ -7067,7 +7653,7 @@ impl ComposeBackend {
         // Generate any immutable state (val) that aren't derived
         // These need to be after ViewModel context setup so variable references get transformed
         for state in &file.state {
-            if !state.mutable && !state.is_derived_state {
+            if !state.mutable && !state.is_derived_state && !self.async_values.contains(&state.name) {
                 output.push_str(&self.indent());
                 let type_annotation = state.type_annotation.as_ref()
                     .map(|t| format!(": {}", t))
@@ -10,12 +10,15 @@ pub mod compose;
 pub mod view;
 
 use crate::transpiler::optimizer::OptimizedAST;
+use crate::transpiler::FeatureUsage;
 
 /// Main code generator - routes to backends based on optimizations
 pub struct CodeGenerator {
     package: String,
     component_name: String,
     component_type: Option<String>,
+    /// Set by `generate`
+    feature_usage: FeatureUsage,
 }
 
 impl CodeGenerator {
@@ -25,6 +28,7 @@ impl CodeGenerator {
             package: package.to_string(),
             component_name: component_name.to_string(),
             component_type: component_type.map(String::from),
+            feature_usage: FeatureUsage::default(),
         }
     }
 
@@ -44,6 +48,13 @@ impl CodeGenerator {
             self.component_type.as_deref(),
         );
 
-        backend.generate_with_optimizations(&optimized_ast.ast, &optimized_ast.optimizations, &optimized_ast.semantic_info)
+        let result = backend.generate_with_optimizations(&optimized_ast.ast, &optimized_ast.optimizations, &optimized_ast.semantic_info);
+        self.feature_usage = backend.feature_usage();
+        result
+    }
+
+    /// Optional libraries the last generated file needs
+    pub fn feature_usage(&self) -> FeatureUsage {
+        self.feature_usage
     }
 }
@@ -5,7 +5,7 @@
 //!
 //! Note: This is future/experimental code not yet fully integrated.
 
-use crate::transpiler::ast::{Component, ForLoopBlock, IfElseBlock, Markup, PropValue, WhitehallFile, WhenBlock};
+use crate::transpiler::ast::{AwaitBlock, Component, ForLoopBlock, IfElseBlock, Markup, PropValue, WhitehallFile, WhenBlock};
 use std::collections::HashSet;
 
 pub struct ViewBackend {
@@ -134,6 +134,7 @@ impl ViewBackend {
             Markup::ForLoop(for_loop) => self.generate_for_loop(for_loop),
             Markup::IfElse(if_else) => self.generate_if_else(if_else),
             Markup::When(when) => self.generate_when(when),
+            Markup::Await(block) => self.generate_await(block),
         }
     }
 
@@ -482,6 +483,51 @@ impl ViewBackend {
         code
     }
 
+    fn generate_await(&mut self, block: &AwaitBlock) -> String {
+        // For @await: build the view for the current UiState
+        let mut code = String::new();
+
+        code.push_str(&format!("when (val state = {}.state) {{\n", block.value));
+        self.indent_level += 1;
+
+        let branches = [
+            ("Loading", None, &block.loading),
+            ("Ok", block.ok_binding.as_ref().map(|b| (b, "value")), &block.ok),
+            ("Err", block.err_binding.as_ref().map(|b| (b, "error")), &block.err),
+        ];
+        for (variant, binding, body) in branches {
+            code.push_str(&format!("{}is UiState.{} -> run {{\n", self.indent(), variant));
+            self.indent_level += 1;
+            if let Some((name, field)) = binding {
+                code.push_str(&format!("{}val {} = state.{}\n", self.indent(), name, field));
+            }
+            match body.len() {
+                0 => code.push_str(&format!("{}TextView(context)\n", self.indent())),
+                1 => {
+                    let child_code = self.generate_markup(&body[0]);
+                    code.push_str(&format!("{}{}\n", self.indent(), child_code));
+                }
+                _ => {
+                    code.push_str(&format!("{}LinearLayout(context).apply {{\n", self.indent()));
+                    self.indent_level += 1;
+                    for child in body {
+                        let child_code = self.generate_markup(child);
+                        code.push_str(&format!("{}addView({})\n", self.indent(), child_code));
+                    }
+                    self.indent_level -= 1;
+                    code.push_str(&format!("{}}}\n", self.indent()));
+                }
+            }
+            self.indent_level -= 1;
+            code.push_str(&format!("{}}}\n", self.indent()));
+        }
+
+        self.indent_level -= 1;
+        code.push_str(&format!("{}}}", self.indent()));
+
+        code
+    }
+
     fn get_prop_value(&self, component: &Component, prop_name: &str) -> Option<String> {
         component.props.iter()
             .find(|p| p.name == prop_name)
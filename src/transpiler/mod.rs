@@ -8,6 +8,8 @@ mod codegen;
 mod optimizer;
 mod parser;
 mod recyclerview;
+pub(crate) mod room;
+mod util;
 
 use analyzer::Analyzer;
 use codegen::CodeGenerator;
@@ -17,6 +19,7 @@ use parser::Parser;
 // Re-export types needed by build_pipeline
 pub use analyzer::{StoreRegistry, StoreInfo, StoreSource};
 pub use ast::WhitehallFile as AST;
+pub use room::{generate_database, generate_migrations, Entity};
 
 /// Transpilation result that can represent one or more output files
 #[derive(Debug, Clone)]
@@ -55,6 +58,31 @@ impl TranspileResult {
     }
 }
 
+/// Framework APIs a file uses that need optional Android libraries
+///
+/// Aggregated across the project by `build_pipeline` to pick the app's dependencies.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeatureUsage {
+    /// `$fetch` (Ktor)
+    pub fetch: bool,
+    /// Screens, `$routes`, `$navigate` or `$route` (navigation-compose)
+    pub navigation: bool,
+    /// `AsyncImage` or `Image` (Coil)
+    pub images: bool,
+    /// `@Parcelize` classes, e.g. for `$saved` state (kotlin-parcelize plugin)
+    pub parcelize: bool,
+}
+
+impl FeatureUsage {
+    /// Combine the usage of two files
+    pub fn merge(&mut self, other: FeatureUsage) {
+        self.fetch |= other.fetch;
+        self.navigation |= other.navigation;
+        self.images |= other.images;
+        self.parcelize |= other.parcelize;
+    }
+}
+
 /// Transpile Whitehall source code to Kotlin/Compose
 ///
 /// # Arguments
@@ -83,9 +111,24 @@ pub fn transpile_with_registry(
     component_type: Option<&str>,
     global_store_registry: Option<&analyzer::StoreRegistry>,
 ) -> Result<TranspileResult, String> {
+    transpile_with_usage(input, package, component_name, component_type, global_store_registry)
+        .map(|(result, _)| result)
+}
+
+/// Transpile with optional global store registry, also reporting the optional
+/// libraries the generated code needs
+pub fn transpile_with_usage(
+    input: &str,
+    package: &str,
+    component_name: &str,
+    component_type: Option<&str>,
+    global_store_registry: Option<&analyzer::StoreRegistry>,
+) -> Result<(TranspileResult, FeatureUsage), String> {
     // 1. Parse input to AST
     let mut parser = Parser::new(input);
     let mut ast = parser.parse()?;
+    check_entity_placement(input, &ast)?;
+    let parcelize = uses_parcelize(&ast);
 
     // 2. Analyze: build semantic information and enrich AST
     //    Phase 0-2: Collect symbols, track usage, detect optimizations
@@ -111,7 +154,42 @@ pub fn transpile_with_registry(
     //    Phase 5: Consume optimizations and route to appropriate backend
     //    Returns TranspileResult (Single or Multiple files)
     let mut codegen = CodeGenerator::new(package, component_name, component_type);
-    codegen.generate(&optimized_ast)
+    let result = codegen.generate(&optimized_ast)?;
+    let mut usage = codegen.feature_usage();
+    usage.parcelize = parcelize;
+    Ok((result, usage))
+}
+
+/// Whether the file declares `@Parcelize` classes, in pass-through Kotlin or as a parsed class
+fn uses_parcelize(ast: &ast::WhitehallFile) -> bool {
+    ast.kotlin_blocks.iter().any(|block| block.content.contains("@Parcelize"))
+        || ast.classes.iter().any(|class| class.annotations.iter().any(|a| a == "Parcelize"))
+}
+
+/// Parse the `@entity` data classes of a file (for project-wide AppDatabase generation)
+pub fn parse_entities(input: &str) -> Result<Vec<Entity>, String> {
+    let ast = Parser::new(input).parse()?;
+    check_entity_placement(input, &ast)?;
+    room::parse_entities(&ast.kotlin_blocks)
+}
+
+/// Room entities and DAOs are only generated for data-only files, so reject
+/// `@entity` next to markup, state or functions
+fn check_entity_placement(input: &str, ast: &ast::WhitehallFile) -> Result<(), String> {
+    if ast.is_data_only() {
+        return Ok(());
+    }
+    let Some(block) = ast.kotlin_blocks.iter().find(|b| room::is_entity_block(b)) else {
+        return Ok(());
+    };
+
+    // Block positions are offsets into the trimmed source
+    let offset = input.len() - input.trim_start().len();
+    let line = input[..offset + block.position].matches('\n').count() + 1;
+    Err(format!(
+        "line {}: @entity data classes must be declared in a file without markup, state or functions (e.g. src/models/)",
+        line
+    ))
 }
 
 /// Parse source code to extract AST for store registry building
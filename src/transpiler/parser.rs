@@ -1,7 +1,7 @@
 /// Parser for Whitehall syntax
 
 use crate::transpiler::ast::{
-    ClassDeclaration, Component, ComponentProp, ConstructorDeclaration, ElseIfBranch,
+    AwaitBlock, ClassDeclaration, Component, ComponentProp, ConstructorDeclaration, ElseIfBranch,
     ForLoopBlock, FunctionDeclaration, IfElseBlock, Import, KotlinBlock, LifecycleHook, Markup,
     PropDeclaration, PropertyDeclaration, PropValue, StateDeclaration, WhenBlock, WhenBranch,
     WhitehallFile,
@@ -189,6 +189,7 @@ impl Parser {
         let mut classes = Vec::new();
         let mut kotlin_blocks: Vec<KotlinBlock> = Vec::new();
         let mut pending_annotations = Vec::new();
+        let mut pending_persist: Option<String> = None; // From @store(persist = "...")
         let mut parsed_store_class = false; // Track if we've parsed a store class
 
         // Parse imports, props, state, functions, lifecycle hooks, and classes (before markup)
@@ -199,16 +200,33 @@ impl Parser {
             if self.peek_char() == Some('@') {
                 self.advance_char(); // Skip @
                 let annotation = self.parse_identifier()?;
-                pending_annotations.push(annotation.clone());
+                let args = self.parse_annotation_args()?;
+
+                if annotation == "store" {
+                    if let Some(args) = &args {
+                        pending_persist = Some(self.parse_store_persist_arg(args)?);
+                    }
+                    pending_annotations.push(annotation.clone());
+                } else if let Some(args) = &args {
+                    // Keep arguments for pass-through annotations (e.g., @Entity(tableName = "notes"))
+                    pending_annotations.push(format!("{}({})", annotation, args));
+                } else {
+                    pending_annotations.push(annotation.clone());
+                }
 
                 // Check if next is "class" or "object" keyword
                 self.skip_whitespace();
                 let next_word = self.peek_word();
                 if next_word == Some("class") || next_word == Some("object") {
-                    classes.push(self.parse_class_declaration(pending_annotations.clone())?);
+                    let mut class = self.parse_class_declaration(pending_annotations.clone())?;
+                    class.persist = pending_persist.take();
+                    classes.push(class);
                     parsed_store_class = true; // Mark that we've seen a store class
                     pending_annotations.clear();
                     continue;
+                } else if annotation == "saved" {
+                    // @saved is for store class properties; component state uses $saved
+                    return Err(self.error_at_pos("@saved is only valid inside a class; use '$saved var' for component state"));
                 } else if annotation == "prop" {
                     // Handle @prop (legacy parsing)
                     pending_annotations.clear();
@@ -246,6 +264,18 @@ impl Parser {
             } else if self.peek_word() == Some("var") || self.peek_word() == Some("val") {
                 // Parse state declarations (only for non-extension properties)
                 state.push(self.parse_state_declaration()?);
+            } else if self.consume_word("$saved") {
+                // $saved var name = value → state that survives process death
+                self.skip_whitespace();
+                if self.peek_word() != Some("var") {
+                    return Err(self.error_at_pos("$saved can only be applied to 'var' declarations"));
+                }
+                let mut declaration = self.parse_state_declaration()?;
+                if declaration.is_derived_state {
+                    return Err(format!("$saved cannot be used with $derived() (variable '{}')", declaration.name));
+                }
+                declaration.is_saved = true;
+                state.push(declaration);
             } else if !parsed_store_class && self.peek_word() == Some("suspend") {
                 // Parse suspend functions as component functions (before store class only)
                 // After store class, is_kotlin_syntax() will catch these and pass through
@@ -305,7 +335,10 @@ impl Parser {
                 // Collect annotations for next kotlin block
                 self.advance_char(); // Skip @
                 let annotation = self.parse_identifier()?;
-                pending_annotations.push(annotation);
+                match self.parse_annotation_args()? {
+                    Some(args) => pending_annotations.push(format!("{}({})", annotation, args)),
+                    None => pending_annotations.push(annotation),
+                }
                 continue;
             }
 
@@ -684,6 +717,7 @@ impl Parser {
             type_annotation,
             initial_value,
             is_derived_state,
+            is_saved: false,
         })
     }
 
@@ -840,16 +874,26 @@ impl Parser {
         // Parse class body: properties and functions
         let mut properties = Vec::new();
         let mut functions = Vec::new();
+        let mut pending_saved = false; // @saved applies to the next property
 
         loop {
             self.skip_whitespace();
 
             // Check for end of class
             if self.peek_char() == Some('}') {
+                if pending_saved {
+                    return Err(self.error_at_pos("@saved must be followed by a 'var' property"));
+                }
                 self.advance_char();
                 break;
             }
 
+            // @saved var name = value → property backed by SavedStateHandle
+            if self.consume_word("@saved") {
+                pending_saved = true;
+                continue;
+            }
+
             // Check for property (with optional visibility modifier)
             if self.peek_word() == Some("var") || self.peek_word() == Some("val")
                 || self.peek_word() == Some("private") || self.peek_word() == Some("protected") || self.peek_word() == Some("public") {
@@ -867,12 +911,23 @@ impl Parser {
 
                 // Now we must have var/val after optional visibility
                 if self.peek_word() == Some("var") || self.peek_word() == Some("val") {
-                    properties.push(self.parse_property_declaration_with_visibility(visibility)?);
+                    let mut property = self.parse_property_declaration_with_visibility(visibility)?;
+                    if pending_saved {
+                        if !property.mutable || property.getter.is_some() {
+                            return Err(format!("@saved can only be applied to 'var' properties (property '{}')", property.name));
+                        }
+                        property.is_saved = true;
+                        pending_saved = false;
+                    }
+                    properties.push(property);
                 } else {
                     return Err(self.error_at_pos("Expected 'var' or 'val' after visibility modifier"));
                 }
             }
             // Check for function
+            else if pending_saved {
+                return Err(self.error_at_pos("@saved must be followed by a 'var' property"));
+            }
             else if self.peek_word() == Some("fun") || self.peek_word() == Some("suspend") {
                 let is_suspend = self.consume_word("suspend"); // Optional suspend
                 if is_suspend {
@@ -896,9 +951,45 @@ impl Parser {
             constructor,
             properties,
             functions,
+            persist: None, // Set by the caller from @store(persist = ...)
         })
     }
 
+    /// Parse optional annotation arguments: `(persist = "settings")` → `persist = "settings"`
+    /// Returns None if the annotation has no argument list
+    fn parse_annotation_args(&mut self) -> Result<Option<String>, String> {
+        if self.peek_char() != Some('(') {
+            return Ok(None);
+        }
+        self.expect_char('(')?;
+        let start = self.pos;
+        let mut depth = 1;
+        let mut in_string = false;
+        while depth > 0 {
+            match self.peek_char() {
+                Some('"') => in_string = !in_string,
+                Some('(') if !in_string => depth += 1,
+                Some(')') if !in_string => depth -= 1,
+                None => return Err(self.error_at_pos("Unclosed '(' in annotation arguments")),
+                _ => {}
+            }
+            self.advance_char();
+        }
+        Ok(Some(self.input[start..self.pos - 1].trim().to_string()))
+    }
+
+    /// Parse the arguments of @store(...): only `persist = "name"` is supported
+    fn parse_store_persist_arg(&self, args: &str) -> Result<String, String> {
+        let re = regex::Regex::new(r#"^persist\s*=\s*"([A-Za-z0-9_\-]+)"$"#).unwrap();
+        match re.captures(args.trim()) {
+            Some(caps) => Ok(caps[1].to_string()),
+            None => Err(self.error_at_pos(&format!(
+                "Invalid @store arguments '{}': expected @store(persist = \"name\")",
+                args
+            ))),
+        }
+    }
+
     fn parse_constructor(&mut self) -> Result<ConstructorDeclaration, String> {
         self.skip_whitespace();
 
@@ -1006,6 +1097,7 @@ impl Parser {
             initial_value,
             getter,
             visibility,
+            is_saved: false,
         })
     }
 
@@ -1507,6 +1599,7 @@ impl Parser {
                 if remaining.starts_with("@if ")
                     || remaining.starts_with("@for ")
                     || remaining.starts_with("@when ")
+                    || remaining.starts_with("@await ")
                 {
                     children.push(self.parse_control_flow()?);
                 } else {
@@ -1555,6 +1648,8 @@ impl Parser {
             self.parse_for_loop()
         } else if self.consume_word("when") {
             self.parse_when()
+        } else if self.consume_word("await") {
+            self.parse_await()
         } else {
             Err("Unknown control flow construct".to_string())
         }
@@ -1783,6 +1878,87 @@ impl Parser {
         Ok(Markup::When(WhenBlock { branches }))
     }
 
+    fn parse_await(&mut self) -> Result<Markup, String> {
+        // Parse: @await value { loading => markup; ok(v) => markup; err(e) => markup }
+        self.skip_whitespace();
+        let value = self.parse_until_char('{')?.trim().to_string();
+        if value.is_empty() {
+            return Err(self.error_at_pos("Expected an $async value after @await"));
+        }
+        self.expect_char('{')?;
+
+        let mut block = AwaitBlock {
+            value,
+            loading: Vec::new(),
+            ok_binding: None,
+            ok: Vec::new(),
+            err_binding: None,
+            err: Vec::new(),
+        };
+
+        loop {
+            self.skip_whitespace();
+            while matches!(self.peek_char(), Some(';') | Some(',')) {
+                self.advance_char();
+                self.skip_whitespace();
+            }
+
+            if self.peek_char() == Some('}') {
+                self.expect_char('}')?;
+                break;
+            }
+
+            let branch = self.parse_identifier()?;
+            self.skip_whitespace();
+            let binding = if self.peek_char() == Some('(') {
+                self.expect_char('(')?;
+                let name = self.parse_until_char(')')?.trim().to_string();
+                self.expect_char(')')?;
+                self.skip_whitespace();
+                Some(name)
+            } else {
+                None
+            };
+
+            self.expect_char('=')?;
+            self.expect_char('>')?;
+            self.skip_whitespace();
+
+            // Body: a single component or a { ... } markup block
+            let body = if self.peek_char() == Some('{') {
+                self.expect_char('{')?;
+                self.parse_markup_block()?
+            } else if self.peek_char() == Some('<') {
+                vec![self.parse_component()?]
+            } else {
+                return Err(self.error_at_pos(&format!("Expected component or {{ ... }} after '{} =>'", branch)));
+            };
+
+            match (branch.as_str(), binding) {
+                ("loading", None) => block.loading = body,
+                ("ok", binding) => {
+                    block.ok_binding = binding;
+                    block.ok = body;
+                }
+                ("err", binding) => {
+                    block.err_binding = binding;
+                    block.err = body;
+                }
+                ("loading", Some(_)) => {
+                    return Err(self.error_at_pos("'loading' branch of @await doesn't take a binding"));
+                }
+                (other, _) => {
+                    return Err(self.error_at_pos(&format!(
+                        "Unknown @await branch '{}' (expected loading, ok or err)",
+                        other
+                    )));
+                }
+            }
+        }
+
+        Ok(Markup::Await(block))
+    }
+
     fn parse_markup_block(&mut self) -> Result<Vec<Markup>, String> {
         // Parse markup until closing brace
         let mut items = Vec::new();
@@ -1858,6 +2034,7 @@ impl Parser {
                 if remaining.starts_with("@if ")
                     || remaining.starts_with("@for ")
                     || remaining.starts_with("@when ")
+                    || remaining.starts_with("@await ")
                 {
                     break;
                 }
@@ -2636,4 +2813,60 @@ mod tests {
         let result = parser.parse();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_parse_saved_state() {
+        let mut parser = Parser::new("$saved var query = \"\"\nvar count = 0\n<Text>{query}</Text>");
+        let file = parser.parse().unwrap();
+        assert_eq!(file.state.len(), 2);
+        assert!(file.state[0].is_saved);
+        assert_eq!(file.state[0].name, "query");
+        assert!(!file.state[1].is_saved);
+
+        let mut parser = Parser::new("$saved val query = \"\"\n<Text>{query}</Text>");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_parse_saved_class_property() {
+        let mut parser = Parser::new("class SearchStore {\n  @saved var query = \"\"\n  var results: List<String> = []\n}");
+        let file = parser.parse().unwrap();
+        let properties = &file.classes[0].properties;
+        assert!(properties[0].is_saved);
+        assert!(!properties[1].is_saved);
+
+        let mut parser = Parser::new("class SearchStore {\n  @saved val query = \"\"\n}");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_parse_store_persist() {
+        let mut parser = Parser::new("@store(persist = \"settings\")\nobject AppSettings {\n  var darkMode = false\n}");
+        let file = parser.parse().unwrap();
+        assert_eq!(file.classes[0].persist.as_deref(), Some("settings"));
+        assert!(file.classes[0].annotations.contains(&"store".to_string()));
+
+        let mut parser = Parser::new("@store\nobject AppSettings {\n  var darkMode = false\n}");
+        assert_eq!(parser.parse().unwrap().classes[0].persist, None);
+
+        let mut parser = Parser::new("@store(cache = true)\nobject AppSettings {\n  var darkMode = false\n}");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_parse_await_block() {
+        let input = "val photos = $async { load() }\n<Column>\n  @await photos {\n    loading => <Spinner />;\n    ok(list) => { <Text>{list.size}</Text> <Text>done</Text> }\n    err(e) => <Text>{e.message}</Text>\n  }\n</Column>";
+        let mut parser = Parser::new(input);
+        let file = parser.parse().unwrap();
+        let Markup::Component(column) = &file.markup else { panic!("expected Column") };
+        let Markup::Await(block) = &column.children[0] else { panic!("expected @await") };
+        assert_eq!(block.value, "photos");
+        assert_eq!(block.loading.len(), 1);
+        assert_eq!(block.ok_binding.as_deref(), Some("list"));
+        assert_eq!(block.ok.len(), 2);
+        assert_eq!(block.err_binding.as_deref(), Some("e"));
+
+        let mut parser = Parser::new("<Column>\n  @await photos {\n    done => <Text>x</Text>\n  }\n</Column>");
+        assert!(parser.parse().unwrap_err().contains("Unknown @await branch 'done'"));
+    }
 }
@@ -0,0 +1,522 @@
+//! Room database generation for `@entity` data classes
+//!
+//! An entity is a pass-through data class (`KotlinBlockType::DataClass`) marked with `@entity`:
+//!
+//! ```text
+//! @entity
+//! data class Note(
+//!   @primary val id: Long = 0,
+//!   val title: String,
+//!   val done: Boolean = false
+//! ) {
+//!   @query("SELECT * FROM notes WHERE done = 0")
+//!   fun pending(): Flow<List<Note>>
+//! }
+//! ```
+//!
+//! Generates the Room `@Entity`, a `NoteDao` with CRUD methods plus the `@query` shortcuts,
+//! and (project-wide, from build_pipeline) the `AppDatabase` class.
+//!
+//! Fields Room can't store as a column (collections, data classes, enums from other files)
+//! are stored as JSON through a generated `<Name>Converters` class.
+
+use regex::Regex;
+
+use crate::transpiler::ast::{KotlinBlock, KotlinBlockType};
+use crate::transpiler::util::{find_matching_paren, split_parameters};
+
+/// A Room entity parsed from an `@entity data class`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub name: String,
+    pub table: String,
+    pub fields: Vec<EntityField>,
+    /// Custom DAO queries from `@query("...")` declarations in the class body
+    pub queries: Vec<EntityQuery>,
+    /// Field types (without `?`) stored as JSON through `<Name>Converters`
+    pub converted_types: Vec<String>,
+    /// Annotations other than @entity (e.g., @Serializable), kept on the generated class
+    annotations: Vec<String>,
+    /// Text after the parameter list (supertypes and remaining class body)
+    rest: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityField {
+    /// Declaration without the @primary marker (e.g., `val id: Long = 0`)
+    pub declaration: String,
+    pub name: String,
+    pub type_name: String,
+    pub is_primary: bool,
+    pub auto_generate: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityQuery {
+    pub sql: String,
+    /// Function signature (e.g., `fun pending(): Flow<List<Note>>`)
+    pub signature: String,
+}
+
+impl Entity {
+    /// DAO interface name (e.g., "NoteDao")
+    pub fn dao_name(&self) -> String {
+        format!("{}Dao", self.name)
+    }
+
+    /// DAO accessor on AppDatabase (e.g., "noteDao")
+    pub fn dao_accessor(&self) -> String {
+        let mut chars = self.name.chars();
+        match chars.next() {
+            Some(first) => format!("{}{}Dao", first.to_ascii_lowercase(), chars.as_str()),
+            None => "dao".to_string(),
+        }
+    }
+
+    /// Type converter class for the JSON-stored fields (e.g., "NoteConverters")
+    pub fn converters_name(&self) -> String {
+        format!("{}Converters", self.name)
+    }
+
+    fn primary_key(&self) -> &EntityField {
+        self.fields.iter().find(|f| f.is_primary).expect("entity without primary key")
+    }
+}
+
+/// Check whether a Kotlin block is an `@entity` data class
+pub fn is_entity_block(block: &KotlinBlock) -> bool {
+    block.block_type == KotlinBlockType::DataClass && entity_annotation(&block.content).is_some()
+}
+
+/// Parse every `@entity` block in a file
+pub fn parse_entities(blocks: &[KotlinBlock]) -> Result<Vec<Entity>, String> {
+    // Room stores enums declared alongside the entity by name
+    let enum_re = Regex::new(r"enum\s+class\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    let enums: Vec<String> = blocks.iter()
+        .filter(|b| b.block_type == KotlinBlockType::EnumClass)
+        .filter_map(|b| enum_re.captures(&b.content).map(|c| c[1].to_string()))
+        .collect();
+
+    blocks.iter()
+        .filter(|b| is_entity_block(b))
+        .map(|b| parse_entity(&b.content, &enums))
+        .collect()
+}
+
+/// Types Room stores as a column without a converter
+const COLUMN_TYPES: &[&str] = &["String", "Int", "Long", "Short", "Byte", "Boolean", "Float", "Double", "ByteArray"];
+
+/// Field types that need a JSON converter, deduplicated
+fn converted_types(fields: &[EntityField], enums: &[String]) -> Vec<String> {
+    let mut types: Vec<String> = fields.iter()
+        .map(|f| f.type_name.trim_end_matches('?').trim().to_string())
+        .filter(|t| !COLUMN_TYPES.contains(&t.as_str()) && !enums.contains(t))
+        .collect();
+    types.sort();
+    types.dedup();
+    types
+}
+
+/// Converter function suffix for a type (e.g., `Map<String, Int>` → "MapStringInt")
+fn converter_suffix(type_name: &str) -> String {
+    type_name.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '_').collect()
+}
+
+/// Find the `@entity` / `@entity(...)` annotation line in the block header
+fn entity_annotation(content: &str) -> Option<String> {
+    let header = &content[..content.find("data class").unwrap_or(0)];
+    header.lines()
+        .map(|l| l.trim())
+        .find(|l| *l == "@entity" || l.starts_with("@entity("))
+        .map(String::from)
+}
+
+/// Parse an `@entity data class` block, given the enum classes declared in the same file
+pub fn parse_entity(content: &str, enums: &[String]) -> Result<Entity, String> {
+    let class_start = content.find("data class")
+        .ok_or_else(|| "@entity must be applied to a data class".to_string())?;
+    let header = &content[..class_start];
+
+    let name_re = Regex::new(r"^data class\s+([A-Za-z_][A-Za-z0-9_]*)\s*\(").unwrap();
+    let caps = name_re.captures(&content[class_start..])
+        .ok_or_else(|| "@entity data class must declare its properties in a primary constructor".to_string())?;
+    let name = caps[1].to_string();
+
+    // Table name: @entity(table = "notes") or derived from the class name
+    let entity_line = entity_annotation(content).unwrap_or_default();
+    let table_re = Regex::new(r#"^@entity\(\s*(?:table|tableName)\s*=\s*"([A-Za-z0-9_]+)"\s*\)$"#).unwrap();
+    let table = if entity_line == "@entity" {
+        default_table_name(&name)
+    } else if let Some(caps) = table_re.captures(&entity_line) {
+        caps[1].to_string()
+    } else {
+        return Err(format!(
+            "Invalid {} on '{}': expected @entity or @entity(table = \"name\")",
+            entity_line, name
+        ));
+    };
+
+    let annotations: Vec<String> = header.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && *l != entity_line)
+        .map(String::from)
+        .collect();
+
+    // Primary constructor parameters
+    let params_start = class_start + caps.get(0).unwrap().end();
+    let params_end = find_matching_paren(content, params_start - 1)
+        .ok_or_else(|| format!("Unclosed parameter list in @entity data class '{}'", name))?;
+    let mut fields = split_parameters(&content[params_start..params_end])
+        .into_iter()
+        .map(|p| parse_field(&p, &name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if fields.iter().filter(|f| f.is_primary).count() > 1 {
+        return Err(format!("@entity '{}' has more than one @primary field", name));
+    }
+    if !fields.iter().any(|f| f.is_primary) {
+        // Fall back to a field named `id`
+        match fields.iter_mut().find(|f| f.name == "id") {
+            Some(id) => {
+                id.is_primary = true;
+                id.auto_generate = is_auto_generated(&id.type_name, &id.declaration);
+            }
+            None => {
+                return Err(format!(
+                    "@entity '{}' needs a primary key: mark a field with @primary or name it 'id'",
+                    name
+                ));
+            }
+        }
+    }
+
+    // Pull @query declarations out of the class body
+    let query_re = Regex::new(r#"@query\(\s*"((?:[^"\\]|\\.)*)"\s*\)\s*((?:suspend\s+)?fun\s+[^\n]+)"#).unwrap();
+    let rest_raw = &content[params_end + 1..];
+    let queries = query_re.captures_iter(rest_raw)
+        .map(|c| EntityQuery { sql: c[1].to_string(), signature: c[2].trim().to_string() })
+        .collect();
+    let rest = strip_empty_body(&query_re.replace_all(rest_raw, ""));
+    let converted_types = converted_types(&fields, enums);
+
+    Ok(Entity { name, table, fields, queries, converted_types, annotations, rest })
+}
+
+fn parse_field(param: &str, entity: &str) -> Result<EntityField, String> {
+    let is_primary = param.contains("@primary");
+    let declaration = param.replace("@primary", "").trim().to_string();
+
+    let field_re = Regex::new(r"(?:val|var)\s+([A-Za-z_][A-Za-z0-9_]*)\s*:\s*([^=]+?)\s*(?:=|$)").unwrap();
+    let caps = field_re.captures(&declaration).ok_or_else(|| {
+        format!("@entity '{}': '{}' must be a `val name: Type` property", entity, declaration)
+    })?;
+    let type_name = caps[2].trim().to_string();
+    let auto_generate = is_primary && is_auto_generated(&type_name, &declaration);
+
+    Ok(EntityField { name: caps[1].to_string(), type_name, is_primary, auto_generate, declaration })
+}
+
+/// Integer keys with a default value (e.g., `val id: Long = 0`) are generated by SQLite
+fn is_auto_generated(type_name: &str, declaration: &str) -> bool {
+    matches!(type_name, "Long" | "Int") && declaration.contains('=')
+}
+
+/// Default table name: snake_case plural of the class name (TodoItem → todo_items)
+fn default_table_name(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_ascii_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(ch.to_ascii_lowercase());
+    }
+
+    if snake.ends_with('s') || snake.ends_with('x') || snake.ends_with("ch") || snake.ends_with("sh") {
+        format!("{}es", snake)
+    } else if snake.ends_with('y') && !snake.ends_with("ay") && !snake.ends_with("ey") && !snake.ends_with("oy") {
+        format!("{}ies", &snake[..snake.len() - 1])
+    } else {
+        format!("{}s", snake)
+    }
+}
+
+/// Drop a class body that only contained @query declarations
+fn strip_empty_body(rest: &str) -> String {
+    let trimmed = rest.trim();
+    if let Some(open) = trimmed.find('{') {
+        let body = trimmed[open + 1..].trim_end_matches('}');
+        if body.trim().is_empty() {
+            return trimmed[..open].trim_end().to_string();
+        }
+    }
+    trimmed.to_string()
+}
+
+/// Generate the Room entity class
+pub fn generate_entity(entity: &Entity) -> String {
+    let mut output = String::new();
+    for annotation in &entity.annotations {
+        output.push_str(annotation);
+        output.push('\n');
+    }
+    if !entity.converted_types.is_empty() {
+        output.push_str(&format!("@TypeConverters({}::class)\n", entity.converters_name()));
+    }
+    output.push_str(&format!("@Entity(tableName = \"{}\")\n", entity.table));
+    output.push_str(&format!("data class {}(\n", entity.name));
+    for (i, field) in entity.fields.iter().enumerate() {
+        let primary = if field.auto_generate {
+            "@PrimaryKey(autoGenerate = true) "
+        } else if field.is_primary {
+            "@PrimaryKey "
+        } else {
+            ""
+        };
+        let comma = if i < entity.fields.len() - 1 { "," } else { "" };
+        output.push_str(&format!("    {}{}{}\n", primary, field.declaration, comma));
+    }
+    output.push(')');
+    if !entity.rest.is_empty() {
+        output.push(' ');
+        output.push_str(&entity.rest);
+    }
+    output.push('\n');
+    if !entity.converted_types.is_empty() {
+        output.push('\n');
+        output.push_str(&generate_converters(entity));
+    }
+    output
+}
+
+/// Generate the JSON type converters for an entity's non-column fields
+fn generate_converters(entity: &Entity) -> String {
+    let mut output = format!("// Stores {} fields Room has no column type for as JSON\n", entity.name);
+    output.push_str(&format!("class {} {{\n", entity.converters_name()));
+    for (i, type_name) in entity.converted_types.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        let suffix = converter_suffix(type_name);
+        output.push_str("    @TypeConverter\n");
+        output.push_str(&format!(
+            "    fun from{}(value: {}): String = Json.encodeToString(value)\n\n",
+            suffix, type_name
+        ));
+        output.push_str("    @TypeConverter\n");
+        output.push_str(&format!(
+            "    fun to{}(value: String): {} = Json.decodeFromString(value)\n",
+            suffix, type_name
+        ));
+    }
+    output.push_str("}\n");
+    output
+}
+
+/// Imports the generated entity file needs for `generate_converters`
+pub fn converter_imports(entities: &[Entity]) -> Vec<String> {
+    if entities.iter().all(|e| e.converted_types.is_empty()) {
+        return Vec::new();
+    }
+    [
+        "androidx.room.TypeConverter",
+        "androidx.room.TypeConverters",
+        "kotlinx.serialization.decodeFromString",
+        "kotlinx.serialization.encodeToString",
+        "kotlinx.serialization.json.Json",
+    ]
+    .iter()
+    .map(|import| import.to_string())
+    .collect()
+}
+
+/// Generate the DAO interface for an entity: CRUD methods plus custom @query functions
+pub fn generate_dao(entity: &Entity) -> String {
+    let key = entity.primary_key();
+    let param = {
+        let mut chars = entity.name.chars();
+        let first = chars.next().map(|c| c.to_ascii_lowercase()).unwrap_or('e');
+        format!("{}{}", first, chars.as_str())
+    };
+
+    let mut output = String::new();
+    output.push_str("@Dao\n");
+    output.push_str(&format!("interface {} {{\n", entity.dao_name()));
+    output.push_str(&format!("    @Query(\"SELECT * FROM {}\")\n", entity.table));
+    output.push_str(&format!("    fun getAll(): Flow<List<{}>>\n\n", entity.name));
+    output.push_str(&format!("    @Query(\"SELECT * FROM {} WHERE {} = :{}\")\n", entity.table, key.name, key.name));
+    output.push_str(&format!("    suspend fun getById({}: {}): {}?\n\n", key.name, key.type_name, entity.name));
+    output.push_str("    @Insert(onConflict = OnConflictStrategy.REPLACE)\n");
+    output.push_str(&format!("    suspend fun insert({}: {}): Long\n\n", param, entity.name));
+    output.push_str("    @Update\n");
+    output.push_str(&format!("    suspend fun update({}: {})\n\n", param, entity.name));
+    output.push_str("    @Delete\n");
+    output.push_str(&format!("    suspend fun delete({}: {})\n", param, entity.name));
+    for query in &entity.queries {
+        output.push_str(&format!("\n    @Query(\"{}\")\n", query.sql));
+        output.push_str(&format!("    {}\n", query.signature));
+    }
+    output.push_str("}\n");
+    output
+}
+
+/// Generate the project-wide AppDatabase from (package, entity) pairs
+pub fn generate_database(package: &str, entities: &[(String, Entity)]) -> String {
+    let mut imports = vec![
+        "android.content.Context".to_string(),
+        "androidx.room.Database".to_string(),
+        "androidx.room.Room".to_string(),
+        "androidx.room.RoomDatabase".to_string(),
+    ];
+    for (entity_package, entity) in entities {
+        if entity_package != package {
+            imports.push(format!("{}.{}", entity_package, entity.name));
+            imports.push(format!("{}.{}", entity_package, entity.dao_name()));
+        }
+    }
+    imports.sort();
+    imports.dedup();
+
+    let mut output = format!("package {}\n\n", package);
+    for import in imports {
+        output.push_str(&format!("import {}\n", import));
+    }
+    output.push('\n');
+
+    let entity_list = entities.iter()
+        .map(|(_, e)| format!("{}::class", e.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    output.push_str(&format!("@Database(entities = [{}], version = 1, exportSchema = false)\n", entity_list));
+    output.push_str("abstract class AppDatabase : RoomDatabase() {\n");
+    for (_, entity) in entities {
+        output.push_str(&format!("    abstract fun {}(): {}\n", entity.dao_accessor(), entity.dao_name()));
+    }
+    output.push_str(r#"
+    companion object {
+        @Volatile
+        private var instance: AppDatabase? = null
+
+        fun get(context: Context): AppDatabase =
+            instance ?: synchronized(this) {
+                instance ?: Room.databaseBuilder(context.applicationContext, AppDatabase::class.java, "app.db")
+                    .addMigrations(*MIGRATIONS)
+                    .build()
+                    .also { instance = it }
+            }
+    }
+}
+"#);
+    output
+}
+
+/// Generate the migrations stub registered by AppDatabase
+pub fn generate_migrations(package: &str) -> String {
+    format!(
+        r#"package {}
+
+import androidx.room.migration.Migration
+
+// Migrations applied by AppDatabase when the schema version changes
+val MIGRATIONS: Array<Migration> = arrayOf()
+"#,
+        package
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entity_with_queries() {
+        let entity = parse_entity(
+            "@entity\ndata class TodoItem(\n  @primary val id: Long = 0,\n  val title: String,\n  val tags: Map<String, Int> = mapOf()\n) {\n  @query(\"SELECT * FROM todo_items WHERE title = :title\")\n  suspend fun byTitle(title: String): List<TodoItem>\n}",
+            &[],
+        ).unwrap();
+
+        assert_eq!(entity.name, "TodoItem");
+        assert_eq!(entity.table, "todo_items");
+        assert_eq!(entity.fields.len(), 3);
+        assert!(entity.fields[0].auto_generate);
+        assert_eq!(entity.fields[2].type_name, "Map<String, Int>");
+        assert_eq!(entity.queries.len(), 1);
+        assert_eq!(entity.queries[0].signature, "suspend fun byTitle(title: String): List<TodoItem>");
+        assert_eq!(entity.rest, "");
+
+        // The map is stored as JSON
+        assert_eq!(entity.converted_types, vec!["Map<String, Int>".to_string()]);
+        let generated = generate_entity(&entity);
+        assert!(generated.starts_with("@TypeConverters(TodoItemConverters::class)\n@Entity(tableName = \"todo_items\")\n"));
+        assert!(generated.contains("class TodoItemConverters {\n"));
+        assert!(generated.contains("    fun fromMapStringInt(value: Map<String, Int>): String = Json.encodeToString(value)\n"));
+        assert!(generated.contains("    fun toMapStringInt(value: String): Map<String, Int> = Json.decodeFromString(value)\n"));
+        assert_eq!(converter_imports(std::slice::from_ref(&entity)).len(), 5);
+
+        let dao = generate_dao(&entity);
+        assert!(dao.contains("interface TodoItemDao {"));
+        assert!(dao.contains("suspend fun getById(id: Long): TodoItem?"));
+        assert!(dao.contains("@Query(\"SELECT * FROM todo_items WHERE title = :title\")"));
+    }
+
+    #[test]
+    fn test_parse_entity_primary_key_rules() {
+        let entity = parse_entity("@entity(table = \"people\")\ndata class Person(val id: String, val name: String)", &[]).unwrap();
+        assert_eq!(entity.table, "people");
+        assert!(entity.fields[0].is_primary);
+        assert!(!entity.fields[0].auto_generate);
+        assert!(generate_entity(&entity).contains("@PrimaryKey val id: String,"));
+
+        let err = parse_entity("@entity\ndata class Tag(val label: String)", &[]).unwrap_err();
+        assert!(err.contains("primary key"));
+
+        assert!(parse_entity("@entity(cache = true)\ndata class Tag(val id: Long)", &[]).is_err());
+    }
+
+    #[test]
+    fn test_column_types_need_no_converters() {
+        let blocks = vec![
+            KotlinBlock {
+                content: "enum class Priority { LOW, HIGH }".to_string(),
+                block_type: KotlinBlockType::EnumClass,
+                position: 0,
+            },
+            KotlinBlock {
+                content: "@entity\ndata class Task(\n  @primary val id: Long = 0,\n  val title: String,\n  val note: String? = null,\n  val priority: Priority,\n  val done: Boolean,\n  val due: Due?\n)".to_string(),
+                block_type: KotlinBlockType::DataClass,
+                position: 1,
+            },
+        ];
+
+        let entities = parse_entities(&blocks).unwrap();
+        // Only the data class from elsewhere needs a converter; nullability is Room's concern
+        assert_eq!(entities[0].converted_types, vec!["Due".to_string()]);
+        assert!(generate_entity(&entities[0]).contains("    fun toDue(value: String): Due = Json.decodeFromString(value)\n"));
+
+        let plain = parse_entity("@entity\ndata class Tag(val id: Long, val label: String)", &[]).unwrap();
+        assert!(plain.converted_types.is_empty());
+        assert!(!generate_entity(&plain).contains("TypeConverters"));
+        assert!(converter_imports(&[plain]).is_empty());
+    }
+
+    #[test]
+    fn test_entity_outside_model_file() {
+        let screen = "var count = 0\n\n@entity\ndata class Note(\n  @primary val id: Long = 0,\n  val title: String\n)\n\n<Text>{count}</Text>\n";
+
+        // Neither AppDatabase collection nor codegen accept it: no DAO would be generated
+        let err = crate::transpiler::parse_entities(screen).unwrap_err();
+        assert!(err.starts_with("line 4: @entity data classes must be declared in a file without markup"), "{}", err);
+        let err = crate::transpiler::transpile(screen, "com.example.app.screens", "HomeScreen", Some("screen")).unwrap_err();
+        assert!(err.starts_with("line 4:"), "{}", err);
+
+        let model = "@entity\ndata class Note(\n  @primary val id: Long = 0,\n  val title: String\n)\n";
+        assert_eq!(crate::transpiler::parse_entities(model).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_default_table_name() {
+        assert_eq!(default_table_name("Note"), "notes");
+        assert_eq!(default_table_name("Category"), "categories");
+        assert_eq!(default_table_name("Address"), "addresses");
+        assert_eq!(default_table_name("Day"), "days");
+    }
+}
@@ -0,0 +1,102 @@
+//! Text helpers shared by the transpiler passes that rewrite Kotlin source
+
+/// Find the `)` matching the `(` at `open`
+pub(crate) fn find_matching_paren(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut prev = '\0';
+    for (i, ch) in s[open..].char_indices() {
+        match ch {
+            '"' if prev != '\\' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+        prev = ch;
+    }
+    None
+}
+
+/// Split an argument list on commas that aren't nested in `()`, `[]`, `{}` or strings
+///
+/// `<` and `>` are left alone, as in expressions they are comparisons and `->`.
+pub(crate) fn split_top_level(s: &str) -> Vec<String> {
+    split_on_commas(s, false)
+}
+
+/// Split a parameter list on commas, also keeping generic arguments (`Map<K, V>`) together
+pub(crate) fn split_parameters(s: &str) -> Vec<String> {
+    split_on_commas(s, true)
+}
+
+fn split_on_commas(s: &str, generics: bool) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut prev = '\0';
+
+    for ch in s.chars() {
+        match ch {
+            '"' if prev != '\\' => in_string = !in_string,
+            '(' | '[' | '{' if !in_string => depth += 1,
+            ')' | ']' | '}' if !in_string => depth -= 1,
+            '<' if generics && !in_string => depth += 1,
+            '>' if generics && !in_string && prev != '-' => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                prev = ch;
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+        prev = ch;
+    }
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matching_paren() {
+        let s = r#"f(a, g(")"), b) + 1"#;
+        assert_eq!(find_matching_paren(s, 1), Some(14));
+        assert_eq!(find_matching_paren("f(a, (b)", 1), None);
+    }
+
+    #[test]
+    fn test_split_top_level() {
+        assert_eq!(
+            split_top_level(r#"url, { headers: { "A": a, "B": b } }, "x, y""#),
+            vec!["url", r#"{ headers: { "A": a, "B": b } }"#, r#""x, y""#]
+        );
+        assert!(split_top_level("  ").is_empty());
+
+        // Comparisons and lambdas don't nest
+        assert_eq!(
+            split_top_level("timeout: if (a < b) 1000 else 5000, body: items.map { x -> x > 0 }, method: m"),
+            vec!["timeout: if (a < b) 1000 else 5000", "body: items.map { x -> x > 0 }", "method: m"]
+        );
+        assert_eq!(split_top_level("a < b, c > d"), vec!["a < b", "c > d"]);
+    }
+
+    #[test]
+    fn test_split_parameters() {
+        assert_eq!(
+            split_parameters("val tags: Map<String, Int> = emptyMap(), val f: (Int) -> Unit = {}"),
+            vec!["val tags: Map<String, Int> = emptyMap()", "val f: (Int) -> Unit = {}"]
+        );
+    }
+}
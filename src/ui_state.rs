@@ -0,0 +1,111 @@
+//! Async data runtime (UiState.kt) behind `$async` values and `@await` blocks
+//!
+//! ```whitehall
+//! val photos = $async { $fetch<List<Photo>>("/photos") }
+//!
+//! @await photos {
+//!   loading => <CircularProgressIndicator />
+//!   ok(list) => <PhotoGrid photos={list} onRefresh={photos.refresh} />
+//!   err(e) => <Button onClick={photos.retry}>Retry</Button>
+//! }
+//! ```
+
+/// Generate UiState.kt for the app's root package
+pub fn generate_ui_state_kt(package: &str) -> String {
+    format!(
+        r#"package {package}
+
+import androidx.compose.runtime.Composable
+import androidx.compose.runtime.LaunchedEffect
+import androidx.compose.runtime.Stable
+import androidx.compose.runtime.getValue
+import androidx.compose.runtime.mutableStateOf
+import androidx.compose.runtime.remember
+import androidx.compose.runtime.rememberCoroutineScope
+import androidx.compose.runtime.rememberUpdatedState
+import androidx.compose.runtime.setValue
+import kotlin.coroutines.cancellation.CancellationException
+import kotlinx.coroutines.CoroutineScope
+import kotlinx.coroutines.Job
+import kotlinx.coroutines.launch
+
+/** State of an `$async` value, matched by `@await` */
+sealed interface UiState<out T> {{
+    data object Loading : UiState<Nothing>
+    data class Ok<T>(val value: T) : UiState<T>
+    data class Err(val error: Throwable) : UiState<Nothing>
+}}
+
+/** An `$async` value: loads once started, then again on retry() or refresh() */
+@Stable
+class AsyncValue<T>(
+    private val scope: CoroutineScope,
+    private val load: suspend () -> T,
+) {{
+    var state: UiState<T> by mutableStateOf(UiState.Loading)
+        private set
+
+    /** True while refresh() reloads behind the current value */
+    var isRefreshing by mutableStateOf(false)
+        private set
+
+    private var job: Job? = null
+
+    /** Load for the first time */
+    fun start() {{
+        if (job == null) launchLoad()
+    }}
+
+    /** Reload from scratch, showing the loading state */
+    fun retry() {{
+        state = UiState.Loading
+        launchLoad()
+    }}
+
+    /** Reload while keeping the current value on screen (pull-to-refresh) */
+    fun refresh() {{
+        isRefreshing = true
+        launchLoad()
+    }}
+
+    private fun launchLoad() {{
+        job?.cancel()
+        job = scope.launch {{
+            state = try {{
+                UiState.Ok(load())
+            }} catch (e: CancellationException) {{
+                throw e
+            }} catch (e: Exception) {{
+                UiState.Err(e)
+            }}
+            isRefreshing = false
+        }}
+    }}
+}}
+
+/** Remember an `$async` value in a component; it reloads when any of [keys] change */
+@Composable
+fun <T> rememberAsync(vararg keys: Any?, load: suspend () -> T): AsyncValue<T> {{
+    val scope = rememberCoroutineScope()
+    val currentLoad by rememberUpdatedState(load)
+    val value = remember(*keys) {{ AsyncValue(scope) {{ currentLoad() }} }}
+    LaunchedEffect(value) {{ value.start() }}
+    return value
+}}
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_ui_state_kt() {
+        let output = generate_ui_state_kt("com.example.app");
+        assert!(output.starts_with("package com.example.app\n"));
+        assert!(output.contains("sealed interface UiState<out T> {"));
+        assert!(output.contains("class AsyncValue<T>("));
+        assert!(output.contains("fun <T> rememberAsync(vararg keys: Any?, load: suspend () -> T): AsyncValue<T> {"));
+    }
+}
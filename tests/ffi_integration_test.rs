@@ -2,11 +2,15 @@ use std::fs;
 use std::path::Path;
 use tempfile::TempDir;
 
-use whitehall::ffi_parser::cpp::discover_cpp_ffi;
-use whitehall::ffi_parser::rust::discover_rust_ffi;
+use whitehall::ffi_parser::c::{c_source_files, discover_c_ffi};
+use whitehall::ffi_parser::cpp::{check_cpp_ffi_types, discover_cpp_ffi, discover_cpp_ffi_types};
+use whitehall::ffi_parser::rust::{
+    check_rust_ffi_classes, check_rust_ffi_types, discover_rust_ffi, discover_rust_ffi_classes, discover_rust_ffi_types,
+};
 use whitehall::ffi_codegen::{
-    generate_kotlin_object, generate_jni_bridge, generate_cmake,
-    generate_kotlin_object_rust, generate_rust_bridge
+    generate_kotlin_object, generate_kotlin_types, generate_jni_bridge, generate_cmake,
+    generate_c_jni_bridge, generate_c_cmake,
+    generate_kotlin_object_rust, generate_kotlin_types_rust, generate_kotlin_class_rust, generate_rust_bridge
 };
 
 #[test]
@@ -70,8 +74,9 @@ int helper(int x) {
     // Test 3: Generate JNI bridge
     let jni_code = generate_jni_bridge(
         &functions,
+        &[],
         "com.example.test",
-        &["ffi/cpp/math.cpp".to_string()],
+        "Math",
     );
 
     assert!(jni_code.contains("#include <jni.h>"));
@@ -225,8 +230,9 @@ std::string formatNumber(std::string format, int number) {
     // Test 3: Generate JNI bridge with string conversions
     let jni_code = generate_jni_bridge(
         &functions,
+        &[],
         "com.example.test",
-        &["ffi/cpp/strings.cpp".to_string()],
+        "Math",
     );
 
     // Check forward declarations
@@ -328,8 +334,9 @@ std::vector<std::string> toUpperCase(std::vector<std::string> strings) {
     // Test 4: Generate JNI bridge with array conversions
     let jni_code = generate_jni_bridge(
         &functions,
+        &[],
         "com.example.test",
-        &["ffi/cpp/arrays.cpp".to_string()],
+        "Math",
     );
 
     println!("{}", jni_code);
@@ -439,6 +446,8 @@ fn helper(x: i32) -> i32 {
     // Test 3: Generate Rust JNI bridge
     let rust_bridge_code = generate_rust_bridge(
         &functions,
+        &[],
+        &[],
         "com.example.test",
     );
 
@@ -570,6 +579,8 @@ pub fn format_number(format: String, number: i32) -> String {
     // Test 3: Generate Rust JNI bridge with string conversions
     let rust_bridge_code = generate_rust_bridge(
         &functions,
+        &[],
+        &[],
         "com.example.test",
     );
 
@@ -643,6 +654,8 @@ pub fn to_upper_case(strings: Vec<String>) -> Vec<String> {
     // Test 4: Generate Rust JNI bridge with array conversions
     let rust_bridge_code = generate_rust_bridge(
         &functions,
+        &[],
+        &[],
         "com.example.test",
     );
 
@@ -650,7 +663,7 @@ pub fn to_upper_case(strings: Vec<String>) -> Vec<String> {
 
     // Check JNI array functions
     assert!(rust_bridge_code.contains("jintArray"));
-    assert!(rust_bridge_code.contains("get_int_array_elements"));
+    assert!(rust_bridge_code.contains("get_int_array_region"));
     assert!(rust_bridge_code.contains("new_int_array"));
     assert!(rust_bridge_code.contains("set_int_array_region"));
 
@@ -730,6 +743,8 @@ pub fn get_first(values: Vec<i32>) -> Result<i32, String> {
     // Test 4: Generate Rust JNI bridge with Result handling
     let rust_bridge_code = generate_rust_bridge(
         &functions,
+        &[],
+        &[],
         "com.example.test",
     );
 
@@ -746,5 +761,1025 @@ pub fn get_first(values: Vec<i32>) -> Result<i32, String> {
 
     // Check for error message conversion
     assert!(rust_bridge_code.contains("format!(\"{}\", e)"));
-    assert!(rust_bridge_code.contains("new_string"));
+    assert!(rust_bridge_code.contains("throw_new(\"java/lang/RuntimeException\", &err_msg)"));
+}
+
+#[test]
+fn test_rust_ffi_structs_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(&rust_dir).unwrap();
+
+    let rust_content = r#"
+#[ffi]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[ffi]
+pub struct Polygon {
+    pub label: String,
+    pub points: Vec<Point>,
+}
+
+#[ffi]
+pub enum Shape {
+    Circle { center: Point, radius: f64 },
+    Poly(Polygon),
+    Empty,
+}
+
+#[ffi]
+pub fn centroid(polygon: Polygon) -> Point {
+    Point { x: 0.0, y: 0.0 }
+}
+
+#[ffi]
+pub fn bounding_shapes(shapes: Vec<Shape>) -> Result<Vec<Shape>, String> {
+    Ok(shapes)
+}
+"#;
+    fs::write(rust_dir.join("lib.rs"), rust_content).unwrap();
+
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    assert_eq!(functions.len(), 2);
+    assert_eq!(types.len(), 3);
+    check_rust_ffi_types(&functions, &types).unwrap();
+
+    // Kotlin: the object uses the generated classes, which live next to it
+    let kotlin_code = generate_kotlin_object_rust(&functions, "com.example.test", "geo", "Geo");
+    assert!(kotlin_code.contains("external fun centroid(polygon: Polygon): Point"));
+    assert!(kotlin_code.contains("external fun boundingShapes(shapes: List<Shape>): List<Shape>"));
+
+    let kotlin_types = generate_kotlin_types_rust(&types, "com.example.test");
+    assert!(kotlin_types.contains("data class Polygon(\n    val label: String,\n    val points: List<Point>,\n)"));
+    assert!(kotlin_types.contains("data class Poly(val value: Polygon) : Shape()"));
+
+    // Rust bridge: field-by-field construction and reads, nested and in lists
+    let bridge = generate_rust_bridge(&functions, &types, &[], "com.example.test");
+    println!("{}", bridge);
+
+    assert!(bridge.contains("use jni::objects::{JClass, JString, JObject, JValue};"));
+    assert!(bridge.contains("fn whitehall_list_to_java<'local, T>("));
+    assert!(bridge.contains("fn whitehall_point_to_java<'local>(env: &mut JNIEnv<'local>, value: &crate::Point) -> JObject<'local> {"));
+    assert!(bridge.contains("env.new_object(\"com/example/test/Point\", \"(DD)V\", &[JValue::Double(value.x), JValue::Double(value.y)])"));
+    assert!(bridge.contains("let f_points = whitehall_list_to_java(env, &value.points, whitehall_point_to_java);"));
+    assert!(bridge.contains("env.new_object(\"com/example/test/Polygon\", \"(Ljava/lang/String;Ljava/util/List;)V\""));
+    assert!(bridge.contains("crate::Shape::Circle { center, radius } => {"));
+    assert!(bridge.contains("let f_center = whitehall_point_to_java(env, center);"));
+    assert!(bridge.contains("JValue::Double(*radius)"));
+    assert!(bridge.contains("env.get_static_field(\"com/example/test/Shape$Empty\", \"INSTANCE\", \"Lcom/example/test/Shape$Empty;\")"));
+    assert!(bridge.contains("x: env.get_field(obj, \"x\", \"D\").and_then(|v| v.d())"));
+    assert!(bridge.contains("if env.is_instance_of(obj, \"com/example/test/Shape$Poly\")"));
+    assert!(bridge.contains("let rust_polygon: crate::Polygon = whitehall_polygon_from_java(&mut env, &unsafe { JObject::from_raw(polygon) });"));
+    assert!(bridge.contains("-> jobject {"));
+    assert!(bridge.contains("whitehall_point_to_java(&mut env, &result).into_raw()"));
+    assert!(bridge.contains("whitehall_list_to_java(&mut env, &result, whitehall_shape_to_java).into_raw()"));
+}
+
+#[test]
+fn test_rust_ffi_option_map_bytes_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(&rust_dir).unwrap();
+
+    let rust_content = r#"
+use std::collections::HashMap;
+
+#[ffi]
+pub struct Profile {
+    pub nickname: Option<String>,
+    pub age: Option<i32>,
+    pub scores: HashMap<String, f64>,
+    pub avatar: Vec<u8>,
+}
+
+#[ffi]
+pub fn find_user(id: Option<i64>) -> Option<String> {
+    id.map(|id| id.to_string())
+}
+
+#[ffi]
+pub fn merge(settings: HashMap<String, Option<String>>) -> HashMap<String, i32> {
+    HashMap::new()
+}
+
+#[ffi]
+pub fn checksum(data: &[u8]) -> Vec<u8> {
+    data.to_vec()
+}
+
+#[ffi]
+pub fn fill(buffer: &mut [u8], value: i32) {
+    buffer.fill(value as u8);
+}
+
+#[ffi]
+pub fn echo_profile(profile: Profile) -> Profile {
+    profile
+}
+"#;
+    fs::write(rust_dir.join("lib.rs"), rust_content).unwrap();
+
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    assert_eq!(functions.len(), 5);
+    check_rust_ffi_types(&functions, &types).unwrap();
+
+    // Kotlin: nullable types, maps, byte arrays and direct ByteBuffers
+    let kotlin_code = generate_kotlin_object_rust(&functions, "com.example.test", "io", "Io");
+    assert!(kotlin_code.contains("external fun findUser(id: Long?): String?"));
+    assert!(kotlin_code.contains("external fun merge(settings: Map<String, String?>): Map<String, Int>"));
+    assert!(kotlin_code.contains("external fun checksum(data: ByteArray): ByteArray"));
+    assert!(kotlin_code.contains("external fun fill(buffer: java.nio.ByteBuffer, value: Int)"));
+
+    let kotlin_types = generate_kotlin_types_rust(&types, "com.example.test");
+    assert!(kotlin_types.contains("val nickname: String?,"));
+    assert!(kotlin_types.contains("val scores: Map<String, Double>,"));
+    assert!(kotlin_types.contains("val avatar: ByteArray,"));
+
+    // Rust bridge: boxing, null checks, map helpers and byte conversions
+    let bridge = generate_rust_bridge(&functions, &types, &[], "com.example.test");
+    println!("{}", bridge);
+
+    assert!(bridge.contains("fn whitehall_map_to_java<'local, T>("));
+    assert!(bridge.contains("fn whitehall_map_from_java<T>("));
+    assert!(bridge.contains("let obj_id = unsafe { JObject::from_raw(id) };"));
+    assert!(bridge.contains("let rust_id = if obj_id.is_null() { None } else { Some(env.call_method(&obj_id, \"longValue\", \"()J\", &[])"));
+    assert!(bridge.contains("let java_result = match &result { Some(v) => JObject::from(env.new_string(v)"));
+    assert!(bridge.contains("java_result.into_raw()"));
+    assert!(bridge.contains("whitehall_map_from_java(&mut env, &obj_settings, |env, obj| if obj.is_null() { None } else { Some("));
+    assert!(bridge.contains("let java_result = whitehall_map_to_java(&mut env, &result, |env, v| env.new_object(\"java/lang/Integer\", \"(I)V\", &[JValue::Int(*v)])"));
+    assert!(bridge.contains("env.convert_byte_array(unsafe { jni::objects::JByteArray::from_raw(data) })"));
+    assert!(bridge.contains("crate::checksum(&rust_data)"));
+    assert!(bridge.contains("env.byte_array_from_slice(&result)"));
+    assert!(bridge.contains("env.get_direct_buffer_address(&buffer_buffer)"));
+    assert!(bridge.contains("std::slice::from_raw_parts_mut(ptr_buffer, len_buffer)"));
+    assert!(bridge.contains("\"(Ljava/lang/String;Ljava/lang/Integer;Ljava/util/Map;[B)V\""));
+    assert!(bridge.contains("env.get_field(obj, \"age\", \"Ljava/lang/Integer;\")"));
+}
+
+#[test]
+fn test_rust_ffi_async_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(&rust_dir).unwrap();
+
+    let rust_content = r#"
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[ffi(async)]
+pub fn hash_file(path: String, cancelled: &AtomicBool) -> Result<Vec<u8>, String> {
+    if cancelled.load(Ordering::Relaxed) {
+        return Err("cancelled".to_string());
+    }
+    Ok(path.into_bytes())
+}
+
+#[ffi(async)]
+pub fn warm_up() {}
+
+#[ffi]
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+"#;
+    fs::write(rust_dir.join("lib.rs"), rust_content).unwrap();
+
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    assert_eq!(functions.len(), 3);
+
+    // Kotlin: a private external plus a suspend wrapper that hides the cancellation flag
+    let kotlin_code = generate_kotlin_object_rust(&functions, "com.example.test", "hash", "Hash");
+    println!("{}", kotlin_code);
+
+    assert!(kotlin_code.contains("import kotlinx.coroutines.suspendCancellableCoroutine"));
+    assert!(kotlin_code.contains("private external fun nativeHashFile(path: String, cancelled: java.nio.ByteBuffer): ByteArray"));
+    assert!(kotlin_code.contains("suspend fun hashFile(path: String): ByteArray = suspendCancellableCoroutine { continuation ->"));
+    assert!(kotlin_code.contains("val cancelled = java.nio.ByteBuffer.allocateDirect(1)"));
+    assert!(kotlin_code.contains("continuation.invokeOnCancellation { cancelled.put(0, 1) }"));
+    assert!(kotlin_code.contains("Dispatchers.IO.asExecutor().execute {"));
+    assert!(kotlin_code.contains("continuation.resumeWith(runCatching { nativeHashFile(path, cancelled) })"));
+    assert!(kotlin_code.contains("private external fun nativeWarmUp(): Unit"));
+    assert!(kotlin_code.contains("suspend fun warmUp(): Unit = suspendCancellableCoroutine { continuation ->"));
+    assert!(kotlin_code.contains("    external fun add(a: Int, b: Int): Int"));
+
+    // Rust bridge: exported under the native name, Result errors still become exceptions
+    let bridge = generate_rust_bridge(&functions, &[], &[], "com.example.test");
+    assert!(bridge.contains("fn Java_com_example_test_nativeHashFile("));
+    assert!(bridge.contains("fn Java_com_example_test_nativeWarmUp("));
+    assert!(bridge.contains("fn Java_com_example_test_add("));
+    assert!(bridge.contains("throw_new"));
+}
+
+#[test]
+fn test_rust_ffi_callbacks_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(&rust_dir).unwrap();
+
+    let rust_content = r#"
+#[ffi]
+pub struct Chunk {
+    pub index: i32,
+    pub data: Vec<u8>,
+}
+
+#[ffi_callback]
+pub trait DownloadListener: Send + Sync {
+    fn on_chunk(&self, chunk: Chunk);
+    fn should_continue(&self, received: i64) -> bool;
+}
+
+#[ffi_callback]
+pub trait Logger {
+    fn log(&self, message: String);
+}
+
+#[ffi]
+pub fn download(url: String, on_progress: impl Fn(i32) + Send + 'static) -> i64 {
+    on_progress(100);
+    0
+}
+
+#[ffi]
+pub fn transform(values: Vec<i32>, mapper: &dyn Fn(i32, String) -> String) -> i32 {
+    0
+}
+
+#[ffi]
+pub fn stream(url: String, listener: Box<dyn DownloadListener>, logger: &dyn Logger) {
+    logger.log(url);
+}
+"#;
+    fs::write(rust_dir.join("lib.rs"), rust_content).unwrap();
+
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    assert_eq!(functions.len(), 3);
+    assert_eq!(types.len(), 3);
+    check_rust_ffi_types(&functions, &types).unwrap();
+
+    // Kotlin: fun interfaces for closures (nested in the object) and for callback traits
+    let kotlin_code = generate_kotlin_object_rust(&functions, "com.example.test", "net", "Net");
+    println!("{}", kotlin_code);
+    assert!(kotlin_code.contains("    fun interface DownloadOnProgressCallback {\n        fun invoke(value: Int)\n    }"));
+    assert!(kotlin_code.contains("        fun invoke(value0: Int, value1: String): String"));
+    assert!(kotlin_code.contains("external fun download(url: String, on_progress: DownloadOnProgressCallback): Long"));
+    assert!(kotlin_code.contains("external fun stream(url: String, listener: DownloadListener, logger: Logger)"));
+
+    let kotlin_types = generate_kotlin_types_rust(&types, "com.example.test");
+    println!("{}", kotlin_types);
+    assert!(kotlin_types.contains("interface DownloadListener {\n    fun onChunk(chunk: Chunk)\n    fun shouldContinue(received: Long): Boolean\n}"));
+    assert!(kotlin_types.contains("fun interface Logger {\n    fun log(message: String)\n}"));
+
+    // Rust bridge: global refs, thread attach and exception handling
+    let bridge = generate_rust_bridge(&functions, &types, &[], "com.example.test");
+    println!("{}", bridge);
+    assert!(bridge.contains("fn whitehall_clear_exception(env: &mut JNIEnv) {"));
+    assert!(bridge.contains("let vm_on_progress = env.get_java_vm().expect(\"Couldn't get JavaVM!\");"));
+    assert!(bridge.contains("let callback_on_progress = env.new_global_ref(unsafe { JObject::from_raw(on_progress) })"));
+    assert!(bridge.contains("let rust_on_progress = move |value: i32| {"));
+    assert!(bridge.contains("let mut env = vm_on_progress.attach_current_thread().expect(\"Couldn't attach thread!\");"));
+    assert!(bridge.contains("env.call_method(callback_on_progress.as_obj(), \"invoke\", \"(I)V\", &[JValue::Int(value)]);"));
+    assert!(bridge.contains("let rust_mapper = move |value0: i32, value1: String| -> String {"));
+    assert!(bridge.contains("\"invoke\", \"(ILjava/lang/String;)Ljava/lang/String;\""));
+    assert!(bridge.contains("crate::transform(rust_values, &rust_mapper)"));
+    assert!(bridge.contains("struct WhitehallDownloadListener {"));
+    assert!(bridge.contains("impl crate::DownloadListener for WhitehallDownloadListener {"));
+    assert!(bridge.contains("fn on_chunk(&self, chunk: crate::Chunk) {"));
+    assert!(bridge.contains("let j_chunk = whitehall_chunk_to_java(&mut env, &chunk);"));
+    assert!(bridge.contains("\"onChunk\", \"(Lcom/example/test/Chunk;)V\""));
+    assert!(bridge.contains("fn should_continue(&self, received: i64) -> bool {"));
+    assert!(bridge.contains("match result.and_then(|v| v.z()) {"));
+    assert!(bridge.contains("crate::stream(rust_url, Box::new(rust_listener), &rust_logger)"));
+    assert!(!bridge.contains("whitehall_logger_to_java"));
+}
+
+#[test]
+fn test_rust_ffi_callback_validation() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(&rust_dir).unwrap();
+
+    // A trait without #[ffi_callback]
+    fs::write(rust_dir.join("lib.rs"), "#[ffi]\npub fn run(listener: impl Listener) {}\n").unwrap();
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    let err = check_rust_ffi_types(&functions, &types).unwrap_err();
+    assert!(err.to_string().contains("Mark the trait with #[ffi_callback]"));
+
+    // Callback traits can't be passed by value
+    fs::write(
+        rust_dir.join("lib.rs"),
+        "#[ffi_callback]\npub trait Listener { fn on_event(&self); }\n#[ffi]\npub fn run(listener: Listener) {}\n",
+    ).unwrap();
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    let err = check_rust_ffi_types(&functions, &types).unwrap_err();
+    assert!(err.to_string().contains("must be taken as impl Listener"));
+}
+
+#[test]
+fn test_rust_ffi_classes_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(&rust_dir).unwrap();
+
+    let rust_content = r#"
+use whitehall::ffi;
+
+#[ffi]
+pub struct Row {
+    pub id: i64,
+    pub name: String,
+}
+
+pub struct Database {
+    path: String,
+    rows: Vec<Row>,
+}
+
+#[ffi]
+impl Database {
+    pub fn open(path: String) -> Result<Self, String> {
+        Ok(Database { path, rows: Vec::new() })
+    }
+
+    pub fn in_memory() -> Database {
+        Database { path: String::new(), rows: Vec::new() }
+    }
+
+    pub fn version() -> String {
+        "1.0".to_string()
+    }
+
+    pub fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    pub fn insert(&mut self, name: String) -> i64 {
+        0
+    }
+
+    pub fn find(&self, id: i64) -> Option<Row> {
+        None
+    }
+
+    pub fn for_each(&self, on_row: &dyn Fn(Row)) {}
+
+    pub fn clear(&mut self) {}
+}
+"#;
+    fs::write(rust_dir.join("lib.rs"), rust_content).unwrap();
+
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let classes = discover_rust_ffi_classes(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    assert!(functions.is_empty());
+    assert_eq!(classes.len(), 1);
+    assert_eq!(classes[0].methods.len(), 8);
+    check_rust_ffi_classes(&classes, &functions, &types).unwrap();
+    check_rust_ffi_types(&functions, &types).unwrap();
+
+    // Kotlin: an AutoCloseable class; static methods live in the companion
+    let kotlin_code = generate_kotlin_class_rust(&classes[0], "com.example.test", "store", 33);
+    println!("{}", kotlin_code);
+    assert!(kotlin_code.contains("class Database private constructor(handle: Long) : AutoCloseable {"));
+    assert!(kotlin_code.contains("import java.lang.ref.Cleaner\n"));
+    assert!(kotlin_code.contains("private val cleanable = cleaner.register(this, Destroy(this.handle))"));
+    assert!(kotlin_code.contains("        lock.write { cleanable.clean() }\n"));
+    assert!(kotlin_code.contains("check(current != 0L) { \"Database is closed\" }"));
+    assert!(kotlin_code.contains("        fun open(path: String): Database = Database(nativeOpen(path))"));
+    assert!(kotlin_code.contains("        fun version(): String = nativeVersion()"));
+    assert!(kotlin_code.contains("    fun insert(name: String): Long = withHandle { nativeInsert(it, name) }"));
+    assert!(kotlin_code.contains("    fun find(id: Long): Row? = withHandle { nativeFind(it, id) }"));
+    assert!(kotlin_code.contains("    fun interface ForEachOnRowCallback {"));
+    assert!(kotlin_code.contains("@JvmStatic private external fun nativeOpen(path: String): Long"));
+    assert!(kotlin_code.contains("@JvmStatic private external fun nativeClear(handle: Long)\n"));
+    assert!(kotlin_code.contains("@JvmStatic private external fun destroyHandle(handle: Long)"));
+    assert!(kotlin_code.contains("System.loadLibrary(\"store\")"));
+
+    // Bridge: the handle is a Box<Mutex<T>> and T must be Send
+    let bridge = generate_rust_bridge(&functions, &types, &classes, "com.example.test");
+    println!("{}", bridge);
+    assert!(bridge.contains("assert_send::<crate::Database>();"));
+    assert!(bridge.contains("fn Java_com_example_test_Database_nativeOpen("));
+    assert!(bridge.contains("match crate::Database::open(rust_path) {"));
+    assert!(bridge.contains("Box::into_raw(Box::new(std::sync::Mutex::new(result))) as jlong"));
+    assert!(bridge.contains("let this = unsafe { &*(handle as *const std::sync::Mutex<crate::Database>) };"));
+    assert!(bridge.contains("    let this = this.lock().unwrap_or_else(|e| e.into_inner());\n    let result = this.path();"));
+    assert!(bridge.contains("    let mut this = this.lock().unwrap_or_else(|e| e.into_inner());\n    this.clear();"));
+    assert!(bridge.contains("this.for_each(&rust_on_row);"));
+    assert!(bridge.contains("fn Java_com_example_test_Database_destroyHandle("));
+    assert!(bridge.contains("drop(unsafe { Box::from_raw(handle as *mut std::sync::Mutex<crate::Database>) });"));
+    assert!(!bridge.contains("-> void"));
+}
+
+#[test]
+fn test_rust_ffi_class_cleanup_below_api_33() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(&rust_dir).unwrap();
+    fs::write(
+        rust_dir.join("lib.rs"),
+        "pub struct Db;\n#[ffi]\nimpl Db {\n    pub fn new() -> Self { Db }\n    pub fn each(&self, f: impl Fn(i32)) {}\n}\n",
+    ).unwrap();
+    let classes = discover_rust_ffi_classes(&ffi_dir).unwrap();
+
+    // Cleaner is API 33+, so older min_sdk values get a PhantomReference queue
+    let kotlin_code = generate_kotlin_class_rust(&classes[0], "com.example.test", "db", 24);
+    println!("{}", kotlin_code);
+    assert!(!kotlin_code.contains("Cleaner"));
+    assert!(kotlin_code.contains("import java.lang.ref.PhantomReference\n"));
+    assert!(kotlin_code.contains("    private val cleanable = Destroy(this, this.handle)\n"));
+    assert!(kotlin_code.contains(
+        "    private class Destroy(referent: Db, private val handle: AtomicLong) : PhantomReference<Db>(referent, queue) {"
+    ));
+    assert!(kotlin_code.contains("            if (!pending.remove(this)) return\n"));
+    assert!(kotlin_code.contains("        private val queue = ReferenceQueue<Db>()\n"));
+    assert!(kotlin_code.contains("            thread(isDaemon = true, name = \"Db-cleaner\") {\n"));
+    assert!(kotlin_code.contains("                    (queue.remove() as Destroy).clean()\n"));
+
+    let kotlin_code = generate_kotlin_class_rust(&classes[0], "com.example.test", "db", 33);
+    assert!(kotlin_code.contains("        private val cleaner = Cleaner.create()\n"));
+    assert!(!kotlin_code.contains("PhantomReference"));
+
+    // close() from inside a method (e.g. the `each` callback) must not wait on the write lock
+    for min_sdk in [24, 33] {
+        let kotlin_code = generate_kotlin_class_rust(&classes[0], "com.example.test", "db", min_sdk);
+        assert!(kotlin_code.contains(
+            "    override fun close() {\n        check(lock.readHoldCount == 0) { \"Db.close() called from inside one of its methods\" }\n        lock.write { cleanable.clean() }\n    }\n"
+        ));
+    }
+}
+
+#[test]
+fn test_rust_ffi_class_validation() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(&rust_dir).unwrap();
+
+    // Opaque types can't be returned from free functions
+    fs::write(
+        rust_dir.join("lib.rs"),
+        "pub struct Db;\n#[ffi]\nimpl Db { pub fn new() -> Self { Db } }\n#[ffi]\npub fn connect() -> Db { Db }\n",
+    ).unwrap();
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let classes = discover_rust_ffi_classes(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    let err = check_rust_ffi_classes(&classes, &functions, &types).unwrap_err();
+    assert!(err.to_string().contains("Opaque type 'Db' in function 'connect'"));
+
+    // A struct can't be both a value type and a handle
+    fs::write(
+        rust_dir.join("lib.rs"),
+        "#[ffi]\npub struct Db { pub id: i32 }\n#[ffi]\nimpl Db { pub fn new() -> Self { Db { id: 0 } } }\n",
+    ).unwrap();
+    let classes = discover_rust_ffi_classes(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    let err = check_rust_ffi_classes(&classes, &[], &types).unwrap_err();
+    assert!(err.to_string().contains("cannot be both an #[ffi] type and an #[ffi] impl"));
+}
+
+#[test]
+fn test_ffi_docs_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    let cpp_dir = ffi_dir.join("cpp");
+    fs::create_dir_all(&rust_dir).unwrap();
+    fs::create_dir_all(&cpp_dir).unwrap();
+
+    let rust_content = r#"
+/// Fetch a page of results.
+///
+/// @param limit Maximum number of rows
+#[ffi(default(limit = 20, query = ""))]
+pub fn search(query: String, limit: i32) -> Vec<String> {
+    Vec::new()
+}
+
+#[ffi(async, default(retries = 3))]
+#[deprecated(note = "use search instead")]
+pub fn fetch(url: String, retries: i32) -> String {
+    String::new()
+}
+
+pub struct Cache;
+
+/// An in-memory cache.
+#[ffi]
+impl Cache {
+    pub fn new() -> Self { Cache }
+
+    /// Look up a key.
+    #[ffi(default(fallback = None))]
+    pub fn get(&self, key: String, fallback: Option<String>) -> Option<String> { fallback }
+}
+"#;
+    fs::write(rust_dir.join("lib.rs"), rust_content).unwrap();
+
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let kotlin_code = generate_kotlin_object_rust(&functions, "com.example.test", "docs", "Docs");
+    println!("{}", kotlin_code);
+    assert!(kotlin_code.contains(
+        "    /**\n     * Fetch a page of results.\n     *\n     * @param limit Maximum number of rows\n     */\n    external fun search(query: String = \"\", limit: Int = 20): Array<String>\n"
+    ));
+    // Async: docs and defaults go on the public wrapper, not the private external
+    assert!(kotlin_code.contains(
+        "    @Deprecated(\"use search instead\")\n    suspend fun fetch(url: String, retries: Int = 3): String ="
+    ));
+    assert!(kotlin_code.contains("    private external fun nativeFetch(url: String, retries: Int): String"));
+
+    let bridge = generate_rust_bridge(&functions, &[], &[], "com.example.test");
+    assert!(bridge.contains("#[allow(deprecated)]\n#[no_mangle]\npub extern \"system\" fn Java_com_example_test_nativeFetch("));
+
+    let classes = discover_rust_ffi_classes(&ffi_dir).unwrap();
+    let class_code = generate_kotlin_class_rust(&classes[0], "com.example.test", "docs", 24);
+    println!("{}", class_code);
+    assert!(class_code.contains("/**\n * An in-memory cache.\n */\nclass Cache private constructor"));
+    assert!(class_code.contains(
+        "    /**\n     * Look up a key.\n     */\n    fun get(key: String, fallback: String? = null): String? ="
+    ));
+
+    let cpp_content = r#"
+/// Clamp a value into a range.
+/// \param value The value to clamp
+/// \return The clamped value
+// @ffi
+[[deprecated("use std::clamp")]]
+int clampValue(int value, int low = 0, int high = 100) {
+    return value;
+}
+"#;
+    fs::write(cpp_dir.join("math.cpp"), cpp_content).unwrap();
+
+    let functions = discover_cpp_ffi(&ffi_dir).unwrap();
+    let kotlin_code = generate_kotlin_object(&functions, "com.example.test", "math", "Math");
+    println!("{}", kotlin_code);
+    assert!(kotlin_code.contains(
+        "    /**\n     * Clamp a value into a range.\n     * @param value The value to clamp\n     * @return The clamped value\n     */\n    @Deprecated(\"use std::clamp\")\n    external fun clampValue(value: Int, low: Int = 0, high: Int = 100): Int\n"
+    ));
+}
+
+#[test]
+fn test_cpp_ffi_structs_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let cpp_dir = ffi_dir.join("cpp");
+    fs::create_dir_all(&cpp_dir).unwrap();
+
+    let cpp_content = r#"
+#include <string>
+#include <vector>
+
+// @ffi
+struct Point {
+    double x;
+    double y;
+};
+
+// @ffi
+enum class Kind { Open, Closed };
+
+// @ffi
+struct Path {
+    std::string label;
+    Kind kind;
+    std::vector<Point> points;
+};
+
+// @ffi
+Path reverse(const Path& path) {
+    return path;
+}
+"#;
+    fs::write(cpp_dir.join("geo.cpp"), cpp_content).unwrap();
+
+    let functions = discover_cpp_ffi(&ffi_dir).unwrap();
+    let types = discover_cpp_ffi_types(&ffi_dir).unwrap();
+    assert_eq!(functions.len(), 1);
+    assert_eq!(types.len(), 3);
+    check_cpp_ffi_types(&functions, &types).unwrap();
+
+    let kotlin_code = generate_kotlin_object(&functions, "com.example.test", "geo", "Geo");
+    assert!(kotlin_code.contains("external fun reverse(path: Path): Path"));
+
+    let kotlin_types = generate_kotlin_types(&types, "com.example.test");
+    assert!(kotlin_types.contains("enum class Kind {\n    Open,\n    Closed,\n}"));
+    assert!(kotlin_types.contains("    val points: List<Point>,\n"));
+
+    let bridge = generate_jni_bridge(&functions, &types, "com.example.test", "Math");
+    println!("{}", bridge);
+
+    assert!(bridge.contains("struct Point {\n    double x;\n    double y;\n};"));
+    assert!(bridge.contains("enum class Kind { Open, Closed };"));
+    assert!(bridge.contains("Path reverse(const Path&);"));
+    assert!(bridge.contains("static jobject whitehall_Path_to_java(JNIEnv* env, const Path& value);"));
+    assert!(bridge.contains("jobject f_points = whitehall_list_to_java(env, value.points, whitehall_Point_to_java);"));
+    assert!(bridge.contains("jmethodID init = env->GetMethodID(cls, \"<init>\", \"(Ljava/lang/String;Lcom/example/test/Kind;Ljava/util/List;)V\");"));
+    assert!(bridge.contains("value.x = env->GetDoubleField(obj, env->GetFieldID(cls, \"x\", \"D\"));"));
+    assert!(bridge.contains("return static_cast<Kind>(ordinal);"));
+    assert!(bridge.contains("Path cpp_path = whitehall_Path_from_java(env, path);"));
+    assert!(bridge.contains("return whitehall_Path_to_java(env, result);"));
+}
+
+#[test]
+fn test_cpp_ffi_callbacks_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let cpp_dir = ffi_dir.join("cpp");
+    fs::create_dir_all(&cpp_dir).unwrap();
+
+    let cpp_content = r#"
+#include <functional>
+#include <string>
+
+// @ffi
+struct Progress {
+    int done;
+    int total;
+};
+
+// @ffi
+void download(const std::string& url, const std::function<void(Progress)>& on_progress) {
+    on_progress(Progress{1, 1});
+}
+
+// @ffi
+int count_matches(const std::string& text, const std::function<bool(const std::string&, int)>& predicate) {
+    return 0;
+}
+
+// @ffi
+std::string describe(int value, const std::function<std::string(int value)>& formatter) {
+    return formatter(value);
+}
+"#;
+    fs::write(cpp_dir.join("net.cpp"), cpp_content).unwrap();
+
+    let functions = discover_cpp_ffi(&ffi_dir).unwrap();
+    let types = discover_cpp_ffi_types(&ffi_dir).unwrap();
+    assert_eq!(functions.len(), 3);
+    check_cpp_ffi_types(&functions, &types).unwrap();
+
+    // Kotlin: one fun interface per std::function parameter, nested in the object
+    let kotlin_code = generate_kotlin_object(&functions, "com.example.test", "net", "Net");
+    println!("{}", kotlin_code);
+    assert!(kotlin_code.contains("    fun interface DownloadOnProgressCallback {\n        fun invoke(value: Progress)\n    }"));
+    assert!(kotlin_code.contains("fun interface CountMatchesPredicateCallback {\n        fun invoke(value0: String, value1: Int): Boolean"));
+    assert!(kotlin_code.contains("external fun download(url: String, on_progress: DownloadOnProgressCallback)"));
+    assert!(kotlin_code.contains("external fun describe(value: Int, formatter: DescribeFormatterCallback): String"));
+
+    // C++ bridge: global ref owned by the std::function, thread attach per call
+    let bridge = generate_jni_bridge(&functions, &types, "com.example.test", "Math");
+    println!("{}", bridge);
+    assert!(bridge.contains("#include <functional>"));
+    assert!(bridge.contains("struct WhitehallAttach {"));
+    assert!(bridge.contains("void download(const std::string&, const std::function<void(Progress)>&);"));
+    assert!(bridge.contains("std::shared_ptr<_jobject> ref_on_progress(env->NewGlobalRef(on_progress), [vm_on_progress](jobject obj) {"));
+    assert!(bridge.contains("std::function<void(Progress)> cpp_on_progress = [vm_on_progress, ref_on_progress](Progress value) {"));
+    assert!(bridge.contains("jobject j_value = whitehall_Progress_to_java(env, value);"));
+    assert!(bridge.contains("env->GetMethodID(cls, \"invoke\", \"(Lcom/example/test/Progress;)V\")"));
+    assert!(bridge.contains("int count_matches(const std::string&, const std::function<bool(const std::string&, int)>&);"));
+    assert!(bridge.contains("env->CallBooleanMethod(ref_predicate.get(), method, j_value0, (jint) value1)"));
+    assert!(bridge.contains("return !whitehall_clear_exception(env) && result == JNI_TRUE;"));
+    assert!(bridge.contains("jstring result = (jstring) env->CallObjectMethod(ref_formatter.get(), method, (jint) value)"));
+    assert!(bridge.contains("download(cpp_url, cpp_on_progress);"));
+}
+
+#[test]
+fn test_cpp_ffi_namespaces_and_overloads_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let cpp_dir = ffi_dir.join("cpp");
+    fs::create_dir_all(&cpp_dir).unwrap();
+
+    let cpp_content = r#"
+#include <string>
+#include <vector>
+
+namespace audio::dsp {
+    // @ffi
+    double gain(double input, double db) {
+        return input * db;
+    }
+
+    // @ffi
+    double gain(const std::vector<double>& input,
+                double db) {
+        return db;
+    }
+}
+
+// @ffi
+int scale(int value) { return value * 2; }
+"#;
+    fs::write(cpp_dir.join("math.cpp"), cpp_content).unwrap();
+
+    let functions = discover_cpp_ffi(&ffi_dir).unwrap();
+    assert_eq!(functions.len(), 3);
+    check_cpp_ffi_types(&functions, &[]).unwrap();
+
+    let kotlin_code = generate_kotlin_object(&functions, "com.example.test", "math", "Math");
+    println!("{}", kotlin_code);
+    assert!(kotlin_code.contains("object Math {\n    external fun scale(value: Int): Int\n\n    object Audio {\n"));
+    assert!(kotlin_code.contains("        object Dsp {\n"));
+    assert!(kotlin_code.contains("            external fun gain(input: Double, db: Double): Double\n"));
+    assert!(kotlin_code.contains("            external fun gain(input: DoubleArray, db: Double): Double\n"));
+    assert_eq!(kotlin_code.matches("System.loadLibrary(\"math\")").count(), 3);
+
+    let bridge = generate_jni_bridge(&functions, &[], "com.example.test", "Math");
+    println!("{}", bridge);
+    assert!(bridge.contains("namespace audio { namespace dsp { double gain(double, double); } }"));
+    assert!(bridge.contains("namespace audio { namespace dsp { double gain(const std::vector<double>&, double); } }"));
+    assert!(bridge.contains("Java_com_example_test_Math_00024Audio_00024Dsp_gain__DD("));
+    assert!(bridge.contains("Java_com_example_test_Math_00024Audio_00024Dsp_gain___3DD("));
+    assert!(bridge.contains("Java_com_example_test_Math_scale("));
+    assert!(bridge.contains("return audio::dsp::gain(input, db);"));
+}
+
+#[test]
+fn test_c_ffi_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let c_dir = ffi_dir.join("c");
+    fs::create_dir_all(&c_dir).unwrap();
+
+    let header = r#"
+#ifndef CODEC_H
+#define CODEC_H
+
+#include <stdint.h>
+
+/// Checksum of a string
+// @ffi
+int64_t checksum(const char *text);
+
+// @ffi
+const char* codec_version(void);
+
+/* Not exposed */
+int codec_internal(int x);
+
+#endif
+"#;
+    fs::write(c_dir.join("codec.h"), header).unwrap();
+    fs::write(c_dir.join("codec.c"), "#include \"codec.h\"\n").unwrap();
+    fs::write(c_dir.join("notes.txt"), "").unwrap();
+
+    let functions = discover_c_ffi(&ffi_dir).unwrap();
+    assert_eq!(functions.len(), 2);
+    assert_eq!(c_source_files(&ffi_dir).unwrap(), vec![c_dir.join("codec.c")]);
+
+    let kotlin_code = generate_kotlin_object(&functions, "com.example.test.ffi.c", "codec", "Codec");
+    println!("{}", kotlin_code);
+    assert!(kotlin_code.contains("    /**\n     * Checksum of a string\n     */\n    external fun checksum(text: String): Long\n"));
+    assert!(kotlin_code.contains("    external fun codecVersion(): String\n"));
+    assert!(!kotlin_code.contains("codecInternal"));
+
+    let bridge = generate_c_jni_bridge(&functions, "com.example.test.ffi.c", "Codec");
+    println!("{}", bridge);
+    assert!(bridge.contains("#include \"codec.h\""));
+    assert!(bridge.contains("Java_com_example_test_ffi_c_Codec_checksum("));
+    assert!(bridge.contains("jlong result = (jlong) checksum(c_text);"));
+    assert!(bridge.contains("Java_com_example_test_ffi_c_Codec_codecVersion("));
+    assert!(bridge.contains("const char* result = codec_version();"));
+
+    let cmake = generate_c_cmake("codec", &["codec.c".to_string()], "codec_bridge.c", "/src/ffi/c", "11", &[], &[]);
+    assert!(cmake.contains("project(\"codec\" C)"));
+}
+
+/// Type-check a generated Rust bridge with `cargo check` against jni 0.21. The other
+/// Rust tests only look at the generated text. Skipped when jni can't be fetched (offline).
+#[test]
+fn test_rust_bridge_type_checks() {
+    let temp_dir = TempDir::new().unwrap();
+    let ffi_dir = temp_dir.path().join("ffi");
+    let rust_dir = ffi_dir.join("rust");
+    fs::create_dir_all(rust_dir.join("src")).unwrap();
+
+    let rust_content = r#"
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use whitehall::{ffi, ffi_callback};
+
+#[ffi]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[ffi]
+pub struct Polygon {
+    pub label: String,
+    pub points: Vec<Point>,
+}
+
+#[ffi]
+pub enum Shape {
+    Circle { center: Point, radius: f64 },
+    Poly(Polygon),
+    Empty,
+}
+
+#[ffi]
+pub fn centroid(polygon: Polygon) -> Point {
+    Point { x: polygon.points.len() as f64, y: 0.0 }
+}
+
+#[ffi]
+pub fn bounding_shapes(shapes: Vec<Shape>) -> Result<Vec<Shape>, String> {
+    Ok(shapes)
+}
+
+#[ffi]
+pub struct Profile {
+    pub nickname: Option<String>,
+    pub age: Option<i32>,
+    pub scores: HashMap<String, f64>,
+    pub avatar: Vec<u8>,
+}
+
+#[ffi]
+pub fn find_user(id: Option<i64>) -> Option<String> {
+    id.map(|id| id.to_string())
+}
+
+#[ffi]
+pub fn merge(settings: HashMap<String, Option<String>>) -> HashMap<String, i32> {
+    settings.into_keys().map(|key| (key, 0)).collect()
+}
+
+#[ffi]
+pub fn checksum(data: &[u8]) -> Vec<u8> {
+    data.to_vec()
+}
+
+#[ffi]
+pub fn fill(buffer: &mut [u8], value: i32) {
+    buffer.fill(value as u8);
+}
+
+#[ffi]
+pub fn echo_profile(profile: Profile) -> Profile {
+    profile
+}
+
+#[ffi]
+pub fn double_values(values: Vec<i32>) -> Vec<i32> {
+    values.iter().map(|v| v * 2).collect()
+}
+
+#[ffi]
+pub fn to_upper_case(strings: Vec<String>) -> Vec<String> {
+    strings.iter().map(|s| s.to_uppercase()).collect()
+}
+
+#[ffi]
+pub fn parse_number(text: String) -> Result<i32, String> {
+    text.parse::<i32>().map_err(|e| e.to_string())
+}
+
+#[ffi(async)]
+pub fn hash_file(path: String, cancelled: &AtomicBool) -> Result<Vec<u8>, String> {
+    if cancelled.load(Ordering::Relaxed) {
+        return Err("cancelled".to_string());
+    }
+    Ok(path.into_bytes())
+}
+
+#[ffi]
+pub struct Chunk {
+    pub index: i32,
+    pub data: Vec<u8>,
+}
+
+#[ffi_callback]
+pub trait DownloadListener: Send + Sync {
+    fn on_chunk(&self, chunk: Chunk);
+    fn should_continue(&self, received: i64) -> bool;
+}
+
+#[ffi_callback]
+pub trait Logger {
+    fn log(&self, message: String);
+}
+
+#[ffi]
+pub fn download(url: String, on_progress: impl Fn(i32) + Send + 'static) -> i64 {
+    on_progress(100);
+    url.len() as i64
+}
+
+#[ffi]
+pub fn transform(values: Vec<i32>, mapper: &dyn Fn(i32, String) -> String) -> i32 {
+    values.iter().map(|v| mapper(*v, String::new()).len() as i32).sum()
+}
+
+#[ffi]
+pub fn stream(url: String, listener: Box<dyn DownloadListener>, logger: &dyn Logger) {
+    if listener.should_continue(0) {
+        logger.log(url);
+    }
+}
+
+#[ffi]
+pub struct Row {
+    pub id: i64,
+    pub name: String,
+}
+
+pub struct Database {
+    rows: Vec<Row>,
+}
+
+#[ffi]
+impl Database {
+    pub fn open(path: String) -> Result<Self, String> {
+        if path.is_empty() {
+            return Err("empty path".to_string());
+        }
+        Ok(Database { rows: Vec::new() })
+    }
+
+    pub fn insert(&mut self, name: String) -> i64 {
+        self.rows.push(Row { id: self.rows.len() as i64, name });
+        self.rows.len() as i64
+    }
+
+    pub fn find(&self, id: i64) -> Option<Row> {
+        self.rows.iter().find(|row| row.id == id).map(|row| Row { id: row.id, name: row.name.clone() })
+    }
+
+    pub fn for_each(&self, on_row: &dyn Fn(Row)) {
+        for row in &self.rows {
+            on_row(Row { id: row.id, name: row.name.clone() });
+        }
+    }
+}
+"#;
+    fs::write(rust_dir.join("src/lib.rs"), rust_content).unwrap();
+
+    let functions = discover_rust_ffi(&ffi_dir).unwrap();
+    let types = discover_rust_ffi_types(&ffi_dir).unwrap();
+    let classes = discover_rust_ffi_classes(&ffi_dir).unwrap();
+    check_rust_ffi_types(&functions, &types).unwrap();
+    check_rust_ffi_classes(&classes, &functions, &types).unwrap();
+    let bridge = generate_rust_bridge(&functions, &types, &classes, "com.example.test");
+
+    // The #[ffi] marker crate whitehall generates under build/generated/ffi_macro
+    let macro_dir = temp_dir.path().join("ffi_macro");
+    fs::create_dir_all(macro_dir.join("src")).unwrap();
+    fs::write(
+        macro_dir.join("Cargo.toml"),
+        "[package]\nname = \"whitehall\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\nproc-macro = true\n",
+    ).unwrap();
+    fs::write(
+        macro_dir.join("src/lib.rs"),
+        "use proc_macro::TokenStream;\n\n\
+         #[proc_macro_attribute]\npub fn ffi(_attr: TokenStream, item: TokenStream) -> TokenStream { item }\n\n\
+         #[proc_macro_attribute]\npub fn ffi_callback(_attr: TokenStream, item: TokenStream) -> TokenStream { item }\n",
+    ).unwrap();
+
+    fs::write(
+        rust_dir.join("Cargo.toml"),
+        "[package]\nname = \"bridge_check\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n\n\
+         [dependencies]\njni = \"0.21\"\nwhitehall = { path = \"../../ffi_macro\" }\n",
+    ).unwrap();
+    fs::write(rust_dir.join("src/lib.rs"), format!("{}\n{}", rust_content, bridge)).unwrap();
+
+    // Share the target directory between runs so jni is only compiled once
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("rust-bridge-check");
+    let check = |offline: bool| {
+        let mut cmd = std::process::Command::new(&cargo);
+        cmd.arg("check").arg("--quiet").current_dir(&rust_dir).env("CARGO_TARGET_DIR", &target_dir);
+        if offline {
+            cmd.arg("--offline");
+        }
+        cmd.output().expect("Failed to run cargo")
+    };
+
+    let mut output = check(true);
+    if !output.status.success() {
+        output = check(false);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && !stderr.contains("could not compile `bridge_check`") {
+        eprintln!("Skipping: couldn't fetch jni 0.21\n{}", stderr);
+        return;
+    }
+    assert!(output.status.success(), "Generated bridge doesn't compile:\n{}\n{}", stderr, bridge);
 }
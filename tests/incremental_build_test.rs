@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use serial_test::serial;
+
+use whitehall::build_pipeline;
+use whitehall::commands::init;
+use whitehall::config;
+
+const LOADER: &str = r#"@prop val albumId: String
+
+val titles = $async(albumId) {
+  $fetch<List<String>>("/albums/${albumId}/titles")
+}
+
+<Column>
+  @await titles {
+    loading => <Text>Loading</Text>
+    ok(list) => <Text>{list.size}</Text>
+    err(e) => <Text>{e.message}</Text>
+  }
+</Column>
+"#;
+
+const NOTE: &str = "@entity\ndata class Note(\n  @primary val id: Long = 0,\n  val title: String\n)\n";
+
+#[test]
+#[serial]
+fn test_incremental_build_removes_unused_runtime_files() {
+    let temp_dir = TempDir::new().unwrap();
+    std::env::set_current_dir(temp_dir.path()).unwrap();
+    init::execute("stale-app").unwrap();
+    std::env::set_current_dir(temp_dir.path().join("stale-app")).unwrap();
+
+    fs::create_dir_all("src/components").unwrap();
+    fs::create_dir_all("src/models").unwrap();
+    fs::write("src/components/Loader.wh", LOADER).unwrap();
+    fs::write("src/models/Note.wh", NOTE).unwrap();
+
+    let config = config::load_config("whitehall.toml").unwrap();
+    let package_dir = Path::new(&config.build.output_dir)
+        .join("app/src/main/kotlin")
+        .join(config.android.package.replace('.', "/"));
+    let gradle = Path::new(&config.build.output_dir).join("app/build.gradle.kts");
+
+    let result = build_pipeline::execute_build(&config, false).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors.iter().map(|e| &e.message).collect::<Vec<_>>());
+    assert!(package_dir.join("Fetch.kt").exists());
+    assert!(package_dir.join("UiState.kt").exists());
+    assert!(package_dir.join("data/AppDatabase.kt").exists());
+    let script = fs::read_to_string(&gradle).unwrap();
+    assert!(script.contains("io.ktor") && script.contains("androidx.room"));
+
+    // As in watch mode: the last $fetch, $async and @entity go away without a clean build
+    fs::write("src/components/Loader.wh", "<Text>Done</Text>\n").unwrap();
+    fs::remove_file("src/models/Note.wh").unwrap();
+
+    let result = build_pipeline::execute_build(&config, false).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors.iter().map(|e| &e.message).collect::<Vec<_>>());
+    assert!(!package_dir.join("Fetch.kt").exists());
+    assert!(!package_dir.join("UiState.kt").exists());
+    assert!(!package_dir.join("data/AppDatabase.kt").exists());
+    assert!(!package_dir.join("data/Migrations.kt").exists());
+    let script = fs::read_to_string(&gradle).unwrap();
+    assert!(!script.contains("io.ktor") && !script.contains("androidx.room"));
+}
+
+const SPEC: &str = r#"openapi: 3.0.3
+info:
+  title: Status
+  version: "1"
+servers:
+  - url: https://status.example.com
+paths:
+  /health:
+    get:
+      operationId: getHealth
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                type: string
+"#;
+
+#[test]
+#[serial]
+fn test_incremental_build_removes_clients_of_deleted_specs() {
+    let temp_dir = TempDir::new().unwrap();
+    std::env::set_current_dir(temp_dir.path()).unwrap();
+    init::execute("api-app").unwrap();
+    std::env::set_current_dir(temp_dir.path().join("api-app")).unwrap();
+
+    fs::create_dir_all("src/api").unwrap();
+    fs::write("src/api/status.yaml", SPEC).unwrap();
+    fs::write("src/api/health.yaml", SPEC.replace("title: Status", "title: Health")).unwrap();
+
+    let config = config::load_config("whitehall.toml").unwrap();
+    let package_dir = Path::new(&config.build.output_dir)
+        .join("app/src/main/kotlin")
+        .join(config.android.package.replace('.', "/"));
+    let gradle = Path::new(&config.build.output_dir).join("app/build.gradle.kts");
+
+    let result = build_pipeline::execute_build(&config, false).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors.iter().map(|e| &e.message).collect::<Vec<_>>());
+    assert!(package_dir.join("api/StatusApi.kt").exists());
+    assert!(package_dir.join("api/HealthApi.kt").exists());
+
+    // A deleted spec takes its client with it; the others are kept
+    fs::remove_file("src/api/health.yaml").unwrap();
+    let result = build_pipeline::execute_build(&config, false).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors.iter().map(|e| &e.message).collect::<Vec<_>>());
+    assert!(package_dir.join("api/StatusApi.kt").exists());
+    assert!(!package_dir.join("api/HealthApi.kt").exists());
+
+    // Without specs nothing in the api package needs Ktor any more
+    fs::remove_file("src/api/status.yaml").unwrap();
+    let result = build_pipeline::execute_build(&config, false).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors.iter().map(|e| &e.message).collect::<Vec<_>>());
+    assert!(!package_dir.join("api/StatusApi.kt").exists());
+    assert!(!package_dir.join("Fetch.kt").exists());
+    assert!(!fs::read_to_string(&gradle).unwrap().contains("io.ktor"));
+}
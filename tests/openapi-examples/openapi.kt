@@ -0,0 +1,68 @@
+package com.example.app.api
+
+import com.example.app.Fetch
+import io.ktor.client.call.body
+import io.ktor.client.request.get
+import io.ktor.client.request.parameter
+import io.ktor.client.request.put
+import io.ktor.client.request.setBody
+import io.ktor.http.ContentType
+import io.ktor.http.contentType
+import kotlinx.serialization.SerialName
+import kotlinx.serialization.Serializable
+import kotlinx.serialization.json.JsonElement
+
+@Serializable
+enum class Units(val value: String) {
+    @SerialName("metric") Metric("metric"),
+    @SerialName("imperial") Imperial("imperial");
+
+    override fun toString() = value
+}
+
+@Serializable
+data class Day(
+    val date: String,
+    val high: Float,
+    val low: Float,
+    val extra: Map<String, Double>? = null
+)
+
+@Serializable
+data class Reading(
+    val timestamp: Long,
+    val value: Double,
+    val raw: JsonElement? = null
+)
+
+@Serializable
+data class Timestamped(
+    val timestamp: Long
+)
+
+@Serializable
+data class GetForecastByCityResponse(
+    val city: String,
+    val days: List<Day>,
+    val alerts: List<String>? = null
+)
+
+object WeatherApi {
+    var baseUrl = "https://eu.weather.example.com"
+
+    private val client get() = Fetch.client
+
+    /** Daily forecast for a city */
+    suspend fun getForecastByCity(city: String, days: Int, units: Units? = null): GetForecastByCityResponse =
+        client.get("$baseUrl/forecast/${city}") {
+            parameter("days", days)
+            parameter("units", units)
+        }.body()
+
+    suspend fun uploadReadings(id: String, body: List<Reading>) {
+        client.put("$baseUrl/stations/${id}/readings") {
+            contentType(ContentType.Application.Json)
+            setBody(body)
+        }
+    }
+}
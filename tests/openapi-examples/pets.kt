@@ -0,0 +1,83 @@
+package com.example.app.api
+
+import com.example.app.Fetch
+import io.ktor.client.call.body
+import io.ktor.client.request.delete
+import io.ktor.client.request.get
+import io.ktor.client.request.header
+import io.ktor.client.request.parameter
+import io.ktor.client.request.post
+import io.ktor.client.request.setBody
+import io.ktor.http.ContentType
+import io.ktor.http.contentType
+import kotlinx.serialization.SerialName
+import kotlinx.serialization.Serializable
+
+/** A pet in the store */
+@Serializable
+data class Pet(
+    val id: Long,
+    val name: String,
+    val tag: String? = null,
+    val status: PetStatus? = null,
+    val owner: PetOwner? = null
+)
+
+@Serializable
+enum class PetStatus(val value: String) {
+    @SerialName("available") Available("available"),
+    @SerialName("pending") Pending("pending"),
+    @SerialName("sold") Sold("sold");
+
+    override fun toString() = value
+}
+
+@Serializable
+data class PetOwner(
+    @SerialName("display_name") val displayName: String? = null
+)
+
+@Serializable
+data class NewPet(
+    val name: String,
+    val tag: String? = null
+)
+
+typealias Pets = List<Pet>
+
+@Serializable
+data class Error(
+    val code: Int,
+    val message: String
+)
+
+object PetsApi {
+    var baseUrl = "https://petstore.example.com/v1"
+
+    private val client get() = Fetch.client
+
+    /** List all pets */
+    suspend fun listPets(limit: Int? = null, tags: List<String>? = null): Pets =
+        client.get("$baseUrl/pets") {
+            parameter("limit", limit)
+            tags?.forEach { parameter("tags", it) }
+        }.body()
+
+    /** Create a pet */
+    suspend fun createPet(body: NewPet): Pet =
+        client.post("$baseUrl/pets") {
+            contentType(ContentType.Application.Json)
+            setBody(body)
+        }.body()
+
+    /** Info for a specific pet */
+    suspend fun showPetById(petId: Long, xRequestID: String? = null): Pet =
+        client.get("$baseUrl/pets/${petId}") {
+            header("X-Request-ID", xRequestID)
+        }.body()
+
+    /** Delete a pet */
+    suspend fun deletePet(petId: Long) {
+        client.delete("$baseUrl/pets/${petId}")
+    }
+}
@@ -0,0 +1,36 @@
+package com.example.app.api
+
+import com.example.app.Fetch
+import io.ktor.client.call.body
+import io.ktor.client.request.get
+import io.ktor.client.request.parameter
+import kotlinx.serialization.SerialName
+import kotlinx.serialization.Serializable
+
+typealias Priority = Int
+
+@Serializable
+data class Task(
+    val id: Long,
+    val priority: Priority,
+    val state: TaskState? = null
+)
+
+@Serializable
+enum class TaskState(val value: String) {
+    @SerialName("open") Open("open"),
+    @SerialName("done") Done("done");
+
+    override fun toString() = value
+}
+
+object TasksApi {
+    var baseUrl = "https://tasks.example.com"
+
+    private val client get() = Fetch.client
+
+    suspend fun getTask(`object`: Long, level: Int? = null): Task =
+        client.get("$baseUrl/tasks/${`object`}") {
+            parameter("level", level)
+        }.body()
+}
@@ -0,0 +1,37 @@
+package com.example.app.api
+
+import com.example.app.Fetch
+import io.ktor.client.call.body
+import io.ktor.client.request.delete
+import io.ktor.client.request.get
+import io.ktor.client.request.post
+import io.ktor.client.request.setBody
+import io.ktor.http.ContentType
+import io.ktor.http.contentType
+import kotlinx.serialization.Serializable
+
+@Serializable
+data class Todo(
+    val id: Long,
+    val title: String,
+    val done: Boolean? = null
+)
+
+object TodosApi {
+    var baseUrl = "https://todos.example.com"
+
+    private val client get() = Fetch.client
+
+    suspend fun listTodos(): List<Todo> =
+        client.get("$baseUrl/todos").body()
+
+    suspend fun addTodo(body: Todo): Todo =
+        client.post("$baseUrl/todos") {
+            contentType(ContentType.Application.Json)
+            setBody(body)
+        }.body()
+
+    suspend fun deleteTodo(id: Long) {
+        client.delete("$baseUrl/todos/${id}")
+    }
+}
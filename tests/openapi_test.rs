@@ -0,0 +1,65 @@
+/// OpenAPI client generation tests
+///
+/// Each spec in tests/openapi-examples/ (`.yaml`, `.yml` or `.json`) is paired with the
+/// expected Kotlin client in `<spec name>.kt` (e.g. pets.yaml → pets.kt). Generation is fully offline.
+
+use std::fs;
+use std::path::PathBuf;
+use whitehall::openapi::{generate_client, load_spec};
+
+fn examples_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("openapi-examples")
+}
+
+/// Normalize whitespace for comparison (ignores minor formatting differences)
+fn normalize_whitespace(s: &str) -> String {
+    s.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn openapi_examples() {
+    let mut specs: Vec<PathBuf> = fs::read_dir(examples_dir())
+        .expect("tests/openapi-examples/ is missing")
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("yaml" | "yml" | "json")))
+        .collect();
+    specs.sort();
+    assert!(!specs.is_empty(), "No specs found in tests/openapi-examples/");
+
+    let mut failures = Vec::new();
+    for spec_path in specs {
+        let file_name = spec_path.file_name().unwrap().to_str().unwrap().to_string();
+        let spec = load_spec(&spec_path).unwrap_or_else(|e| panic!("Failed to load {}: {:#}", file_name, e));
+        let actual = generate_client(&spec, "com.example.app");
+
+        let stem = spec_path.file_stem().unwrap().to_str().unwrap();
+        let expected_path = examples_dir().join(format!("{}.kt", stem));
+        let expected = fs::read_to_string(&expected_path)
+            .unwrap_or_else(|_| panic!("Missing expected output {}", expected_path.display()));
+
+        if normalize_whitespace(&actual) != normalize_whitespace(&expected) {
+            eprintln!("\n✗ MISMATCH in {}\n--- actual\n{}--- end", file_name, actual);
+            failures.push(file_name);
+        }
+    }
+
+    assert!(failures.is_empty(), "OpenAPI examples failed: {:?}", failures);
+}
+
+#[test]
+fn test_client_names() {
+    let spec = load_spec(&examples_dir().join("pets.yaml")).unwrap();
+    assert_eq!(spec.name, "PetsApi");
+
+    // openapi.json is named after info.title
+    let spec = load_spec(&examples_dir().join("openapi.json")).unwrap();
+    assert_eq!(spec.name, "WeatherApi");
+    assert_eq!(spec.base_url, "https://eu.weather.example.com");
+}
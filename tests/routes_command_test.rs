@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Run the whitehall binary in `dir`, without colors
+fn whitehall(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_whitehall"))
+        .args(args)
+        .current_dir(dir)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+/// A project whose routes `user-settings` and `userSettings` both become `UserSettings`
+fn project_with_duplicate_route_names() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    assert!(whitehall(temp_dir.path(), &["init", "routes-app"]).status.success());
+
+    let routes_dir = temp_dir.path().join("routes-app/src/routes");
+    for route in ["user-settings", "userSettings"] {
+        fs::create_dir_all(routes_dir.join(route)).unwrap();
+        fs::write(routes_dir.join(route).join("+screen.wh"), "<Text>Settings</Text>\n").unwrap();
+    }
+    temp_dir
+}
+
+#[test]
+fn test_routes_json_keeps_stdout_to_the_report() {
+    let temp_dir = project_with_duplicate_route_names();
+    let output = whitehall(&temp_dir.path().join("routes-app"), &["routes", "--json"]);
+
+    // Error-severity issues fail the command
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Route validation failed with 1 error(s)"), "{}", stderr);
+
+    // stdout is exactly one JSON document
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let routes = report["routes"].as_array().unwrap();
+    assert!(routes.iter().any(|r| r["path"] == "/user-settings"));
+    for route in routes {
+        assert!(route["name"].is_string() && route["screen"].is_string() && route["source"].is_string());
+        assert!(route["params"].is_array() && route["layouts"].is_array());
+    }
+    assert!(report["layouts"].is_array());
+
+    let issues = report["issues"].as_array().unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0]["severity"], "error");
+    assert_eq!(issues[0]["kind"], "duplicate-route-name");
+    assert!(issues[0]["message"].as_str().unwrap().contains("'UserSettings'"));
+    assert_eq!(issues[0]["source"], "src/routes/userSettings/+screen.wh");
+}
+
+#[test]
+fn test_routes_prints_issues_to_stderr() {
+    let temp_dir = project_with_duplicate_route_names();
+    let output = whitehall(&temp_dir.path().join("routes-app"), &["routes"]);
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.contains("/user-settings"), "{}", stdout);
+    assert!(!stdout.contains("error:") && !stdout.ends_with("\n\n"), "{}", stdout);
+    assert!(stderr.starts_with("\nerror: Route '/user-settings' and route '/userSettings'"), "{}", stderr);
+}
@@ -75,6 +75,9 @@ fn test_version_validation() {
         java: "21".to_string(),
         gradle: "8.4".to_string(),
         agp: "8.2.0".to_string(),
+        kotlin: "2.1.0".to_string(),
+        min_sdk: 24,
+        target_sdk: 34,
     };
     assert!(validate_compatibility(&valid_config).is_ok());
 
@@ -83,6 +86,9 @@ fn test_version_validation() {
         java: "11".to_string(),
         gradle: "8.4".to_string(),
         agp: "8.2.0".to_string(),
+        kotlin: "2.1.0".to_string(),
+        min_sdk: 24,
+        target_sdk: 34,
     };
     let result = validate_compatibility(&invalid_config);
     assert!(result.is_err());
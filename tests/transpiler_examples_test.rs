@@ -383,4 +383,39 @@ mod tests {
         assert_eq!(test.metadata.package, "com.example.app.screens");
         assert_eq!(test.metadata.type_hint, Some("screen".to_string()));
     }
+
+    #[test]
+    fn test_fetch_options_with_comparisons_and_lambdas() {
+        use whitehall::transpiler::transpile;
+
+        let input = r#"suspend fun save(items: List<Int>, retries: Int) {
+  $fetch.post("/items", items.filter { it -> it > 0 }, { timeout: if (retries < 3) 5000 else 30000, headers: { "X-Count": "${items.size}" } })
+}
+
+<Text>Saving</Text>
+"#;
+        let result = transpile(input, "com.example.app.components", "Saver", None).unwrap();
+        let output = result.primary_content();
+        assert!(output.contains(
+            r#"Fetch.request<Unit>("/items", method = "POST", body = items.filter { it -> it > 0 }, timeout = if (retries < 3) 5000 else 30000, headers = mapOf("X-Count" to "${items.size}"))"#
+        ), "{}", output);
+    }
+
+    #[test]
+    fn test_fetch_rejects_unknown_options() {
+        use whitehall::transpiler::transpile;
+
+        for (options, key) in [
+            (r#"{ heders: { "A": "b" } }"#, "heders"),
+            (r#"{ credentials: "include" }"#, "credentials"),
+        ] {
+            let input = format!(
+                "suspend fun load() {{\n  val user: String = $fetch(\"/me\", {})\n}}\n\n<Text>Hi</Text>\n",
+                options
+            );
+            let err = transpile(&input, "com.example.app.components", "Profile", None).unwrap_err();
+            assert!(err.contains(&format!("Unknown $fetch option '{}'", key)), "{}", err);
+            assert!(err.contains("method, headers, body, timeout"), "{}", err);
+        }
+    }
 }
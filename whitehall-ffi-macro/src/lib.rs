@@ -5,19 +5,85 @@
 //! during code generation. The attribute itself does nothing at runtime.
 
 extern crate proc_macro;
-use proc_macro::TokenStream;
+use proc_macro::{Delimiter, TokenStream, TokenTree};
 
 /// Marker attribute for functions to be exposed via FFI/JNI
 ///
+/// `#[ffi(async)]` exposes the function to Kotlin as a `suspend fun` that runs
+/// on `Dispatchers.IO`. An optional `&AtomicBool` parameter is set when the
+/// calling coroutine is cancelled. `#[ffi(default(limit = 10))]` gives the
+/// Kotlin parameter a default value; doc comments and `#[deprecated]` are
+/// carried over to the Kotlin declaration as well.
+///
+/// On an `impl` block, every method is exported and the type is exposed to
+/// Kotlin as an `AutoCloseable` class wrapping a native handle.
+///
 /// # Example
 /// ```
 /// #[ffi]
 /// pub fn add(a: i32, b: i32) -> i32 {
 ///     a + b
 /// }
+///
+/// #[ffi(async)]
+/// pub fn checksum(path: String, cancelled: &AtomicBool) -> Result<Vec<u8>, String> {
+///     // ...
+/// }
+///
+/// #[ffi]
+/// impl Database {
+///     pub fn open(path: String) -> Result<Self, String> {
+///         // ...
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn ffi(attr: TokenStream, item: TokenStream) -> TokenStream {
+    // Arguments: `async` and `default(name = literal, ...)`, separated by commas
+    let mut tokens = attr.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let valid = match &token {
+            TokenTree::Ident(ident) if ident.to_string() == "async" => true,
+            TokenTree::Ident(ident) if ident.to_string() == "default" => matches!(
+                tokens.next(),
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis
+            ),
+            _ => false,
+        };
+        if !valid {
+            return format!(
+                "compile_error!(\"unknown #[ffi] argument `{}`, expected `async` or `default(..)`\");",
+                token
+            )
+            .parse()
+            .unwrap();
+        }
+        if let Some(TokenTree::Punct(punct)) = tokens.peek() {
+            if punct.as_char() == ',' {
+                tokens.next();
+            }
+        }
+    }
+
+    // Pass through unchanged - this is just a marker for whitehall's parser
+    item
+}
+
+/// Marker attribute for traits implemented in Kotlin and passed to native code
+///
+/// Functions take the trait as `impl Trait`, `&dyn Trait` or `Box<dyn Trait>`;
+/// Whitehall generates a Kotlin interface and a bridge implementation that
+/// calls back into it from any thread.
+///
+/// # Example
+/// ```
+/// #[ffi_callback]
+/// pub trait ProgressListener: Send + Sync {
+///     fn on_progress(&self, percent: i32);
+/// }
 /// ```
 #[proc_macro_attribute]
-pub fn ffi(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn ffi_callback(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // Pass through unchanged - this is just a marker for whitehall's parser
     item
 }